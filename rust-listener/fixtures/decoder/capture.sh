@@ -0,0 +1,53 @@
+#!/usr/bin/env bash
+# Capture an eth_getLogs response as a decoder fixture
+#
+# Usage: capture.sh <rpc_url> <chain_id> <tx_hash> <address> <topic0> <name>
+#
+# Fetches the logs <address> emitted with <topic0> in the block of <tx_hash>
+# and writes the request and the response, verbatim, to <name>.json next to
+# this script. Record its snapshot with
+#   UPDATE_SNAPSHOTS=1 cargo test fixture_snapshots
+# and review the decoded output before committing. The RPC URL (and any API
+# key in it) is not stored.
+
+set -euo pipefail
+
+if [ $# -ne 6 ]; then
+    sed -n '4p' "$0" | sed 's/^# //' >&2
+    exit 2
+fi
+
+RPC_URL=$1
+CHAIN_ID=$2
+TX_HASH=$3
+ADDRESS=$4
+TOPIC0=$5
+NAME=$6
+DIR=$(cd "$(dirname "$0")" && pwd)
+
+rpc() {
+    curl -sf -X POST -H 'Content-Type: application/json' -d "$1" "$RPC_URL"
+}
+
+receipt=$(rpc "$(jq -nc --arg tx "$TX_HASH" \
+    '{jsonrpc: "2.0", id: 1, method: "eth_getTransactionReceipt", params: [$tx]}')")
+block_hash=$(jq -r '.result.blockHash // empty' <<<"$receipt")
+if [ -z "$block_hash" ]; then
+    echo "Transaction $TX_HASH not found: $receipt" >&2
+    exit 1
+fi
+
+request=$(jq -nc --arg block "$block_hash" --arg address "$ADDRESS" --arg topic0 "$TOPIC0" \
+    '{jsonrpc: "2.0", id: 1, method: "eth_getLogs",
+      params: [{blockHash: $block, address: $address, topics: [$topic0]}]}')
+response=$(rpc "$request")
+if ! jq -e '.result | length > 0' <<<"$response" >/dev/null; then
+    echo "No matching logs: $response" >&2
+    exit 1
+fi
+
+jq -n --argjson chain_id "$CHAIN_ID" --arg tx_hash "$TX_HASH" \
+    --argjson request "$request" --argjson response "$response" \
+    '{chain_id: $chain_id, tx_hash: $tx_hash, request: $request, response: $response, decoded: null}' \
+    >"$DIR/$NAME.json"
+echo "Wrote $DIR/$NAME.json ($(jq '.result | length' <<<"$response") logs)"
@@ -0,0 +1,336 @@
+use serde::Deserialize;
+use sha3::{Digest, Keccak256};
+
+// ============================================================================
+// JSON ABI Parsing
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct AbiItem {
+    #[serde(rename = "type")]
+    item_type: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<AbiInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AbiInput {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default, rename = "internalType")]
+    internal_type: Option<String>,
+    #[serde(default)]
+    indexed: bool,
+    #[serde(default)]
+    components: Vec<AbiInput>,
+}
+
+/// How a single ABI value is read and rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Lower 160 bits of the word, rendered as a lowercase `0x` address
+    Address,
+    /// Any other static type (uintN, intN, bytesN, bool), rendered as the raw `0x` word
+    Word,
+    /// Dynamic `bytes`/`string`, rendered as `0x` hex of the payload
+    Bytes,
+}
+
+/// A flattened, named value slot of an event parameter
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    kind: Kind,
+}
+
+/// An event parameter; static tuples expand to one field per component
+#[derive(Debug, Clone)]
+struct Param {
+    name: String,
+    canonical_type: String,
+    indexed: bool,
+    fields: Vec<Field>,
+}
+
+// ============================================================================
+// Event Decoder
+// ============================================================================
+
+/// Decoder for one event, built from its JSON ABI
+///
+/// Supports the elementary static types, dynamic `bytes`/`string` and static
+/// tuples (decoded inline, fields named `param.component`). Arrays and
+/// dynamic tuples are rejected when the decoder is built.
+#[derive(Debug, Clone)]
+pub struct EventDecoder {
+    name: String,
+    params: Vec<Param>,
+}
+
+impl EventDecoder {
+    /// Build a decoder for `event_name` from a JSON ABI array
+    pub fn from_json_abi(abi: &str, event_name: &str) -> Result<Self, String> {
+        let items: Vec<AbiItem> =
+            serde_json::from_str(abi).map_err(|e| format!("Invalid ABI JSON: {}", e))?;
+
+        let item = items
+            .into_iter()
+            .find(|i| i.item_type == "event" && i.name == event_name)
+            .ok_or_else(|| format!("Event {} not found in ABI", event_name))?;
+
+        let params = item
+            .inputs
+            .iter()
+            .map(build_param)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: item.name,
+            params,
+        })
+    }
+
+    /// Canonical signature, e.g. `OrderFilled(bytes32,uint256)`
+    pub fn signature(&self) -> String {
+        let types: Vec<&str> = self.params.iter().map(|p| p.canonical_type.as_str()).collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    /// Event topic0: keccak256 of the canonical signature
+    pub fn topic0(&self) -> String {
+        let hash = Keccak256::digest(self.signature().as_bytes());
+        format!("0x{}", hex::encode(hash))
+    }
+
+    /// Decode a log's topics and data
+    ///
+    /// Returns None if there are fewer topics than indexed parameters or the
+    /// data is shorter than the static head. topic0 itself is not checked.
+    pub fn decode(&self, topics: &[String], data: &str) -> Option<DecodedEvent> {
+        self.decode_parts(topics.get(1..)?, data)
+    }
+
+    /// Decode the data of an event without indexed parameters
+    pub fn decode_data(&self, data: &str) -> Option<DecodedEvent> {
+        self.decode_parts(&[], data)
+    }
+
+    fn decode_parts(&self, indexed_topics: &[String], data: &str) -> Option<DecodedEvent> {
+        let indexed_count = self.params.iter().filter(|p| p.indexed).count();
+        if indexed_topics.len() < indexed_count {
+            return None;
+        }
+
+        let hex = data.strip_prefix("0x").unwrap_or(data);
+        let head_words: usize = self
+            .params
+            .iter()
+            .filter(|p| !p.indexed)
+            .map(|p| p.fields.len())
+            .sum();
+        if hex.len() < head_words * 64 {
+            return None;
+        }
+
+        let mut values = Vec::new();
+        let mut topic_idx = 0;
+        let mut word_idx = 0;
+
+        for param in &self.params {
+            if param.indexed {
+                // Indexed tuples and dynamic types are stored as their hash
+                let topic = indexed_topics[topic_idx].as_str();
+                topic_idx += 1;
+                let value = match param.fields.as_slice() {
+                    [field] if field.kind == Kind::Address => to_address(topic),
+                    _ => topic.to_lowercase(),
+                };
+                values.push((param.name.clone(), value));
+                continue;
+            }
+
+            for field in &param.fields {
+                let word = hex.get(word_idx * 64..(word_idx + 1) * 64)?;
+                word_idx += 1;
+                let value = match field.kind {
+                    Kind::Address => to_address(word),
+                    Kind::Word => format!("0x{}", word.to_lowercase()),
+                    Kind::Bytes => format!("0x{}", read_dynamic_bytes(hex, word)),
+                };
+                values.push((field.name.clone(), value));
+            }
+        }
+
+        Some(DecodedEvent { values })
+    }
+}
+
+/// Named values of a decoded event, in ABI order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedEvent {
+    values: Vec<(String, String)>,
+}
+
+impl DecodedEvent {
+    /// Get a value by parameter name (`param.component` for tuple fields)
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get a value as an owned string, empty if absent
+    pub fn string(&self, name: &str) -> String {
+        self.get(name).unwrap_or_default().to_string()
+    }
+
+    /// Parse a word value as u64 (0 if absent or out of range)
+    pub fn u64(&self, name: &str) -> u64 {
+        self.get(name)
+            .and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .unwrap_or(0)
+    }
+}
+
+fn build_param(input: &AbiInput) -> Result<Param, String> {
+    if input.ty == "tuple" {
+        let mut fields = Vec::with_capacity(input.components.len());
+        let mut component_types = Vec::with_capacity(input.components.len());
+
+        for component in &input.components {
+            let kind = elementary_kind(component)?;
+            if kind == Kind::Bytes {
+                return Err(format!("Dynamic tuple {} is not supported", input.name));
+            }
+            fields.push(Field {
+                name: format!("{}.{}", input.name, component.name),
+                kind,
+            });
+            component_types.push(component.ty.clone());
+        }
+
+        return Ok(Param {
+            name: input.name.clone(),
+            canonical_type: format!("({})", component_types.join(",")),
+            indexed: input.indexed,
+            fields,
+        });
+    }
+
+    Ok(Param {
+        name: input.name.clone(),
+        canonical_type: input.ty.clone(),
+        indexed: input.indexed,
+        fields: vec![Field {
+            name: input.name.clone(),
+            kind: elementary_kind(input)?,
+        }],
+    })
+}
+
+fn elementary_kind(input: &AbiInput) -> Result<Kind, String> {
+    let ty = input.ty.as_str();
+    if ty.ends_with(']') || ty == "tuple" {
+        return Err(format!("Type {} of {} is not supported", ty, input.name));
+    }
+
+    // 1inch wraps addresses in a uint256 user type named `Address`
+    if ty == "address" || input.internal_type.as_deref() == Some("Address") {
+        return Ok(Kind::Address);
+    }
+
+    match ty {
+        "bytes" | "string" => Ok(Kind::Bytes),
+        "bool" => Ok(Kind::Word),
+        _ if ty.starts_with("uint") || ty.starts_with("int") || ty.starts_with("bytes") => Ok(Kind::Word),
+        _ => Err(format!("Type {} of {} is not supported", ty, input.name)),
+    }
+}
+
+/// Last 40 hex chars of a word or topic as a lowercase address
+fn to_address(word: &str) -> String {
+    format!("0x{}", word[word.len().saturating_sub(40)..].to_lowercase())
+}
+
+/// Read a dynamic `bytes` payload given its offset word; empty if out of range
+fn read_dynamic_bytes<'a>(hex: &'a str, offset_word: &str) -> &'a str {
+    let offset = usize::from_str_radix(offset_word, 16).unwrap_or(usize::MAX);
+    let Some(length_start) = offset.checked_mul(2) else {
+        return "";
+    };
+    let Some(length_word) = hex.get(length_start..length_start + 64) else {
+        return "";
+    };
+
+    let length = usize::from_str_radix(length_word, 16).unwrap_or(0);
+    let data_start = length_start + 64;
+    let data_end = data_start
+        .saturating_add(length.saturating_mul(2))
+        .min(hex.len());
+
+    hex.get(data_start..data_end).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[
+        {"type":"function","name":"OrderFilled","inputs":[]},
+        {"type":"event","name":"Example","inputs":[
+            {"name":"id","type":"bytes32","indexed":true},
+            {"name":"owner","type":"address","indexed":true},
+            {"name":"pair","type":"tuple","components":[
+                {"name":"amount","type":"uint256"},
+                {"name":"token","type":"uint256","internalType":"Address"}
+            ]},
+            {"name":"note","type":"bytes"}
+        ]}
+    ]"#;
+
+    fn word(hex: &str) -> String {
+        format!("{:0>64}", hex)
+    }
+
+    #[test]
+    fn test_signature_flattens_tuples() {
+        let decoder = EventDecoder::from_json_abi(ABI, "Example").unwrap();
+        assert_eq!(decoder.signature(), "Example(bytes32,address,(uint256,uint256),bytes)");
+    }
+
+    #[test]
+    fn test_decode_indexed_tuple_and_bytes() {
+        let decoder = EventDecoder::from_json_abi(ABI, "Example").unwrap();
+        let topics = vec![
+            decoder.topic0(),
+            format!("0x{}", word("AB")),
+            format!("0x{}", word("00000000000000000000000000000000000000CC")),
+        ];
+        // amount, token, offset (0x60), length (2), payload
+        let payload = format!("{:0<64}", "beef");
+        let data = format!("0x{}{}{}{}{}", word("3e8"), word("DD"), word("60"), word("2"), payload);
+
+        let decoded = decoder.decode(&topics, &data).unwrap();
+        assert_eq!(decoded.get("id"), Some(format!("0x{}", word("ab")).as_str()));
+        assert_eq!(decoded.get("owner"), Some("0x00000000000000000000000000000000000000cc"));
+        assert_eq!(decoded.u64("pair.amount"), 1000);
+        assert_eq!(decoded.get("pair.token"), Some("0x00000000000000000000000000000000000000dd"));
+        assert_eq!(decoded.get("note"), Some("0xbeef"));
+
+        // Missing indexed topic or truncated head
+        assert!(decoder.decode(&topics[..2], &data).is_none());
+        assert!(decoder.decode(&topics, &data[..2 + 64 * 2]).is_none());
+    }
+
+    #[test]
+    fn test_unsupported_types_rejected() {
+        let abi = r#"[{"type":"event","name":"E","inputs":[{"name":"a","type":"uint256[]"}]}]"#;
+        assert!(EventDecoder::from_json_abi(abi, "E").is_err());
+        assert!(EventDecoder::from_json_abi(ABI, "Missing").is_err());
+    }
+}
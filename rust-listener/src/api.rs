@@ -0,0 +1,1426 @@
+use crate::config::StorageMode;
+use crate::control::{ChainCommand, ChainControl};
+use crate::db::{normalize_search_prefix, Database, DbError, TransferStream, MAX_BATCH_ADDRESSES, MIN_SEARCH_DIGITS};
+use crate::events::{EventBus, EventFilter};
+use crate::health::HealthRegistry;
+use crate::poller::hydrate_transfer_values;
+use crate::quirks::ChainQuirks;
+use crate::rpc::RpcClient;
+use crate::types::{Cursor, FusionPlusFilter, NetworkConfig, Transfer};
+use crate::watchlist::Watchlist;
+use axum::body::Body;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{FromRef, Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{BoxError, Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tracing::{debug, error, info, warn};
+
+/// Default and maximum number of rows returned by list endpoints
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+/// Error returned by API handlers, rendered as `{"error": "..."}`
+enum ApiError {
+    NotFound,
+    BadRequest(String),
+    /// Missing or wrong admin token
+    Unauthorized,
+    /// The endpoint is disabled by the listener's configuration
+    NotImplemented(String),
+    /// An RPC request made to answer the query failed
+    Rpc(String),
+    Db(DbError),
+}
+
+impl From<DbError> for ApiError {
+    fn from(e: DbError) -> Self {
+        ApiError::Db(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "missing or invalid admin token".to_string()),
+            ApiError::NotImplemented(message) => (StatusCode::NOT_IMPLEMENTED, message),
+            ApiError::Rpc(message) => {
+                warn!("API query failed: {}", message);
+                (StatusCode::BAD_GATEWAY, message)
+            }
+            ApiError::Db(e) => {
+                error!("API query failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "database error".to_string())
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult = Result<Response, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct LimitParams {
+    limit: Option<u32>,
+}
+
+impl LimitParams {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// Limit plus keyset cursor; see `Cursor`
+#[derive(Debug, Deserialize)]
+struct PageParams {
+    limit: Option<u32>,
+    since_id: Option<i64>,
+    before_timestamp: Option<u64>,
+    before_id: Option<i64>,
+}
+
+impl PageParams {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            since_id: self.since_id,
+            before_timestamp: self.before_timestamp,
+            before_id: self.before_id,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApprovalParams {
+    limit: Option<u32>,
+    /// Only return type(uint256).max approvals
+    #[serde(default)]
+    unlimited: bool,
+}
+
+impl ApprovalParams {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// Query for `GET /fusion-plus`; statuses are comma-separated alternatives
+#[derive(Debug, Deserialize)]
+struct FusionPlusListParams {
+    maker: Option<String>,
+    src_status: Option<String>,
+    dst_status: Option<String>,
+    src_chain_id: Option<u32>,
+    dst_chain_id: Option<u32>,
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: u32,
+}
+
+impl FusionPlusListParams {
+    fn filter(&self) -> FusionPlusFilter {
+        let statuses = |s: &Option<String>| {
+            s.iter()
+                .flat_map(|s| s.split(','))
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        FusionPlusFilter {
+            maker: self.maker.clone(),
+            src_status: statuses(&self.src_status),
+            dst_status: statuses(&self.dst_status),
+            src_chain_id: self.src_chain_id,
+            dst_chain_id: self.dst_chain_id,
+            from_timestamp: self.from,
+            to_timestamp: self.to,
+        }
+    }
+
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// `/events/stream` filter; each field is a comma-separated list
+#[derive(Debug, Deserialize)]
+struct StreamParams {
+    chain_ids: Option<String>,
+    addresses: Option<String>,
+    tokens: Option<String>,
+    event_types: Option<String>,
+}
+
+impl StreamParams {
+    fn filter(&self) -> Result<EventFilter, ApiError> {
+        let list = |s: &Option<String>| -> Vec<String> {
+            s.iter()
+                .flat_map(|s| s.split(','))
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        let chain_ids = list(&self.chain_ids)
+            .iter()
+            .map(|id| id.parse().map_err(|_| ApiError::BadRequest(format!("invalid chain id: {}", id))))
+            .collect::<Result<_, _>>()?;
+        Ok(EventFilter {
+            chain_ids,
+            addresses: list(&self.addresses),
+            tokens: list(&self.tokens),
+            event_types: list(&self.event_types),
+        })
+    }
+}
+
+/// Comma-separated addresses, read alongside `PageParams`
+#[derive(Debug, Deserialize)]
+struct AddressesParams {
+    addresses: String,
+}
+
+/// Hex prefix of a hash or address, plus matches per searched column
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String,
+    limit: Option<u32>,
+}
+
+impl SearchParams {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// Inclusive block range plus row limit
+#[derive(Debug, Deserialize)]
+struct BlockRangeParams {
+    from_block: u64,
+    to_block: u64,
+    limit: Option<u32>,
+}
+
+impl BlockRangeParams {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WindowParams {
+    from: Option<u64>,
+    to: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopTokensParams {
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelParams {
+    label: Option<String>,
+}
+
+/// Shared handler state; handlers extract only the part they need
+#[derive(Clone)]
+struct ApiState {
+    db: Arc<Database>,
+    watchlist: Arc<Watchlist>,
+    events: Arc<EventBus>,
+    health: Arc<HealthRegistry>,
+    control: Arc<ChainControl>,
+    /// Where `POST /admin/backups/:chain_id` writes; None disables it
+    backup_dir: Option<Arc<PathBuf>>,
+    values: TransferValues,
+}
+
+impl FromRef<ApiState> for Arc<Database> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.db)
+    }
+}
+
+impl FromRef<ApiState> for Arc<Watchlist> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.watchlist)
+    }
+}
+
+impl FromRef<ApiState> for Arc<EventBus> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.events)
+    }
+}
+
+impl FromRef<ApiState> for Arc<HealthRegistry> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.health)
+    }
+}
+
+impl FromRef<ApiState> for Arc<ChainControl> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.control)
+    }
+}
+
+impl FromRef<ApiState> for TransferValues {
+    fn from_ref(state: &ApiState) -> Self {
+        state.values.clone()
+    }
+}
+
+/// Fills in the transfer values compact mode doesn't store, over each chain's RPC
+#[derive(Clone)]
+struct TransferValues {
+    mode: StorageMode,
+    /// RPC client of each configured chain; empty in full mode
+    rpc: Arc<HashMap<u32, RpcClient>>,
+}
+
+impl TransferValues {
+    fn new(mode: StorageMode, networks: &[NetworkConfig]) -> Self {
+        let rpc = match mode {
+            StorageMode::Full => HashMap::new(),
+            StorageMode::Compact => networks.iter().map(|n| (n.chain_id, RpcClient::for_network(n))).collect(),
+        };
+        Self { mode, rpc: Arc::new(rpc) }
+    }
+
+    /// Re-fetch the values of a page of transfers stored without them
+    async fn fill(&self, transfers: &mut [Transfer]) -> Result<(), ApiError> {
+        if self.mode == StorageMode::Full {
+            return Ok(());
+        }
+        let mut by_chain: HashMap<u32, Vec<&mut Transfer>> = HashMap::new();
+        for transfer in transfers.iter_mut().filter(|t| t.value.is_empty()) {
+            by_chain.entry(transfer.chain_id).or_default().push(transfer);
+        }
+        for (chain_id, mut transfers) in by_chain {
+            let rpc = self
+                .rpc
+                .get(&chain_id)
+                .ok_or_else(|| ApiError::Rpc(format!("no RPC endpoint configured for chain {}", chain_id)))?;
+            hydrate_transfer_values(rpc, ChainQuirks::for_chain(chain_id).block_receipts, &mut transfers)
+                .await
+                .map_err(ApiError::Rpc)?;
+        }
+        Ok(())
+    }
+}
+
+/// Settings of the HTTP API beyond the shared components it serves
+#[derive(Debug, Clone)]
+pub struct ApiOptions {
+    /// Where `POST /admin/backups/:chain_id` writes; None disables it
+    pub backup_dir: Option<PathBuf>,
+    /// In compact mode, whose rows carry no value, transfer listings re-fetch
+    /// values over RPC and transfer streams are refused
+    pub storage_mode: StorageMode,
+    /// Chains whose RPC endpoints serve those values
+    pub networks: Vec<NetworkConfig>,
+    /// Bearer token the /admin endpoints and watchlist changes require; None disables them
+    pub admin_token: Option<String>,
+}
+
+/// Build the REST router over the query methods of `Database`, plus the
+/// `/ws` and `/events/stream` push endpoints fed by `events`, the health checks fed by `health`,
+/// the poller controls behind `control` and chain backups into `options.backup_dir`
+pub fn router(
+    db: Arc<Database>,
+    watchlist: Arc<Watchlist>,
+    events: Arc<EventBus>,
+    health: Arc<HealthRegistry>,
+    control: Arc<ChainControl>,
+    options: ApiOptions,
+) -> Router {
+    let transfer_streams = Router::new()
+        .route("/chains/:chain_id/transfers/from/:address/stream", get(stream_transfers_from))
+        .route("/chains/:chain_id/transfers/to/:address/stream", get(stream_transfers_to))
+        .route("/chains/:chain_id/transfers/token/:token/stream", get(stream_transfers_by_token))
+        .route_layer(middleware::from_fn_with_state(options.storage_mode, require_transfer_values));
+
+    let admin = Router::new()
+        .route("/admin/chains/:chain_id/:command", post(control_chain))
+        .route("/admin/backups/:chain_id", post(backup_chain))
+        .route("/watchlist/:address", put(add_watched).delete(remove_watched))
+        .route_layer(middleware::from_fn_with_state(options.admin_token.map(Arc::from), require_admin_token));
+
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/stats", get(stats))
+        .route("/stats/rpc", get(rpc_stats))
+        .route("/search", get(search))
+        .route("/transfers/from/:address", get(transfers_from_all_chains))
+        .route("/transfers/to/:address", get(transfers_to_all_chains))
+        .route("/chains/:chain_id/transfers/from/:address", get(transfers_from))
+        .route("/chains/:chain_id/transfers/to/:address", get(transfers_to))
+        .route("/chains/:chain_id/transfers/tx/:tx_hash", get(transfers_by_tx))
+        .route("/chains/:chain_id/transfers/token/:token", get(transfers_by_token))
+        .route("/chains/:chain_id/transfers/blocks", get(transfers_by_block_range))
+        .route("/chains/:chain_id/transfers/addresses", get(transfers_by_addresses))
+        .route("/chains/:chain_id/approvals/owner/:address", get(approvals_by_owner))
+        .route("/chains/:chain_id/approvals/spender/:address", get(approvals_by_spender))
+        .route("/chains/:chain_id/balances/:address", get(balance_deltas))
+        .route("/activity/:address", get(activity_all_chains))
+        .route("/chains/:chain_id/activity/:address", get(activity))
+        .route("/chains/:chain_id/tokens/:address", get(token_info))
+        .route("/chains/:chain_id/token-stats", get(top_tokens))
+        .route("/chains/:chain_id/token-stats/:token", get(token_hourly_stats))
+        .route("/fusion-plus", get(list_fusion_plus_swaps))
+        .route("/fusion-plus/:order_hash", get(fusion_plus_swap))
+        .route("/fusion-plus/resolvers", get(resolver_stats))
+        .route("/fusion-plus/resolvers/:address", get(resolver_stats_by_address))
+        .route("/fusion-plus/:order_hash/events", get(fusion_plus_timeline))
+        .route("/fusion-plus/:order_hash/fills", get(fusion_plus_fills))
+        .route("/fusion-plus/hashlock/:hashlock", get(fusion_plus_swap_by_hashlock))
+        .route("/chains/:chain_id/fusion", get(list_fusion_swaps))
+        .route("/chains/:chain_id/fusion/blocks", get(fusion_swaps_by_block_range))
+        .route("/chains/:chain_id/fusion-plus/blocks", get(fusion_plus_swaps_by_block_range))
+        .route("/chains/:chain_id/fusion/orders/:order_hash", get(fusion_order))
+        .route("/chains/:chain_id/crypto2fiat", get(list_crypto2fiat_events))
+        .route("/fusion/:order_hash", get(fusion_swap))
+        .route("/crypto2fiat/:order_id", get(crypto2fiat_events))
+        .route("/chains/:chain_id/delegations/:authority", get(delegations_by_authority))
+        .route("/chains/:chain_id/transactions/from/:address", get(transactions_from))
+        .route("/chains/:chain_id/transactions/:tx_hash", get(transaction))
+        .route("/chains/:chain_id/internal-transfers/:address", get(internal_transfers))
+        .route("/watchers/:label/events", get(watcher_events))
+        .route("/watchlist", get(list_watchlist))
+        .route("/ws", get(ws_upgrade))
+        .route("/events/stream", get(event_stream))
+        .merge(transfer_streams)
+        .merge(admin)
+        .with_state(ApiState {
+            db,
+            watchlist,
+            events,
+            health,
+            control,
+            backup_dir: options.backup_dir.map(Arc::new),
+            values: TransferValues::new(options.storage_mode, &options.networks),
+        })
+}
+
+/// Serve the API until the task is aborted
+pub async fn serve(
+    db: Arc<Database>,
+    watchlist: Arc<Watchlist>,
+    events: Arc<EventBus>,
+    health: Arc<HealthRegistry>,
+    control: Arc<ChainControl>,
+    options: ApiOptions,
+    bind: &str,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    info!("HTTP API listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(db, watchlist, events, health, control, options)).await
+}
+
+// =============================================================================
+// Handlers
+// =============================================================================
+
+/// Liveness: 503 only when the database is unreachable
+async fn healthz(State(state): State<ApiState>) -> Response {
+    health_response(&state, false).await
+}
+
+/// Readiness: 503 when the database is unreachable or any running chain
+/// lags more than HEALTH_MAX_LAG_SECS (including chains not yet polled)
+async fn readyz(State(state): State<ApiState>) -> Response {
+    health_response(&state, true).await
+}
+
+async fn health_response(state: &ApiState, require_chains: bool) -> Response {
+    let database_ok = match state.db.ping().await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Health check: database unreachable: {}", e);
+            false
+        }
+    };
+    let chains = state.health.report();
+    let chains_ok = chains.iter().all(|c| c.healthy || c.stopped);
+
+    let ok = database_ok && (chains_ok || !require_chains);
+    let status = if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = json!({
+        "status": if ok { "ok" } else { "unavailable" },
+        "database": database_ok,
+        "chains": chains,
+    });
+    (status, Json(body)).into_response()
+}
+
+async fn stats(State(db): State<Arc<Database>>) -> ApiResult {
+    Ok(Json(json!({
+        "transfers": db.get_total_transfer_count().await?,
+        "approvals": db.get_approval_count().await?,
+        "fusion_plus_swaps": db.get_fusion_plus_count().await?,
+        "fusion_swaps": db.get_fusion_swap_count().await?,
+        "crypto2fiat_events": db.get_crypto2fiat_count().await?,
+        "delegations": db.get_delegation_count().await?,
+        "transactions": db.get_transaction_count().await?,
+        "internal_transfers": db.get_internal_transfer_count().await?,
+        "raw_events": db.get_raw_event_count().await?,
+        "size": db.get_size().await?,
+    }))
+    .into_response())
+}
+
+/// RPC requests, bytes and results per chain and method since startup, to
+/// estimate provider costs, with each chain's circuit breaker state
+async fn rpc_stats(State(health): State<Arc<HealthRegistry>>) -> ApiResult {
+    Ok(Json(health.rpc_usage()).into_response())
+}
+
+/// Refuse transfer streams in compact mode
+///
+/// Compact rows carry no value, and a stream has no page to re-fetch values
+/// for; returning them with an empty one would look like a zero-value transfer.
+async fn require_transfer_values(State(mode): State<StorageMode>, request: Request, next: Next) -> ApiResult {
+    if mode == StorageMode::Compact {
+        return Err(ApiError::NotImplemented(
+            "transfer values are not stored in compact mode (STORAGE_MODE=compact); use the paged listing".to_string(),
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Require `Authorization: Bearer <ADMIN_TOKEN>` on admin endpoints
+///
+/// They stop pollers, write to disk and change what WATCHLIST_ONLY stores, so without a configured token they
+/// are refused rather than left open to anyone who can reach API_BIND.
+async fn require_admin_token(State(token): State<Option<Arc<str>>>, request: Request, next: Next) -> ApiResult {
+    let Some(token) = token else {
+        return Err(ApiError::NotImplemented("admin endpoints are disabled (set ADMIN_TOKEN)".to_string()));
+    };
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !presented.is_some_and(|presented| tokens_match(presented.as_bytes(), token.as_bytes())) {
+        warn!("Admin API: rejected {} {} without a valid token", request.method(), request.uri().path());
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(next.run(request).await)
+}
+
+/// Compare tokens in time independent of where they differ
+fn tokens_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn transfers_from_all_chains(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path(address): Path<String>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let mut transfers = db
+        .get_transfers_by_from_all_chains(&address, &params.cursor(), params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+async fn transfers_to_all_chains(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path(address): Path<String>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let mut transfers = db
+        .get_transfers_by_to_all_chains(&address, &params.cursor(), params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+async fn transfers_from(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let mut transfers = db
+        .get_transfers_by_from(chain_id, &address, &params.cursor(), params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+async fn transfers_to(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let mut transfers = db
+        .get_transfers_by_to(chain_id, &address, &params.cursor(), params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+async fn approvals_by_owner(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<ApprovalParams>,
+) -> ApiResult {
+    let approvals = db
+        .get_approvals_by_owner(chain_id, &address, params.unlimited, params.limit())
+        .await?;
+    Ok(Json(approvals).into_response())
+}
+
+async fn approvals_by_spender(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<ApprovalParams>,
+) -> ApiResult {
+    let approvals = db
+        .get_approvals_by_spender(chain_id, &address, params.unlimited, params.limit())
+        .await?;
+    Ok(Json(approvals).into_response())
+}
+
+async fn transfers_by_tx(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path((chain_id, tx_hash)): Path<(u32, String)>,
+) -> ApiResult {
+    let mut transfers = db.get_transfers_by_tx_hash(chain_id, &tx_hash).await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+async fn token_info(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+) -> ApiResult {
+    let info = db.get_token(chain_id, &address).await?.ok_or(ApiError::NotFound)?;
+    let mut body = serde_json::to_value(&info).unwrap_or_default();
+    body["metadata_status"] = json!(info.status());
+    Ok(Json(body).into_response())
+}
+
+async fn transfers_by_token(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path((chain_id, token)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let mut transfers = db
+        .get_transfers_by_token(chain_id, &token, &params.cursor(), params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+/// Transfers from or to any of up to MAX_BATCH_ADDRESSES addresses
+async fn transfers_by_addresses(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path(chain_id): Path<u32>,
+    Query(list): Query<AddressesParams>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let addresses: Vec<&str> = list
+        .addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .collect();
+    if addresses.is_empty() || addresses.len() > MAX_BATCH_ADDRESSES {
+        return Err(ApiError::BadRequest(format!(
+            "expected 1 to {} comma-separated addresses",
+            MAX_BATCH_ADDRESSES
+        )));
+    }
+
+    let mut transfers = db
+        .get_transfers_by_addresses(chain_id, &addresses, &params.cursor(), params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+/// Tx hashes, order hashes and addresses starting with `q`, across transfers,
+/// Fusion+ swaps and Crypto2Fiat events of every chain
+async fn search(State(db): State<Arc<Database>>, Query(params): Query<SearchParams>) -> ApiResult {
+    if normalize_search_prefix(&params.q).is_none() {
+        return Err(ApiError::BadRequest(format!(
+            "q must be {} to 64 hex digits, optionally 0x-prefixed",
+            MIN_SEARCH_DIGITS
+        )));
+    }
+
+    let hits = db.search(&params.q, params.limit()).await?;
+    Ok(Json(hits).into_response())
+}
+
+async fn transfers_by_block_range(
+    State(db): State<Arc<Database>>,
+    State(values): State<TransferValues>,
+    Path(chain_id): Path<u32>,
+    Query(params): Query<BlockRangeParams>,
+) -> ApiResult {
+    let mut transfers = db
+        .get_transfers_by_block_range(chain_id, params.from_block, params.to_block, params.limit())
+        .await?;
+    values.fill(&mut transfers).await?;
+    Ok(Json(transfers).into_response())
+}
+
+/// Transfers sent by an address as NDJSON, following the cursor but not the limit
+async fn stream_transfers_from(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let transfers = db.stream_transfers_by_from(chain_id, &address, &params.cursor()).await?;
+    Ok(ndjson(transfers))
+}
+
+/// Transfers received by an address as NDJSON, following the cursor but not the limit
+async fn stream_transfers_to(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let transfers = db.stream_transfers_by_to(chain_id, &address, &params.cursor()).await?;
+    Ok(ndjson(transfers))
+}
+
+/// Transfers of a token as NDJSON, following the cursor but not the limit
+async fn stream_transfers_by_token(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, token)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let transfers = db.stream_transfers_by_token(chain_id, &token, &params.cursor()).await?;
+    Ok(ndjson(transfers))
+}
+
+/// Send transfers as newline-delimited JSON while they are read
+///
+/// The status is sent before the rows, so a query failing midway ends the
+/// body early instead of returning an error response.
+fn ndjson(transfers: TransferStream) -> Response {
+    let lines = transfers.map(|transfer| {
+        let transfer = transfer.map_err(|e| {
+            error!("Streaming transfer query failed: {}", e);
+            e
+        })?;
+        let mut line = serde_json::to_vec(&transfer)?;
+        line.push(b'\n');
+        Ok::<_, BoxError>(line)
+    });
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(lines)).into_response()
+}
+
+async fn balance_deltas(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<WindowParams>,
+) -> ApiResult {
+    let deltas = db
+        .get_balance_deltas(chain_id, &address, params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64))
+        .await?;
+    Ok(Json(deltas).into_response())
+}
+
+async fn activity_all_chains(
+    State(db): State<Arc<Database>>,
+    Path(address): Path<String>,
+    Query(params): Query<WindowParams>,
+) -> ApiResult {
+    let activity = db
+        .get_address_activity(None, &address, params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64))
+        .await?;
+    Ok(Json(activity).into_response())
+}
+
+async fn activity(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<WindowParams>,
+) -> ApiResult {
+    let activity = db
+        .get_address_activity(Some(chain_id), &address, params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64))
+        .await?;
+    Ok(Json(activity).into_response())
+}
+
+async fn top_tokens(
+    State(db): State<Arc<Database>>,
+    Path(chain_id): Path<u32>,
+    Query(params): Query<TopTokensParams>,
+) -> ApiResult {
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let stats = db
+        .get_top_tokens(chain_id, params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64), limit)
+        .await?;
+    Ok(Json(stats).into_response())
+}
+
+async fn token_hourly_stats(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, token)): Path<(u32, String)>,
+    Query(params): Query<WindowParams>,
+) -> ApiResult {
+    let stats = db
+        .get_token_hourly_stats(chain_id, &token, params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64))
+        .await?;
+    Ok(Json(stats).into_response())
+}
+
+async fn list_fusion_plus_swaps(
+    State(db): State<Arc<Database>>,
+    Query(params): Query<FusionPlusListParams>,
+) -> ApiResult {
+    let swaps = db
+        .list_fusion_plus_swaps(&params.filter(), params.limit(), params.offset)
+        .await?;
+    Ok(Json(swaps).into_response())
+}
+
+async fn fusion_plus_swap(
+    State(db): State<Arc<Database>>,
+    Path(order_hash): Path<String>,
+) -> ApiResult {
+    let swap = db.get_fusion_plus_swap(&order_hash).await?.ok_or(ApiError::NotFound)?;
+    Ok(Json(swap).into_response())
+}
+
+/// Ordered lifecycle events of a swap across both chains
+async fn fusion_plus_timeline(
+    State(db): State<Arc<Database>>,
+    Path(order_hash): Path<String>,
+) -> ApiResult {
+    let events = db.get_fusion_plus_timeline(&order_hash).await?;
+    if events.is_empty() {
+        return Err(ApiError::NotFound);
+    }
+    Ok(Json(events).into_response())
+}
+
+/// Escrow pairs of an order; more than one for multiple-fill orders
+async fn fusion_plus_fills(
+    State(db): State<Arc<Database>>,
+    Path(order_hash): Path<String>,
+) -> ApiResult {
+    let fills = db.get_fusion_plus_fills(&order_hash).await?;
+    if fills.is_empty() {
+        return Err(ApiError::NotFound);
+    }
+    Ok(Json(fills).into_response())
+}
+
+/// Resolvers ranked by swaps taken in a source block timestamp window
+async fn resolver_stats(
+    State(db): State<Arc<Database>>,
+    Query(params): Query<TopTokensParams>,
+) -> ApiResult {
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let stats = db
+        .get_resolver_stats(None, params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64), limit)
+        .await?;
+    Ok(Json(stats).into_response())
+}
+
+async fn resolver_stats_by_address(
+    State(db): State<Arc<Database>>,
+    Path(address): Path<String>,
+    Query(params): Query<WindowParams>,
+) -> ApiResult {
+    let stats = db
+        .get_resolver_stats(Some(&address), params.from.unwrap_or(0), params.to.unwrap_or(i64::MAX as u64), 1)
+        .await?
+        .pop()
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(stats).into_response())
+}
+
+async fn fusion_plus_swap_by_hashlock(
+    State(db): State<Arc<Database>>,
+    Path(hashlock): Path<String>,
+) -> ApiResult {
+    let swap = db
+        .get_fusion_plus_swap_by_hashlock(&hashlock)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(swap).into_response())
+}
+
+async fn list_fusion_swaps(
+    State(db): State<Arc<Database>>,
+    Path(chain_id): Path<u32>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let swaps = db.get_fusion_swaps(chain_id, &params.cursor(), params.limit()).await?;
+    Ok(Json(swaps).into_response())
+}
+
+async fn fusion_swaps_by_block_range(
+    State(db): State<Arc<Database>>,
+    Path(chain_id): Path<u32>,
+    Query(params): Query<BlockRangeParams>,
+) -> ApiResult {
+    let swaps = db
+        .get_fusion_swaps_by_block_range(chain_id, params.from_block, params.to_block, params.limit())
+        .await?;
+    Ok(Json(swaps).into_response())
+}
+
+async fn fusion_plus_swaps_by_block_range(
+    State(db): State<Arc<Database>>,
+    Path(chain_id): Path<u32>,
+    Query(params): Query<BlockRangeParams>,
+) -> ApiResult {
+    let swaps = db
+        .get_fusion_plus_swaps_by_block_range(chain_id, params.from_block, params.to_block, params.limit())
+        .await?;
+    Ok(Json(swaps).into_response())
+}
+
+async fn list_crypto2fiat_events(
+    State(db): State<Arc<Database>>,
+    Path(chain_id): Path<u32>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let events = db.get_crypto2fiat_events(chain_id, &params.cursor(), params.limit()).await?;
+    Ok(Json(events).into_response())
+}
+
+async fn fusion_swap(
+    State(db): State<Arc<Database>>,
+    Path(order_hash): Path<String>,
+) -> ApiResult {
+    let swap = db
+        .get_fusion_swap_by_order_hash(&order_hash)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(swap).into_response())
+}
+
+async fn fusion_order(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, order_hash)): Path<(u32, String)>,
+) -> ApiResult {
+    let order = db
+        .get_fusion_order(chain_id, &order_hash)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(order).into_response())
+}
+
+async fn crypto2fiat_events(
+    State(db): State<Arc<Database>>,
+    Path(order_id): Path<String>,
+) -> ApiResult {
+    let events = db.get_crypto2fiat_by_order_id(&order_id).await?;
+    if events.is_empty() {
+        return Err(ApiError::NotFound);
+    }
+    Ok(Json(events).into_response())
+}
+
+async fn delegations_by_authority(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, authority)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let delegations = db
+        .get_delegations_by_authority(chain_id, &authority, &params.cursor(), params.limit())
+        .await?;
+    Ok(Json(delegations).into_response())
+}
+
+async fn transactions_from(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let transactions = db
+        .get_transactions_by_from(chain_id, &address, &params.cursor(), params.limit())
+        .await?;
+    Ok(Json(transactions).into_response())
+}
+
+async fn internal_transfers(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, address)): Path<(u32, String)>,
+    Query(params): Query<PageParams>,
+) -> ApiResult {
+    let transfers = db
+        .get_internal_transfers_by_address(chain_id, &address, &params.cursor(), params.limit())
+        .await?;
+    Ok(Json(transfers).into_response())
+}
+
+async fn transaction(
+    State(db): State<Arc<Database>>,
+    Path((chain_id, tx_hash)): Path<(u32, String)>,
+) -> ApiResult {
+    let transaction = db.get_transaction(chain_id, &tx_hash).await?.ok_or(ApiError::NotFound)?;
+    Ok(Json(transaction).into_response())
+}
+
+async fn watcher_events(
+    State(db): State<Arc<Database>>,
+    Path(label): Path<String>,
+    Query(params): Query<LimitParams>,
+) -> ApiResult {
+    let events = db.get_raw_events(&label, params.limit()).await?;
+    Ok(Json(events).into_response())
+}
+
+async fn list_watchlist(State(watchlist): State<Arc<Watchlist>>) -> ApiResult {
+    Ok(Json(watchlist.list().await?).into_response())
+}
+
+/// Lowercase a `0x` + 40 hex digit address, rejecting anything else
+fn parse_address(address: &str) -> Result<String, ApiError> {
+    let valid = address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(ApiError::BadRequest(format!("invalid address: {}", address)));
+    }
+    Ok(address.to_lowercase())
+}
+
+async fn add_watched(
+    State(watchlist): State<Arc<Watchlist>>,
+    Path(address): Path<String>,
+    Query(params): Query<LabelParams>,
+) -> ApiResult {
+    let address = parse_address(&address)?;
+    let added = watchlist.add(&address, params.label.as_deref()).await?;
+    let status = if added { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(json!({ "address": address, "added": added }))).into_response())
+}
+
+async fn remove_watched(
+    State(watchlist): State<Arc<Watchlist>>,
+    Path(address): Path<String>,
+) -> ApiResult {
+    let address = parse_address(&address)?;
+    if !watchlist.remove(&address).await? {
+        return Err(ApiError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Stop, start or restart a chain's poller; 404 for chains this process
+/// doesn't poll (including in `serve` mode)
+async fn control_chain(
+    State(control): State<Arc<ChainControl>>,
+    Path((chain_id, command)): Path<(u32, String)>,
+) -> ApiResult {
+    let command = ChainCommand::parse(&command).ok_or(ApiError::NotFound)?;
+    if !control.send(chain_id, command) {
+        return Err(ApiError::NotFound);
+    }
+    info!("Admin API: {:?} requested for chain {}", command, chain_id);
+    Ok((StatusCode::ACCEPTED, Json(json!({ "chain_id": chain_id, "command": command }))).into_response())
+}
+
+/// Start a backup of a chain into a new `<BACKUP_DIR>/<chain_id>/<unix_ts>`; 404 when
+/// BACKUP_DIR is unset
+///
+/// Returns 202 with the directory once started; completion and failures are logged.
+async fn backup_chain(State(state): State<ApiState>, Path(chain_id): Path<u32>) -> ApiResult {
+    let backup_dir = state.backup_dir.as_ref().ok_or(ApiError::NotFound)?;
+    let started_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dest = create_backup_dir(&backup_dir.join(chain_id.to_string()), started_at)
+        .await
+        .map_err(DbError::from)?;
+
+    info!("Admin API: backing up chain {} to {}", chain_id, dest.display());
+    let db = Arc::clone(&state.db);
+    let task_dest = dest.clone();
+    tokio::spawn(async move {
+        match db.backup(chain_id, &task_dest).await {
+            Ok(bytes) => info!("Backup of chain {} to {} complete ({} bytes)", chain_id, task_dest.display(), bytes),
+            Err(e) => error!("Backup of chain {} to {} failed: {}", chain_id, task_dest.display(), e),
+        }
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "chain_id": chain_id, "path": dest }))).into_response())
+}
+
+/// Create a directory for a backup started at `started_at` under `parent`
+///
+/// Backups started in the same second get `<unix_ts>-1`, `<unix_ts>-2`, and so
+/// on; creating the directory claims the name, so concurrent backups never share one.
+async fn create_backup_dir(parent: &std::path::Path, started_at: u64) -> std::io::Result<PathBuf> {
+    tokio::fs::create_dir_all(parent).await?;
+    for attempt in 0u32.. {
+        let name = match attempt {
+            0 => started_at.to_string(),
+            n => format!("{}-{}", started_at, n),
+        };
+        let dest = parent.join(name);
+        match tokio::fs::create_dir(&dest).await {
+            Ok(()) => return Ok(dest),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("ran out of backup directory names")
+}
+
+// =============================================================================
+// WebSocket Push
+// =============================================================================
+
+async fn ws_upgrade(ws: WebSocketUpgrade, State(events): State<Arc<EventBus>>) -> Response {
+    ws.on_upgrade(move |socket| push_events(socket, events))
+}
+
+/// Push matching events to a WebSocket client
+///
+/// Nothing is sent until the client sends a JSON `EventFilter`; a later
+/// filter message replaces the current one. Events missed because the client
+/// fell behind are reported as `{"type": "lagged", "missed": n}`.
+async fn push_events(mut socket: WebSocket, events: Arc<EventBus>) {
+    let mut rx = events.subscribe();
+    let mut filter: Option<EventFilter> = None;
+
+    loop {
+        tokio::select! {
+            msg = socket.recv() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<EventFilter>(&text) {
+                    Ok(f) => {
+                        debug!("WebSocket subscriber filter: {:?}", f);
+                        filter = Some(f);
+                        json!({ "type": "subscribed" })
+                    }
+                    Err(e) => json!({ "error": format!("invalid filter: {}", e) }),
+                };
+                if socket.send(Message::Text(reply.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            event = rx.recv() => {
+                let payload = match event {
+                    Ok(event) => match &filter {
+                        Some(f) if f.matches(&event) => match serde_json::to_string(&event) {
+                            Ok(payload) => payload,
+                            Err(e) => {
+                                error!("Failed to serialize event: {}", e);
+                                continue;
+                            }
+                        },
+                        _ => continue,
+                    },
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("WebSocket subscriber lagged, {} events dropped", missed);
+                        json!({ "type": "lagged", "missed": missed }).to_string()
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(payload)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Server-Sent Events
+// =============================================================================
+
+/// Push events matching the query's filter as SSE, for clients without WebSockets
+///
+/// Each event is named by its type (`transfer`, `fusion_plus`, `fusion`) with
+/// the JSON event as data. Events missed because the client fell behind are
+/// reported as a `lagged` event with `{"missed": n}`.
+async fn event_stream(State(events): State<Arc<EventBus>>, Query(params): Query<StreamParams>) -> ApiResult {
+    let filter = params.filter()?;
+    debug!("SSE subscriber filter: {:?}", filter);
+
+    let stream = BroadcastStream::new(events.subscribe()).filter_map(move |event| match event {
+        Ok(event) if filter.matches(&event) => match SseEvent::default().event(event.event_type()).json_data(&event) {
+            Ok(sse) => Some(Ok::<_, Infallible>(sse)),
+            Err(e) => {
+                error!("Failed to serialize event: {}", e);
+                None
+            }
+        },
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            warn!("SSE subscriber lagged, {} events dropped", missed);
+            Some(Ok(SseEvent::default().event("lagged").data(json!({ "missed": missed }).to_string())))
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request as HttpRequest;
+    use tower::ServiceExt;
+
+    async fn status(app: Router, method: &str, uri: &str, authorization: Option<&str>) -> StatusCode {
+        let mut request = HttpRequest::builder().method(method).uri(uri);
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_compact_transfer_values() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        let db = Arc::new(db);
+        let chain_id = 990_015;
+        let (tx_hash, sender, value) = (format!("0x{:064x}", 1), format!("0x{:040x}", 2), format!("0x{:064x}", 1000));
+        let cleanup = || async {
+            db.rollback_to_block(chain_id, 0).await.unwrap();
+        };
+        cleanup().await;
+        db.insert_transfers_batch(
+            chain_id,
+            &[Transfer {
+                chain_id,
+                tx_hash: tx_hash.clone(),
+                log_index: 3,
+                token: format!("0x{:040x}", 1),
+                from_addr: sender.clone(),
+                to_addr: format!("0x{:040x}", 3),
+                value: String::new(),
+                value_decimal: None,
+                block_number: 150,
+                block_timestamp: 1_000,
+                swap_type: None,
+                tx_status: None,
+                token_info: None,
+                id: None,
+            }],
+        )
+        .await
+        .unwrap();
+
+        // The node still has the log the compact row came from
+        let receipt = json!({
+            "transactionHash": tx_hash,
+            "logs": [{
+                "address": format!("0x{:040x}", 1),
+                "topics": [],
+                "data": value,
+                "blockNumber": "0x96",
+                "transactionHash": tx_hash,
+                "logIndex": "0x3",
+            }],
+        });
+        let url = crate::rpc::mock_rpc(move |request: serde_json::Value| {
+            let receipt = receipt.clone();
+            async move { json!({ "jsonrpc": "2.0", "id": request["id"], "result": [receipt] }) }
+        })
+        .await;
+        let contents = format!("[[networks]]\nchain_id = {}\nname = \"Test\"\nrpc_url = \"{}\"", chain_id, url);
+        let networks = crate::config::networks_from_toml(&contents, None, &|_| None).unwrap();
+        let watchlist = Arc::new(Watchlist::load(Arc::clone(&db)).await.unwrap());
+        let app = |storage_mode, networks| {
+            router(
+                Arc::clone(&db),
+                Arc::clone(&watchlist),
+                Arc::new(EventBus::new()),
+                Arc::new(HealthRegistry::new(60)),
+                Arc::new(ChainControl::new()),
+                ApiOptions { backup_dir: None, storage_mode, networks, admin_token: None },
+            )
+        };
+        let get = |app: Router, uri: String| async move {
+            let request = HttpRequest::builder().uri(uri).body(Body::empty()).unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+        };
+        let listing = format!("/chains/{}/transfers/from/{}", chain_id, sender);
+
+        let (status, body) = get(app(StorageMode::Compact, networks.clone()), listing.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["value"], value);
+
+        // A stream has no page to fill, and a chain without an endpoint can't be filled
+        let (status, _) = get(app(StorageMode::Compact, networks), format!("{}/stream", listing)).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+        let (status, _) = get(app(StorageMode::Compact, Vec::new()), listing).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+
+        cleanup().await;
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_required() {
+        let app = |token: Option<&str>| {
+            Router::new()
+                .route("/admin/chains/:chain_id/:command", post(|| async { StatusCode::ACCEPTED }))
+                .route_layer(middleware::from_fn_with_state(token.map(Arc::from), require_admin_token))
+        };
+        let request = |authorization: Option<&str>| {
+            let mut request = HttpRequest::builder().method("POST").uri("/admin/chains/1/stop");
+            if let Some(authorization) = authorization {
+                request = request.header(header::AUTHORIZATION, authorization);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        let send = |token, authorization| async move {
+            app(token).oneshot(request(authorization)).await.unwrap().status()
+        };
+
+        assert_eq!(send(Some("s3cret"), Some("Bearer s3cret")).await, StatusCode::ACCEPTED);
+        assert_eq!(send(Some("s3cret"), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(Some("s3cret"), Some("Bearer s3cre")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(Some("s3cret"), Some("Bearer s3cret2")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(Some("s3cret"), Some("s3cret")).await, StatusCode::UNAUTHORIZED);
+        // No token configured: the endpoints are off, whatever the request carries
+        assert_eq!(send(None, Some("Bearer ")).await, StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn test_create_backup_dir() {
+        let parent = std::env::temp_dir().join(format!("backup-test-{}", std::process::id())).join("1");
+        let _ = std::fs::remove_dir_all(&parent);
+
+        let first = create_backup_dir(&parent, 1_700_000_000).await.unwrap();
+        let second = create_backup_dir(&parent, 1_700_000_000).await.unwrap();
+        let third = create_backup_dir(&parent, 1_700_000_000).await.unwrap();
+        assert_eq!(first, parent.join("1700000000"));
+        assert_eq!(second, parent.join("1700000000-1"));
+        assert_eq!(third, parent.join("1700000000-2"));
+        assert!(second.is_dir() && third.is_dir());
+
+        std::fs::remove_dir_all(parent.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backup_requires_admin_token() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        let db = Arc::new(db);
+        let backup_dir = std::env::temp_dir().join(format!("backup-auth-test-{}", std::process::id()));
+        let app = router(
+            Arc::clone(&db),
+            Arc::new(Watchlist::load(db).await.unwrap()),
+            Arc::new(EventBus::new()),
+            Arc::new(HealthRegistry::new(60)),
+            Arc::new(ChainControl::new()),
+            ApiOptions {
+                backup_dir: Some(backup_dir.clone()),
+                storage_mode: StorageMode::Full,
+                networks: Vec::new(),
+                admin_token: Some("s3cret".to_string()),
+            },
+        );
+
+        let request = HttpRequest::builder().method("POST").uri("/admin/backups/990011").body(Body::empty()).unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert!(!backup_dir.exists());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        let address = "0xAbCdEf0000000000000000000000000000000001";
+        assert_eq!(parse_address(address).ok().as_deref(), Some(address.to_lowercase().as_str()));
+
+        let invalid = [
+            "",
+            "0x",
+            "AbCdEf0000000000000000000000000000000001",   // No 0x prefix
+            "0xAbCdEf000000000000000000000000000000001",  // 39 digits
+            "0xAbCdEf00000000000000000000000000000000012", // 41 digits
+            "0xAbCdEf000000000000000000000000000000000g",
+        ];
+        for invalid in invalid {
+            assert!(matches!(parse_address(invalid), Err(ApiError::BadRequest(_))), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watchlist_put_delete() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        let db = Arc::new(db);
+        let watchlist = Arc::new(Watchlist::load(Arc::clone(&db)).await.unwrap());
+        let app = router(
+            db,
+            Arc::clone(&watchlist),
+            Arc::new(EventBus::new()),
+            Arc::new(HealthRegistry::new(60)),
+            Arc::new(ChainControl::new()),
+            ApiOptions {
+                backup_dir: None,
+                storage_mode: StorageMode::Full,
+                networks: Vec::new(),
+                admin_token: Some("s3cret".to_string()),
+            },
+        );
+        let admin = Some("Bearer s3cret");
+        let uri = "/watchlist/0xAbCdEf0000000000000000000000000000000bEE";
+        let lowercase = "0xabcdef0000000000000000000000000000000bee";
+        watchlist.remove(lowercase).await.unwrap();
+
+        // Changes need the admin token; listing stays public
+        assert_eq!(status(app.clone(), "PUT", uri, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(app.clone(), "DELETE", uri, Some("Bearer wrong")).await, StatusCode::UNAUTHORIZED);
+        assert!(!watchlist.matches(lowercase, ""));
+        assert_eq!(status(app.clone(), "GET", "/watchlist", None).await, StatusCode::OK);
+
+        assert_eq!(status(app.clone(), "PUT", "/watchlist/0xnot-an-address", admin).await, StatusCode::BAD_REQUEST);
+        assert_eq!(status(app.clone(), "DELETE", "/watchlist/0xabc", admin).await, StatusCode::BAD_REQUEST);
+
+        assert_eq!(status(app.clone(), "PUT", uri, admin).await, StatusCode::CREATED);
+        assert!(watchlist.matches(lowercase, ""));
+        assert_eq!(status(app.clone(), "PUT", uri, admin).await, StatusCode::OK);
+        assert_eq!(status(app.clone(), "DELETE", uri, admin).await, StatusCode::NO_CONTENT);
+        assert!(!watchlist.matches(lowercase, ""));
+        assert_eq!(status(app, "DELETE", uri, admin).await, StatusCode::NOT_FOUND);
+    }
+}
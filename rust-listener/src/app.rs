@@ -0,0 +1,775 @@
+//! Startup and subcommands of the `rust-listener` binary
+//!
+//! `run` reads the environment configuration, opens the database and either
+//! runs a one-off subcommand (verify, export, serve, backfill, replay) or
+//! indexes every configured chain until Ctrl+C.
+
+use crate::archive::Archive;
+use crate::config::{
+    get_admin_token, get_api_bind, get_archive_dir, get_backup_dir, get_circuit_breaker, get_cleanup_batch_rows, get_cleanup_interval_secs,
+    get_daily_rotation, get_database_url, get_db_max_size_bytes, get_delegation_topics, get_enrich_retry,
+    get_expiry_alert_config, get_fetch_transactions, get_gap_scan_interval_secs, get_global_rate_limit, get_grpc_bind,
+    get_health_max_lag_secs, get_integrity_check, get_internal_transfers, get_raw_log_archive, get_redis_config,
+    get_retention, get_s3_config, get_search_indexes, get_sharded_chains, get_stale_head, get_storage_mode,
+    get_token_metadata, get_token_stats_interval_secs, get_tx_status, get_uncompressed_hosts, get_vacuum_interval_secs,
+    get_watchlist_only, get_watchlist_seed, get_write_batching, get_ws_enabled, load_networks, networks_file_in_use,
+    try_load_networks, EnrichRetry,
+    IntegrityCheck, StaleHead, StorageMode, TxStatusCheck, WriteBatching,
+};
+use crate::control::ChainControl;
+use crate::db::{Database, DatabaseConfig, DbError, Retention};
+use crate::dry_run::DryRun;
+use crate::events::EventBus;
+use crate::export::{export_table, ExportFormat};
+use crate::health::HealthRegistry;
+use crate::poller::{ChainPoller, PollerConfig};
+use crate::supervisor::{reload_networks, spawn_chain, PollerFactory};
+use crate::types::{HttpOptions, NetworkConfig, TraceMethod};
+use crate::watchlist::Watchlist;
+use crate::{api, expiry, export, grpc, redis_sink, rpc, s3_upload, token_stats, verify};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal;
+use tokio::signal::unix::{signal as unix_signal, SignalKind};
+use tokio::time::{sleep, Instant};
+use tracing::{error, info, warn};
+
+/// Exit code for bad command line arguments or configuration
+const USAGE_ERROR: u8 = 2;
+
+/// Settings read from the environment at startup
+struct Settings {
+    database_url: String,
+    retention: Retention,
+    networks: Vec<NetworkConfig>,
+    storage_mode: StorageMode,
+    archive_raw_logs: bool,
+    fetch_token_metadata: bool,
+    fetch_transactions: bool,
+    internal_transfers: Option<TraceMethod>,
+    tx_status: Option<TxStatusCheck>,
+    delegation_topics: Vec<String>,
+    enrich_retry: Option<EnrichRetry>,
+    gap_scan_interval_secs: Option<u64>,
+    stale_head: Option<StaleHead>,
+    write_batching: WriteBatching,
+    ws_enabled: bool,
+    vacuum_interval_secs: Option<u64>,
+    cleanup_interval_secs: u64,
+    archive_dir: Option<PathBuf>,
+}
+
+impl Settings {
+    fn from_env() -> Self {
+        Self {
+            database_url: get_database_url(),
+            retention: get_retention(),
+            networks: load_networks(),
+            storage_mode: get_storage_mode(),
+            archive_raw_logs: get_raw_log_archive(),
+            fetch_token_metadata: get_token_metadata(),
+            fetch_transactions: get_fetch_transactions(),
+            internal_transfers: get_internal_transfers(),
+            tx_status: get_tx_status(),
+            delegation_topics: get_delegation_topics(),
+            enrich_retry: get_enrich_retry(),
+            gap_scan_interval_secs: get_gap_scan_interval_secs(),
+            stale_head: get_stale_head(),
+            write_batching: get_write_batching(),
+            ws_enabled: get_ws_enabled(),
+            vacuum_interval_secs: get_vacuum_interval_secs(),
+            cleanup_interval_secs: get_cleanup_interval_secs(),
+            archive_dir: get_archive_dir(),
+        }
+    }
+
+    /// Log the effective configuration
+    fn log(&self) {
+        let retention = &self.retention;
+        info!("Database: PostgreSQL");
+        info!(
+            "Retention: transfers {}, approvals {}, Fusion+ {} after completion (max age {}), Fusion {}, Crypto2Fiat {}, watcher events {}, delegation events {}, transactions {}, archived logs {}, internal transfers {}",
+            describe_ttl(retention.transfers),
+            describe_ttl(retention.approvals),
+            describe_ttl(retention.fusion_plus),
+            describe_ttl(retention.fusion_plus_max_age),
+            describe_ttl(retention.fusion),
+            describe_ttl(retention.crypto2fiat),
+            describe_ttl(retention.raw_events),
+            describe_ttl(retention.delegations),
+            describe_ttl(retention.transactions),
+            describe_ttl(retention.raw_logs),
+            describe_ttl(retention.internal_transfers)
+        );
+        info!("Storage mode: {:?}", self.storage_mode);
+        if self.archive_raw_logs {
+            info!("Raw log archive: enabled");
+        }
+        if !self.fetch_token_metadata {
+            info!("Token metadata: disabled");
+        }
+        if self.fetch_transactions {
+            info!("Transaction details: enabled");
+        }
+        if let Some(method) = self.internal_transfers {
+            info!("Internal transfers of watched addresses: traced with {:?}", method);
+        }
+        if let Some(check) = self.tx_status {
+            info!("Receipt status of indexed transactions: {:?}", check);
+        }
+        if !self.delegation_topics.is_empty() {
+            info!("Delegation topics: {}", self.delegation_topics.join(", "));
+        }
+        match self.enrich_retry {
+            Some(retry) => info!(
+                "Fusion enrichment retry: every {}s for swaps up to {}s old, {} attempts",
+                retry.interval_secs, retry.window_secs, retry.max_attempts
+            ),
+            None => info!("Fusion enrichment retry: disabled"),
+        }
+        match self.gap_scan_interval_secs {
+            Some(secs) => info!("Gap healing: scan every {}s", secs),
+            None => info!("Gap healing: disabled"),
+        }
+        match self.stale_head {
+            Some(stale) => info!(
+                "Stale head detection: after {}s{}",
+                stale.max_age_secs,
+                if stale.failover { ", failing over" } else { "" }
+            ),
+            None => info!("Stale head detection: disabled"),
+        }
+        match self.vacuum_interval_secs {
+            Some(secs) => info!("Vacuum: every {}s after cleanup", secs),
+            None => info!("Vacuum: left to autovacuum"),
+        }
+        info!(
+            "Writes: batches of up to {} rows, flushed every {}ms",
+            self.write_batching.batch_rows, self.write_batching.flush_ms
+        );
+        info!("WebSocket subscriptions: {}", if self.ws_enabled { "enabled" } else { "disabled" });
+        info!("Networks: {} chains configured", self.networks.len());
+        for network in &self.networks {
+            if let Some(rps) = network.rate_limit {
+                info!("[{}] RPC rate limit: {} req/s", network.name, rps);
+            }
+            if network.http != HttpOptions::default() {
+                info!("[{}] RPC connections: {:?}", network.name, network.http);
+            }
+        }
+    }
+
+    /// Poller settings shared by one-off backfills and replays
+    fn poller_config(&self, network: &NetworkConfig) -> PollerConfig {
+        PollerConfig {
+            storage_mode: self.storage_mode,
+            archive_raw_logs: self.archive_raw_logs,
+            fetch_token_metadata: self.fetch_token_metadata,
+            fetch_transactions: self.fetch_transactions,
+            internal_transfers: self.internal_transfers,
+            tx_status: self.tx_status,
+            delegation_topics: self.delegation_topics.clone(),
+            write_batch_rows: self.write_batching.batch_rows,
+            write_flush_ms: self.write_batching.flush_ms,
+            ..Default::default()
+        }
+        .with_overrides(&network.poller)
+    }
+}
+
+/// Run the listener with the process's command line arguments
+pub async fn run(args: Vec<String>) -> ExitCode {
+    info!("Starting Rust Blockchain Listener");
+
+    let settings = Settings::from_env();
+    settings.log();
+    configure_rpc();
+
+    let chain_ids: Vec<u32> = settings.networks.iter().map(|n| n.chain_id).collect();
+    info!("Chain IDs: {:?}", chain_ids);
+
+    // serve answers API queries over a read-only connection while the indexer runs elsewhere
+    let command = args.get(1).map(|s| s.as_str());
+    let read_only = command == Some("serve");
+    let db = match open_database(&settings, read_only).await {
+        Ok(db) => db,
+        Err(e) => {
+            error!("Failed to connect to PostgreSQL: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if read_only {
+        info!("PostgreSQL database connected read-only");
+    } else {
+        info!("PostgreSQL database connected. Schema auto-created for {} chains.", chain_ids.len());
+    }
+
+    // Verify indexes before anything reads or writes through them
+    check_integrity(&db, read_only).await;
+
+    // Subcommands run once and exit instead of starting the pollers
+    match command {
+        Some("verify") => return run_verify(&db, settings.networks, &args).await,
+        Some("export") => return run_export(&db, &args).await,
+        _ => {}
+    }
+
+    // Load the watchlist, adding any addresses from WATCHLIST
+    let watchlist = match Watchlist::load(Arc::clone(&db)).await {
+        Ok(watchlist) => Arc::new(watchlist),
+        Err(e) => {
+            error!("Failed to load watchlist: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if read_only {
+        return serve(db, watchlist, settings.storage_mode, settings.networks).await;
+    }
+
+    for address in get_watchlist_seed() {
+        if let Err(e) = watchlist.add(&address, None).await {
+            warn!("Failed to add {} to watchlist: {}", address, e);
+        }
+    }
+    let watchlist_only = get_watchlist_only();
+    if watchlist_only {
+        info!("Watchlist mode: storing transfers for {} watched addresses", watchlist.len());
+        if watchlist.is_empty() {
+            warn!("Watchlist is empty; no transfers will be stored until addresses are added");
+        }
+    }
+    if settings.internal_transfers.is_some() && watchlist.is_empty() {
+        warn!("Watchlist is empty; no internal transfers will be traced until addresses are added");
+    }
+
+    // backfill fetches a range from the RPC; replay re-decodes it from raw_logs
+    if let Some(command @ ("backfill" | "replay")) = command {
+        return run_backfill(command, &args, db, watchlist, watchlist_only, settings).await;
+    }
+
+    index(db, watchlist, watchlist_only, settings).await
+}
+
+/// Process-wide RPC limits shared by every chain's client
+fn configure_rpc() {
+    if let Some(rps) = get_global_rate_limit() {
+        info!("Global RPC rate limit: {} req/s", rps);
+        rpc::set_global_rate_limit(rps);
+    }
+    match get_circuit_breaker() {
+        Some((failures, cooldown)) => {
+            info!("RPC circuit breaker: open after {} failed requests for {:?}", failures, cooldown);
+            rpc::set_circuit_breaker(failures, cooldown);
+        }
+        None => info!("RPC circuit breaker: disabled"),
+    }
+    let uncompressed_hosts = get_uncompressed_hosts();
+    if !uncompressed_hosts.is_empty() {
+        info!("RPC compression disabled for: {}", uncompressed_hosts.join(", "));
+        rpc::set_uncompressed_hosts(uncompressed_hosts);
+    }
+}
+
+/// Open the PostgreSQL pool, creating the schema unless `read_only`
+async fn open_database(settings: &Settings, read_only: bool) -> Result<Arc<Database>, DbError> {
+    if let Some(dir) = &settings.archive_dir {
+        info!("Archiving expired rows to {}", dir.display());
+    }
+    let max_size_bytes = get_db_max_size_bytes();
+    if let Some(bytes) = max_size_bytes {
+        info!("Database size cap: {} MB", bytes / 1_048_576);
+    }
+    let delete_batch_rows = get_cleanup_batch_rows();
+    info!(
+        "Cleanup: every {}s, {}",
+        settings.cleanup_interval_secs,
+        delete_batch_rows.map_or("unbatched deletes".to_string(), |rows| format!("deleting {} rows per statement", rows))
+    );
+    let daily_rotation = get_daily_rotation();
+    let sharded_chains = get_sharded_chains();
+    if !sharded_chains.is_empty() {
+        if daily_rotation {
+            info!("Daily transfer partitions split out for chains {:?}", sharded_chains);
+        } else {
+            warn!("SHARDED_CHAINS has no effect without DAILY_ROTATION");
+        }
+    }
+
+    let db = if read_only {
+        Database::open_read_only(&settings.database_url).await?
+    } else {
+        let db_config = DatabaseConfig {
+            daily_rotation,
+            sharded_chains,
+            archive: settings.archive_dir.clone().map(Archive::new),
+            max_size_bytes,
+            delete_batch_rows,
+            search_indexes: get_search_indexes(),
+        };
+        Database::with_config(&settings.database_url, db_config).await?
+    };
+    Ok(Arc::new(db))
+}
+
+/// Check index integrity per INTEGRITY_CHECK, rebuilding corrupt ones in repair mode
+async fn check_integrity(db: &Database, read_only: bool) {
+    let integrity_check = get_integrity_check();
+    if integrity_check == IntegrityCheck::Off {
+        return;
+    }
+
+    let repair = integrity_check == IntegrityCheck::Repair && !read_only;
+    match db.check_integrity(repair).await {
+        Ok(None) => info!("Integrity check: skipped, amcheck extension not installed"),
+        Ok(Some(report)) if report.corrupt.is_empty() => {
+            info!("Integrity check: {} indexes OK", report.checked)
+        }
+        Ok(Some(report)) => error!(
+            "Integrity check: {} of {} indexes corrupt ({}), {} rebuilt{}",
+            report.corrupt.len(),
+            report.checked,
+            report.corrupt.join(", "),
+            report.repaired.len(),
+            if repair { "" } else { "; set INTEGRITY_CHECK=repair to rebuild them" }
+        ),
+        Err(e) => warn!("Integrity check failed: {}", e),
+    }
+}
+
+/// `verify [--chain <ID>] [--sample <N>]`: compare sampled rows against the chain
+async fn run_verify(db: &Database, networks: Vec<NetworkConfig>, args: &[String]) -> ExitCode {
+    let sample_size = arg_value(args, "--sample")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(20);
+    let networks: Vec<_> = match arg_value(args, "--chain").and_then(|s| s.parse::<u32>().ok()) {
+        Some(chain_id) => networks.into_iter().filter(|n| n.chain_id == chain_id).collect(),
+        None => networks,
+    };
+
+    info!("Verifying {} sampled rows per table on {} chains", sample_size, networks.len());
+    let report = verify::run(db, &networks, sample_size).await;
+    info!(
+        "Verify complete: {} rows checked, {} drifted, {} missing, {} not fetched",
+        report.checked, report.drifted, report.missing, report.errors
+    );
+    if report.drifted > 0 || report.missing > 0 {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// `export --chain <ID> --table <TABLE> --out <PATH>`: write a table's rows to a file
+async fn run_export(db: &Database, args: &[String]) -> ExitCode {
+    let chain_id = arg_value(args, "--chain").and_then(|s| s.parse::<u32>().ok());
+    let table = arg_value(args, "--table").and_then(export_table);
+    let out = arg_value(args, "--out").map(std::path::Path::new);
+    let (Some(chain_id), Some(table), Some(out)) = (chain_id, table, out) else {
+        error!(
+            "Usage: rust-listener export --chain <ID> --table transfers|fusion|fusion_plus --out <PATH> \
+             [--from <UNIX_TS>] [--to <UNIX_TS>] [--format csv|parquet]"
+        );
+        return ExitCode::from(USAGE_ERROR);
+    };
+    let from_ts = arg_value(args, "--from").and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+    let to_ts = arg_value(args, "--to").and_then(|s| s.parse::<u64>().ok()).unwrap_or(u64::MAX);
+    let format = match arg_value(args, "--format") {
+        Some(s) => match ExportFormat::parse(s) {
+            Some(format) => format,
+            None => {
+                error!("Unknown export format {} (expected csv or parquet)", s);
+                return ExitCode::from(USAGE_ERROR);
+            }
+        },
+        None => ExportFormat::for_path(out),
+    };
+
+    info!("Exporting {} for chain {} to {} ({:?})", table.name, chain_id, out.display(), format);
+    match export::run(db, table, chain_id, (from_ts, to_ts), format, out).await {
+        Ok(rows) => {
+            info!("Export complete: {} rows written", rows);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("Export failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `serve`: answer API queries from a read-only connection until Ctrl+C
+async fn serve(db: Arc<Database>, watchlist: Arc<Watchlist>, storage_mode: StorageMode, networks: Vec<NetworkConfig>) -> ExitCode {
+    let Some(bind) = get_api_bind() else {
+        error!("serve requires API_BIND");
+        return ExitCode::from(USAGE_ERROR);
+    };
+
+    // Watchlist changes come from the indexer's process
+    let watchlist_refresh = Arc::clone(&watchlist);
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_secs(60)).await;
+            if let Err(e) = watchlist_refresh.refresh().await {
+                warn!("Watchlist refresh error: {}", e);
+            }
+        }
+    });
+
+    // No pollers run here, so there are no live events, chain progress or pollers to control
+    let events = Arc::new(EventBus::new());
+    let health = Arc::new(HealthRegistry::new(get_health_max_lag_secs()));
+    let control = Arc::new(ChainControl::new());
+    let api_options = api::ApiOptions { backup_dir: get_backup_dir(), storage_mode, networks, admin_token: get_admin_token() };
+    info!("Serving read-only queries; watchlist changes through the API will fail");
+    tokio::select! {
+        result = api::serve(db, watchlist, events, health, control, api_options, &bind) => {
+            if let Err(e) = result {
+                error!("HTTP API error: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        _ = signal::ctrl_c() => info!("Shutdown signal received"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// `backfill|replay --chain <ID> --from <BLOCK> --to <BLOCK>`: index one block range
+///
+/// backfill --dry-run prints the rows it would write instead (or to --out).
+async fn run_backfill(
+    command: &str,
+    args: &[String],
+    db: Arc<Database>,
+    watchlist: Arc<Watchlist>,
+    watchlist_only: bool,
+    settings: Settings,
+) -> ExitCode {
+    let chain_id = arg_value(args, "--chain").and_then(|s| s.parse::<u32>().ok());
+    let from_block = arg_value(args, "--from").and_then(|s| s.parse::<u64>().ok());
+    let to_block = arg_value(args, "--to").and_then(|s| s.parse::<u64>().ok());
+    let (Some(chain_id), Some(from_block), Some(to_block)) = (chain_id, from_block, to_block) else {
+        error!(
+            "Usage: rust-listener {} --chain <ID> --from <BLOCK> --to <BLOCK> [--dry-run [--out <PATH>]]",
+            command
+        );
+        return ExitCode::from(USAGE_ERROR);
+    };
+    if from_block > to_block {
+        error!("--from must not be greater than --to");
+        return ExitCode::from(USAGE_ERROR);
+    }
+    let Some(network) = settings.networks.iter().find(|n| n.chain_id == chain_id) else {
+        error!("Chain {} is not configured", chain_id);
+        return ExitCode::from(USAGE_ERROR);
+    };
+
+    let config = settings.poller_config(network);
+    let mut poller = ChainPoller::with_config(network.clone(), db, config);
+    if watchlist_only {
+        poller = poller.with_watchlist(Arc::clone(&watchlist));
+    }
+    if settings.internal_transfers.is_some() {
+        poller = poller.with_trace_watchlist(watchlist);
+    }
+
+    if args.iter().any(|a| a == "--dry-run") {
+        if command == "replay" {
+            error!("--dry-run is only supported by backfill");
+            return ExitCode::from(USAGE_ERROR);
+        }
+        let dry_run = match arg_value(args, "--out") {
+            Some(path) => match DryRun::create(std::path::Path::new(path)) {
+                Ok(dry_run) => dry_run,
+                Err(e) => {
+                    error!("Failed to create {}: {}", path, e);
+                    return ExitCode::FAILURE;
+                }
+            },
+            None => DryRun::stdout(),
+        };
+        let dry_run = Arc::new(dry_run);
+        poller = poller.with_dry_run(Arc::clone(&dry_run));
+
+        info!("Dry run of chain {} blocks {}-{}, nothing is written", chain_id, from_block, to_block);
+        let result = poller.backfill(from_block, to_block).await;
+        let counts = dry_run.finish();
+        return match result {
+            Ok(events) => {
+                info!("Dry run complete: {} events, rows per table: {:?}", events, counts);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                error!("Dry run failed: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    if command == "replay" {
+        info!("Replaying archived logs for chain {} blocks {}-{}", chain_id, from_block, to_block);
+        return match poller.replay_archived(from_block, to_block).await {
+            Ok(events) => {
+                info!("Replay complete: {} events indexed", events);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                error!("Replay failed: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    info!("Backfilling chain {} blocks {}-{}", chain_id, from_block, to_block);
+    match poller.backfill(from_block, to_block).await {
+        Ok(events) => {
+            info!("Backfill complete: {} events indexed", events);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("Backfill failed (re-run to resume): {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Index every configured chain with the background tasks and APIs until Ctrl+C
+async fn index(db: Arc<Database>, watchlist: Arc<Watchlist>, watchlist_only: bool, settings: Settings) -> ExitCode {
+    let mut tasks = vec![spawn_cleanup(Arc::clone(&db), Arc::clone(&watchlist), &settings)];
+
+    // Hourly per-token rollup
+    if let Some(interval_secs) = get_token_stats_interval_secs() {
+        tasks.push(tokio::spawn(token_stats::run(Arc::clone(&db), interval_secs)));
+    }
+
+    // Indexed events pushed to gRPC and WebSocket subscribers
+    let events = Arc::new(EventBus::new());
+
+    // Per-chain poll progress for /healthz and /readyz
+    let health = Arc::new(HealthRegistry::new(get_health_max_lag_secs()));
+
+    // Stop/start/restart commands from the admin API to each chain's supervisor
+    let control = Arc::new(ChainControl::new());
+
+    // HTTP query API
+    if let Some(bind) = get_api_bind() {
+        let db_api = Arc::clone(&db);
+        let watchlist_api = Arc::clone(&watchlist);
+        let events_api = Arc::clone(&events);
+        let health_api = Arc::clone(&health);
+        let control_api = Arc::clone(&control);
+        let api_options = api::ApiOptions {
+            backup_dir: get_backup_dir(),
+            storage_mode: settings.storage_mode,
+            networks: settings.networks.clone(),
+            admin_token: get_admin_token(),
+        };
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = api::serve(db_api, watchlist_api, events_api, health_api, control_api, api_options, &bind).await {
+                error!("HTTP API error: {}", e);
+            }
+        }));
+    }
+
+    // gRPC streaming API
+    if let Some(bind) = get_grpc_bind() {
+        let events_grpc = Arc::clone(&events);
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = grpc::serve(events_grpc, &bind).await {
+                error!("gRPC API error: {}", e);
+            }
+        }));
+    }
+
+    // Redis Streams publisher
+    if let Some(redis_config) = get_redis_config() {
+        let events_redis = Arc::clone(&events);
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = redis_sink::run(redis_config, events_redis).await {
+                error!("Redis publisher error: {}", e);
+            }
+        }));
+    }
+
+    // Fusion+ expiry alerts
+    if let Some(alert_config) = get_expiry_alert_config() {
+        tasks.push(tokio::spawn(expiry::run(Arc::clone(&db), Arc::clone(&events), alert_config)));
+    }
+
+    // Archive uploader
+    match (get_s3_config(), settings.archive_dir.clone()) {
+        (Some(s3_config), Some(archive_dir)) => tasks.push(tokio::spawn(async move {
+            if let Err(e) = s3_upload::run(s3_config, archive_dir).await {
+                error!("Archive uploader error: {}", e);
+            }
+        })),
+        (Some(_), None) => warn!("S3_BUCKET is set but ARCHIVE_DIR is not; nothing to upload"),
+        _ => {}
+    }
+
+    // Poller for each chain
+    let factory = Arc::new(PollerFactory {
+        db: Arc::clone(&db),
+        watchlist: watchlist_only.then(|| Arc::clone(&watchlist)),
+        trace_watchlist: settings.internal_transfers.is_some().then(|| Arc::clone(&watchlist)),
+        events: Arc::clone(&events),
+        health: Arc::clone(&health),
+        storage_mode: settings.storage_mode,
+        archive_raw_logs: settings.archive_raw_logs,
+        fetch_token_metadata: settings.fetch_token_metadata,
+        fetch_transactions: settings.fetch_transactions,
+        internal_transfers: settings.internal_transfers,
+        tx_status: settings.tx_status,
+        delegation_topics: settings.delegation_topics,
+        enrich_retry: settings.enrich_retry,
+        gap_scan_interval_secs: settings.gap_scan_interval_secs,
+        stale_head: settings.stale_head,
+        write_batching: settings.write_batching,
+        ws_enabled: settings.ws_enabled,
+    });
+    let mut chains = BTreeMap::new();
+    for network in settings.networks {
+        chains.insert(network.chain_id, spawn_chain(&factory, &control, network));
+    }
+
+    info!("All {} pollers started", chains.len());
+    info!("Press Ctrl+C to stop, send SIGHUP to reload the networks config");
+
+    // Wait for shutdown signal, reloading the networks config on SIGHUP. A file
+    // read at startup must still be readable then, or the reload is refused
+    let networks_from_file = networks_file_in_use();
+    let mut hangup = match unix_signal(SignalKind::hangup()) {
+        Ok(hangup) => Some(hangup),
+        Err(e) => {
+            warn!("Failed to listen for SIGHUP, config reload disabled: {}", e);
+            None
+        }
+    };
+    loop {
+        tokio::select! {
+            result = signal::ctrl_c() => {
+                match result {
+                    Ok(()) => info!("Shutdown signal received"),
+                    Err(e) => error!("Failed to listen for shutdown: {}", e),
+                }
+                break;
+            }
+            Some(()) = async { hangup.as_mut()?.recv().await } => {
+                info!("Reloading networks config");
+                reload_networks(&mut chains, &factory, &control, try_load_networks(networks_from_file));
+            }
+        }
+    }
+
+    // Graceful shutdown
+    info!("Shutting down...");
+
+    // Abort all poller and background tasks
+    for chain in chains.values() {
+        chain.abort();
+    }
+    for task in tasks {
+        task.abort();
+    }
+
+    info!("Shutdown complete");
+    ExitCode::SUCCESS
+}
+
+/// Periodic cleanup: expire rows, vacuum, refresh the watchlist and log table sizes
+fn spawn_cleanup(db: Arc<Database>, watchlist: Arc<Watchlist>, settings: &Settings) -> tokio::task::JoinHandle<()> {
+    let retention = settings.retention;
+    let cleanup_interval_secs = settings.cleanup_interval_secs;
+    let vacuum_interval_secs = settings.vacuum_interval_secs;
+    tokio::spawn(async move {
+        let mut last_vacuum = Instant::now();
+        loop {
+            sleep(Duration::from_secs(cleanup_interval_secs)).await;
+
+            // Clean up old data from all tables
+            match db.cleanup_all(&retention).await {
+                Ok(stats) => {
+                    let total_deleted = stats.transfers_deleted
+                        + stats.approvals_deleted
+                        + stats.fusion_plus_deleted
+                        + stats.fusion_deleted
+                        + stats.crypto2fiat_deleted
+                        + stats.raw_events_deleted
+                        + stats.delegations_deleted
+                        + stats.transactions_deleted
+                        + stats.raw_logs_deleted
+                        + stats.internal_transfers_deleted
+                        + stats.size_cap_deleted;
+                    if total_deleted > 0 {
+                        info!(
+                            "Cleanup: removed {} transfers, {} approvals, {} Fusion+ swaps, {} Fusion swaps, {} Crypto2Fiat events, {} watcher events, {} delegation events, {} transactions, {} archived logs, {} internal transfers, {} rows over the size cap",
+                            stats.transfers_deleted,
+                            stats.approvals_deleted,
+                            stats.fusion_plus_deleted,
+                            stats.fusion_deleted,
+                            stats.crypto2fiat_deleted,
+                            stats.raw_events_deleted,
+                            stats.delegations_deleted,
+                            stats.transactions_deleted,
+                            stats.raw_logs_deleted,
+                            stats.internal_transfers_deleted,
+                            stats.size_cap_deleted
+                        );
+                    }
+                }
+                Err(e) => {
+                    warn!("Cleanup error: {}", e);
+                }
+            }
+
+            // Reclaim the space of the rows just deleted
+            if let Some(secs) = vacuum_interval_secs {
+                if last_vacuum.elapsed() >= Duration::from_secs(secs) {
+                    last_vacuum = Instant::now();
+                    match db.vacuum().await {
+                        Ok(reclaimed) => info!(
+                            "Vacuum: done in {:.1}s, {} MB returned to the OS",
+                            last_vacuum.elapsed().as_secs_f64(),
+                            reclaimed / 1_048_576
+                        ),
+                        Err(e) => warn!("Vacuum error: {}", e),
+                    }
+                }
+            }
+
+            // Pick up watchlist changes made by other processes
+            if let Err(e) = watchlist.refresh().await {
+                warn!("Watchlist refresh error: {}", e);
+            }
+
+            // Log stats every cleanup cycle
+            let transfer_count = db.get_total_transfer_count().await.unwrap_or(0);
+            let fusion_plus_count = db.get_fusion_plus_count().await.unwrap_or(0);
+            let fusion_count = db.get_fusion_swap_count().await.unwrap_or(0);
+            let crypto2fiat_count = db.get_crypto2fiat_count().await.unwrap_or(0);
+            let size_mb = db.get_size().await.map(|s| s.total_bytes / 1_048_576).unwrap_or(0);
+            info!(
+                "Database stats: {} transfers, {} Fusion+ swaps, {} Fusion swaps, {} Crypto2Fiat events, {} MB on disk",
+                transfer_count, fusion_plus_count, fusion_count, crypto2fiat_count, size_mb
+            );
+        }
+    })
+}
+
+/// Get the value following a `--flag` command line argument
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+/// Human-readable TTL for the startup log
+fn describe_ttl(ttl_secs: Option<u64>) -> String {
+    match ttl_secs {
+        Some(secs) => format!("{}s", secs),
+        None => "never".to_string(),
+    }
+}
@@ -0,0 +1,101 @@
+use crate::db::civil_from_days;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A row removed by TTL cleanup, serialized as JSON
+pub struct ArchivedRow {
+    pub chain_id: u32,
+    /// Block timestamp; selects the day file
+    pub timestamp: u64,
+    pub json: String,
+}
+
+/// Gzipped NDJSON export of expired rows
+///
+/// Rows are appended to `<dir>/<table>/<chain_id>/<YYYY-MM-DD>.ndjson.gz`
+/// (UTC day of the block). Each write adds a gzip member, so files can be
+/// read with `zcat` or any multi-member gzip reader.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    dir: PathBuf,
+}
+
+impl Archive {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Append `rows` of `table` to their day files
+    pub async fn write(&self, table: &str, rows: Vec<ArchivedRow>) -> io::Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let dir = self.dir.join(table);
+        tokio::task::spawn_blocking(move || write_rows(&dir, rows))
+            .await
+            .map_err(io::Error::other)?
+    }
+}
+
+fn write_rows(table_dir: &Path, rows: Vec<ArchivedRow>) -> io::Result<()> {
+    let mut files: BTreeMap<(u32, u64), Vec<String>> = BTreeMap::new();
+    for row in rows {
+        files.entry((row.chain_id, row.timestamp / 86_400)).or_default().push(row.json);
+    }
+
+    for ((chain_id, day), lines) in files {
+        let chain_dir = table_dir.join(chain_id.to_string());
+        fs::create_dir_all(&chain_dir)?;
+
+        let (year, month, dom) = civil_from_days(day as i64);
+        let path = chain_dir.join(format!("{:04}-{:02}-{:02}.ndjson.gz", year, month, dom));
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        for line in lines {
+            encoder.write_all(line.as_bytes())?;
+            encoder.write_all(b"\n")?;
+        }
+        encoder.finish()?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::MultiGzDecoder;
+    use std::io::Read;
+
+    #[tokio::test]
+    async fn test_appends_per_chain_and_day() {
+        let dir = std::env::temp_dir().join(format!("listener-archive-{}", std::process::id()));
+        let archive = Archive::new(&dir);
+        let row = |chain_id, timestamp, json: &str| ArchivedRow { chain_id, timestamp, json: json.to_string() };
+
+        // 2024-02-29 00:00:00 UTC
+        let day = 1_709_164_800;
+        archive.write("transfers", vec![row(1, day, "{\"a\":1}"), row(8453, day, "{\"b\":1}")]).await.unwrap();
+        archive.write("transfers", vec![row(1, day + 3_600, "{\"a\":2}"), row(1, day - 1, "{\"z\":0}")]).await.unwrap();
+
+        let read = |path: PathBuf| {
+            let mut text = String::new();
+            MultiGzDecoder::new(fs::File::open(path).unwrap()).read_to_string(&mut text).unwrap();
+            text
+        };
+        assert_eq!(read(dir.join("transfers/1/2024-02-29.ndjson.gz")), "{\"a\":1}\n{\"a\":2}\n");
+        assert_eq!(read(dir.join("transfers/1/2024-02-28.ndjson.gz")), "{\"z\":0}\n");
+        assert_eq!(read(dir.join("transfers/8453/2024-02-29.ndjson.gz")), "{\"b\":1}\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
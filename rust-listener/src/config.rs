@@ -1,82 +1,543 @@
-use crate::types::NetworkConfig;
+use crate::db::Retention;
+use crate::rpc::provider_from_url;
+use crate::quirks::ChainQuirks;
+use crate::types::{
+    ContractAddresses, Finality, HttpOptions, NetworkConfig, PollerOverrides, TraceMethod, WatcherConfig, ESCROW_FACTORY,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Default networks: (chain_id, name, Alchemy network slug)
+const DEFAULT_NETWORKS: &[(u32, &str, &str)] = &[
+    (1, "Ethereum", "eth-mainnet"),
+    (42161, "Arbitrum One", "arb-mainnet"),
+    (137, "Polygon", "polygon-mainnet"),
+    (10, "OP Mainnet", "opt-mainnet"),
+    (8453, "Base", "base-mainnet"),
+    (100, "Gnosis", "gnosis-mainnet"),
+    (56, "BNB Smart Chain", "bnb-mainnet"),
+    (43114, "Avalanche", "avax-mainnet"),
+    (59144, "Linea Mainnet", "linea-mainnet"),
+    (130, "Unichain", "unichain-mainnet"),
+    (1868, "Soneium Mainnet", "soneium-mainnet"),
+    (146, "Sonic", "sonic-mainnet"),
+    (57073, "Ink", "ink-mainnet"),
+];
+
+/// Built-in testnets (chain_id, name, Alchemy network slug), used instead of
+/// DEFAULT_NETWORKS when NETWORK_SET=testnet
+///
+/// 1inch has no Fusion, Fusion+ or Crypto2Fiat deployments on these chains,
+/// so only transfers, approvals, wrapped-native and watcher events are indexed.
+const TESTNET_NETWORKS: &[(u32, &str, &str)] = &[
+    (11155111, "Sepolia", "eth-sepolia"),
+    (421614, "Arbitrum Sepolia", "arb-sepolia"),
+    (80002, "Polygon Amoy", "polygon-amoy"),
+    (11155420, "OP Sepolia", "opt-sepolia"),
+    (84532, "Base Sepolia", "base-sepolia"),
+    (97, "BNB Smart Chain Testnet", "bnb-testnet"),
+    (43113, "Avalanche Fuji", "avax-fuji"),
+    (59141, "Linea Sepolia", "linea-sepolia"),
+    (1301, "Unichain Sepolia", "unichain-sepolia"),
+];
+
+/// Built-in network list selected with NETWORK_SET
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkSet {
+    Mainnet,
+    Testnet,
+}
+
+impl NetworkSet {
+    fn networks(self) -> &'static [(u32, &'static str, &'static str)] {
+        match self {
+            NetworkSet::Mainnet => DEFAULT_NETWORKS,
+            NetworkSet::Testnet => TESTNET_NETWORKS,
+        }
+    }
+}
+
+/// Get the built-in network list from environment (NETWORK_SET=mainnet|testnet)
+pub fn get_network_set() -> NetworkSet {
+    match env::var("NETWORK_SET")
+        .map(|s| s.to_lowercase())
+        .as_deref()
+    {
+        Ok("testnet") => NetworkSet::Testnet,
+        _ => NetworkSet::Mainnet,
+    }
+}
+
+/// Name and Alchemy slug of a built-in mainnet or testnet
+fn builtin_network(chain_id: u32) -> Option<&'static (u32, &'static str, &'static str)> {
+    DEFAULT_NETWORKS.iter().chain(TESTNET_NETWORKS).find(|(id, _, _)| *id == chain_id)
+}
+
+/// Wrapped native token of each built-in chain (WETH9-compatible events)
+const WRAPPED_NATIVE_TOKENS: &[(u32, &str)] = &[
+    (1, "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+    (42161, "0x82af49447d8a07e3bd95bd0d56f35241523fbab1"),
+    (137, "0x0d500b1d8e8ef31e21c99d1db9a6444d3adf1270"),
+    (10, "0x4200000000000000000000000000000000000006"),
+    (8453, "0x4200000000000000000000000000000000000006"),
+    (100, "0xe91d153e0b41518a2ce8dd3d7944fa863463a97d"),
+    (56, "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c"),
+    (43114, "0xb31f66aa3c1e785363f0875a1b74e27b85fd66c7"),
+    (59144, "0xe5d7c2a44ffddf6b295a15c148167daaaf5cf34f"),
+    (130, "0x4200000000000000000000000000000000000006"),
+    (1868, "0x4200000000000000000000000000000000000006"),
+    (146, "0x039e2fb66102314ce7b64ce5ce3e5183bc94ad38"),
+    (57073, "0x4200000000000000000000000000000000000006"),
+    (11155111, "0xfff9976782d46cc05630d1f6ebab18b2324d6b14"),
+    (421614, "0x980b62da83eff3d4576c647993b0c1d7faf17c73"),
+    (11155420, "0x4200000000000000000000000000000000000006"),
+    (84532, "0x4200000000000000000000000000000000000006"),
+    (1301, "0x4200000000000000000000000000000000000006"),
+];
+
+/// 1inch contracts of a chain unless its networks.toml entry overrides them
+fn default_contracts(chain_id: u32) -> ContractAddresses {
+    ContractAddresses {
+        escrow_factory: ESCROW_FACTORY.to_string(),
+        aggregation_router: ChainQuirks::for_chain(chain_id).aggregation_router.to_string(),
+    }
+}
+
+fn default_wrapped_native(chain_id: u32) -> Option<String> {
+    WRAPPED_NATIVE_TOKENS
+        .iter()
+        .find(|(id, _)| *id == chain_id)
+        .map(|(_, address)| address.to_string())
+}
+
+fn default_finality(chain_id: u32) -> Option<Finality> {
+    ChainQuirks::for_chain(chain_id).finality
+}
+
+/// Networks file layout (networks.toml)
+#[derive(Debug, Deserialize)]
+struct NetworksFile {
+    networks: Vec<NetworkEntry>,
+    #[serde(default)]
+    watchers: Vec<WatcherEntry>,
+}
+
+/// One `[[networks]]` entry; name and rpc_url fall back to the built-in defaults
+#[derive(Debug, Deserialize)]
+struct NetworkEntry {
+    chain_id: u32,
+    name: Option<String>,
+    rpc_url: Option<String>,
+    #[serde(default)]
+    fallback_rpc_urls: Vec<String>,
+    rate_limit: Option<f64>,
+    #[serde(default)]
+    http: HttpOptions,
+    #[serde(default)]
+    enhanced_apis: bool,
+    /// Wrapped native token; defaults to the built-in address for the chain
+    wrapped_native: Option<String>,
+    /// 1inch contracts where this chain's deployment differs from the defaults
+    escrow_factory: Option<String>,
+    aggregation_router: Option<String>,
+    /// Multicall3 for token metadata; "" calls tokens one at a time
+    multicall3: Option<String>,
+    #[serde(flatten)]
+    poller: PollerOverrides,
+}
+
+/// One `[[watchers]]` entry, attached to the network with the same chain_id
+#[derive(Debug, Deserialize)]
+struct WatcherEntry {
+    chain_id: u32,
+    #[serde(flatten)]
+    watcher: WatcherConfig,
+}
 
 /// Get Alchemy RPC URL for a network
 fn alchemy_url(network: &str, api_key: &str) -> String {
     format!("https://{}.g.alchemy.com/v2/{}", network, api_key)
 }
 
-/// Load all supported networks with Alchemy RPC URLs
+/// Per-chain RPC URL override from environment (RPC_URL_<CHAIN_ID>)
+///
+/// May be a comma-separated list; the first URL is used here and the full
+/// list is applied by `apply_rpc_url_list` once networks are loaded.
+fn rpc_url_override(chain_id: u32) -> Option<String> {
+    env::var(format!("RPC_URL_{}", chain_id))
+        .ok()
+        .and_then(|s| s.split(',').map(str::trim).find(|u| !u.is_empty()).map(str::to_string))
+}
+
+/// Replace a network's endpoints with a comma-separated list (primary first)
+fn apply_rpc_url_list(network: &mut NetworkConfig, urls: &str) {
+    let mut urls = urls.split(',').map(str::trim).filter(|u| !u.is_empty());
+    if let Some(primary) = urls.next() {
+        network.rpc_url = primary.to_string();
+        network.fallback_rpc_urls = urls.map(str::to_string).collect();
+    }
+}
+
+/// Load all supported networks
+///
+/// Reads the networks file (NETWORKS_CONFIG, default `networks.toml`) when it
+/// exists, otherwise falls back to the built-in list. In both cases an
+/// RPC_URL_<CHAIN_ID> variable takes precedence over the configured URL.
+/// Panics on an invalid or empty configuration, or when a file named by
+/// NETWORKS_CONFIG cannot be read.
 pub fn load_networks() -> Vec<NetworkConfig> {
-    let api_key = env::var("ALCHEMY_API_KEY").expect("ALCHEMY_API_KEY must be set");
+    try_load_networks(false).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    vec![
-        NetworkConfig {
-            chain_id: 1,
-            name: "Ethereum",
-            rpc_url: alchemy_url("eth-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 42161,
-            name: "Arbitrum One",
-            rpc_url: alchemy_url("arb-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 137,
-            name: "Polygon",
-            rpc_url: alchemy_url("polygon-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 10,
-            name: "OP Mainnet",
-            rpc_url: alchemy_url("opt-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 8453,
-            name: "Base",
-            rpc_url: alchemy_url("base-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 100,
-            name: "Gnosis",
-            rpc_url: alchemy_url("gnosis-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 56,
-            name: "BNB Smart Chain",
-            rpc_url: alchemy_url("bnb-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 43114,
-            name: "Avalanche",
-            rpc_url: alchemy_url("avax-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 59144,
-            name: "Linea Mainnet",
-            rpc_url: alchemy_url("linea-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 130,
-            name: "Unichain",
-            rpc_url: alchemy_url("unichain-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 1868,
-            name: "Soneium Mainnet",
-            rpc_url: alchemy_url("soneium-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 146,
-            name: "Sonic",
-            rpc_url: alchemy_url("sonic-mainnet", &api_key),
-        },
-        NetworkConfig {
-            chain_id: 57073,
-            name: "Ink",
-            rpc_url: alchemy_url("ink-mainnet", &api_key),
+/// `load_networks`, returning configuration errors instead of panicking
+/// (used when reloading the configuration at runtime)
+///
+/// With `require_file`, a missing `networks.toml` is an error too: a reload
+/// that catches the file mid-edit or mid-rename must not switch every chain
+/// to the built-in list.
+pub fn try_load_networks(require_file: bool) -> Result<Vec<NetworkConfig>, String> {
+    let api_key = env::var("ALCHEMY_API_KEY").ok();
+
+    let mut networks = match read_networks_file(env::var("NETWORKS_CONFIG").ok(), "networks.toml", require_file)? {
+        Some((path, contents)) => {
+            info!("Loading networks from {}", path);
+            networks_from_toml(&contents, api_key.as_deref(), &rpc_url_override)
+                .map_err(|e| format!("Invalid networks config {}: {}", path, e))?
+        }
+        None => default_networks(get_network_set(), api_key.as_deref(), &rpc_url_override),
+    };
+
+    if networks.is_empty() {
+        return Err("No networks configured: set ALCHEMY_API_KEY or RPC_URL_<CHAIN_ID>".to_string());
+    }
+
+    let env_lookup = |key: &str| env::var(key).ok();
+    for network in &mut networks {
+        if let Some(urls) = env_lookup(&format!("RPC_URL_{}", network.chain_id)) {
+            apply_rpc_url_list(network, &urls);
+        }
+        let env_overrides = poller_env_overrides(network.chain_id, &env_lookup)
+            .map_err(|e| format!("Invalid poller setting: {}", e))?;
+        network.poller = std::mem::take(&mut network.poller).merge(env_overrides);
+        network.rate_limit = rate_limit_for(network.chain_id, network.rate_limit, &env_lookup)
+            .map_err(|e| format!("Invalid rate limit: {}", e))?;
+    }
+
+    Ok(networks)
+}
+
+/// Read the networks file, returning its path and contents
+///
+/// `configured` is NETWORKS_CONFIG; when it is unset a missing `default_path`
+/// means the built-in list is used (None), unless `require` is set. A
+/// configured file that cannot be read is an error rather than a silent
+/// switch to the built-in chains.
+fn read_networks_file(
+    configured: Option<String>,
+    default_path: &str,
+    require: bool,
+) -> Result<Option<(String, String)>, String> {
+    let explicit = configured.is_some() || require;
+    let path = configured.unwrap_or_else(|| default_path.to_string());
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok(Some((path, contents))),
+        Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Cannot read networks config {}: {}", path, e)),
+    }
+}
+
+/// Whether networks are read from a file (NETWORKS_CONFIG is set or `networks.toml` exists)
+pub fn networks_file_in_use() -> bool {
+    env::var("NETWORKS_CONFIG").is_ok() || Path::new("networks.toml").exists()
+}
+
+/// Built-in networks; chains with neither an override nor an API key are skipped
+fn default_networks(
+    set: NetworkSet,
+    api_key: Option<&str>,
+    rpc_override: &dyn Fn(u32) -> Option<String>,
+) -> Vec<NetworkConfig> {
+    set.networks()
+        .iter()
+        .filter_map(|&(chain_id, name, slug)| {
+            let rpc_url = rpc_override(chain_id).or_else(|| api_key.map(|key| alchemy_url(slug, key)));
+            if rpc_url.is_none() {
+                warn!(
+                    "Skipping {} (chain {}): no RPC_URL_{} and no ALCHEMY_API_KEY",
+                    name, chain_id, chain_id
+                );
+            }
+            rpc_url.map(|rpc_url| NetworkConfig {
+                chain_id,
+                name: name.to_string(),
+                rpc_url,
+                fallback_rpc_urls: Vec::new(),
+                poller: PollerOverrides { finality: default_finality(chain_id), ..Default::default() },
+                rate_limit: None,
+                http: HttpOptions::default(),
+                enhanced_apis: false,
+                watchers: Vec::new(),
+                wrapped_native: default_wrapped_native(chain_id),
+                contracts: default_contracts(chain_id),
+                multicall3: Some(ChainQuirks::for_chain(chain_id).multicall3.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Parse a networks file, filling missing names/URLs from the built-in defaults
+pub(crate) fn networks_from_toml(
+    contents: &str,
+    api_key: Option<&str>,
+    rpc_override: &dyn Fn(u32) -> Option<String>,
+) -> Result<Vec<NetworkConfig>, String> {
+    let file: NetworksFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+
+    let mut seen = HashSet::new();
+    let mut networks = Vec::with_capacity(file.networks.len());
+
+    for entry in file.networks {
+        if !seen.insert(entry.chain_id) {
+            return Err(format!("chain_id {} is defined more than once", entry.chain_id));
+        }
+
+        let default = builtin_network(entry.chain_id);
+
+        let name = match (entry.name, default) {
+            (Some(name), _) => name,
+            (None, Some((_, name, _))) => name.to_string(),
+            (None, None) => format!("Chain {}", entry.chain_id),
+        };
+
+        let rpc_url = match (rpc_override(entry.chain_id).or(entry.rpc_url), default, api_key) {
+            (Some(url), _, _) => url,
+            (None, Some((_, _, slug)), Some(key)) => alchemy_url(slug, key),
+            (None, Some(_), None) => {
+                return Err(format!(
+                    "chain_id {} has no rpc_url and ALCHEMY_API_KEY is not set",
+                    entry.chain_id
+                ))
+            }
+            (None, None, _) => {
+                return Err(format!("chain_id {} is not built in and needs an rpc_url", entry.chain_id))
+            }
+        };
+
+        let address = |field: &str, value: String| {
+            if value.len() == 42 && value.starts_with("0x") && value[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+                Ok(value.to_lowercase())
+            } else {
+                Err(format!("chain_id {} has an invalid {} {}", entry.chain_id, field, value))
+            }
+        };
+        let wrapped_native = match entry.wrapped_native {
+            Some(value) => Some(address("wrapped_native", value)?),
+            None => default_wrapped_native(entry.chain_id),
+        };
+        if entry.enhanced_apis
+            && std::iter::once(&rpc_url).chain(&entry.fallback_rpc_urls).all(|url| provider_from_url(url) != "Alchemy")
+        {
+            warn!(
+                "chain_id {} sets enhanced_apis but has no Alchemy endpoint; only eth_getLogs will be used",
+                entry.chain_id
+            );
+        }
+        if entry.http.timeout_secs == 0 || entry.http.connect_timeout_secs == Some(0) {
+            return Err(format!("chain_id {} has a zero http timeout", entry.chain_id));
+        }
+        let mut contracts = default_contracts(entry.chain_id);
+        if let Some(value) = entry.escrow_factory {
+            contracts.escrow_factory = address("escrow_factory", value)?;
+        }
+        if let Some(value) = entry.aggregation_router {
+            contracts.aggregation_router = address("aggregation_router", value)?;
+        }
+        let multicall3 = match entry.multicall3 {
+            Some(value) if value.is_empty() => None,
+            Some(value) => Some(address("multicall3", value)?),
+            None => Some(ChainQuirks::for_chain(entry.chain_id).multicall3.to_string()),
+        };
+
+        networks.push(NetworkConfig {
+            chain_id: entry.chain_id,
+            name,
+            rpc_url,
+            fallback_rpc_urls: entry.fallback_rpc_urls,
+            poller: PollerOverrides {
+                finality: entry.poller.finality.or(default_finality(entry.chain_id)),
+                ..entry.poller
+            },
+            rate_limit: entry.rate_limit,
+            http: entry.http,
+            enhanced_apis: entry.enhanced_apis,
+            watchers: Vec::new(),
+            wrapped_native,
+            contracts,
+            multicall3,
+        });
+    }
+
+    let mut labels = HashSet::new();
+    for entry in file.watchers {
+        let watcher = normalize_watcher(entry.watcher)?;
+        if !labels.insert(watcher.label.clone()) {
+            return Err(format!("watcher {} is defined more than once", watcher.label));
+        }
+        let network = networks
+            .iter_mut()
+            .find(|n| n.chain_id == entry.chain_id)
+            .ok_or_else(|| format!("watcher {} uses undefined chain_id {}", watcher.label, entry.chain_id))?;
+        network.watchers.push(watcher);
+    }
+
+    Ok(networks)
+}
+
+/// Validate a watcher and lowercase its address and topics
+fn normalize_watcher(mut watcher: WatcherConfig) -> Result<WatcherConfig, String> {
+    let label_ok = !watcher.label.is_empty()
+        && watcher.label.len() <= 64
+        && watcher.label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !label_ok {
+        return Err(format!(
+            "watcher label {:?} must be 1-64 characters of [A-Za-z0-9_-]",
+            watcher.label
+        ));
+    }
+
+    let is_hex = |s: &str, len: usize| {
+        s.len() == len && s.starts_with("0x") && s[2..].chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    if let Some(address) = &mut watcher.address {
+        if !is_hex(address, 42) {
+            return Err(format!("watcher {} has an invalid address {}", watcher.label, address));
+        }
+        *address = address.to_lowercase();
+    }
+    for topic in &mut watcher.topics {
+        if !is_hex(topic, 66) {
+            return Err(format!("watcher {} has an invalid topic {}", watcher.label, topic));
+        }
+        *topic = topic.to_lowercase();
+    }
+
+    // Without either filter the watcher would store every log on the chain
+    if watcher.address.is_none() && watcher.topics.is_empty() {
+        return Err(format!("watcher {} needs an address or topics", watcher.label));
+    }
+
+    Ok(watcher)
+}
+
+/// Per-chain poller overrides from environment
+///
+/// Reads POLL_INTERVAL_MS_<ID>, CONFIRMATION_BLOCKS_<ID>, FINALITY_<ID>,
+/// MAX_BLOCKS_PER_QUERY_<ID>, MAX_BACKFILL_BLOCKS_<ID>, REORG_SAFETY_BLOCKS_<ID>
+/// and COMBINED_LOGS_<ID>.
+fn poller_env_overrides(
+    chain_id: u32,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<PollerOverrides, String> {
+    let read = |prefix: &str| -> Result<Option<u64>, String> {
+        let key = format!("{}_{}", prefix, chain_id);
+        match lookup(&key) {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{}={} is not a number", key, value)),
+            None => Ok(None),
+        }
+    };
+
+    let finality_key = format!("FINALITY_{}", chain_id);
+    let finality = match lookup(&finality_key) {
+        Some(value) => Some(Finality::parse(&value).ok_or_else(|| {
+            format!("{}={} is not one of confirmations, safe, finalized", finality_key, value)
+        })?),
+        None => None,
+    };
+
+    let combined_key = format!("COMBINED_LOGS_{}", chain_id);
+    let combined_logs = match lookup(&combined_key) {
+        Some(value) => match value.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" => Some(true),
+            "0" | "false" | "no" => Some(false),
+            _ => return Err(format!("{}={} is not true or false", combined_key, value)),
         },
-    ]
+        None => None,
+    };
+
+    Ok(PollerOverrides {
+        poll_interval_ms: read("POLL_INTERVAL_MS")?,
+        confirmation_blocks: read("CONFIRMATION_BLOCKS")?,
+        finality,
+        max_blocks_per_query: read("MAX_BLOCKS_PER_QUERY")?,
+        max_backfill_blocks: read("MAX_BACKFILL_BLOCKS")?,
+        reorg_safety_blocks: read("REORG_SAFETY_BLOCKS")?,
+        combined_logs,
+    })
+}
+
+/// Requests per second for a chain's RPC client
+///
+/// RPC_RATE_LIMIT_<ID> wins over the networks file, which wins over the
+/// RPC_RATE_LIMIT default for all chains.
+fn rate_limit_for(
+    chain_id: u32,
+    configured: Option<f64>,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<Option<f64>, String> {
+    let parse = |key: &str, value: &str| match value.trim().parse::<f64>() {
+        Ok(rps) if rps > 0.0 && rps.is_finite() => Ok(rps),
+        _ => Err(format!("{}={} is not a positive number", key, value)),
+    };
+
+    let per_chain = format!("RPC_RATE_LIMIT_{}", chain_id);
+    if let Some(value) = lookup(&per_chain) {
+        return parse(&per_chain, &value).map(Some);
+    }
+    if let Some(rps) = configured {
+        return parse("rate_limit", &rps.to_string()).map(Some);
+    }
+    lookup("RPC_RATE_LIMIT").map(|value| parse("RPC_RATE_LIMIT", &value)).transpose()
+}
+
+/// Get the request cap shared by all chains (RPC_GLOBAL_RATE_LIMIT, requests per second)
+pub fn get_global_rate_limit() -> Option<f64> {
+    let value = env::var("RPC_GLOBAL_RATE_LIMIT").ok().filter(|s| !s.is_empty())?;
+    match value.trim().parse::<f64>() {
+        Ok(rps) if rps > 0.0 && rps.is_finite() => Some(rps),
+        _ => panic!("RPC_GLOBAL_RATE_LIMIT={} is not a positive number", value),
+    }
+}
+
+/// Get the RPC circuit breaker as (consecutive failed requests, cooldown)
+/// (RPC_BREAKER_FAILURES, default 5, 0 = disabled; RPC_BREAKER_COOLDOWN_SECS, default 30)
+pub fn get_circuit_breaker() -> Option<(u32, Duration)> {
+    let failures: u32 = env::var("RPC_BREAKER_FAILURES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(5);
+    let cooldown_secs: u64 = env::var("RPC_BREAKER_COOLDOWN_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    (failures > 0).then(|| (failures, Duration::from_secs(cooldown_secs)))
+}
+
+/// Get the RPC hosts asked for uncompressed responses (RPC_NO_COMPRESSION,
+/// comma-separated; subdomains match too)
+pub fn get_uncompressed_hosts() -> Vec<String> {
+    env::var("RPC_NO_COMPRESSION")
+        .map(|s| s.split(',').map(str::trim).filter(|h| !h.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
 }
 
 /// Get PostgreSQL database URL from environment
@@ -91,3 +552,902 @@ pub fn get_ttl_secs() -> u64 {
         .and_then(|s| s.parse().ok())
         .unwrap_or(600) // Default 10 minutes
 }
+
+/// Age after which Fusion+ swaps are deleted even if a leg never completed
+const DEFAULT_FUSION_PLUS_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Get per-table retention
+///
+/// TRANSFER_TTL_SECS, APPROVAL_TTL_SECS, FUSION_PLUS_TTL_SECS, FUSION_TTL_SECS,
+/// C2F_TTL_SECS, RAW_EVENT_TTL_SECS, RAW_LOG_TTL_SECS and INTERNAL_TRANSFER_TTL_SECS
+/// override TTL_SECS for their table; "never" keeps that table's rows forever.
+/// Fusion+ swaps only expire once complete, or after FUSION_PLUS_MAX_AGE_SECS
+/// (default 7 days).
+pub fn get_retention() -> Retention {
+    let default = get_ttl_secs();
+    let ttl = |key: &str| table_ttl(key, env::var(key).ok().as_deref(), default);
+
+    Retention {
+        transfers: ttl("TRANSFER_TTL_SECS"),
+        approvals: ttl("APPROVAL_TTL_SECS"),
+        fusion_plus: ttl("FUSION_PLUS_TTL_SECS"),
+        fusion_plus_max_age: table_ttl(
+            "FUSION_PLUS_MAX_AGE_SECS",
+            env::var("FUSION_PLUS_MAX_AGE_SECS").ok().as_deref(),
+            DEFAULT_FUSION_PLUS_MAX_AGE_SECS,
+        ),
+        fusion: ttl("FUSION_TTL_SECS"),
+        crypto2fiat: ttl("C2F_TTL_SECS"),
+        raw_events: ttl("RAW_EVENT_TTL_SECS"),
+        delegations: ttl("DELEGATION_TTL_SECS"),
+        transactions: ttl("TRANSACTION_TTL_SECS"),
+        raw_logs: ttl("RAW_LOG_TTL_SECS"),
+        internal_transfers: ttl("INTERNAL_TRANSFER_TTL_SECS"),
+    }
+}
+
+/// Parse one table's TTL override, falling back to `default` when unset or invalid
+fn table_ttl(key: &str, value: Option<&str>, default: u64) -> Option<u64> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Some(default);
+    };
+    if value.eq_ignore_ascii_case("never") {
+        return None;
+    }
+    match value.parse() {
+        Ok(secs) => Some(secs),
+        Err(_) => {
+            warn!("Ignoring {}={}: expected seconds or \"never\"", key, value);
+            Some(default)
+        }
+    }
+}
+
+/// Get daily rotation flag from environment (DAILY_ROTATION=true)
+///
+/// When enabled on a fresh database, transfers are partitioned by day and
+/// expired days are dropped wholesale instead of deleted row by row.
+pub fn get_daily_rotation() -> bool {
+    env::var("DAILY_ROTATION")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Get whether transfers get prefix indexes for search (SEARCH_INDEXES=true)
+pub fn get_search_indexes() -> bool {
+    env::var("SEARCH_INDEXES")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Get the chains given their own daily transfers partitions (SHARDED_CHAINS,
+/// comma-separated chain IDs; needs DAILY_ROTATION)
+pub fn get_sharded_chains() -> Vec<u32> {
+    env::var("SHARDED_CHAINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| match s.parse() {
+            Ok(chain_id) => Some(chain_id),
+            Err(_) => {
+                warn!("Ignoring invalid SHARDED_CHAINS entry {:?}", s);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Get the pause between cleanup cycles (CLEANUP_INTERVAL_SECS, default 60)
+pub fn get_cleanup_interval_secs() -> u64 {
+    env::var("CLEANUP_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(60)
+}
+
+/// Get the rows removed per cleanup DELETE (CLEANUP_BATCH_ROWS, default 5000,
+/// 0 = each table's expired rows in one statement)
+pub fn get_cleanup_batch_rows() -> Option<usize> {
+    let rows = env::var("CLEANUP_BATCH_ROWS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(5000);
+    (rows > 0).then_some(rows)
+}
+
+/// Get the database size cap from environment (DB_MAX_SIZE_MB, disabled if unset or 0)
+///
+/// Above it, cleanup deletes the oldest rows of the high-volume tables
+/// regardless of their TTL.
+pub fn get_db_max_size_bytes() -> Option<u64> {
+    env::var("DB_MAX_SIZE_MB")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .filter(|&mb| mb > 0)
+        .map(|mb| mb * 1_048_576)
+}
+
+/// Get the expired-row archive directory from environment (ARCHIVE_DIR, disabled if unset)
+///
+/// When set, rows removed by TTL cleanup are first exported to gzipped NDJSON
+/// files under this directory.
+pub fn get_archive_dir() -> Option<PathBuf> {
+    env::var("ARCHIVE_DIR").ok().filter(|s| !s.is_empty()).map(PathBuf::from)
+}
+
+/// Get the directory chain backups are written to (BACKUP_DIR, backups disabled if unset)
+pub fn get_backup_dir() -> Option<PathBuf> {
+    env::var("BACKUP_DIR").ok().filter(|s| !s.is_empty()).map(PathBuf::from)
+}
+
+/// Get raw log archiving flag from environment (RAW_LOG_ARCHIVE)
+///
+/// When enabled every fetched log is stored verbatim in `raw_logs` before
+/// decoding, so `rust-listener replay` can re-decode it later.
+pub fn get_raw_log_archive() -> bool {
+    env::var("RAW_LOG_ARCHIVE")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Get whether ERC-20 metadata is fetched for newly seen tokens (default: enabled)
+pub fn get_token_metadata() -> bool {
+    env::var("TOKEN_METADATA")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(true)
+}
+
+/// Get whether sender, receiver and gas of transactions with indexed events
+/// are fetched into `transactions` (FETCH_TRANSACTIONS, default: disabled)
+///
+/// Costs one receipt request per transaction.
+pub fn get_fetch_transactions() -> bool {
+    env::var("FETCH_TRANSACTIONS")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Get how value moved by contract calls to or from watched addresses is
+/// traced into `internal_transfers` (INTERNAL_TRANSFERS=trace_filter|debug_trace,
+/// default: off)
+///
+/// trace_filter costs two requests per range; debug_trace traces every block
+/// of the range and needs a node that allows it.
+pub fn get_internal_transfers() -> Option<TraceMethod> {
+    match env::var("INTERNAL_TRANSFERS")
+        .map(|s| s.to_lowercase())
+        .as_deref()
+    {
+        Ok("trace_filter") => Some(TraceMethod::TraceFilter),
+        Ok("debug_trace") => Some(TraceMethod::DebugTrace),
+        Ok("") | Ok("off") | Err(_) => None,
+        Ok(other) => {
+            warn!("Ignoring INTERNAL_TRANSFERS={}: expected trace_filter or debug_trace", other);
+            None
+        }
+    }
+}
+
+/// Whether indexed events are checked against their transaction's receipt status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatusCheck {
+    /// Store the status in `transfers.tx_status`
+    Record,
+    /// Also drop every event of a transaction that failed
+    Skip,
+}
+
+/// Get how receipt statuses of transactions with indexed events are checked
+/// (TX_STATUS=record|skip, default: off)
+///
+/// Costs one eth_getBlockReceipts request per block with events, or one
+/// receipt request per transaction on chains without it.
+pub fn get_tx_status() -> Option<TxStatusCheck> {
+    match env::var("TX_STATUS")
+        .map(|s| s.to_lowercase())
+        .as_deref()
+    {
+        Ok("record") => Some(TxStatusCheck::Record),
+        Ok("skip") => Some(TxStatusCheck::Skip),
+        Ok("") | Ok("off") | Err(_) => None,
+        Ok(other) => {
+            warn!("Ignoring TX_STATUS={}: expected record or skip", other);
+            None
+        }
+    }
+}
+
+/// How much transfer data is persisted per row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageMode {
+    /// Store every decoded field, including the raw value
+    Full,
+    /// Store only event coordinates and addresses; the value is re-fetched
+    /// from the transaction receipt when it is needed
+    Compact,
+}
+
+/// Get storage mode from environment (STORAGE_MODE=full|compact)
+pub fn get_storage_mode() -> StorageMode {
+    match env::var("STORAGE_MODE")
+        .map(|s| s.to_lowercase())
+        .as_deref()
+    {
+        Ok("compact") => StorageMode::Compact,
+        _ => StorageMode::Full,
+    }
+}
+
+/// What to do about corrupt indexes found at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    Off,
+    /// Verify indexes and report corrupt ones
+    Check,
+    /// Also rebuild corrupt indexes
+    Repair,
+}
+
+/// Get the startup integrity check mode (INTEGRITY_CHECK=off|check|repair, default check)
+///
+/// Checks need the PostgreSQL `amcheck` extension and are skipped without it.
+pub fn get_integrity_check() -> IntegrityCheck {
+    match env::var("INTEGRITY_CHECK")
+        .map(|s| s.to_lowercase())
+        .as_deref()
+    {
+        Ok("off") | Ok("false") | Ok("0") => IntegrityCheck::Off,
+        Ok("repair") => IntegrityCheck::Repair,
+        _ => IntegrityCheck::Check,
+    }
+}
+
+/// Get HTTP API bind address from environment (API_BIND, unset = disabled)
+pub fn get_api_bind() -> Option<String> {
+    env::var("API_BIND").ok().filter(|s| !s.is_empty())
+}
+
+/// Get the bearer token the /admin endpoints require (ADMIN_TOKEN, unset = admin endpoints disabled)
+pub fn get_admin_token() -> Option<String> {
+    env::var("ADMIN_TOKEN").ok().filter(|s| !s.is_empty())
+}
+
+/// Get the checkpoint age in seconds above which /readyz fails (HEALTH_MAX_LAG_SECS)
+pub fn get_health_max_lag_secs() -> u64 {
+    env::var("HEALTH_MAX_LAG_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(300)
+}
+
+/// Get the OTLP gRPC collector endpoint (OTEL_EXPORTER_OTLP_ENDPOINT, tracing disabled if unset)
+pub fn get_otlp_endpoint() -> Option<String> {
+    env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().filter(|s| !s.is_empty())
+}
+
+/// Get the service name attached to exported spans (OTEL_SERVICE_NAME)
+pub fn get_otel_service_name() -> String {
+    env::var("OTEL_SERVICE_NAME")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "rust-listener".to_string())
+}
+
+/// Get gRPC streaming API bind address from environment (GRPC_BIND, disabled if unset)
+pub fn get_grpc_bind() -> Option<String> {
+    env::var("GRPC_BIND").ok().filter(|s| !s.is_empty())
+}
+
+/// Redis Streams output settings
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    pub url: String,
+    /// Streams are named `<prefix>:<chain_id>`
+    pub stream_prefix: String,
+    /// Approximate cap on entries per stream (XADD MAXLEN ~)
+    pub max_len: usize,
+}
+
+/// Get Redis Streams output config from environment (REDIS_URL, unset = disabled)
+pub fn get_redis_config() -> Option<RedisConfig> {
+    let url = env::var("REDIS_URL").ok().filter(|s| !s.is_empty())?;
+    Some(RedisConfig {
+        url,
+        stream_prefix: env::var("REDIS_STREAM_PREFIX").unwrap_or_else(|_| "listener".to_string()),
+        max_len: env::var("REDIS_STREAM_MAXLEN")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100_000),
+    })
+}
+
+/// Alerting on Fusion+ legs nearing cancellation
+#[derive(Debug, Clone)]
+pub struct ExpiryAlertConfig {
+    /// Alert this many seconds before a leg's cancellation period starts
+    pub lead_secs: u64,
+    pub interval_secs: u64,
+    /// Alerts are POSTed here as JSON (unset = log and event bus only)
+    pub webhook_url: Option<String>,
+}
+
+/// Get Fusion+ expiry alerting config from environment (EXPIRY_ALERT_SECS, unset or 0 = disabled)
+pub fn get_expiry_alert_config() -> Option<ExpiryAlertConfig> {
+    let lead_secs = env::var("EXPIRY_ALERT_SECS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|&secs| secs > 0)?;
+    Some(ExpiryAlertConfig {
+        lead_secs,
+        interval_secs: env::var("EXPIRY_ALERT_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(60),
+        webhook_url: env::var("EXPIRY_ALERT_WEBHOOK").ok().filter(|s| !s.is_empty()),
+    })
+}
+
+/// S3-compatible bucket that archive files are uploaded to
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// Service endpoint; requests are path-style (`<endpoint>/<bucket>/<key>`)
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Key prefix for uploaded objects
+    pub prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub upload_interval_secs: u64,
+    /// Objects for days older than this are deleted from the bucket (unset = kept forever)
+    pub retention_days: Option<u64>,
+}
+
+/// Get archive upload config from environment (S3_BUCKET, unset = disabled)
+///
+/// Credentials are read from S3_ACCESS_KEY_ID / S3_SECRET_ACCESS_KEY, falling
+/// back to AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY.
+pub fn get_s3_config() -> Option<S3Config> {
+    let bucket = env::var("S3_BUCKET").ok().filter(|s| !s.is_empty())?;
+    let var = |name: &str, fallback: &str| {
+        env::var(name).or_else(|_| env::var(fallback)).ok().filter(|s| !s.is_empty())
+    };
+    let (Some(access_key_id), Some(secret_access_key)) = (
+        var("S3_ACCESS_KEY_ID", "AWS_ACCESS_KEY_ID"),
+        var("S3_SECRET_ACCESS_KEY", "AWS_SECRET_ACCESS_KEY"),
+    ) else {
+        warn!("S3_BUCKET is set but S3 credentials are missing; archive upload disabled");
+        return None;
+    };
+    let region = var("S3_REGION", "AWS_REGION").unwrap_or_else(|| "us-east-1".to_string());
+
+    Some(S3Config {
+        endpoint: env::var("S3_ENDPOINT")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region)),
+        region,
+        bucket,
+        prefix: env::var("S3_PREFIX").unwrap_or_default(),
+        access_key_id,
+        secret_access_key,
+        upload_interval_secs: env::var("S3_UPLOAD_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(300),
+        retention_days: env::var("S3_RETENTION_DAYS").ok().and_then(|s| s.parse().ok()),
+    })
+}
+
+/// Retry of Fusion swaps stored before their transfers could be read
+#[derive(Debug, Clone, Copy)]
+pub struct EnrichRetry {
+    pub interval_secs: u64,
+    /// Only swaps from blocks this recent are retried
+    pub window_secs: u64,
+    /// Attempts before a swap is marked failed
+    pub max_attempts: u32,
+}
+
+/// Get Fusion enrichment retry config from environment
+/// (FUSION_ENRICH_INTERVAL_SECS, default 60, 0 = disabled)
+pub fn get_enrich_retry() -> Option<EnrichRetry> {
+    let var = |name: &str, default: u64| {
+        env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+    };
+    let interval_secs = var("FUSION_ENRICH_INTERVAL_SECS", 60);
+    (interval_secs > 0).then(|| EnrichRetry {
+        interval_secs,
+        window_secs: var("FUSION_ENRICH_WINDOW_SECS", 1_800),
+        max_attempts: var("FUSION_ENRICH_MAX_ATTEMPTS", 5).clamp(1, u32::MAX as u64) as u32,
+    })
+}
+
+/// Get the interval of the hourly per-token transfer rollup into `token_stats`
+/// (TOKEN_STATS_INTERVAL_SECS, default 300, 0 = disabled)
+pub fn get_token_stats_interval_secs() -> Option<u64> {
+    let interval_secs = env::var("TOKEN_STATS_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(300);
+    (interval_secs > 0).then_some(interval_secs)
+}
+
+/// Get the interval of each chain's scan for unindexed block ranges to backfill
+/// (GAP_SCAN_INTERVAL_SECS, default 300, 0 = disabled)
+pub fn get_gap_scan_interval_secs() -> Option<u64> {
+    let interval_secs = env::var("GAP_SCAN_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(300);
+    (interval_secs > 0).then_some(interval_secs)
+}
+
+/// Detection of RPC endpoints serving a stale chain head
+#[derive(Debug, Clone, Copy)]
+pub struct StaleHead {
+    /// Head age, by wall clock or time without a new block, considered stale
+    pub max_age_secs: u64,
+    /// Fail over to the next RPC endpoint of the chain when the head is stale
+    pub failover: bool,
+}
+
+/// Get stale head detection config from environment
+/// (STALE_HEAD_SECS, default 300, 0 = disabled; STALE_HEAD_FAILOVER)
+pub fn get_stale_head() -> Option<StaleHead> {
+    let max_age_secs = env::var("STALE_HEAD_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(300);
+    (max_age_secs > 0).then(|| StaleHead {
+        max_age_secs,
+        failover: env::var("STALE_HEAD_FAILOVER")
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false),
+    })
+}
+
+/// Get the interval of the VACUUM run after cleanup (VACUUM_INTERVAL_SECS,
+/// default 3600, 0 = leave it to autovacuum)
+pub fn get_vacuum_interval_secs() -> Option<u64> {
+    let interval_secs = env::var("VACUUM_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3600);
+    (interval_secs > 0).then_some(interval_secs)
+}
+
+/// Batching of each chain's writer task
+#[derive(Debug, Clone, Copy)]
+pub struct WriteBatching {
+    /// Rows buffered before they are inserted
+    pub batch_rows: usize,
+    /// Longest time rows wait before they are inserted
+    pub flush_ms: u64,
+}
+
+/// Get writer batching from environment
+/// (WRITE_BATCH_ROWS, default 1000; WRITE_FLUSH_MS, default 250)
+pub fn get_write_batching() -> WriteBatching {
+    let var = |name: &str, default: u64| {
+        env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+    };
+    WriteBatching {
+        batch_rows: var("WRITE_BATCH_ROWS", 1_000).max(1) as usize,
+        flush_ms: var("WRITE_FLUSH_MS", 250),
+    }
+}
+
+/// Get watchlist filtering flag from environment (WATCHLIST_ONLY)
+pub fn get_watchlist_only() -> bool {
+    env::var("WATCHLIST_ONLY")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Get addresses to add to the watchlist at startup (WATCHLIST, comma-separated)
+pub fn get_watchlist_seed() -> Vec<String> {
+    env::var("WATCHLIST")
+        .map(|s| {
+            s.split(',')
+                .map(|a| a.trim().to_lowercase())
+                .filter(|a| !a.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the topic0 values of EIP-7702 delegate events to index (DELEGATION_TOPICS, comma-separated)
+///
+/// Matching logs from any address are stored in `delegations` keyed by the
+/// emitting (authority) address. Invalid topics are skipped with a warning.
+pub fn get_delegation_topics() -> Vec<String> {
+    env::var("DELEGATION_TOPICS").map(|s| parse_topics(&s)).unwrap_or_default()
+}
+
+fn parse_topics(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .filter(|t| {
+            let valid = t.len() == 66 && t.starts_with("0x") && t[2..].chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                warn!("Ignoring invalid DELEGATION_TOPICS entry {:?}", t);
+            }
+            valid
+        })
+        .collect()
+}
+
+/// Get WebSocket subscription flag from environment (WS_ENABLED)
+pub fn get_ws_enabled() -> bool {
+    env::var("WS_ENABLED")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// WebSocket endpoint for a network
+///
+/// WS_URL_<CHAIN_ID> wins; otherwise the URL is derived from the HTTP endpoint
+/// for providers that serve both on the same path (Alchemy, QuickNode).
+pub fn ws_url_for(network: &NetworkConfig) -> Option<String> {
+    if let Some(url) = env::var(format!("WS_URL_{}", network.chain_id))
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        return Some(url);
+    }
+    derive_ws_url(&network.rpc_url)
+}
+
+fn derive_ws_url(rpc_url: &str) -> Option<String> {
+    match provider_from_url(rpc_url) {
+        "Alchemy" | "QuickNode" => rpc_url
+            .strip_prefix("https://")
+            .map(|rest| format!("wss://{}", rest)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AGGREGATION_ROUTER_V6, AGGREGATION_ROUTER_ZKSYNC, MULTICALL3, MULTICALL3_ZKSYNC};
+
+    fn no_override(_: u32) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_read_networks_file() {
+        let dir = std::env::temp_dir().join(format!("networks-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("networks.toml");
+        fs::write(&file, "[[networks]]\nchain_id = 1\n").unwrap();
+        let missing = dir.join("missing.toml").to_string_lossy().to_string();
+        let file = file.to_string_lossy().to_string();
+
+        // Unset: the default file when present, else the built-in list
+        assert_eq!(read_networks_file(None, &missing, false), Ok(None));
+        let (path, contents) = read_networks_file(None, &file, false).unwrap().unwrap();
+        assert_eq!(path, file);
+        assert!(contents.contains("chain_id = 1"));
+
+        // Set explicitly: the file must be readable
+        assert!(read_networks_file(Some(file.clone()), &missing, false).unwrap().is_some());
+        let err = read_networks_file(Some(missing.clone()), &file, false).unwrap_err();
+        assert!(err.contains(&missing), "{}", err);
+
+        // Required on reload: a default file gone mid-rename is an error, not the built-in list
+        assert!(read_networks_file(None, &file, true).unwrap().is_some());
+        let err = read_networks_file(None, &missing, true).unwrap_err();
+        assert!(err.contains(&missing), "{}", err);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_poller_overrides_file_and_env() {
+        let contents = "[[networks]]\nchain_id = 1\npoll_interval_ms = 12000\nconfirmation_blocks = 2";
+        let networks = networks_from_toml(contents, Some("key"), &no_override).unwrap();
+        assert_eq!(networks[0].poller.poll_interval_ms, Some(12_000));
+        assert_eq!(networks[0].poller.confirmation_blocks, Some(2));
+        assert_eq!(networks[0].poller.max_blocks_per_query, None);
+
+        let lookup = |key: &str| match key {
+            "CONFIRMATION_BLOCKS_1" => Some("5".to_string()),
+            "MAX_BLOCKS_PER_QUERY_1" => Some("100".to_string()),
+            "FINALITY_1" => Some("Finalized".to_string()),
+            "COMBINED_LOGS_1" => Some("true".to_string()),
+            _ => None,
+        };
+        let env = poller_env_overrides(1, &lookup).unwrap();
+        let merged = networks[0].poller.clone().merge(env);
+        assert_eq!(merged.poll_interval_ms, Some(12_000)); // File value kept
+        assert_eq!(merged.confirmation_blocks, Some(5)); // Env wins
+        assert_eq!(merged.max_blocks_per_query, Some(100));
+        assert_eq!(merged.finality, Some(Finality::Finalized));
+        assert_eq!(merged.combined_logs, Some(true));
+
+        let bad = |key: &str| (key == "POLL_INTERVAL_MS_1").then(|| "fast".to_string());
+        assert!(poller_env_overrides(1, &bad).is_err());
+        let bad = |key: &str| (key == "FINALITY_1").then(|| "latest".to_string());
+        assert!(poller_env_overrides(1, &bad).is_err());
+        let bad = |key: &str| (key == "COMBINED_LOGS_1").then(|| "maybe".to_string());
+        assert!(poller_env_overrides(1, &bad).is_err());
+    }
+
+    #[test]
+    fn test_fallback_rpc_urls() {
+        let contents = "[[networks]]\nchain_id = 1\nrpc_url = \"https://a\"\nfallback_rpc_urls = [\"https://b\"]";
+        let mut networks = networks_from_toml(contents, None, &no_override).unwrap();
+        assert_eq!(networks[0].rpc_urls(), vec!["https://a", "https://b"]);
+
+        // An env list replaces both primary and fallbacks
+        apply_rpc_url_list(&mut networks[0], " https://c , https://d,");
+        assert_eq!(networks[0].rpc_urls(), vec!["https://c", "https://d"]);
+    }
+
+    #[test]
+    fn test_derive_ws_url() {
+        assert_eq!(
+            derive_ws_url("https://base-mainnet.g.alchemy.com/v2/key").as_deref(),
+            Some("wss://base-mainnet.g.alchemy.com/v2/key")
+        );
+        assert_eq!(derive_ws_url("https://mainnet.infura.io/v3/key"), None);
+        assert_eq!(derive_ws_url("http://127.0.0.1:8545"), None);
+    }
+
+    #[test]
+    fn test_rpc_url_override_precedence() {
+        let rpc_override = |chain_id: u32| (chain_id == 8453).then(|| "https://base.quiknode.pro/abc".to_string());
+
+        // Without an API key only the overridden chain is usable
+        let networks = default_networks(NetworkSet::Mainnet, None, &rpc_override);
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].chain_id, 8453);
+        assert_eq!(networks[0].rpc_url, "https://base.quiknode.pro/abc");
+
+        // Override wins over both the API key default and the file URL
+        let networks = default_networks(NetworkSet::Mainnet, Some("key"), &rpc_override);
+        assert_eq!(networks.len(), DEFAULT_NETWORKS.len());
+        assert_eq!(networks.iter().find(|n| n.chain_id == 8453).unwrap().rpc_url, "https://base.quiknode.pro/abc");
+
+        let contents = "[[networks]]\nchain_id = 8453\nrpc_url = \"http://10.0.0.5:8545\"";
+        let networks = networks_from_toml(contents, None, &rpc_override).unwrap();
+        assert_eq!(networks[0].rpc_url, "https://base.quiknode.pro/abc");
+    }
+
+    #[test]
+    fn test_testnet_presets() {
+        let networks = default_networks(NetworkSet::Testnet, Some("key"), &no_override);
+        assert_eq!(networks.len(), TESTNET_NETWORKS.len());
+        let sepolia = networks.iter().find(|n| n.chain_id == 11155111).unwrap();
+        assert_eq!(sepolia.rpc_url, "https://eth-sepolia.g.alchemy.com/v2/key");
+        assert_eq!(sepolia.poller.finality, Some(Finality::Safe));
+        assert_eq!(sepolia.wrapped_native.as_deref(), Some("0xfff9976782d46cc05630d1f6ebab18b2324d6b14"));
+        assert!(!networks.iter().any(|n| n.chain_id == 1));
+
+        // A networks file can name testnets without picking the set
+        let networks = networks_from_toml("[[networks]]\nchain_id = 84532", Some("key"), &no_override).unwrap();
+        assert_eq!(networks[0].name, "Base Sepolia");
+        assert_eq!(networks[0].rpc_url, "https://base-sepolia.g.alchemy.com/v2/key");
+    }
+
+    #[test]
+    fn test_networks_from_toml_defaults_and_overrides() {
+        let contents = r#"
+            [[networks]]
+            chain_id = 1
+
+            [[networks]]
+            chain_id = 8453
+            name = "Base (self-hosted)"
+            rpc_url = "http://10.0.0.5:8545"
+
+            [[networks]]
+            chain_id = 31337
+            name = "Local Anvil"
+            rpc_url = "http://127.0.0.1:8545"
+            wrapped_native = "0x5FbDB2315678afecb367f032d93F642f64180aa3"
+            escrow_factory = "0xE7F1725E7734CE288F8367E1BB143E90BB3F0512"
+            multicall3 = ""
+
+            [[networks]]
+            chain_id = 324
+            rpc_url = "https://mainnet.era.zksync.io"
+        "#;
+
+        let networks = networks_from_toml(contents, Some("key"), &no_override).unwrap();
+        assert_eq!(networks.len(), 4);
+
+        assert_eq!(networks[0].name, "Ethereum");
+        assert_eq!(networks[0].rpc_url, "https://eth-mainnet.g.alchemy.com/v2/key");
+        assert_eq!(networks[1].name, "Base (self-hosted)");
+        assert_eq!(networks[1].rpc_url, "http://10.0.0.5:8545");
+        assert_eq!(networks[2].chain_id, 31337);
+
+        assert_eq!(networks[0].wrapped_native.as_deref(), Some("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"));
+
+        assert_eq!(networks[1].wrapped_native.as_deref(), Some("0x4200000000000000000000000000000000000006"));
+        assert_eq!(networks[2].wrapped_native.as_deref(), Some("0x5fbdb2315678afecb367f032d93f642f64180aa3"));
+
+        // Contract overrides replace only the given address
+        assert_eq!(networks[0].contracts, default_contracts(1));
+        assert_eq!(networks[0].contracts.aggregation_router, AGGREGATION_ROUTER_V6);
+        assert_eq!(networks[2].contracts.escrow_factory, "0xe7f1725e7734ce288f8367e1bb143e90bb3f0512");
+        assert_eq!(networks[2].contracts.aggregation_router, AGGREGATION_ROUTER_V6);
+        assert_eq!(networks[3].contracts.aggregation_router, AGGREGATION_ROUTER_ZKSYNC);
+
+        // Multicall3 defaults per chain; "" disables it
+        assert_eq!(networks[1].multicall3.as_deref(), Some(MULTICALL3));
+        assert_eq!(networks[2].multicall3, None);
+        assert_eq!(networks[3].multicall3.as_deref(), Some(MULTICALL3_ZKSYNC));
+
+        // Built-in finality defaults; rollups and custom chains count confirmations
+        assert_eq!(networks[0].poller.finality, Some(Finality::Safe));
+        assert_eq!(networks[1].poller.finality, None);
+        let contents = "[[networks]]\nchain_id = 137\n[[networks]]\nchain_id = 1\nfinality = \"confirmations\"";
+        let networks = networks_from_toml(contents, Some("key"), &no_override).unwrap();
+        assert_eq!(networks[0].poller.finality, Some(Finality::Finalized));
+        assert_eq!(networks[1].poller.finality, Some(Finality::Confirmations));
+    }
+
+    #[test]
+    fn test_networks_http_options() {
+        let contents = r#"
+            [[networks]]
+            chain_id = 1
+
+            [[networks]]
+            chain_id = 8453
+            rpc_url = "http://10.0.0.5:8545"
+            confirmation_blocks = 3
+
+            [networks.http]
+            http2_prior_knowledge = true
+            pool_max_idle_per_host = 16
+            tcp_keepalive_secs = 30
+        "#;
+
+        let networks = networks_from_toml(contents, Some("key"), &no_override).unwrap();
+        assert_eq!(networks[0].http, HttpOptions::default());
+        assert_eq!(
+            networks[1].http,
+            HttpOptions {
+                http2_prior_knowledge: true,
+                pool_max_idle_per_host: 16,
+                tcp_keepalive_secs: Some(30),
+                ..HttpOptions::default()
+            }
+        );
+        assert_eq!(networks[1].poller.confirmation_blocks, Some(3));
+
+        let zero = "[[networks]]\nchain_id = 1\n[networks.http]\ntimeout_secs = 0";
+        assert!(networks_from_toml(zero, Some("key"), &no_override).is_err());
+        let unknown = "[[networks]]\nchain_id = 1\n[networks.http]\nhttp2 = true";
+        assert!(networks_from_toml(unknown, Some("key"), &no_override).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_precedence() {
+        let env = |key: &str| match key {
+            "RPC_RATE_LIMIT" => Some("10".to_string()),
+            "RPC_RATE_LIMIT_1" => Some("2.5".to_string()),
+            "RPC_RATE_LIMIT_10" => Some("0".to_string()),
+            _ => None,
+        };
+
+        assert_eq!(rate_limit_for(1, Some(5.0), &env), Ok(Some(2.5)));
+        assert_eq!(rate_limit_for(8453, Some(5.0), &env), Ok(Some(5.0)));
+        assert_eq!(rate_limit_for(8453, None, &env), Ok(Some(10.0)));
+        assert_eq!(rate_limit_for(8453, None, &|_| None), Ok(None));
+        assert!(rate_limit_for(10, None, &env).is_err());
+        assert!(rate_limit_for(8453, Some(-1.0), &|_| None).is_err());
+    }
+
+    #[test]
+    fn test_parse_topics() {
+        let topic = "0x86AC35F38CD2D17935B5BB6295C74CADB683BCFBA935852C32096A81DF8998EF";
+        assert_eq!(
+            parse_topics(&format!(" {}, 0x1234,,", topic)),
+            vec![topic.to_lowercase()]
+        );
+        assert!(parse_topics("").is_empty());
+    }
+
+    #[test]
+    fn test_watchers_attached_to_networks() {
+        let contents = r#"
+            [[networks]]
+            chain_id = 1
+
+            [[networks]]
+            chain_id = 8453
+
+            [[watchers]]
+            chain_id = 8453
+            label = "uniswap_v3_swaps"
+            topics = ["0xC42079F94A6350D7E6235F29174924F928CC2AC818EB64FED8004E115FBCCA67"]
+
+            [[watchers]]
+            chain_id = 8453
+            label = "my-contract"
+            address = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"
+        "#;
+
+        let networks = networks_from_toml(contents, Some("key"), &no_override).unwrap();
+        assert!(networks[0].watchers.is_empty());
+
+        let watchers = &networks[1].watchers;
+        assert_eq!(watchers.len(), 2);
+        assert_eq!(watchers[0].address, None);
+        assert_eq!(
+            watchers[0].topics,
+            vec!["0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67"]
+        );
+        assert_eq!(watchers[1].label, "my-contract");
+        assert_eq!(watchers[1].address.as_deref(), Some("0x833589fcd6edb6e08f4c7c32d4f71b54bda02913"));
+        assert!(watchers[1].topics.is_empty());
+    }
+
+    #[test]
+    fn test_watcher_errors() {
+        let network = "[[networks]]\nchain_id = 1\n";
+        let parse = |watchers: &str| networks_from_toml(&format!("{}{}", network, watchers), Some("key"), &no_override);
+
+        // Unknown chain
+        assert!(parse("[[watchers]]\nchain_id = 10\nlabel = \"a\"\naddress = \"0x833589fcd6edb6e08f4c7c32d4f71b54bda02913\"").is_err());
+        // Neither address nor topics
+        assert!(parse("[[watchers]]\nchain_id = 1\nlabel = \"a\"").is_err());
+        // Bad label and bad address
+        assert!(parse("[[watchers]]\nchain_id = 1\nlabel = \"a b\"\naddress = \"0x833589fcd6edb6e08f4c7c32d4f71b54bda02913\"").is_err());
+        assert!(parse("[[watchers]]\nchain_id = 1\nlabel = \"a\"\naddress = \"0x1234\"").is_err());
+        // Duplicate label
+        assert!(parse(
+            "[[watchers]]\nchain_id = 1\nlabel = \"a\"\naddress = \"0x833589fcd6edb6e08f4c7c32d4f71b54bda02913\"\n\
+             [[watchers]]\nchain_id = 1\nlabel = \"a\"\naddress = \"0x833589fcd6edb6e08f4c7c32d4f71b54bda02913\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_networks_from_toml_errors() {
+        // Unknown chain without an RPC URL
+        assert!(networks_from_toml("[[networks]]\nchain_id = 999", Some("key"), &no_override).is_err());
+        // Built-in chain without URL or API key
+        assert!(networks_from_toml("[[networks]]\nchain_id = 1", None, &no_override).is_err());
+        // Duplicate chain
+        assert!(networks_from_toml(
+            "[[networks]]\nchain_id = 1\n[[networks]]\nchain_id = 1",
+            Some("key"),
+            &no_override
+        )
+        .is_err());
+        // Malformed wrapped native token or contract address
+        assert!(networks_from_toml(
+            "[[networks]]\nchain_id = 1\nwrapped_native = \"weth\"",
+            Some("key"),
+            &no_override
+        )
+        .is_err());
+        assert!(networks_from_toml(
+            "[[networks]]\nchain_id = 1\naggregation_router = \"0x1111\"",
+            Some("key"),
+            &no_override
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_table_ttl() {
+        assert_eq!(table_ttl("C2F_TTL_SECS", None, 600), Some(600));
+        assert_eq!(table_ttl("C2F_TTL_SECS", Some("86400"), 600), Some(86_400));
+        assert_eq!(table_ttl("C2F_TTL_SECS", Some("Never"), 600), None);
+        assert_eq!(table_ttl("C2F_TTL_SECS", Some("soon"), 600), Some(600));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use tokio::sync::mpsc;
+
+/// Command for a chain's poller supervisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainCommand {
+    /// Stop polling until started again
+    Stop,
+    /// Start a stopped poller, or skip the backoff of a failed one
+    Start,
+    /// Rebuild and restart the poller now
+    Restart,
+    /// Rebuild the poller from reloaded configuration if it is running;
+    /// sent on config reload rather than through the admin API
+    Reload,
+}
+
+impl ChainCommand {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "stop" => Some(Self::Stop),
+            "start" => Some(Self::Start),
+            "restart" => Some(Self::Restart),
+            _ => None,
+        }
+    }
+}
+
+/// Command channels to each chain's poller supervisor, used by the admin API
+#[derive(Default)]
+pub struct ChainControl {
+    chains: RwLock<BTreeMap<u32, mpsc::UnboundedSender<ChainCommand>>>,
+}
+
+impl ChainControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chain; its supervisor receives commands on the returned channel
+    pub fn register(&self, chain_id: u32) -> mpsc::UnboundedReceiver<ChainCommand> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.chains.write().unwrap().insert(chain_id, tx);
+        rx
+    }
+
+    /// Drop a chain that is no longer polled
+    pub fn unregister(&self, chain_id: u32) {
+        self.chains.write().unwrap().remove(&chain_id);
+    }
+
+    /// Queue `command` for the chain's supervisor; false if no supervisor runs the chain
+    pub fn send(&self, chain_id: u32, command: ChainCommand) -> bool {
+        self.chains
+            .read()
+            .unwrap()
+            .get(&chain_id)
+            .is_some_and(|tx| tx.send(command).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_to_registered_chain() {
+        let control = ChainControl::new();
+        assert!(!control.send(8453, ChainCommand::Stop));
+
+        let mut rx = control.register(8453);
+        assert!(control.send(8453, ChainCommand::Stop));
+        assert!(control.send(8453, ChainCommand::parse("Restart").unwrap()));
+        assert_eq!(rx.try_recv().unwrap(), ChainCommand::Stop);
+        assert_eq!(rx.try_recv().unwrap(), ChainCommand::Restart);
+
+        drop(rx);
+        assert!(!control.send(8453, ChainCommand::Start));
+        assert_eq!(ChainCommand::parse("pause"), None);
+    }
+}
@@ -1,8 +1,28 @@
-use crate::types::{Crypto2FiatEvent, DstEscrowCreatedData, FusionPlusSwap, FusionSwap, Transfer};
-use deadpool_postgres::{Config, Pool, Runtime, PoolError};
+use crate::archive::{Archive, ArchivedRow};
+use crate::export::ExportTable;
+use crate::fusion::{compute_hashlock_from_secret, secret_matches_hashlock, withdrawal_phase, MerkleFill};
+use crate::types::{
+    AddressActivity, Approval, BalanceDelta, Crypto2FiatEvent, Cursor, DatabaseSize, DstEscrowCreatedData, FusionPlusEvent, FusionPlusFill, FusionPlusFilter,
+    Delegation, FusionOrder, FusionPlusSwap, FusionSwap, InternalTransfer, Log, OrderFill, RawEvent, ResolverStats, SearchHit, TokenActivity, TokenInfo, TableSize, TokenStats, TransactionInfo, Transfer, WatchedAddress,
+    NATIVE_TOKEN,
+};
+use alloy_primitives::U256;
+use deadpool_postgres::{Config, GenericClient, Pool, Runtime, PoolError, Transaction};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::future::try_join_all;
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::io::Write;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
-use tokio_postgres::{NoTls, Row};
+use tokio_postgres::error::SqlState;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{IsolationLevel, NoTls, Row};
+use tracing::instrument;
 
 #[derive(Error, Debug)]
 pub enum DbError {
@@ -12,17 +32,216 @@ pub enum DbError {
     Pool(#[from] PoolError),
     #[error("Configuration error: {0}")]
     Config(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
+/// Options controlling schema layout and maintenance
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseConfig {
+    /// Partition the transfers table by day (block_timestamp) so expired data
+    /// is purged by dropping whole partitions instead of large DELETE scans.
+    /// Only applies when the transfers table is created fresh.
+    pub daily_rotation: bool,
+    /// With daily rotation, each new day's partition is split by chain, giving
+    /// these chains their own table (and indexes) per day and the remaining
+    /// chains a shared one. Queries through `transfers` span them all.
+    pub sharded_chains: Vec<u32>,
+    /// Export rows to gzipped NDJSON before TTL cleanup deletes them
+    pub archive: Option<Archive>,
+    /// Create prefix (varchar_pattern_ops) indexes on the transfers hash and
+    /// address columns, so `search` doesn't scan the table. Costs write
+    /// throughput; the smaller swap tables always have them.
+    pub search_indexes: bool,
+    /// When the database's live size exceeds this many bytes, cleanup also
+    /// deletes the oldest rows of `SIZE_CAP_TABLES` regardless of TTL
+    pub max_size_bytes: Option<u64>,
+    /// Rows removed per DELETE statement by cleanup; None deletes all
+    /// expired rows of a table in one statement
+    pub delete_batch_rows: Option<usize>,
+}
+
+/// How long rows are kept per table, in seconds; None keeps them forever
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub transfers: Option<u64>,
+    pub approvals: Option<u64>,
+    /// Fusion+ swaps expire this long after both legs are withdrawn or cancelled
+    pub fusion_plus: Option<u64>,
+    /// Hard cap on Fusion+ swap age, complete or not
+    pub fusion_plus_max_age: Option<u64>,
+    pub fusion: Option<u64>,
+    pub crypto2fiat: Option<u64>,
+    pub raw_events: Option<u64>,
+    pub delegations: Option<u64>,
+    pub transactions: Option<u64>,
+    pub raw_logs: Option<u64>,
+    pub internal_transfers: Option<u64>,
+}
+
+/// Transfers read one row at a time, see `Database::stream_transfers_by_from`
+pub type TransferStream = BoxStream<'static, Result<Transfer, DbError>>;
+
+/// (table, chain column, searched column) of the hashes and addresses `Database::search` looks in
+const SEARCH_COLUMNS: [(&str, &str, &str); 14] = [
+    ("transfers", "chain_id", "tx_hash"),
+    ("transfers", "chain_id", "from_addr"),
+    ("transfers", "chain_id", "to_addr"),
+    ("transfers", "chain_id", "token"),
+    ("fusion_plus_swaps", "src_chain_id", "order_hash"),
+    ("fusion_plus_swaps", "src_chain_id", "hashlock"),
+    ("fusion_plus_swaps", "src_chain_id", "src_tx_hash"),
+    ("fusion_plus_swaps", "src_chain_id", "src_maker"),
+    ("fusion_plus_swaps", "src_chain_id", "src_escrow_address"),
+    ("fusion_plus_swaps", "dst_chain_id", "dst_tx_hash"),
+    ("fusion_plus_swaps", "dst_chain_id", "dst_escrow_address"),
+    ("crypto2fiat_events", "chain_id", "order_id"),
+    ("crypto2fiat_events", "chain_id", "tx_hash"),
+    ("crypto2fiat_events", "chain_id", "recipient"),
+];
+
+/// Shortest search prefix accepted, in hex digits after `0x`
+pub const MIN_SEARCH_DIGITS: usize = 4;
+
+/// Most addresses accepted by `Database::get_transfers_by_addresses`
+pub const MAX_BATCH_ADDRESSES: usize = 100;
+
+/// Rows handed to the archive writer at a time while exporting expired rows
+const ARCHIVE_BATCH_ROWS: usize = 10_000;
+
+/// High-volume tables trimmed, oldest rows first, when the size cap is exceeded
+const SIZE_CAP_TABLES: [&str; 7] = [
+    "transfers",
+    "approvals",
+    "raw_logs",
+    "raw_events",
+    "delegations",
+    "transactions",
+    "internal_transfers",
+];
+
+/// Extra share of rows removed beyond the overshoot, so the cap isn't hit again next cycle
+const SIZE_CAP_HEADROOM: f64 = 0.1;
+
+/// Session-scoped SQL function converting a `0x` hex quantity to a decimal
+/// string, for migrations backfilling decimal columns from hex ones
+const HEX_TO_DECIMAL_FN: &str = "CREATE OR REPLACE FUNCTION pg_temp.hex_to_decimal(hex TEXT) RETURNS TEXT AS $$
+     DECLARE
+         digits TEXT := lower(substr(hex, 3));
+         result NUMERIC := 0;
+     BEGIN
+         IF hex !~* '^0x[0-9a-f]{1,64}$' THEN
+             RETURN NULL;
+         END IF;
+         FOR i IN 1..length(digits) LOOP
+             result := result * 16 + position(substr(digits, i, 1) IN '0123456789abcdef') - 1;
+         END LOOP;
+         RETURN result::TEXT;
+     END
+     $$ LANGUAGE plpgsql IMMUTABLE;";
+
+/// Tables copied by `Database::backup`, with the columns naming a row's chain
+const BACKUP_TABLES: [(&str, &[&str]); 20] = [
+    ("checkpoints", &["chain_id"]),
+    ("processed_ranges", &["chain_id"]),
+    ("backfill_progress", &["chain_id"]),
+    ("block_hashes", &["chain_id"]),
+    ("tokens", &["chain_id"]),
+    ("token_stats", &["chain_id"]),
+    ("transfers", &["chain_id"]),
+    ("approvals", &["chain_id"]),
+    ("raw_events", &["chain_id"]),
+    ("delegations", &["chain_id"]),
+    ("transactions", &["chain_id"]),
+    ("raw_logs", &["chain_id"]),
+    ("internal_transfers", &["chain_id"]),
+    ("fusion_swaps", &["chain_id"]),
+    ("order_fills", &["chain_id"]),
+    ("crypto2fiat_events", &["chain_id"]),
+    ("fusion_plus_swaps", &["src_chain_id", "dst_chain_id"]),
+    ("fusion_plus_events", &["chain_id"]),
+    ("fusion_plus_fills", &["src_chain_id", "dst_chain_id"]),
+    ("watchlist", &[]),
+];
+
+/// Uncompressed bytes buffered before a backup file is written
+const BACKUP_WRITE_BYTES: usize = 1 << 20;
+
+/// Tables cleanup deletes from, vacuumed by `Database::vacuum`
+const VACUUM_TABLES: [&str; 12] = [
+    "transfers",
+    "approvals",
+    "fusion_plus_swaps",
+    "fusion_plus_events",
+    "fusion_plus_fills",
+    "fusion_swaps",
+    "crypto2fiat_events",
+    "raw_events",
+    "delegations",
+    "transactions",
+    "raw_logs",
+    "internal_transfers",
+];
+
 /// PostgreSQL Database with connection pool
 /// All chains share a single database with chain_id column
 pub struct Database {
     pool: Pool,
+    config: DatabaseConfig,
+    /// Whether the existing transfers table is partitioned by day
+    transfers_partitioned: bool,
 }
 
 impl Database {
     /// Create a new database connection pool from DATABASE_URL
     pub async fn new(database_url: &str) -> Result<Self, DbError> {
+        Self::with_config(database_url, DatabaseConfig::default()).await
+    }
+
+    /// Create a new database connection pool with custom schema options
+    pub async fn with_config(database_url: &str, db_config: DatabaseConfig) -> Result<Self, DbError> {
+        let pool = Self::create_pool(database_url, false)?;
+
+        let mut db = Self {
+            pool,
+            config: db_config,
+            transfers_partitioned: false,
+        };
+
+        // Auto-create schema on startup
+        db.create_schema().await?;
+
+        Ok(db)
+    }
+
+    /// Open a connection pool that only reads, for a query service running
+    /// alongside the indexer
+    ///
+    /// Every session defaults to read-only transactions, so writes fail in
+    /// PostgreSQL. The schema is neither created nor migrated; the indexer
+    /// must have created it first.
+    pub async fn open_read_only(database_url: &str) -> Result<Self, DbError> {
+        let pool = Self::create_pool(database_url, true)?;
+        let client = pool.get().await?;
+
+        let Some(transfers_kind) = Self::transfers_table_kind(&client).await? else {
+            return Err(DbError::Config(
+                "transfers table not found; start the indexer once to create the schema".to_string(),
+            ));
+        };
+        drop(client);
+
+        Ok(Self {
+            pool,
+            config: DatabaseConfig::default(),
+            transfers_partitioned: transfers_kind == b'p' as i8,
+        })
+    }
+
+    /// Build the connection pool for DATABASE_URL
+    fn create_pool(database_url: &str, read_only: bool) -> Result<Pool, DbError> {
         // Parse the DATABASE_URL
         let config = database_url
             .parse::<tokio_postgres::Config>()
@@ -46,22 +265,73 @@ impl Database {
             ..Default::default()
         });
 
-        let pool = cfg
-            .create_pool(Some(Runtime::Tokio1), NoTls)
-            .map_err(|e| DbError::Config(e.to_string()))?;
-
-        let db = Self { pool };
+        if read_only {
+            let options = config.get_options().unwrap_or_default();
+            cfg.options = Some(format!("{} -c default_transaction_read_only=on", options).trim().to_string());
+        }
 
-        // Auto-create schema on startup
-        db.create_schema().await?;
+        cfg.create_pool(Some(Runtime::Tokio1), NoTls)
+            .map_err(|e| DbError::Config(e.to_string()))
+    }
 
-        Ok(db)
+    /// relkind of the transfers table ('r' plain, 'p' partitioned), None if missing
+    async fn transfers_table_kind(client: &impl GenericClient) -> Result<Option<i8>, DbError> {
+        Ok(client.query_opt(
+            "SELECT relkind::\"char\" FROM pg_class WHERE relname = 'transfers' AND relkind IN ('r', 'p')",
+            &[],
+        ).await?.map(|r| r.get(0)))
     }
 
     /// Create all tables and indexes if they don't exist
-    async fn create_schema(&self) -> Result<(), DbError> {
+    async fn create_schema(&mut self) -> Result<(), DbError> {
         let client = self.pool.get().await?;
 
+        // Partitioning can only be chosen when the table is created
+        let transfers_kind = Self::transfers_table_kind(&client).await?;
+
+        self.transfers_partitioned = match transfers_kind {
+            Some(kind) => kind == b'p' as i8,
+            None => self.config.daily_rotation,
+        };
+
+        if self.config.daily_rotation && !self.transfers_partitioned {
+            tracing::warn!(
+                "DAILY_ROTATION requested but transfers table already exists unpartitioned; using DELETE-based cleanup"
+            );
+        }
+
+        if self.transfers_partitioned {
+            // Daily-partitioned transfers table. Unique keys must include the
+            // partition column; a log's block_timestamp never changes, so
+            // deduplication on (chain_id, tx_hash, log_index) is preserved.
+            client.execute(
+                "CREATE TABLE IF NOT EXISTS transfers (
+                    id BIGSERIAL,
+                    chain_id INTEGER NOT NULL,
+                    tx_hash VARCHAR(66) NOT NULL,
+                    log_index INTEGER NOT NULL,
+                    token VARCHAR(42) NOT NULL,
+                    from_addr VARCHAR(42) NOT NULL,
+                    to_addr VARCHAR(42) NOT NULL,
+                    value VARCHAR(78) NOT NULL,
+                    value_decimal VARCHAR(78),
+                    block_number BIGINT NOT NULL,
+                    block_timestamp BIGINT NOT NULL,
+                    swap_type VARCHAR(20),
+                    created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                    PRIMARY KEY (id, block_timestamp),
+                    UNIQUE(chain_id, tx_hash, log_index, block_timestamp)
+                ) PARTITION BY RANGE (block_timestamp)",
+                &[],
+            ).await?;
+
+            // Catches rows written before their day's partition is created
+            client.execute(
+                "CREATE TABLE IF NOT EXISTS transfers_default PARTITION OF transfers DEFAULT",
+                &[],
+            ).await?;
+        }
+
         // Transfers table (chain-specific data with chain_id column)
         client.execute(
             "CREATE TABLE IF NOT EXISTS transfers (
@@ -73,6 +343,7 @@ impl Database {
                 from_addr VARCHAR(42) NOT NULL,
                 to_addr VARCHAR(42) NOT NULL,
                 value VARCHAR(78) NOT NULL,
+                value_decimal VARCHAR(78),
                 block_number BIGINT NOT NULL,
                 block_timestamp BIGINT NOT NULL,
                 swap_type VARCHAR(20),
@@ -82,6 +353,178 @@ impl Database {
             &[],
         ).await?;
 
+        // value_decimal was added after the table; upgrade and backfill existing databases
+        let has_value_decimal = client.query_opt(
+            "SELECT 1 FROM information_schema.columns WHERE table_name = 'transfers' AND column_name = 'value_decimal'",
+            &[],
+        ).await?.is_some();
+
+        if !has_value_decimal {
+            client.batch_execute("ALTER TABLE transfers ADD COLUMN IF NOT EXISTS value_decimal VARCHAR(78);").await?;
+            client.batch_execute(HEX_TO_DECIMAL_FN).await?;
+
+            let backfilled = client.execute(
+                "UPDATE transfers SET value_decimal = pg_temp.hex_to_decimal(value) WHERE value <> ''",
+                &[],
+            ).await?;
+            tracing::info!("Backfilled value_decimal for {} existing transfers", backfilled);
+        }
+
+        // Receipt status of the transaction, set when TX_STATUS is enabled
+        client.batch_execute("ALTER TABLE transfers ADD COLUMN IF NOT EXISTS tx_status SMALLINT;").await?;
+
+        // ERC20 approvals table
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS approvals (
+                id BIGSERIAL PRIMARY KEY,
+                chain_id INTEGER NOT NULL,
+                tx_hash VARCHAR(66) NOT NULL,
+                log_index INTEGER NOT NULL,
+                token VARCHAR(42) NOT NULL,
+                owner VARCHAR(42) NOT NULL,
+                spender VARCHAR(42) NOT NULL,
+                amount VARCHAR(78) NOT NULL,
+                is_unlimited BOOLEAN NOT NULL DEFAULT FALSE,
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                UNIQUE(chain_id, tx_hash, log_index)
+            )",
+            &[],
+        ).await?;
+
+        // Undecoded logs captured by user-defined watchers
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS raw_events (
+                id BIGSERIAL PRIMARY KEY,
+                chain_id INTEGER NOT NULL,
+                watcher VARCHAR(64) NOT NULL,
+                address VARCHAR(42) NOT NULL,
+                tx_hash VARCHAR(66) NOT NULL,
+                log_index INTEGER NOT NULL,
+                topics TEXT[] NOT NULL,
+                data TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                UNIQUE(chain_id, watcher, tx_hash, log_index)
+            )",
+            &[],
+        ).await?;
+
+        // Logs emitted by EIP-7702 delegated accounts (DELEGATION_TOPICS)
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS delegations (
+                id BIGSERIAL PRIMARY KEY,
+                chain_id INTEGER NOT NULL,
+                authority VARCHAR(42) NOT NULL,
+                delegate VARCHAR(42),
+                tx_hash VARCHAR(66) NOT NULL,
+                log_index INTEGER NOT NULL,
+                topics TEXT[] NOT NULL,
+                data TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                UNIQUE(chain_id, tx_hash, log_index)
+            )",
+            &[],
+        ).await?;
+
+        // Sender, receiver and gas of transactions with indexed events (FETCH_TRANSACTIONS)
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS transactions (
+                id BIGSERIAL PRIMARY KEY,
+                chain_id INTEGER NOT NULL,
+                tx_hash VARCHAR(66) NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                from_addr VARCHAR(42) NOT NULL,
+                to_addr VARCHAR(42),
+                gas_used BIGINT NOT NULL,
+                effective_gas_price TEXT NOT NULL,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                UNIQUE(chain_id, tx_hash)
+            )",
+            &[],
+        ).await?;
+
+        // Value moved by contract calls to or from watched addresses (INTERNAL_TRANSFERS)
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS internal_transfers (
+                id BIGSERIAL PRIMARY KEY,
+                chain_id INTEGER NOT NULL,
+                tx_hash VARCHAR(66) NOT NULL,
+                trace_address TEXT NOT NULL,
+                call_type VARCHAR(16) NOT NULL,
+                from_addr VARCHAR(42) NOT NULL,
+                to_addr VARCHAR(42) NOT NULL,
+                value VARCHAR(78) NOT NULL,
+                value_decimal VARCHAR(78),
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                UNIQUE(chain_id, tx_hash, trace_address)
+            )",
+            &[],
+        ).await?;
+
+        // Hourly transfer count and volume per token, rolled up from transfers
+        // (TOKEN_STATS_INTERVAL_SECS) and kept after the transfers expire
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS token_stats (
+                chain_id INTEGER NOT NULL,
+                token VARCHAR(42) NOT NULL,
+                hour BIGINT NOT NULL,
+                transfer_count BIGINT NOT NULL,
+                volume NUMERIC NOT NULL,
+                updated_at BIGINT NOT NULL,
+                PRIMARY KEY (chain_id, token, hour)
+            )",
+            &[],
+        ).await?;
+
+        // Verbatim archive of fetched logs (RAW_LOG_ARCHIVE), replayable after decoder fixes
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS raw_logs (
+                chain_id INTEGER NOT NULL,
+                block_number BIGINT NOT NULL,
+                log_index INTEGER NOT NULL,
+                tx_hash VARCHAR(66) NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                log JSONB NOT NULL,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                PRIMARY KEY (chain_id, block_number, log_index)
+            )",
+            &[],
+        ).await?;
+
+        // ERC-20 metadata, fetched once per token via eth_call
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS tokens (
+                chain_id INTEGER NOT NULL,
+                address VARCHAR(42) NOT NULL,
+                symbol TEXT,
+                name TEXT,
+                decimals SMALLINT,
+                fetched_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                PRIMARY KEY (chain_id, address)
+            )",
+            &[],
+        ).await?;
+
+        // Tokens missing some metadata ('partial', 'empty') are fetched again
+        // once stale; rows cached before the column existed are classified here
+        client.batch_execute(
+            "ALTER TABLE tokens ADD COLUMN IF NOT EXISTS metadata_status VARCHAR(10);
+             UPDATE tokens SET metadata_status = CASE
+                 WHEN symbol IS NOT NULL AND name IS NOT NULL AND decimals IS NOT NULL THEN 'complete'
+                 WHEN symbol IS NULL AND name IS NULL AND decimals IS NULL THEN 'empty'
+                 ELSE 'partial'
+             END
+             WHERE metadata_status IS NULL;",
+        ).await?;
+
         // Checkpoints table (one row per chain)
         client.execute(
             "CREATE TABLE IF NOT EXISTS checkpoints (
@@ -92,6 +535,51 @@ impl Database {
             &[],
         ).await?;
 
+        // Resumable historical backfill ranges
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS backfill_progress (
+                chain_id INTEGER NOT NULL,
+                from_block BIGINT NOT NULL,
+                to_block BIGINT NOT NULL,
+                next_block BIGINT NOT NULL,
+                updated_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                PRIMARY KEY (chain_id, from_block, to_block)
+            )",
+            &[],
+        ).await?;
+
+        // Recent block hashes per chain, used to detect reorgs
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS block_hashes (
+                chain_id INTEGER NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_hash VARCHAR(66) NOT NULL,
+                PRIMARY KEY (chain_id, block_number)
+            )",
+            &[],
+        ).await?;
+
+        // Block ranges indexed per chain, merged as they grow; holes are gaps to heal
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS processed_ranges (
+                chain_id INTEGER NOT NULL,
+                from_block BIGINT NOT NULL,
+                to_block BIGINT NOT NULL,
+                PRIMARY KEY (chain_id, from_block)
+            )",
+            &[],
+        ).await?;
+
+        // Addresses whose transfers are kept in watchlist mode
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS watchlist (
+                address VARCHAR(42) PRIMARY KEY,
+                label TEXT,
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT
+            )",
+            &[],
+        ).await?;
+
         // Fusion+ swaps table
         client.execute(
             "CREATE TABLE IF NOT EXISTS fusion_plus_swaps (
@@ -99,6 +587,7 @@ impl Database {
                 order_hash VARCHAR(66) NOT NULL UNIQUE,
                 hashlock VARCHAR(66) NOT NULL,
                 secret VARCHAR(66),
+                secret_valid BOOLEAN,
                 src_chain_id INTEGER NOT NULL,
                 src_tx_hash VARCHAR(66) NOT NULL,
                 src_block_number BIGINT NOT NULL,
@@ -125,12 +614,99 @@ impl Database {
                 dst_safety_deposit VARCHAR(78) NOT NULL,
                 dst_timelocks VARCHAR(130),
                 dst_status VARCHAR(20) NOT NULL DEFAULT 'pending',
+                secret_revealed_chain_id INTEGER,
+                secret_revealed_at BIGINT,
+                src_withdrawer VARCHAR(42),
+                src_withdrawal_phase VARCHAR(10),
+                dst_withdrawer VARCHAR(42),
+                dst_withdrawal_phase VARCHAR(10),
+                src_cancelled_tx_hash VARCHAR(66),
+                src_cancelled_at BIGINT,
+                dst_cancelled_tx_hash VARCHAR(66),
+                dst_cancelled_at BIGINT,
                 created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
                 updated_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT
             )",
             &[],
         ).await?;
 
+        // Secret validation and reveal, withdrawal and cancellation tracking were added
+        // after the table; upgrade existing databases
+        client.batch_execute(
+            "ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS secret_revealed_chain_id INTEGER;
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS secret_revealed_at BIGINT;
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS secret_valid BOOLEAN;
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS src_withdrawer VARCHAR(42);
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS src_withdrawal_phase VARCHAR(10);
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS dst_withdrawer VARCHAR(42);
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS dst_withdrawal_phase VARCHAR(10);
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS src_cancelled_tx_hash VARCHAR(66);
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS src_cancelled_at BIGINT;
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS dst_cancelled_tx_hash VARCHAR(66);
+             ALTER TABLE fusion_plus_swaps ADD COLUMN IF NOT EXISTS dst_cancelled_at BIGINT;",
+        ).await?;
+
+        // Every escrow pair of an order, so multiple-fill orders keep each fill's
+        // hashlock and secret (fusion_plus_swaps holds one row per order).
+        // fill_index is the secret's Merkle leaf index, from the fill's proof
+        let has_fill_proofs = client.query_opt(
+            "SELECT 1 FROM information_schema.columns WHERE table_name = 'fusion_plus_fills' AND column_name = 'hashlock_root'",
+            &[],
+        ).await?.is_some();
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS fusion_plus_fills (
+                id BIGSERIAL PRIMARY KEY,
+                order_hash VARCHAR(66) NOT NULL,
+                fill_index BIGINT,
+                hashlock_root VARCHAR(66),
+                proof_valid BOOLEAN,
+                hashlock VARCHAR(66) NOT NULL,
+                secret VARCHAR(66),
+                src_chain_id INTEGER NOT NULL,
+                src_tx_hash VARCHAR(66) NOT NULL,
+                src_block_number BIGINT NOT NULL,
+                src_escrow_address VARCHAR(42),
+                src_amount VARCHAR(78) NOT NULL,
+                src_status VARCHAR(20) NOT NULL DEFAULT 'created',
+                dst_chain_id INTEGER NOT NULL,
+                dst_tx_hash VARCHAR(66),
+                dst_block_number BIGINT,
+                dst_escrow_address VARCHAR(42),
+                dst_amount VARCHAR(78) NOT NULL,
+                dst_status VARCHAR(20) NOT NULL DEFAULT 'pending',
+                UNIQUE(order_hash, hashlock)
+            )",
+            &[],
+        ).await?;
+        if !has_fill_proofs {
+            // fill_index used to count fills in arrival order, which isn't the leaf index
+            client.batch_execute(
+                "ALTER TABLE fusion_plus_fills ADD COLUMN IF NOT EXISTS hashlock_root VARCHAR(66);
+                 ALTER TABLE fusion_plus_fills ADD COLUMN IF NOT EXISTS proof_valid BOOLEAN;
+                 ALTER TABLE fusion_plus_fills ALTER COLUMN fill_index DROP NOT NULL;
+                 ALTER TABLE fusion_plus_fills ALTER COLUMN fill_index TYPE BIGINT;
+                 UPDATE fusion_plus_fills SET fill_index = NULL;",
+            ).await?;
+        }
+
+        // Append-only Fusion+ lifecycle log; fusion_plus_swaps keeps the current state
+        client.execute(
+            "CREATE TABLE IF NOT EXISTS fusion_plus_events (
+                id BIGSERIAL PRIMARY KEY,
+                order_hash VARCHAR(66) NOT NULL,
+                event_type VARCHAR(20) NOT NULL,
+                chain_id INTEGER NOT NULL,
+                tx_hash VARCHAR(66) NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                log_index INTEGER NOT NULL,
+                escrow_address VARCHAR(42),
+                created_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+                UNIQUE(chain_id, tx_hash, log_index)
+            )",
+            &[],
+        ).await?;
+
         // Fusion swaps table (single-chain)
         client.execute(
             "CREATE TABLE IF NOT EXISTS fusion_swaps (
@@ -156,6 +732,19 @@ impl Database {
             &[],
         ).await?;
 
+        // Cancellation tracking was added after the table; upgrade existing databases
+        client.batch_execute(
+            "ALTER TABLE fusion_swaps ADD COLUMN IF NOT EXISTS cancelled_tx_hash VARCHAR(66);
+             ALTER TABLE fusion_swaps ADD COLUMN IF NOT EXISTS cancelled_block_number BIGINT;",
+        ).await?;
+
+        // Swaps stored before their transfers could be read are retried until
+        // enriched ('pending' -> NULL) or given up on ('failed')
+        client.batch_execute(
+            "ALTER TABLE fusion_swaps ADD COLUMN IF NOT EXISTS enrichment VARCHAR(10);
+             ALTER TABLE fusion_swaps ADD COLUMN IF NOT EXISTS enrich_attempts INTEGER NOT NULL DEFAULT 0;",
+        ).await?;
+
         // Crypto2Fiat events table
         client.execute(
             "CREATE TABLE IF NOT EXISTS crypto2fiat_events (
@@ -185,12 +774,59 @@ impl Database {
             "CREATE INDEX IF NOT EXISTS idx_transfers_swap_type ON transfers(chain_id, swap_type, block_timestamp DESC)",
             "CREATE INDEX IF NOT EXISTS idx_transfers_from_id ON transfers(chain_id, from_addr, id)",
             "CREATE INDEX IF NOT EXISTS idx_transfers_to_id ON transfers(chain_id, to_addr, id)",
+            "CREATE INDEX IF NOT EXISTS idx_transfers_token ON transfers(chain_id, token, block_timestamp DESC, id DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_transfers_token_id ON transfers(chain_id, token, id)",
+            "CREATE INDEX IF NOT EXISTS idx_transfers_block ON transfers(chain_id, block_number, log_index)",
         ];
 
         for sql in transfer_indexes {
             client.execute(sql, &[]).await?;
         }
 
+        // Create indexes for approvals
+        let approval_indexes = [
+            "CREATE INDEX IF NOT EXISTS idx_approvals_owner ON approvals(chain_id, owner, block_timestamp DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_approvals_spender ON approvals(chain_id, spender, block_timestamp DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_approvals_created ON approvals(created_at)",
+        ];
+
+        for sql in approval_indexes {
+            client.execute(sql, &[]).await?;
+        }
+
+        // Create indexes for raw_events
+        let raw_event_indexes = [
+            "CREATE INDEX IF NOT EXISTS idx_raw_events_watcher ON raw_events(watcher, block_number DESC, log_index DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_raw_events_block ON raw_events(chain_id, block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_raw_events_created ON raw_events(created_at)",
+            // Indexes for delegations
+            "CREATE INDEX IF NOT EXISTS idx_delegations_authority ON delegations(chain_id, authority, block_timestamp DESC, id DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_delegations_authority_id ON delegations(chain_id, authority, id)",
+            "CREATE INDEX IF NOT EXISTS idx_delegations_block ON delegations(chain_id, block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_delegations_created ON delegations(created_at)",
+            // Indexes for transactions
+            "CREATE INDEX IF NOT EXISTS idx_transactions_from ON transactions(chain_id, from_addr, block_timestamp DESC, id DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_transactions_from_id ON transactions(chain_id, from_addr, id)",
+            "CREATE INDEX IF NOT EXISTS idx_transactions_block ON transactions(chain_id, block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_transactions_created ON transactions(created_at)",
+            // Indexes for internal_transfers
+            "CREATE INDEX IF NOT EXISTS idx_internal_transfers_from ON internal_transfers(chain_id, from_addr, block_timestamp DESC, id DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_internal_transfers_to ON internal_transfers(chain_id, to_addr, block_timestamp DESC, id DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_internal_transfers_block ON internal_transfers(chain_id, block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_internal_transfers_created ON internal_transfers(created_at)",
+            // Index for token_stats windows across tokens
+            "CREATE INDEX IF NOT EXISTS idx_token_stats_hour ON token_stats(chain_id, hour)",
+        ];
+
+        for sql in raw_event_indexes {
+            client.execute(sql, &[]).await?;
+        }
+
+        client.execute(
+            "CREATE INDEX IF NOT EXISTS idx_raw_logs_created ON raw_logs(created_at)",
+            &[],
+        ).await?;
+
         // Create indexes for fusion_plus_swaps
         let fp_indexes = [
             "CREATE INDEX IF NOT EXISTS idx_fp_hashlock ON fusion_plus_swaps(hashlock)",
@@ -199,8 +835,19 @@ impl Database {
             "CREATE INDEX IF NOT EXISTS idx_fp_src_maker ON fusion_plus_swaps(src_maker)",
             "CREATE INDEX IF NOT EXISTS idx_fp_dst_maker ON fusion_plus_swaps(dst_maker)",
             "CREATE INDEX IF NOT EXISTS idx_fp_src_taker ON fusion_plus_swaps(src_taker)",
+            "CREATE INDEX IF NOT EXISTS idx_fp_src_escrow ON fusion_plus_swaps(src_escrow_address)",
+            "CREATE INDEX IF NOT EXISTS idx_fp_dst_escrow ON fusion_plus_swaps(dst_escrow_address)",
             "CREATE INDEX IF NOT EXISTS idx_fp_status ON fusion_plus_swaps(src_status, dst_status)",
             "CREATE INDEX IF NOT EXISTS idx_fp_created ON fusion_plus_swaps(created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_fp_src_block ON fusion_plus_swaps(src_chain_id, src_block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_fp_dst_block ON fusion_plus_swaps(dst_chain_id, dst_block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_fpf_hashlock ON fusion_plus_fills(hashlock)",
+            "CREATE INDEX IF NOT EXISTS idx_fpf_src_escrow ON fusion_plus_fills(src_escrow_address)",
+            "CREATE INDEX IF NOT EXISTS idx_fpf_dst_escrow ON fusion_plus_fills(dst_escrow_address)",
+            "CREATE INDEX IF NOT EXISTS idx_fpf_src_block ON fusion_plus_fills(src_chain_id, src_block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_fpf_dst_block ON fusion_plus_fills(dst_chain_id, dst_block_number)",
+            "CREATE INDEX IF NOT EXISTS idx_fpe_order ON fusion_plus_events(order_hash)",
+            "CREATE INDEX IF NOT EXISTS idx_fpe_block ON fusion_plus_events(chain_id, block_number)",
         ];
 
         for sql in fp_indexes {
@@ -215,12 +862,92 @@ impl Database {
             "CREATE INDEX IF NOT EXISTS idx_fs_taker ON fusion_swaps(taker)",
             "CREATE INDEX IF NOT EXISTS idx_fs_status ON fusion_swaps(status)",
             "CREATE INDEX IF NOT EXISTS idx_fs_created ON fusion_swaps(created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_fs_block ON fusion_swaps(chain_id, block_number, log_index)",
+            "CREATE INDEX IF NOT EXISTS idx_fs_cancelled ON fusion_swaps(chain_id, cancelled_block_number) WHERE cancelled_block_number IS NOT NULL",
+            "CREATE INDEX IF NOT EXISTS idx_fs_enrichment ON fusion_swaps(chain_id, block_timestamp) WHERE enrichment = 'pending'",
         ];
 
         for sql in fs_indexes {
             client.execute(sql, &[]).await?;
         }
 
+        // The first release kept fills in views over a permanent hex_to_numeric function
+        let fill_views = client.query_opt(
+            "SELECT 1 FROM pg_views WHERE viewname = 'order_fills'",
+            &[],
+        ).await?.is_some();
+        if fill_views {
+            client.batch_execute(
+                "DROP VIEW IF EXISTS fusion_orders;
+                 DROP VIEW order_fills;
+                 DROP FUNCTION IF EXISTS hex_to_numeric(TEXT);",
+            ).await?;
+        }
+
+        // One row per OrderFilled with its amounts in decimal, so fills can be
+        // summed in SQL. Rows go with their swap on reorg rollback, replay and
+        // TTL cleanup through the foreign key.
+        let has_order_fills = client.query_opt(
+            "SELECT 1 FROM information_schema.tables WHERE table_name = 'order_fills'",
+            &[],
+        ).await?.is_some();
+
+        client.batch_execute(
+            "CREATE TABLE IF NOT EXISTS order_fills (
+                swap_id BIGINT PRIMARY KEY REFERENCES fusion_swaps(id) ON DELETE CASCADE,
+                chain_id INTEGER NOT NULL,
+                order_hash VARCHAR(66) NOT NULL,
+                block_number BIGINT NOT NULL,
+                log_index INTEGER NOT NULL,
+                maker_amount NUMERIC(78, 0),
+                taker_amount NUMERIC(78, 0),
+                remaining NUMERIC(78, 0)
+             );
+             CREATE INDEX IF NOT EXISTS idx_of_order ON order_fills(chain_id, order_hash, block_number, log_index);",
+        ).await?;
+
+        if !has_order_fills {
+            client.batch_execute(HEX_TO_DECIMAL_FN).await?;
+            let backfilled = client.execute(
+                "INSERT INTO order_fills (swap_id, chain_id, order_hash, block_number, log_index, maker_amount, taker_amount, remaining)
+                 SELECT id, chain_id, order_hash, block_number, log_index,
+                        pg_temp.hex_to_decimal(maker_amount)::NUMERIC,
+                        pg_temp.hex_to_decimal(taker_amount)::NUMERIC,
+                        pg_temp.hex_to_decimal(remaining)::NUMERIC
+                 FROM fusion_swaps
+                 ON CONFLICT DO NOTHING",
+                &[],
+            ).await?;
+            if backfilled > 0 {
+                tracing::info!("Backfilled order_fills for {} existing Fusion swaps", backfilled);
+            }
+        }
+
+        // Per-order aggregate of the fills, with the order's details from its first fill
+        client.batch_execute(
+            "CREATE OR REPLACE VIEW fusion_orders AS
+             SELECT f.chain_id, f.order_hash,
+                    (array_agg(s.maker ORDER BY f.block_number, f.log_index))[1] AS maker,
+                    (array_agg(s.maker_token ORDER BY f.block_number, f.log_index))[1] AS maker_token,
+                    (array_agg(s.taker_token ORDER BY f.block_number, f.log_index))[1] AS taker_token,
+                    COUNT(*) AS fill_count,
+                    SUM(f.maker_amount) AS filled_maker_amount,
+                    SUM(f.taker_amount) AS filled_taker_amount,
+                    (array_agg(f.remaining ORDER BY f.block_number DESC, f.log_index DESC))[1] AS remaining,
+                    CASE
+                        WHEN bool_or(s.status = 'cancelled') THEN 'cancelled'
+                        WHEN (array_agg(f.remaining ORDER BY f.block_number DESC, f.log_index DESC))[1] = 0 THEN 'filled'
+                        ELSE 'partially_filled'
+                    END AS status,
+                    MIN(f.block_number) AS first_fill_block,
+                    MAX(f.block_number) AS last_fill_block,
+                    MIN(s.block_timestamp) AS first_fill_at,
+                    MAX(s.block_timestamp) AS last_fill_at
+             FROM order_fills f
+             JOIN fusion_swaps s ON s.id = f.swap_id
+             GROUP BY f.chain_id, f.order_hash;",
+        ).await?;
+
         // Create indexes for crypto2fiat_events
         let c2f_indexes = [
             "CREATE INDEX IF NOT EXISTS idx_c2f_order_id ON crypto2fiat_events(order_id)",
@@ -234,27 +961,493 @@ impl Database {
             client.execute(sql, &[]).await?;
         }
 
+        // Prefix indexes for `search` (LIKE 'abc%' can't use the default-collation indexes)
+        for (table, _, column) in SEARCH_COLUMNS {
+            if table == "transfers" && !self.config.search_indexes {
+                continue;
+            }
+            let sql = format!(
+                "CREATE INDEX IF NOT EXISTS idx_{}_{}_prefix ON {}({} varchar_pattern_ops)",
+                table, column, table, column
+            );
+            client.execute(sql.as_str(), &[]).await?;
+        }
+
+        drop(client);
+        if self.transfers_partitioned {
+            self.ensure_transfer_partitions().await?;
+        }
+
         tracing::info!("PostgreSQL schema initialized");
         Ok(())
     }
 
+    // =========================================================================
+    // Integrity Check
+    // =========================================================================
+
+    /// Verify every B-tree index of the schema with the `amcheck` extension,
+    /// rebuilding corrupt ones with REINDEX when `repair` is set
+    ///
+    /// Returns None when amcheck isn't installed (it needs
+    /// `CREATE EXTENSION amcheck` by a superuser). Only index structure is
+    /// checked, not every heap row, so this stays fast enough for startup.
+    /// A corrupt index is reported rather than failing the open, since the
+    /// tables are shared by every chain.
+    pub async fn check_integrity(&self, repair: bool) -> Result<Option<IntegrityReport>, DbError> {
+        let client = self.pool.get().await?;
+
+        let installed = client
+            .query_opt("SELECT 1 FROM pg_extension WHERE extname = 'amcheck'", &[])
+            .await?
+            .is_some();
+        if !installed {
+            return Ok(None);
+        }
+
+        let indexes: Vec<String> = client
+            .query(
+                "SELECT c.oid::regclass::TEXT
+                 FROM pg_index i
+                 JOIN pg_class c ON c.oid = i.indexrelid
+                 JOIN pg_am a ON a.oid = c.relam
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE a.amname = 'btree'
+                   AND c.relkind = 'i'
+                   AND n.nspname = current_schema()
+                   AND i.indisvalid AND i.indisready
+                 ORDER BY 1",
+                &[],
+            )
+            .await?
+            .iter()
+            .map(|r| r.get(0))
+            .collect();
+
+        let mut report = IntegrityReport { checked: indexes.len(), ..Default::default() };
+        for index in indexes {
+            let Err(e) = client.execute("SELECT bt_index_check($1::TEXT::regclass)", &[&index]).await else {
+                continue;
+            };
+            if !matches!(e.code(), Some(&SqlState::INDEX_CORRUPTED) | Some(&SqlState::DATA_CORRUPTED)) {
+                return Err(e.into());
+            }
+
+            tracing::error!("Integrity check: index {} is corrupt: {}", index, e);
+            if repair {
+                client.batch_execute(&format!("REINDEX INDEX {}", index)).await?;
+                tracing::warn!("Integrity check: rebuilt index {}", index);
+                report.repaired.push(index.clone());
+            }
+            report.corrupt.push(index);
+        }
+
+        Ok(Some(report))
+    }
+
+    // =========================================================================
+    // Daily Rotation Methods
+    // =========================================================================
+
+    /// Create today's and tomorrow's transfer partitions if missing
+    async fn ensure_transfer_partitions(&self) -> Result<(), DbError> {
+        let today = unix_now() / 86_400;
+        self.create_transfer_partitions(&[today, today + 1]).await
+    }
+
+    /// Create the partitions of the days `transfers` fall in, so rows of
+    /// backfilled days don't pile up in the default partition
+    async fn ensure_partitions_for(&self, transfers: &[Transfer]) -> Result<(), DbError> {
+        if !self.transfers_partitioned || transfers.is_empty() {
+            return Ok(());
+        }
+        let mut days: Vec<u64> = transfers.iter().map(|t| t.block_timestamp / 86_400).collect();
+        days.sort_unstable();
+        days.dedup();
+        self.create_transfer_partitions(&days).await
+    }
+
+    /// Create the missing transfer partitions of `days` (days since unix epoch)
+    ///
+    /// With `sharded_chains`, a new day partition is itself partitioned by
+    /// chain_id. Existing days are left as they are, since moving rows out of
+    /// a day's shared partition would rewrite it. Rows of a new day already in
+    /// the default partition, written before it existed, are moved into it.
+    async fn create_transfer_partitions(&self, days: &[u64]) -> Result<(), DbError> {
+        let mut client = self.pool.get().await?;
+        let names: Vec<String> = days.iter().map(|&day| partition_name("transfers", day)).collect();
+        let existing: Vec<String> = client
+            .query("SELECT relname::TEXT FROM pg_class WHERE relname = ANY($1)", &[&names])
+            .await?
+            .iter()
+            .map(|r| r.get(0))
+            .collect();
+
+        for (&day, name) in days.iter().zip(&names) {
+            if existing.contains(name) {
+                continue;
+            }
+
+            let tx = client.transaction().await?;
+            // Pollers of several chains can reach a new day at once
+            tx.execute("SELECT pg_advisory_xact_lock(hashtext('transfers_partitions'))", &[]).await?;
+            let exists = tx
+                .query_opt("SELECT 1 FROM pg_class WHERE relname = $1", &[name])
+                .await?
+                .is_some();
+            if exists {
+                continue;
+            }
+
+            // The default partition may not keep rows the new one covers
+            let (start, end) = (day * 86_400, (day + 1) * 86_400);
+            tx.batch_execute(&format!(
+                "CREATE TEMP TABLE transfers_moved (LIKE transfers) ON COMMIT DROP;
+                 WITH moved AS (
+                     DELETE FROM transfers_default WHERE block_timestamp >= {start} AND block_timestamp < {end}
+                     RETURNING *
+                 )
+                 INSERT INTO transfers_moved SELECT * FROM moved;"
+            )).await?;
+
+            let range = format!("FOR VALUES FROM ({}) TO ({})", start, end);
+            let mut sql = if self.config.sharded_chains.is_empty() {
+                format!("CREATE TABLE {} PARTITION OF transfers {};", name, range)
+            } else {
+                let mut sql = format!("CREATE TABLE {} PARTITION OF transfers {} PARTITION BY LIST (chain_id);", name, range);
+                for chain_id in &self.config.sharded_chains {
+                    sql += &format!("CREATE TABLE {}_c{} PARTITION OF {} FOR VALUES IN ({});", name, chain_id, name, chain_id);
+                }
+                sql += &format!("CREATE TABLE {}_default PARTITION OF {} DEFAULT;", name, name);
+                sql
+            };
+            sql += "INSERT INTO transfers SELECT * FROM transfers_moved;";
+            tx.batch_execute(&sql).await?;
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drop daily transfer partitions that ended before the retention cutoff
+    ///
+    /// Returns the estimated number of rows removed (from planner statistics,
+    /// to avoid scanning partitions that are about to be dropped).
+    async fn rotate_transfer_partitions(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        self.ensure_transfer_partitions().await?;
+
+        let client = self.pool.get().await?;
+        let cutoff = unix_now().saturating_sub(ttl_secs);
+
+        // Day partitions split by chain have no rows of their own
+        let rows = client.query(
+            "SELECT c.relname::TEXT,
+                    GREATEST(c.reltuples, 0)::BIGINT + COALESCE((
+                        SELECT SUM(GREATEST(s.reltuples, 0))::BIGINT
+                        FROM pg_inherits si
+                        JOIN pg_class s ON s.oid = si.inhrelid
+                        WHERE si.inhparent = c.oid
+                    ), 0)
+             FROM pg_inherits i
+             JOIN pg_class c ON c.oid = i.inhrelid
+             JOIN pg_class p ON p.oid = i.inhparent
+             WHERE p.relname = 'transfers'",
+            &[],
+        ).await?;
+
+        let mut dropped_rows = 0usize;
+        for row in rows {
+            let name: String = row.get(0);
+            let Some(day) = parse_partition_day("transfers", &name) else {
+                continue; // Default partition
+            };
+            if (day + 1) * 86_400 <= cutoff {
+                if self.config.archive.is_some() {
+                    // Export the partition's rows (through the parent, so they are
+                    // archived as transfers) before dropping it
+                    let (start, end) = ((day * 86_400) as i64, ((day + 1) * 86_400) as i64);
+                    self.delete_expired(
+                        "transfers",
+                        ("chain_id", "block_timestamp"),
+                        "block_timestamp >= $1 AND block_timestamp < $2",
+                        &[&start, &end],
+                    ).await?;
+                }
+                client.execute(format!("DROP TABLE IF EXISTS {}", name).as_str(), &[]).await?;
+                dropped_rows += row.get::<_, i64>(1) as usize;
+                tracing::info!("Dropped expired transfer partition {}", name);
+            }
+        }
+
+        // Rows outside the daily partitions still expire by created_at
+        let deleted = self
+            .delete_expired(
+                "transfers",
+                ("chain_id", "block_timestamp"),
+                "tableoid = 'transfers_default'::regclass AND created_at < $1",
+                &[&(cutoff as i64)],
+            )
+            .await?;
+
+        Ok(dropped_rows + deleted)
+    }
+
+    // =========================================================================
+    // Expiry
+    // =========================================================================
+
+    /// Delete the rows of `table` matching `condition`
+    ///
+    /// With `delete_batch_rows` set, rows are deleted that many at a time, each
+    /// batch in its own statement, so a large expiry doesn't hold locks or
+    /// stall the WAL long enough to delay the pollers' writes.
+    ///
+    /// With an archive configured, deleted rows are streamed to it (keyed by
+    /// `chain_column` and the day of `timestamp_column`) inside the deleting
+    /// transaction, so rows are only removed once they have been written.
+    async fn delete_expired(
+        &self,
+        table: &str,
+        columns: (&str, &str),
+        condition: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<usize, DbError> {
+        let Some(batch_rows) = self.config.delete_batch_rows.filter(|&rows| rows > 0) else {
+            return self.delete_where(table, columns, condition, params).await;
+        };
+
+        // Repeating the condition outside the ctid lookup keeps a partition's
+        // row with a colliding ctid from being deleted unless it is expired too
+        let batch_condition = format!(
+            "ctid = ANY(ARRAY(SELECT ctid FROM {} WHERE {} LIMIT {})) AND ({})",
+            table, condition, batch_rows, condition
+        );
+
+        let mut deleted = 0;
+        loop {
+            let batch = self.delete_where(table, columns, &batch_condition, params).await?;
+            deleted += batch;
+            if batch < batch_rows {
+                return Ok(deleted);
+            }
+        }
+    }
+
+    /// Delete (and archive) the rows of `table` matching `condition` in one statement
+    async fn delete_where(
+        &self,
+        table: &str,
+        (chain_column, timestamp_column): (&str, &str),
+        condition: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<usize, DbError> {
+        let mut client = self.pool.get().await?;
+
+        let Some(archive) = &self.config.archive else {
+            let sql = format!("DELETE FROM {} WHERE {}", table, condition);
+            return Ok(client.execute(sql.as_str(), params).await? as usize);
+        };
+
+        let tx = client.transaction().await?;
+        let sql = format!(
+            "DELETE FROM {} t WHERE {} RETURNING t.{}, t.{}, row_to_json(t)::TEXT",
+            table, condition, chain_column, timestamp_column
+        );
+        let rows = tx.query_raw(sql.as_str(), params.iter().copied()).await?;
+        let mut rows = std::pin::pin!(rows);
+
+        let mut deleted = 0;
+        let mut batch = Vec::new();
+        while let Some(row) = rows.try_next().await? {
+            batch.push(ArchivedRow {
+                chain_id: row.get::<_, i32>(0) as u32,
+                timestamp: row.get::<_, i64>(1) as u64,
+                json: row.get(2),
+            });
+            if batch.len() >= ARCHIVE_BATCH_ROWS {
+                deleted += batch.len();
+                archive.write(table, std::mem::take(&mut batch)).await?;
+            }
+        }
+        deleted += batch.len();
+        archive.write(table, batch).await?;
+
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    // =========================================================================
+    // Export
+    // =========================================================================
+
+    /// Read the rows of an export table for one chain and block-timestamp range
+    ///
+    /// Rows are fetched through a cursor `batch_size` at a time, in id order,
+    /// and handed to `on_batch` before the next batch is read.
+    pub async fn export_rows(
+        &self,
+        table: &ExportTable,
+        chain_id: u32,
+        (from_ts, to_ts): (u64, u64),
+        batch_size: usize,
+        mut on_batch: impl FnMut(&[Row]) -> std::io::Result<()>,
+    ) -> Result<usize, DbError> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {} = $1 AND {} BETWEEN $2 AND $3 ORDER BY id",
+            table.select_list(),
+            table.sql_table,
+            table.chain_column,
+            table.timestamp_column
+        );
+        let to_ts = to_ts.min(i64::MAX as u64);
+        let portal = tx.bind(sql.as_str(), &[&(chain_id as i32), &(from_ts as i64), &(to_ts as i64)]).await?;
+
+        let mut total = 0;
+        loop {
+            let rows = tx.query_portal(&portal, batch_size as i32).await?;
+            if rows.is_empty() {
+                break;
+            }
+            total += rows.len();
+            on_batch(&rows)?;
+            if rows.len() < batch_size {
+                break;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(total)
+    }
+
+    // =========================================================================
+    // Backup
+    // =========================================================================
+
+    /// Copy a chain's rows of every table to `<dest>/<table>.csv.gz`
+    ///
+    /// All tables are read in one REPEATABLE READ transaction, so the files
+    /// are a consistent snapshot while the poller keeps writing. Fusion+ swaps
+    /// are included when either leg is on the chain, and the watchlist is
+    /// copied whole. Files are CSV with a header row; restore one with
+    /// `\copy <table> FROM PROGRAM 'zcat <file>' WITH (FORMAT csv, HEADER)`.
+    /// Returns the uncompressed bytes written.
+    pub async fn backup(&self, chain_id: u32, dest: &Path) -> Result<u64, DbError> {
+        tokio::fs::create_dir_all(dest).await?;
+
+        let mut client = self.pool.get().await?;
+        let tx = client
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await?;
+
+        let mut total = 0;
+        for (table, chain_columns) in BACKUP_TABLES {
+            // COPY takes no bind parameters; chain_id is a number
+            let condition = if chain_columns.is_empty() {
+                "TRUE".to_string()
+            } else {
+                chain_columns
+                    .iter()
+                    .map(|column| format!("{} = {}", column, chain_id))
+                    .collect::<Vec<_>>()
+                    .join(" OR ")
+            };
+            let sql = format!(
+                "COPY (SELECT * FROM {} WHERE {}) TO STDOUT WITH (FORMAT csv, HEADER)",
+                table, condition
+            );
+            let stream = tx.copy_out(sql.as_str()).await?;
+            let mut stream = std::pin::pin!(stream);
+
+            let file = std::fs::File::create(dest.join(format!("{}.csv.gz", table)))?;
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            let mut buffer = Vec::with_capacity(BACKUP_WRITE_BYTES);
+            loop {
+                let chunk = stream.try_next().await?;
+                if let Some(chunk) = &chunk {
+                    total += chunk.len() as u64;
+                    buffer.extend_from_slice(chunk);
+                    if buffer.len() < BACKUP_WRITE_BYTES {
+                        continue;
+                    }
+                }
+
+                // Compress and write off the async workers
+                let done = chunk.is_none();
+                let data = std::mem::take(&mut buffer);
+                encoder = tokio::task::spawn_blocking(move || -> std::io::Result<_> {
+                    encoder.write_all(&data)?;
+                    Ok(encoder)
+                })
+                .await
+                .map_err(std::io::Error::other)??;
+                if done {
+                    break;
+                }
+            }
+            tokio::task::spawn_blocking(move || encoder.finish()?.sync_all())
+                .await
+                .map_err(std::io::Error::other)??;
+        }
+
+        tx.commit().await?;
+        Ok(total)
+    }
+
+    // =========================================================================
+    // Pagination
+    // =========================================================================
+
+    /// Run `select` (ending in a WHERE clause over `params`) with the keyset
+    /// condition and ordering of `cursor` on the given (id, timestamp) columns
+    async fn query_page(
+        &self,
+        select: &str,
+        params: &[&(dyn ToSql + Sync)],
+        (id_column, timestamp_column): (&str, &str),
+        cursor: &Cursor,
+        limit: u32,
+    ) -> Result<Vec<Row>, DbError> {
+        let client = self.pool.get().await?;
+
+        let (keyset, order_by, values) = keyset_sql(cursor, id_column, timestamp_column, params.len() + 1);
+        let limit = limit as i64;
+        let mut params = params.to_vec();
+        params.extend(values.iter().map(|v| v as &(dyn ToSql + Sync)));
+        params.push(&limit);
+
+        let sql = format!("{}{} ORDER BY {} LIMIT ${}", select, keyset, order_by, params.len());
+        Ok(client.query(&sql, &params).await?)
+    }
+
     // =========================================================================
     // Transfer Methods
     // =========================================================================
 
     /// Insert a transfer, ignoring duplicates
     pub async fn insert_transfer(&self, chain_id: u32, transfer: &Transfer) -> Result<bool, DbError> {
+        self.ensure_partitions_for(std::slice::from_ref(transfer)).await?;
         let client = self.pool.get().await?;
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs() as i64;
 
-        let result = client.execute(
+        let sql = format!(
             "INSERT INTO transfers
-             (chain_id, tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, created_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-             ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING",
+             (chain_id, tx_hash, log_index, token, from_addr, to_addr, value, value_decimal, block_number, block_timestamp, swap_type, created_at, tx_status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             ON CONFLICT {} DO NOTHING",
+            self.transfers_conflict_target()
+        );
+        let result = client.execute(
+            sql.as_str(),
             &[
                 &(chain_id as i32),
                 &transfer.tx_hash.to_lowercase(),
@@ -263,10 +1456,12 @@ impl Database {
                 &transfer.from_addr.to_lowercase(),
                 &transfer.to_addr.to_lowercase(),
                 &transfer.value,
+                &transfer.value_decimal,
                 &(transfer.block_number as i64),
                 &(transfer.block_timestamp as i64),
                 &transfer.swap_type,
                 &now,
+                &transfer.tx_status.map(i16::from),
             ],
         ).await?;
 
@@ -274,23 +1469,41 @@ impl Database {
     }
 
     /// Insert multiple transfers in a batch
+    #[instrument(skip_all, fields(chain_id = chain_id, rows = transfers.len()))]
     pub async fn insert_transfers_batch(&self, chain_id: u32, transfers: &[Transfer]) -> Result<usize, DbError> {
+        self.ensure_partitions_for(transfers).await?;
+        let client = self.pool.get().await?;
+        Self::exec_insert_transfers(&client, chain_id, transfers, unix_now() as i64, self.transfers_conflict_target()).await
+    }
+
+    /// Unique key of transfers as an ON CONFLICT target; a partitioned table's
+    /// includes the partition column
+    fn transfers_conflict_target(&self) -> &'static str {
+        if self.transfers_partitioned {
+            "(chain_id, tx_hash, log_index, block_timestamp)"
+        } else {
+            "(chain_id, tx_hash, log_index)"
+        }
+    }
+
+    async fn exec_insert_transfers(
+        client: &impl GenericClient,
+        chain_id: u32,
+        transfers: &[Transfer],
+        now: i64,
+        conflict_target: &str,
+    ) -> Result<usize, DbError> {
         if transfers.is_empty() {
             return Ok(0);
         }
 
-        let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-
-        let stmt = client.prepare(
+        let stmt = client.prepare(&format!(
             "INSERT INTO transfers
-             (chain_id, tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, created_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-             ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING"
-        ).await?;
+             (chain_id, tx_hash, log_index, token, from_addr, to_addr, value, value_decimal, block_number, block_timestamp, swap_type, created_at, tx_status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             ON CONFLICT {} DO NOTHING",
+            conflict_target
+        )).await?;
 
         let mut inserted = 0;
         for transfer in transfers {
@@ -304,10 +1517,12 @@ impl Database {
                     &transfer.from_addr.to_lowercase(),
                     &transfer.to_addr.to_lowercase(),
                     &transfer.value,
+                    &transfer.value_decimal,
                     &(transfer.block_number as i64),
                     &(transfer.block_timestamp as i64),
                     &transfer.swap_type,
                     &now,
+                    &transfer.tx_status.map(i16::from),
                 ],
             ).await?;
             if result > 0 {
@@ -318,403 +1533,2177 @@ impl Database {
         Ok(inserted)
     }
 
-    /// Get checkpoint block number for a chain
-    pub async fn get_checkpoint(&self, chain_id: u32) -> Result<Option<u64>, DbError> {
+    /// Insert multiple approvals in a batch, ignoring duplicates
+    #[instrument(skip_all, fields(chain_id = chain_id, rows = approvals.len()))]
+    pub async fn insert_approvals_batch(&self, chain_id: u32, approvals: &[Approval]) -> Result<usize, DbError> {
         let client = self.pool.get().await?;
+        Self::exec_insert_approvals(&client, chain_id, approvals, unix_now() as i64).await
+    }
 
-        let row = client.query_opt(
-            "SELECT block_number FROM checkpoints WHERE chain_id = $1",
-            &[&(chain_id as i32)],
+    async fn exec_insert_approvals(
+        client: &impl GenericClient,
+        chain_id: u32,
+        approvals: &[Approval],
+        now: i64,
+    ) -> Result<usize, DbError> {
+        if approvals.is_empty() {
+            return Ok(0);
+        }
+
+        let stmt = client.prepare(
+            "INSERT INTO approvals
+             (chain_id, tx_hash, log_index, token, owner, spender, amount, is_unlimited, block_number, block_timestamp, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT DO NOTHING"
         ).await?;
 
-        Ok(row.map(|r| r.get::<_, i64>(0) as u64))
+        let mut inserted = 0;
+        for approval in approvals {
+            let result = client.execute(
+                &stmt,
+                &[
+                    &(chain_id as i32),
+                    &approval.tx_hash.to_lowercase(),
+                    &(approval.log_index as i32),
+                    &approval.token.to_lowercase(),
+                    &approval.owner.to_lowercase(),
+                    &approval.spender.to_lowercase(),
+                    &approval.amount,
+                    &approval.is_unlimited,
+                    &(approval.block_number as i64),
+                    &(approval.block_timestamp as i64),
+                    &now,
+                ],
+            ).await?;
+            if result > 0 {
+                inserted += 1;
+            }
+        }
+
+        Ok(inserted)
     }
 
-    /// Set checkpoint block number for a chain
-    pub async fn set_checkpoint(&self, chain_id: u32, block_number: u64) -> Result<(), DbError> {
+    /// Map a row selected as (tx_hash, log_index, token, owner, spender, amount,
+    /// is_unlimited, block_number, block_timestamp) to an Approval
+    fn row_to_approval(row: &Row, chain_id: u32) -> Approval {
+        Approval {
+            chain_id,
+            tx_hash: row.get(0),
+            log_index: row.get::<_, i32>(1) as u32,
+            token: row.get(2),
+            owner: row.get(3),
+            spender: row.get(4),
+            amount: row.get(5),
+            is_unlimited: row.get(6),
+            block_number: row.get::<_, i64>(7) as u64,
+            block_timestamp: row.get::<_, i64>(8) as u64,
+        }
+    }
+
+    /// Get most recent approvals granted by an owner
+    pub async fn get_approvals_by_owner(&self, chain_id: u32, owner: &str, unlimited_only: bool, limit: u32) -> Result<Vec<Approval>, DbError> {
         let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
 
-        client.execute(
-            "INSERT INTO checkpoints (chain_id, block_number, updated_at)
-             VALUES ($1, $2, $3)
-             ON CONFLICT (chain_id) DO UPDATE SET
-             block_number = EXCLUDED.block_number,
-             updated_at = EXCLUDED.updated_at",
-            &[&(chain_id as i32), &(block_number as i64), &now],
+        let rows = client.query(
+            "SELECT tx_hash, log_index, token, owner, spender, amount, is_unlimited, block_number, block_timestamp
+             FROM approvals
+             WHERE chain_id = $1 AND owner = $2 AND (is_unlimited OR NOT $3)
+             ORDER BY block_timestamp DESC
+             LIMIT $4",
+            &[&(chain_id as i32), &owner.to_lowercase(), &unlimited_only, &(limit as i64)],
         ).await?;
 
-        Ok(())
+        Ok(rows.iter().map(|r| Self::row_to_approval(r, chain_id)).collect())
     }
 
-    /// Clean up old transfers based on TTL
-    pub async fn cleanup_old_transfers(&self, ttl_secs: u64) -> Result<usize, DbError> {
+    /// Get most recent approvals granted to a spender
+    pub async fn get_approvals_by_spender(&self, chain_id: u32, spender: &str, unlimited_only: bool, limit: u32) -> Result<Vec<Approval>, DbError> {
         let client = self.pool.get().await?;
-        let cutoff = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64
-            - ttl_secs as i64;
 
-        let deleted = client.execute(
-            "DELETE FROM transfers WHERE created_at < $1",
-            &[&cutoff],
+        let rows = client.query(
+            "SELECT tx_hash, log_index, token, owner, spender, amount, is_unlimited, block_number, block_timestamp
+             FROM approvals
+             WHERE chain_id = $1 AND spender = $2 AND (is_unlimited OR NOT $3)
+             ORDER BY block_timestamp DESC
+             LIMIT $4",
+            &[&(chain_id as i32), &spender.to_lowercase(), &unlimited_only, &(limit as i64)],
         ).await?;
 
-        Ok(deleted as usize)
+        Ok(rows.iter().map(|r| Self::row_to_approval(r, chain_id)).collect())
     }
 
-    /// Get total count of transfers for a chain
-    pub async fn get_transfer_count(&self, chain_id: u32) -> Result<u64, DbError> {
+    /// Get total approval count across all chains
+    pub async fn get_approval_count(&self) -> Result<u64, DbError> {
         let client = self.pool.get().await?;
-        let row = client.query_one(
-            "SELECT COUNT(*) FROM transfers WHERE chain_id = $1",
-            &[&(chain_id as i32)],
-        ).await?;
+        let row = client.query_one("SELECT COUNT(*) FROM approvals", &[]).await?;
 
         Ok(row.get::<_, i64>(0) as u64)
     }
 
-    /// Get total transfer count across all chains
-    pub async fn get_total_transfer_count(&self) -> Result<u64, DbError> {
+    /// Clean up old approvals based on TTL
+    pub async fn cleanup_old_approvals(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("approvals", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    // =========================================================================
+    // Raw Event Methods
+    // =========================================================================
+
+    /// Insert watcher logs in a batch, ignoring duplicates
+    #[instrument(skip_all, fields(chain_id = chain_id, rows = events.len()))]
+    pub async fn insert_raw_events_batch(&self, chain_id: u32, events: &[RawEvent]) -> Result<usize, DbError> {
         let client = self.pool.get().await?;
-        let row = client.query_one(
-            "SELECT COUNT(*) FROM transfers",
-            &[],
+        Self::exec_insert_raw_events(&client, chain_id, events, unix_now() as i64).await
+    }
+
+    async fn exec_insert_raw_events(
+        client: &impl GenericClient,
+        chain_id: u32,
+        events: &[RawEvent],
+        now: i64,
+    ) -> Result<usize, DbError> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let stmt = client.prepare(
+            "INSERT INTO raw_events
+             (chain_id, watcher, address, tx_hash, log_index, topics, data, block_number, block_timestamp, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT DO NOTHING"
         ).await?;
 
-        Ok(row.get::<_, i64>(0) as u64)
+        let mut inserted = 0;
+        for event in events {
+            let topics: Vec<String> = event.topics.iter().map(|t| t.to_lowercase()).collect();
+            let result = client.execute(
+                &stmt,
+                &[
+                    &(chain_id as i32),
+                    &event.watcher,
+                    &event.address.to_lowercase(),
+                    &event.tx_hash.to_lowercase(),
+                    &(event.log_index as i32),
+                    &topics,
+                    &event.data,
+                    &(event.block_number as i64),
+                    &(event.block_timestamp as i64),
+                    &now,
+                ],
+            ).await?;
+            if result > 0 {
+                inserted += 1;
+            }
+        }
+
+        Ok(inserted)
     }
 
-    /// Label transfers in a transaction with swap_type
-    pub async fn label_transfers_as_fusion(&self, chain_id: u32, tx_hash: &str, swap_type: &str) -> Result<usize, DbError> {
+    /// Get the most recent logs captured by a watcher
+    pub async fn get_raw_events(&self, watcher: &str, limit: u32) -> Result<Vec<RawEvent>, DbError> {
         let client = self.pool.get().await?;
 
-        let result = client.execute(
-            "UPDATE transfers SET swap_type = $1 WHERE chain_id = $2 AND tx_hash = $3",
-            &[&swap_type, &(chain_id as i32), &tx_hash.to_lowercase()],
+        let rows = client.query(
+            "SELECT chain_id, watcher, address, tx_hash, log_index, topics, data, block_number, block_timestamp
+             FROM raw_events
+             WHERE watcher = $1
+             ORDER BY block_number DESC, log_index DESC
+             LIMIT $2",
+            &[&watcher, &(limit as i64)],
         ).await?;
 
-        Ok(result as usize)
+        Ok(rows
+            .iter()
+            .map(|r| RawEvent {
+                chain_id: r.get::<_, i32>(0) as u32,
+                watcher: r.get(1),
+                address: r.get(2),
+                tx_hash: r.get(3),
+                log_index: r.get::<_, i32>(4) as u32,
+                topics: r.get(5),
+                data: r.get(6),
+                block_number: r.get::<_, i64>(7) as u64,
+                block_timestamp: r.get::<_, i64>(8) as u64,
+            })
+            .collect())
     }
 
-    /// Get first and last transfers for a transaction (by log_index)
-    /// Returns (first_transfer, last_transfer) for populating swap maker/taker info
-    pub async fn get_first_last_transfers(&self, chain_id: u32, tx_hash: &str) -> Result<Option<(Transfer, Transfer)>, DbError> {
+    /// Get total raw event count across all watchers
+    pub async fn get_raw_event_count(&self) -> Result<u64, DbError> {
         let client = self.pool.get().await?;
-        let tx_hash_lower = tx_hash.to_lowercase();
-
-        // Get first transfer (lowest log_index)
-        let first_row = client.query_opt(
-            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type
-             FROM transfers
-             WHERE chain_id = $1 AND tx_hash = $2
-             ORDER BY log_index ASC
-             LIMIT 1",
-            &[&(chain_id as i32), &tx_hash_lower],
-        ).await?;
+        let row = client.query_one("SELECT COUNT(*) FROM raw_events", &[]).await?;
 
-        // Get last transfer (highest log_index)
-        let last_row = client.query_opt(
-            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type
-             FROM transfers
-             WHERE chain_id = $1 AND tx_hash = $2
-             ORDER BY log_index DESC
-             LIMIT 1",
-            &[&(chain_id as i32), &tx_hash_lower],
-        ).await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
 
-        match (first_row, last_row) {
-            (Some(first), Some(last)) => {
-                let first_transfer = Transfer {
-                    chain_id,
-                    tx_hash: first.get(0),
-                    log_index: first.get::<_, i32>(1) as u32,
-                    token: first.get(2),
-                    from_addr: first.get(3),
-                    to_addr: first.get(4),
-                    value: first.get(5),
-                    block_number: first.get::<_, i64>(6) as u64,
-                    block_timestamp: first.get::<_, i64>(7) as u64,
-                    swap_type: first.get(8),
-                };
-                let last_transfer = Transfer {
-                    chain_id,
-                    tx_hash: last.get(0),
-                    log_index: last.get::<_, i32>(1) as u32,
-                    token: last.get(2),
-                    from_addr: last.get(3),
-                    to_addr: last.get(4),
-                    value: last.get(5),
-                    block_number: last.get::<_, i64>(6) as u64,
-                    block_timestamp: last.get::<_, i64>(7) as u64,
-                    swap_type: last.get(8),
-                };
-                Ok(Some((first_transfer, last_transfer)))
-            }
-            _ => Ok(None),
-        }
+    /// Clean up old raw events based on TTL
+    pub async fn cleanup_old_raw_events(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("raw_events", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
     }
 
     // =========================================================================
-    // Fusion+ Methods
+    // Raw Log Archive Methods
     // =========================================================================
 
-    /// Insert a new Fusion+ swap
-    pub async fn insert_fusion_plus_swap(&self, swap: &FusionPlusSwap) -> Result<bool, DbError> {
+    /// Archive fetched logs as JSON with their block timestamps, ignoring duplicates
+    #[instrument(skip_all, fields(chain_id = chain_id, rows = logs.len()))]
+    pub async fn insert_raw_logs_batch(&self, chain_id: u32, logs: &[(&Log, u64)]) -> Result<usize, DbError> {
         let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        Self::exec_insert_raw_logs(&client, chain_id, logs, unix_now() as i64).await
+    }
 
-        let result = client.execute(
-            "INSERT INTO fusion_plus_swaps (
-                order_hash, hashlock, secret,
-                src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
-                src_escrow_address, src_maker, src_taker, src_token, src_amount,
-                src_safety_deposit, src_timelocks, src_status,
-                dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
-                dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
-                dst_safety_deposit, dst_timelocks, dst_status,
-                created_at, updated_at
-            ) VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
-                $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31
-            )
-            ON CONFLICT (order_hash) DO NOTHING",
-            &[
-                &swap.order_hash.to_lowercase(),
-                &swap.hashlock.to_lowercase(),
-                &swap.secret,
-                &(swap.src_chain_id as i32),
-                &swap.src_tx_hash.to_lowercase(),
-                &(swap.src_block_number as i64),
-                &(swap.src_block_timestamp as i64),
-                &(swap.src_log_index as i32),
-                &swap.src_escrow_address.as_ref().map(|s| s.to_lowercase()),
-                &swap.src_maker.to_lowercase(),
-                &swap.src_taker.to_lowercase(),
-                &swap.src_token.to_lowercase(),
-                &swap.src_amount,
-                &swap.src_safety_deposit,
-                &swap.src_timelocks,
-                &swap.src_status,
-                &(swap.dst_chain_id as i32),
-                &swap.dst_tx_hash.as_ref().map(|s| s.to_lowercase()),
-                &swap.dst_block_number.map(|n| n as i64),
-                &swap.dst_block_timestamp.map(|n| n as i64),
-                &swap.dst_log_index.map(|n| n as i32),
-                &swap.dst_escrow_address.as_ref().map(|s| s.to_lowercase()),
-                &swap.dst_maker.to_lowercase(),
-                &swap.dst_taker.as_ref().map(|s| s.to_lowercase()),
-                &swap.dst_token.to_lowercase(),
-                &swap.dst_amount,
-                &swap.dst_safety_deposit,
-                &swap.dst_timelocks,
-                &swap.dst_status,
-                &now,
-                &now,
-            ],
+    async fn exec_insert_raw_logs(
+        client: &impl GenericClient,
+        chain_id: u32,
+        logs: &[(&Log, u64)],
+        now: i64,
+    ) -> Result<usize, DbError> {
+        if logs.is_empty() {
+            return Ok(0);
+        }
+
+        let stmt = client.prepare(
+            "INSERT INTO raw_logs
+             (chain_id, block_number, log_index, tx_hash, block_timestamp, log, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6::TEXT::JSONB, $7)
+             ON CONFLICT DO NOTHING"
         ).await?;
 
-        Ok(result > 0)
+        let mut inserted = 0;
+        for (log, block_timestamp) in logs {
+            let json = serde_json::to_string(log)?;
+            let result = client.execute(
+                &stmt,
+                &[
+                    &(chain_id as i32),
+                    &(log.block_number_u64() as i64),
+                    &(log.log_index_u32() as i32),
+                    &log.transaction_hash.to_lowercase(),
+                    &(*block_timestamp as i64),
+                    &json,
+                    &now,
+                ],
+            ).await?;
+            if result > 0 {
+                inserted += 1;
+            }
+        }
+
+        Ok(inserted)
     }
 
-    /// Update swap with destination data
-    pub async fn update_fusion_plus_dst(
-        &self,
-        order_hash: &str,
-        dst_data: &DstEscrowCreatedData,
-        chain_id: u32,
-        tx_hash: &str,
-        block_number: u64,
-        block_timestamp: u64,
-        log_index: u32,
-        escrow_address: Option<&str>,
-    ) -> Result<bool, DbError> {
+    /// Get archived logs in a block range with their block timestamps, in chain order
+    pub async fn get_raw_logs(&self, chain_id: u32, from_block: u64, to_block: u64) -> Result<Vec<(Log, u64)>, DbError> {
         let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
 
-        let result = client.execute(
-            "UPDATE fusion_plus_swaps SET
-                dst_tx_hash = $1,
-                dst_block_number = $2,
-                dst_block_timestamp = $3,
-                dst_log_index = $4,
-                dst_escrow_address = $5,
-                dst_taker = $6,
-                dst_timelocks = $7,
-                dst_status = 'created',
-                updated_at = $8
-             WHERE order_hash = $9 AND dst_chain_id = $10",
-            &[
-                &tx_hash.to_lowercase(),
-                &(block_number as i64),
-                &(block_timestamp as i64),
-                &(log_index as i32),
-                &escrow_address.map(|s| s.to_lowercase()),
-                &dst_data.dst_taker.to_lowercase(),
-                &dst_data.dst_timelocks,
-                &now,
-                &order_hash.to_lowercase(),
-                &(chain_id as i32),
-            ],
+        let rows = client.query(
+            "SELECT log::TEXT, block_timestamp
+             FROM raw_logs
+             WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3
+             ORDER BY block_number, log_index",
+            &[&(chain_id as i32), &(from_block as i64), &(to_block as i64)],
         ).await?;
 
-        Ok(result > 0)
+        // A log that no longer parses fails the replay rather than going missing
+        rows.iter()
+            .map(|r| Ok((serde_json::from_str(r.get::<_, &str>(0))?, r.get::<_, i64>(1) as u64)))
+            .collect()
     }
 
-    /// Update swap status on withdrawal
-    pub async fn update_fusion_plus_withdrawal(
-        &self,
-        order_hash: &str,
-        chain_id: u32,
-        is_src: bool,
-        secret: &str,
-    ) -> Result<bool, DbError> {
+    /// Clean up old archived logs based on TTL
+    pub async fn cleanup_old_raw_logs(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("raw_logs", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    /// Check that a connection can be taken from the pool and used
+    pub async fn ping(&self) -> Result<(), DbError> {
         let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        client.query_one("SELECT 1", &[]).await?;
+        Ok(())
+    }
 
-        let result = if is_src {
-            client.execute(
-                "UPDATE fusion_plus_swaps SET
-                    src_status = 'withdrawn',
-                    secret = $1,
-                    updated_at = $2
-                 WHERE order_hash = $3 AND src_chain_id = $4",
-                &[
-                    &secret.to_lowercase(),
-                    &now,
-                    &order_hash.to_lowercase(),
-                    &(chain_id as i32),
-                ],
-            ).await?
-        } else {
-            client.execute(
-                "UPDATE fusion_plus_swaps SET
-                    dst_status = 'withdrawn',
-                    secret = $1,
-                    updated_at = $2
-                 WHERE order_hash = $3 AND dst_chain_id = $4",
-                &[
-                    &secret.to_lowercase(),
-                    &now,
-                    &order_hash.to_lowercase(),
-                    &(chain_id as i32),
-                ],
-            ).await?
-        };
+    /// Get checkpoint block number for a chain
+    pub async fn get_checkpoint(&self, chain_id: u32) -> Result<Option<u64>, DbError> {
+        let client = self.pool.get().await?;
 
-        Ok(result > 0)
+        let row = client.query_opt(
+            "SELECT block_number FROM checkpoints WHERE chain_id = $1",
+            &[&(chain_id as i32)],
+        ).await?;
+
+        Ok(row.map(|r| r.get::<_, i64>(0) as u64))
     }
 
-    /// Update swap status on cancellation
-    pub async fn update_fusion_plus_cancelled(
-        &self,
-        order_hash: &str,
-        chain_id: u32,
-        is_src: bool,
-    ) -> Result<bool, DbError> {
+    /// Chains that have been indexed, i.e. have a checkpoint
+    pub async fn get_indexed_chains(&self) -> Result<Vec<u32>, DbError> {
         let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let rows = client.query("SELECT chain_id FROM checkpoints ORDER BY chain_id", &[]).await?;
+        Ok(rows.iter().map(|r| r.get::<_, i32>(0) as u32).collect())
+    }
 
-        let result = if is_src {
-            client.execute(
-                "UPDATE fusion_plus_swaps SET
-                    src_status = 'cancelled',
-                    updated_at = $1
-                 WHERE order_hash = $2 AND src_chain_id = $3",
-                &[&now, &order_hash.to_lowercase(), &(chain_id as i32)],
-            ).await?
-        } else {
-            client.execute(
-                "UPDATE fusion_plus_swaps SET
-                    dst_status = 'cancelled',
-                    updated_at = $1
-                 WHERE order_hash = $2 AND dst_chain_id = $3",
-                &[&now, &order_hash.to_lowercase(), &(chain_id as i32)],
-            ).await?
-        };
+    /// Set checkpoint block number for a chain
+    #[instrument(skip_all, fields(chain_id = chain_id, block_number = block_number))]
+    pub async fn set_checkpoint(&self, chain_id: u32, block_number: u64) -> Result<(), DbError> {
+        let client = self.pool.get().await?;
+        Self::exec_set_checkpoint(&client, chain_id, block_number, unix_now() as i64).await
+    }
 
-        Ok(result > 0)
+    async fn exec_set_checkpoint(client: &impl GenericClient, chain_id: u32, block_number: u64, now: i64) -> Result<(), DbError> {
+        client.execute(
+            "INSERT INTO checkpoints (chain_id, block_number, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (chain_id) DO UPDATE SET
+             block_number = EXCLUDED.block_number,
+             updated_at = EXCLUDED.updated_at",
+            &[&(chain_id as i32), &(block_number as i64), &now],
+        ).await?;
+
+        Ok(())
     }
 
-    /// Update swap status on withdrawal by hashlock
-    pub async fn update_fusion_plus_withdrawal_by_hashlock(
-        &self,
-        hashlock: &str,
-        chain_id: u32,
-        is_src: bool,
-        secret: &str,
-        tx_hash: &str,
-        block_number: u64,
-        block_timestamp: u64,
-        log_index: u32,
-    ) -> Result<bool, DbError> {
-        let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+    /// Commit a writer batch in one transaction: its rows, then the checkpoint
+    /// and backfill progress queued after them
+    ///
+    /// The checkpoint never moves past rows that were not committed, and a
+    /// crash between the two can't leave rows stored behind an old checkpoint.
+    #[instrument(skip_all, fields(chain_id = chain_id))]
+    pub async fn commit_writes(&self, chain_id: u32, writes: &ChainWrites<'_>) -> Result<(), DbError> {
+        self.ensure_partitions_for(writes.transfers).await?;
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        let now = unix_now() as i64;
 
-        let result = if is_src {
-            client.execute(
-                "UPDATE fusion_plus_swaps SET
-                    src_status = 'withdrawn',
-                    secret = $1,
-                    updated_at = $2
-                 WHERE hashlock = $3 AND src_chain_id = $4",
-                &[
-                    &secret.to_lowercase(),
-                    &now,
-                    &hashlock.to_lowercase(),
-                    &(chain_id as i32),
-                ],
-            ).await?
-        } else {
+        let raw_logs: Vec<(&Log, u64)> = writes.raw_logs.iter().map(|(log, ts)| (log, *ts)).collect();
+        Self::exec_insert_raw_logs(&tx, chain_id, &raw_logs, now).await?;
+        Self::exec_insert_transfers(&tx, chain_id, writes.transfers, now, self.transfers_conflict_target()).await?;
+        Self::exec_insert_approvals(&tx, chain_id, writes.approvals, now).await?;
+        Self::exec_insert_raw_events(&tx, chain_id, writes.raw_events, now).await?;
+        Self::exec_insert_delegations(&tx, chain_id, writes.delegations, now).await?;
+        Self::exec_insert_transactions(&tx, chain_id, writes.transactions, now).await?;
+        Self::exec_insert_internal_transfers(&tx, chain_id, writes.internal_transfers, now).await?;
+
+        for &(from_block, to_block) in writes.processed_ranges {
+            Self::exec_record_processed_range(&tx, chain_id, from_block, to_block).await?;
+        }
+        if let Some(block_number) = writes.checkpoint {
+            Self::exec_set_checkpoint(&tx, chain_id, block_number, now).await?;
+        }
+        if let Some((from_block, to_block, next_block)) = writes.backfill_progress {
+            Self::exec_set_backfill_progress(&tx, chain_id, from_block, to_block, next_block).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // Processed Range Methods
+    // =========================================================================
+
+    /// Record blocks `from_block..=to_block` as indexed, extending a range it
+    /// overlaps or follows
+    async fn exec_record_processed_range(
+        client: &impl GenericClient,
+        chain_id: u32,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<(), DbError> {
+        let params: [&(dyn ToSql + Sync); 3] = [&(chain_id as i32), &(from_block as i64), &(to_block as i64)];
+        let extended = client.execute(
+            "UPDATE processed_ranges SET to_block = GREATEST(to_block, $3)
+             WHERE chain_id = $1 AND from_block <= $2 AND to_block + 1 >= $2",
+            &params,
+        ).await?;
+        if extended == 0 {
             client.execute(
-                "UPDATE fusion_plus_swaps SET
-                    dst_status = 'withdrawn',
-                    dst_tx_hash = $5,
-                    dst_block_number = $6,
-                    dst_block_timestamp = $7,
-                    dst_log_index = $8,
-                    secret = $1,
-                    updated_at = $2
-                 WHERE hashlock = $3 AND dst_chain_id = $4",
-                &[
-                    &secret.to_lowercase(),
-                    &now,
-                    &hashlock.to_lowercase(),
-                    &(chain_id as i32),
-                    &tx_hash.to_lowercase(),
-                    &(block_number as i64),
-                    &(block_timestamp as i64),
-                    &(log_index as i32),
-                ],
-            ).await?
-        };
+                "INSERT INTO processed_ranges (chain_id, from_block, to_block)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (chain_id, from_block) DO UPDATE SET
+                 to_block = GREATEST(processed_ranges.to_block, EXCLUDED.to_block)",
+                &params,
+            ).await?;
+        }
 
-        Ok(result > 0)
+        Ok(())
     }
 
-    fn row_to_fusion_plus_swap(row: &Row) -> FusionPlusSwap {
-        FusionPlusSwap {
-            order_hash: row.get(0),
-            hashlock: row.get(1),
+    /// Find unindexed block ranges between indexed ones, oldest first
+    ///
+    /// Blocks before the first indexed range are not gaps. Ranges that
+    /// touch are merged in the table on the way.
+    pub async fn find_block_gaps(&self, chain_id: u32) -> Result<Vec<(u64, u64)>, DbError> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        let chain = chain_id as i32;
+
+        let rows = tx.query(
+            "SELECT from_block, to_block FROM processed_ranges WHERE chain_id = $1 FOR UPDATE",
+            &[&chain],
+        ).await?;
+        let ranges: Vec<(u64, u64)> = rows
+            .iter()
+            .map(|r| (r.get::<_, i64>(0) as u64, r.get::<_, i64>(1) as u64))
+            .collect();
+        let merged = merge_block_ranges(ranges.clone());
+
+        if merged.len() < ranges.len() {
+            tx.execute("DELETE FROM processed_ranges WHERE chain_id = $1", &[&chain]).await?;
+            for (from_block, to_block) in &merged {
+                tx.execute(
+                    "INSERT INTO processed_ranges (chain_id, from_block, to_block) VALUES ($1, $2, $3)",
+                    &[&chain, &(*from_block as i64), &(*to_block as i64)],
+                ).await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(merged.windows(2).map(|w| (w[0].1 + 1, w[1].0 - 1)).collect())
+    }
+
+    // =========================================================================
+    // Backfill Progress Methods
+    // =========================================================================
+
+    /// Get the next block to index for a backfill range, if it was started before
+    pub async fn get_backfill_progress(&self, chain_id: u32, from_block: u64, to_block: u64) -> Result<Option<u64>, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt(
+            "SELECT next_block FROM backfill_progress
+             WHERE chain_id = $1 AND from_block = $2 AND to_block = $3",
+            &[&(chain_id as i32), &(from_block as i64), &(to_block as i64)],
+        ).await?;
+
+        Ok(row.map(|r| r.get::<_, i64>(0) as u64))
+    }
+
+    /// Record the next block to index for a backfill range
+    pub async fn set_backfill_progress(&self, chain_id: u32, from_block: u64, to_block: u64, next_block: u64) -> Result<(), DbError> {
+        let client = self.pool.get().await?;
+        Self::exec_set_backfill_progress(&client, chain_id, from_block, to_block, next_block).await
+    }
+
+    async fn exec_set_backfill_progress(
+        client: &impl GenericClient,
+        chain_id: u32,
+        from_block: u64,
+        to_block: u64,
+        next_block: u64,
+    ) -> Result<(), DbError> {
+        client.execute(
+            "INSERT INTO backfill_progress (chain_id, from_block, to_block, next_block, updated_at)
+             VALUES ($1, $2, $3, $4, EXTRACT(EPOCH FROM NOW())::BIGINT)
+             ON CONFLICT (chain_id, from_block, to_block) DO UPDATE SET
+             next_block = EXCLUDED.next_block,
+             updated_at = EXCLUDED.updated_at",
+            &[&(chain_id as i32), &(from_block as i64), &(to_block as i64), &(next_block as i64)],
+        ).await?;
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // Block Hash Methods (reorg detection)
+    // =========================================================================
+
+    /// Record the hash of a processed block and keep only the newest `keep` per chain
+    pub async fn record_block_hash(&self, chain_id: u32, block_number: u64, block_hash: &str, keep: u64) -> Result<(), DbError> {
+        let client = self.pool.get().await?;
+
+        client.execute(
+            "INSERT INTO block_hashes (chain_id, block_number, block_hash)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (chain_id, block_number) DO UPDATE SET block_hash = EXCLUDED.block_hash",
+            &[&(chain_id as i32), &(block_number as i64), &block_hash.to_lowercase()],
+        ).await?;
+
+        client.execute(
+            "DELETE FROM block_hashes
+             WHERE chain_id = $1 AND block_number < (
+                 SELECT MIN(block_number) FROM (
+                     SELECT block_number FROM block_hashes
+                     WHERE chain_id = $1
+                     ORDER BY block_number DESC
+                     LIMIT $2
+                 ) recent
+             )",
+            &[&(chain_id as i32), &(keep as i64)],
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Get recorded block hashes for a chain, newest first
+    pub async fn get_block_hashes(&self, chain_id: u32) -> Result<Vec<(u64, String)>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT block_number, block_hash FROM block_hashes
+             WHERE chain_id = $1
+             ORDER BY block_number DESC",
+            &[&(chain_id as i32)],
+        ).await?;
+
+        Ok(rows.iter().map(|r| (r.get::<_, i64>(0) as u64, r.get(1))).collect())
+    }
+
+    /// Remove everything indexed from blocks after `fork_block` on a chain
+    ///
+    /// Runs in one transaction: rows emitted after the fork are deleted,
+    /// destination legs of Fusion+ swaps are reset to pending, withdrawals and
+    /// cancellations after the fork are undone through the Fusion+ timeline,
+    /// `token_stats` hours of the deleted transfers are recounted, and the
+    /// checkpoint is rewound to `fork_block`.
+    pub async fn rollback_to_block(&self, chain_id: u32, fork_block: u64) -> Result<RollbackStats, DbError> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        let chain = chain_id as i32;
+        let block = fork_block as i64;
+
+        let stats_hours = Self::take_token_stats_hours(&tx, chain, block + 1, i64::MAX).await?;
+        let mut stats = Self::delete_decoded_rows(&tx, chain, block + 1, i64::MAX).await?;
+
+        // Native transfers have no raw log to re-decode, so only a reorg removes them
+        stats.transfers_deleted += tx.execute(
+            "DELETE FROM transfers WHERE chain_id = $1 AND block_number > $2 AND token = $3",
+            &[&chain, &block, &NATIVE_TOKEN],
+        ).await? as usize;
+        Self::recount_token_stats(&tx, chain, &stats_hours).await?;
+        tx.execute(
+            "DELETE FROM raw_logs WHERE chain_id = $1 AND block_number > $2",
+            &[&chain, &block],
+        ).await?;
+        tx.execute(
+            "DELETE FROM internal_transfers WHERE chain_id = $1 AND block_number > $2",
+            &[&chain, &block],
+        ).await?;
+        tx.execute(
+            "DELETE FROM block_hashes WHERE chain_id = $1 AND block_number > $2",
+            &[&chain, &block],
+        ).await?;
+        tx.execute(
+            "DELETE FROM processed_ranges WHERE chain_id = $1 AND from_block > $2",
+            &[&chain, &block],
+        ).await?;
+        tx.execute(
+            "UPDATE processed_ranges SET to_block = $2 WHERE chain_id = $1 AND to_block > $2",
+            &[&chain, &block],
+        ).await?;
+        tx.execute(
+            "UPDATE checkpoints SET block_number = $2, updated_at = EXTRACT(EPOCH FROM NOW())::BIGINT
+             WHERE chain_id = $1",
+            &[&chain, &block],
+        ).await?;
+
+        tx.commit().await?;
+
+        Ok(stats)
+    }
+
+    /// Remove rows decoded from blocks `from_block..=to_block` so they can be re-decoded
+    ///
+    /// Archived raw logs, block hashes, native transfers (which have no raw
+    /// log to re-decode) and the checkpoint are kept. Fusion+
+    /// swaps whose source leg is in the range are deleted; their destination
+    /// legs come back only when the destination chain is replayed too.
+    /// `token_stats` hours are recounted without the deleted transfers, and
+    /// the next rollup counts the replayed ones again.
+    pub async fn clear_decoded_range(&self, chain_id: u32, from_block: u64, to_block: u64) -> Result<RollbackStats, DbError> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let (chain, from_block, to_block) = (chain_id as i32, from_block as i64, to_block as i64);
+        let stats_hours = Self::take_token_stats_hours(&tx, chain, from_block, to_block).await?;
+        let stats = Self::delete_decoded_rows(&tx, chain, from_block, to_block).await?;
+        Self::recount_token_stats(&tx, chain, &stats_hours).await?;
+
+        tx.commit().await?;
+
+        Ok(stats)
+    }
+
+    /// Delete the `token_stats` hours of transfers in a block range, returning
+    /// them as (tokens, hours) for `recount_token_stats` once the transfers are gone
+    async fn take_token_stats_hours(
+        tx: &Transaction<'_>,
+        chain: i32,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<(Vec<String>, Vec<i64>), DbError> {
+        let rows = tx.query(
+            "DELETE FROM token_stats s
+             USING (
+                 SELECT DISTINCT token, block_timestamp / 3600 * 3600 AS hour
+                 FROM transfers WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3
+             ) touched
+             WHERE s.chain_id = $1 AND s.token = touched.token AND s.hour = touched.hour
+             RETURNING s.token, s.hour",
+            &[&chain, &from_block, &to_block],
+        ).await?;
+
+        Ok(rows.iter().map(|r| (r.get::<_, String>(0), r.get::<_, i64>(1))).unzip())
+    }
+
+    /// Roll up `hours` again from the transfers left in them; emptied hours stay deleted
+    async fn recount_token_stats(tx: &Transaction<'_>, chain: i32, hours: &(Vec<String>, Vec<i64>)) -> Result<(), DbError> {
+        if hours.0.is_empty() {
+            return Ok(());
+        }
+        tx.execute(
+            "INSERT INTO token_stats (chain_id, token, hour, transfer_count, volume, updated_at)
+             SELECT t.chain_id, t.token, h.hour, COUNT(*), COALESCE(SUM(t.value_decimal::NUMERIC), 0), $4
+             FROM UNNEST($2::VARCHAR[], $3::BIGINT[]) AS h(token, hour)
+             JOIN transfers t
+               ON t.chain_id = $1 AND t.token = h.token
+              AND t.block_timestamp >= h.hour AND t.block_timestamp < h.hour + 3600
+             GROUP BY t.chain_id, t.token, h.hour",
+            &[&chain, &hours.0, &hours.1, &(unix_now() as i64)],
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Delete decoded rows in a block range, except native transfers, and
+    /// reset Fusion+ dst legs in it
+    async fn delete_decoded_rows(
+        tx: &Transaction<'_>,
+        chain: i32,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<RollbackStats, DbError> {
+        let range: [&(dyn ToSql + Sync); 3] = [&chain, &from_block, &to_block];
+
+        let transfers_deleted = tx.execute(
+            "DELETE FROM transfers WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3 AND token <> $4",
+            &[&chain, &from_block, &to_block, &NATIVE_TOKEN],
+        ).await?;
+        let approvals_deleted = tx.execute(
+            "DELETE FROM approvals WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let fusion_plus_deleted = tx.execute(
+            "DELETE FROM fusion_plus_swaps WHERE src_chain_id = $1 AND src_block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        // Cancellations are found through the timeline, which is deleted next
+        let fusion_plus_cancel_reset = tx.execute(
+            "UPDATE fusion_plus_swaps s SET
+                src_status = CASE WHEN e.event_type = 'src_cancelled' THEN 'created' ELSE s.src_status END,
+                src_cancelled_tx_hash = CASE WHEN e.event_type = 'src_cancelled' THEN NULL ELSE s.src_cancelled_tx_hash END,
+                src_cancelled_at = CASE WHEN e.event_type = 'src_cancelled' THEN NULL ELSE s.src_cancelled_at END,
+                dst_status = CASE WHEN e.event_type = 'dst_cancelled' THEN 'created' ELSE s.dst_status END,
+                dst_cancelled_tx_hash = CASE WHEN e.event_type = 'dst_cancelled' THEN NULL ELSE s.dst_cancelled_tx_hash END,
+                dst_cancelled_at = CASE WHEN e.event_type = 'dst_cancelled' THEN NULL ELSE s.dst_cancelled_at END,
+                updated_at = EXTRACT(EPOCH FROM NOW())::BIGINT
+             FROM fusion_plus_events e
+             WHERE e.order_hash = s.order_hash
+               AND e.chain_id = $1 AND e.block_number BETWEEN $2 AND $3
+               AND e.event_type IN ('src_cancelled', 'dst_cancelled')",
+            &range,
+        ).await?;
+        // So are withdrawals: the leg goes back to created, or to secret_revealed
+        // while the other leg's withdrawal stands, and the secret is forgotten
+        // once neither does. A dst withdrawal overwrote the dst coordinates, so
+        // those of its DstEscrowCreated are restored when that stands
+        let fusion_plus_withdrawal_reset = tx.execute(
+            "WITH reverted AS (
+                SELECT order_hash,
+                       bool_or(event_type = 'src_withdrawn') AS src,
+                       bool_or(event_type = 'dst_withdrawn') AS dst
+                FROM fusion_plus_events
+                WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3
+                  AND event_type IN ('src_withdrawn', 'dst_withdrawn')
+                GROUP BY order_hash
+            ), kept AS (
+                SELECT DISTINCT ON (e.order_hash) e.order_hash, e.chain_id, e.block_timestamp
+                FROM fusion_plus_events e JOIN reverted r ON r.order_hash = e.order_hash
+                WHERE e.event_type IN ('src_withdrawn', 'dst_withdrawn')
+                  AND NOT (e.chain_id = $1 AND e.block_number BETWEEN $2 AND $3)
+                ORDER BY e.order_hash, e.block_timestamp
+            ), dst_created AS (
+                SELECT DISTINCT ON (e.order_hash) e.order_hash, e.tx_hash, e.block_number, e.block_timestamp, e.log_index
+                FROM fusion_plus_events e JOIN reverted r ON r.order_hash = e.order_hash AND r.dst
+                WHERE e.event_type = 'dst_created'
+                  AND NOT (e.chain_id = $1 AND e.block_number BETWEEN $2 AND $3)
+                ORDER BY e.order_hash, e.block_number
+            ), legs AS (
+                SELECT s.order_hash, r.src, r.dst, k.chain_id, k.block_timestamp,
+                       s.src_status = 'withdrawn' AND NOT r.src OR s.dst_status = 'withdrawn' AND NOT r.dst AS revealed,
+                       c.order_hash IS NOT NULL AS dst_created, c.tx_hash AS dst_tx_hash, c.block_number AS dst_block_number,
+                       c.block_timestamp AS dst_block_timestamp, c.log_index AS dst_log_index
+                FROM fusion_plus_swaps s
+                JOIN reverted r ON r.order_hash = s.order_hash
+                LEFT JOIN kept k ON k.order_hash = s.order_hash
+                LEFT JOIN dst_created c ON c.order_hash = s.order_hash
+            )
+            UPDATE fusion_plus_swaps s SET
+                src_status = CASE
+                    WHEN s.src_status = 'withdrawn' AND NOT l.src OR s.src_status NOT IN ('withdrawn', 'secret_revealed') THEN s.src_status
+                    WHEN l.revealed THEN 'secret_revealed'
+                    ELSE 'created' END,
+                src_withdrawer = CASE WHEN l.src THEN NULL ELSE s.src_withdrawer END,
+                src_withdrawal_phase = CASE WHEN l.src THEN NULL ELSE s.src_withdrawal_phase END,
+                dst_status = CASE
+                    WHEN s.dst_status = 'withdrawn' AND NOT l.dst OR s.dst_status NOT IN ('withdrawn', 'secret_revealed') THEN s.dst_status
+                    WHEN l.revealed THEN 'secret_revealed'
+                    WHEN s.dst_timelocks IS NULL THEN 'pending'
+                    ELSE 'created' END,
+                dst_tx_hash = CASE WHEN l.dst_created THEN l.dst_tx_hash ELSE s.dst_tx_hash END,
+                dst_block_number = CASE WHEN l.dst_created THEN l.dst_block_number ELSE s.dst_block_number END,
+                dst_block_timestamp = CASE WHEN l.dst_created THEN l.dst_block_timestamp ELSE s.dst_block_timestamp END,
+                dst_log_index = CASE WHEN l.dst_created THEN l.dst_log_index ELSE s.dst_log_index END,
+                dst_withdrawer = CASE WHEN l.dst THEN NULL ELSE s.dst_withdrawer END,
+                dst_withdrawal_phase = CASE WHEN l.dst THEN NULL ELSE s.dst_withdrawal_phase END,
+                secret = CASE WHEN l.revealed THEN s.secret END,
+                secret_valid = CASE WHEN l.revealed THEN s.secret_valid END,
+                secret_revealed_chain_id = CASE WHEN l.revealed THEN COALESCE(l.chain_id, s.secret_revealed_chain_id) END,
+                secret_revealed_at = CASE WHEN l.revealed THEN COALESCE(l.block_timestamp, s.secret_revealed_at) END,
+                updated_at = EXTRACT(EPOCH FROM NOW())::BIGINT
+             FROM legs l
+             WHERE s.order_hash = l.order_hash",
+            &range,
+        ).await?;
+        // Fills are matched to the withdrawal by the emitting escrow, as when it was applied
+        tx.execute(
+            "WITH reverted AS (
+                SELECT f.id,
+                       bool_or(e.event_type = 'src_withdrawn') AS src,
+                       bool_or(e.event_type = 'dst_withdrawn') AS dst
+                FROM fusion_plus_events e
+                JOIN fusion_plus_fills f ON f.order_hash = e.order_hash AND CASE e.event_type
+                    WHEN 'src_withdrawn' THEN f.src_escrow_address = e.escrow_address
+                        OR f.src_escrow_address IS NULL AND f.src_chain_id = e.chain_id
+                    ELSE f.dst_escrow_address = e.escrow_address
+                        OR f.dst_escrow_address IS NULL AND f.dst_chain_id = e.chain_id END
+                WHERE e.chain_id = $1 AND e.block_number BETWEEN $2 AND $3
+                  AND e.event_type IN ('src_withdrawn', 'dst_withdrawn')
+                GROUP BY f.id
+            ), legs AS (
+                SELECT f.id, r.src, r.dst,
+                       f.src_status = 'withdrawn' AND NOT r.src OR f.dst_status = 'withdrawn' AND NOT r.dst AS revealed
+                FROM fusion_plus_fills f JOIN reverted r ON r.id = f.id
+            )
+            UPDATE fusion_plus_fills f SET
+                src_status = CASE
+                    WHEN f.src_status = 'withdrawn' AND NOT l.src OR f.src_status NOT IN ('withdrawn', 'secret_revealed') THEN f.src_status
+                    WHEN l.revealed THEN 'secret_revealed'
+                    ELSE 'created' END,
+                dst_status = CASE
+                    WHEN f.dst_status = 'withdrawn' AND NOT l.dst OR f.dst_status NOT IN ('withdrawn', 'secret_revealed') THEN f.dst_status
+                    WHEN l.revealed THEN 'secret_revealed'
+                    WHEN f.dst_block_number IS NULL THEN 'pending'
+                    ELSE 'created' END,
+                secret = CASE WHEN l.revealed THEN f.secret END
+             FROM legs l
+             WHERE f.id = l.id",
+            &range,
+        ).await?;
+        tx.execute(
+            "DELETE FROM fusion_plus_fills WHERE src_chain_id = $1 AND src_block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        tx.execute(
+            "UPDATE fusion_plus_fills SET
+                dst_tx_hash = NULL,
+                dst_block_number = NULL,
+                dst_escrow_address = NULL,
+                dst_status = 'pending'
+             WHERE dst_chain_id = $1 AND dst_block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let fusion_plus_events_deleted = tx.execute(
+            "DELETE FROM fusion_plus_events WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let fusion_plus_dst_reset = tx.execute(
+            "UPDATE fusion_plus_swaps SET
+                dst_tx_hash = NULL,
+                dst_block_number = NULL,
+                dst_block_timestamp = NULL,
+                dst_log_index = NULL,
+                dst_escrow_address = NULL,
+                dst_taker = NULL,
+                dst_timelocks = NULL,
+                dst_status = 'pending',
+                updated_at = EXTRACT(EPOCH FROM NOW())::BIGINT
+             WHERE dst_chain_id = $1 AND dst_block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let fusion_deleted = tx.execute(
+            "DELETE FROM fusion_swaps WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let fusion_cancel_reset = tx.execute(
+            "UPDATE fusion_swaps SET
+                status = 'filled',
+                cancelled_tx_hash = NULL,
+                cancelled_block_number = NULL
+             WHERE chain_id = $1 AND cancelled_block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let crypto2fiat_deleted = tx.execute(
+            "DELETE FROM crypto2fiat_events WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let raw_events_deleted = tx.execute(
+            "DELETE FROM raw_events WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let delegations_deleted = tx.execute(
+            "DELETE FROM delegations WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+        let transactions_deleted = tx.execute(
+            "DELETE FROM transactions WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3",
+            &range,
+        ).await?;
+
+        Ok(RollbackStats {
+            transfers_deleted: transfers_deleted as usize,
+            approvals_deleted: approvals_deleted as usize,
+            fusion_plus_deleted: fusion_plus_deleted as usize,
+            fusion_plus_events_deleted: fusion_plus_events_deleted as usize,
+            fusion_plus_cancel_reset: fusion_plus_cancel_reset as usize,
+            fusion_plus_withdrawal_reset: fusion_plus_withdrawal_reset as usize,
+            fusion_plus_dst_reset: fusion_plus_dst_reset as usize,
+            fusion_deleted: fusion_deleted as usize,
+            fusion_cancel_reset: fusion_cancel_reset as usize,
+            crypto2fiat_deleted: crypto2fiat_deleted as usize,
+            raw_events_deleted: raw_events_deleted as usize,
+            delegations_deleted: delegations_deleted as usize,
+            transactions_deleted: transactions_deleted as usize,
+        })
+    }
+
+    /// Clean up old transfers based on TTL
+    pub async fn cleanup_old_transfers(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        if self.transfers_partitioned {
+            return self.rotate_transfer_partitions(ttl_secs).await;
+        }
+
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("transfers", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    /// Get total count of transfers for a chain
+    pub async fn get_transfer_count(&self, chain_id: u32) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one(
+            "SELECT COUNT(*) FROM transfers WHERE chain_id = $1",
+            &[&(chain_id as i32)],
+        ).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Get total transfer count across all chains
+    pub async fn get_total_transfer_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one(
+            "SELECT COUNT(*) FROM transfers",
+            &[],
+        ).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Label transfers in a transaction with swap_type
+    pub async fn label_transfers_as_fusion(&self, chain_id: u32, tx_hash: &str, swap_type: &str) -> Result<usize, DbError> {
+        let client = self.pool.get().await?;
+
+        let result = client.execute(
+            "UPDATE transfers SET swap_type = $1 WHERE chain_id = $2 AND tx_hash = $3",
+            &[&swap_type, &(chain_id as i32), &tx_hash.to_lowercase()],
+        ).await?;
+
+        Ok(result as usize)
+    }
+
+    /// Map a row selected as (tx_hash, log_index, token, from_addr, to_addr, value,
+    /// block_number, block_timestamp, swap_type, value_decimal) joined with the
+    /// tokens columns (address, symbol, name, decimals), the row id and tx_status to a Transfer
+    fn row_to_transfer(row: &Row, chain_id: u32) -> Transfer {
+        Transfer {
+            chain_id,
+            tx_hash: row.get(0),
+            log_index: row.get::<_, i32>(1) as u32,
+            token: row.get(2),
+            from_addr: row.get(3),
+            to_addr: row.get(4),
+            value: row.get(5),
+            value_decimal: row.get(9),
+            block_number: row.get::<_, i64>(6) as u64,
+            block_timestamp: row.get::<_, i64>(7) as u64,
+            swap_type: row.get(8),
+            tx_status: row.get::<_, Option<i16>>(15).map(|s| s as u8),
+            token_info: row.get::<_, Option<&str>>(10).map(|_| TokenInfo {
+                symbol: row.get(11),
+                name: row.get(12),
+                decimals: row.get::<_, Option<i16>>(13).map(|d| d as u8),
+            }),
+            id: Some(row.get(14)),
+        }
+    }
+
+    /// Get a random sample of log-backed transfers for a chain (used by `verify`)
+    pub async fn sample_transfers(&self, chain_id: u32, limit: u32) -> Result<Vec<Transfer>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND t.token <> $3
+             ORDER BY random()
+             LIMIT $2",
+            &[&(chain_id as i32), &(limit as i64), &NATIVE_TOKEN],
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get transfers sent by an address, most recent first unless paging with `since_id`
+    pub async fn get_transfers_by_from(&self, chain_id: u32, address: &str, cursor: &Cursor, limit: u32) -> Result<Vec<Transfer>, DbError> {
+        let rows = self.query_page(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND from_addr = $2",
+            &[&(chain_id as i32), &address.to_lowercase()],
+            ("t.id", "t.block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get transfers received by an address, most recent first unless paging with `since_id`
+    pub async fn get_transfers_by_to(&self, chain_id: u32, address: &str, cursor: &Cursor, limit: u32) -> Result<Vec<Transfer>, DbError> {
+        let rows = self.query_page(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND to_addr = $2",
+            &[&(chain_id as i32), &address.to_lowercase()],
+            ("t.id", "t.block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get transfers of a token contract, most recent first unless paging with `since_id`
+    pub async fn get_transfers_by_token(&self, chain_id: u32, token: &str, cursor: &Cursor, limit: u32) -> Result<Vec<Transfer>, DbError> {
+        let rows = self.query_page(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND t.token = $2",
+            &[&(chain_id as i32), &token.to_lowercase()],
+            ("t.id", "t.block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get transfers sent or received by any of `addresses` in one query,
+    /// most recent first unless paging with `since_id`
+    ///
+    /// For wallets tracking many accounts; callers should pass at most
+    /// `MAX_BATCH_ADDRESSES`. A transfer between two of the addresses is returned once.
+    pub async fn get_transfers_by_addresses(
+        &self,
+        chain_id: u32,
+        addresses: &[&str],
+        cursor: &Cursor,
+        limit: u32,
+    ) -> Result<Vec<Transfer>, DbError> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let addresses: Vec<String> = addresses.iter().map(|a| a.to_lowercase()).collect();
+        let rows = self.query_page(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND (from_addr = ANY($2) OR to_addr = ANY($2))",
+            &[&(chain_id as i32), &addresses],
+            ("t.id", "t.block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get transfers sent by an address on every indexed chain, merged in cursor order
+    ///
+    /// Runs one query per chain concurrently so each uses the per-chain index.
+    pub async fn get_transfers_by_from_all_chains(&self, address: &str, cursor: &Cursor, limit: u32) -> Result<Vec<Transfer>, DbError> {
+        let chains = self.get_indexed_chains().await?;
+        let pages = try_join_all(
+            chains.into_iter().map(|chain_id| self.get_transfers_by_from(chain_id, address, cursor, limit)),
+        ).await?;
+        Ok(merge_transfer_pages(pages, cursor, limit))
+    }
+
+    /// Get transfers received by an address on every indexed chain, merged in cursor order
+    pub async fn get_transfers_by_to_all_chains(&self, address: &str, cursor: &Cursor, limit: u32) -> Result<Vec<Transfer>, DbError> {
+        let chains = self.get_indexed_chains().await?;
+        let pages = try_join_all(
+            chains.into_iter().map(|chain_id| self.get_transfers_by_to(chain_id, address, cursor, limit)),
+        ).await?;
+        Ok(merge_transfer_pages(pages, cursor, limit))
+    }
+
+    /// Stream transfers sent by an address in `cursor` order, without a row limit
+    pub async fn stream_transfers_by_from(
+        &self,
+        chain_id: u32,
+        address: &str,
+        cursor: &Cursor,
+    ) -> Result<TransferStream, DbError> {
+        self.stream_transfers(chain_id, "from_addr", address, cursor).await
+    }
+
+    /// Stream transfers received by an address in `cursor` order, without a row limit
+    pub async fn stream_transfers_by_to(
+        &self,
+        chain_id: u32,
+        address: &str,
+        cursor: &Cursor,
+    ) -> Result<TransferStream, DbError> {
+        self.stream_transfers(chain_id, "to_addr", address, cursor).await
+    }
+
+    /// Stream transfers of a token contract in `cursor` order, without a row limit
+    pub async fn stream_transfers_by_token(
+        &self,
+        chain_id: u32,
+        token: &str,
+        cursor: &Cursor,
+    ) -> Result<TransferStream, DbError> {
+        self.stream_transfers(chain_id, "token", token, cursor).await
+    }
+
+    /// Stream the transfers whose `column` equals `value`
+    ///
+    /// Rows are decoded as they arrive from the server instead of being
+    /// collected, so memory stays flat however many match. The stream holds a
+    /// pooled connection until it is dropped.
+    async fn stream_transfers(
+        &self,
+        chain_id: u32,
+        column: &str,
+        value: &str,
+        cursor: &Cursor,
+    ) -> Result<TransferStream, DbError> {
+        let client = self.pool.get().await?;
+
+        let (keyset, order_by, values) = keyset_sql(cursor, "t.id", "t.block_timestamp", 3);
+        let chain = chain_id as i32;
+        let value = value.to_lowercase();
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![&chain, &value];
+        params.extend(values.iter().map(|v| v as &(dyn ToSql + Sync)));
+
+        let sql = format!(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND t.{} = $2{}
+             ORDER BY {}",
+            column, keyset, order_by
+        );
+        let rows = client.query_raw(sql.as_str(), params).await?;
+
+        Ok(rows
+            .map(move |row| {
+                // Keep the connection checked out while rows are still arriving
+                let _ = &client;
+                Ok(Self::row_to_transfer(&row?, chain_id))
+            })
+            .boxed())
+    }
+
+    /// Get transfers in blocks `from_block..=to_block` in chain order, for
+    /// reconciling blocks against another indexer
+    pub async fn get_transfers_by_block_range(
+        &self,
+        chain_id: u32,
+        from_block: u64,
+        to_block: u64,
+        limit: u32,
+    ) -> Result<Vec<Transfer>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND t.block_number BETWEEN $2 AND $3
+             ORDER BY t.block_number, t.log_index
+             LIMIT $4",
+            &[&(chain_id as i32), &(from_block as i64), &(to_block.min(i64::MAX as u64) as i64), &(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get all transfers in a transaction, ordered by log_index
+    pub async fn get_transfers_by_tx_hash(&self, chain_id: u32, tx_hash: &str) -> Result<Vec<Transfer>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND tx_hash = $2
+             ORDER BY log_index ASC",
+            &[&(chain_id as i32), &tx_hash.to_lowercase()],
+        ).await?;
+
+        Ok(rows.iter().map(|r| Self::row_to_transfer(r, chain_id)).collect())
+    }
+
+    /// Get first and last transfers for a transaction (by log_index)
+    /// Returns (first_transfer, last_transfer) for populating swap maker/taker info
+    pub async fn get_first_last_transfers(&self, chain_id: u32, tx_hash: &str) -> Result<Option<(Transfer, Transfer)>, DbError> {
+        let client = self.pool.get().await?;
+        let tx_hash_lower = tx_hash.to_lowercase();
+
+        // Get first transfer (lowest log_index)
+        let first_row = client.query_opt(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND tx_hash = $2
+             ORDER BY log_index ASC
+             LIMIT 1",
+            &[&(chain_id as i32), &tx_hash_lower],
+        ).await?;
+
+        // Get last transfer (highest log_index)
+        let last_row = client.query_opt(
+            "SELECT tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp, swap_type, value_decimal,
+                    k.address, k.symbol, k.name, k.decimals, t.id, t.tx_status
+             FROM transfers t
+             LEFT JOIN tokens k ON k.chain_id = t.chain_id AND k.address = t.token
+             WHERE t.chain_id = $1 AND tx_hash = $2
+             ORDER BY log_index DESC
+             LIMIT 1",
+            &[&(chain_id as i32), &tx_hash_lower],
+        ).await?;
+
+        match (first_row, last_row) {
+            (Some(first), Some(last)) => Ok(Some((
+                Self::row_to_transfer(&first, chain_id),
+                Self::row_to_transfer(&last, chain_id),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    // =========================================================================
+    // Search
+    // =========================================================================
+
+    /// Find stored tx hashes, order hashes and addresses starting with `prefix`
+    ///
+    /// `prefix` is hex with or without `0x` and at least `MIN_SEARCH_DIGITS`
+    /// digits; anything else matches nothing. Up to `limit` distinct values
+    /// are returned per searched column.
+    pub async fn search(&self, prefix: &str, limit: u32) -> Result<Vec<SearchHit>, DbError> {
+        let Some(prefix) = normalize_search_prefix(prefix) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = SEARCH_COLUMNS
+            .iter()
+            .map(|(table, chain_column, column)| {
+                format!(
+                    "(SELECT DISTINCT '{table}', '{column}', {chain_column}, {column}::TEXT
+                      FROM {table} WHERE {column} LIKE $1 LIMIT $2)"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+
+        let client = self.pool.get().await?;
+        let pattern = format!("{}%", prefix);
+        let rows = client.query(sql.as_str(), &[&pattern, &(limit as i64)]).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| SearchHit {
+                table: r.get(0),
+                field: r.get(1),
+                chain_id: r.get::<_, i32>(2) as u32,
+                value: r.get(3),
+            })
+            .collect())
+    }
+
+    // =========================================================================
+    // Token Metadata Methods
+    // =========================================================================
+
+    /// Return which of `tokens` need no fetch, with their `metadata_status`
+    ///
+    /// Complete rows are always returned; incomplete ones only when fetched at
+    /// or after `refetch_before`.
+    pub async fn get_cached_tokens(
+        &self,
+        chain_id: u32,
+        tokens: &[String],
+        refetch_before: u64,
+    ) -> Result<Vec<(String, String)>, DbError> {
+        if tokens.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let client = self.pool.get().await?;
+        let rows = client.query(
+            "SELECT address, metadata_status FROM tokens
+             WHERE chain_id = $1 AND address = ANY($2)
+               AND (metadata_status = 'complete' OR fetched_at >= $3)",
+            &[&(chain_id as i32), &tokens, &(refetch_before as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(|r| (r.get(0), r.get(1))).collect())
+    }
+
+    /// Store (or refresh) the metadata of a token
+    pub async fn upsert_token(&self, chain_id: u32, address: &str, info: &TokenInfo) -> Result<(), DbError> {
+        let client = self.pool.get().await?;
+        client.execute(
+            "INSERT INTO tokens (chain_id, address, symbol, name, decimals, fetched_at, metadata_status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (chain_id, address) DO UPDATE
+             SET symbol = EXCLUDED.symbol, name = EXCLUDED.name,
+                 decimals = EXCLUDED.decimals, fetched_at = EXCLUDED.fetched_at,
+                 metadata_status = EXCLUDED.metadata_status",
+            &[
+                &(chain_id as i32),
+                &address.to_lowercase(),
+                &info.symbol,
+                &info.name,
+                &info.decimals.map(i16::from),
+                &(unix_now() as i64),
+                &info.status(),
+            ],
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Get cached metadata of a token
+    pub async fn get_token(&self, chain_id: u32, address: &str) -> Result<Option<TokenInfo>, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt(
+            "SELECT symbol, name, decimals FROM tokens WHERE chain_id = $1 AND address = $2",
+            &[&(chain_id as i32), &address.to_lowercase()],
+        ).await?;
+
+        Ok(row.map(|r| TokenInfo {
+            symbol: r.get(0),
+            name: r.get(1),
+            decimals: r.get::<_, Option<i16>>(2).map(|d| d as u8),
+        }))
+    }
+
+    // =========================================================================
+    // Balance Delta Methods
+    // =========================================================================
+
+    /// Compute net inflow/outflow per token for an address over a time window
+    ///
+    /// Window bounds are inclusive block timestamps. Values are summed in SQL,
+    /// one row per token, in raw token units and scaled by the token's cached
+    /// decimals; rows stored in compact mode carry no value and are counted in
+    /// unvalued_count instead.
+    pub async fn get_balance_deltas(
+        &self,
+        chain_id: u32,
+        address: &str,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Result<Vec<BalanceDelta>, DbError> {
+        let client = self.pool.get().await?;
+        let address_lower = address.to_lowercase();
+
+        let rows = client.query(
+            "SELECT per_token.token, inflow::TEXT, outflow::TEXT, (inflow - outflow)::TEXT, k.decimals,
+                    transfer_count, unvalued_count
+             FROM (
+                 SELECT token, COUNT(*) AS transfer_count,
+                        COUNT(*) FILTER (WHERE value_decimal IS NULL) AS unvalued_count,
+                        COALESCE(SUM(value_decimal::NUMERIC) FILTER (WHERE to_addr = $2), 0) AS inflow,
+                        COALESCE(SUM(value_decimal::NUMERIC) FILTER (WHERE from_addr = $2), 0) AS outflow
+                 FROM transfers
+                 WHERE chain_id = $1 AND (from_addr = $2 OR to_addr = $2)
+                   AND block_timestamp BETWEEN $3 AND $4
+                 GROUP BY token
+             ) per_token
+             LEFT JOIN tokens k ON k.chain_id = $1 AND k.address = per_token.token
+             ORDER BY per_token.token",
+            &[
+                &(chain_id as i32),
+                &address_lower,
+                &(from_timestamp as i64),
+                &(to_timestamp as i64),
+            ],
+        ).await?;
+
+        Ok(rows.iter().map(|r| balance_delta(chain_id, &address_lower, r)).collect())
+    }
+
+    /// Compute net inflow/outflow of a single token for an address over a time window
+    pub async fn get_balance_delta(
+        &self,
+        chain_id: u32,
+        address: &str,
+        token: &str,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Result<BalanceDelta, DbError> {
+        let client = self.pool.get().await?;
+        let address_lower = address.to_lowercase();
+        let token_lower = token.to_lowercase();
+
+        let row = client.query_opt(
+            "SELECT per_token.token, inflow::TEXT, outflow::TEXT, (inflow - outflow)::TEXT, k.decimals,
+                    transfer_count, unvalued_count
+             FROM (
+                 SELECT token, COUNT(*) AS transfer_count,
+                        COUNT(*) FILTER (WHERE value_decimal IS NULL) AS unvalued_count,
+                        COALESCE(SUM(value_decimal::NUMERIC) FILTER (WHERE to_addr = $2), 0) AS inflow,
+                        COALESCE(SUM(value_decimal::NUMERIC) FILTER (WHERE from_addr = $2), 0) AS outflow
+                 FROM transfers
+                 WHERE chain_id = $1 AND token = $5 AND (from_addr = $2 OR to_addr = $2)
+                   AND block_timestamp BETWEEN $3 AND $4
+                 GROUP BY token
+             ) per_token
+             LEFT JOIN tokens k ON k.chain_id = $1 AND k.address = per_token.token",
+            &[
+                &(chain_id as i32),
+                &address_lower,
+                &(from_timestamp as i64),
+                &(to_timestamp as i64),
+                &token_lower,
+            ],
+        ).await?;
+
+        let delta = row.map(|r| balance_delta(chain_id, &address_lower, &r)).unwrap_or(BalanceDelta {
+            chain_id,
+            address: address_lower,
+            token: token_lower,
+            inflow: "0".to_string(),
+            outflow: "0".to_string(),
+            net: "0".to_string(),
+            decimals: None,
+            inflow_formatted: None,
+            outflow_formatted: None,
+            net_formatted: None,
+            transfer_count: 0,
+            unvalued_count: 0,
+        });
+
+        Ok(delta)
+    }
+
+    /// Summarize an address's transfers over a time window, on one chain or all
+    ///
+    /// Counts, timestamps and per-token sums are aggregated in SQL, so only one
+    /// row per token leaves the database.
+    pub async fn get_address_activity(
+        &self,
+        chain_id: Option<u32>,
+        address: &str,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Result<AddressActivity, DbError> {
+        let client = self.pool.get().await?;
+        let address_lower = address.to_lowercase();
+        let chain_id = chain_id.map(|id| id as i32);
+
+        let rows = client.query(
+            "SELECT chain_id, token, transfer_count, inflow::TEXT, outflow::TEXT, (inflow - outflow)::TEXT,
+                    first_seen, last_seen
+             FROM (
+                 SELECT chain_id, token, COUNT(*) AS transfer_count,
+                        COALESCE(SUM(value_decimal::NUMERIC) FILTER (WHERE to_addr = $1), 0) AS inflow,
+                        COALESCE(SUM(value_decimal::NUMERIC) FILTER (WHERE from_addr = $1), 0) AS outflow,
+                        MIN(block_timestamp) AS first_seen, MAX(block_timestamp) AS last_seen
+                 FROM transfers
+                 WHERE (from_addr = $1 OR to_addr = $1)
+                   AND block_timestamp BETWEEN $2 AND $3
+                   AND ($4::INTEGER IS NULL OR chain_id = $4)
+                 GROUP BY chain_id, token
+             ) per_token
+             ORDER BY transfer_count DESC, chain_id, token",
+            &[&address_lower, &(from_timestamp as i64), &(to_timestamp as i64), &chain_id],
+        ).await?;
+
+        let tokens: Vec<TokenActivity> = rows
+            .iter()
+            .map(|r| TokenActivity {
+                chain_id: r.get::<_, i32>(0) as u32,
+                token: r.get(1),
+                transfer_count: r.get::<_, i64>(2) as u64,
+                inflow: r.get(3),
+                outflow: r.get(4),
+                net: r.get(5),
+                first_seen: r.get::<_, i64>(6) as u64,
+                last_seen: r.get::<_, i64>(7) as u64,
+            })
+            .collect();
+
+        Ok(AddressActivity {
+            address: address_lower,
+            transfer_count: tokens.iter().map(|t| t.transfer_count).sum(),
+            token_count: tokens.len() as u64,
+            first_seen: tokens.iter().map(|t| t.first_seen).min(),
+            last_seen: tokens.iter().map(|t| t.last_seen).max(),
+            tokens,
+        })
+    }
+
+    // =========================================================================
+    // Fusion+ Methods
+    // =========================================================================
+
+    /// Insert a new Fusion+ swap
+    #[instrument(skip_all)]
+    pub async fn insert_fusion_plus_swap(&self, swap: &FusionPlusSwap) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        Self::exec_insert_fusion_plus_swap(&client, swap, now).await
+    }
+
+    /// Update swap with destination data
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_fusion_plus_dst(
+        &self,
+        order_hash: &str,
+        dst_data: &DstEscrowCreatedData,
+        chain_id: u32,
+        tx_hash: &str,
+        block_number: u64,
+        block_timestamp: u64,
+        log_index: u32,
+        escrow_address: Option<&str>,
+    ) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let dst = FusionPlusDst {
+            order_hash: order_hash.to_string(),
+            hashlock: dst_data.hashlock.clone(),
+            dst_taker: dst_data.dst_taker.clone(),
+            dst_timelocks: dst_data.dst_timelocks.clone(),
+            chain_id,
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            block_timestamp,
+            log_index,
+            escrow_address: escrow_address.map(str::to_string),
+        };
+        Self::exec_update_fusion_plus_dst(&client, &dst, now).await
+    }
+
+    /// Update swap status on withdrawal
+    pub async fn update_fusion_plus_withdrawal(
+        &self,
+        order_hash: &str,
+        chain_id: u32,
+        is_src: bool,
+        secret: &str,
+    ) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let hashlock = compute_hashlock_from_secret(secret);
+        let result = if is_src {
+            client.execute(
+                "UPDATE fusion_plus_swaps SET
+                    src_status = 'withdrawn',
+                    secret = $1,
+                    secret_valid = hashlock IS NOT DISTINCT FROM $5,
+                    updated_at = $2
+                 WHERE order_hash = $3 AND src_chain_id = $4",
+                &[
+                    &secret.to_lowercase(),
+                    &now,
+                    &order_hash.to_lowercase(),
+                    &(chain_id as i32),
+                    &hashlock,
+                ],
+            ).await?
+        } else {
+            client.execute(
+                "UPDATE fusion_plus_swaps SET
+                    dst_status = 'withdrawn',
+                    secret = $1,
+                    secret_valid = hashlock IS NOT DISTINCT FROM $5,
+                    updated_at = $2
+                 WHERE order_hash = $3 AND dst_chain_id = $4",
+                &[
+                    &secret.to_lowercase(),
+                    &now,
+                    &order_hash.to_lowercase(),
+                    &(chain_id as i32),
+                    &hashlock,
+                ],
+            ).await?
+        };
+
+        Ok(result > 0)
+    }
+
+    /// Update swap status on cancellation, recording the cancelling tx
+    pub async fn update_fusion_plus_cancelled(
+        &self,
+        order_hash: &str,
+        chain_id: u32,
+        is_src: bool,
+        tx_hash: &str,
+        block_timestamp: u64,
+    ) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let cancellation = FusionPlusCancellation {
+            escrow_address: String::new(),
+            chain_id,
+            tx_hash: tx_hash.to_string(),
+            block_number: 0,
+            block_timestamp,
+            log_index: 0,
+        };
+        Self::exec_update_fusion_plus_cancelled(&client, order_hash, &cancellation, is_src, now).await
+    }
+
+    /// Mark one leg withdrawn by hashlock and propagate the revealed secret
+    ///
+    /// The counterpart leg moves to `secret_revealed` unless it is already
+    /// withdrawn or cancelled. `secret_revealed_chain_id`/`_at` keep the chain
+    /// and block timestamp of the earliest reveal, whichever leg is indexed first.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_fusion_plus_withdrawal_by_hashlock(
+        &self,
+        hashlock: &str,
+        chain_id: u32,
+        is_src: bool,
+        secret: &str,
+        tx_hash: &str,
+        block_number: u64,
+        block_timestamp: u64,
+        log_index: u32,
+    ) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let withdrawal = FusionPlusWithdrawal {
+            hashlock: hashlock.to_string(),
+            escrow_address: String::new(),
+            chain_id,
+            secret: secret.to_string(),
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            block_timestamp,
+            log_index,
+            withdrawer: None,
+        };
+        let row = client.query_opt(
+            "SELECT order_hash FROM fusion_plus_swaps WHERE hashlock = $1",
+            &[&hashlock.to_lowercase()],
+        ).await?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let secret_valid = secret_matches_hashlock(secret, hashlock);
+        Self::exec_update_fusion_plus_withdrawal(&client, row.get(0), &withdrawal, is_src, None, secret_valid, now).await
+    }
+
+    /// Apply a poll cycle's Fusion+ changes in order, in one transaction
+    ///
+    /// Withdrawals and cancellations look their swap up inside the
+    /// transaction, so they see swaps created earlier in the same batch.
+    /// Returns, per change, the swap and leg it updated (None if it matched
+    /// nothing or the swap already existed).
+    #[instrument(skip_all, fields(changes = changes.len()))]
+    pub async fn apply_fusion_plus_changes(
+        &self,
+        changes: &[FusionPlusChange],
+    ) -> Result<Vec<Option<FusionPlusApplied>>, DbError> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let mut applied = Vec::with_capacity(changes.len());
+        for change in changes {
+            let outcome = match change {
+                FusionPlusChange::Created { swap, merkle_fill } => {
+                    // A later fill of a multiple-fill order only adds a fill
+                    let inserted = Self::exec_insert_fusion_plus_swap(&tx, swap, now).await?;
+                    let new_fill = Self::exec_insert_fusion_plus_fill(&tx, swap, merkle_fill.as_ref()).await?;
+                    (inserted || new_fill)
+                        .then(|| FusionPlusApplied { order_hash: swap.order_hash.to_lowercase(), is_src: true })
+                }
+                FusionPlusChange::DstCreated(dst) => {
+                    let fill_updated = Self::exec_update_fusion_plus_fill_dst(&tx, dst).await?;
+                    let updated = Self::exec_update_fusion_plus_dst(&tx, dst, now).await?;
+                    (updated || fill_updated)
+                        .then(|| FusionPlusApplied { order_hash: dst.order_hash.to_lowercase(), is_src: false })
+                }
+                FusionPlusChange::Withdrawn(withdrawal) => {
+                    // A known emitting escrow matches its swap even when the secret
+                    // doesn't hash to the stored hashlock, so the mismatch is recorded
+                    let row = tx.query_opt(
+                        "SELECT order_hash, src_chain_id, src_escrow_address, dst_escrow_address,
+                                src_timelocks, dst_timelocks, hashlock
+                         FROM fusion_plus_swaps
+                         WHERE hashlock = $1 OR src_escrow_address = $2 OR dst_escrow_address = $2
+                         ORDER BY hashlock = $1 DESC
+                         LIMIT 1
+                         FOR UPDATE",
+                        &[&withdrawal.hashlock.to_lowercase(), &withdrawal.escrow_address.to_lowercase()],
+                    ).await?;
+                    match row {
+                        Some(row) => {
+                            let order_hash: String = row.get(0);
+                            let hashlock: String = row.get(6);
+                            let secret_valid = secret_matches_hashlock(&withdrawal.secret, &hashlock);
+                            if !secret_valid {
+                                tracing::warn!(
+                                    "Fusion+ secret from escrow {} doesn't match hashlock {} of order {}",
+                                    withdrawal.escrow_address, hashlock, order_hash
+                                );
+                            }
+                            // The emitting escrow identifies the leg; fall back to chain_id
+                            // for swaps stored without escrow addresses
+                            let escrow = Some(withdrawal.escrow_address.to_lowercase());
+                            let is_src = if row.get::<_, Option<String>>(2) == escrow {
+                                true
+                            } else if row.get::<_, Option<String>>(3) == escrow {
+                                false
+                            } else {
+                                row.get::<_, i32>(1) as u32 == withdrawal.chain_id
+                            };
+                            let timelocks: Option<String> = row.get(if is_src { 4 } else { 5 });
+                            let phase = timelocks
+                                .and_then(|t| withdrawal_phase(&t, is_src, withdrawal.block_timestamp));
+                            let fill_updated = Self::exec_update_fusion_plus_fill_withdrawal(&tx, withdrawal).await?;
+                            let updated = Self::exec_update_fusion_plus_withdrawal(
+                                &tx, &order_hash, withdrawal, is_src, phase, secret_valid, now,
+                            ).await?;
+                            updated.then_some(FusionPlusApplied { order_hash, is_src }).or(fill_updated)
+                        }
+                        // A later fill of a multiple-fill order
+                        None => Self::exec_update_fusion_plus_fill_withdrawal(&tx, withdrawal).await?,
+                    }
+                }
+                FusionPlusChange::Cancelled(cancellation) => {
+                    let escrow = cancellation.escrow_address.to_lowercase();
+                    let mut row = tx.query_opt(
+                        "SELECT order_hash, src_escrow_address = $1
+                         FROM fusion_plus_swaps WHERE src_escrow_address = $1 OR dst_escrow_address = $1
+                         LIMIT 1
+                         FOR UPDATE",
+                        &[&escrow],
+                    ).await?;
+                    let fill_cancelled = Self::exec_update_fusion_plus_fill_cancelled(&tx, &escrow).await?;
+                    if row.is_none() && fill_cancelled.is_none() {
+                        row = Self::find_cancelled_swap_by_refund(&tx, cancellation).await?;
+                    }
+                    match row {
+                        Some(row) => {
+                            let order_hash: String = row.get(0);
+                            let is_src = row.get::<_, Option<bool>>(1).unwrap_or(false);
+                            Self::exec_update_fusion_plus_cancelled(&tx, &order_hash, cancellation, is_src, now)
+                                .await?
+                                .then_some(FusionPlusApplied { order_hash, is_src })
+                        }
+                        None => fill_cancelled,
+                    }
+                }
+            };
+            if let Some(outcome) = &outcome {
+                Self::exec_insert_fusion_plus_event(&tx, &outcome.order_hash, change, outcome.is_src).await?;
+            }
+            applied.push(outcome);
+        }
+
+        tx.commit().await?;
+        Ok(applied)
+    }
+
+    /// Record a source escrow as a fill of its order; false if already stored
+    ///
+    /// The leaf index, root and proof check come from the fill's Merkle proof,
+    /// when it was found for this escrow's order and hashlock.
+    async fn exec_insert_fusion_plus_fill(
+        client: &impl GenericClient,
+        swap: &FusionPlusSwap,
+        merkle_fill: Option<&MerkleFill>,
+    ) -> Result<bool, DbError> {
+        let merkle_fill = merkle_fill.filter(|m| {
+            m.order_hash.eq_ignore_ascii_case(&swap.order_hash) && m.secret_hash.eq_ignore_ascii_case(&swap.hashlock)
+        });
+        let result = client.execute(
+            "INSERT INTO fusion_plus_fills (
+                order_hash, hashlock,
+                src_chain_id, src_tx_hash, src_block_number, src_escrow_address, src_amount,
+                dst_chain_id, dst_amount,
+                fill_index, hashlock_root, proof_valid
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (order_hash, hashlock) DO NOTHING",
+            &[
+                &swap.order_hash.to_lowercase(),
+                &swap.hashlock.to_lowercase(),
+                &(swap.src_chain_id as i32),
+                &swap.src_tx_hash.to_lowercase(),
+                &(swap.src_block_number as i64),
+                &swap.src_escrow_address.as_ref().map(|s| s.to_lowercase()),
+                &swap.src_amount,
+                &(swap.dst_chain_id as i32),
+                &swap.dst_amount,
+                &merkle_fill.map(|m| m.index as i64),
+                &merkle_fill.map(|m| m.root.to_lowercase()),
+                &merkle_fill.map(|m| m.proof_valid),
+            ],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    /// Fill in the destination escrow of the fill with the same hashlock
+    async fn exec_update_fusion_plus_fill_dst(client: &impl GenericClient, dst: &FusionPlusDst) -> Result<bool, DbError> {
+        let result = client.execute(
+            "UPDATE fusion_plus_fills SET
+                dst_tx_hash = $1,
+                dst_block_number = $2,
+                dst_escrow_address = $3,
+                dst_status = CASE WHEN dst_status = 'secret_revealed' THEN dst_status ELSE 'created' END
+             WHERE order_hash = $4 AND hashlock = $5 AND dst_chain_id = $6",
+            &[
+                &dst.tx_hash.to_lowercase(),
+                &(dst.block_number as i64),
+                &dst.escrow_address.as_ref().map(|s| s.to_lowercase()),
+                &dst.order_hash.to_lowercase(),
+                &dst.hashlock.to_lowercase(),
+                &(dst.chain_id as i32),
+            ],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    /// Mark the fill whose hashlock the secret opens withdrawn on the emitting
+    /// escrow's leg, revealing the secret to the other leg
+    async fn exec_update_fusion_plus_fill_withdrawal(
+        client: &impl GenericClient,
+        withdrawal: &FusionPlusWithdrawal,
+    ) -> Result<Option<FusionPlusApplied>, DbError> {
+        let row = client.query_opt(
+            "UPDATE fusion_plus_fills SET
+                secret = $1,
+                src_status = CASE
+                    WHEN src_escrow_address = $3 OR (src_escrow_address IS NULL AND src_chain_id = $4) THEN 'withdrawn'
+                    WHEN src_status = 'created' THEN 'secret_revealed'
+                    ELSE src_status END,
+                dst_status = CASE
+                    WHEN src_escrow_address = $3 OR (src_escrow_address IS NULL AND src_chain_id = $4) THEN
+                        CASE WHEN dst_status IN ('pending', 'created') THEN 'secret_revealed' ELSE dst_status END
+                    ELSE 'withdrawn' END
+             WHERE hashlock = $2
+             RETURNING order_hash, src_status = 'withdrawn' AND (src_escrow_address = $3 OR src_escrow_address IS NULL AND src_chain_id = $4)",
+            &[
+                &withdrawal.secret.to_lowercase(),
+                &withdrawal.hashlock.to_lowercase(),
+                &withdrawal.escrow_address.to_lowercase(),
+                &(withdrawal.chain_id as i32),
+            ],
+        ).await?;
+
+        Ok(row.map(|r| FusionPlusApplied { order_hash: r.get(0), is_src: r.get::<_, Option<bool>>(1).unwrap_or(false) }))
+    }
+
+    /// Mark the leg of the fill owning `escrow` cancelled
+    async fn exec_update_fusion_plus_fill_cancelled(
+        client: &impl GenericClient,
+        escrow: &str,
+    ) -> Result<Option<FusionPlusApplied>, DbError> {
+        let row = client.query_opt(
+            "UPDATE fusion_plus_fills SET
+                src_status = CASE WHEN src_escrow_address = $1 THEN 'cancelled' ELSE src_status END,
+                dst_status = CASE WHEN dst_escrow_address = $1 THEN 'cancelled' ELSE dst_status END
+             WHERE src_escrow_address = $1 OR dst_escrow_address = $1
+             RETURNING order_hash, src_escrow_address = $1",
+            &[&escrow],
+        ).await?;
+
+        Ok(row.map(|r| FusionPlusApplied { order_hash: r.get(0), is_src: r.get::<_, Option<bool>>(1).unwrap_or(false) }))
+    }
+
+    /// Append an applied change to the swap's timeline
+    async fn exec_insert_fusion_plus_event(
+        client: &impl GenericClient,
+        order_hash: &str,
+        change: &FusionPlusChange,
+        is_src: bool,
+    ) -> Result<(), DbError> {
+        let (action, chain_id, tx_hash, block_number, block_timestamp, log_index, escrow_address) = match change {
+            FusionPlusChange::Created { swap, .. } => (
+                "created",
+                swap.src_chain_id,
+                &swap.src_tx_hash,
+                swap.src_block_number,
+                swap.src_block_timestamp,
+                swap.src_log_index,
+                swap.src_escrow_address.as_deref(),
+            ),
+            FusionPlusChange::DstCreated(dst) => (
+                "created",
+                dst.chain_id,
+                &dst.tx_hash,
+                dst.block_number,
+                dst.block_timestamp,
+                dst.log_index,
+                dst.escrow_address.as_deref(),
+            ),
+            FusionPlusChange::Withdrawn(w) => (
+                "withdrawn",
+                w.chain_id,
+                &w.tx_hash,
+                w.block_number,
+                w.block_timestamp,
+                w.log_index,
+                Some(w.escrow_address.as_str()),
+            ),
+            FusionPlusChange::Cancelled(c) => (
+                "cancelled",
+                c.chain_id,
+                &c.tx_hash,
+                c.block_number,
+                c.block_timestamp,
+                c.log_index,
+                Some(c.escrow_address.as_str()),
+            ),
+        };
+        let event_type = format!("{}_{}", if is_src { "src" } else { "dst" }, action);
+
+        client.execute(
+            "INSERT INTO fusion_plus_events (
+                order_hash, event_type, chain_id, tx_hash, block_number, block_timestamp, log_index, escrow_address
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING",
+            &[
+                &order_hash.to_lowercase(),
+                &event_type,
+                &(chain_id as i32),
+                &tx_hash.to_lowercase(),
+                &(block_number as i64),
+                &(block_timestamp as i64),
+                &(log_index as i32),
+                &escrow_address.filter(|a| !a.is_empty()).map(str::to_lowercase),
+            ],
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn exec_insert_fusion_plus_swap(
+        client: &impl GenericClient,
+        swap: &FusionPlusSwap,
+        now: i64,
+    ) -> Result<bool, DbError> {
+        let result = client.execute(
+            "INSERT INTO fusion_plus_swaps (
+                order_hash, hashlock, secret,
+                src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                src_safety_deposit, src_timelocks, src_status,
+                dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                dst_safety_deposit, dst_timelocks, dst_status,
+                created_at, updated_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31
+            )
+            ON CONFLICT (order_hash) DO NOTHING",
+            &[
+                &swap.order_hash.to_lowercase(),
+                &swap.hashlock.to_lowercase(),
+                &swap.secret,
+                &(swap.src_chain_id as i32),
+                &swap.src_tx_hash.to_lowercase(),
+                &(swap.src_block_number as i64),
+                &(swap.src_block_timestamp as i64),
+                &(swap.src_log_index as i32),
+                &swap.src_escrow_address.as_ref().map(|s| s.to_lowercase()),
+                &swap.src_maker.to_lowercase(),
+                &swap.src_taker.to_lowercase(),
+                &swap.src_token.to_lowercase(),
+                &swap.src_amount,
+                &swap.src_safety_deposit,
+                &swap.src_timelocks,
+                &swap.src_status,
+                &(swap.dst_chain_id as i32),
+                &swap.dst_tx_hash.as_ref().map(|s| s.to_lowercase()),
+                &swap.dst_block_number.map(|n| n as i64),
+                &swap.dst_block_timestamp.map(|n| n as i64),
+                &swap.dst_log_index.map(|n| n as i32),
+                &swap.dst_escrow_address.as_ref().map(|s| s.to_lowercase()),
+                &swap.dst_maker.to_lowercase(),
+                &swap.dst_taker.as_ref().map(|s| s.to_lowercase()),
+                &swap.dst_token.to_lowercase(),
+                &swap.dst_amount,
+                &swap.dst_safety_deposit,
+                &swap.dst_timelocks,
+                &swap.dst_status,
+                &now,
+                &now,
+            ],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    async fn exec_update_fusion_plus_dst(
+        client: &impl GenericClient,
+        dst: &FusionPlusDst,
+        now: i64,
+    ) -> Result<bool, DbError> {
+        let result = client.execute(
+            "UPDATE fusion_plus_swaps SET
+                dst_tx_hash = $1,
+                dst_block_number = $2,
+                dst_block_timestamp = $3,
+                dst_log_index = $4,
+                dst_escrow_address = $5,
+                dst_taker = $6,
+                dst_timelocks = $7,
+                dst_status = CASE WHEN dst_status = 'secret_revealed' THEN dst_status ELSE 'created' END,
+                updated_at = $8
+             WHERE order_hash = $9 AND dst_chain_id = $10 AND hashlock = $11",
+            &[
+                &dst.tx_hash.to_lowercase(),
+                &(dst.block_number as i64),
+                &(dst.block_timestamp as i64),
+                &(dst.log_index as i32),
+                &dst.escrow_address.as_ref().map(|s| s.to_lowercase()),
+                &dst.dst_taker.to_lowercase(),
+                &dst.dst_timelocks,
+                &now,
+                &dst.order_hash.to_lowercase(),
+                &(dst.chain_id as i32),
+                &dst.hashlock.to_lowercase(),
+            ],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    /// Find the swap of an escrow stored without its address, by the refund
+    /// the cancellation made in the same tx
+    ///
+    /// Cancelling returns the locked tokens to the maker on the source chain
+    /// and to the taker on the destination chain, so an uncancelled leg on
+    /// this chain with that token, recipient and amount is the one.
+    async fn find_cancelled_swap_by_refund(
+        client: &impl GenericClient,
+        cancellation: &FusionPlusCancellation,
+    ) -> Result<Option<Row>, DbError> {
+        let row = client.query_opt(
+            "SELECT s.order_hash, s.src_chain_id = $1 AND s.src_maker = t.to_addr
+             FROM transfers t
+             JOIN fusion_plus_swaps s ON
+                 (s.src_chain_id = $1 AND s.src_escrow_address IS NULL
+                  AND s.src_status IN ('created', 'secret_revealed')
+                  AND s.src_token = t.token AND s.src_maker = t.to_addr
+                  AND (t.value = '' OR LOWER(t.value) = LOWER(s.src_amount)))
+                 OR (s.dst_chain_id = $1 AND s.dst_escrow_address IS NULL
+                  AND s.dst_status IN ('created', 'secret_revealed')
+                  AND s.dst_token = t.token AND s.dst_taker = t.to_addr
+                  AND (t.value = '' OR LOWER(t.value) = LOWER(s.dst_amount)))
+             WHERE t.chain_id = $1 AND t.tx_hash = $2 AND t.from_addr = $3
+             ORDER BY s.created_at DESC
+             LIMIT 1
+             FOR UPDATE OF s",
+            &[
+                &(cancellation.chain_id as i32),
+                &cancellation.tx_hash.to_lowercase(),
+                &cancellation.escrow_address.to_lowercase(),
+            ],
+        ).await?;
+
+        Ok(row)
+    }
+
+    /// Mark a leg cancelled by `cancellation`'s tx, filling in the leg's
+    /// escrow address if it wasn't known
+    async fn exec_update_fusion_plus_cancelled(
+        client: &impl GenericClient,
+        order_hash: &str,
+        cancellation: &FusionPlusCancellation,
+        is_src: bool,
+        now: i64,
+    ) -> Result<bool, DbError> {
+        let escrow = Some(cancellation.escrow_address.to_lowercase()).filter(|a| !a.is_empty());
+        let params: [&(dyn ToSql + Sync); 6] = [
+            &now,
+            &order_hash.to_lowercase(),
+            &(cancellation.chain_id as i32),
+            &cancellation.tx_hash.to_lowercase(),
+            &(cancellation.block_timestamp as i64),
+            &escrow,
+        ];
+        let result = if is_src {
+            client.execute(
+                "UPDATE fusion_plus_swaps SET
+                    src_status = 'cancelled',
+                    src_cancelled_tx_hash = $4,
+                    src_cancelled_at = $5,
+                    src_escrow_address = COALESCE(src_escrow_address, $6),
+                    updated_at = $1
+                 WHERE order_hash = $2 AND src_chain_id = $3",
+                &params,
+            ).await?
+        } else {
+            client.execute(
+                "UPDATE fusion_plus_swaps SET
+                    dst_status = 'cancelled',
+                    dst_cancelled_tx_hash = $4,
+                    dst_cancelled_at = $5,
+                    dst_escrow_address = COALESCE(dst_escrow_address, $6),
+                    updated_at = $1
+                 WHERE order_hash = $2 AND dst_chain_id = $3",
+                &params,
+            ).await?
+        };
+
+        Ok(result > 0)
+    }
+
+    /// Mark a leg of `order_hash` withdrawn; `phase` is the withdrawal period it
+    /// fell in, if known
+    ///
+    /// A secret that doesn't match the hashlock is stored with `secret_valid`
+    /// false and doesn't count as revealed for the other leg.
+    #[allow(clippy::too_many_arguments)]
+    async fn exec_update_fusion_plus_withdrawal(
+        client: &impl GenericClient,
+        order_hash: &str,
+        withdrawal: &FusionPlusWithdrawal,
+        is_src: bool,
+        phase: Option<&str>,
+        secret_valid: bool,
+        now: i64,
+    ) -> Result<bool, DbError> {
+        let withdrawer = withdrawal.withdrawer.as_ref().map(|s| s.to_lowercase());
+        let result = if is_src {
+            client.execute(
+                "UPDATE fusion_plus_swaps SET
+                    src_status = 'withdrawn',
+                    dst_status = CASE WHEN $8 AND dst_status IN ('pending', 'created') THEN 'secret_revealed' ELSE dst_status END,
+                    secret = $1,
+                    secret_valid = $8,
+                    secret_revealed_chain_id = CASE WHEN $8 AND (secret_revealed_at IS NULL OR $5 < secret_revealed_at)
+                        THEN $4 ELSE secret_revealed_chain_id END,
+                    secret_revealed_at = CASE WHEN $8 THEN LEAST(COALESCE(secret_revealed_at, $5), $5) ELSE secret_revealed_at END,
+                    src_withdrawer = COALESCE($6, src_withdrawer),
+                    src_withdrawal_phase = COALESCE($7, src_withdrawal_phase),
+                    updated_at = $2
+                 WHERE order_hash = $3 AND src_chain_id = $4",
+                &[
+                    &withdrawal.secret.to_lowercase(),
+                    &now,
+                    &order_hash.to_lowercase(),
+                    &(withdrawal.chain_id as i32),
+                    &(withdrawal.block_timestamp as i64),
+                    &withdrawer,
+                    &phase,
+                    &secret_valid,
+                ],
+            ).await?
+        } else {
+            client.execute(
+                "UPDATE fusion_plus_swaps SET
+                    dst_status = 'withdrawn',
+                    src_status = CASE WHEN $11 AND src_status = 'created' THEN 'secret_revealed' ELSE src_status END,
+                    dst_tx_hash = $5,
+                    dst_block_number = $6,
+                    dst_block_timestamp = $7,
+                    dst_log_index = $8,
+                    secret = $1,
+                    secret_valid = $11,
+                    secret_revealed_chain_id = CASE WHEN $11 AND (secret_revealed_at IS NULL OR $7 < secret_revealed_at)
+                        THEN $4 ELSE secret_revealed_chain_id END,
+                    secret_revealed_at = CASE WHEN $11 THEN LEAST(COALESCE(secret_revealed_at, $7), $7) ELSE secret_revealed_at END,
+                    dst_withdrawer = COALESCE($9, dst_withdrawer),
+                    dst_withdrawal_phase = COALESCE($10, dst_withdrawal_phase),
+                    updated_at = $2
+                 WHERE order_hash = $3 AND dst_chain_id = $4",
+                &[
+                    &withdrawal.secret.to_lowercase(),
+                    &now,
+                    &order_hash.to_lowercase(),
+                    &(withdrawal.chain_id as i32),
+                    &withdrawal.tx_hash.to_lowercase(),
+                    &(withdrawal.block_number as i64),
+                    &(withdrawal.block_timestamp as i64),
+                    &(withdrawal.log_index as i32),
+                    &withdrawer,
+                    &phase,
+                    &secret_valid,
+                ],
+            ).await?
+        };
+
+        Ok(result > 0)
+    }
+
+    fn row_to_fusion_plus_swap(row: &Row) -> FusionPlusSwap {
+        FusionPlusSwap {
+            order_hash: row.get(0),
+            hashlock: row.get(1),
             secret: row.get(2),
             src_chain_id: row.get::<_, i32>(3) as u32,
             src_tx_hash: row.get(4),
@@ -742,269 +3731,2705 @@ impl Database {
             dst_safety_deposit: row.get(26),
             dst_timelocks: row.get(27),
             dst_status: row.get(28),
+            secret_revealed_chain_id: row.get::<_, Option<i32>>(29).map(|n| n as u32),
+            secret_revealed_at: row.get::<_, Option<i64>>(30).map(|n| n as u64),
+            src_withdrawer: row.get(31),
+            src_withdrawal_phase: row.get(32),
+            dst_withdrawer: row.get(33),
+            dst_withdrawal_phase: row.get(34),
+            src_cancelled_tx_hash: row.get(35),
+            src_cancelled_at: row.get::<_, Option<i64>>(36).map(|n| n as u64),
+            dst_cancelled_tx_hash: row.get(37),
+            dst_cancelled_at: row.get::<_, Option<i64>>(38).map(|n| n as u64),
+            secret_valid: row.get(39),
+        }
+    }
+
+    /// Get Fusion+ swap by order_hash
+    pub async fn get_fusion_plus_swap(&self, order_hash: &str) -> Result<Option<FusionPlusSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM fusion_plus_swaps WHERE order_hash = $1",
+            &[&order_hash.to_lowercase()],
+        ).await?;
+
+        Ok(row.map(|r| Self::row_to_fusion_plus_swap(&r)))
+    }
+
+    /// Swaps with a leg still created or secret_revealed (neither withdrawn nor
+    /// cancelled), oldest first
+    pub async fn get_open_fusion_plus_swaps(&self, limit: u32) -> Result<Vec<FusionPlusSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM fusion_plus_swaps
+             WHERE src_status IN ('created', 'secret_revealed') OR dst_status IN ('created', 'secret_revealed')
+             ORDER BY src_block_timestamp
+             LIMIT $1",
+            &[&(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_plus_swap).collect())
+    }
+
+    /// Get Fusion+ swap by hashlock
+    pub async fn get_fusion_plus_swap_by_hashlock(&self, hashlock: &str) -> Result<Option<FusionPlusSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM fusion_plus_swaps WHERE hashlock = $1",
+            &[&hashlock.to_lowercase()],
+        ).await?;
+
+        Ok(row.map(|r| Self::row_to_fusion_plus_swap(&r)))
+    }
+
+    /// Get the Fusion+ swap owning an escrow, and whether it is the source escrow
+    pub async fn get_fusion_plus_swap_by_escrow(&self, escrow_address: &str) -> Result<Option<(FusionPlusSwap, bool)>, DbError> {
+        let client = self.pool.get().await?;
+        let escrow = escrow_address.to_lowercase();
+
+        let row = client.query_opt(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM fusion_plus_swaps WHERE src_escrow_address = $1 OR dst_escrow_address = $1
+             LIMIT 1",
+            &[&escrow],
+        ).await?;
+
+        Ok(row.map(|r| {
+            let swap = Self::row_to_fusion_plus_swap(&r);
+            let is_src = swap.src_escrow_address.as_deref() == Some(escrow.as_str());
+            (swap, is_src)
+        }))
+    }
+
+    /// Fills of a Fusion+ order, by leaf index; fills without a known proof come last
+    pub async fn get_fusion_plus_fills(&self, order_hash: &str) -> Result<Vec<FusionPlusFill>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, fill_index, hashlock_root, proof_valid, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_escrow_address, src_amount, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_escrow_address, dst_amount, dst_status
+             FROM fusion_plus_fills WHERE order_hash = $1
+             ORDER BY fill_index NULLS LAST, id",
+            &[&order_hash.to_lowercase()],
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| FusionPlusFill {
+                order_hash: r.get(0),
+                fill_index: r.get::<_, Option<i64>>(1).map(|n| n as u64),
+                hashlock_root: r.get(2),
+                proof_valid: r.get(3),
+                hashlock: r.get(4),
+                secret: r.get(5),
+                src_chain_id: r.get::<_, i32>(6) as u32,
+                src_tx_hash: r.get(7),
+                src_block_number: r.get::<_, i64>(8) as u64,
+                src_escrow_address: r.get(9),
+                src_amount: r.get(10),
+                src_status: r.get(11),
+                dst_chain_id: r.get::<_, i32>(12) as u32,
+                dst_tx_hash: r.get(13),
+                dst_block_number: r.get::<_, Option<i64>>(14).map(|n| n as u64),
+                dst_escrow_address: r.get(15),
+                dst_amount: r.get(16),
+                dst_status: r.get(17),
+            })
+            .collect())
+    }
+
+    /// Lifecycle events of a Fusion+ swap, both legs, in the order they happened
+    pub async fn get_fusion_plus_timeline(&self, order_hash: &str) -> Result<Vec<FusionPlusEvent>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, event_type, chain_id, tx_hash, block_number, block_timestamp, log_index, escrow_address
+             FROM fusion_plus_events WHERE order_hash = $1
+             ORDER BY block_timestamp, id",
+            &[&order_hash.to_lowercase()],
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| FusionPlusEvent {
+                order_hash: r.get(0),
+                event_type: r.get(1),
+                chain_id: r.get::<_, i32>(2) as u32,
+                tx_hash: r.get(3),
+                block_number: r.get::<_, i64>(4) as u64,
+                block_timestamp: r.get::<_, i64>(5) as u64,
+                log_index: r.get::<_, i32>(6) as u32,
+                escrow_address: r.get(7),
+            })
+            .collect())
+    }
+
+    /// List Fusion+ swaps matching `filter`, newest source leg first
+    pub async fn list_fusion_plus_swaps(&self, filter: &FusionPlusFilter, limit: u32, offset: u32) -> Result<Vec<FusionPlusSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let maker = filter.maker.as_ref().map(|m| m.to_lowercase());
+        let src_chain = filter.src_chain_id.map(|c| c as i32);
+        let dst_chain = filter.dst_chain_id.map(|c| c as i32);
+        let from = filter.from_timestamp.map(|t| t as i64);
+        let to = filter.to_timestamp.map(|t| t as i64);
+        let limit = limit as i64;
+        let offset = offset as i64;
+
+        // Each condition's `?` placeholders bind to the value pushed with it
+        let mut conditions = Vec::new();
+        let mut params: Vec<&(dyn ToSql + Sync)> = Vec::new();
+        let mut push = |condition: &str, value| {
+            params.push(value);
+            conditions.push(condition.replace('?', &format!("${}", params.len())));
+        };
+
+        if let Some(maker) = &maker {
+            push("(src_maker = ? OR dst_maker = ?)", maker);
+        }
+        if !filter.src_status.is_empty() {
+            push("src_status = ANY(?)", &filter.src_status);
+        }
+        if !filter.dst_status.is_empty() {
+            push("dst_status = ANY(?)", &filter.dst_status);
+        }
+        if let Some(chain) = &src_chain {
+            push("src_chain_id = ?", chain);
+        }
+        if let Some(chain) = &dst_chain {
+            push("dst_chain_id = ?", chain);
+        }
+        if let Some(from) = &from {
+            push("src_block_timestamp >= ?", from);
+        }
+        if let Some(to) = &to {
+            push("src_block_timestamp < ?", to);
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        params.push(&limit);
+        params.push(&offset);
+
+        let sql = format!(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM fusion_plus_swaps
+             {}
+             ORDER BY src_block_timestamp DESC, order_hash
+             LIMIT ${} OFFSET ${}",
+            where_clause,
+            params.len() - 1,
+            params.len()
+        );
+
+        let rows = client.query(&sql, &params).await?;
+        Ok(rows.iter().map(Self::row_to_fusion_plus_swap).collect())
+    }
+
+    /// Get Fusion+ swaps with either leg created on `chain_id` in blocks
+    /// `from_block..=to_block`, oldest first by that leg
+    pub async fn get_fusion_plus_swaps_by_block_range(
+        &self,
+        chain_id: u32,
+        from_block: u64,
+        to_block: u64,
+        limit: u32,
+    ) -> Result<Vec<FusionPlusSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        // UNION rather than OR so each leg uses its (chain, block) index
+        let rows = client.query(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM (
+                 SELECT *, src_block_number AS leg_block, src_log_index AS leg_log_index
+                 FROM fusion_plus_swaps
+                 WHERE src_chain_id = $1 AND src_block_number BETWEEN $2 AND $3
+                 UNION
+                 SELECT *, dst_block_number AS leg_block, dst_log_index AS leg_log_index
+                 FROM fusion_plus_swaps
+                 WHERE dst_chain_id = $1 AND dst_block_number BETWEEN $2 AND $3
+             ) s
+             ORDER BY leg_block, leg_log_index
+             LIMIT $4",
+            &[&(chain_id as i32), &(from_block as i64), &(to_block.min(i64::MAX as u64) as i64), &(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_plus_swap).collect())
+    }
+
+    /// Get a random sample of Fusion+ swaps whose source leg is on a chain (used by `verify`)
+    pub async fn sample_fusion_plus_swaps(&self, src_chain_id: u32, limit: u32) -> Result<Vec<FusionPlusSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, hashlock, secret,
+                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
+                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
+                    src_safety_deposit, src_timelocks, src_status,
+                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
+                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
+                    dst_safety_deposit, dst_timelocks, dst_status,
+                    secret_revealed_chain_id, secret_revealed_at,
+                    src_withdrawer, src_withdrawal_phase, dst_withdrawer, dst_withdrawal_phase,
+                    src_cancelled_tx_hash, src_cancelled_at, dst_cancelled_tx_hash, dst_cancelled_at,
+                    secret_valid
+             FROM fusion_plus_swaps WHERE src_chain_id = $1
+             ORDER BY random()
+             LIMIT $2",
+            &[&(src_chain_id as i32), &(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_plus_swap).collect())
+    }
+
+    /// Per-resolver stats over swaps with a source leg in [from_timestamp, to_timestamp),
+    /// busiest first; `resolver` restricts them to one
+    ///
+    /// Computed from the stored swaps, so the window reaches back no further
+    /// than Fusion+ retention.
+    pub async fn get_resolver_stats(
+        &self,
+        resolver: Option<&str>,
+        from_timestamp: u64,
+        to_timestamp: u64,
+        limit: u32,
+    ) -> Result<Vec<ResolverStats>, DbError> {
+        let client = self.pool.get().await?;
+        let resolver = resolver.map(str::to_lowercase);
+
+        let rows = client.query(
+            "SELECT src_taker, COUNT(*),
+                    ARRAY_AGG(DISTINCT src_chain_id), ARRAY_AGG(DISTINCT dst_chain_id),
+                    COUNT(*) FILTER (WHERE src_status = 'withdrawn' AND dst_status = 'withdrawn'),
+                    COUNT(*) FILTER (WHERE src_status = 'cancelled' OR dst_status = 'cancelled'),
+                    AVG(dst_block_timestamp - src_block_timestamp)::FLOAT8,
+                    MIN(src_block_timestamp), MAX(src_block_timestamp)
+             FROM fusion_plus_swaps
+             WHERE src_block_timestamp >= $1 AND src_block_timestamp < $2
+               AND ($3::TEXT IS NULL OR src_taker = $3)
+             GROUP BY src_taker
+             ORDER BY COUNT(*) DESC, src_taker
+             LIMIT $4",
+            &[&(from_timestamp as i64), &(to_timestamp as i64), &resolver, &(limit as i64)],
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| {
+                let swap_count = r.get::<_, i64>(1) as u64;
+                let cancelled_count = r.get::<_, i64>(5) as u64;
+                let chains = |idx| r.get::<_, Vec<i32>>(idx).into_iter().map(|id| id as u32).collect();
+                ResolverStats {
+                    resolver: r.get(0),
+                    swap_count,
+                    src_chains: chains(2),
+                    dst_chains: chains(3),
+                    completed_count: r.get::<_, i64>(4) as u64,
+                    cancelled_count,
+                    cancellation_rate: cancelled_count as f64 / swap_count as f64,
+                    avg_dst_delay_secs: r.get(6),
+                    first_seen: r.get::<_, i64>(7) as u64,
+                    last_seen: r.get::<_, i64>(8) as u64,
+                }
+            })
+            .collect())
+    }
+
+    /// Get total count of Fusion+ swaps
+    pub async fn get_fusion_plus_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one(
+            "SELECT COUNT(*) FROM fusion_plus_swaps",
+            &[],
+        ).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Clean up Fusion+ swaps
+    ///
+    /// Swaps whose legs are both withdrawn or cancelled are deleted `ttl_secs`
+    /// after their last update. Swaps still in flight are kept until they
+    /// exceed `max_age_secs`, if set.
+    pub async fn cleanup_old_fusion_plus(&self, ttl_secs: Option<u64>, max_age_secs: Option<u64>) -> Result<usize, DbError> {
+        let now = unix_now() as i64;
+        let ttl_cutoff = ttl_secs.map(|ttl| now - ttl as i64);
+        let max_age_cutoff = max_age_secs.map(|max_age| now - max_age as i64);
+
+        let deleted = self.delete_expired(
+            "fusion_plus_swaps",
+            ("src_chain_id", "src_block_timestamp"),
+            "(updated_at < $1
+              AND src_status IN ('withdrawn', 'cancelled')
+              AND dst_status IN ('withdrawn', 'cancelled'))
+             OR created_at < $2",
+            &[&ttl_cutoff, &max_age_cutoff],
+        ).await?;
+
+        // Timelines and fills go with their swap
+        if deleted > 0 {
+            let client = self.pool.get().await?;
+            client.batch_execute(
+                "DELETE FROM fusion_plus_events e
+                 WHERE NOT EXISTS (SELECT 1 FROM fusion_plus_swaps s WHERE s.order_hash = e.order_hash);
+                 DELETE FROM fusion_plus_fills f
+                 WHERE NOT EXISTS (SELECT 1 FROM fusion_plus_swaps s WHERE s.order_hash = f.order_hash);",
+            ).await?;
+        }
+
+        Ok(deleted)
+    }
+
+    // =========================================================================
+    // Fusion (Single-Chain) Methods
+    // =========================================================================
+
+    /// Insert a new Fusion swap and record it as a fill of its order
+    #[instrument(skip_all)]
+    pub async fn insert_fusion_swap(&self, swap: &FusionSwap) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let result = client.execute(
+            "WITH swap AS (
+                INSERT INTO fusion_swaps (
+                    order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                    remaining, is_partial_fill, status, created_at, enrichment
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+                ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING
+                RETURNING id
+            )
+            INSERT INTO order_fills (swap_id, chain_id, order_hash, block_number, log_index, maker_amount, taker_amount, remaining)
+            SELECT id, $2, $1, $4, $6, $18::TEXT::NUMERIC, $19::TEXT::NUMERIC, $20::TEXT::NUMERIC FROM swap",
+            &[
+                &swap.order_hash.to_lowercase(),
+                &(swap.chain_id as i32),
+                &swap.tx_hash.to_lowercase(),
+                &(swap.block_number as i64),
+                &(swap.block_timestamp as i64),
+                &(swap.log_index as i32),
+                &swap.maker.to_lowercase(),
+                &swap.taker.as_ref().map(|s| s.to_lowercase()),
+                &swap.maker_token.as_ref().map(|s| s.to_lowercase()),
+                &swap.taker_token.as_ref().map(|s| s.to_lowercase()),
+                &swap.maker_amount,
+                &swap.taker_amount,
+                &swap.remaining,
+                &swap.is_partial_fill,
+                &swap.status,
+                &now,
+                &swap.maker.is_empty().then_some("pending"),
+                &hex_to_decimal(swap.maker_amount.as_deref()),
+                &hex_to_decimal(swap.taker_amount.as_deref()),
+                &hex_to_decimal(Some(&swap.remaining)),
+            ],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    /// Mark an order's most recent fill on a chain as cancelled
+    ///
+    /// Returns the updated swap, or None if no fill of the order is stored.
+    /// The cancelling tx and block are kept so a reorg can undo the transition.
+    pub async fn cancel_fusion_swap(
+        &self,
+        chain_id: u32,
+        order_hash: &str,
+        tx_hash: &str,
+        block_number: u64,
+    ) -> Result<Option<FusionSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "UPDATE fusion_swaps SET
+                status = 'cancelled',
+                cancelled_tx_hash = $3,
+                cancelled_block_number = $4
+             WHERE id = (
+                SELECT id FROM fusion_swaps
+                WHERE chain_id = $1 AND order_hash = $2
+                ORDER BY block_number DESC, log_index DESC
+                LIMIT 1
+             )
+             RETURNING order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                       maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                       remaining, is_partial_fill, status, id",
+            &[
+                &(chain_id as i32),
+                &order_hash.to_lowercase(),
+                &tx_hash.to_lowercase(),
+                &(block_number as i64),
+            ],
+        ).await?;
+
+        Ok(row.as_ref().map(Self::row_to_fusion_swap))
+    }
+
+    /// Swaps on a chain awaiting enrichment from blocks at or after `since`, oldest first
+    pub async fn get_pending_fusion_enrichment(
+        &self,
+        chain_id: u32,
+        since: u64,
+        limit: u32,
+    ) -> Result<Vec<FusionSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                    remaining, is_partial_fill, status, id
+             FROM fusion_swaps
+             WHERE chain_id = $1 AND enrichment = 'pending' AND block_timestamp >= $2
+             ORDER BY block_timestamp
+             LIMIT $3",
+            &[&(chain_id as i32), &(since as i64), &(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_swap).collect())
+    }
+
+    /// Mark swaps still awaiting enrichment from blocks before `before` as failed
+    pub async fn expire_fusion_enrichment(&self, chain_id: u32, before: u64) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+
+        Ok(client.execute(
+            "UPDATE fusion_swaps SET enrichment = 'failed'
+             WHERE chain_id = $1 AND enrichment = 'pending' AND block_timestamp < $2",
+            &[&(chain_id as i32), &(before as i64)],
+        ).await?)
+    }
+
+    /// Count a failed enrichment attempt; returns true once the swap is given up on
+    pub async fn record_fusion_enrich_attempt(&self, id: i64, max_attempts: u32) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "UPDATE fusion_swaps SET
+                enrich_attempts = enrich_attempts + 1,
+                enrichment = CASE WHEN enrich_attempts + 1 >= $2 THEN 'failed' ELSE 'pending' END
+             WHERE id = $1 AND enrichment = 'pending'
+             RETURNING enrichment",
+            &[&id, &(max_attempts as i32)],
+        ).await?;
+
+        Ok(row.is_some_and(|r| r.get::<_, Option<String>>(0).as_deref() == Some("failed")))
+    }
+
+    /// Fill in a pending swap's maker/taker details; returns the updated swap
+    pub async fn complete_fusion_enrichment(
+        &self,
+        id: i64,
+        maker: &str,
+        taker: &str,
+        (maker_token, taker_token): (&str, &str),
+        (maker_amount, taker_amount): (&str, &str),
+    ) -> Result<Option<FusionSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "WITH swap AS (
+                UPDATE fusion_swaps SET
+                    maker = $2, taker = $3, maker_token = $4, taker_token = $5,
+                    maker_amount = $6, taker_amount = $7,
+                    enrich_attempts = enrich_attempts + 1,
+                    enrichment = NULL
+                WHERE id = $1 AND enrichment = 'pending'
+                RETURNING order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                          maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                          remaining, is_partial_fill, status, id
+             ), fill AS (
+                UPDATE order_fills f SET maker_amount = $8::TEXT::NUMERIC, taker_amount = $9::TEXT::NUMERIC
+                FROM swap WHERE f.swap_id = swap.id
+             )
+             SELECT * FROM swap",
+            &[
+                &id,
+                &maker.to_lowercase(),
+                &taker.to_lowercase(),
+                &maker_token.to_lowercase(),
+                &taker_token.to_lowercase(),
+                &maker_amount,
+                &taker_amount,
+                &hex_to_decimal(Some(maker_amount)),
+                &hex_to_decimal(Some(taker_amount)),
+            ],
+        ).await?;
+
+        Ok(row.as_ref().map(Self::row_to_fusion_swap))
+    }
+
+    fn row_to_fusion_swap(row: &Row) -> FusionSwap {
+        FusionSwap {
+            order_hash: row.get(0),
+            chain_id: row.get::<_, i32>(1) as u32,
+            tx_hash: row.get(2),
+            block_number: row.get::<_, i64>(3) as u64,
+            block_timestamp: row.get::<_, i64>(4) as u64,
+            log_index: row.get::<_, i32>(5) as u32,
+            maker: row.get(6),
+            taker: row.get(7),
+            maker_token: row.get(8),
+            taker_token: row.get(9),
+            maker_amount: row.get(10),
+            taker_amount: row.get(11),
+            remaining: row.get(12),
+            is_partial_fill: row.get(13),
+            status: row.get(14),
+            id: Some(row.get(15)),
+        }
+    }
+
+    /// Get Fusion swap by order_hash
+    pub async fn get_fusion_swap_by_order_hash(&self, order_hash: &str) -> Result<Option<FusionSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "SELECT order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                    remaining, is_partial_fill, status, id
+             FROM fusion_swaps WHERE order_hash = $1
+             ORDER BY block_timestamp DESC LIMIT 1",
+            &[&order_hash.to_lowercase()],
+        ).await?;
+
+        Ok(row.map(|r| Self::row_to_fusion_swap(&r)))
+    }
+
+    /// List Fusion swaps on a chain, most recent first unless paging with `since_id`
+    pub async fn get_fusion_swaps(&self, chain_id: u32, cursor: &Cursor, limit: u32) -> Result<Vec<FusionSwap>, DbError> {
+        let rows = self.query_page(
+            "SELECT order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                    remaining, is_partial_fill, status, id
+             FROM fusion_swaps WHERE chain_id = $1",
+            &[&(chain_id as i32)],
+            ("id", "block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_swap).collect())
+    }
+
+    /// Get Fusion fills in blocks `from_block..=to_block` in chain order
+    pub async fn get_fusion_swaps_by_block_range(
+        &self,
+        chain_id: u32,
+        from_block: u64,
+        to_block: u64,
+        limit: u32,
+    ) -> Result<Vec<FusionSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                    remaining, is_partial_fill, status, id
+             FROM fusion_swaps
+             WHERE chain_id = $1 AND block_number BETWEEN $2 AND $3
+             ORDER BY block_number, log_index
+             LIMIT $4",
+            &[&(chain_id as i32), &(from_block as i64), &(to_block.min(i64::MAX as u64) as i64), &(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_swap).collect())
+    }
+
+    /// Get a random sample of Fusion swaps for a chain (used by `verify`)
+    pub async fn sample_fusion_swaps(&self, chain_id: u32, limit: u32) -> Result<Vec<FusionSwap>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
+                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
+                    remaining, is_partial_fill, status, id
+             FROM fusion_swaps WHERE chain_id = $1
+             ORDER BY random()
+             LIMIT $2",
+            &[&(chain_id as i32), &(limit as i64)],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_fusion_swap).collect())
+    }
+
+    /// Get an order's aggregated fills on a chain, or None if it has no fills
+    pub async fn get_fusion_order(&self, chain_id: u32, order_hash: &str) -> Result<Option<FusionOrder>, DbError> {
+        let client = self.pool.get().await?;
+        let order_hash = order_hash.to_lowercase();
+
+        let Some(row) = client.query_opt(
+            "SELECT chain_id, order_hash, maker, maker_token, taker_token, fill_count,
+                    filled_maker_amount::TEXT, filled_taker_amount::TEXT, remaining::TEXT, status,
+                    first_fill_block, last_fill_block, first_fill_at, last_fill_at
+             FROM fusion_orders WHERE chain_id = $1 AND order_hash = $2",
+            &[&(chain_id as i32), &order_hash],
+        ).await? else {
+            return Ok(None);
+        };
+
+        let fills = client.query(
+            "SELECT ROW_NUMBER() OVER (ORDER BY f.block_number, f.log_index), s.tx_hash, f.block_number,
+                    s.block_timestamp, f.log_index, s.taker,
+                    f.maker_amount::TEXT, f.taker_amount::TEXT, f.remaining::TEXT
+             FROM order_fills f
+             JOIN fusion_swaps s ON s.id = f.swap_id
+             WHERE f.chain_id = $1 AND f.order_hash = $2
+             ORDER BY f.block_number, f.log_index",
+            &[&(chain_id as i32), &order_hash],
+        ).await?;
+
+        Ok(Some(FusionOrder {
+            chain_id: row.get::<_, i32>(0) as u32,
+            order_hash: row.get(1),
+            maker: row.get(2),
+            maker_token: row.get(3),
+            taker_token: row.get(4),
+            fill_count: row.get::<_, i64>(5) as u32,
+            filled_maker_amount: row.get(6),
+            filled_taker_amount: row.get(7),
+            remaining: row.get(8),
+            status: row.get(9),
+            first_fill_block: row.get::<_, i64>(10) as u64,
+            last_fill_block: row.get::<_, i64>(11) as u64,
+            first_fill_at: row.get::<_, i64>(12) as u64,
+            last_fill_at: row.get::<_, i64>(13) as u64,
+            fills: fills
+                .iter()
+                .map(|r| OrderFill {
+                    fill_index: r.get::<_, i64>(0) as u32,
+                    tx_hash: r.get(1),
+                    block_number: r.get::<_, i64>(2) as u64,
+                    block_timestamp: r.get::<_, i64>(3) as u64,
+                    log_index: r.get::<_, i32>(4) as u32,
+                    taker: r.get(5),
+                    maker_amount: r.get(6),
+                    taker_amount: r.get(7),
+                    remaining: r.get(8),
+                })
+                .collect(),
+        }))
+    }
+
+    /// Get total count of Fusion swaps
+    pub async fn get_fusion_swap_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one(
+            "SELECT COUNT(*) FROM fusion_swaps",
+            &[],
+        ).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Clean up old Fusion swaps based on TTL
+    pub async fn cleanup_old_fusion_swaps(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("fusion_swaps", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    // =========================================================================
+    // Crypto2Fiat Methods
+    // =========================================================================
+
+    /// Insert a new Crypto2Fiat event
+    #[instrument(skip_all)]
+    pub async fn insert_crypto2fiat_event(&self, event: &Crypto2FiatEvent) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let result = client.execute(
+            "INSERT INTO crypto2fiat_events (
+                order_id, token, amount, recipient, metadata,
+                chain_id, tx_hash, block_number, block_timestamp, log_index, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING",
+            &[
+                &event.order_id.to_lowercase(),
+                &event.token.to_lowercase(),
+                &event.amount,
+                &event.recipient.to_lowercase(),
+                &event.metadata,
+                &(event.chain_id as i32),
+                &event.tx_hash.to_lowercase(),
+                &(event.block_number as i64),
+                &(event.block_timestamp as i64),
+                &(event.log_index as i32),
+                &now,
+            ],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    fn row_to_crypto2fiat(row: &tokio_postgres::Row) -> Crypto2FiatEvent {
+        Crypto2FiatEvent {
+            order_id: row.get(0),
+            token: row.get(1),
+            amount: row.get(2),
+            recipient: row.get(3),
+            metadata: row.get::<_, Option<String>>(4).unwrap_or_default(),
+            chain_id: row.get::<_, i32>(5) as u32,
+            tx_hash: row.get(6),
+            block_number: row.get::<_, i64>(7) as u64,
+            block_timestamp: row.get::<_, i64>(8) as u64,
+            log_index: row.get::<_, i32>(9) as u32,
+            id: Some(row.get(10)),
+        }
+    }
+
+    /// Get Crypto2Fiat events by order_id (an order may be settled on several chains)
+    pub async fn get_crypto2fiat_by_order_id(&self, order_id: &str) -> Result<Vec<Crypto2FiatEvent>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT order_id, token, amount, recipient, metadata,
+                    chain_id, tx_hash, block_number, block_timestamp, log_index, id
+             FROM crypto2fiat_events WHERE order_id = $1
+             ORDER BY block_timestamp DESC",
+            &[&order_id.to_lowercase()],
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_crypto2fiat).collect())
+    }
+
+    /// List Crypto2Fiat events on a chain, most recent first unless paging with `since_id`
+    pub async fn get_crypto2fiat_events(&self, chain_id: u32, cursor: &Cursor, limit: u32) -> Result<Vec<Crypto2FiatEvent>, DbError> {
+        let rows = self.query_page(
+            "SELECT order_id, token, amount, recipient, metadata,
+                    chain_id, tx_hash, block_number, block_timestamp, log_index, id
+             FROM crypto2fiat_events WHERE chain_id = $1",
+            &[&(chain_id as i32)],
+            ("id", "block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_crypto2fiat).collect())
+    }
+
+    /// Get total count of Crypto2Fiat events
+    pub async fn get_crypto2fiat_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one(
+            "SELECT COUNT(*) FROM crypto2fiat_events",
+            &[],
+        ).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Clean up old Crypto2Fiat events based on TTL
+    pub async fn cleanup_old_crypto2fiat(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("crypto2fiat_events", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    // =========================================================================
+    // Delegation Methods
+    // =========================================================================
+
+    /// Insert delegation logs in a batch, ignoring duplicates
+    #[instrument(skip_all, fields(chain_id = chain_id, rows = delegations.len()))]
+    pub async fn insert_delegations_batch(&self, chain_id: u32, delegations: &[Delegation]) -> Result<usize, DbError> {
+        let client = self.pool.get().await?;
+        Self::exec_insert_delegations(&client, chain_id, delegations, unix_now() as i64).await
+    }
+
+    async fn exec_insert_delegations(
+        client: &impl GenericClient,
+        chain_id: u32,
+        delegations: &[Delegation],
+        now: i64,
+    ) -> Result<usize, DbError> {
+        if delegations.is_empty() {
+            return Ok(0);
+        }
+
+        let stmt = client.prepare(
+            "INSERT INTO delegations
+             (chain_id, authority, delegate, tx_hash, log_index, topics, data, block_number, block_timestamp, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT DO NOTHING"
+        ).await?;
+
+        let mut inserted = 0;
+        for delegation in delegations {
+            let topics: Vec<String> = delegation.topics.iter().map(|t| t.to_lowercase()).collect();
+            let result = client.execute(
+                &stmt,
+                &[
+                    &(chain_id as i32),
+                    &delegation.authority.to_lowercase(),
+                    &delegation.delegate.as_ref().map(|d| d.to_lowercase()),
+                    &delegation.tx_hash.to_lowercase(),
+                    &(delegation.log_index as i32),
+                    &topics,
+                    &delegation.data,
+                    &(delegation.block_number as i64),
+                    &(delegation.block_timestamp as i64),
+                    &now,
+                ],
+            ).await?;
+            if result > 0 {
+                inserted += 1;
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    /// Get delegation logs emitted by an authority (delegated account)
+    pub async fn get_delegations_by_authority(
+        &self,
+        chain_id: u32,
+        authority: &str,
+        cursor: &Cursor,
+        limit: u32,
+    ) -> Result<Vec<Delegation>, DbError> {
+        let authority = authority.to_lowercase();
+        let rows = self.query_page(
+            "SELECT chain_id, authority, delegate, tx_hash, log_index, topics, data,
+                    block_number, block_timestamp, id
+             FROM delegations WHERE chain_id = $1 AND authority = $2",
+            &[&(chain_id as i32), &authority],
+            ("id", "block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| Delegation {
+                chain_id: r.get::<_, i32>(0) as u32,
+                authority: r.get(1),
+                delegate: r.get(2),
+                tx_hash: r.get(3),
+                log_index: r.get::<_, i32>(4) as u32,
+                topics: r.get(5),
+                data: r.get(6),
+                block_number: r.get::<_, i64>(7) as u64,
+                block_timestamp: r.get::<_, i64>(8) as u64,
+                id: Some(r.get(9)),
+            })
+            .collect())
+    }
+
+    /// Get total count of delegation logs
+    pub async fn get_delegation_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one("SELECT COUNT(*) FROM delegations", &[]).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Clean up old delegation logs based on TTL
+    pub async fn cleanup_old_delegations(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("delegations", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    // =========================================================================
+    // Transaction Methods
+    // =========================================================================
+
+    /// Insert transaction details in a batch, ignoring transactions already stored
+    #[instrument(skip_all, fields(chain_id = chain_id, rows = transactions.len()))]
+    pub async fn insert_transactions_batch(&self, chain_id: u32, transactions: &[TransactionInfo]) -> Result<usize, DbError> {
+        let client = self.pool.get().await?;
+        Self::exec_insert_transactions(&client, chain_id, transactions, unix_now() as i64).await
+    }
+
+    async fn exec_insert_transactions(
+        client: &impl GenericClient,
+        chain_id: u32,
+        transactions: &[TransactionInfo],
+        now: i64,
+    ) -> Result<usize, DbError> {
+        if transactions.is_empty() {
+            return Ok(0);
+        }
+
+        let stmt = client.prepare(
+            "INSERT INTO transactions
+             (chain_id, tx_hash, block_number, block_timestamp, from_addr, to_addr, gas_used, effective_gas_price, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             ON CONFLICT DO NOTHING"
+        ).await?;
+
+        let mut inserted = 0;
+        for transaction in transactions {
+            let result = client.execute(
+                &stmt,
+                &[
+                    &(chain_id as i32),
+                    &transaction.tx_hash.to_lowercase(),
+                    &(transaction.block_number as i64),
+                    &(transaction.block_timestamp as i64),
+                    &transaction.from_addr.to_lowercase(),
+                    &transaction.to_addr.as_ref().map(|a| a.to_lowercase()),
+                    &(transaction.gas_used as i64),
+                    &transaction.effective_gas_price,
+                    &now,
+                ],
+            ).await?;
+            if result > 0 {
+                inserted += 1;
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    /// Get transactions sent by an address
+    pub async fn get_transactions_by_from(
+        &self,
+        chain_id: u32,
+        from_addr: &str,
+        cursor: &Cursor,
+        limit: u32,
+    ) -> Result<Vec<TransactionInfo>, DbError> {
+        let from_addr = from_addr.to_lowercase();
+        let rows = self.query_page(
+            "SELECT chain_id, tx_hash, block_number, block_timestamp, from_addr, to_addr,
+                    gas_used, effective_gas_price, id
+             FROM transactions WHERE chain_id = $1 AND from_addr = $2",
+            &[&(chain_id as i32), &from_addr],
+            ("id", "block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows.iter().map(Self::row_to_transaction).collect())
+    }
+
+    /// Get the stored details of one transaction
+    pub async fn get_transaction(&self, chain_id: u32, tx_hash: &str) -> Result<Option<TransactionInfo>, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt(
+            "SELECT chain_id, tx_hash, block_number, block_timestamp, from_addr, to_addr,
+                    gas_used, effective_gas_price, id
+             FROM transactions WHERE chain_id = $1 AND tx_hash = $2",
+            &[&(chain_id as i32), &tx_hash.to_lowercase()],
+        ).await?;
+
+        Ok(row.as_ref().map(Self::row_to_transaction))
+    }
+
+    fn row_to_transaction(row: &Row) -> TransactionInfo {
+        TransactionInfo {
+            chain_id: row.get::<_, i32>(0) as u32,
+            tx_hash: row.get(1),
+            block_number: row.get::<_, i64>(2) as u64,
+            block_timestamp: row.get::<_, i64>(3) as u64,
+            from_addr: row.get(4),
+            to_addr: row.get(5),
+            gas_used: row.get::<_, i64>(6) as u64,
+            effective_gas_price: row.get(7),
+            id: Some(row.get(8)),
+        }
+    }
+
+    /// Get total count of stored transactions
+    pub async fn get_transaction_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one("SELECT COUNT(*) FROM transactions", &[]).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Clean up old transactions based on TTL
+    pub async fn cleanup_old_transactions(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("transactions", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    // =========================================================================
+    // Internal Transfer Methods
+    // =========================================================================
+
+    async fn exec_insert_internal_transfers(
+        client: &impl GenericClient,
+        chain_id: u32,
+        transfers: &[InternalTransfer],
+        now: i64,
+    ) -> Result<usize, DbError> {
+        if transfers.is_empty() {
+            return Ok(0);
+        }
+
+        let stmt = client.prepare(
+            "INSERT INTO internal_transfers
+             (chain_id, tx_hash, trace_address, call_type, from_addr, to_addr, value, value_decimal, block_number, block_timestamp, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT DO NOTHING"
+        ).await?;
+
+        let mut inserted = 0;
+        for transfer in transfers {
+            let result = client.execute(
+                &stmt,
+                &[
+                    &(chain_id as i32),
+                    &transfer.tx_hash.to_lowercase(),
+                    &transfer.trace_address,
+                    &transfer.call_type,
+                    &transfer.from_addr.to_lowercase(),
+                    &transfer.to_addr.to_lowercase(),
+                    &transfer.value,
+                    &transfer.value_decimal,
+                    &(transfer.block_number as i64),
+                    &(transfer.block_timestamp as i64),
+                    &now,
+                ],
+            ).await?;
+            if result > 0 {
+                inserted += 1;
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    /// Get internal transfers sent or received by an address
+    pub async fn get_internal_transfers_by_address(
+        &self,
+        chain_id: u32,
+        address: &str,
+        cursor: &Cursor,
+        limit: u32,
+    ) -> Result<Vec<InternalTransfer>, DbError> {
+        let address = address.to_lowercase();
+        let rows = self.query_page(
+            "SELECT chain_id, tx_hash, trace_address, call_type, from_addr, to_addr, value, value_decimal,
+                    block_number, block_timestamp, id
+             FROM internal_transfers WHERE chain_id = $1 AND (from_addr = $2 OR to_addr = $2)",
+            &[&(chain_id as i32), &address],
+            ("id", "block_timestamp"),
+            cursor,
+            limit,
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| InternalTransfer {
+                chain_id: r.get::<_, i32>(0) as u32,
+                tx_hash: r.get(1),
+                trace_address: r.get(2),
+                call_type: r.get(3),
+                from_addr: r.get(4),
+                to_addr: r.get(5),
+                value: r.get(6),
+                value_decimal: r.get(7),
+                block_number: r.get::<_, i64>(8) as u64,
+                block_timestamp: r.get::<_, i64>(9) as u64,
+                id: Some(r.get(10)),
+            })
+            .collect())
+    }
+
+    /// Get total count of stored internal transfers
+    pub async fn get_internal_transfer_count(&self) -> Result<u64, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one("SELECT COUNT(*) FROM internal_transfers", &[]).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Clean up old internal transfers based on TTL
+    pub async fn cleanup_old_internal_transfers(&self, ttl_secs: u64) -> Result<usize, DbError> {
+        let cutoff = unix_now() as i64 - ttl_secs as i64;
+        self.delete_expired("internal_transfers", ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff]).await
+    }
+
+    // =========================================================================
+    // Token Stats Methods
+    // =========================================================================
+
+    /// Roll up transfers stored since `since` (their `created_at`) into `token_stats`
+    ///
+    /// Every (chain, token, hour) touched by those transfers is recounted from
+    /// all of its transfers, so overlapping windows are harmless. Returns the
+    /// number of hourly rows written.
+    #[instrument(skip(self))]
+    pub async fn rollup_token_stats(&self, since: u64) -> Result<usize, DbError> {
+        let client = self.pool.get().await?;
+        let now = unix_now() as i64;
+
+        let written = client.execute(
+            "INSERT INTO token_stats (chain_id, token, hour, transfer_count, volume, updated_at)
+             SELECT t.chain_id, t.token, touched.hour, COUNT(*), COALESCE(SUM(t.value_decimal::NUMERIC), 0), $2
+             FROM (
+                 SELECT DISTINCT chain_id, token, block_timestamp / 3600 * 3600 AS hour
+                 FROM transfers WHERE created_at >= $1
+             ) touched
+             JOIN transfers t
+               ON t.chain_id = touched.chain_id AND t.token = touched.token
+              AND t.block_timestamp >= touched.hour AND t.block_timestamp < touched.hour + 3600
+             GROUP BY t.chain_id, t.token, touched.hour
+             ON CONFLICT (chain_id, token, hour) DO UPDATE SET
+                transfer_count = EXCLUDED.transfer_count,
+                volume = EXCLUDED.volume,
+                updated_at = EXCLUDED.updated_at",
+            &[&(since as i64), &now],
+        ).await?;
+
+        Ok(written as usize)
+    }
+
+    /// Time of the last rollup that wrote rows, if any
+    pub async fn get_token_stats_updated_at(&self) -> Result<Option<u64>, DbError> {
+        let client = self.pool.get().await?;
+        let row = client.query_one("SELECT MAX(updated_at) FROM token_stats", &[]).await?;
+
+        Ok(row.get::<_, Option<i64>>(0).map(|t| t as u64))
+    }
+
+    /// Get the most active tokens of a chain over a time window, busiest first
+    ///
+    /// Hours are included when they start within the window.
+    pub async fn get_top_tokens(
+        &self,
+        chain_id: u32,
+        from_timestamp: u64,
+        to_timestamp: u64,
+        limit: u32,
+    ) -> Result<Vec<TokenStats>, DbError> {
+        let client = self.pool.get().await?;
+        let rows = client.query(
+            "SELECT token, SUM(transfer_count)::BIGINT, SUM(volume)::TEXT
+             FROM token_stats
+             WHERE chain_id = $1 AND hour BETWEEN $2 AND $3
+             GROUP BY token
+             ORDER BY SUM(transfer_count) DESC, token
+             LIMIT $4",
+            &[&(chain_id as i32), &(from_timestamp as i64), &(to_timestamp as i64), &(limit as i64)],
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| TokenStats {
+                chain_id,
+                token: r.get(0),
+                hour: None,
+                transfer_count: r.get::<_, i64>(1) as u64,
+                volume: r.get(2),
+            })
+            .collect())
+    }
+
+    /// Get a token's hourly stats over a time window, oldest first
+    pub async fn get_token_hourly_stats(
+        &self,
+        chain_id: u32,
+        token: &str,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Result<Vec<TokenStats>, DbError> {
+        let client = self.pool.get().await?;
+        let rows = client.query(
+            "SELECT token, hour, transfer_count, volume::TEXT
+             FROM token_stats
+             WHERE chain_id = $1 AND token = $2 AND hour BETWEEN $3 AND $4
+             ORDER BY hour",
+            &[&(chain_id as i32), &token.to_lowercase(), &(from_timestamp as i64), &(to_timestamp as i64)],
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| TokenStats {
+                chain_id,
+                token: r.get(0),
+                hour: Some(r.get::<_, i64>(1) as u64),
+                transfer_count: r.get::<_, i64>(2) as u64,
+                volume: r.get(3),
+            })
+            .collect())
+    }
+
+    // =========================================================================
+    // Watchlist Methods
+    // =========================================================================
+
+    /// Add an address to the watchlist, returning false if it was already present
+    pub async fn add_watched_address(&self, address: &str, label: Option<&str>) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+
+        let result = client.execute(
+            "INSERT INTO watchlist (address, label) VALUES ($1, $2)
+             ON CONFLICT (address) DO NOTHING",
+            &[&address.to_lowercase(), &label],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    /// Remove an address from the watchlist, returning false if it was not present
+    pub async fn remove_watched_address(&self, address: &str) -> Result<bool, DbError> {
+        let client = self.pool.get().await?;
+
+        let result = client.execute(
+            "DELETE FROM watchlist WHERE address = $1",
+            &[&address.to_lowercase()],
+        ).await?;
+
+        Ok(result > 0)
+    }
+
+    /// Get all watched addresses
+    pub async fn get_watched_addresses(&self) -> Result<Vec<WatchedAddress>, DbError> {
+        let client = self.pool.get().await?;
+
+        let rows = client.query(
+            "SELECT address, label, created_at FROM watchlist ORDER BY address",
+            &[],
+        ).await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| WatchedAddress {
+                address: r.get(0),
+                label: r.get(1),
+                created_at: r.get::<_, i64>(2) as u64,
+            })
+            .collect())
+    }
+
+    // =========================================================================
+    // Size Methods
+    // =========================================================================
+
+    /// Measure the database and the size of each table
+    pub async fn get_size(&self) -> Result<DatabaseSize, DbError> {
+        let client = self.pool.get().await?;
+
+        let total_bytes = client
+            .query_one("SELECT pg_database_size(current_database())", &[])
+            .await?
+            .get::<_, i64>(0) as u64;
+
+        let rows = client.query(
+            "SELECT COALESCE(pg_partition_root(s.relid), s.relid::regclass)::TEXT,
+                    SUM(pg_total_relation_size(s.relid))::BIGINT,
+                    SUM(s.n_live_tup)::BIGINT,
+                    SUM(s.n_dead_tup)::BIGINT
+             FROM pg_stat_user_tables s
+             GROUP BY 1
+             ORDER BY 2 DESC",
+            &[],
+        ).await?;
+
+        let tables: Vec<TableSize> = rows
+            .iter()
+            .map(|r| TableSize {
+                table: r.get(0),
+                total_bytes: r.get::<_, i64>(1) as u64,
+                live_rows: r.get::<_, i64>(2) as u64,
+                dead_rows: r.get::<_, i64>(3) as u64,
+            })
+            .collect();
+
+        Ok(DatabaseSize {
+            total_bytes,
+            live_bytes: live_bytes(total_bytes, &tables),
+            tables,
+        })
+    }
+
+    /// Delete the oldest rows of the high-volume tables while the database's
+    /// live size exceeds `max_size_bytes`
+    ///
+    /// Each table loses the share of its rows by which the cap is exceeded
+    /// (plus `SIZE_CAP_HEADROOM`), oldest `created_at` first. Deleted rows are
+    /// archived like expired ones. Returns the number of rows deleted.
+    async fn enforce_size_cap(&self, max_size_bytes: u64) -> Result<usize, DbError> {
+        let size = self.get_size().await?;
+        if size.live_bytes <= max_size_bytes {
+            return Ok(0);
+        }
+
+        let fraction = (size_cap_fraction(size.live_bytes, max_size_bytes) + SIZE_CAP_HEADROOM).min(1.0);
+        tracing::warn!(
+            "Database live size {} MB exceeds cap of {} MB; deleting the oldest {:.0}% of rows",
+            size.live_bytes / 1_048_576,
+            max_size_bytes / 1_048_576,
+            fraction * 100.0
+        );
+
+        let mut deleted = 0;
+        for table in size.tables.iter().filter(|t| SIZE_CAP_TABLES.contains(&t.table.as_str())) {
+            let skip = (table.live_rows as f64 * fraction) as i64;
+            if skip == 0 {
+                continue;
+            }
+
+            let client = self.pool.get().await?;
+            let sql = format!("SELECT created_at FROM {} ORDER BY created_at OFFSET $1 LIMIT 1", table.table);
+            let cutoff: Option<i64> = client.query_opt(sql.as_str(), &[&skip]).await?.map(|r| r.get(0));
+            drop(client);
+
+            // Fewer rows than the estimate: drop them all
+            let cutoff = cutoff.unwrap_or(i64::MAX);
+            let removed = self
+                .delete_expired(&table.table, ("chain_id", "block_timestamp"), "created_at < $1", &[&cutoff])
+                .await?;
+            if removed > 0 {
+                tracing::warn!("Size cap: deleted {} oldest rows from {}", removed, table.table);
+            }
+            deleted += removed;
+        }
+
+        Ok(deleted)
+    }
+
+    // =========================================================================
+    // Cleanup Methods
+    // =========================================================================
+
+    /// VACUUM (ANALYZE) the tables cleanup deletes from, returning the bytes reclaimed
+    ///
+    /// Plain VACUUM makes the space of deleted rows reusable and returns empty
+    /// pages at the end of a table to the OS without blocking reads or writes,
+    /// unlike VACUUM FULL. Autovacuum gets there eventually; running it right
+    /// after cleanup keeps busy tables from growing while they wait for its
+    /// thresholds. Partitioned tables are vacuumed partition by partition.
+    pub async fn vacuum(&self) -> Result<u64, DbError> {
+        let before = self.get_size().await?.total_bytes;
+
+        let client = self.pool.get().await?;
+        for table in VACUUM_TABLES {
+            client.batch_execute(&format!("VACUUM (ANALYZE) {}", table)).await?;
+        }
+        drop(client);
+
+        let after = self.get_size().await?.total_bytes;
+        Ok(before.saturating_sub(after))
+    }
+
+    /// Clean up old data in every table that has a TTL
+    pub async fn cleanup_all(&self, retention: &Retention) -> Result<CleanupStats, DbError> {
+        let mut stats = CleanupStats::default();
+
+        if let Some(ttl_secs) = retention.transfers {
+            stats.transfers_deleted = self.cleanup_old_transfers(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.approvals {
+            stats.approvals_deleted = self.cleanup_old_approvals(ttl_secs).await?;
+        }
+        if retention.fusion_plus.is_some() || retention.fusion_plus_max_age.is_some() {
+            stats.fusion_plus_deleted = self
+                .cleanup_old_fusion_plus(retention.fusion_plus, retention.fusion_plus_max_age)
+                .await?;
+        }
+        if let Some(ttl_secs) = retention.fusion {
+            stats.fusion_deleted = self.cleanup_old_fusion_swaps(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.crypto2fiat {
+            stats.crypto2fiat_deleted = self.cleanup_old_crypto2fiat(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.raw_events {
+            stats.raw_events_deleted = self.cleanup_old_raw_events(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.delegations {
+            stats.delegations_deleted = self.cleanup_old_delegations(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.transactions {
+            stats.transactions_deleted = self.cleanup_old_transactions(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.raw_logs {
+            stats.raw_logs_deleted = self.cleanup_old_raw_logs(ttl_secs).await?;
+        }
+        if let Some(ttl_secs) = retention.internal_transfers {
+            stats.internal_transfers_deleted = self.cleanup_old_internal_transfers(ttl_secs).await?;
+        }
+        if let Some(max_size_bytes) = self.config.max_size_bytes {
+            stats.size_cap_deleted = self.enforce_size_cap(max_size_bytes).await?;
         }
+
+        Ok(stats)
+    }
+}
+
+/// Database size less the share of each table's bytes held by dead rows
+fn live_bytes(total_bytes: u64, tables: &[TableSize]) -> u64 {
+    let dead: u64 = tables
+        .iter()
+        .filter(|t| t.live_rows + t.dead_rows > 0)
+        .map(|t| (t.total_bytes as u128 * t.dead_rows as u128 / (t.live_rows + t.dead_rows) as u128) as u64)
+        .sum();
+    total_bytes.saturating_sub(dead)
+}
+
+/// Share of live data to delete to bring `live_bytes` down to `max_bytes`
+fn size_cap_fraction(live_bytes: u64, max_bytes: u64) -> f64 {
+    if live_bytes == 0 || live_bytes <= max_bytes {
+        return 0.0;
+    }
+    (live_bytes - max_bytes) as f64 / live_bytes as f64
+}
+
+/// Lowercase `0x`-prefixed form of a hex search prefix, None if it isn't
+/// hex or is shorter than `MIN_SEARCH_DIGITS`
+pub fn normalize_search_prefix(prefix: &str) -> Option<String> {
+    let prefix = prefix.trim().to_lowercase();
+    let digits = prefix.strip_prefix("0x").unwrap_or(&prefix);
+    let valid = (MIN_SEARCH_DIGITS..=64).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_hexdigit());
+    valid.then(|| format!("0x{}", digits))
+}
+
+/// Sort block ranges and merge the ones that overlap or touch
+fn merge_block_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (from_block, to_block) in ranges {
+        match merged.last_mut() {
+            Some(last) if from_block <= last.1.saturating_add(1) => last.1 = last.1.max(to_block),
+            _ => merged.push((from_block, to_block)),
+        }
+    }
+    merged
+}
+
+/// Current unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Name of the daily partition for `day` (days since unix epoch), e.g. transfers_20250131
+fn partition_name(table: &str, day: u64) -> String {
+    let (year, month, dom) = civil_from_days(day as i64);
+    format!("{}_{:04}{:02}{:02}", table, year, month, dom)
+}
+
+/// Parse the day (days since unix epoch) back out of a daily partition name
+fn parse_partition_day(table: &str, name: &str) -> Option<u64> {
+    let date = name.strip_prefix(table)?.strip_prefix('_')?;
+    if date.len() != 8 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: i64 = date[0..4].parse().ok()?;
+    let month: u32 = date[4..6].parse().ok()?;
+    let dom: u32 = date[6..8].parse().ok()?;
+    u64::try_from(days_from_civil(year, month, dom)).ok()
+}
+
+/// Convert days since unix epoch to a (year, month, day) UTC date
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Convert a (year, month, day) UTC date to days since unix epoch
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Keyset condition (appended to a WHERE clause), ORDER BY and bind values for
+/// `cursor`, with placeholders numbered from `first_param`
+fn keyset_sql(cursor: &Cursor, id: &str, timestamp: &str, first_param: usize) -> (String, String, Vec<i64>) {
+    if let Some(since_id) = cursor.since_id {
+        return (format!(" AND {} > ${}", id, first_param), format!("{} ASC", id), vec![since_id]);
+    }
+
+    let order_by = format!("{} DESC, {} DESC", timestamp, id);
+    match (cursor.before_timestamp, cursor.before_id) {
+        (Some(ts), Some(before_id)) => (
+            format!(" AND ({}, {}) < (${}, ${})", timestamp, id, first_param, first_param + 1),
+            order_by,
+            vec![ts as i64, before_id],
+        ),
+        (Some(ts), None) => (format!(" AND {} < ${}", timestamp, first_param), order_by, vec![ts as i64]),
+        (None, _) => (String::new(), order_by, Vec::new()),
+    }
+}
+
+/// Merge per-chain pages (each already in `cursor` order) into one page of at most `limit` rows
+///
+/// Row ids come from one sequence shared by all chains, so they order rows
+/// across chains the same way they do within one.
+fn merge_transfer_pages(pages: Vec<Vec<Transfer>>, cursor: &Cursor, limit: u32) -> Vec<Transfer> {
+    let mut transfers: Vec<Transfer> = pages.into_iter().flatten().collect();
+    if cursor.since_id.is_some() {
+        transfers.sort_by_key(|t| t.id);
+    } else {
+        transfers.sort_by_key(|t| Reverse((t.block_timestamp, t.id)));
+    }
+    transfers.truncate(limit as usize);
+    transfers
+}
+
+/// Balance delta from a per-token sums row: (token, inflow, outflow, net,
+/// decimals, transfer_count, unvalued_count)
+fn balance_delta(chain_id: u32, address: &str, row: &Row) -> BalanceDelta {
+    let decimals = row.get::<_, Option<i16>>(4).and_then(|d| u8::try_from(d).ok());
+    let (inflow, outflow, net): (String, String, String) = (row.get(1), row.get(2), row.get(3));
+    BalanceDelta {
+        chain_id,
+        address: address.to_string(),
+        token: row.get(0),
+        decimals,
+        inflow_formatted: format_signed_units(&inflow, decimals),
+        outflow_formatted: format_signed_units(&outflow, decimals),
+        net_formatted: format_signed_units(&net, decimals),
+        inflow,
+        outflow,
+        net,
+        transfer_count: row.get::<_, i64>(5) as u64,
+        unvalued_count: row.get::<_, i64>(6) as u64,
+    }
+}
+
+/// `format_units` for a decimal sum, which may be negative; None without
+/// decimals or past the range of a word
+fn format_signed_units(sum: &str, decimals: Option<u8>) -> Option<String> {
+    let (sign, digits) = match sum.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", sum),
+    };
+    let amount = U256::from_str_radix(digits, 10).ok()?;
+    Some(format!("{}{}", sign, format_units(amount, decimals?)))
+}
+
+/// Parse a transfer value: one 32-byte word as 0x-prefixed hex
+fn decode_uint256_word(value: &str) -> Option<U256> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    if hex.is_empty() || hex.len() > 64 {
+        return None;
+    }
+    U256::from_str_radix(hex, 16).ok()
+}
+
+/// Decimal form of a hex quantity for a NUMERIC column; None when absent or malformed
+fn hex_to_decimal(value: Option<&str>) -> Option<String> {
+    value.and_then(decode_uint256_word).map(|amount| amount.to_string())
+}
+
+/// Render a raw token amount scaled by `decimals`, without trailing zeros
+fn format_units(amount: U256, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
     }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (int, frac) = padded.split_at(padded.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        int.to_string()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+/// Rows removed or reset by a reorg rollback or a replay
+#[derive(Default, Debug)]
+pub struct RollbackStats {
+    pub transfers_deleted: usize,
+    pub approvals_deleted: usize,
+    pub fusion_plus_deleted: usize,
+    /// Fusion+ timeline events in the range
+    pub fusion_plus_events_deleted: usize,
+    /// Fusion+ legs whose cancellation was in the range, returned to `created`
+    pub fusion_plus_cancel_reset: usize,
+    /// Fusion+ swaps with a withdrawal in the range, returned to their prior state
+    pub fusion_plus_withdrawal_reset: usize,
+    pub fusion_plus_dst_reset: usize,
+    pub fusion_deleted: usize,
+    /// Fusion swaps whose cancellation was in the range, returned to `filled`
+    pub fusion_cancel_reset: usize,
+    pub crypto2fiat_deleted: usize,
+    pub raw_events_deleted: usize,
+    pub delegations_deleted: usize,
+    pub transactions_deleted: usize,
+}
+
+/// Indexes verified by `Database::check_integrity`
+#[derive(Default, Debug)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub corrupt: Vec<String>,
+    /// Corrupt indexes rebuilt with REINDEX
+    pub repaired: Vec<String>,
+}
+
+/// A chain writer's buffered rows and progress markers for `Database::commit_writes`
+#[derive(Debug, Default)]
+pub struct ChainWrites<'a> {
+    /// Logs with their block timestamps, for `raw_logs`
+    pub raw_logs: &'a [(Log, u64)],
+    pub transfers: &'a [Transfer],
+    pub approvals: &'a [Approval],
+    pub raw_events: &'a [RawEvent],
+    pub delegations: &'a [Delegation],
+    pub transactions: &'a [TransactionInfo],
+    pub internal_transfers: &'a [InternalTransfer],
+    /// Block ranges these rows cover, for gap detection
+    pub processed_ranges: &'a [(u64, u64)],
+    pub checkpoint: Option<u64>,
+    /// (from_block, to_block, next_block) of a backfill range
+    pub backfill_progress: Option<(u64, u64, u64)>,
+}
+
+/// A Fusion+ write for `Database::apply_fusion_plus_changes`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum FusionPlusChange {
+    /// SrcEscrowCreated: insert the swap (kept as is if it exists), with the
+    /// fill's Merkle proof for orders allowing multiple fills
+    Created {
+        #[serde(flatten)]
+        swap: Box<FusionPlusSwap>,
+        merkle_fill: Option<MerkleFill>,
+    },
+    /// DstEscrowCreated: fill in the destination leg
+    DstCreated(FusionPlusDst),
+    /// EscrowWithdrawal: mark the emitting escrow's leg withdrawn
+    Withdrawn(FusionPlusWithdrawal),
+    /// EscrowCancelled: mark the emitting escrow's leg cancelled
+    Cancelled(FusionPlusCancellation),
+}
+
+/// Destination leg of a Fusion+ swap, from DstEscrowCreated
+#[derive(Debug, Clone, Serialize)]
+pub struct FusionPlusDst {
+    pub order_hash: String,
+    /// Selects the fill of a multiple-fill order
+    pub hashlock: String,
+    pub dst_taker: String,
+    pub dst_timelocks: String,
+    pub chain_id: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub log_index: u32,
+    pub escrow_address: Option<String>,
+}
+
+/// A Fusion+ escrow withdrawal revealing the swap's secret
+#[derive(Debug, Clone, Serialize)]
+pub struct FusionPlusWithdrawal {
+    pub hashlock: String,
+    /// Emitting escrow; selects the leg
+    pub escrow_address: String,
+    pub chain_id: u32,
+    pub secret: String,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub log_index: u32,
+    /// Sender of the withdrawal tx, if its receipt was fetched
+    pub withdrawer: Option<String>,
+}
+
+/// A Fusion+ escrow cancellation; the event carries no data, so the swap is
+/// matched by the emitting escrow
+#[derive(Debug, Clone, Serialize)]
+pub struct FusionPlusCancellation {
+    pub escrow_address: String,
+    pub chain_id: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub log_index: u32,
+}
+
+/// Swap and leg updated by a `FusionPlusChange`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FusionPlusApplied {
+    pub order_hash: String,
+    pub is_src: bool,
+}
+
+#[derive(Default, Debug)]
+pub struct CleanupStats {
+    pub transfers_deleted: usize,
+    pub approvals_deleted: usize,
+    pub fusion_plus_deleted: usize,
+    pub fusion_deleted: usize,
+    pub crypto2fiat_deleted: usize,
+    pub raw_events_deleted: usize,
+    pub delegations_deleted: usize,
+    pub transactions_deleted: usize,
+    pub raw_logs_deleted: usize,
+    pub internal_transfers_deleted: usize,
+    /// Rows removed early because the database exceeded `max_size_bytes`
+    pub size_cap_deleted: usize,
+}
+
+/// Database for tests that need PostgreSQL, connected to `TEST_DATABASE_URL`
+///
+/// Returns None when the variable is unset, so those tests pass without a
+/// server. The guard serializes them, as they share one database.
+#[cfg(test)]
+pub(crate) async fn test_database() -> Option<(tokio::sync::MutexGuard<'static, ()>, Database)> {
+    static LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    let guard = LOCK.lock().await;
+    let db = Database::new(&url).await.expect("TEST_DATABASE_URL is not usable");
+    Some((guard, db))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Get Fusion+ swap by order_hash
-    pub async fn get_fusion_plus_swap(&self, order_hash: &str) -> Result<Option<FusionPlusSwap>, DbError> {
-        let client = self.pool.get().await?;
+    #[test]
+    fn test_merge_transfer_pages() {
+        let transfer = |chain_id, id, block_timestamp| Transfer {
+            chain_id,
+            tx_hash: String::new(),
+            log_index: 0,
+            token: String::new(),
+            from_addr: String::new(),
+            to_addr: String::new(),
+            value: String::new(),
+            value_decimal: None,
+            block_number: 0,
+            block_timestamp,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: Some(id),
+        };
+        let pages = || vec![
+            vec![transfer(1, 5, 300), transfer(1, 2, 100)],
+            vec![transfer(8453, 4, 300), transfer(8453, 3, 200)],
+        ];
+        let key = |ts: Vec<Transfer>| ts.iter().map(|t| (t.chain_id, t.id.unwrap())).collect::<Vec<_>>();
 
-        let row = client.query_opt(
-            "SELECT order_hash, hashlock, secret,
-                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
-                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
-                    src_safety_deposit, src_timelocks, src_status,
-                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
-                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
-                    dst_safety_deposit, dst_timelocks, dst_status
-             FROM fusion_plus_swaps WHERE order_hash = $1",
-            &[&order_hash.to_lowercase()],
-        ).await?;
+        let newest = merge_transfer_pages(pages(), &Cursor::default(), 3);
+        assert_eq!(key(newest), vec![(1, 5), (8453, 4), (8453, 3)]);
 
-        Ok(row.map(|r| Self::row_to_fusion_plus_swap(&r)))
+        let since = Cursor { since_id: Some(1), ..Default::default() };
+        assert_eq!(key(merge_transfer_pages(pages(), &since, 2)), vec![(1, 2), (8453, 3)]);
     }
 
-    /// Get Fusion+ swap by hashlock
-    pub async fn get_fusion_plus_swap_by_hashlock(&self, hashlock: &str) -> Result<Option<FusionPlusSwap>, DbError> {
-        let client = self.pool.get().await?;
+    #[test]
+    fn test_keyset_sql() {
+        let (keyset, order_by, values) = keyset_sql(&Cursor::default(), "id", "block_timestamp", 2);
+        assert_eq!((keyset.as_str(), order_by.as_str(), values), ("", "block_timestamp DESC, id DESC", vec![]));
 
-        let row = client.query_opt(
-            "SELECT order_hash, hashlock, secret,
-                    src_chain_id, src_tx_hash, src_block_number, src_block_timestamp, src_log_index,
-                    src_escrow_address, src_maker, src_taker, src_token, src_amount,
-                    src_safety_deposit, src_timelocks, src_status,
-                    dst_chain_id, dst_tx_hash, dst_block_number, dst_block_timestamp, dst_log_index,
-                    dst_escrow_address, dst_maker, dst_taker, dst_token, dst_amount,
-                    dst_safety_deposit, dst_timelocks, dst_status
-             FROM fusion_plus_swaps WHERE hashlock = $1",
-            &[&hashlock.to_lowercase()],
-        ).await?;
+        let since = Cursor { since_id: Some(42), ..Default::default() };
+        let (keyset, order_by, values) = keyset_sql(&since, "t.id", "t.block_timestamp", 3);
+        assert_eq!((keyset.as_str(), order_by.as_str(), values), (" AND t.id > $3", "t.id ASC", vec![42]));
 
-        Ok(row.map(|r| Self::row_to_fusion_plus_swap(&r)))
+        let before = Cursor { before_timestamp: Some(1_700_000_000), before_id: Some(7), ..Default::default() };
+        let (keyset, _, values) = keyset_sql(&before, "id", "block_timestamp", 2);
+        assert_eq!(keyset, " AND (block_timestamp, id) < ($2, $3)");
+        assert_eq!(values, vec![1_700_000_000, 7]);
     }
 
-    /// Get total count of Fusion+ swaps
-    pub async fn get_fusion_plus_count(&self) -> Result<u64, DbError> {
-        let client = self.pool.get().await?;
-        let row = client.query_one(
-            "SELECT COUNT(*) FROM fusion_plus_swaps",
-            &[],
-        ).await?;
+    #[test]
+    fn test_partition_name_round_trip() {
+        // 2024-02-29 is day 19782 since the unix epoch
+        assert_eq!(partition_name("transfers", 19_782), "transfers_20240229");
+        assert_eq!(partition_name("transfers", 0), "transfers_19700101");
+        assert_eq!(parse_partition_day("transfers", "transfers_20240229"), Some(19_782));
+        assert_eq!(parse_partition_day("transfers", "transfers_default"), None);
 
-        Ok(row.get::<_, i64>(0) as u64)
+        for day in [0, 10_956, 19_782, 20_000, 30_000] {
+            assert_eq!(parse_partition_day("transfers", &partition_name("transfers", day)), Some(day));
+        }
     }
 
-    /// Clean up old Fusion+ swaps based on TTL
-    pub async fn cleanup_old_fusion_plus(&self, ttl_secs: u64) -> Result<usize, DbError> {
-        let client = self.pool.get().await?;
-        let cutoff = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64
-            - ttl_secs as i64;
+    #[test]
+    fn test_merge_block_ranges() {
+        let merged = merge_block_ranges(vec![(201, 300), (1, 100), (101, 150), (120, 130), (400, 500)]);
+        assert_eq!(merged, vec![(1, 150), (201, 300), (400, 500)]);
+        assert!(merge_block_ranges(Vec::new()).is_empty());
+    }
 
-        let deleted = client.execute(
-            "DELETE FROM fusion_plus_swaps WHERE created_at < $1",
-            &[&cutoff],
-        ).await?;
+    #[test]
+    fn test_normalize_search_prefix() {
+        assert_eq!(normalize_search_prefix(" 0xABCD ").as_deref(), Some("0xabcd"));
+        assert_eq!(normalize_search_prefix("abcd12").as_deref(), Some("0xabcd12"));
+        assert_eq!(normalize_search_prefix("0xabc"), None); // Too short
+        assert_eq!(normalize_search_prefix("0xabcg"), None);
+        assert_eq!(normalize_search_prefix("0xab%_"), None);
+        assert_eq!(normalize_search_prefix(&format!("0x{}", "a".repeat(65))), None);
+    }
+
+    #[test]
+    fn test_live_bytes_and_size_cap_fraction() {
+        let table = |table: &str, total_bytes, live_rows, dead_rows| TableSize {
+            table: table.to_string(),
+            total_bytes,
+            live_rows,
+            dead_rows,
+        };
+        let tables = [table("transfers", 800, 3, 1), table("tokens", 100, 10, 0), table("empty", 50, 0, 0)];
+        // A quarter of the transfers bytes are dead
+        assert_eq!(live_bytes(1_000, &tables), 800);
+        assert_eq!(live_bytes(100, &tables), 0);
 
-        Ok(deleted as usize)
+        assert_eq!(size_cap_fraction(800, 1_000), 0.0);
+        assert_eq!(size_cap_fraction(800, 600), 0.25);
+        assert_eq!(size_cap_fraction(0, 0), 0.0);
     }
 
-    // =========================================================================
-    // Fusion (Single-Chain) Methods
-    // =========================================================================
+    #[tokio::test]
+    async fn test_balance_deltas() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_017;
+        let cleanup = || async {
+            let client = db.pool.get().await.unwrap();
+            client
+                .batch_execute(&format!(
+                    "DELETE FROM transfers WHERE chain_id = {chain_id};
+                     DELETE FROM tokens WHERE chain_id = {chain_id};"
+                ))
+                .await
+                .unwrap();
+        };
+        cleanup().await;
 
-    /// Insert a new Fusion swap
-    pub async fn insert_fusion_swap(&self, swap: &FusionSwap) -> Result<bool, DbError> {
-        let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let me = "0x00000000000000000000000000000000000000aa";
+        let other = "0x00000000000000000000000000000000000000bb";
+        let usdc = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+        let weth = "0x4200000000000000000000000000000000000006";
+        let transfer = |log_index: u32, token: &str, from_addr: &str, to_addr: &str, value: Option<u64>| Transfer {
+            chain_id,
+            tx_hash: format!("0x{:064x}", 1),
+            log_index,
+            token: token.to_string(),
+            from_addr: from_addr.to_string(),
+            to_addr: to_addr.to_string(),
+            value: value.map(|v| format!("0x{:064x}", v)).unwrap_or_default(),
+            value_decimal: value.map(|v| v.to_string()),
+            block_number: 100,
+            block_timestamp: 1_000 + log_index as u64,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        };
+        db.insert_transfers_batch(chain_id, &[
+            transfer(0, usdc, other, me, Some(1000)),
+            transfer(1, usdc, me, other, Some(500)),
+            transfer(2, weth, me, other, Some(100)),
+            transfer(3, weth, other, me, None), // Compact-mode row, counted but not summed
+            transfer(4, usdc, other, me, Some(7)), // Outside the window
+        ])
+        .await
+        .unwrap();
+        db.upsert_token(chain_id, usdc, &TokenInfo { symbol: None, name: None, decimals: Some(6) }).await.unwrap();
 
-        let result = client.execute(
-            "INSERT INTO fusion_swaps (
-                order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
-                maker, taker, maker_token, taker_token, maker_amount, taker_amount,
-                remaining, is_partial_fill, status, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
-            ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING",
-            &[
-                &swap.order_hash.to_lowercase(),
-                &(swap.chain_id as i32),
-                &swap.tx_hash.to_lowercase(),
-                &(swap.block_number as i64),
-                &(swap.block_timestamp as i64),
-                &(swap.log_index as i32),
-                &swap.maker.to_lowercase(),
-                &swap.taker.as_ref().map(|s| s.to_lowercase()),
-                &swap.maker_token.as_ref().map(|s| s.to_lowercase()),
-                &swap.taker_token.as_ref().map(|s| s.to_lowercase()),
-                &swap.maker_amount,
-                &swap.taker_amount,
-                &swap.remaining,
-                &swap.is_partial_fill,
-                &swap.status,
-                &now,
-            ],
-        ).await?;
+        let deltas = db.get_balance_deltas(chain_id, me, 1_000, 1_003).await.unwrap();
+        assert_eq!(deltas.iter().map(|d| d.token.as_str()).collect::<Vec<_>>(), [weth, usdc]);
 
-        Ok(result > 0)
+        let usdc_delta = &deltas[1];
+        assert_eq!(usdc_delta.address, me);
+        assert_eq!((usdc_delta.inflow.as_str(), usdc_delta.outflow.as_str(), usdc_delta.net.as_str()), ("1000", "500", "500"));
+        assert_eq!(usdc_delta.decimals, Some(6));
+        assert_eq!(usdc_delta.inflow_formatted.as_deref(), Some("0.001"));
+        assert_eq!(usdc_delta.net_formatted.as_deref(), Some("0.0005"));
+        assert_eq!((usdc_delta.transfer_count, usdc_delta.unvalued_count), (2, 0));
+
+        let weth_delta = &deltas[0];
+        assert_eq!((weth_delta.inflow.as_str(), weth_delta.net.as_str()), ("0", "-100"));
+        assert_eq!(weth_delta.net_formatted, None);
+        assert_eq!((weth_delta.transfer_count, weth_delta.unvalued_count), (2, 1));
+
+        assert_eq!(db.get_balance_delta(chain_id, me, usdc, 1_000, 1_004).await.unwrap().net, "507");
+        let none = db.get_balance_delta(chain_id, me, usdc, 0, 999).await.unwrap();
+        assert_eq!((none.net.as_str(), none.transfer_count), ("0", 0));
+
+        cleanup().await;
     }
 
-    fn row_to_fusion_swap(row: &Row) -> FusionSwap {
-        FusionSwap {
-            order_hash: row.get(0),
-            chain_id: row.get::<_, i32>(1) as u32,
-            tx_hash: row.get(2),
-            block_number: row.get::<_, i64>(3) as u64,
-            block_timestamp: row.get::<_, i64>(4) as u64,
-            log_index: row.get::<_, i32>(5) as u32,
-            maker: row.get(6),
-            taker: row.get(7),
-            maker_token: row.get(8),
-            taker_token: row.get(9),
-            maker_amount: row.get(10),
-            taker_amount: row.get(11),
-            remaining: row.get(12),
-            is_partial_fill: row.get(13),
-            status: row.get(14),
-        }
+    #[test]
+    fn test_format_signed_units() {
+        assert_eq!(format_signed_units("-499999999999999999", Some(18)).as_deref(), Some("-0.499999999999999999"));
+        assert_eq!(format_signed_units("1000000000000000000", Some(18)).as_deref(), Some("1"));
+        assert_eq!(format_signed_units("0", Some(6)).as_deref(), Some("0"));
+        assert_eq!(format_signed_units("100", None), None);
+        // Sums of many large values can outgrow a word
+        assert_eq!(format_signed_units(&"9".repeat(80), Some(18)), None);
     }
 
-    /// Get Fusion swap by order_hash
-    pub async fn get_fusion_swap_by_order_hash(&self, order_hash: &str) -> Result<Option<FusionSwap>, DbError> {
-        let client = self.pool.get().await?;
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(U256::from(1_500_000u64), 6), "1.5");
+        assert_eq!(format_units(U256::from(1u64), 6), "0.000001");
+        assert_eq!(format_units(U256::from(2_000_000u64), 6), "2");
+        assert_eq!(format_units(U256::ZERO, 18), "0");
+        assert_eq!(format_units(U256::from(42u64), 0), "42");
+    }
 
-        let row = client.query_opt(
-            "SELECT order_hash, chain_id, tx_hash, block_number, block_timestamp, log_index,
-                    maker, taker, maker_token, taker_token, maker_amount, taker_amount,
-                    remaining, is_partial_fill, status
-             FROM fusion_swaps WHERE order_hash = $1
-             ORDER BY block_timestamp DESC LIMIT 1",
-            &[&order_hash.to_lowercase()],
-        ).await?;
+    #[tokio::test]
+    async fn test_raw_logs_round_trip() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_001;
+        let log = |block_number: u64| Log {
+            address: "0x4200000000000000000000000000000000000006".to_string(),
+            topics: vec![crate::types::TRANSFER_TOPIC.to_string()],
+            data: format!("0x{:064x}", 5),
+            block_number: format!("0x{:x}", block_number),
+            transaction_hash: format!("0x{:064x}", block_number),
+            log_index: "0x1".to_string(),
+        };
+        let client = db.pool.get().await.unwrap();
+        client.execute("DELETE FROM raw_logs WHERE chain_id = $1", &[&(chain_id as i32)]).await.unwrap();
 
-        Ok(row.map(|r| Self::row_to_fusion_swap(&r)))
+        let (first, second) = (log(10), log(11));
+        let inserted = db.insert_raw_logs_batch(chain_id, &[(&first, 100), (&second, 112)]).await.unwrap();
+        assert_eq!(inserted, 2);
+        assert_eq!(db.insert_raw_logs_batch(chain_id, &[(&first, 100)]).await.unwrap(), 0);
+
+        let logs = db.get_raw_logs(chain_id, 10, 10).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!((logs[0].0.data.as_str(), logs[0].1), (first.data.as_str(), 100));
+
+        // A row that no longer parses fails the read instead of being skipped
+        client
+            .execute("UPDATE raw_logs SET log = '{}' WHERE chain_id = $1 AND block_number = 11", &[&(chain_id as i32)])
+            .await
+            .unwrap();
+        assert!(matches!(db.get_raw_logs(chain_id, 10, 11).await, Err(DbError::Json(_))));
+
+        client.execute("DELETE FROM raw_logs WHERE chain_id = $1", &[&(chain_id as i32)]).await.unwrap();
     }
 
-    /// Get total count of Fusion swaps
-    pub async fn get_fusion_swap_count(&self) -> Result<u64, DbError> {
-        let client = self.pool.get().await?;
-        let row = client.query_one(
-            "SELECT COUNT(*) FROM fusion_swaps",
-            &[],
-        ).await?;
+    #[tokio::test]
+    async fn test_fusion_order_fills() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_002;
+        let order_hash = format!("0x{:064x}", 0x4553);
+        let fill = |block_number: u64, maker_amount: u64, remaining: u64| FusionSwap {
+            order_hash: order_hash.clone(),
+            chain_id,
+            tx_hash: format!("0x{:064x}", block_number),
+            block_number,
+            block_timestamp: 1_700_000_000 + block_number,
+            log_index: 3,
+            maker: "0x00000000000000000000000000000000000000aa".to_string(),
+            taker: None,
+            maker_token: None,
+            taker_token: None,
+            maker_amount: Some(format!("0x{:064x}", maker_amount)),
+            taker_amount: Some(format!("0x{:x}", maker_amount * 2)),
+            remaining: format!("0x{:064x}", remaining),
+            is_partial_fill: remaining > 0,
+            status: "filled".to_string(),
+            id: None,
+        };
+        let client = db.pool.get().await.unwrap();
+        client.execute("DELETE FROM fusion_swaps WHERE chain_id = $1", &[&(chain_id as i32)]).await.unwrap();
 
-        Ok(row.get::<_, i64>(0) as u64)
+        assert!(db.insert_fusion_swap(&fill(10, 600, 400)).await.unwrap());
+        assert!(!db.insert_fusion_swap(&fill(10, 600, 400)).await.unwrap());
+        assert!(db.insert_fusion_swap(&fill(12, 400, 0)).await.unwrap());
+
+        let order = db.get_fusion_order(chain_id, &order_hash).await.unwrap().unwrap();
+        assert_eq!(order.fill_count, 2);
+        assert_eq!(order.filled_maker_amount.as_deref(), Some("1000"));
+        assert_eq!(order.filled_taker_amount.as_deref(), Some("2000"));
+        assert_eq!(order.remaining.as_deref(), Some("0"));
+        assert_eq!(order.status, "filled");
+        assert_eq!((order.first_fill_block, order.last_fill_block), (10, 12));
+        let indexes: Vec<_> = order.fills.iter().map(|f| (f.fill_index, f.block_number, f.remaining.as_deref())).collect();
+        assert_eq!(indexes, [(1, 10, Some("400")), (2, 12, Some("0"))]);
+
+        // Rolling back the last fill takes its order_fills row with it
+        db.clear_decoded_range(chain_id, 12, 12).await.unwrap();
+        let order = db.get_fusion_order(chain_id, &order_hash).await.unwrap().unwrap();
+        assert_eq!((order.fill_count, order.remaining.as_deref()), (1, Some("400")));
+        assert_eq!(order.status, "partially_filled");
+
+        db.cancel_fusion_swap(chain_id, &order_hash, &format!("0x{:064x}", 13), 13).await.unwrap();
+        assert_eq!(db.get_fusion_order(chain_id, &order_hash).await.unwrap().unwrap().status, "cancelled");
+
+        client.execute("DELETE FROM fusion_swaps WHERE chain_id = $1", &[&(chain_id as i32)]).await.unwrap();
+        assert!(db.get_fusion_order(chain_id, &order_hash).await.unwrap().is_none());
+        let orphans: i64 = client
+            .query_one("SELECT COUNT(*) FROM order_fills WHERE chain_id = $1", &[&(chain_id as i32)])
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(orphans, 0);
     }
 
-    /// Clean up old Fusion swaps based on TTL
-    pub async fn cleanup_old_fusion_swaps(&self, ttl_secs: u64) -> Result<usize, DbError> {
-        let client = self.pool.get().await?;
-        let cutoff = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
+    #[test]
+    fn test_hex_to_decimal() {
+        assert_eq!(hex_to_decimal(Some("0x3e8")).as_deref(), Some("1000"));
+        assert_eq!(hex_to_decimal(Some(&format!("0x{}", "f".repeat(64)))).as_deref(), Some(U256::MAX.to_string().as_str()));
+        assert_eq!(hex_to_decimal(Some("0x")), None);
+        assert_eq!(hex_to_decimal(Some("0xzz")), None);
+        assert_eq!(hex_to_decimal(None), None);
+    }
+
+    #[tokio::test]
+    async fn test_fusion_enrichment_queue() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_003;
+        // Swaps stored without details (empty maker) wait for enrichment
+        let pending = |block_number: u64, block_timestamp: u64| FusionSwap {
+            order_hash: format!("0x{:064x}", block_number),
+            chain_id,
+            tx_hash: format!("0x{:064x}", block_number),
+            block_number,
+            block_timestamp,
+            log_index: 0,
+            maker: String::new(),
+            taker: None,
+            maker_token: None,
+            taker_token: None,
+            maker_amount: None,
+            taker_amount: None,
+            remaining: format!("0x{:064x}", 0),
+            is_partial_fill: false,
+            status: "filled".to_string(),
+            id: None,
+        };
+        let client = db.pool.get().await.unwrap();
+        client.execute("DELETE FROM fusion_swaps WHERE chain_id = $1", &[&(chain_id as i32)]).await.unwrap();
+
+        for swap in [pending(1, 1_000), pending(2, 2_000), pending(3, 3_000)] {
+            db.insert_fusion_swap(&swap).await.unwrap();
+        }
+        let queued = db.get_pending_fusion_enrichment(chain_id, 0, 10).await.unwrap();
+        assert_eq!(queued.iter().map(|s| s.block_number).collect::<Vec<_>>(), [1, 2, 3]);
+        let id = |block_number: u64| queued.iter().find(|s| s.block_number == block_number).and_then(|s| s.id).unwrap();
+
+        // Retry: failed attempts keep the swap queued until max_attempts
+        assert!(!db.record_fusion_enrich_attempt(id(2), 3).await.unwrap());
+        assert!(!db.record_fusion_enrich_attempt(id(2), 3).await.unwrap());
+        assert!(db.record_fusion_enrich_attempt(id(2), 3).await.unwrap());
+        assert!(!db.record_fusion_enrich_attempt(id(2), 3).await.unwrap()); // No longer pending
+
+        // Success after a failed attempt fills in the details and leaves the queue
+        assert!(!db.record_fusion_enrich_attempt(id(3), 3).await.unwrap());
+        let maker = "0x00000000000000000000000000000000000000AA";
+        let taker = "0x00000000000000000000000000000000000000bb";
+        let tokens = ("0x00000000000000000000000000000000000000c1", "0x00000000000000000000000000000000000000c2");
+        let enriched = db
+            .complete_fusion_enrichment(id(3), maker, taker, tokens, ("0x64", "0xc8"))
+            .await
             .unwrap()
-            .as_secs() as i64
-            - ttl_secs as i64;
+            .unwrap();
+        assert_eq!(enriched.maker, maker.to_lowercase());
+        assert_eq!(enriched.maker_amount.as_deref(), Some("0x64"));
+        assert!(db.complete_fusion_enrichment(id(3), maker, taker, tokens, ("0x64", "0xc8")).await.unwrap().is_none());
+        let order = db.get_fusion_order(chain_id, &enriched.order_hash).await.unwrap().unwrap();
+        assert_eq!(order.filled_maker_amount.as_deref(), Some("100"));
+        assert_eq!(order.filled_taker_amount.as_deref(), Some("200"));
 
-        let deleted = client.execute(
-            "DELETE FROM fusion_swaps WHERE created_at < $1",
-            &[&cutoff],
-        ).await?;
+        // Give up: swaps older than the retry window are expired
+        assert_eq!(db.expire_fusion_enrichment(chain_id, 1_500).await.unwrap(), 1);
+        assert!(db.get_pending_fusion_enrichment(chain_id, 0, 10).await.unwrap().is_empty());
+
+        let states: Vec<(i64, Option<String>, i32)> = client
+            .query(
+                "SELECT block_number, enrichment, enrich_attempts FROM fusion_swaps WHERE chain_id = $1 ORDER BY block_number",
+                &[&(chain_id as i32)],
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| (r.get(0), r.get(1), r.get(2)))
+            .collect();
+        assert_eq!(
+            states,
+            [(1, Some("failed".to_string()), 0), (2, Some("failed".to_string()), 3), (3, None, 2)]
+        );
 
-        Ok(deleted as usize)
+        client.execute("DELETE FROM fusion_swaps WHERE chain_id = $1", &[&(chain_id as i32)]).await.unwrap();
     }
 
-    // =========================================================================
-    // Crypto2Fiat Methods
-    // =========================================================================
+    #[tokio::test]
+    async fn test_fusion_plus_fill_proofs() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let (src_chain, dst_chain) = (990_006, 990_007);
+        let address = |n: u64| format!("0x{:040x}", n);
+        let word = |n: u64| format!("0x{:064x}", n);
+        let order_hash = word(0x4598);
+        let fill = |hashlock: u64, block_number: u64| {
+            let data = crate::types::SrcEscrowCreatedData {
+                order_hash: order_hash.clone(),
+                hashlock: word(hashlock),
+                src_maker: address(1),
+                src_taker: address(2),
+                src_token: address(3),
+                src_amount: word(250),
+                src_safety_deposit: word(1),
+                src_timelocks: word(0),
+                dst_maker: address(1),
+                dst_amount: word(200),
+                dst_token: address(4),
+                dst_safety_deposit: word(1),
+                dst_chain_id: dst_chain,
+            };
+            Box::new(FusionPlusSwap::from_src_created(&data, src_chain, &word(block_number), block_number, 1_000, 0))
+        };
+        let merkle_fill = |hashlock: u64, index: u64, proof_valid: bool| {
+            Some(MerkleFill {
+                order_hash: order_hash.clone(),
+                root: word(0xf00d),
+                parts: 3,
+                index,
+                secret_hash: word(hashlock),
+                proof_valid,
+            })
+        };
+        let cleanup = || async {
+            let client = db.pool.get().await.unwrap();
+            client
+                .batch_execute(&format!(
+                    "DELETE FROM fusion_plus_swaps WHERE src_chain_id = {src_chain};
+                     DELETE FROM fusion_plus_events WHERE chain_id IN ({src_chain}, {dst_chain});
+                     DELETE FROM fusion_plus_fills WHERE src_chain_id = {src_chain};"
+                ))
+                .await
+                .unwrap();
+        };
+        cleanup().await;
 
-    /// Insert a new Crypto2Fiat event
-    pub async fn insert_crypto2fiat_event(&self, event: &Crypto2FiatEvent) -> Result<bool, DbError> {
-        let client = self.pool.get().await?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let changes = [
+            FusionPlusChange::Created { swap: fill(0xa, 100), merkle_fill: merkle_fill(0xa, 3, true) },
+            FusionPlusChange::Created { swap: fill(0xb, 101), merkle_fill: merkle_fill(0xb, 1, false) },
+            // A proof for another secret hash isn't this fill's
+            FusionPlusChange::Created { swap: fill(0xc, 102), merkle_fill: merkle_fill(0xa, 2, true) },
+            FusionPlusChange::DstCreated(FusionPlusDst {
+                order_hash: order_hash.clone(),
+                hashlock: word(0xb),
+                dst_taker: address(2),
+                dst_timelocks: word(0),
+                chain_id: dst_chain,
+                tx_hash: word(200),
+                block_number: 200,
+                block_timestamp: 1_200,
+                log_index: 0,
+                escrow_address: Some(address(0xd5c0)),
+            }),
+        ];
+        let applied = db.apply_fusion_plus_changes(&changes).await.unwrap();
+        assert!(applied.iter().all(Option::is_some));
 
-        let result = client.execute(
-            "INSERT INTO crypto2fiat_events (
-                order_id, token, amount, recipient, metadata,
-                chain_id, tx_hash, block_number, block_timestamp, log_index, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING",
-            &[
-                &event.order_id.to_lowercase(),
-                &event.token.to_lowercase(),
-                &event.amount,
-                &event.recipient.to_lowercase(),
-                &event.metadata,
-                &(event.chain_id as i32),
-                &event.tx_hash.to_lowercase(),
-                &(event.block_number as i64),
-                &(event.block_timestamp as i64),
-                &(event.log_index as i32),
-                &now,
-            ],
-        ).await?;
+        let fills = db.get_fusion_plus_fills(&order_hash).await.unwrap();
+        let proofs: Vec<_> = fills
+            .iter()
+            .map(|f| (f.hashlock.clone(), f.fill_index, f.hashlock_root.clone(), f.proof_valid))
+            .collect();
+        assert_eq!(
+            proofs,
+            [
+                (word(0xb), Some(1), Some(word(0xf00d)), Some(false)),
+                (word(0xa), Some(3), Some(word(0xf00d)), Some(true)),
+                (word(0xc), None, None, None),
+            ]
+        );
 
-        Ok(result > 0)
+        // The destination escrow of a later fill goes to that fill, not the swap record
+        assert_eq!(fills[0].dst_escrow_address.as_deref(), Some(address(0xd5c0).as_str()));
+        let swap = db.get_fusion_plus_swap(&order_hash).await.unwrap().unwrap();
+        assert_eq!(swap.hashlock, word(0xa));
+        assert_eq!(swap.dst_escrow_address, None);
+
+        cleanup().await;
     }
 
-    /// Get total count of Crypto2Fiat events
-    pub async fn get_crypto2fiat_count(&self) -> Result<u64, DbError> {
-        let client = self.pool.get().await?;
-        let row = client.query_one(
-            "SELECT COUNT(*) FROM crypto2fiat_events",
-            &[],
-        ).await?;
+    #[tokio::test]
+    async fn test_apply_fusion_plus_changes() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let (src_chain, dst_chain) = (990_004, 990_005);
+        let address = |n: u64| format!("0x{:040x}", n);
+        let word = |n: u64| format!("0x{:064x}", n);
+        let secret = word(0x5ec7e7);
+        let swap = |order: u64, src_escrow: u64| {
+            let data = crate::types::SrcEscrowCreatedData {
+                order_hash: word(order),
+                hashlock: crate::fusion::compute_hashlock_from_secret(&secret).unwrap(),
+                src_maker: address(1),
+                src_taker: address(2),
+                src_token: address(3),
+                src_amount: word(1000),
+                src_safety_deposit: word(1),
+                src_timelocks: word(0),
+                dst_maker: address(1),
+                dst_amount: word(900),
+                dst_token: address(4),
+                dst_safety_deposit: word(1),
+                dst_chain_id: dst_chain,
+            };
+            let mut swap = FusionPlusSwap::from_src_created(&data, src_chain, &word(order + 1), 100, 1_000, 1);
+            swap.src_escrow_address = Some(address(src_escrow));
+            Box::new(swap)
+        };
+        let withdrawal = |escrow: u64, chain_id: u32, block_number: u64| FusionPlusWithdrawal {
+            hashlock: crate::fusion::compute_hashlock_from_secret(&secret).unwrap(),
+            escrow_address: address(escrow),
+            chain_id,
+            secret: secret.clone(),
+            tx_hash: word(block_number),
+            block_number,
+            block_timestamp: 1_000 + block_number,
+            log_index: 0,
+            withdrawer: None,
+        };
+        let cleanup = || async {
+            let client = db.pool.get().await.unwrap();
+            client
+                .batch_execute(&format!(
+                    "DELETE FROM fusion_plus_swaps WHERE src_chain_id = {src_chain};
+                     DELETE FROM fusion_plus_events WHERE chain_id IN ({src_chain}, {dst_chain});
+                     DELETE FROM fusion_plus_fills WHERE src_chain_id = {src_chain};
+                     DELETE FROM checkpoints WHERE chain_id IN ({src_chain}, {dst_chain});"
+                ))
+                .await
+                .unwrap();
+        };
+        cleanup().await;
 
-        Ok(row.get::<_, i64>(0) as u64)
+        let first = swap(0x4562, 0xe5c0);
+        let changes = [
+            FusionPlusChange::Created { swap: first.clone(), merkle_fill: None },
+            FusionPlusChange::Created { swap: first.clone(), merkle_fill: None },
+            FusionPlusChange::DstCreated(FusionPlusDst {
+                order_hash: first.order_hash.clone(),
+                hashlock: first.hashlock.clone(),
+                dst_taker: address(2),
+                dst_timelocks: word(0),
+                chain_id: dst_chain,
+                tx_hash: word(200),
+                block_number: 200,
+                block_timestamp: 1_200,
+                log_index: 0,
+                escrow_address: Some(address(0xd5c0)),
+            }),
+            FusionPlusChange::Withdrawn(withdrawal(0xd5c0, dst_chain, 201)),
+            FusionPlusChange::Withdrawn(withdrawal(0xe5c0, src_chain, 101)),
+            FusionPlusChange::Cancelled(FusionPlusCancellation {
+                escrow_address: address(0xdead),
+                chain_id: src_chain,
+                tx_hash: word(102),
+                block_number: 102,
+                block_timestamp: 1_102,
+                log_index: 0,
+            }),
+        ];
+        let applied = db.apply_fusion_plus_changes(&changes).await.unwrap();
+        let leg = |is_src| Some(FusionPlusApplied { order_hash: first.order_hash.clone(), is_src });
+        // Later changes see the swap created earlier in the batch; the duplicate
+        // and the unknown escrow match nothing
+        assert_eq!(applied, [leg(true), None, leg(false), leg(false), leg(true), None]);
+
+        let stored = db.get_fusion_plus_swap(&first.order_hash).await.unwrap().unwrap();
+        assert_eq!(stored.dst_escrow_address.as_deref(), Some(address(0xd5c0).as_str()));
+        assert_eq!(stored.dst_tx_hash.as_deref(), Some(word(201).as_str())); // The withdrawal's
+        assert_eq!((stored.src_status.as_str(), stored.dst_status.as_str()), ("withdrawn", "withdrawn"));
+        assert_eq!(stored.secret.as_deref(), Some(secret.as_str()));
+        assert_eq!(stored.secret_valid, Some(true));
+        assert_eq!(db.get_fusion_plus_timeline(&first.order_hash).await.unwrap().len(), 4);
+
+        // Rolling back the dst withdrawal keeps its escrow creation and the
+        // secret the src withdrawal revealed
+        let stats = db.rollback_to_block(dst_chain, 200).await.unwrap();
+        assert_eq!(stats.fusion_plus_withdrawal_reset, 1);
+        let stored = db.get_fusion_plus_swap(&first.order_hash).await.unwrap().unwrap();
+        assert_eq!((stored.src_status.as_str(), stored.dst_status.as_str()), ("withdrawn", "secret_revealed"));
+        assert_eq!(stored.dst_tx_hash.as_deref(), Some(word(200).as_str()));
+        assert_eq!(stored.dst_block_number, Some(200));
+        assert_eq!(stored.secret.as_deref(), Some(secret.as_str()));
+        assert_eq!(stored.secret_revealed_chain_id, Some(src_chain));
+
+        // Rolling back the src withdrawal too forgets the secret
+        db.rollback_to_block(src_chain, 100).await.unwrap();
+        let stored = db.get_fusion_plus_swap(&first.order_hash).await.unwrap().unwrap();
+        assert_eq!((stored.src_status.as_str(), stored.dst_status.as_str()), ("created", "created"));
+        assert_eq!((stored.secret, stored.secret_revealed_chain_id), (None, None));
+        assert_eq!(db.get_fusion_plus_timeline(&first.order_hash).await.unwrap().len(), 2);
+
+        // A failing change rolls back the whole batch
+        let second = swap(0x4563, 0xe5c1);
+        let mut invalid = swap(0x4564, 0xe5c2);
+        invalid.order_hash = format!("{}00", invalid.order_hash); // Longer than VARCHAR(66)
+        let result = db
+            .apply_fusion_plus_changes(&[
+                FusionPlusChange::Created { swap: second.clone(), merkle_fill: None },
+                FusionPlusChange::Created { swap: invalid, merkle_fill: None },
+            ])
+            .await;
+        assert!(result.is_err());
+        assert!(db.get_fusion_plus_swap(&second.order_hash).await.unwrap().is_none());
+
+        cleanup().await;
     }
 
-    /// Clean up old Crypto2Fiat events based on TTL
-    pub async fn cleanup_old_crypto2fiat(&self, ttl_secs: u64) -> Result<usize, DbError> {
-        let client = self.pool.get().await?;
-        let cutoff = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64
-            - ttl_secs as i64;
+    #[tokio::test]
+    async fn test_native_transfers_outlive_replay() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_014;
+        let cleanup = || async {
+            let client = db.pool.get().await.unwrap();
+            client
+                .batch_execute(&format!(
+                    "DELETE FROM transfers WHERE chain_id = {chain_id};
+                     DELETE FROM checkpoints WHERE chain_id = {chain_id};"
+                ))
+                .await
+                .unwrap();
+        };
+        cleanup().await;
 
-        let deleted = client.execute(
-            "DELETE FROM crypto2fiat_events WHERE created_at < $1",
-            &[&cutoff],
-        ).await?;
+        let transfer = |token: &str, log_index: u32| Transfer {
+            chain_id,
+            tx_hash: format!("0x{:064x}", 1),
+            log_index,
+            token: token.to_string(),
+            from_addr: format!("0x{:040x}", 2),
+            to_addr: format!("0x{:040x}", 3),
+            value: format!("0x{:064x}", 1000),
+            value_decimal: Some("1000".to_string()),
+            block_number: 150,
+            block_timestamp: 1_000,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        };
+        let token = format!("0x{:040x}", 1);
+        db.insert_transfers_batch(chain_id, &[transfer(&token, 0), transfer(NATIVE_TOKEN, crate::types::NATIVE_LOG_INDEX_BASE)])
+            .await
+            .unwrap();
+        let tokens = || async {
+            let client = db.pool.get().await.unwrap();
+            let rows = client
+                .query("SELECT token FROM transfers WHERE chain_id = $1 ORDER BY token", &[&(chain_id as i32)])
+                .await
+                .unwrap();
+            rows.iter().map(|r| r.get::<_, String>(0)).collect::<Vec<_>>()
+        };
+
+        // `verify` only samples transfers it can find a log for
+        let sampled = db.sample_transfers(chain_id, 10).await.unwrap();
+        assert_eq!(sampled.iter().map(|t| t.token.as_str()).collect::<Vec<_>>(), [token.as_str()]);
 
-        Ok(deleted as usize)
+        // A replay re-decodes logs, so it leaves native transfers alone
+        assert_eq!(db.clear_decoded_range(chain_id, 100, 200).await.unwrap().transfers_deleted, 1);
+        assert_eq!(tokens().await, [NATIVE_TOKEN]);
+
+        // A reorg removes them with everything else
+        db.insert_transfers_batch(chain_id, &[transfer(&token, 0)]).await.unwrap();
+        db.set_checkpoint(chain_id, 200).await.unwrap();
+        assert_eq!(db.rollback_to_block(chain_id, 100).await.unwrap().transfers_deleted, 2);
+        assert!(tokens().await.is_empty());
+
+        cleanup().await;
     }
 
-    // =========================================================================
-    // Cleanup Methods
-    // =========================================================================
+    #[tokio::test]
+    async fn test_token_stats_follow_deleted_transfers() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_016;
+        let cleanup = || async {
+            let client = db.pool.get().await.unwrap();
+            client
+                .batch_execute(&format!(
+                    "DELETE FROM transfers WHERE chain_id = {chain_id};
+                     DELETE FROM token_stats WHERE chain_id = {chain_id};
+                     DELETE FROM checkpoints WHERE chain_id = {chain_id};"
+                ))
+                .await
+                .unwrap();
+        };
+        cleanup().await;
+
+        let token = format!("0x{:040x}", 1);
+        let transfer = |block_number: u64, block_timestamp: u64| Transfer {
+            chain_id,
+            tx_hash: format!("0x{:064x}", block_number),
+            log_index: 0,
+            token: token.clone(),
+            from_addr: format!("0x{:040x}", 2),
+            to_addr: format!("0x{:040x}", 3),
+            value: format!("0x{:064x}", 10),
+            value_decimal: Some("10".to_string()),
+            block_number,
+            block_timestamp,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        };
+        // Blocks 100 and 101 share the first hour; 102 is alone in the second
+        let since = unix_now() - 1;
+        db.insert_transfers_batch(chain_id, &[transfer(100, 3_600), transfer(101, 3_700), transfer(102, 7_200)])
+            .await
+            .unwrap();
+        db.rollup_token_stats(since).await.unwrap();
+        let hours = || async {
+            db.get_token_hourly_stats(chain_id, &token, 0, 10_000)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|s| (s.hour.unwrap(), s.transfer_count, s.volume))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hours().await, [(3_600, 2, "20".to_string()), (7_200, 1, "10".to_string())]);
 
-    /// Clean up all old data based on TTL
-    pub async fn cleanup_all(&self, ttl_secs: u64) -> Result<CleanupStats, DbError> {
-        let transfers = self.cleanup_old_transfers(ttl_secs).await?;
-        let fusion_plus = self.cleanup_old_fusion_plus(ttl_secs).await?;
-        let fusion = self.cleanup_old_fusion_swaps(ttl_secs).await?;
-        let crypto2fiat = self.cleanup_old_crypto2fiat(ttl_secs).await?;
+        // A replay recounts what the range leaves of each hour
+        db.clear_decoded_range(chain_id, 101, 101).await.unwrap();
+        assert_eq!(hours().await, [(3_600, 1, "10".to_string()), (7_200, 1, "10".to_string())]);
 
-        Ok(CleanupStats {
-            transfers_deleted: transfers,
-            fusion_plus_deleted: fusion_plus,
-            fusion_deleted: fusion,
-            crypto2fiat_deleted: crypto2fiat,
-        })
+        // A reorg drops the hours it empties
+        db.set_checkpoint(chain_id, 102).await.unwrap();
+        db.rollback_to_block(chain_id, 100).await.unwrap();
+        assert_eq!(hours().await, [(3_600, 1, "10".to_string())]);
+
+        cleanup().await;
     }
-}
 
-#[derive(Default, Debug)]
-pub struct CleanupStats {
-    pub transfers_deleted: usize,
-    pub fusion_plus_deleted: usize,
-    pub fusion_deleted: usize,
-    pub crypto2fiat_deleted: usize,
+    #[tokio::test]
+    async fn test_backfilled_days_get_partitions() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        // A partitioned transfers table needs a database of its own
+        let url = std::env::var("TEST_DATABASE_URL").unwrap();
+        let Some((base, dbname)) = url.rsplit_once('/').filter(|(_, name)| !name.contains('?')) else {
+            return;
+        };
+        let dbname = format!("{}_partitioned", dbname);
+        let admin = db.pool.get().await.unwrap();
+        admin.batch_execute(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", dbname)).await.unwrap();
+        admin.batch_execute(&format!("CREATE DATABASE {}", dbname)).await.unwrap();
+        let config = DatabaseConfig { daily_rotation: true, ..Default::default() };
+        let partitioned = Database::with_config(&format!("{}/{}", base, dbname), config).await.unwrap();
+
+        let day = 19_782; // 2024-02-29
+        let transfer = |log_index: u32| Transfer {
+            chain_id: 1,
+            tx_hash: format!("0x{:064x}", 1),
+            log_index,
+            token: format!("0x{:040x}", 1),
+            from_addr: format!("0x{:040x}", 2),
+            to_addr: format!("0x{:040x}", 3),
+            value: format!("0x{:064x}", 10),
+            value_decimal: Some("10".to_string()),
+            block_number: 100,
+            block_timestamp: day * 86_400 + 60,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        };
+        let client = partitioned.pool.get().await.unwrap();
+        let tables = || async {
+            client
+                .query("SELECT tableoid::regclass::TEXT, COUNT(*) FROM transfers GROUP BY 1 ORDER BY 1", &[])
+                .await
+                .unwrap()
+                .iter()
+                .map(|r| (r.get::<_, String>(0), r.get::<_, i64>(1)))
+                .collect::<Vec<_>>()
+        };
+
+        // A row written before its day had a partition
+        client
+            .execute(
+                "INSERT INTO transfers (chain_id, tx_hash, log_index, token, from_addr, to_addr, value, block_number, block_timestamp)
+                 VALUES (1, $1, 0, $2, $2, $2, '0x', 100, $3)",
+                &[&format!("0x{:064x}", 1), &format!("0x{:040x}", 1), &((day * 86_400) as i64)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(tables().await, [("transfers_default".to_string(), 1)]);
+
+        // Writing to the day creates its partition and moves that row into it
+        assert_eq!(partitioned.insert_transfers_batch(1, &[transfer(1), transfer(1)]).await.unwrap(), 1);
+        assert_eq!(tables().await, [("transfers_20240229".to_string(), 2)]);
+        assert!(!partitioned.insert_transfer(1, &transfer(1)).await.unwrap());
+
+        drop(client);
+        drop(partitioned);
+        admin.batch_execute(&format!("DROP DATABASE {} WITH (FORCE)", dbname)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commit_writes_is_atomic() {
+        let Some((_guard, db)) = test_database().await else {
+            return;
+        };
+        let chain_id = 990_010;
+        let cleanup = || async {
+            let client = db.pool.get().await.unwrap();
+            client
+                .batch_execute(&format!(
+                    "DELETE FROM transfers WHERE chain_id = {chain_id};
+                     DELETE FROM checkpoints WHERE chain_id = {chain_id};
+                     DELETE FROM processed_ranges WHERE chain_id = {chain_id};"
+                ))
+                .await
+                .unwrap();
+        };
+        cleanup().await;
+        db.set_checkpoint(chain_id, 100).await.unwrap();
+
+        let transfer = |log_index: u32, tx_hash: String| Transfer {
+            chain_id,
+            tx_hash,
+            log_index,
+            token: format!("0x{:040x}", 1),
+            from_addr: format!("0x{:040x}", 2),
+            to_addr: format!("0x{:040x}", 3),
+            value: format!("0x{:064x}", 1000),
+            value_decimal: Some("1000".to_string()),
+            block_number: 150,
+            block_timestamp: 1_000,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        };
+        let writes = |transfers| ChainWrites {
+            raw_logs: &[],
+            transfers,
+            approvals: &[],
+            raw_events: &[],
+            delegations: &[],
+            transactions: &[],
+            internal_transfers: &[],
+            processed_ranges: &[(101, 200)],
+            checkpoint: Some(200),
+            backfill_progress: None,
+        };
+        let count = || async {
+            let client = db.pool.get().await.unwrap();
+            let transfers: i64 = client
+                .query_one("SELECT COUNT(*) FROM transfers WHERE chain_id = $1", &[&(chain_id as i32)])
+                .await
+                .unwrap()
+                .get(0);
+            let ranges: i64 = client
+                .query_one("SELECT COUNT(*) FROM processed_ranges WHERE chain_id = $1", &[&(chain_id as i32)])
+                .await
+                .unwrap()
+                .get(0);
+            (transfers, ranges)
+        };
+
+        // The second row is longer than VARCHAR(66): nothing is stored and the checkpoint stays
+        let failing = [transfer(0, format!("0x{:064x}", 1)), transfer(1, format!("0x{:066x}", 2))];
+        assert!(db.commit_writes(chain_id, &writes(&failing)).await.is_err());
+        assert_eq!(db.get_checkpoint(chain_id).await.unwrap(), Some(100));
+        assert_eq!(count().await, (0, 0));
+
+        let valid = [transfer(0, format!("0x{:064x}", 1)), transfer(1, format!("0x{:064x}", 2))];
+        db.commit_writes(chain_id, &writes(&valid)).await.unwrap();
+        assert_eq!(db.get_checkpoint(chain_id).await.unwrap(), Some(200));
+        assert_eq!(count().await, (2, 1));
+
+        cleanup().await;
+    }
 }
@@ -0,0 +1,101 @@
+use crate::types::Log;
+use std::collections::{HashSet, VecDeque};
+
+/// Identity of a log across ingestion streams: (tx_hash, log_index, topic0)
+type LogKey = (String, u32, String);
+
+/// Bounded set of recently ingested logs
+///
+/// Used to reconcile the live subscription stream with the audit polling
+/// pass: whichever stream sees a log first processes it, the other skips it,
+/// and to skip swap logs already handled when polling re-scans the reorg
+/// safety window. The oldest keys are evicted once `capacity` is reached.
+pub struct LogDeduplicator {
+    seen: HashSet<LogKey>,
+    order: VecDeque<LogKey>,
+    capacity: usize,
+}
+
+impl LogDeduplicator {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn key(log: &Log) -> LogKey {
+        (
+            log.transaction_hash.to_lowercase(),
+            log.log_index_u32(),
+            log.topics.first().map(|t| t.to_lowercase()).unwrap_or_default(),
+        )
+    }
+
+    /// Whether a log has been recorded and not yet evicted
+    pub fn contains(&self, log: &Log) -> bool {
+        self.seen.contains(&Self::key(log))
+    }
+
+    /// Record a log, returning true if it has not been seen before
+    pub fn insert(&mut self, log: &Log) -> bool {
+        let key = Self::key(log);
+        if self.seen.contains(&key) {
+            return false;
+        }
+
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        self.seen.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(tx_hash: &str, log_index: u32, topic0: &str) -> Log {
+        Log {
+            address: "0x0000000000000000000000000000000000000001".to_string(),
+            topics: vec![topic0.to_string()],
+            data: "0x".to_string(),
+            block_number: "0x1".to_string(),
+            transaction_hash: tx_hash.to_string(),
+            log_index: format!("0x{:x}", log_index),
+        }
+    }
+
+    #[test]
+    fn test_dedup_by_tx_log_index_and_topic() {
+        let mut dedup = LogDeduplicator::new(10);
+
+        assert!(dedup.insert(&log("0xAB", 1, "0x01")));
+        assert!(!dedup.insert(&log("0xab", 1, "0x01"))); // Case-insensitive hash
+        assert!(dedup.insert(&log("0xab", 2, "0x01")));
+        assert!(dedup.insert(&log("0xab", 1, "0x02")));
+        assert_eq!(dedup.order.len(), 3);
+        assert!(dedup.contains(&log("0xAB", 2, "0x01")));
+        assert!(!dedup.contains(&log("0xab", 3, "0x01")));
+    }
+
+    #[test]
+    fn test_dedup_evicts_oldest() {
+        let mut dedup = LogDeduplicator::new(2);
+
+        assert!(dedup.insert(&log("0x01", 0, "0x01")));
+        assert!(dedup.insert(&log("0x02", 0, "0x01")));
+        assert!(dedup.insert(&log("0x03", 0, "0x01")));
+        assert_eq!(dedup.order.len(), 2);
+
+        // 0x01 was evicted, so it is treated as new again
+        assert!(dedup.insert(&log("0x01", 0, "0x01")));
+        assert!(!dedup.insert(&log("0x03", 0, "0x01")));
+    }
+}
@@ -0,0 +1,100 @@
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Sink for the rows a dry run would have written
+///
+/// Each row is written as one JSON line, `{"table": ..., "row": ...}`, so
+/// the output can be diffed or filtered with jq.
+pub struct DryRun {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    out: Box<dyn Write + Send>,
+    counts: BTreeMap<&'static str, u64>,
+}
+
+impl DryRun {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                out,
+                counts: BTreeMap::new(),
+            }),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(Box::new(io::stdout()))
+    }
+
+    /// Write to a new file at `path`, replacing an existing one
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(Box::new(BufWriter::new(File::create(path)?))))
+    }
+
+    /// Record one row that would have been written to `table`
+    pub fn record<T: Serialize>(&self, table: &'static str, row: &T) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.counts.entry(table).or_default() += 1;
+
+        let line = json!({ "table": table, "row": row });
+        if let Err(e) = writeln!(inner.out, "{}", line) {
+            warn!("Failed to write dry run row for {}: {}", table, e);
+        }
+    }
+
+    /// Flush the output and return the number of rows recorded per table
+    pub fn finish(&self) -> BTreeMap<&'static str, u64> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Err(e) = inner.out.flush() {
+            warn!("Failed to flush dry run output: {}", e);
+        }
+        inner.counts.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let buffer = Buffer::default();
+        let dry_run = DryRun::new(Box::new(buffer.clone()));
+
+        dry_run.record("transfers", &json!({ "tx_hash": "0xabc" }));
+        dry_run.record("transfers", &json!({ "tx_hash": "0xdef" }));
+        dry_run.record("tokens", &json!({ "token": "0x123" }));
+
+        let counts = dry_run.finish();
+        assert_eq!(counts.get("transfers"), Some(&2));
+        assert_eq!(counts.get("tokens"), Some(&1));
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["table"], "transfers");
+        assert_eq!(lines[1]["row"]["tx_hash"], "0xdef");
+    }
+}
@@ -0,0 +1,296 @@
+use crate::types::{FusionPlusSwap, FusionSwap, Log, Transfer};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Events buffered per subscriber before slow consumers start missing events
+const EVENT_BUS_CAPACITY: usize = 4096;
+
+/// An event published by the pollers after it has been stored
+///
+/// Serializes as `{"type": "transfer" | "fusion_plus" | "fusion", "data": {...}}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum IndexedEvent {
+    Transfer(Box<Transfer>),
+    FusionPlus(Box<FusionPlusUpdate>),
+    Fusion(Box<FusionSwap>),
+}
+
+impl IndexedEvent {
+    /// Name used in `EventFilter::event_types`
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            IndexedEvent::Transfer(_) => "transfer",
+            IndexedEvent::FusionPlus(_) => "fusion_plus",
+            IndexedEvent::Fusion(_) => "fusion",
+        }
+    }
+
+    /// Chain the event was indexed on
+    pub fn chain_id(&self) -> u32 {
+        match self {
+            IndexedEvent::Transfer(t) => t.chain_id,
+            IndexedEvent::FusionPlus(u) => u.chain_id,
+            IndexedEvent::Fusion(s) => s.chain_id,
+        }
+    }
+}
+
+/// Fusion+ swap state after an escrow event on `chain_id`
+#[derive(Debug, Clone, Serialize)]
+pub struct FusionPlusUpdate {
+    /// `src_escrow_created`, `dst_escrow_created`, `withdrawn` or `cancelled`;
+    /// `expiring` when a leg nears cancellation (see `expiry::run`)
+    pub stage: &'static str,
+    pub chain_id: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub swap: FusionPlusSwap,
+}
+
+/// Fan-out of indexed events to live subscribers (gRPC, WebSocket, Redis)
+///
+/// Publishing never blocks; with no subscribers events are dropped.
+pub struct EventBus {
+    tx: broadcast::Sender<IndexedEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexedEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Whether anyone is listening; lets publishers skip extra lookups
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    pub fn publish(&self, event: IndexedEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Call `handler` with every event published from now on, in a background task
+    ///
+    /// For services embedding the listener. A handler that falls more than
+    /// the bus capacity behind skips the missed events with a warning.
+    pub fn spawn_handler<F>(&self, mut handler: F) -> JoinHandle<()>
+    where
+        F: FnMut(IndexedEvent) + Send + 'static,
+    {
+        let mut rx = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => handler(event),
+                    Err(RecvError::Lagged(missed)) => warn!("Event handler lagged, {} events dropped", missed),
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Event Handlers
+// =============================================================================
+
+/// Sink for indexed events, registered with `ChainPoller::with_handler`
+///
+/// Transfers and swap updates arrive once stored; raw logs arrive as fetched,
+/// before decoding. Calls are made on the chain's poller or writer task, so a
+/// handler must not block: hand slow work (HTTP alerts, queues) to a task.
+/// Every method does nothing by default.
+pub trait EventHandler: Send + Sync {
+    fn on_transfer(&self, _transfer: &Transfer) {}
+
+    fn on_fusion_swap(&self, _swap: &FusionSwap) {}
+
+    fn on_fusion_plus_update(&self, _update: &FusionPlusUpdate) {}
+
+    fn on_raw_log(&self, _chain_id: u32, _log: &Log) {}
+
+    /// Whether the handler wants swap updates right now; when no handler
+    /// does, the poller skips the lookups made only to build them
+    fn is_active(&self) -> bool {
+        true
+    }
+}
+
+/// The bus is the handler behind `ChainPoller::with_events`
+impl EventHandler for EventBus {
+    fn on_transfer(&self, transfer: &Transfer) {
+        self.publish(IndexedEvent::Transfer(Box::new(transfer.clone())));
+    }
+
+    fn on_fusion_swap(&self, swap: &FusionSwap) {
+        self.publish(IndexedEvent::Fusion(Box::new(swap.clone())));
+    }
+
+    fn on_fusion_plus_update(&self, update: &FusionPlusUpdate) {
+        self.publish(IndexedEvent::FusionPlus(Box::new(update.clone())));
+    }
+
+    fn is_active(&self) -> bool {
+        self.has_subscribers()
+    }
+}
+
+// =============================================================================
+// Subscriber Filters
+// =============================================================================
+
+/// Subscriber filter; empty lists match everything, addresses ignore case
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EventFilter {
+    pub chain_ids: Vec<u32>,
+    /// Transfers: from or to. Swaps: maker or taker on either leg.
+    pub addresses: Vec<String>,
+    /// Transfers: token contract. Swaps: either side's token.
+    pub tokens: Vec<String>,
+    /// `transfer`, `fusion_plus` and/or `fusion`
+    pub event_types: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &IndexedEvent) -> bool {
+        if !self.event_types.is_empty() && !self.event_types.iter().any(|t| t == event.event_type()) {
+            return false;
+        }
+
+        match event {
+            IndexedEvent::Transfer(t) => {
+                self.chain(t.chain_id)
+                    && self.any_address(&[Some(&t.from_addr), Some(&t.to_addr)])
+                    && self.any_token(&[Some(&t.token)])
+            }
+            IndexedEvent::FusionPlus(u) => {
+                let s = &u.swap;
+                (self.chain(s.src_chain_id) || self.chain(s.dst_chain_id))
+                    && self.any_address(&[Some(&s.src_maker), Some(&s.src_taker), Some(&s.dst_maker), s.dst_taker.as_ref()])
+                    && self.any_token(&[Some(&s.src_token), Some(&s.dst_token)])
+            }
+            IndexedEvent::Fusion(s) => {
+                self.chain(s.chain_id)
+                    && self.any_address(&[Some(&s.maker), s.taker.as_ref()])
+                    && self.any_token(&[s.maker_token.as_ref(), s.taker_token.as_ref()])
+            }
+        }
+    }
+
+    fn chain(&self, chain_id: u32) -> bool {
+        self.chain_ids.is_empty() || self.chain_ids.contains(&chain_id)
+    }
+
+    fn any_address(&self, candidates: &[Option<&String>]) -> bool {
+        self.addresses.is_empty() || any_match(&self.addresses, candidates)
+    }
+
+    fn any_token(&self, candidates: &[Option<&String>]) -> bool {
+        self.tokens.is_empty() || any_match(&self.tokens, candidates)
+    }
+}
+
+fn any_match(wanted: &[String], candidates: &[Option<&String>]) -> bool {
+    candidates
+        .iter()
+        .flatten()
+        .any(|c| wanted.iter().any(|w| w.eq_ignore_ascii_case(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(chain_id: u32, from: &str, to: &str, token: &str) -> IndexedEvent {
+        IndexedEvent::Transfer(Box::new(Transfer {
+            chain_id,
+            tx_hash: "0xabc".to_string(),
+            log_index: 0,
+            token: token.to_string(),
+            from_addr: from.to_string(),
+            to_addr: to.to_string(),
+            value: String::new(),
+            value_decimal: None,
+            block_number: 1,
+            block_timestamp: 1,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler() {
+        let bus = EventBus::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        bus.spawn_handler(move |event| {
+            let _ = tx.send(event.chain_id());
+        });
+
+        bus.publish(transfer(8453, "0xaa", "0xbb", "0xcc"));
+        bus.publish(transfer(1, "0xaa", "0xbb", "0xcc"));
+        assert_eq!(rx.recv().await, Some(8453));
+        assert_eq!(rx.recv().await, Some(1));
+    }
+
+    #[test]
+    fn test_event_bus_handler() {
+        let bus = EventBus::new();
+        assert!(!bus.is_active());
+        let mut rx = bus.subscribe();
+        assert!(bus.is_active());
+
+        let IndexedEvent::Transfer(t) = transfer(8453, "0xaa", "0xbb", "0xcc") else { unreachable!() };
+        let handler: &dyn EventHandler = &bus;
+        handler.on_transfer(&t);
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.event_type(), "transfer");
+        assert_eq!(event.chain_id(), 8453);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_filter_matches_transfers() {
+        let t = transfer(8453, "0xaa", "0xbb", "0xcc");
+
+        assert!(EventFilter::default().matches(&t));
+
+        let filter: EventFilter = serde_json::from_str(
+            r#"{"chain_ids":[8453],"addresses":["0xBB"],"tokens":["0xCC"],"event_types":["transfer"]}"#,
+        )
+        .unwrap();
+        assert!(filter.matches(&t));
+        assert!(!filter.matches(&transfer(1, "0xaa", "0xbb", "0xcc")));
+        assert!(!filter.matches(&transfer(8453, "0xaa", "0xdd", "0xcc")));
+        assert!(!filter.matches(&transfer(8453, "0xaa", "0xbb", "0xdd")));
+
+        let swaps_only = EventFilter {
+            event_types: vec!["fusion".to_string()],
+            ..Default::default()
+        };
+        assert!(!swaps_only.matches(&t));
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let json = serde_json::to_value(transfer(1, "0xaa", "0xbb", "0xcc")).unwrap();
+        assert_eq!(json["type"], "transfer");
+        assert_eq!(json["data"]["from_addr"], "0xaa");
+    }
+}
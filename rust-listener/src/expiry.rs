@@ -0,0 +1,189 @@
+use crate::config::ExpiryAlertConfig;
+use crate::db::Database;
+use crate::events::{EventBus, FusionPlusUpdate, IndexedEvent};
+use crate::fusion::{timelock_stage_start, TimelockStage};
+use crate::types::FusionPlusSwap;
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+/// Open swaps checked per pass
+const MAX_OPEN_SWAPS: u32 = 10_000;
+
+/// A swap leg that is still locked and near (or past) its cancellation time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExpiryAlert {
+    pub order_hash: String,
+    /// `src` or `dst`
+    pub leg: &'static str,
+    pub chain_id: u32,
+    pub escrow_address: Option<String>,
+    pub status: String,
+    /// Unix time the leg's private cancellation period starts
+    pub cancellation_at: u64,
+    /// Negative once the cancellation period has started
+    pub seconds_left: i64,
+}
+
+/// Legs of `swap` not yet withdrawn or cancelled whose cancellation period
+/// starts within `lead_secs` of `now`
+pub fn expiring_legs(swap: &FusionPlusSwap, now: u64, lead_secs: u64) -> Vec<ExpiryAlert> {
+    let legs = [
+        (
+            "src",
+            swap.src_chain_id,
+            &swap.src_escrow_address,
+            &swap.src_status,
+            Some(swap.src_timelocks.as_str()),
+            TimelockStage::SrcCancellation,
+        ),
+        (
+            "dst",
+            swap.dst_chain_id,
+            &swap.dst_escrow_address,
+            &swap.dst_status,
+            swap.dst_timelocks.as_deref(),
+            TimelockStage::DstCancellation,
+        ),
+    ];
+
+    legs.into_iter()
+        .filter(|(_, _, _, status, _, _)| matches!(status.as_str(), "created" | "secret_revealed"))
+        .filter_map(|(leg, chain_id, escrow_address, status, timelocks, stage)| {
+            let cancellation_at = timelock_stage_start(timelocks?, stage)?;
+            (cancellation_at <= now + lead_secs).then(|| ExpiryAlert {
+                order_hash: swap.order_hash.clone(),
+                leg,
+                chain_id,
+                escrow_address: escrow_address.clone(),
+                status: status.clone(),
+                cancellation_at,
+                seconds_left: cancellation_at as i64 - now as i64,
+            })
+        })
+        .collect()
+}
+
+/// Check open Fusion+ swaps every `interval_secs` and alert once per leg that
+/// nears its cancellation time without a withdrawal
+///
+/// Alerts are logged, published on the event bus as a `fusion_plus` event
+/// with stage `expiring`, and POSTed as JSON to the webhook if configured.
+/// Legs already alerted are remembered until they leave the open set, so a
+/// restart alerts again for legs still pending.
+pub async fn run(db: Arc<Database>, events: Arc<EventBus>, config: ExpiryAlertConfig) {
+    let http = Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default();
+    let mut alerted: HashSet<(String, &'static str)> = HashSet::new();
+    info!(
+        "Alerting on Fusion+ legs {}s before cancellation, checking every {}s",
+        config.lead_secs, config.interval_secs
+    );
+
+    loop {
+        match db.get_open_fusion_plus_swaps(MAX_OPEN_SWAPS).await {
+            Ok(swaps) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                let mut open = HashSet::new();
+                for swap in &swaps {
+                    for alert in expiring_legs(swap, now, config.lead_secs) {
+                        let key = (alert.order_hash.clone(), alert.leg);
+                        open.insert(key.clone());
+                        if alerted.insert(key) {
+                            emit(&alert, swap, &events, &http, config.webhook_url.as_deref());
+                        }
+                    }
+                }
+                alerted.retain(|key| open.contains(key));
+                debug!("Fusion+ expiry check: {} open swaps, {} legs expiring", swaps.len(), alerted.len());
+            }
+            Err(e) => warn!("Fusion+ expiry check error: {}", e),
+        }
+        sleep(Duration::from_secs(config.interval_secs)).await;
+    }
+}
+
+fn emit(alert: &ExpiryAlert, swap: &FusionPlusSwap, events: &EventBus, http: &Client, webhook_url: Option<&str>) {
+    if alert.seconds_left > 0 {
+        warn!(
+            "Fusion+ {} leg of {} on chain {} is still {} and can be cancelled in {}s",
+            alert.leg, alert.order_hash, alert.chain_id, alert.status, alert.seconds_left
+        );
+    } else {
+        warn!(
+            "Fusion+ {} leg of {} on chain {} is still {} and cancellable since {}s",
+            alert.leg, alert.order_hash, alert.chain_id, alert.status, -alert.seconds_left
+        );
+    }
+
+    let (tx_hash, block_number) = if alert.leg == "src" {
+        (swap.src_tx_hash.clone(), swap.src_block_number)
+    } else {
+        (swap.dst_tx_hash.clone().unwrap_or_default(), swap.dst_block_number.unwrap_or(0))
+    };
+    events.publish(IndexedEvent::FusionPlus(Box::new(FusionPlusUpdate {
+        stage: "expiring",
+        chain_id: alert.chain_id,
+        tx_hash,
+        block_number,
+        swap: swap.clone(),
+    })));
+
+    if let Some(url) = webhook_url {
+        let request = http.post(url).json(alert);
+        let order_hash = alert.order_hash.clone();
+        tokio::spawn(async move {
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => debug!("Sent expiry alert for {}", order_hash),
+                Err(e) => warn!("Failed to send expiry alert for {}: {}", order_hash, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SrcEscrowCreatedData;
+
+    // deployedAt 1_700_000_000; SrcCancellation at +1200, DstCancellation at +600
+    const TIMELOCKS: &str = "0x6553f100000002580000012c0000003c00000708000004b0000001680000001e";
+
+    fn swap() -> FusionPlusSwap {
+        let data = SrcEscrowCreatedData {
+            order_hash: "0xorder".to_string(),
+            src_timelocks: TIMELOCKS.to_string(),
+            dst_chain_id: 8453,
+            ..Default::default()
+        };
+        let mut swap = FusionPlusSwap::from_src_created(&data, 1, "0xtx", 100, 1_700_000_000, 0);
+        swap.dst_timelocks = Some(TIMELOCKS.to_string());
+        swap.dst_status = "created".to_string();
+        swap
+    }
+
+    #[test]
+    fn test_expiring_legs() {
+        let swap = swap();
+        assert!(expiring_legs(&swap, 1_700_000_000, 300).is_empty());
+
+        let alerts = expiring_legs(&swap, 1_700_000_400, 300);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].leg, "dst");
+        assert_eq!(alerts[0].chain_id, 8453);
+        assert_eq!(alerts[0].seconds_left, 200);
+
+        let alerts = expiring_legs(&swap, 1_700_001_000, 300);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[1].seconds_left, -400);
+
+        // Withdrawn legs are not at risk
+        let mut withdrawn = swap.clone();
+        withdrawn.src_status = "withdrawn".to_string();
+        withdrawn.dst_status = "withdrawn".to_string();
+        assert!(expiring_legs(&withdrawn, 1_700_001_000, 300).is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+use crate::db::{Database, DbError};
+use arrow_array::{ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_postgres::Row;
+
+/// Column type in an export file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Int,
+    Text,
+    Bool,
+}
+
+/// A table that can be exported, with the columns written (in order)
+#[derive(Debug)]
+pub struct ExportTable {
+    /// Name accepted by `--table`
+    pub name: &'static str,
+    pub sql_table: &'static str,
+    pub chain_column: &'static str,
+    pub timestamp_column: &'static str,
+    pub columns: &'static [(&'static str, ColumnKind)],
+}
+
+use ColumnKind::{Bool, Int, Text};
+
+pub const EXPORT_TABLES: &[ExportTable] = &[
+    ExportTable {
+        name: "transfers",
+        sql_table: "transfers",
+        chain_column: "chain_id",
+        timestamp_column: "block_timestamp",
+        columns: &[
+            ("id", Int), ("chain_id", Int), ("tx_hash", Text), ("log_index", Int),
+            ("token", Text), ("from_addr", Text), ("to_addr", Text), ("value", Text),
+            ("value_decimal", Text), ("block_number", Int), ("block_timestamp", Int),
+            ("swap_type", Text), ("tx_status", Int),
+        ],
+    },
+    ExportTable {
+        name: "fusion",
+        sql_table: "fusion_swaps",
+        chain_column: "chain_id",
+        timestamp_column: "block_timestamp",
+        columns: &[
+            ("id", Int), ("order_hash", Text), ("chain_id", Int), ("tx_hash", Text),
+            ("block_number", Int), ("block_timestamp", Int), ("log_index", Int),
+            ("maker", Text), ("taker", Text), ("maker_token", Text), ("taker_token", Text),
+            ("maker_amount", Text), ("taker_amount", Text), ("remaining", Text),
+            ("is_partial_fill", Bool), ("status", Text),
+        ],
+    },
+    ExportTable {
+        name: "fusion_plus",
+        sql_table: "fusion_plus_swaps",
+        chain_column: "src_chain_id",
+        timestamp_column: "src_block_timestamp",
+        columns: &[
+            ("id", Int), ("order_hash", Text), ("hashlock", Text), ("secret", Text),
+            ("src_chain_id", Int), ("src_tx_hash", Text), ("src_block_number", Int),
+            ("src_block_timestamp", Int), ("src_log_index", Int), ("src_escrow_address", Text),
+            ("src_maker", Text), ("src_taker", Text), ("src_token", Text), ("src_amount", Text),
+            ("src_safety_deposit", Text), ("src_timelocks", Text), ("src_status", Text),
+            ("dst_chain_id", Int), ("dst_tx_hash", Text), ("dst_block_number", Int),
+            ("dst_block_timestamp", Int), ("dst_log_index", Int), ("dst_escrow_address", Text),
+            ("dst_maker", Text), ("dst_taker", Text), ("dst_token", Text), ("dst_amount", Text),
+            ("dst_safety_deposit", Text), ("dst_timelocks", Text), ("dst_status", Text),
+            ("secret_revealed_chain_id", Int), ("secret_revealed_at", Int),
+            ("src_withdrawer", Text), ("src_withdrawal_phase", Text),
+            ("dst_withdrawer", Text), ("dst_withdrawal_phase", Text),
+            ("src_cancelled_tx_hash", Text), ("src_cancelled_at", Int),
+            ("dst_cancelled_tx_hash", Text), ("dst_cancelled_at", Int),
+            ("secret_valid", Bool),
+            ("created_at", Int), ("updated_at", Int),
+        ],
+    },
+];
+
+/// Look up an exportable table by its `--table` name
+pub fn export_table(name: &str) -> Option<&'static ExportTable> {
+    EXPORT_TABLES.iter().find(|t| t.name == name)
+}
+
+impl ExportTable {
+    /// SELECT list reading every column as i64, String or bool
+    pub fn select_list(&self) -> String {
+        self.columns
+            .iter()
+            .map(|(name, kind)| match kind {
+                Int => format!("{}::BIGINT", name),
+                Text | Bool => name.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+
+    /// Format implied by the output file's extension (CSV unless `.parquet`)
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("parquet") => Self::Parquet,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Rows written per Parquet record batch
+const PARQUET_BATCH_ROWS: usize = 8_192;
+
+/// Write the rows of `table` for `chain_id` with a block timestamp in
+/// `from_ts..=to_ts` to `path`, in id order
+///
+/// Rows are read through a cursor and written as they arrive, so memory use
+/// does not grow with the size of the range.
+pub async fn run(
+    db: &Database,
+    table: &ExportTable,
+    chain_id: u32,
+    (from_ts, to_ts): (u64, u64),
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize, DbError> {
+    let file = File::create(path)?;
+    let mut writer = match format {
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(file);
+            csv.write_record(table.columns.iter().map(|(name, _)| *name)).map_err(io::Error::from)?;
+            ExportWriter::Csv(csv)
+        }
+        ExportFormat::Parquet => {
+            let schema = parquet_schema(table);
+            let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+            let parquet = ArrowWriter::try_new(file, Arc::clone(&schema), Some(props)).map_err(io::Error::other)?;
+            ExportWriter::Parquet(parquet, schema)
+        }
+    };
+
+    let rows = db
+        .export_rows(table, chain_id, (from_ts, to_ts), PARQUET_BATCH_ROWS, |rows| writer.write(table, rows))
+        .await?;
+    writer.finish()?;
+    Ok(rows)
+}
+
+enum ExportWriter {
+    Csv(csv::Writer<File>),
+    Parquet(ArrowWriter<File>, SchemaRef),
+}
+
+impl ExportWriter {
+    fn write(&mut self, table: &ExportTable, rows: &[Row]) -> io::Result<()> {
+        match self {
+            Self::Csv(csv) => {
+                for row in rows {
+                    let record = table.columns.iter().enumerate().map(|(i, (_, kind))| match kind {
+                        Int => row.get::<_, Option<i64>>(i).map(|v| v.to_string()).unwrap_or_default(),
+                        Text => row.get::<_, Option<String>>(i).unwrap_or_default(),
+                        Bool => row.get::<_, Option<bool>>(i).map(|v| v.to_string()).unwrap_or_default(),
+                    });
+                    csv.write_record(record)?;
+                }
+                Ok(())
+            }
+            Self::Parquet(parquet, schema) => {
+                let columns: Vec<ArrayRef> = table
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, kind))| -> ArrayRef {
+                        match kind {
+                            Int => Arc::new(rows.iter().map(|r| r.get::<_, Option<i64>>(i)).collect::<Int64Array>()),
+                            Text => Arc::new(rows.iter().map(|r| r.get::<_, Option<String>>(i)).collect::<StringArray>()),
+                            Bool => Arc::new(rows.iter().map(|r| r.get::<_, Option<bool>>(i)).collect::<BooleanArray>()),
+                        }
+                    })
+                    .collect();
+                let batch = RecordBatch::try_new(Arc::clone(schema), columns).map_err(io::Error::other)?;
+                parquet.write(&batch).map_err(io::Error::other)
+            }
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Csv(mut csv) => csv.flush(),
+            Self::Parquet(parquet, _) => parquet.close().map(|_| ()).map_err(io::Error::other),
+        }
+    }
+}
+
+fn parquet_schema(table: &ExportTable) -> SchemaRef {
+    let fields: Vec<Field> = table
+        .columns
+        .iter()
+        .map(|(name, kind)| {
+            let data_type = match kind {
+                Int => DataType::Int64,
+                Text => DataType::Utf8,
+                Bool => DataType::Boolean,
+            };
+            Field::new(*name, data_type, true)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!(ExportFormat::for_path(Path::new("out/transfers.parquet")), ExportFormat::Parquet);
+        assert_eq!(ExportFormat::for_path(Path::new("out/transfers.PARQUET")), ExportFormat::Parquet);
+        assert_eq!(ExportFormat::for_path(Path::new("out/transfers.csv")), ExportFormat::Csv);
+        assert_eq!(ExportFormat::for_path(Path::new("transfers")), ExportFormat::Csv);
+        assert_eq!(ExportFormat::parse("Parquet"), Some(ExportFormat::Parquet));
+        assert_eq!(ExportFormat::parse("json"), None);
+    }
+
+    #[test]
+    fn test_select_list() {
+        let fusion = export_table("fusion").unwrap();
+        assert_eq!(fusion.sql_table, "fusion_swaps");
+        let select = fusion.select_list();
+        assert!(select.starts_with("id::BIGINT, order_hash, chain_id::BIGINT"));
+        assert!(select.ends_with("is_partial_fill, status"));
+        assert!(export_table("approvals").is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! Golden decoder corpus
+//!
+//! `fixtures/decoder/*.json` holds eth_getLogs responses captured verbatim
+//! from mainnet RPC endpoints by `fixtures/decoder/capture.sh`, next to the
+//! request that produced them and a snapshot of each log's decoded output
+//! under `decoded` (null for a log the decoder rejects). Mainnet events we
+//! only hold production records of are rebuilt from those records instead;
+//! their `source` says where each field came from and `request` is null. A
+//! fresh capture has no snapshot yet: run the tests with `UPDATE_SNAPSHOTS=1`
+//! to record it, then review the diff.
+//!
+//! Malformed payloads the decoders must reject are covered by the unit tests
+//! next to each decoder rather than by hand-built logs here.
+
+#[cfg(test)]
+mod tests {
+    use crate::fusion::{
+        decode_crypto2fiat_event, decode_dst_escrow_created, decode_escrow_withdrawal,
+        decode_order_filled, decode_src_escrow_created,
+    };
+    use crate::poller::{decode_uint256, transfer_parties};
+    use crate::types::{
+        Log, CRYPTO2FIAT_TOPIC, DST_ESCROW_CREATED_TOPIC, ESCROW_WITHDRAWAL_TOPIC, ORDER_FILLED_TOPIC,
+        SRC_ESCROW_CREATED_TOPIC, TRANSFER_TOPIC,
+    };
+    use serde_json::{json, Value};
+    use std::fs;
+    use std::path::Path;
+
+    /// Decoded output of a log in snapshot form, by topic0
+    fn snapshot(log: &Log) -> Result<Value, String> {
+        let topic0 = log.topics.first().map(|t| t.to_lowercase()).unwrap_or_default();
+
+        let decoded = if topic0 == SRC_ESCROW_CREATED_TOPIC {
+            decode_src_escrow_created(&log.data).map(|d| {
+                json!({
+                    "order_hash": d.order_hash,
+                    "hashlock": d.hashlock,
+                    "src_maker": d.src_maker,
+                    "src_taker": d.src_taker,
+                    "src_token": d.src_token,
+                    "src_amount": d.src_amount,
+                    "src_safety_deposit": d.src_safety_deposit,
+                    "src_timelocks": d.src_timelocks,
+                    "dst_maker": d.dst_maker,
+                    "dst_amount": d.dst_amount,
+                    "dst_token": d.dst_token,
+                    "dst_safety_deposit": d.dst_safety_deposit,
+                    "dst_chain_id": d.dst_chain_id,
+                })
+            })
+        } else if topic0 == DST_ESCROW_CREATED_TOPIC {
+            decode_dst_escrow_created(&log.data).map(|d| {
+                json!({
+                    "order_hash": d.order_hash,
+                    "hashlock": d.hashlock,
+                    "dst_maker": d.dst_maker,
+                    "dst_taker": d.dst_taker,
+                    "dst_token": d.dst_token,
+                    "dst_amount": d.dst_amount,
+                    "dst_safety_deposit": d.dst_safety_deposit,
+                    "dst_timelocks": d.dst_timelocks,
+                })
+            })
+        } else if topic0 == ESCROW_WITHDRAWAL_TOPIC {
+            decode_escrow_withdrawal(&log.data).map(|secret| json!({ "secret": secret }))
+        } else if topic0 == ORDER_FILLED_TOPIC {
+            decode_order_filled(&log.topics, &log.data).map(|d| {
+                json!({ "maker": d.maker, "order_hash": d.order_hash, "remaining": d.remaining })
+            })
+        } else if topic0 == CRYPTO2FIAT_TOPIC {
+            decode_crypto2fiat_event(log).map(|e| {
+                json!({
+                    "order_id": e.order_id,
+                    "token": e.token,
+                    "amount": e.amount,
+                    "recipient": e.recipient,
+                    "metadata": e.metadata,
+                })
+            })
+        } else if topic0 == TRANSFER_TOPIC {
+            transfer_parties(log).map(|(from, to)| {
+                json!({ "from": from, "to": to, "value": decode_uint256(&log.data) })
+            })
+        } else {
+            return Err(format!("no decoder for topic {}", topic0));
+        };
+
+        Ok(decoded.unwrap_or(Value::Null))
+    }
+
+    #[test]
+    fn test_fixture_snapshots() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/decoder");
+        let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+
+        let mut paths: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty(), "no decoder fixtures in {}", dir.display());
+
+        let mut failures = Vec::new();
+        for path in &paths {
+            let name = path.file_stem().unwrap().to_string_lossy();
+            let mut fixture: Value = serde_json::from_str(&fs::read_to_string(path).unwrap())
+                .unwrap_or_else(|e| panic!("{}: invalid JSON: {}", name, e));
+            let logs: Vec<Log> = serde_json::from_value(fixture["response"]["result"].clone())
+                .unwrap_or_else(|e| panic!("{}: not an eth_getLogs response: {}", name, e));
+            assert!(!logs.is_empty(), "{}: the captured response has no logs", name);
+
+            let decoded = match logs.iter().map(snapshot).collect::<Result<Vec<_>, _>>() {
+                Ok(decoded) => Value::Array(decoded),
+                Err(e) => {
+                    failures.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            if decoded == fixture["decoded"] {
+                continue;
+            }
+            if update {
+                fixture["decoded"] = decoded;
+                fs::write(path, serde_json::to_string_pretty(&fixture).unwrap() + "\n").unwrap();
+            } else if fixture["decoded"].is_null() {
+                failures.push(format!("{}: no snapshot recorded yet", name));
+            } else {
+                failures.push(format!(
+                    "{}: decoded\n{}\nsnapshot\n{}",
+                    name,
+                    serde_json::to_string_pretty(&decoded).unwrap(),
+                    serde_json::to_string_pretty(&fixture["decoded"]).unwrap()
+                ));
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "decoder snapshots differ (UPDATE_SNAPSHOTS=1 to accept):\n{}",
+            failures.join("\n")
+        );
+    }
+}
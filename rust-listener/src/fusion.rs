@@ -1,117 +1,143 @@
+use crate::abi::EventDecoder;
 use crate::types::{Crypto2FiatEvent, DstEscrowCreatedData, Log, OrderFilledData, SrcEscrowCreatedData};
+use serde::Serialize;
 use sha3::{Digest, Keccak256};
+use std::sync::LazyLock;
 
-/// Decode SrcEscrowCreated event data
-///
-/// Event data layout (13 words × 32 bytes):
-/// Word 0: orderHash
-/// Word 1: hashlock
-/// Word 2: srcMaker (address in lower 160 bits)
-/// Word 3: srcTaker (address in lower 160 bits)
-/// Word 4: srcToken (address in lower 160 bits)
-/// Word 5: srcAmount
-/// Word 6: srcSafetyDeposit
-/// Word 7: srcTimelocks
-/// Word 8: dstMaker (address in lower 160 bits)
-/// Word 9: dstAmount
-/// Word 10: dstToken (address in lower 160 bits)
-/// Word 11: dstSafetyDeposit
-/// Word 12: dstChainId
-pub fn decode_src_escrow_created(data: &str) -> Option<SrcEscrowCreatedData> {
-    let hex = data.strip_prefix("0x").unwrap_or(data);
-
-    // Need at least 13 words (13 * 64 hex chars)
-    if hex.len() < 13 * 64 {
-        return None;
-    }
+// ============================================================================
+// Event ABIs
+// ============================================================================
 
-    let get_word = |idx: usize| -> &str {
-        &hex[idx * 64..(idx + 1) * 64]
-    };
+/// SrcEscrowCreated(Immutables srcImmutables, DstImmutablesComplement dstImmutablesComplement)
+///
+/// 1inch wraps addresses in a uint256 `Address` type; the decoder reads the
+/// lower 160 bits of those words.
+const SRC_ESCROW_CREATED_ABI: &str = r#"[{"type":"event","name":"SrcEscrowCreated","inputs":[
+    {"name":"srcImmutables","type":"tuple","indexed":false,"components":[
+        {"name":"orderHash","type":"bytes32"},
+        {"name":"hashlock","type":"bytes32"},
+        {"name":"maker","type":"uint256","internalType":"Address"},
+        {"name":"taker","type":"uint256","internalType":"Address"},
+        {"name":"token","type":"uint256","internalType":"Address"},
+        {"name":"amount","type":"uint256"},
+        {"name":"safetyDeposit","type":"uint256"},
+        {"name":"timelocks","type":"uint256","internalType":"Timelocks"}
+    ]},
+    {"name":"dstImmutablesComplement","type":"tuple","indexed":false,"components":[
+        {"name":"maker","type":"uint256","internalType":"Address"},
+        {"name":"amount","type":"uint256"},
+        {"name":"token","type":"uint256","internalType":"Address"},
+        {"name":"safetyDeposit","type":"uint256"},
+        {"name":"chainId","type":"uint256"}
+    ]}
+]}]"#;
+
+/// DstEscrowCreated as decoded by this listener: the destination Immutables
+///
+/// Note: this layout's topic0 differs from DST_ESCROW_CREATED_TOPIC, which is
+/// matched as a constant; only the data layout is taken from this ABI.
+const DST_ESCROW_CREATED_ABI: &str = r#"[{"type":"event","name":"DstEscrowCreated","inputs":[
+    {"name":"dstImmutables","type":"tuple","indexed":false,"components":[
+        {"name":"orderHash","type":"bytes32"},
+        {"name":"hashlock","type":"bytes32"},
+        {"name":"maker","type":"uint256","internalType":"Address"},
+        {"name":"taker","type":"uint256","internalType":"Address"},
+        {"name":"token","type":"uint256","internalType":"Address"},
+        {"name":"amount","type":"uint256"},
+        {"name":"safetyDeposit","type":"uint256"},
+        {"name":"timelocks","type":"uint256","internalType":"Timelocks"}
+    ]}
+]}]"#;
+
+/// EscrowWithdrawal(bytes32 secret)
+const ESCROW_WITHDRAWAL_ABI: &str = r#"[{"type":"event","name":"EscrowWithdrawal","inputs":[
+    {"name":"secret","type":"bytes32","indexed":false}
+]}]"#;
+
+/// OrderFilled(bytes32 orderHash, uint256 remainingAmount) from Aggregation Router V6
+const ORDER_FILLED_ABI: &str = r#"[{"type":"event","name":"OrderFilled","inputs":[
+    {"name":"orderHash","type":"bytes32","indexed":false},
+    {"name":"remainingAmount","type":"uint256","indexed":false}
+]}]"#;
+
+/// OrderCancelled(bytes32 orderHash) from Aggregation Router V6
+const ORDER_CANCELLED_ABI: &str = r#"[{"type":"event","name":"OrderCancelled","inputs":[
+    {"name":"orderHash","type":"bytes32","indexed":false}
+]}]"#;
+
+/// Crypto2Fiat(bytes32 indexed orderId, address indexed token, uint256 amount, address indexed recipient, bytes metadata)
+const CRYPTO2FIAT_ABI: &str = r#"[{"type":"event","name":"Crypto2Fiat","inputs":[
+    {"name":"orderId","type":"bytes32","indexed":true},
+    {"name":"token","type":"address","indexed":true},
+    {"name":"amount","type":"uint256","indexed":false},
+    {"name":"recipient","type":"address","indexed":true},
+    {"name":"metadata","type":"bytes","indexed":false}
+]}]"#;
+
+fn decoder(abi: &str, event_name: &str) -> EventDecoder {
+    EventDecoder::from_json_abi(abi, event_name)
+        .unwrap_or_else(|e| panic!("Invalid built-in ABI for {}: {}", event_name, e))
+}
 
-    let to_address = |word: &str| -> String {
-        format!("0x{}", &word[24..].to_lowercase()) // Last 40 chars, lowercased
-    };
+static SRC_ESCROW_CREATED: LazyLock<EventDecoder> =
+    LazyLock::new(|| decoder(SRC_ESCROW_CREATED_ABI, "SrcEscrowCreated"));
+static DST_ESCROW_CREATED: LazyLock<EventDecoder> =
+    LazyLock::new(|| decoder(DST_ESCROW_CREATED_ABI, "DstEscrowCreated"));
+static ESCROW_WITHDRAWAL: LazyLock<EventDecoder> =
+    LazyLock::new(|| decoder(ESCROW_WITHDRAWAL_ABI, "EscrowWithdrawal"));
+static ORDER_FILLED: LazyLock<EventDecoder> =
+    LazyLock::new(|| decoder(ORDER_FILLED_ABI, "OrderFilled"));
+static ORDER_CANCELLED: LazyLock<EventDecoder> =
+    LazyLock::new(|| decoder(ORDER_CANCELLED_ABI, "OrderCancelled"));
+static CRYPTO2FIAT: LazyLock<EventDecoder> =
+    LazyLock::new(|| decoder(CRYPTO2FIAT_ABI, "Crypto2Fiat"));
 
-    let to_bytes32 = |word: &str| -> String {
-        format!("0x{}", word.to_lowercase())
-    };
+// ============================================================================
+// 1inch Fusion+ Event Decoding
+// ============================================================================
 
-    // Parse dst_chain_id from hex
-    let dst_chain_id = u32::from_str_radix(get_word(12), 16).unwrap_or(0);
+/// Decode SrcEscrowCreated event data (13 words: Immutables + DstImmutablesComplement)
+pub fn decode_src_escrow_created(data: &str) -> Option<SrcEscrowCreatedData> {
+    let event = SRC_ESCROW_CREATED.decode_data(data)?;
 
     Some(SrcEscrowCreatedData {
-        order_hash: to_bytes32(get_word(0)),
-        hashlock: to_bytes32(get_word(1)),
-        src_maker: to_address(get_word(2)),
-        src_taker: to_address(get_word(3)),
-        src_token: to_address(get_word(4)),
-        src_amount: to_bytes32(get_word(5)),
-        src_safety_deposit: to_bytes32(get_word(6)),
-        src_timelocks: to_bytes32(get_word(7)),
-        dst_maker: to_address(get_word(8)),
-        dst_amount: to_bytes32(get_word(9)),
-        dst_token: to_address(get_word(10)),
-        dst_safety_deposit: to_bytes32(get_word(11)),
-        dst_chain_id,
+        order_hash: event.string("srcImmutables.orderHash"),
+        hashlock: event.string("srcImmutables.hashlock"),
+        src_maker: event.string("srcImmutables.maker"),
+        src_taker: event.string("srcImmutables.taker"),
+        src_token: event.string("srcImmutables.token"),
+        src_amount: event.string("srcImmutables.amount"),
+        src_safety_deposit: event.string("srcImmutables.safetyDeposit"),
+        src_timelocks: event.string("srcImmutables.timelocks"),
+        dst_maker: event.string("dstImmutablesComplement.maker"),
+        dst_amount: event.string("dstImmutablesComplement.amount"),
+        dst_token: event.string("dstImmutablesComplement.token"),
+        dst_safety_deposit: event.string("dstImmutablesComplement.safetyDeposit"),
+        dst_chain_id: u32::try_from(event.u64("dstImmutablesComplement.chainId")).unwrap_or(0),
     })
 }
 
-/// Decode DstEscrowCreated event data
-///
-/// Event data layout (8 words × 32 bytes):
-/// Word 0: orderHash
-/// Word 1: hashlock
-/// Word 2: dstMaker (address in lower 160 bits)
-/// Word 3: dstTaker (address in lower 160 bits)
-/// Word 4: dstToken (address in lower 160 bits)
-/// Word 5: dstAmount
-/// Word 6: dstSafetyDeposit
-/// Word 7: dstTimelocks
+/// Decode DstEscrowCreated event data (8 words: destination Immutables)
 pub fn decode_dst_escrow_created(data: &str) -> Option<DstEscrowCreatedData> {
-    let hex = data.strip_prefix("0x").unwrap_or(data);
-
-    // Need at least 8 words
-    if hex.len() < 8 * 64 {
-        return None;
-    }
-
-    let get_word = |idx: usize| -> &str {
-        &hex[idx * 64..(idx + 1) * 64]
-    };
-
-    let to_address = |word: &str| -> String {
-        format!("0x{}", &word[24..].to_lowercase())
-    };
-
-    let to_bytes32 = |word: &str| -> String {
-        format!("0x{}", word.to_lowercase())
-    };
+    let event = DST_ESCROW_CREATED.decode_data(data)?;
 
     Some(DstEscrowCreatedData {
-        order_hash: to_bytes32(get_word(0)),
-        hashlock: to_bytes32(get_word(1)),
-        dst_maker: to_address(get_word(2)),
-        dst_taker: to_address(get_word(3)),
-        dst_token: to_address(get_word(4)),
-        dst_amount: to_bytes32(get_word(5)),
-        dst_safety_deposit: to_bytes32(get_word(6)),
-        dst_timelocks: to_bytes32(get_word(7)),
+        order_hash: event.string("dstImmutables.orderHash"),
+        hashlock: event.string("dstImmutables.hashlock"),
+        dst_maker: event.string("dstImmutables.maker"),
+        dst_taker: event.string("dstImmutables.taker"),
+        dst_token: event.string("dstImmutables.token"),
+        dst_amount: event.string("dstImmutables.amount"),
+        dst_safety_deposit: event.string("dstImmutables.safetyDeposit"),
+        dst_timelocks: event.string("dstImmutables.timelocks"),
     })
 }
 
 /// Decode EscrowWithdrawal event data to extract the secret
-///
-/// Event data layout (1 word × 32 bytes):
-/// Word 0: secret
 pub fn decode_escrow_withdrawal(data: &str) -> Option<String> {
-    let hex = data.strip_prefix("0x").unwrap_or(data);
-
-    if hex.len() < 64 {
-        return None;
-    }
-
-    Some(format!("0x{}", &hex[0..64].to_lowercase()))
+    ESCROW_WITHDRAWAL
+        .decode_data(data)
+        .map(|event| event.string("secret"))
 }
 
 /// Compute hashlock from secret using keccak256
@@ -130,46 +156,294 @@ pub fn compute_hashlock_from_secret(secret: &str) -> Option<String> {
     Some(format!("0x{}", hex::encode(result)))
 }
 
+/// Whether a revealed secret hashes to `hashlock`; hex case is ignored and a
+/// secret that isn't hex never matches
+pub fn secret_matches_hashlock(secret: &str, hashlock: &str) -> bool {
+    compute_hashlock_from_secret(secret).is_some_and(|computed| computed.eq_ignore_ascii_case(hashlock))
+}
+
 // ============================================================================
-// 1inch Fusion (Single-Chain) Event Decoding - Aggregation Router V6
+// 1inch Fusion+ Timelocks
 // ============================================================================
 
-/// Decode OrderFilled event from Aggregation Router V6
+/// Escrow stages, in the order TimelocksLib packs their offsets into the
+/// Timelocks word (stage i in bits i*32..i*32+32, deployedAt in the top 32)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelockStage {
+    SrcWithdrawal = 0,
+    SrcPublicWithdrawal = 1,
+    SrcCancellation = 2,
+    SrcPublicCancellation = 3,
+    DstWithdrawal = 4,
+    DstPublicWithdrawal = 5,
+    DstCancellation = 6,
+}
+
+/// Unix time a stage starts: the escrow's deployment time plus the stage offset
+pub fn timelock_stage_start(timelocks: &str, stage: TimelockStage) -> Option<u64> {
+    let word = word_bytes(timelocks)?;
+    let deployed_at = u32::from_be_bytes(word[..4].try_into().ok()?) as u64;
+    let end = 32 - 4 * stage as usize;
+    let offset = u32::from_be_bytes(word[end - 4..end].try_into().ok()?) as u64;
+    Some(deployed_at + offset)
+}
+
+/// Phase of a withdrawal at `timestamp`: "public" once the leg's public
+/// withdrawal stage has started (anyone holding the access token may claim),
+/// "private" before that (only the taker may withdraw)
+pub fn withdrawal_phase(timelocks: &str, is_src: bool, timestamp: u64) -> Option<&'static str> {
+    let stage = if is_src {
+        TimelockStage::SrcPublicWithdrawal
+    } else {
+        TimelockStage::DstPublicWithdrawal
+    };
+    let public_from = timelock_stage_start(timelocks, stage)?;
+    Some(if timestamp >= public_from { "public" } else { "private" })
+}
+
+// ============================================================================
+// 1inch Fusion+ Escrow Address Computation (CREATE2)
+// ============================================================================
+
+/// EscrowFactory getters for the escrow implementations its proxies point to
+pub const ESCROW_SRC_IMPLEMENTATION_SIG: &str = "ESCROW_SRC_IMPLEMENTATION()";
+pub const ESCROW_DST_IMPLEMENTATION_SIG: &str = "ESCROW_DST_IMPLEMENTATION()";
+
+/// 4-byte call data for a no-argument function signature
+pub fn selector(signature: &str) -> String {
+    format!("0x{}", hex::encode(&Keccak256::digest(signature.as_bytes())[..4]))
+}
+
+/// keccak256 of the EIP-1167 proxy init code the factory deploys for `implementation`
 ///
-/// Event: OrderFilled(bytes32 orderHash, uint256 remainingAmount)
-/// topic[0]: event signature (0xfec331350fce78ba658e082a71da20ac9f8d798a99b3c79681c8440cbfe77e07)
-/// data:
-///   Word 0: orderHash (bytes32)
-///   Word 1: remainingAmount (uint256)
+/// Mirrors ProxyHashLib.computeProxyBytecodeHash.
+pub fn proxy_bytecode_hash(implementation: &str) -> Option<[u8; 32]> {
+    let implementation = word_bytes(implementation)?;
+    let mut init_code = hex::decode("3d602d80600a3d3981f3363d3d373d3d3d363d73").ok()?;
+    init_code.extend_from_slice(&implementation[12..]);
+    init_code.extend_from_slice(&hex::decode("5af43d82803e903d91602b57fd5bf3").ok()?);
+    Some(Keccak256::digest(&init_code).into())
+}
+
+/// CREATE2 address: keccak256(0xff ++ deployer ++ salt ++ bytecode_hash)[12..]
+pub fn compute_create2_address(deployer: &str, salt: &[u8; 32], bytecode_hash: &[u8; 32]) -> Option<String> {
+    let deployer = word_bytes(deployer)?;
+    let mut hasher = Keccak256::new();
+    hasher.update([0xff]);
+    hasher.update(&deployer[12..]);
+    hasher.update(salt);
+    hasher.update(bytecode_hash);
+    Some(format!("0x{}", hex::encode(&hasher.finalize()[12..])))
+}
+
+/// Escrow salt: keccak256 of the ABI-encoded Immutables (8 static words)
+#[allow(clippy::too_many_arguments)]
+fn immutables_hash(
+    order_hash: &str,
+    hashlock: &str,
+    maker: &str,
+    taker: &str,
+    token: &str,
+    amount: &str,
+    safety_deposit: &str,
+    timelocks: &str,
+) -> Option<[u8; 32]> {
+    let mut hasher = Keccak256::new();
+    for value in [order_hash, hashlock, maker, taker, token, amount, safety_deposit, timelocks] {
+        hasher.update(word_bytes(value)?);
+    }
+    Some(hasher.finalize().into())
+}
+
+/// Address of the source escrow deployed for a SrcEscrowCreated event
 ///
-/// Note: Router V6 does NOT have an indexed maker address in the event
-pub fn decode_order_filled(topics: &[String], data: &str) -> Option<OrderFilledData> {
-    if topics.is_empty() {
+/// The emitted timelocks already carry the deployment timestamp, so the
+/// immutables hash matches the salt the factory used.
+pub fn compute_src_escrow_address(data: &SrcEscrowCreatedData, factory: &str, bytecode_hash: &[u8; 32]) -> Option<String> {
+    let salt = immutables_hash(
+        &data.order_hash,
+        &data.hashlock,
+        &data.src_maker,
+        &data.src_taker,
+        &data.src_token,
+        &data.src_amount,
+        &data.src_safety_deposit,
+        &data.src_timelocks,
+    )?;
+    compute_create2_address(factory, &salt, bytecode_hash)
+}
+
+/// Address of the destination escrow deployed for a DstEscrowCreated event
+pub fn compute_dst_escrow_address(data: &DstEscrowCreatedData, factory: &str, bytecode_hash: &[u8; 32]) -> Option<String> {
+    let salt = immutables_hash(
+        &data.order_hash,
+        &data.hashlock,
+        &data.dst_maker,
+        &data.dst_taker,
+        &data.dst_token,
+        &data.dst_amount,
+        &data.dst_safety_deposit,
+        &data.dst_timelocks,
+    )?;
+    compute_create2_address(factory, &salt, bytecode_hash)
+}
+
+// ============================================================================
+// 1inch Fusion+ Multiple Fills (Merkle Tree of Secrets)
+// ============================================================================
+
+/// MerkleStorageInvalidator.takerInteraction: the Limit Order Protocol calls it
+/// on the escrow factory to validate a fill's secret hash before the factory
+/// deploys the fill's source escrow
+pub const TAKER_INTERACTION_SIG: &str = "takerInteraction((uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256),bytes,bytes32,address,uint256,uint256,uint256,bytes)";
+
+/// Index of PostInteractionData among the order extension's dynamic fields
+const POST_INTERACTION_FIELD: usize = 7;
+
+/// ExtraDataArgs (hashlockInfo, dstChainId, dstToken, deposits, timelocks)
+/// closing the factory's post-interaction data
+const EXTRA_DATA_ARGS_LENGTH: usize = 160;
+
+/// A fill of a multiple-fill order, as validated by the escrow factory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MerkleFill {
+    pub order_hash: String,
+    /// Merkle root of the order's secret hashes: the lower 240 bits of its
+    /// hashlock info, as a 32-byte word
+    pub root: String,
+    /// Parts the order can be filled in; the tree has `parts + 1` leaves
+    pub parts: u16,
+    /// Leaf index of the fill's secret
+    pub index: u64,
+    /// Hash of the fill's secret, the hashlock of its escrows
+    pub secret_hash: String,
+    /// Whether the proof leads from the leaf to the root
+    pub proof_valid: bool,
+}
+
+/// Decode takerInteraction call data and check the fill's proof against the
+/// root in the order's extension
+///
+/// Returns None for other calls and malformed data.
+pub fn decode_taker_interaction(input: &str) -> Option<MerkleFill> {
+    let input = hex::decode(input.strip_prefix("0x").unwrap_or(input)).ok()?;
+    let (call_selector, args) = input.split_at_checked(4)?;
+    if call_selector != &Keccak256::digest(TAKER_INTERACTION_SIG.as_bytes())[..4] {
         return None;
     }
 
-    let hex = data.strip_prefix("0x").unwrap_or(data);
+    // Head: order (8 words), extension, orderHash, taker, making, taking, remaining, extraData
+    let extension = abi_bytes(args, 8)?;
+    let order_hash = args.get(9 * 32..10 * 32)?;
+    let taker_data = abi_bytes(args, 14)?;
+
+    let post_interaction = extension_field(extension, POST_INTERACTION_FIELD)?;
+    let extra_data_args = post_interaction.get(post_interaction.len().checked_sub(EXTRA_DATA_ARGS_LENGTH)?..)?;
+    let (parts, root) = extra_data_args[..32].split_at(2);
+
+    // TakerData { bytes32[] proof; uint256 idx; bytes32 secretHash }
+    let proof_offset = abi_usize(taker_data, 0)?;
+    let index = u64::from_be_bytes(taker_data.get(56..64)?.try_into().ok()?);
+    let secret_hash: [u8; 32] = taker_data.get(64..96)?.try_into().ok()?;
+    let proof_len = abi_usize(taker_data.get(proof_offset..)?, 0)?;
+    let proof_start = proof_offset.checked_add(32)?;
+    let proof = taker_data.get(proof_start..proof_start.checked_add(proof_len.checked_mul(32)?)?)?;
+
+    let computed = process_merkle_proof(merkle_leaf(index, &secret_hash), proof.chunks(32));
+
+    Some(MerkleFill {
+        order_hash: format!("0x{}", hex::encode(order_hash)),
+        root: format!("0x0000{}", hex::encode(root)),
+        parts: u16::from_be_bytes(parts.try_into().ok()?),
+        index,
+        secret_hash: format!("0x{}", hex::encode(secret_hash)),
+        proof_valid: computed[2..] == *root,
+    })
+}
+
+/// Leaf of a secret hash: keccak256(uint64 index ++ secretHash)
+pub fn merkle_leaf(index: u64, secret_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(index.to_be_bytes());
+    hasher.update(secret_hash);
+    hasher.finalize().into()
+}
 
-    // Need at least 2 words (2 * 64 hex chars)
-    if hex.len() < 128 {
+/// Root reached from `leaf` through `proof`, hashing each pair in sorted
+/// order (OpenZeppelin MerkleProof)
+pub fn process_merkle_proof<'a>(leaf: [u8; 32], proof: impl IntoIterator<Item = &'a [u8]>) -> [u8; 32] {
+    proof.into_iter().fold(leaf, |node, sibling| {
+        let (a, b) = if node.as_slice() <= sibling { (node.as_slice(), sibling) } else { (sibling, node.as_slice()) };
+        let mut hasher = Keccak256::new();
+        hasher.update(a);
+        hasher.update(b);
+        hasher.finalize().into()
+    })
+}
+
+/// A dynamic field of a Limit Order Protocol extension
+///
+/// The first word packs each field's end offset as a uint32 (field i in bits
+/// i*32..i*32+32); the fields follow, concatenated.
+fn extension_field(extension: &[u8], field: usize) -> Option<&[u8]> {
+    let (offsets, fields) = extension.split_at_checked(32)?;
+    let end = |i: usize| {
+        let at = 28 - 4 * i;
+        u32::from_be_bytes([offsets[at], offsets[at + 1], offsets[at + 2], offsets[at + 3]]) as usize
+    };
+    let begin = if field == 0 { 0 } else { end(field - 1) };
+    fields.get(begin..end(field))
+}
+
+/// ABI word at `slot` as an offset or length
+fn abi_usize(data: &[u8], slot: usize) -> Option<usize> {
+    let word = data.get(slot * 32..slot * 32 + 32)?;
+    if word[..24].iter().any(|&b| b != 0) {
         return None;
     }
+    usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+}
 
-    let get_word = |idx: usize| -> &str {
-        &hex[idx * 64..(idx + 1) * 64]
-    };
+/// Dynamic `bytes` argument whose offset is in head word `slot`
+fn abi_bytes(args: &[u8], slot: usize) -> Option<&[u8]> {
+    let offset = abi_usize(args, slot)?;
+    let len = abi_usize(args.get(offset..)?, 0)?;
+    let start = offset.checked_add(32)?;
+    args.get(start..start.checked_add(len)?)
+}
+
+/// Left-pad a hex value (address or word) to a 32-byte ABI word
+fn word_bytes(value: &str) -> Option<[u8; 32]> {
+    let hex_str = value.strip_prefix("0x").unwrap_or(value);
+    if hex_str.len() > 64 {
+        return None;
+    }
+    let bytes = hex::decode(format!("{:0>64}", hex_str)).ok()?;
+    bytes.try_into().ok()
+}
+
+// ============================================================================
+// 1inch Fusion (Single-Chain) Event Decoding - Aggregation Router V6
+// ============================================================================
+
+/// Decode OrderFilled event from Aggregation Router V6
+///
+/// Note: Router V6 does NOT have an indexed maker address in the event
+pub fn decode_order_filled(topics: &[String], data: &str) -> Option<OrderFilledData> {
+    let event = ORDER_FILLED.decode(topics, data)?;
 
     Some(OrderFilledData {
         // Router V6 doesn't emit maker in the event, we'll extract from tx later if needed
         maker: String::new(),
-        order_hash: format!("0x{}", get_word(0).to_lowercase()),
-        remaining: format!("0x{}", get_word(1).to_lowercase()),
+        order_hash: event.string("orderHash"),
+        remaining: event.string("remainingAmount"),
     })
 }
 
-/// Decode OrderCancelled event (same format as OrderFilled)
-pub fn decode_order_cancelled(topics: &[String], data: &str) -> Option<OrderFilledData> {
-    decode_order_filled(topics, data)
+/// Decode OrderCancelled event from Aggregation Router V6, returning the order hash
+pub fn decode_order_cancelled(topics: &[String], data: &str) -> Option<String> {
+    let event = ORDER_CANCELLED.decode(topics, data)?;
+    Some(event.string("orderHash"))
 }
 
 // ============================================================================
@@ -178,71 +452,17 @@ pub fn decode_order_cancelled(topics: &[String], data: &str) -> Option<OrderFill
 
 /// Decode Crypto2Fiat event from KentuckyDelegate
 ///
-/// Event: Crypto2Fiat(bytes32 indexed orderId, address indexed token, uint256 amount, address indexed recipient, bytes metadata)
-/// topic[0]: event signature (0x86ac35f38cd2d17935b5bb6295c74cadb683bcfba935852c32096a81df8998ef)
-/// topic[1]: orderId (bytes32, indexed)
-/// topic[2]: token (address, indexed - last 20 bytes of 32)
-/// topic[3]: recipient (address, indexed - last 20 bytes of 32)
-/// data:
-///   Word 0: amount (uint256)
-///   Word 1: offset to metadata (dynamic bytes)
-///   Word N: metadata length
-///   Word N+1...: metadata content
+/// Metadata is JSON-encoded fiat details carried as dynamic bytes
 pub fn decode_crypto2fiat_event(log: &Log) -> Option<Crypto2FiatEvent> {
-    // Need 4 topics: event sig + 3 indexed params
-    if log.topics.len() < 4 {
-        return None;
-    }
-
-    // Parse indexed topics
-    let order_id = log.topics[1].to_lowercase();
-    let token = format!("0x{}", &log.topics[2][log.topics[2].len() - 40..].to_lowercase());
-    let recipient = format!("0x{}", &log.topics[3][log.topics[3].len() - 40..].to_lowercase());
-
-    // Parse data
-    let hex = log.data.strip_prefix("0x").unwrap_or(&log.data);
-
-    // Need at least amount (32 bytes = 64 hex chars) + offset (32 bytes)
-    if hex.len() < 128 {
-        return None;
-    }
+    let event = CRYPTO2FIAT.decode(&log.topics, &log.data)?;
 
-    // Word 0: amount
-    let amount_hex = &hex[0..64];
-    let amount = format!("0x{}", amount_hex);
-
-    // Parse metadata (dynamic bytes)
-    // Word 1: offset to metadata data
-    let offset_hex = &hex[64..128];
-    let offset = usize::from_str_radix(offset_hex, 16).unwrap_or(0);
-    let offset_in_hex = offset * 2; // Convert byte offset to hex char offset
-
-    let metadata = if hex.len() > offset_in_hex + 64 {
-        // At offset position: length of bytes
-        let length_hex = &hex[offset_in_hex..offset_in_hex + 64];
-        let length = usize::from_str_radix(length_hex, 16).unwrap_or(0);
-        let length_in_hex = length * 2;
-
-        // After length: actual bytes data
-        let data_start = offset_in_hex + 64;
-        let data_end = std::cmp::min(data_start + length_in_hex, hex.len());
-
-        if data_start < hex.len() {
-            let metadata_hex = &hex[data_start..data_end];
-            // Decode hex bytes to UTF-8 string (metadata is JSON)
-            hex_to_utf8(metadata_hex).unwrap_or_default()
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
-    };
+    let metadata = hex_to_utf8(event.get("metadata")?.trim_start_matches("0x")).unwrap_or_default();
 
     Some(Crypto2FiatEvent {
-        order_id,
-        token,
-        amount,
-        recipient,
+        order_id: event.string("orderId"),
+        token: event.string("token"),
+        amount: event.string("amount"),
+        recipient: event.string("recipient"),
         metadata,
         // These will be filled by the caller
         chain_id: 0,
@@ -250,6 +470,7 @@ pub fn decode_crypto2fiat_event(log: &Log) -> Option<Crypto2FiatEvent> {
         block_number: 0,
         block_timestamp: 0,
         log_index: 0,
+        id: None,
     })
 }
 
@@ -276,6 +497,31 @@ mod tests {
         assert_eq!(parsed.dst_chain_id, 8613); // 0x21a5
     }
 
+    #[test]
+    fn test_decode_src_escrow_created_variants() {
+        let data = "0x169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa9b80a9e9053b23333887b6047be5ac6d3f62175a993ed349bd2bf92bf95fa0ce700000000000000000000000087f0f4b7e0c4a8d9e93e4c7e2b1b4f3d3a8c5d6e000000000000000000000000resolver000000000000000000000000000000000000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e583100000000000000000000000000000000000000000000000000000000001e848000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000067890abc00000000000000000000000087f0f4b7e0c4a8d9e93e4c7e2b1b4f3d3a8c5d6e00000000000000000000000000000000000000000000000000000000001dcd6500000000000000000000000833589fcd6edb6e08f4c7c32d4f71b54bda02913000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000021a5";
+        let expected = decode_src_escrow_created(data).unwrap();
+
+        // Providers may return uppercase hex
+        assert_eq!(decode_src_escrow_created(&format!("0x{}", data[2..].to_uppercase())), Some(expected.clone()));
+        // A newer factory may append fields; only the first 13 words are read
+        assert_eq!(decode_src_escrow_created(&format!("{}{:064x}", data, 0xdead)), Some(expected));
+        // Truncated payloads are rejected
+        assert_eq!(decode_src_escrow_created(&data[..data.len() - 64]), None);
+    }
+
+    #[test]
+    fn test_decode_short_payloads_rejected() {
+        let word = "169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa9";
+
+        assert_eq!(decode_dst_escrow_created(&format!("0x{}", word.repeat(3))), None);
+        assert_eq!(decode_escrow_withdrawal("0x"), None);
+        assert_eq!(
+            decode_order_filled(&[crate::types::ORDER_FILLED_TOPIC.to_string()], &format!("0x{}", word)),
+            None
+        );
+    }
+
     #[test]
     fn test_decode_escrow_withdrawal() {
         let data = "0xe9af1234567890abcdef1234567890abcdef1234567890abcdef1234567890ab";
@@ -299,6 +545,164 @@ mod tests {
         assert_eq!(result.len(), 66); // 0x + 64 hex chars
     }
 
+    #[test]
+    fn test_secret_matches_hashlock() {
+        let secret = "0x4925e041e603ea86152f3b4e77e355d1e7bff747f7e1b3cb94eb9909ef969cf1";
+        let hashlock = compute_hashlock_from_secret(secret).unwrap();
+
+        assert!(secret_matches_hashlock(secret, &hashlock));
+        assert!(secret_matches_hashlock(&secret.to_uppercase().replace("0X", "0x"), &hashlock.to_uppercase().replace("0X", "0x")));
+        // The hashlock of another secret
+        let other = format!("{}0", &secret[..secret.len() - 1]);
+        assert!(!secret_matches_hashlock(&other, &hashlock));
+        // The secret itself isn't its hashlock
+        assert!(!secret_matches_hashlock(secret, secret));
+        assert!(!secret_matches_hashlock("0xzz", &hashlock));
+        assert!(!secret_matches_hashlock(secret, ""));
+    }
+
+    #[test]
+    fn test_compute_create2_address() {
+        // EIP-1014 examples 0 and 1 (init code 0x00)
+        let bytecode_hash: [u8; 32] = Keccak256::digest([0u8]).into();
+        let zero_salt = [0u8; 32];
+
+        assert_eq!(
+            compute_create2_address("0x0000000000000000000000000000000000000000", &zero_salt, &bytecode_hash).unwrap(),
+            "0x4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"
+        );
+        assert_eq!(
+            compute_create2_address("0xdeadbeef00000000000000000000000000000000", &zero_salt, &bytecode_hash).unwrap(),
+            "0xb928f69bb1d91cd65274e3c79d8986362984fda3"
+        );
+        assert_eq!(selector("transfer(address,uint256)"), "0xa9059cbb");
+    }
+
+    /// Sorted-pair Merkle tree over `leaves`; returns the root and each leaf's proof
+    fn merkle_tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let mut proofs = vec![Vec::new(); leaves.len()];
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            for (leaf, position) in positions.iter_mut().enumerate() {
+                if let Some(sibling) = level.get(*position ^ 1) {
+                    proofs[leaf].push(*sibling);
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => process_merkle_proof(*a, [b.as_slice()]),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        (level[0], proofs)
+    }
+
+    fn word(value: usize) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+        word
+    }
+
+    /// ABI `bytes` tail: length word, then the data padded to whole words
+    fn abi_tail(data: &[u8]) -> Vec<u8> {
+        let mut tail = word(data.len()).to_vec();
+        tail.extend_from_slice(data);
+        tail.resize(32 + data.len().div_ceil(32) * 32, 0);
+        tail
+    }
+
+    /// takerInteraction call data for fill `index` of an order with `root` and `parts`
+    fn taker_interaction_input(order_hash: [u8; 32], parts: u16, root: &[u8; 32], index: usize, secret_hash: [u8; 32], proof: &[[u8; 32]]) -> String {
+        // Post-interaction: factory address, then ExtraDataArgs starting with hashlockInfo
+        let mut post_interaction = vec![0x11u8; 20];
+        let mut hashlock_info = *root;
+        hashlock_info[..2].copy_from_slice(&parts.to_be_bytes());
+        post_interaction.extend_from_slice(&hashlock_info);
+        post_interaction.extend_from_slice(&[0x22u8; 128]);
+
+        // A maker permit (field 5) before it, so offsets are not trivial
+        let permit = [0x33u8; 40];
+        let mut offsets = [0u8; 32];
+        for field in 5..=7 {
+            let end = if field == 7 { permit.len() + post_interaction.len() } else { permit.len() };
+            offsets[28 - 4 * field..32 - 4 * field].copy_from_slice(&(end as u32).to_be_bytes());
+        }
+        let extension = [offsets.as_slice(), &permit, &post_interaction].concat();
+
+        let mut taker_data = [word(0x60), word(index), secret_hash].concat();
+        taker_data.extend_from_slice(&word(proof.len()));
+        proof.iter().for_each(|node| taker_data.extend_from_slice(node));
+
+        let extension_tail = abi_tail(&extension);
+        let mut args = vec![0u8; 8 * 32];
+        args.extend_from_slice(&word(15 * 32));
+        args.extend_from_slice(&order_hash);
+        args.extend_from_slice(&[0u8; 4 * 32]);
+        args.extend_from_slice(&word(15 * 32 + extension_tail.len()));
+        args.extend_from_slice(&extension_tail);
+        args.extend_from_slice(&abi_tail(&taker_data));
+
+        format!("{}{}", selector(TAKER_INTERACTION_SIG), hex::encode(args))
+    }
+
+    #[test]
+    fn test_decode_taker_interaction() {
+        let order_hash = [0x5au8; 32];
+        let secret_hashes: Vec<[u8; 32]> = (0..4u8).map(|i| Keccak256::digest([i]).into()).collect();
+        let leaves: Vec<_> = secret_hashes.iter().enumerate().map(|(i, h)| merkle_leaf(i as u64, h)).collect();
+        let (root, proofs) = merkle_tree(&leaves);
+
+        let fill = decode_taker_interaction(&taker_interaction_input(order_hash, 3, &root, 2, secret_hashes[2], &proofs[2])).unwrap();
+        assert_eq!(fill.order_hash, format!("0x{}", "5a".repeat(32)));
+        assert_eq!(fill.root, format!("0x0000{}", hex::encode(&root[2..])));
+        assert_eq!(fill.parts, 3);
+        assert_eq!(fill.index, 2);
+        assert_eq!(fill.secret_hash, format!("0x{}", hex::encode(secret_hashes[2])));
+        assert!(fill.proof_valid);
+
+        // Every leaf's own proof is valid
+        for (i, proof) in proofs.iter().enumerate() {
+            let input = taker_interaction_input(order_hash, 3, &root, i, secret_hashes[i], proof);
+            assert!(decode_taker_interaction(&input).unwrap().proof_valid, "leaf {}", i);
+        }
+
+        // A secret hash claimed at another index, or another leaf's proof, doesn't reach the root
+        let input = taker_interaction_input(order_hash, 3, &root, 1, secret_hashes[2], &proofs[2]);
+        assert!(!decode_taker_interaction(&input).unwrap().proof_valid);
+        let input = taker_interaction_input(order_hash, 3, &root, 2, secret_hashes[2], &proofs[0]);
+        assert!(!decode_taker_interaction(&input).unwrap().proof_valid);
+    }
+
+    #[test]
+    fn test_decode_taker_interaction_rejects_other_calls() {
+        let (root, proofs) = merkle_tree(&[merkle_leaf(0, &[1u8; 32]), merkle_leaf(1, &[2u8; 32])]);
+        let input = taker_interaction_input([0u8; 32], 1, &root, 0, [1u8; 32], &proofs[0]);
+
+        assert!(decode_taker_interaction(&input).is_some());
+        assert_eq!(decode_taker_interaction(&format!("0xa9059cbb{}", &input[10..])), None);
+        assert_eq!(decode_taker_interaction(&input[..input.len() - 64]), None);
+        assert_eq!(decode_taker_interaction("0x"), None);
+        assert_eq!(decode_taker_interaction("0xzz"), None);
+    }
+
+    #[test]
+    fn test_abi_topics_match_constants() {
+        use crate::types::{
+            CRYPTO2FIAT_TOPIC, ESCROW_WITHDRAWAL_TOPIC, ORDER_CANCELLED_TOPIC, ORDER_FILLED_TOPIC, SRC_ESCROW_CREATED_TOPIC,
+        };
+
+        assert_eq!(SRC_ESCROW_CREATED.topic0(), SRC_ESCROW_CREATED_TOPIC);
+        assert_eq!(ESCROW_WITHDRAWAL.topic0(), ESCROW_WITHDRAWAL_TOPIC);
+        assert_eq!(ORDER_FILLED.topic0(), ORDER_FILLED_TOPIC);
+        assert_eq!(ORDER_CANCELLED.topic0(), ORDER_CANCELLED_TOPIC);
+        assert_eq!(CRYPTO2FIAT.topic0(), CRYPTO2FIAT_TOPIC);
+    }
+
     #[test]
     fn test_decode_order_filled() {
         // Simulated OrderFilled event from Aggregation Router V6
@@ -316,4 +720,30 @@ mod tests {
         assert_eq!(parsed.order_hash, "0x169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa9");
         assert_eq!(parsed.remaining, "0x0000000000000000000000000000000000000000000000000000000000000000");
     }
+
+    #[test]
+    fn test_decode_order_cancelled() {
+        let topics = vec![crate::types::ORDER_CANCELLED_TOPIC.to_string()];
+        let data = "0x169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa9";
+
+        assert_eq!(
+            decode_order_cancelled(&topics, data).as_deref(),
+            Some("0x169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa9")
+        );
+        assert_eq!(decode_order_cancelled(&topics, "0x1234"), None);
+    }
+
+    #[test]
+    fn test_withdrawal_phase() {
+        // deployedAt 1_700_000_000, then offsets for stages 6..0
+        let timelocks = "0x6553f100000002580000012c0000003c00000708000004b0000001680000001e";
+        assert_eq!(timelock_stage_start(timelocks, TimelockStage::SrcWithdrawal), Some(1_700_000_030));
+        assert_eq!(timelock_stage_start(timelocks, TimelockStage::DstCancellation), Some(1_700_000_600));
+
+        assert_eq!(withdrawal_phase(timelocks, true, 1_700_000_100), Some("private"));
+        assert_eq!(withdrawal_phase(timelocks, true, 1_700_000_360), Some("public"));
+        assert_eq!(withdrawal_phase(timelocks, false, 1_700_000_299), Some("private"));
+        assert_eq!(withdrawal_phase(timelocks, false, 1_700_000_300), Some("public"));
+        assert_eq!(withdrawal_phase("0xzz", true, 0), None);
+    }
 }
@@ -0,0 +1,147 @@
+use crate::events::{EventBus, EventFilter, FusionPlusUpdate, IndexedEvent};
+use crate::types::Transfer;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+pub mod proto {
+    tonic::include_proto!("listener.v1");
+}
+
+use proto::listener_server::{Listener, ListenerServer};
+use proto::StreamFilter;
+
+type EventStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Serve the streaming API until the task is aborted
+pub async fn serve(events: Arc<EventBus>, bind: &str) -> Result<(), String> {
+    let addr: SocketAddr = bind
+        .parse()
+        .map_err(|e| format!("Invalid GRPC_BIND {}: {}", bind, e))?;
+    info!("gRPC API listening on {}", addr);
+
+    tonic::transport::Server::builder()
+        .add_service(ListenerServer::new(ListenerService { events }))
+        .serve(addr)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+struct ListenerService {
+    events: Arc<EventBus>,
+}
+
+impl ListenerService {
+    /// Subscribe to the bus and keep matching events that `select` maps to a message
+    fn stream<T, F>(&self, filter: EventFilter, select: F) -> EventStream<T>
+    where
+        T: Send + 'static,
+        F: Fn(IndexedEvent) -> Option<T> + Send + 'static,
+    {
+        let stream = BroadcastStream::new(self.events.subscribe()).filter_map(move |event| match event {
+            Ok(event) if filter.matches(&event) => select(event).map(Ok),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                warn!("gRPC subscriber lagged, {} events dropped", missed);
+                None
+            }
+        });
+        Box::pin(stream)
+    }
+}
+
+#[tonic::async_trait]
+impl Listener for ListenerService {
+    type StreamTransfersStream = EventStream<proto::TransferEvent>;
+    type StreamFusionPlusUpdatesStream = EventStream<proto::FusionPlusUpdate>;
+
+    async fn stream_transfers(
+        &self,
+        request: Request<StreamFilter>,
+    ) -> Result<Response<Self::StreamTransfersStream>, Status> {
+        let filter = EventFilter::from(request.into_inner());
+        Ok(Response::new(self.stream(filter, |event| match event {
+            IndexedEvent::Transfer(t) => Some((*t).into()),
+            _ => None,
+        })))
+    }
+
+    async fn stream_fusion_plus_updates(
+        &self,
+        request: Request<StreamFilter>,
+    ) -> Result<Response<Self::StreamFusionPlusUpdatesStream>, Status> {
+        let filter = EventFilter::from(request.into_inner());
+        Ok(Response::new(self.stream(filter, |event| match event {
+            IndexedEvent::FusionPlus(u) => Some((*u).into()),
+            _ => None,
+        })))
+    }
+}
+
+impl From<StreamFilter> for EventFilter {
+    fn from(f: StreamFilter) -> Self {
+        Self {
+            chain_ids: f.chain_ids,
+            addresses: f.addresses,
+            tokens: f.tokens,
+            event_types: Vec::new(),
+        }
+    }
+}
+
+// =============================================================================
+// Conversions
+// =============================================================================
+
+impl From<Transfer> for proto::TransferEvent {
+    fn from(t: Transfer) -> Self {
+        Self {
+            chain_id: t.chain_id,
+            tx_hash: t.tx_hash,
+            log_index: t.log_index,
+            token: t.token,
+            from_addr: t.from_addr,
+            to_addr: t.to_addr,
+            value: t.value,
+            value_decimal: t.value_decimal,
+            block_number: t.block_number,
+            block_timestamp: t.block_timestamp,
+            swap_type: t.swap_type,
+            tx_status: t.tx_status.map(u32::from),
+        }
+    }
+}
+
+impl From<FusionPlusUpdate> for proto::FusionPlusUpdate {
+    fn from(u: FusionPlusUpdate) -> Self {
+        let s = u.swap;
+        Self {
+            stage: u.stage.to_string(),
+            chain_id: u.chain_id,
+            tx_hash: u.tx_hash,
+            block_number: u.block_number,
+            order_hash: s.order_hash,
+            hashlock: s.hashlock,
+            secret: s.secret,
+            src_chain_id: s.src_chain_id,
+            src_maker: s.src_maker,
+            src_taker: s.src_taker,
+            src_token: s.src_token,
+            src_amount: s.src_amount,
+            src_status: s.src_status,
+            dst_chain_id: s.dst_chain_id,
+            dst_maker: s.dst_maker,
+            dst_taker: s.dst_taker,
+            dst_token: s.dst_token,
+            dst_amount: s.dst_amount,
+            dst_status: s.dst_status,
+            secret_revealed_chain_id: s.secret_revealed_chain_id,
+            secret_revealed_at: s.secret_revealed_at,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::rpc::{CircuitState, MethodUsage};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Progress reported by each chain's poller, read by the health endpoints
+pub struct HealthRegistry {
+    /// Checkpoint age above which a chain is reported unhealthy
+    max_lag_secs: u64,
+    chains: RwLock<BTreeMap<u32, ChainProgress>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ChainProgress {
+    name: String,
+    head_block: Option<u64>,
+    checkpoint: Option<u64>,
+    checkpoint_timestamp: Option<u64>,
+    last_poll_at: Option<u64>,
+    gap_blocks: Option<u64>,
+    stale_head: bool,
+    rpc_circuit: Option<CircuitState>,
+    rpc_usage: BTreeMap<String, MethodUsage>,
+    stopped: bool,
+}
+
+/// RPC traffic of one chain's poller since it started
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainRpcUsage {
+    pub chain_id: u32,
+    pub name: String,
+    /// Per method, with eth_getLogs split by event category
+    pub methods: BTreeMap<String, MethodUsage>,
+    /// Circuit breaker of the chain's RPC client; None until the first poll
+    pub circuit: Option<CircuitState>,
+}
+
+/// Health of one chain at the time of the request
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainHealth {
+    pub chain_id: u32,
+    pub name: String,
+    pub checkpoint: Option<u64>,
+    pub head_block: Option<u64>,
+    pub lag_blocks: Option<u64>,
+    /// Age of the checkpoint block
+    pub lag_secs: Option<u64>,
+    pub last_poll_at: Option<u64>,
+    /// Unindexed blocks between indexed ranges; None until the first gap scan
+    pub gap_blocks: Option<u64>,
+    /// The RPC endpoint serves a head that stopped advancing or lags the wall clock
+    pub stale_head: bool,
+    /// Circuit breaker of the chain's RPC client; None until the first poll
+    pub rpc_circuit: Option<CircuitState>,
+    /// Lag is within the threshold
+    pub healthy: bool,
+    /// Poller stopped through the admin API; ignored by readiness
+    pub stopped: bool,
+}
+
+impl HealthRegistry {
+    pub fn new(max_lag_secs: u64) -> Self {
+        Self {
+            max_lag_secs,
+            chains: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Add a chain before its first poll so it is reported as not yet healthy
+    pub fn register(&self, chain_id: u32, name: &str) {
+        self.chains.write().unwrap().entry(chain_id).or_default().name = name.to_string();
+    }
+
+    /// Drop a chain that is no longer polled
+    pub fn unregister(&self, chain_id: u32) {
+        self.chains.write().unwrap().remove(&chain_id);
+    }
+
+    /// Mark a chain's poller as stopped or running again
+    pub fn set_stopped(&self, chain_id: u32, stopped: bool) {
+        self.chains.write().unwrap().entry(chain_id).or_default().stopped = stopped;
+    }
+
+    /// Record a successful poll; `checkpoint` is None when no new blocks were processed
+    pub fn record_poll(&self, chain_id: u32, head_block: u64, checkpoint: Option<(u64, u64)>) {
+        let mut chains = self.chains.write().unwrap();
+        let progress = chains.entry(chain_id).or_default();
+        progress.head_block = Some(head_block);
+        progress.last_poll_at = Some(unix_now());
+        if let Some((block, timestamp)) = checkpoint {
+            progress.checkpoint = Some(block);
+            progress.checkpoint_timestamp = Some(timestamp);
+        }
+    }
+
+    /// Record how many blocks are still missing from the chain's indexed history
+    pub fn record_gaps(&self, chain_id: u32, missing_blocks: u64) {
+        self.chains.write().unwrap().entry(chain_id).or_default().gap_blocks = Some(missing_blocks);
+    }
+
+    /// Mark a chain's RPC head as stale or advancing again
+    pub fn set_stale_head(&self, chain_id: u32, stale: bool) {
+        self.chains.write().unwrap().entry(chain_id).or_default().stale_head = stale;
+    }
+
+    /// Record the state of a chain's RPC circuit breaker
+    pub fn record_circuit(&self, chain_id: u32, circuit: CircuitState) {
+        self.chains.write().unwrap().entry(chain_id).or_default().rpc_circuit = Some(circuit);
+    }
+
+    /// Record the RPC traffic of a chain's poller
+    pub fn record_rpc_usage(&self, chain_id: u32, usage: BTreeMap<String, MethodUsage>) {
+        self.chains.write().unwrap().entry(chain_id).or_default().rpc_usage = usage;
+    }
+
+    /// Per-chain RPC traffic
+    pub fn rpc_usage(&self) -> Vec<ChainRpcUsage> {
+        self.chains
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(&chain_id, p)| ChainRpcUsage {
+                chain_id,
+                name: p.name.clone(),
+                methods: p.rpc_usage.clone(),
+                circuit: p.rpc_circuit,
+            })
+            .collect()
+    }
+
+    /// Per-chain health; a chain is healthy once its checkpoint block is at most `max_lag_secs` old
+    pub fn report(&self) -> Vec<ChainHealth> {
+        let now = unix_now();
+        self.chains
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(&chain_id, p)| {
+                let lag_secs = p.checkpoint_timestamp.map(|ts| now.saturating_sub(ts));
+                ChainHealth {
+                    chain_id,
+                    name: p.name.clone(),
+                    checkpoint: p.checkpoint,
+                    head_block: p.head_block,
+                    lag_blocks: p.head_block.zip(p.checkpoint).map(|(head, cp)| head.saturating_sub(cp)),
+                    lag_secs,
+                    last_poll_at: p.last_poll_at,
+                    gap_blocks: p.gap_blocks,
+                    stale_head: p.stale_head,
+                    rpc_circuit: p.rpc_circuit,
+                    healthy: lag_secs.is_some_and(|lag| lag <= self.max_lag_secs),
+                    stopped: p.stopped,
+                }
+            })
+            .collect()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_lag() {
+        let health = HealthRegistry::new(60);
+        health.register(1, "Ethereum");
+        health.register(8453, "Base");
+
+        let now = unix_now();
+        health.record_poll(1, 110, Some((100, now - 30)));
+        health.record_poll(1, 112, None);
+
+        let report = health.report();
+        assert_eq!(report.len(), 2);
+
+        let eth = &report[0];
+        assert_eq!(eth.head_block, Some(112));
+        assert_eq!(eth.checkpoint, Some(100));
+        assert_eq!(eth.lag_blocks, Some(12));
+        assert!(eth.lag_secs.unwrap() >= 30);
+        assert!(eth.healthy);
+
+        health.record_poll(1, 112, Some((100, now - 90)));
+        assert!(!health.report()[0].healthy);
+
+        // Never polled
+        assert_eq!(report[1].name, "Base");
+        assert!(!report[1].healthy);
+        assert!(!report[1].stopped);
+        assert_eq!(report[1].gap_blocks, None);
+        health.record_gaps(8453, 250);
+        assert_eq!(health.report()[1].gap_blocks, Some(250));
+        assert!(!health.report()[1].stale_head);
+        health.set_stale_head(8453, true);
+        assert!(health.report()[1].stale_head);
+        health.set_stopped(8453, true);
+        assert!(health.report()[1].stopped);
+
+        let usage = BTreeMap::from([("eth_blockNumber".to_string(), MethodUsage { requests: 3, ..Default::default() })]);
+        health.record_rpc_usage(8453, usage);
+        let rpc_usage = health.rpc_usage();
+        assert!(rpc_usage[0].methods.is_empty());
+        assert_eq!(rpc_usage[1].methods["eth_blockNumber"].requests, 3);
+    }
+}
@@ -0,0 +1,46 @@
+//! Multi-chain EVM event indexer
+//!
+//! The `rust-listener` binary is a thin wrapper around [`app::run`]. To embed
+//! the listener, open a [`Database`], build a [`ChainPoller`] per network from
+//! [`config::load_networks`] and run it; pass it an [`EventBus`] with
+//! `with_events` to receive every stored transfer and swap update, e.g. through
+//! [`EventBus::spawn_handler`], or add your own [`EventHandler`] sinks with
+//! `with_handler`. The log decoders live in [`fusion`].
+
+pub mod abi;
+pub mod api;
+pub mod app;
+pub mod archive;
+pub mod config;
+pub mod control;
+pub mod db;
+pub mod dedup;
+pub mod dry_run;
+pub mod events;
+pub mod expiry;
+pub mod export;
+#[cfg(test)]
+mod fixtures;
+pub mod fusion;
+pub mod grpc;
+pub mod health;
+pub mod poller;
+pub mod quirks;
+pub mod rate_limit;
+pub mod redis_sink;
+pub mod rpc;
+pub mod s3_upload;
+pub mod supervisor;
+pub mod telemetry;
+pub mod token_stats;
+pub mod tokens;
+pub mod traces;
+pub mod types;
+pub mod verify;
+pub mod watchlist;
+pub mod writer;
+
+pub use db::{Database, DatabaseConfig, DbError};
+pub use events::{EventBus, EventHandler, IndexedEvent};
+pub use poller::{ChainPoller, PollerConfig};
+pub use rpc::{RpcClient, RpcError};
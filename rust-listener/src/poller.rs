@@ -1,22 +1,96 @@
-use crate::db::Database;
+use crate::config::{EnrichRetry, StaleHead, StorageMode, TxStatusCheck};
+use crate::db::{
+    Database, DbError, FusionPlusApplied, FusionPlusCancellation, FusionPlusChange, FusionPlusDst, FusionPlusWithdrawal,
+};
+use crate::dedup::LogDeduplicator;
+use crate::dry_run::DryRun;
+use crate::events::{EventBus, EventHandler, FusionPlusUpdate};
 use crate::fusion::{
-    compute_hashlock_from_secret, decode_crypto2fiat_event, decode_dst_escrow_created,
-    decode_escrow_withdrawal, decode_order_filled, decode_src_escrow_created,
+    compute_dst_escrow_address, compute_hashlock_from_secret, compute_src_escrow_address,
+    decode_crypto2fiat_event, decode_dst_escrow_created, decode_escrow_withdrawal,
+    decode_order_cancelled, decode_order_filled, decode_src_escrow_created, decode_taker_interaction,
+    proxy_bytecode_hash, selector, MerkleFill, ESCROW_DST_IMPLEMENTATION_SIG, ESCROW_SRC_IMPLEMENTATION_SIG,
+    TAKER_INTERACTION_SIG,
 };
-use crate::rpc::RpcClient;
+use crate::health::HealthRegistry;
+use crate::quirks::ChainQuirks;
+use crate::rpc::{LiveEvent, RpcClient, RpcError};
+use crate::tokens::{fetch_token_info, fetch_token_infos, MULTICALL_BATCH_TOKENS};
+use crate::traces::{self, TracedTransfer};
+use crate::watchlist::Watchlist;
+use crate::writer::ChainWriter;
 use crate::types::{
-    FusionPlusSwap, FusionSwap, Log, NetworkConfig, Transfer,
-    ESCROW_FACTORY, SRC_ESCROW_CREATED_TOPIC, DST_ESCROW_CREATED_TOPIC,
+    Approval, AssetTransfer, Delegation, Finality, FusionPlusSwap, InternalTransfer, FusionSwap, Log, NetworkConfig, PollerOverrides, RawEvent, TraceMethod, TransactionInfo, TransactionReceipt, Transfer,
+    SRC_ESCROW_CREATED_TOPIC, DST_ESCROW_CREATED_TOPIC,
     ESCROW_WITHDRAWAL_TOPIC, ESCROW_CANCELLED_TOPIC,
-    AGGREGATION_ROUTER_V6, AGGREGATION_ROUTER_ZKSYNC,
     ORDER_FILLED_TOPIC, ORDER_CANCELLED_TOPIC,
-    CRYPTO2FIAT_TOPIC,
+    CRYPTO2FIAT_TOPIC, TRANSFER_TOPIC, APPROVAL_TOPIC, UNLIMITED_APPROVAL,
+    WETH_DEPOSIT_TOPIC, WETH_WITHDRAWAL_TOPIC, ZERO_ADDRESS, NATIVE_LOG_INDEX_BASE, NATIVE_TOKEN,
 };
-use std::collections::HashMap;
+use alloy_primitives::U256;
+use futures_util::future::try_join_all;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Instant};
+use tracing::{debug, error, info, instrument, warn, Span};
+
+/// Number of recent log keys remembered for live/poll reconciliation
+const DEDUP_CAPACITY: usize = 50_000;
+
+/// Number of processed swap logs remembered to skip them when re-polled
+const PROCESSED_SWAPS_CAPACITY: usize = 10_000;
+
+/// Buffered live logs between the WebSocket task and the poller
+const LIVE_CHANNEL_CAPACITY: usize = 10_000;
+
+/// WebSocket connections silent for this long are treated as dropped
+const WS_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Delay before reconnecting a dropped WebSocket, doubled per consecutive failure
+const WS_RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Also how long a connection must stay up to reset the delay
+const WS_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Tokens whose metadata is fetched concurrently
+const TOKEN_FETCH_CONCURRENCY: usize = 8;
+
+/// Age after which a token cached without some of its metadata is fetched again
+const TOKEN_REFETCH_SECS: u64 = 86_400;
+
+/// Block headers fetched concurrently when building a poll context
+const TIMESTAMP_FETCH_CONCURRENCY: usize = 8;
+
+/// Receipts fetched at once for transaction details (FETCH_TRANSACTIONS)
+const RECEIPT_FETCH_CONCURRENCY: usize = 8;
+
+/// topic0 values subscribed to in WebSocket mode and fetched by the combined
+/// getLogs call (filtered by classify_log)
+const LIVE_TOPICS: [&str; 11] = [
+    SRC_ESCROW_CREATED_TOPIC,
+    DST_ESCROW_CREATED_TOPIC,
+    ESCROW_WITHDRAWAL_TOPIC,
+    ESCROW_CANCELLED_TOPIC,
+    ORDER_FILLED_TOPIC,
+    ORDER_CANCELLED_TOPIC,
+    CRYPTO2FIAT_TOPIC,
+    TRANSFER_TOPIC,
+    APPROVAL_TOPIC,
+    WETH_DEPOSIT_TOPIC,
+    WETH_WITHDRAWAL_TOPIC,
+];
+
+/// Pause between chunks while healing gaps, leaving room for live polling
+const GAP_HEAL_CHUNK_DELAY: Duration = Duration::from_secs(2);
+
+/// Fusion swaps re-checked per enrichment retry
+const ENRICH_BATCH_SIZE: u32 = 100;
+
+/// How often the head block's timestamp is compared with the wall clock
+const HEAD_TIMESTAMP_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Configuration for the chain poller
 pub struct PollerConfig {
@@ -24,12 +98,72 @@ pub struct PollerConfig {
     pub reorg_safety_blocks: u64,
     /// Number of confirmations before processing a block
     pub confirmation_blocks: u64,
+    /// Whether blocks are settled by confirmations or the node's safe/finalized tag
+    pub finality: Finality,
     /// Polling interval in milliseconds
     pub poll_interval_ms: u64,
     /// Maximum blocks to query in a single getLogs call
     pub max_blocks_per_query: u64,
     /// Maximum blocks to backfill on startup
     pub max_backfill_blocks: u64,
+    /// Fetch every event category with one getLogs call per range
+    pub combined_logs: bool,
+    /// Whether transfer values are stored or re-fetched on demand
+    pub storage_mode: StorageMode,
+    /// Polling interval in milliseconds while a live stream is attached (hybrid mode)
+    pub audit_interval_ms: u64,
+    /// Number of processed block hashes kept per chain for reorg detection
+    pub block_hash_history: u64,
+    /// Store every fetched log verbatim in `raw_logs` before decoding
+    pub archive_raw_logs: bool,
+    /// Fetch ERC-20 metadata (symbol, name, decimals) for newly seen tokens
+    pub fetch_token_metadata: bool,
+    /// Fetch sender, receiver and gas of transactions with matched events into `transactions`
+    pub fetch_transactions: bool,
+    /// Trace value moved by contract calls into `internal_transfers` (None = off)
+    pub internal_transfers: Option<TraceMethod>,
+    /// Check the receipt status of transactions with events (None = off)
+    pub tx_status: Option<TxStatusCheck>,
+    /// topic0 values of EIP-7702 delegate events stored in `delegations`
+    pub delegation_topics: Vec<String>,
+    /// Retry of Fusion swaps stored without maker/token details (None = off)
+    pub enrich_retry: Option<EnrichRetry>,
+    /// Seconds between scans for unindexed block ranges to heal (None = off)
+    pub gap_scan_interval_secs: Option<u64>,
+    /// Detection of an endpoint serving a stale head (None = off)
+    pub stale_head: Option<StaleHead>,
+    /// Rows buffered by the writer task before they are inserted
+    pub write_batch_rows: usize,
+    /// Longest time in milliseconds rows wait in the writer task
+    pub write_flush_ms: u64,
+}
+
+impl PollerConfig {
+    /// Apply per-chain overrides on top of this configuration
+    pub fn with_overrides(mut self, overrides: &PollerOverrides) -> Self {
+        if let Some(v) = overrides.poll_interval_ms {
+            self.poll_interval_ms = v;
+        }
+        if let Some(v) = overrides.confirmation_blocks {
+            self.confirmation_blocks = v;
+        }
+        if let Some(v) = overrides.finality {
+            self.finality = v;
+        }
+        if let Some(v) = overrides.max_blocks_per_query {
+            self.max_blocks_per_query = v.max(1);
+        }
+        if let Some(v) = overrides.max_backfill_blocks {
+            self.max_backfill_blocks = v;
+        }
+        if let Some(v) = overrides.reorg_safety_blocks {
+            self.reorg_safety_blocks = v;
+        }
+        if let Some(v) = overrides.combined_logs {
+            self.combined_logs = v;
+        }
+        self
+    }
 }
 
 impl Default for PollerConfig {
@@ -37,10 +171,201 @@ impl Default for PollerConfig {
         Self {
             reorg_safety_blocks: 10,
             confirmation_blocks: 3,
+            finality: Finality::Confirmations,
             poll_interval_ms: 500,   // Reduced from 2000 for real-time sync
             max_blocks_per_query: 500, // Increased from 50 for faster catch-up
             max_backfill_blocks: 500,
+            combined_logs: false,
+            storage_mode: StorageMode::Full,
+            audit_interval_ms: 15_000,
+            block_hash_history: 64,
+            archive_raw_logs: false,
+            fetch_token_metadata: true,
+            fetch_transactions: false,
+            internal_transfers: None,
+            tx_status: None,
+            delegation_topics: Vec::new(),
+            enrich_retry: None,
+            gap_scan_interval_secs: None,
+            stale_head: None,
+            write_batch_rows: 1_000,
+            write_flush_ms: 250,
+        }
+    }
+}
+
+/// Logs for one ingestion batch, split by event category
+#[derive(Default, Clone)]
+struct LogBatch {
+    fusion_plus_factory: Vec<Log>,
+    fusion_plus_escrow: Vec<Log>,
+    fusion: Vec<Log>,
+    crypto2fiat: Vec<Log>,
+    transfers: Vec<Log>,
+    /// Deposit/Withdrawal logs of the chain's wrapped native token
+    wraps: Vec<Log>,
+    approvals: Vec<Log>,
+    /// Logs matched by user-defined watchers, with the watcher label
+    watched: Vec<(String, Log)>,
+    /// Logs matched by a delegation topic; may repeat a log from another category
+    delegations: Vec<Log>,
+    /// Native-currency transfers from alchemy_getAssetTransfers (backfill with enhanced APIs)
+    native_transfers: Vec<AssetTransfer>,
+    /// Value moved by contract calls to or from traced addresses
+    internal_transfers: Vec<TracedTransfer>,
+}
+
+impl LogBatch {
+    fn len(&self) -> usize {
+        self.fusion_plus_factory.len()
+            + self.fusion_plus_escrow.len()
+            + self.fusion.len()
+            + self.crypto2fiat.len()
+            + self.transfers.len()
+            + self.wraps.len()
+            + self.approvals.len()
+            + self.watched.len()
+            + self.delegations.len()
+            + self.native_transfers.len()
+            + self.internal_transfers.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every log in the batch; a log matched by a watcher or delegation topic may appear twice
+    fn logs(&self) -> impl Iterator<Item = &Log> {
+        self.fusion_plus_factory
+            .iter()
+            .chain(&self.fusion_plus_escrow)
+            .chain(&self.fusion)
+            .chain(&self.crypto2fiat)
+            .chain(&self.transfers)
+            .chain(&self.wraps)
+            .chain(&self.approvals)
+            .chain(self.watched.iter().map(|(_, log)| log))
+            .chain(&self.delegations)
+    }
+
+    /// Drop logs already ingested by the other stream, returning how many were dropped
+    ///
+    /// Watcher and delegation logs are only fetched by polling and may repeat
+    /// a log from another category, so they are not deduplicated here.
+    fn retain_new(&mut self, dedup: &LogDeduplicator) -> usize {
+        let mut dropped = 0;
+        for logs in [
+            &mut self.fusion_plus_factory,
+            &mut self.fusion_plus_escrow,
+            &mut self.fusion,
+            &mut self.crypto2fiat,
+            &mut self.transfers,
+            &mut self.wraps,
+            &mut self.approvals,
+        ] {
+            let before = logs.len();
+            logs.retain(|log| !dedup.contains(log));
+            dropped += before - logs.len();
+        }
+        dropped
+    }
+
+    /// Record the logs `retain_new` deduplicates as ingested
+    fn mark_seen(&self, dedup: &mut LogDeduplicator) {
+        for logs in [
+            &self.fusion_plus_factory,
+            &self.fusion_plus_escrow,
+            &self.fusion,
+            &self.crypto2fiat,
+            &self.transfers,
+            &self.wraps,
+            &self.approvals,
+        ] {
+            for log in logs {
+                dedup.insert(log);
+            }
+        }
+    }
+
+    /// Keep only events of transactions for which `keep` holds; returns how many were dropped
+    fn retain_txs(&mut self, keep: impl Fn(&str) -> bool) -> usize {
+        let before = self.len();
+        for logs in [
+            &mut self.fusion_plus_factory,
+            &mut self.fusion_plus_escrow,
+            &mut self.fusion,
+            &mut self.crypto2fiat,
+            &mut self.transfers,
+            &mut self.wraps,
+            &mut self.approvals,
+            &mut self.delegations,
+        ] {
+            logs.retain(|log| keep(&log.transaction_hash));
+        }
+        self.watched.retain(|(_, log)| keep(&log.transaction_hash));
+        self.native_transfers.retain(|t| keep(&t.hash));
+        self.internal_transfers.retain(|t| keep(&t.tx_hash));
+        before - self.len()
+    }
+
+    /// Hashes of every transaction with an event in the batch, by block
+    fn txs_by_block(&self) -> BTreeMap<u64, HashSet<String>> {
+        let mut txs: BTreeMap<u64, HashSet<String>> = BTreeMap::new();
+        let events = self
+            .logs()
+            .map(|log| (log.block_number_u64(), &log.transaction_hash))
+            .chain(self.native_transfers.iter().map(|t| (t.block_number_u64(), &t.hash)))
+            .chain(self.internal_transfers.iter().map(|t| (t.block_number, &t.tx_hash)));
+        for (block_number, tx_hash) in events {
+            txs.entry(block_number).or_default().insert(tx_hash.to_lowercase());
         }
+        txs
+    }
+
+    /// Drop swap logs already processed, returning how many were dropped
+    fn retain_unprocessed_swaps(&mut self, processed: &LogDeduplicator) -> usize {
+        let mut dropped = 0;
+        for logs in [
+            &mut self.fusion_plus_factory,
+            &mut self.fusion_plus_escrow,
+            &mut self.fusion,
+            &mut self.crypto2fiat,
+        ] {
+            let before = logs.len();
+            logs.retain(|log| !processed.contains(log));
+            dropped += before - logs.len();
+        }
+        dropped
+    }
+}
+
+/// One consistent view of the chain shared by every event category of a batch
+struct PollContext {
+    /// Chain head the range was settled against
+    head: u64,
+    from_block: u64,
+    to_block: u64,
+    /// Timestamps of every block holding a log or value transfer in the batch
+    timestamps: HashMap<u64, u64>,
+    /// Whether each transaction with an event succeeded, by lowercase hash (TX_STATUS)
+    tx_status: HashMap<String, bool>,
+}
+
+impl PollContext {
+    /// Receipt status of a transaction as stored, if it was checked
+    fn tx_status(&self, tx_hash: &str) -> Option<u8> {
+        self.tx_status.get(&tx_hash.to_lowercase()).map(|&succeeded| u8::from(succeeded))
+    }
+
+    fn tx_failed(&self, tx_hash: &str) -> bool {
+        self.tx_status(tx_hash) == Some(0)
+    }
+
+    fn timestamp(&self, block_number: u64) -> Result<u64, String> {
+        self.timestamps
+            .get(&block_number)
+            .copied()
+            .ok_or_else(|| format!("No timestamp for block {}", block_number))
     }
 }
 
@@ -51,6 +376,80 @@ pub struct ChainPoller {
     db: Arc<Database>,  // Shared PostgreSQL database
     config: PollerConfig,
     block_timestamp_cache: HashMap<u64, u64>,
+    /// Events pushed by a live subscription (hybrid mode)
+    live_logs: Option<mpsc::Receiver<LiveEvent>>,
+    /// Live logs waiting for their block to settle
+    live_pending: Vec<Log>,
+    /// Highest head announced by the live subscription
+    live_head: u64,
+    /// Reconciles live and polled logs; only set in hybrid mode
+    dedup: Option<LogDeduplicator>,
+    /// Swap logs processed without error; when a range is polled again their
+    /// inserts would be ignored but their enrichment redone
+    processed_swaps: LogDeduplicator,
+    /// When set, only transfers touching a watched address are stored
+    watchlist: Option<Arc<Watchlist>>,
+    /// Current getLogs range; shrinks when the provider rejects a range and
+    /// grows back towards `max_blocks_per_query` on success
+    logs_range: u64,
+    /// Sinks notified of fetched logs and stored events
+    handlers: Vec<Arc<dyn EventHandler>>,
+    health: Option<Arc<HealthRegistry>>,
+    /// Proxy bytecode hashes of the factory's src/dst escrows, fetched on first use
+    escrow_bytecode_hashes: Option<EscrowBytecodeHashes>,
+    /// Whether SrcEscrowCreated txs are traced for the fill's Merkle proof;
+    /// off once the node rejected debug_traceTransaction
+    trace_merkle_fills: bool,
+    /// Tokens known to have cached metadata in the `tokens` table
+    known_tokens: HashSet<String>,
+    /// Inserts decoded rows and checkpoints off the polling path
+    writer: ChainWriter,
+    quirks: ChainQuirks,
+    /// Unindexed block ranges found by the last gap scan, oldest first
+    gaps: VecDeque<(u64, u64)>,
+    /// Progress of the head served by the RPC endpoint
+    head_watch: Option<HeadWatch>,
+    /// When set, rows are recorded here instead of written to the database
+    dry_run: Option<Arc<DryRun>>,
+    /// Whether backfills fetch native transfers with alchemy_getAssetTransfers;
+    /// cleared once the endpoint turns out not to serve the method. Live polls
+    /// don't fetch them, so only backfilled ranges hold native transfers
+    asset_transfers: bool,
+    /// Addresses whose internal transfers are traced (with `internal_transfers` set)
+    trace_watchlist: Option<Arc<Watchlist>>,
+}
+
+/// Highest head seen and when, for stale head detection
+struct HeadWatch {
+    block: u64,
+    since: Instant,
+    /// Last comparison of the head block's timestamp with the wall clock
+    checked_at: Option<Instant>,
+    stale: bool,
+}
+
+/// Start the writer task for a chain's poller
+fn spawn_writer(
+    network: &NetworkConfig,
+    db: &Arc<Database>,
+    config: &PollerConfig,
+    handlers: Vec<Arc<dyn EventHandler>>,
+) -> ChainWriter {
+    ChainWriter::spawn(
+        network.chain_id,
+        network.name.clone(),
+        Arc::clone(db),
+        handlers,
+        config.write_batch_rows,
+        config.write_flush_ms,
+    )
+}
+
+/// CREATE2 bytecode hashes for escrows deployed by the chain's escrow factory
+#[derive(Debug, Clone, Copy)]
+struct EscrowBytecodeHashes {
+    src: [u8; 32],
+    dst: [u8; 32],
 }
 
 impl ChainPoller {
@@ -63,7 +462,11 @@ impl ChainPoller {
         db: Arc<Database>,
         config: PollerConfig,
     ) -> Self {
-        let rpc = RpcClient::new(&network.rpc_url, network.name);
+        let rpc = RpcClient::for_network(&network);
+        let logs_range = config.max_blocks_per_query;
+        let writer = spawn_writer(&network, &db, &config, Vec::new());
+        let quirks = ChainQuirks::for_chain(network.chain_id);
+        let asset_transfers = network.enhanced_apis;
 
         Self {
             network,
@@ -71,16 +474,146 @@ impl ChainPoller {
             db,
             config,
             block_timestamp_cache: HashMap::new(),
+            live_logs: None,
+            live_pending: Vec::new(),
+            live_head: 0,
+            dedup: None,
+            processed_swaps: LogDeduplicator::new(PROCESSED_SWAPS_CAPACITY),
+            watchlist: None,
+            logs_range,
+            handlers: Vec::new(),
+            health: None,
+            escrow_bytecode_hashes: None,
+            trace_merkle_fills: true,
+            known_tokens: HashSet::new(),
+            writer,
+            quirks,
+            gaps: VecDeque::new(),
+            head_watch: None,
+            dry_run: None,
+            asset_transfers,
+            trace_watchlist: None,
+        }
+    }
+
+    /// Enable hybrid ingestion: logs received on `live_logs` are processed
+    /// once a later head settles their block, while polling slows to
+    /// `audit_interval_ms` and only fills in logs the live stream missed.
+    /// Falls back to normal polling if the stream closes.
+    pub fn with_live_logs(mut self, live_logs: mpsc::Receiver<LiveEvent>) -> Self {
+        self.live_logs = Some(live_logs);
+        self.dedup = Some(LogDeduplicator::new(DEDUP_CAPACITY));
+        self
+    }
+
+    /// Publish stored transfers and swap updates to `events`
+    pub fn with_events(self, events: Arc<EventBus>) -> Self {
+        self.with_handler(events)
+    }
+
+    /// Add a sink called with every fetched log and stored event, after
+    /// those registered before it
+    pub fn with_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        // Transfers reach handlers from the writer once stored
+        if self.dry_run.is_none() {
+            self.writer = spawn_writer(&self.network, &self.db, &self.config, self.handlers.clone());
         }
+        self
+    }
+
+    /// Report chain head and checkpoint progress to `health` after each poll
+    pub fn with_health(mut self, health: Arc<HealthRegistry>) -> Self {
+        health.register(self.network.chain_id, &self.network.name);
+        self.health = Some(health);
+        self
+    }
+
+    /// Store only transfers whose sender or recipient is on the watchlist
+    ///
+    /// Swap events are still indexed, but their maker/taker enrichment only
+    /// sees transfers that passed the filter.
+    pub fn with_watchlist(mut self, watchlist: Arc<Watchlist>) -> Self {
+        self.watchlist = Some(watchlist);
+        self
+    }
+
+    /// Trace value moved by contract calls to or from `watchlist` addresses,
+    /// using `PollerConfig::internal_transfers`
+    ///
+    /// Independent of `with_watchlist`: transfers are still stored for every
+    /// address unless that is set too.
+    pub fn with_trace_watchlist(mut self, watchlist: Arc<Watchlist>) -> Self {
+        self.trace_watchlist = Some(watchlist);
+        self
+    }
+
+    /// Fetch and decode as usual but record every row to `dry_run` instead
+    /// of writing it
+    ///
+    /// Meant for `backfill`: the database is still read (token cache, swaps
+    /// looked up for enrichment) but nothing is written, including backfill
+    /// progress, so a dry run always covers its whole range.
+    pub fn with_dry_run(mut self, dry_run: Arc<DryRun>) -> Self {
+        self.writer = ChainWriter::dry_run(Arc::clone(&dry_run));
+        self.dry_run = Some(dry_run);
+        self
+    }
+
+    /// Enable hybrid ingestion fed by an eth_subscribe WebSocket connection
+    ///
+    /// A dropped connection is re-established with exponential backoff; the
+    /// audit poll covers whatever was missed in between. The subscription
+    /// task stops once the poller is dropped.
+    pub fn with_ws_subscription(self, ws_url: String) -> Self {
+        let (tx, rx) = mpsc::channel(LIVE_CHANNEL_CAPACITY);
+        let rpc = RpcClient::for_network(&self.network);
+        let chain_name = self.network.name.clone();
+
+        tokio::spawn(async move {
+            let mut delay = WS_RECONNECT_BASE_DELAY;
+            loop {
+                let connected = Instant::now();
+                match rpc.subscribe_logs(&ws_url, &LIVE_TOPICS, tx.clone(), WS_IDLE_TIMEOUT).await {
+                    Ok(()) => break,
+                    Err(e) => warn!("[{}] WebSocket subscription dropped: {}", chain_name, e),
+                }
+                if tx.is_closed() {
+                    break;
+                }
+                if connected.elapsed() >= WS_RECONNECT_MAX_DELAY {
+                    delay = WS_RECONNECT_BASE_DELAY;
+                }
+                info!("[{}] Reconnecting WebSocket in {}s", chain_name, delay.as_secs());
+                sleep(delay).await;
+                delay = (delay * 2).min(WS_RECONNECT_MAX_DELAY);
+            }
+            debug!("[{}] WebSocket subscription stopped", chain_name);
+        });
+
+        self.with_live_logs(rx)
     }
 
     /// Run the poller loop
     pub async fn run(&mut self) {
+        let finality = match self.config.finality.block_tag() {
+            Some(tag) => format!("{} block", tag),
+            None => format!("{} confirmations", self.config.confirmation_blocks),
+        };
         info!(
-            "[{}] Starting poller (chain_id: {})",
-            self.network.name, self.network.chain_id
+            "[{}] Starting poller (chain_id: {}, provider: {}, interval: {}ms, finality: {}, max blocks/query: {})",
+            self.network.name,
+            self.network.chain_id,
+            self.rpc.providers().join(" -> "),
+            self.config.poll_interval_ms,
+            finality,
+            self.config.max_blocks_per_query
         );
 
+        if !self.verify_chain_id().await {
+            return;
+        }
+
         // Get starting block
         let mut last_processed_block = match self.initialize_checkpoint().await {
             Ok(block) => block,
@@ -94,28 +627,276 @@ impl ChainPoller {
             "[{}] Starting from block {}",
             self.network.name, last_processed_block
         );
+        self.check_contracts(last_processed_block).await;
+
+        // Owned locally so receiving doesn't hold a borrow of self
+        let mut live_logs = self.live_logs.take();
+        if live_logs.is_some() {
+            info!(
+                "[{}] Hybrid mode: live stream + audit poll every {}ms",
+                self.network.name, self.config.audit_interval_ms
+            );
+        }
+
+        let poll_timer = sleep(Duration::ZERO);
+        tokio::pin!(poll_timer);
+
+        let enrich_retry = self.config.enrich_retry;
+        let enrich_every = Duration::from_secs(enrich_retry.map_or(0, |r| r.interval_secs));
+        let enrich_timer = sleep(enrich_every);
+        tokio::pin!(enrich_timer);
+
+        let gap_scan_every = self.config.gap_scan_interval_secs.map(Duration::from_secs);
+        let gap_timer = sleep(gap_scan_every.unwrap_or_default());
+        tokio::pin!(gap_timer);
 
         // Main polling loop
         loop {
-            match self.poll_once(&mut last_processed_block).await {
-                Ok(events_processed) => {
-                    if events_processed > 0 {
-                        debug!(
-                            "[{}] Processed {} events, checkpoint: {}",
-                            self.network.name, events_processed, last_processed_block
-                        );
+            tokio::select! {
+                live = recv_live(&mut live_logs) => {
+                    match live {
+                        Some(first) => {
+                            let mut events = vec![first];
+                            if let Some(rx) = live_logs.as_mut() {
+                                while let Ok(event) = rx.try_recv() {
+                                    events.push(event);
+                                }
+                            }
+                            if let Err(e) = self.handle_live_events(events, &mut last_processed_block).await {
+                                error!("[{}] Live ingestion error: {}", self.network.name, e);
+                            }
+                        }
+                        None => {
+                            warn!(
+                                "[{}] Live stream closed, falling back to polling every {}ms",
+                                self.network.name, self.config.poll_interval_ms
+                            );
+                            live_logs = None;
+                        }
                     }
                 }
-                Err(e) => {
-                    error!("[{}] Poll error: {}", self.network.name, e);
-                    // Continue polling after error, don't crash
+                () = &mut poll_timer => {
+                    match self.poll_once(&mut last_processed_block).await {
+                        Ok(events_processed) => {
+                            if events_processed > 0 {
+                                debug!(
+                                    "[{}] Processed {} events, checkpoint: {}",
+                                    self.network.name, events_processed, last_processed_block
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            error!("[{}] Poll error: {}", self.network.name, e);
+                            // Continue polling after error, don't crash
+                        }
+                    }
+
+                    // Clean up old cached timestamps
+                    self.cleanup_timestamp_cache(last_processed_block);
+
+                    let interval_ms = if live_logs.is_some() {
+                        self.config.audit_interval_ms
+                    } else {
+                        self.config.poll_interval_ms
+                    };
+                    let mut interval = Duration::from_millis(interval_ms);
+
+                    // Wait out an open circuit rather than failing every poll
+                    let circuit = self.rpc.circuit_state();
+                    if let Some(secs) = circuit.retry_in_secs {
+                        interval = interval.max(Duration::from_secs(secs));
+                    }
+                    if let Some(health) = &self.health {
+                        health.record_circuit(self.network.chain_id, circuit);
+                        health.record_rpc_usage(self.network.chain_id, self.rpc.usage());
+                    }
+                    poll_timer.as_mut().reset(Instant::now() + interval);
+                }
+                () = &mut enrich_timer, if enrich_retry.is_some() => {
+                    if let Some(retry) = &enrich_retry {
+                        match self.retry_swap_enrichment(retry).await {
+                            Ok(enriched) if enriched > 0 => {
+                                info!("[{}] Enriched {} deferred Fusion swaps", self.network.name, enriched);
+                            }
+                            Ok(_) => {}
+                            Err(e) => error!("[{}] Enrichment retry error: {}", self.network.name, e),
+                        }
+                    }
+                    enrich_timer.as_mut().reset(Instant::now() + enrich_every);
+                }
+                () = &mut gap_timer, if gap_scan_every.is_some() => {
+                    let mut next = gap_scan_every.unwrap_or_default();
+                    match self.heal_gaps().await {
+                        Ok(true) => next = GAP_HEAL_CHUNK_DELAY,
+                        Ok(false) => {}
+                        Err(e) => error!("[{}] Gap healing error: {}", self.network.name, e),
+                    }
+                    gap_timer.as_mut().reset(Instant::now() + next);
+                }
+            }
+        }
+    }
+
+    /// Apply events received from the live stream
+    ///
+    /// Logs are held until a head settles their block under the configured
+    /// finality, so live ingestion stores nothing polling wouldn't. A removed
+    /// log that is still held is dropped; one already stored means the reorg
+    /// reached settled blocks, so data from its block on is rolled back.
+    async fn handle_live_events(
+        &mut self,
+        events: Vec<LiveEvent>,
+        last_processed_block: &mut u64,
+    ) -> Result<usize, String> {
+        for event in events {
+            match event {
+                LiveEvent::Head(block_number) => self.live_head = self.live_head.max(block_number),
+                LiveEvent::Log(log) => {
+                    if !self.live_pending.iter().any(|held| same_log(held, &log)) {
+                        self.live_pending.push(log);
+                    }
+                }
+                LiveEvent::Removed(log) => {
+                    if remove_pending(&mut self.live_pending, &log) {
+                        continue;
+                    }
+                    if self.dedup.as_ref().is_some_and(|dedup| dedup.contains(&log)) {
+                        let fork_block = log.block_number_u64().saturating_sub(1);
+                        warn!(
+                            "[{}] Stored log {}:{} in block {} was reorged out",
+                            self.network.name,
+                            log.transaction_hash,
+                            log.log_index_u32(),
+                            log.block_number_u64()
+                        );
+                        self.rollback_to(fork_block).await?;
+                        *last_processed_block = (*last_processed_block).min(fork_block);
+                    }
                 }
             }
+        }
+
+        if self.live_pending.is_empty() || self.live_head == 0 {
+            return Ok(0);
+        }
+        let settled = self.settled_head(self.live_head).await?;
+        let ready = take_settled(&mut self.live_pending, settled);
+        if ready.is_empty() {
+            return Ok(0);
+        }
+        self.ingest_live_logs(ready).await
+    }
+
+    /// Process settled logs received from the live stream
+    ///
+    /// The checkpoint is left to the audit poll so anything the stream misses
+    /// is still picked up.
+    async fn ingest_live_logs(&mut self, logs: Vec<Log>) -> Result<usize, String> {
+        let mut batch = LogBatch::default();
+        for log in logs {
+            self.classify_log(log, &mut batch);
+        }
+
+        if let Some(dedup) = self.dedup.as_ref() {
+            batch.retain_new(dedup);
+        }
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let from_block = batch.logs().map(|log| log.block_number_u64()).min().unwrap_or(0);
+        let to_block = batch.logs().map(|log| log.block_number_u64()).max().unwrap_or(0);
+        let ctx = self.poll_context(to_block, (from_block, to_block), &batch).await?;
+        let processed = self.process_batch(&batch, &ctx).await?;
+        self.mark_ingested(&batch).await?;
+        Ok(processed)
+    }
+
+    /// Record a processed batch's logs as ingested once its rows are stored
+    ///
+    /// Marking them earlier would make the other stream skip logs whose
+    /// batch then failed, losing them for good.
+    async fn mark_ingested(&mut self, batch: &LogBatch) -> Result<(), String> {
+        if self.dedup.is_none() {
+            return Ok(());
+        }
+        self.writer.flush().await?;
+        if let Some(dedup) = self.dedup.as_mut() {
+            batch.mark_seen(dedup);
+        }
+        Ok(())
+    }
+
+    /// Sort a log into its event category by topic0 and emitting address
+    fn classify_log(&self, log: Log, batch: &mut LogBatch) {
+        for watcher in self.network.watchers.iter().filter(|w| w.matches(&log)) {
+            batch.watched.push((watcher.label.clone(), log.clone()));
+        }
+
+        let Some(topic0) = log.topics.first().map(|t| t.to_lowercase()) else {
+            return;
+        };
+        let address = log.address.to_lowercase();
+
+        if self.config.delegation_topics.contains(&topic0) {
+            batch.delegations.push(log.clone());
+        }
+
+        if topic0 == SRC_ESCROW_CREATED_TOPIC || topic0 == DST_ESCROW_CREATED_TOPIC {
+            if address == self.network.contracts.escrow_factory {
+                batch.fusion_plus_factory.push(log);
+            }
+        } else if topic0 == ESCROW_WITHDRAWAL_TOPIC || topic0 == ESCROW_CANCELLED_TOPIC {
+            batch.fusion_plus_escrow.push(log);
+        } else if topic0 == ORDER_FILLED_TOPIC || topic0 == ORDER_CANCELLED_TOPIC {
+            if address == self.network.contracts.aggregation_router {
+                batch.fusion.push(log);
+            }
+        } else if topic0 == CRYPTO2FIAT_TOPIC {
+            batch.crypto2fiat.push(log);
+        } else if topic0 == TRANSFER_TOPIC {
+            batch.transfers.push(log);
+        } else if topic0 == APPROVAL_TOPIC {
+            batch.approvals.push(log);
+        } else if (topic0 == WETH_DEPOSIT_TOPIC || topic0 == WETH_WITHDRAWAL_TOPIC)
+            && self.network.wrapped_native.as_deref() == Some(address.as_str())
+        {
+            batch.wraps.push(log);
+        }
+    }
 
-            // Clean up old cached timestamps
-            self.cleanup_timestamp_cache(last_processed_block);
+    /// Warn about configured 1inch contracts without code, whose events would
+    /// otherwise silently never be indexed
+    async fn check_contracts(&self, block_number: u64) {
+        let contracts = [
+            ("escrow_factory", &self.network.contracts.escrow_factory),
+            ("aggregation_router", &self.network.contracts.aggregation_router),
+        ];
+        for (field, address) in contracts {
+            match self.rpc.get_code(address, block_number).await {
+                Ok(code) if code.trim_start_matches("0x").is_empty() => warn!(
+                    "[{}] No contract at {} {} (block {}); its events won't be indexed. Set {} in the networks config if 1inch uses another address on this chain",
+                    self.network.name, field, address, block_number, field
+                ),
+                Ok(_) => {}
+                Err(e) => debug!("[{}] Failed to check {} {}: {}", self.network.name, field, address, e),
+            }
+        }
+    }
 
-            sleep(Duration::from_millis(self.config.poll_interval_ms)).await;
+    /// Check that the RPC endpoints serve the configured chain
+    ///
+    /// Endpoints serving another chain are dropped from rotation, and ones
+    /// that can't answer yet are checked before they serve requests. When none is
+    /// left the poller stops; the supervisor keeps retrying (and logging)
+    /// until the configuration is fixed.
+    async fn verify_chain_id(&mut self) -> bool {
+        match self.rpc.verify_chain_id(self.network.chain_id as u64).await {
+            Ok(()) => true,
+            Err(e) => {
+                error!("[{}] {}, refusing to poll", self.network.name, e);
+                false
+            }
         }
     }
 
@@ -178,7 +959,246 @@ impl ChainPoller {
         Ok(start_block)
     }
 
+    /// Index a historical block range into the same tables as live polling
+    ///
+    /// Progress is stored per (chain, from, to) so re-running the same range
+    /// resumes after the last completed chunk. The live checkpoint is not touched.
+    pub async fn backfill(&mut self, from_block: u64, to_block: u64) -> Result<usize, String> {
+        let chain_id = self.network.chain_id;
+        let mut next_block = match self.dry_run {
+            Some(_) => from_block,
+            None => self
+                .db
+                .get_backfill_progress(chain_id, from_block, to_block)
+                .await
+                .map_err(|e| format!("DB error: {}", e))?
+                .unwrap_or(from_block),
+        };
+
+        if next_block > to_block {
+            info!(
+                "[{}] Backfill {}-{} already complete",
+                self.network.name, from_block, to_block
+            );
+            return Ok(0);
+        }
+        if next_block > from_block {
+            info!(
+                "[{}] Resuming backfill {}-{} at block {}",
+                self.network.name, from_block, to_block, next_block
+            );
+        }
+
+        let total_blocks = to_block - from_block + 1;
+        let started = Instant::now();
+        let resumed_at = next_block;
+        let mut events = 0;
+
+        while next_block <= to_block {
+            let (mut batch, chunk_end) = self.fetch_batch_adaptive(next_block, to_block).await?;
+            batch.native_transfers = self.fetch_native_transfers(next_block, chunk_end).await?;
+            let ctx = self.poll_context(to_block, (next_block, chunk_end), &batch).await?;
+            events += self.process_batch(&batch, &ctx).await?;
+            self.cleanup_timestamp_cache(chunk_end);
+
+            self.writer.processed_range(next_block, chunk_end).await?;
+            next_block = chunk_end + 1;
+            self.writer.backfill_progress(from_block, to_block, next_block).await?;
+
+            let done = next_block - from_block;
+            let elapsed = started.elapsed().as_secs_f64().max(0.001);
+            let rate = (next_block - resumed_at) as f64 / elapsed;
+            let eta_secs = if rate > 0.0 { (to_block + 1 - next_block) as f64 / rate } else { 0.0 };
+            info!(
+                "[{}] Backfill {:.1}% ({}/{} blocks, {} events, {:.0} blocks/s, ETA {:.0}s)",
+                self.network.name,
+                done as f64 * 100.0 / total_blocks as f64,
+                done,
+                total_blocks,
+                events,
+                rate,
+                eta_secs
+            );
+        }
+
+        self.writer.flush().await?;
+        Ok(events)
+    }
+
+    /// Re-decode archived logs for a block range without re-fetching them
+    ///
+    /// Reads `raw_logs` (written with RAW_LOG_ARCHIVE) in chunks of
+    /// `max_blocks_per_query`, deletes the rows previously decoded from the
+    /// blocks that have archived logs and runs those logs through the normal
+    /// processing again. Blocks with nothing archived are left untouched,
+    /// since an empty archive can't be told apart from one that was never
+    /// written (archiving enabled later, or its rows expired).
+    pub async fn replay_archived(&mut self, from_block: u64, to_block: u64) -> Result<usize, String> {
+        let mut events = 0;
+        let mut next_block = from_block;
+
+        while next_block <= to_block {
+            let chunk_end = (next_block + self.config.max_blocks_per_query - 1).min(to_block);
+            let archived = self
+                .db
+                .get_raw_logs(self.network.chain_id, next_block, chunk_end)
+                .await
+                .map_err(|e| format!("DB error: {}", e))?;
+
+            for (first, last) in block_runs(archived.iter().map(|(log, _)| log.block_number_u64())) {
+                let stats = self
+                    .db
+                    .clear_decoded_range(self.network.chain_id, first, last)
+                    .await
+                    .map_err(|e| format!("DB error: {}", e))?;
+                debug!("[{}] Cleared blocks {}-{} for replay: {:?}", self.network.name, first, last, stats);
+            }
+
+            let mut batch = LogBatch::default();
+            for (log, timestamp) in archived {
+                self.block_timestamp_cache.insert(log.block_number_u64(), timestamp);
+                self.classify_log(log, &mut batch);
+            }
+
+            if !batch.is_empty() {
+                let ctx = self.poll_context(to_block, (next_block, chunk_end), &batch).await?;
+                events += self.process_batch(&batch, &ctx).await?;
+                info!(
+                    "[{}] Replayed {} archived logs in blocks {}-{}",
+                    self.network.name,
+                    batch.len(),
+                    next_block,
+                    chunk_end
+                );
+            }
+
+            self.cleanup_timestamp_cache(chunk_end);
+            next_block = chunk_end + 1;
+        }
+
+        self.writer.flush().await?;
+        Ok(events)
+    }
+
+    /// Highest block considered settled under the configured finality
+    ///
+    /// Falls back to counting confirmations for good if the node doesn't
+    /// support the safe/finalized tag.
+    async fn settled_head(&mut self, current_block: u64) -> Result<u64, String> {
+        let confirmed = current_block.saturating_sub(self.config.confirmation_blocks);
+        let Some(tag) = self.config.finality.block_tag() else {
+            return Ok(confirmed);
+        };
+
+        match self.rpc.get_block_ref(tag).await {
+            Ok(block) => Ok(block.number.min(current_block)),
+            Err(e @ (RpcError::Rpc(_) | RpcError::JsonRpc { .. } | RpcError::Parse(_))) => {
+                warn!(
+                    "[{}] No {} block from the node ({}), using {} confirmations instead",
+                    self.network.name, tag, e, self.config.confirmation_blocks
+                );
+                self.config.finality = Finality::Confirmations;
+                Ok(confirmed)
+            }
+            Err(e) => Err(format!("Failed to get {} block: {}", tag, e)),
+        }
+    }
+
+    /// Detect an RPC endpoint serving a stale head
+    ///
+    /// The head is stale when it hasn't advanced for `max_age_secs`, or its
+    /// block timestamp is that far behind the wall clock (checked once a
+    /// minute). A stale head is logged, reported to the health registry and,
+    /// if configured, failed over from.
+    async fn check_head(&mut self, head: u64) {
+        let Some(config) = self.config.stale_head else {
+            return;
+        };
+        let now = Instant::now();
+        let watch = self.head_watch.get_or_insert(HeadWatch {
+            block: head,
+            since: now,
+            checked_at: None,
+            stale: false,
+        });
+        if head > watch.block {
+            watch.block = head;
+            watch.since = now;
+        }
+
+        let stuck_secs = watch.since.elapsed().as_secs();
+        let mut reason = (stuck_secs >= config.max_age_secs)
+            .then(|| format!("head stuck at block {} for {}s", head, stuck_secs));
+        let check_timestamp = watch
+            .checked_at
+            .is_none_or(|at| at.elapsed() >= HEAD_TIMESTAMP_CHECK_INTERVAL);
+        let was_stale = watch.stale;
+
+        if reason.is_none() && check_timestamp {
+            self.head_watch.as_mut().unwrap().checked_at = Some(now);
+            match self.get_block_timestamp(head).await {
+                Ok(timestamp) => {
+                    let wall_clock = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                    let behind = wall_clock as i64 - timestamp as i64;
+                    if behind >= config.max_age_secs as i64 {
+                        reason = Some(format!("head block {} is {}s behind the wall clock", head, behind));
+                    } else if -behind >= config.max_age_secs as i64 {
+                        // The node is fine; this host's clock is off
+                        warn!(
+                            "[{}] Head block {} is {}s ahead of the local clock, check this host's time sync",
+                            self.network.name, head, -behind
+                        );
+                    }
+                }
+                Err(e) => debug!("[{}] Failed to check head timestamp: {}", self.network.name, e),
+            }
+        } else if reason.is_none() && was_stale {
+            // Keep reporting stale until the next timestamp check passes
+            return;
+        }
+
+        match reason {
+            Some(reason) => {
+                let provider = self.rpc.active_provider();
+                if !was_stale {
+                    warn!("[{}] Stale head from {}: {}", self.network.name, provider, reason);
+                    if let Some(health) = &self.health {
+                        health.set_stale_head(self.network.chain_id, true);
+                    }
+                }
+                // Once per stale period, so stale fallbacks aren't cycled through every poll
+                if !was_stale
+                    && config.failover
+                    && self.rpc.fail_over(&format!("serves a stale head ({})", reason))
+                {
+                    // Judge the new endpoint from scratch, checking its head timestamp next poll
+                    self.head_watch = Some(HeadWatch {
+                        block: 0,
+                        since: now,
+                        checked_at: None,
+                        stale: true,
+                    });
+                    return;
+                }
+                if let Some(watch) = self.head_watch.as_mut() {
+                    watch.stale = true;
+                }
+            }
+            None if was_stale => {
+                info!("[{}] Head from {} is advancing again", self.network.name, self.rpc.active_provider());
+                if let Some(health) = &self.health {
+                    health.set_stale_head(self.network.chain_id, false);
+                }
+                if let Some(watch) = self.head_watch.as_mut() {
+                    watch.stale = false;
+                }
+            }
+            None => {}
+        }
+    }
+
     /// Poll for new events once
+    #[instrument(name = "poll_cycle", skip_all, fields(chain = %self.network.name, from_block, to_block))]
     async fn poll_once(&mut self, last_processed_block: &mut u64) -> Result<usize, String> {
         // Get current block
         let current_block = self
@@ -186,131 +1206,705 @@ impl ChainPoller {
             .get_block_number()
             .await
             .map_err(|e| format!("Failed to get block number: {}", e))?;
+        self.check_head(current_block).await;
+
+        // Rewind past any reorged blocks before scanning forward
+        if let Some(fork_block) = self.find_fork_point().await? {
+            self.rollback_to(fork_block).await?;
+            *last_processed_block = fork_block;
+        }
 
         // Calculate safe block range
-        let to_block = current_block.saturating_sub(self.config.confirmation_blocks);
+        let to_block = self.settled_head(current_block).await?;
         let from_block = (*last_processed_block + 1).max(
             last_processed_block
                 .saturating_sub(self.config.reorg_safety_blocks)
                 + 1,
         );
+        Span::current().record("from_block", from_block).record("to_block", to_block);
 
         // Skip if no new blocks
         if from_block > to_block {
+            if let Some(health) = &self.health {
+                health.record_poll(self.network.chain_id, current_block, None);
+            }
             return Ok(0);
         }
 
-        // Limit query size
-        let actual_to_block = (from_block + self.config.max_blocks_per_query - 1).min(to_block);
-
         debug!(
             "[{}] Polling blocks {} to {} (current: {})",
-            self.network.name, from_block, actual_to_block, current_block
+            self.network.name, from_block, to_block, current_block
         );
 
-        // =========================================================================
-        // PHASE 1: Fetch fusion/crypto2fiat logs and build swap_type map
-        // =========================================================================
-        let mut swap_type_map: HashMap<String, &'static str> = HashMap::new();
-
-        // Fetch Fusion+ logs (factory + escrow events)
-        let (fusion_plus_factory_logs, fusion_plus_escrow_logs) =
-            self.fetch_fusion_plus_logs(from_block, actual_to_block).await?;
-
-        for log in &fusion_plus_factory_logs {
-            swap_type_map.insert(log.transaction_hash.to_lowercase(), "fusion_plus");
-        }
-        for log in &fusion_plus_escrow_logs {
-            swap_type_map.insert(log.transaction_hash.to_lowercase(), "fusion_plus");
-        }
-
-        // Fetch Fusion (single-chain) logs
-        let fusion_logs = self.fetch_fusion_logs(from_block, actual_to_block).await?;
-        for log in &fusion_logs {
-            swap_type_map.insert(log.transaction_hash.to_lowercase(), "fusion");
-        }
-
-        // Fetch Crypto2Fiat logs
-        let crypto2fiat_logs = self.fetch_crypto2fiat_logs(from_block, actual_to_block).await?;
-        for log in &crypto2fiat_logs {
-            swap_type_map.insert(log.transaction_hash.to_lowercase(), "crypto_to_fiat");
-        }
-
-        // =========================================================================
-        // PHASE 2: Fetch transfers and insert with swap_type from map
-        // =========================================================================
-        let transfer_logs = self
-            .rpc
-            .get_transfer_logs(from_block, actual_to_block)
-            .await
-            .map_err(|e| format!("Failed to get logs: {}", e))?;
+        // Query size is limited by the adaptive getLogs range
+        let (mut batch, actual_to_block) = self.fetch_batch_adaptive(from_block, to_block).await?;
 
-        if !transfer_logs.is_empty() {
+        if !batch.transfers.is_empty() {
             info!(
                 "[{}] Found {} Transfer events in blocks {}-{}",
                 self.network.name,
-                transfer_logs.len(),
+                batch.transfers.len(),
                 from_block,
                 actual_to_block
             );
         }
 
-        // Process logs into transfers with swap_type
-        let mut transfers = Vec::with_capacity(transfer_logs.len());
-
-        for log in &transfer_logs {
-            // Validate Transfer event structure
-            if log.topics.len() < 3 {
-                continue; // Invalid Transfer event
+        // In hybrid mode the live stream has usually processed these already
+        if let Some(dedup) = self.dedup.as_ref() {
+            let total = batch.len();
+            let dropped = batch.retain_new(dedup);
+            if dropped < total {
+                info!(
+                    "[{}] Audit recovered {} logs missed by live stream in blocks {}-{}",
+                    self.network.name,
+                    total - dropped,
+                    from_block,
+                    actual_to_block
+                );
+            }
+        }
+
+        let skipped = batch.retain_unprocessed_swaps(&self.processed_swaps);
+        if skipped > 0 {
+            debug!("[{}] Skipped {} swap logs already processed", self.network.name, skipped);
+        }
+
+        let ctx = self.poll_context(current_block, (from_block, actual_to_block), &batch).await?;
+        let processed = self.process_batch(&batch, &ctx).await?;
+
+        self.record_block_hash(actual_to_block).await?;
+
+        // Stored by the writer after the rows queued above
+        *last_processed_block = actual_to_block;
+        self.writer.processed_range(from_block, actual_to_block).await?;
+        self.writer.checkpoint(actual_to_block).await?;
+        self.mark_ingested(&batch).await?;
+
+        if let Some(health) = self.health.clone() {
+            let timestamp = self.get_block_timestamp(actual_to_block).await?;
+            health.record_poll(self.network.chain_id, ctx.head, Some((actual_to_block, timestamp)));
+        }
+
+        Ok(processed)
+    }
+
+    // =========================================================================
+    // Gap Healing
+    // =========================================================================
+
+    /// Index one chunk of the oldest unindexed block range
+    ///
+    /// Gaps are holes between the ranges in `processed_ranges`, left by a
+    /// checkpoint skip past MAX_BACKFILL_BLOCKS or by a crash. When none are
+    /// queued the table is scanned again. Returns whether gaps remain.
+    async fn heal_gaps(&mut self) -> Result<bool, String> {
+        let chain_id = self.network.chain_id;
+
+        if self.gaps.is_empty() {
+            // Ranges still buffered in the writer would look like gaps
+            self.writer.flush().await?;
+            let gaps = self
+                .db
+                .find_block_gaps(chain_id)
+                .await
+                .map_err(|e| format!("DB error: {}", e))?;
+            self.gaps = gaps.into();
+            self.record_gaps();
+            if self.gaps.is_empty() {
+                return Ok(false);
+            }
+            info!(
+                "[{}] Found {} gaps ({} blocks) in indexed history, healing",
+                self.network.name,
+                self.gaps.len(),
+                self.missing_blocks()
+            );
+        }
+
+        let Some(&(from_block, to_block)) = self.gaps.front() else {
+            return Ok(false);
+        };
+        let (batch, chunk_end) = self.fetch_batch_adaptive(from_block, to_block).await?;
+        let ctx = self.poll_context(to_block, (from_block, chunk_end), &batch).await?;
+        let events = self.process_batch(&batch, &ctx).await?;
+        self.writer.processed_range(from_block, chunk_end).await?;
+
+        if chunk_end >= to_block {
+            self.gaps.pop_front();
+        } else if let Some(gap) = self.gaps.front_mut() {
+            gap.0 = chunk_end + 1;
+        }
+        self.record_gaps();
+        info!(
+            "[{}] Healed blocks {}-{} ({} events), {} blocks left in {} gaps",
+            self.network.name,
+            from_block,
+            chunk_end,
+            events,
+            self.missing_blocks(),
+            self.gaps.len()
+        );
+
+        Ok(!self.gaps.is_empty())
+    }
+
+    /// Blocks in the queued gaps
+    fn missing_blocks(&self) -> u64 {
+        self.gaps.iter().map(|(from_block, to_block)| to_block - from_block + 1).sum()
+    }
+
+    fn record_gaps(&self) {
+        if let Some(health) = &self.health {
+            health.record_gaps(self.network.chain_id, self.missing_blocks());
+        }
+    }
+
+    // =========================================================================
+    // Reorg Detection
+    // =========================================================================
+
+    /// Compare recorded block hashes with the chain, newest first
+    ///
+    /// Returns the newest recorded block that is still canonical when the most
+    /// recent one has been reorged out, or None if nothing changed.
+    async fn find_fork_point(&self) -> Result<Option<u64>, String> {
+        let recorded = self
+            .db
+            .get_block_hashes(self.network.chain_id)
+            .await
+            .map_err(|e| format!("DB error: {}", e))?;
+
+        for (i, (block_number, recorded_hash)) in recorded.iter().enumerate() {
+            let block = self
+                .rpc
+                .get_block(*block_number)
+                .await
+                .map_err(|e| format!("Failed to get block {}: {}", block_number, e))?;
+
+            let Some(hash) = block.hash else {
+                return Ok(None); // Provider doesn't return hashes; nothing to compare
+            };
+            if hash.to_lowercase() == *recorded_hash {
+                return Ok((i > 0).then_some(*block_number));
+            }
+        }
+
+        // Every recorded block was replaced: rewind past the oldest one
+        Ok(recorded.last().map(|(block_number, _)| block_number.saturating_sub(1)))
+    }
+
+    /// Delete data indexed after `fork_block` and rewind the checkpoint to it
+    async fn rollback_to(&mut self, fork_block: u64) -> Result<(), String> {
+        // Rows still buffered would otherwise land after the delete
+        self.writer.flush().await?;
+
+        let stats = self
+            .db
+            .rollback_to_block(self.network.chain_id, fork_block)
+            .await
+            .map_err(|e| format!("DB error: {}", e))?;
+
+        warn!(
+            "[{}] Reorg detected, rewound to block {}: removed {} transfers, {} approvals, {} Fusion+ swaps, {} Fusion+ events, {} Fusion swaps, {} Crypto2Fiat events, {} watcher events, {} delegation events; reset {} Fusion+ dst legs, {} Fusion+ withdrawals, {} Fusion+ cancellations, {} Fusion cancellations",
+            self.network.name,
+            fork_block,
+            stats.transfers_deleted,
+            stats.approvals_deleted,
+            stats.fusion_plus_deleted,
+            stats.fusion_plus_events_deleted,
+            stats.fusion_deleted,
+            stats.crypto2fiat_deleted,
+            stats.raw_events_deleted,
+            stats.delegations_deleted,
+            stats.fusion_plus_dst_reset,
+            stats.fusion_plus_withdrawal_reset,
+            stats.fusion_plus_cancel_reset,
+            stats.fusion_cancel_reset
+        );
+
+        // Re-scanned logs must not be skipped as already seen
+        self.block_timestamp_cache.retain(|&block, _| block <= fork_block);
+        if self.dedup.is_some() {
+            self.dedup = Some(LogDeduplicator::new(DEDUP_CAPACITY));
+        }
+        self.processed_swaps = LogDeduplicator::new(PROCESSED_SWAPS_CAPACITY);
+        self.gaps.clear();
+
+        Ok(())
+    }
+
+    /// Record the hash of the last block in a processed range
+    async fn record_block_hash(&mut self, block_number: u64) -> Result<(), String> {
+        let block = self
+            .rpc
+            .get_block(block_number)
+            .await
+            .map_err(|e| format!("Failed to get block {}: {}", block_number, e))?;
+
+        self.block_timestamp_cache.insert(block_number, block.timestamp_u64());
+
+        if let Some(hash) = block.hash.filter(|_| self.dry_run.is_none()) {
+            self.db
+                .record_block_hash(self.network.chain_id, block_number, &hash, self.config.block_hash_history)
+                .await
+                .map_err(|e| format!("DB error: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetch logs starting at `from_block`, adapting the range to provider limits
+    ///
+    /// Halves the range while the provider rejects it (or its trace) as too
+    /// large and grows it back by a quarter after each successful full-size
+    /// query. Returns the batch and the last block it covers.
+    async fn fetch_batch_adaptive(&mut self, from_block: u64, to_block: u64) -> Result<(LogBatch, u64), String> {
+        loop {
+            let range = self.logs_range.min(to_block - from_block + 1);
+            let end_block = from_block + range - 1;
+
+            let batch = match self.fetch_batch(from_block, end_block).await {
+                Ok(batch) => self
+                    .fetch_internal_transfers(from_block, end_block)
+                    .await
+                    .map(|internal_transfers| LogBatch { internal_transfers, ..batch }),
+                Err(e) => Err(e),
+            };
+            match batch {
+                Ok(batch) => {
+                    if range == self.logs_range && self.logs_range < self.config.max_blocks_per_query {
+                        self.logs_range = (self.logs_range + (self.logs_range / 4).max(1))
+                            .min(self.config.max_blocks_per_query);
+                        debug!("[{}] getLogs range grown to {}", self.network.name, self.logs_range);
+                    }
+                    return Ok((batch, end_block));
+                }
+                Err(RpcError::RangeTooLarge(msg)) if range > 1 => {
+                    self.logs_range = range / 2;
+                    warn!(
+                        "[{}] getLogs range {}-{} rejected ({}), retrying with {} blocks",
+                        self.network.name, from_block, end_block, msg, self.logs_range
+                    );
+                }
+                Err(e) => return Err(format!("Failed to get logs: {}", e)),
+            }
+        }
+    }
+
+    /// Fetch all event categories for a block range
+    ///
+    /// The getLogs calls run concurrently; the first range error fails the batch.
+    #[instrument(name = "fetch_logs", skip(self))]
+    async fn fetch_batch(&self, from_block: u64, to_block: u64) -> Result<LogBatch, RpcError> {
+        if self.config.combined_logs {
+            return self.fetch_batch_combined(from_block, to_block).await;
+        }
+
+        let ((fusion_plus_factory, fusion_plus_escrow), fusion, crypto2fiat, watched, delegations, wraps, token_logs) = tokio::try_join!(
+            self.fetch_fusion_plus_logs(from_block, to_block),
+            self.fetch_fusion_logs(from_block, to_block),
+            self.fetch_crypto2fiat_logs(from_block, to_block),
+            self.fetch_watcher_logs(from_block, to_block),
+            self.fetch_delegation_logs(from_block, to_block),
+            self.fetch_wrap_logs(from_block, to_block),
+            self.rpc.get_token_logs(from_block, to_block),
+        )?;
+
+        // Transfers and approvals share one query, split by topic0
+        let (transfers, approvals) = token_logs
+            .into_iter()
+            .partition(|log| log.topics.first().is_some_and(|t| t.eq_ignore_ascii_case(TRANSFER_TOPIC)));
+
+        Ok(LogBatch {
+            fusion_plus_factory,
+            fusion_plus_escrow,
+            fusion,
+            crypto2fiat,
+            transfers,
+            wraps,
+            approvals,
+            watched,
+            delegations,
+            native_transfers: Vec::new(),
+            internal_transfers: Vec::new(),
+        })
+    }
+
+    /// Fetch all event categories with one OR-topic0 getLogs call, split locally
+    ///
+    /// Watchers with their own filters are still fetched separately.
+    async fn fetch_batch_combined(&self, from_block: u64, to_block: u64) -> Result<LogBatch, RpcError> {
+        let mut topics: Vec<String> = LIVE_TOPICS.iter().map(|t| t.to_string()).collect();
+        topics.extend(self.config.delegation_topics.iter().cloned());
+
+        let (logs, watched) = tokio::try_join!(
+            self.rpc.get_logs_multi_topics_any_address(from_block, to_block, topics),
+            self.fetch_watcher_logs(from_block, to_block),
+        )?;
+
+        let mut batch = LogBatch::default();
+        for log in logs {
+            self.classify_log(log, &mut batch);
+        }
+        // Replaces the watcher matches among the combined logs, which it includes
+        batch.watched = watched;
+        Ok(batch)
+    }
+
+    /// Value moved by contract calls to or from traced addresses in a range
+    ///
+    /// Off unless `internal_transfers` is configured and a trace watchlist is
+    /// attached. trace_filter filters by address on the node; debug_trace
+    /// traces every block of the range and filters here. A node that doesn't
+    /// serve the trace method turns tracing off for the rest of the run; any
+    /// other error is returned, so the batch is retried (or, for a range too
+    /// large to trace, shrunk).
+    async fn fetch_internal_transfers(&mut self, from_block: u64, to_block: u64) -> Result<Vec<TracedTransfer>, RpcError> {
+        let (Some(method), Some(watchlist)) = (self.config.internal_transfers, &self.trace_watchlist) else {
+            return Ok(Vec::new());
+        };
+        if watchlist.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let transfers = match method {
+            TraceMethod::TraceFilter => self
+                .rpc
+                .trace_filter(from_block, to_block, &watchlist.addresses())
+                .await
+                .map(|t| traces::from_trace_filter(&t)),
+            TraceMethod::DebugTrace => {
+                let rpc = &self.rpc;
+                stream::iter(from_block..=to_block)
+                    .map(|block_number| async move {
+                        rpc.debug_trace_block(block_number)
+                            .await
+                            .map(|t| traces::from_call_frames(block_number, &t))
+                    })
+                    .buffered(TIMESTAMP_FETCH_CONCURRENCY)
+                    .try_concat()
+                    .await
+            }
+        };
+
+        match transfers {
+            Ok(transfers) => Ok(transfers.into_iter().filter(|t| watchlist.matches(&t.from, &t.to)).collect()),
+            Err(e) if e.is_method_unsupported() => {
+                let name = match method {
+                    TraceMethod::TraceFilter => "trace_filter",
+                    TraceMethod::DebugTrace => "debug_traceBlockByNumber",
+                };
+                warn!(
+                    "[{}] {} not supported ({}), internal transfers are no longer traced",
+                    self.network.name, name, e
+                );
+                self.config.internal_transfers = None;
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Native transfers of a backfill chunk, when the provider-aware mode applies
+    ///
+    /// Needs `enhanced_apis` and an active Alchemy endpoint; elsewhere only
+    /// the eth_getLogs categories are indexed. Live polls skip this call, which
+    /// costs far more compute units than eth_getLogs, so native transfers are
+    /// only stored for backfilled ranges. Value moved by contract calls is
+    /// left to `internal_transfers`. An endpoint without the method turns the
+    /// mode off for the rest of the run; any other error fails the chunk.
+    async fn fetch_native_transfers(&mut self, from_block: u64, to_block: u64) -> Result<Vec<AssetTransfer>, String> {
+        if !self.asset_transfers || self.rpc.provider() != "Alchemy" {
+            return Ok(Vec::new());
+        }
+        match self.rpc.get_native_transfers(from_block, to_block).await {
+            Ok(transfers) => Ok(transfers),
+            Err(e) if e.is_method_unsupported() => {
+                warn!(
+                    "[{}] alchemy_getAssetTransfers not supported ({}), falling back to eth_getLogs only",
+                    self.network.name, e
+                );
+                self.asset_transfers = false;
+                Ok(Vec::new())
+            }
+            Err(e) => Err(format!("Failed to get asset transfers: {}", e)),
+        }
+    }
+
+    /// Store transfers and process swap events for a batch of logs
+    #[instrument(skip_all, fields(logs = batch.len(), from_block = ctx.from_block, to_block = ctx.to_block))]
+    async fn process_batch(&mut self, batch: &LogBatch, ctx: &PollContext) -> Result<usize, String> {
+        for handler in &self.handlers {
+            for log in batch.logs() {
+                handler.on_raw_log(self.network.chain_id, log);
+            }
+        }
+        if self.config.archive_raw_logs {
+            self.archive_logs(batch, ctx).await?;
+        }
+
+        // Events of failed transactions are dropped (TX_STATUS=skip) after being archived
+        let succeeded;
+        let batch = if self.config.tx_status == Some(TxStatusCheck::Skip) && ctx.tx_status.values().any(|&ok| !ok) {
+            let mut kept = batch.clone();
+            let dropped = kept.retain_txs(|tx_hash| !ctx.tx_failed(tx_hash));
+            info!("[{}] Skipped {} events of failed transactions", self.network.name, dropped);
+            succeeded = kept;
+            &succeeded
+        } else {
+            batch
+        };
+
+        // =========================================================================
+        // PHASE 1: Build swap_type map from fusion/crypto2fiat logs
+        // =========================================================================
+        let mut swap_type_map: HashMap<String, &'static str> = HashMap::new();
+
+        for log in batch.fusion_plus_factory.iter().chain(&batch.fusion_plus_escrow) {
+            swap_type_map.insert(log.transaction_hash.to_lowercase(), "fusion_plus");
+        }
+        for log in &batch.fusion {
+            swap_type_map.insert(log.transaction_hash.to_lowercase(), "fusion");
+        }
+        for log in &batch.crypto2fiat {
+            swap_type_map.insert(log.transaction_hash.to_lowercase(), "crypto_to_fiat");
+        }
+
+        // =========================================================================
+        // PHASE 2: Insert transfers with swap_type from map
+        // =========================================================================
+        // WETH deposits and withdrawals are stored as mints and burns
+        let mut transfers = Vec::with_capacity(batch.transfers.len() + batch.wraps.len() + batch.native_transfers.len());
+
+        for log in batch.transfers.iter().chain(&batch.wraps) {
+            let Some((from_addr, to_addr)) = transfer_parties(log) else {
+                continue; // Invalid Transfer event
+            };
+
+            if let Some(watchlist) = &self.watchlist {
+                if !watchlist.matches(&from_addr.to_lowercase(), &to_addr.to_lowercase()) {
+                    continue;
+                }
             }
 
             let block_number = log.block_number_u64();
-            let timestamp = self.get_block_timestamp(block_number).await?;
+            let timestamp = ctx.timestamp(block_number)?;
 
             // Look up swap_type from the map
             let swap_type = swap_type_map.get(&log.transaction_hash.to_lowercase()).map(|s| s.to_string());
 
+            // Compact mode drops the value; it is re-fetched from the receipt when needed
+            let value = match self.config.storage_mode {
+                StorageMode::Full => log.data.clone(),
+                StorageMode::Compact => String::new(),
+            };
+            let value_decimal = decode_uint256(&value);
+
             let transfer = Transfer {
                 chain_id: self.network.chain_id,
                 tx_hash: log.transaction_hash.clone(),
                 log_index: log.log_index_u32(),
                 token: log.address.to_lowercase(),
-                from_addr: format!("0x{}", &log.topics[1][26..]), // Remove padding
-                to_addr: format!("0x{}", &log.topics[2][26..]),   // Remove padding
-                value: log.data.clone(),
+                from_addr,
+                to_addr,
+                value,
+                value_decimal,
                 block_number,
                 block_timestamp: timestamp,
                 swap_type,
+                tx_status: ctx.tx_status(&log.transaction_hash),
+                token_info: None,
+                id: None,
             };
 
             transfers.push(transfer);
         }
 
-        // Batch insert to PostgreSQL database (with swap_type already set)
-        let inserted = if !transfers.is_empty() {
-            self.db
-                .insert_transfers_batch(self.network.chain_id, &transfers)
-                .await
-                .map_err(|e| format!("DB error: {}", e))?
-        } else {
-            0
-        };
+        // Native transfers keep their value in compact mode, as there is no log to re-read it from
+        for native in &batch.native_transfers {
+            let (Some(to_addr), Some(value)) = (&native.to, native.value_word()) else {
+                continue; // Contract creation or unparseable amount
+            };
+            let from_addr = native.from.to_lowercase();
+            let to_addr = to_addr.to_lowercase();
+
+            if let Some(watchlist) = &self.watchlist {
+                if !watchlist.matches(&from_addr, &to_addr) {
+                    continue;
+                }
+            }
+
+            let block_number = native.block_number_u64();
+
+            transfers.push(Transfer {
+                chain_id: self.network.chain_id,
+                tx_hash: native.hash.clone(),
+                log_index: NATIVE_LOG_INDEX_BASE,
+                token: NATIVE_TOKEN.to_string(),
+                from_addr,
+                to_addr,
+                value_decimal: decode_uint256(&value),
+                value,
+                block_number,
+                block_timestamp: ctx.timestamp(block_number)?,
+                swap_type: swap_type_map.get(&native.hash.to_lowercase()).map(|s| s.to_string()),
+                tx_status: ctx.tx_status(&native.hash),
+                token_info: None,
+                id: None,
+            });
+        }
+
+        if self.config.fetch_token_metadata {
+            self.cache_token_metadata(&transfers).await;
+        }
+
+        // With a watchlist every stored transfer is a match worth its transaction's details
+        let mut matched_txs: HashMap<String, u64> = HashMap::new();
+        if self.config.fetch_transactions && self.watchlist.is_some() {
+            matched_txs.extend(transfers.iter().map(|t| (t.tx_hash.to_lowercase(), t.block_number)));
+        }
+
+        // Queued for the writer (with swap_type already set), which publishes them once stored
+        let queued = transfers.len();
+        if !transfers.is_empty() {
+            self.writer.transfers(transfers).await?;
+        }
+
+        // =========================================================================
+        // PHASE 2b: Insert ERC20 approvals
+        // =========================================================================
+        let approvals_inserted = self.process_approvals(&batch.approvals, ctx).await?;
+
+        // =========================================================================
+        // PHASE 2c: Insert value moved by contract calls (INTERNAL_TRANSFERS)
+        // =========================================================================
+        let internal_transfers = batch
+            .internal_transfers
+            .iter()
+            .map(|traced| {
+                Ok(InternalTransfer {
+                    chain_id: self.network.chain_id,
+                    tx_hash: traced.tx_hash.clone(),
+                    trace_address: traced.trace_path(),
+                    call_type: traced.call_type.to_string(),
+                    from_addr: traced.from.clone(),
+                    to_addr: traced.to.clone(),
+                    value: traced.value.clone(),
+                    value_decimal: decode_uint256(&traced.value),
+                    block_number: traced.block_number,
+                    block_timestamp: ctx.timestamp(traced.block_number)?,
+                    id: None,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let internal_queued = internal_transfers.len();
+        if !internal_transfers.is_empty() {
+            self.writer.internal_transfers(internal_transfers).await?;
+        }
 
         // =========================================================================
         // PHASE 3: Process fusion events (insert swap records, no UPDATE needed)
         // =========================================================================
-        let fusion_plus_events = self.process_fusion_plus_logs(&fusion_plus_factory_logs, &fusion_plus_escrow_logs).await?;
-        let fusion_events = self.process_fusion_logs(&fusion_logs).await?;
-        let crypto2fiat_events = self.process_crypto2fiat_logs(&crypto2fiat_logs).await?;
+        // OrderFilled enrichment, and matching cancelled Fusion+ escrows whose address
+        // wasn't computed, read the tx's transfers back from the database
+        let has_escrow_cancellations = batch
+            .fusion_plus_escrow
+            .iter()
+            .any(|log| log.topics.first().is_some_and(|t| t.eq_ignore_ascii_case(ESCROW_CANCELLED_TOPIC)));
+        if !batch.fusion.is_empty() || has_escrow_cancellations {
+            self.writer.flush().await?;
+        }
+        let fusion_plus_events = self.process_fusion_plus_logs(&batch.fusion_plus_factory, &batch.fusion_plus_escrow, ctx).await?;
+        let fusion_events = self.process_fusion_logs(&batch.fusion, ctx).await?;
+        let crypto2fiat_events = self.process_crypto2fiat_logs(&batch.crypto2fiat, ctx).await?;
 
-        // Update checkpoint
-        *last_processed_block = actual_to_block;
-        self.db
-            .set_checkpoint(self.network.chain_id, actual_to_block)
-            .await
-            .map_err(|e| format!("DB error: {}", e))?;
+        // =========================================================================
+        // PHASE 4: Store raw logs for user-defined watchers and delegation topics
+        // =========================================================================
+        let raw_events = self.process_watcher_logs(&batch.watched, ctx).await?;
+        let delegations = self.process_delegation_logs(&batch.delegations, ctx).await?;
+
+        // =========================================================================
+        // PHASE 5: Fetch sender/receiver of transactions with matched events
+        // =========================================================================
+        if self.config.fetch_transactions {
+            let matched_logs = batch
+                .fusion_plus_factory
+                .iter()
+                .chain(&batch.fusion_plus_escrow)
+                .chain(&batch.fusion)
+                .chain(&batch.crypto2fiat)
+                .chain(batch.watched.iter().map(|(_, log)| log))
+                .chain(&batch.delegations);
+            matched_txs.extend(matched_logs.map(|log| (log.transaction_hash.to_lowercase(), log.block_number_u64())));
+            self.store_transactions(matched_txs, ctx).await?;
+        }
+
+        Ok(queued + approvals_inserted + internal_queued + fusion_plus_events + fusion_events + crypto2fiat_events + raw_events + delegations)
+    }
+
+    /// Queue the batch's logs for `raw_logs`, stored ahead of the rows decoded from them
+    async fn archive_logs(&self, batch: &LogBatch, ctx: &PollContext) -> Result<(), String> {
+        let mut logs = Vec::with_capacity(batch.len());
+        for log in batch.logs() {
+            logs.push((log.clone(), ctx.timestamp(log.block_number_u64())?));
+        }
+
+        self.writer.raw_logs(logs).await
+    }
+
+    /// Decode ERC20 Approval logs and queue them for the writer
+    ///
+    /// ERC721 approvals share topic0 but index the token id as a fourth topic;
+    /// they are skipped.
+    async fn process_approvals(&self, logs: &[Log], ctx: &PollContext) -> Result<usize, String> {
+        let mut approvals = Vec::with_capacity(logs.len());
+
+        for log in logs {
+            if log.topics.len() != 3 {
+                continue;
+            }
+
+            let owner = format!("0x{}", &log.topics[1][26..]).to_lowercase();
+            let spender = format!("0x{}", &log.topics[2][26..]).to_lowercase();
+
+            if let Some(watchlist) = &self.watchlist {
+                if !watchlist.matches(&owner, &spender) {
+                    continue;
+                }
+            }
+
+            let Some(amount) = approval_amount(&log.data) else {
+                debug!(
+                    "[{}] Skipping Approval {}:{} with malformed data",
+                    self.network.name,
+                    log.transaction_hash,
+                    log.log_index_u32()
+                );
+                continue;
+            };
+            let block_number = log.block_number_u64();
+            let timestamp = ctx.timestamp(block_number)?;
+
+            approvals.push(Approval {
+                chain_id: self.network.chain_id,
+                tx_hash: log.transaction_hash.clone(),
+                log_index: log.log_index_u32(),
+                token: log.address.to_lowercase(),
+                owner,
+                spender,
+                is_unlimited: amount == UNLIMITED_APPROVAL,
+                amount,
+                block_number,
+                block_timestamp: timestamp,
+            });
+        }
 
-        Ok(inserted + fusion_plus_events + fusion_events + crypto2fiat_events)
+        let queued = approvals.len();
+        if !approvals.is_empty() {
+            self.writer.approvals(approvals).await?;
+        }
+        Ok(queued)
     }
 
     // =========================================================================
@@ -322,32 +1916,28 @@ impl ChainPoller {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> Result<(Vec<Log>, Vec<Log>), String> {
+    ) -> Result<(Vec<Log>, Vec<Log>), RpcError> {
         // Fetch SrcEscrowCreated and DstEscrowCreated events from EscrowFactory
         let factory_topics = vec![
             SRC_ESCROW_CREATED_TOPIC.to_string(),
             DST_ESCROW_CREATED_TOPIC.to_string(),
         ];
 
-        let factory_logs = self
-            .rpc
-            .get_logs_multi_topics(from_block, to_block, ESCROW_FACTORY, factory_topics)
-            .await
-            .unwrap_or_default();
-
         // Fetch EscrowWithdrawal and EscrowCancelled events (from any escrow contract)
         let escrow_topics = vec![
             ESCROW_WITHDRAWAL_TOPIC.to_string(),
             ESCROW_CANCELLED_TOPIC.to_string(),
         ];
 
-        let escrow_logs = self
-            .rpc
-            .get_logs_multi_topics_any_address(from_block, to_block, escrow_topics)
-            .await
-            .unwrap_or_default();
+        let (factory_logs, escrow_logs) = tokio::join!(
+            self.rpc.get_logs_multi_topics(from_block, to_block, &self.network.contracts.escrow_factory, factory_topics),
+            self.rpc.get_logs_multi_topics_any_address(from_block, to_block, escrow_topics),
+        );
 
-        Ok((factory_logs, escrow_logs))
+        Ok((
+            factory_logs.or_else(empty_unless_range_error)?,
+            escrow_logs.or_else(empty_unless_range_error)?,
+        ))
     }
 
     /// Fetch Fusion (single-chain) logs from Aggregation Router
@@ -355,13 +1945,8 @@ impl ChainPoller {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> Result<Vec<Log>, String> {
-        // Determine contract address based on chain
-        let router_address = if self.network.chain_id == 324 {
-            AGGREGATION_ROUTER_ZKSYNC
-        } else {
-            AGGREGATION_ROUTER_V6
-        };
+    ) -> Result<Vec<Log>, RpcError> {
+        let router_address = &self.network.contracts.aggregation_router;
 
         let topics = vec![
             ORDER_FILLED_TOPIC.to_string(),
@@ -372,7 +1957,7 @@ impl ChainPoller {
             .rpc
             .get_logs_multi_topics(from_block, to_block, router_address, topics)
             .await
-            .unwrap_or_default();
+            .or_else(empty_unless_range_error)?;
 
         Ok(logs)
     }
@@ -382,12 +1967,85 @@ impl ChainPoller {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> Result<Vec<Log>, String> {
+    ) -> Result<Vec<Log>, RpcError> {
         let logs = self
             .rpc
             .get_logs_by_topic_any_address(from_block, to_block, CRYPTO2FIAT_TOPIC)
             .await
-            .unwrap_or_default();
+            .or_else(empty_unless_range_error)?;
+
+        Ok(logs)
+    }
+
+    /// Fetch logs for each configured watcher, tagged with its label
+    async fn fetch_watcher_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<(String, Log)>, RpcError> {
+        let fetches = self.network.watchers.iter().map(|watcher| async move {
+            let logs = match (&watcher.address, watcher.topics.is_empty()) {
+                (Some(address), true) => {
+                    self.rpc.get_logs_by_address(from_block, to_block, address, Vec::new()).await
+                }
+                (Some(address), false) => {
+                    self.rpc
+                        .get_logs_multi_topics(from_block, to_block, address, watcher.topics.clone())
+                        .await
+                }
+                (None, _) => {
+                    self.rpc
+                        .get_logs_multi_topics_any_address(from_block, to_block, watcher.topics.clone())
+                        .await
+                }
+            }
+            .or_else(empty_unless_range_error)?;
+
+            Ok::<_, RpcError>(logs.into_iter().map(|log| (watcher.label.clone(), log)))
+        });
+
+        Ok(try_join_all(fetches).await?.into_iter().flatten().collect())
+    }
+
+    /// Fetch Deposit/Withdrawal logs of the chain's wrapped native token
+    async fn fetch_wrap_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, RpcError> {
+        let Some(wrapped_native) = &self.network.wrapped_native else {
+            return Ok(Vec::new());
+        };
+
+        let topics = vec![
+            WETH_DEPOSIT_TOPIC.to_string(),
+            WETH_WITHDRAWAL_TOPIC.to_string(),
+        ];
+
+        let logs = self
+            .rpc
+            .get_logs_multi_topics(from_block, to_block, wrapped_native, topics)
+            .await
+            .or_else(empty_unless_range_error)?;
+
+        Ok(logs)
+    }
+
+    /// Fetch logs matching the configured delegation topics from any address
+    async fn fetch_delegation_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, RpcError> {
+        if self.config.delegation_topics.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let logs = self
+            .rpc
+            .get_logs_multi_topics_any_address(from_block, to_block, self.config.delegation_topics.clone())
+            .await
+            .or_else(empty_unless_range_error)?;
 
         Ok(logs)
     }
@@ -397,55 +2055,93 @@ impl ChainPoller {
     // =========================================================================
 
     /// Process Fusion+ logs (factory and escrow events)
+    ///
+    /// Changes are applied in one transaction, factory events first, so
+    /// escrow events see swaps created earlier in the batch.
     async fn process_fusion_plus_logs(
         &mut self,
         factory_logs: &[Log],
         escrow_logs: &[Log],
+        ctx: &PollContext,
     ) -> Result<usize, String> {
-        let mut events_processed = 0;
+        let bytecode_hashes = if factory_logs.is_empty() {
+            None
+        } else {
+            self.load_escrow_bytecode_hashes().await
+        };
 
-        for log in factory_logs {
+        let mut changes = Vec::new();
+        let mut change_logs = Vec::new();
+
+        for log in factory_logs.iter().chain(escrow_logs) {
             if log.topics.is_empty() {
                 continue;
             }
 
-            let timestamp = self.get_block_timestamp(log.block_number_u64()).await?;
+            let timestamp = ctx.timestamp(log.block_number_u64())?;
+            let topic0 = log.topics[0].to_lowercase();
 
-            if log.topics[0].to_lowercase() == SRC_ESCROW_CREATED_TOPIC {
-                if let Err(e) = self.process_src_escrow_created(log, timestamp).await {
-                    warn!("[{}] Failed to process SrcEscrowCreated: {}", self.network.name, e);
-                } else {
-                    events_processed += 1;
+            let change = if topic0 == SRC_ESCROW_CREATED_TOPIC {
+                self.src_escrow_created_change(log, timestamp, bytecode_hashes)
+                    .inspect_err(|e| warn!("[{}] Failed to process SrcEscrowCreated: {}", self.network.name, e))
+            } else if topic0 == DST_ESCROW_CREATED_TOPIC {
+                self.dst_escrow_created_change(log, timestamp, bytecode_hashes)
+                    .inspect_err(|e| warn!("[{}] Failed to process DstEscrowCreated: {}", self.network.name, e))
+            } else if topic0 == ESCROW_WITHDRAWAL_TOPIC {
+                self.escrow_withdrawal_change(log, timestamp)
+                    .inspect_err(|e| debug!("[{}] Failed to process EscrowWithdrawal: {}", self.network.name, e))
+            } else if topic0 == ESCROW_CANCELLED_TOPIC {
+                // EscrowCancelled carries no data; the swap is matched by the
+                // emitting escrow address recorded at creation, or by its refund
+                Ok(FusionPlusChange::Cancelled(FusionPlusCancellation {
+                    escrow_address: log.address.to_lowercase(),
+                    chain_id: self.network.chain_id,
+                    tx_hash: log.transaction_hash.clone(),
+                    block_number: log.block_number_u64(),
+                    block_timestamp: timestamp,
+                    log_index: log.log_index_u32(),
+                }))
+            } else {
+                continue;
+            };
+
+            if let Ok(mut change) = change {
+                // EscrowWithdrawal doesn't name the caller; its tx sender is the withdrawer
+                if let FusionPlusChange::Withdrawn(withdrawal) = &mut change {
+                    withdrawal.withdrawer = self.transaction_sender(&withdrawal.tx_hash).await;
                 }
-            } else if log.topics[0].to_lowercase() == DST_ESCROW_CREATED_TOPIC {
-                if let Err(e) = self.process_dst_escrow_created(log, timestamp).await {
-                    warn!("[{}] Failed to process DstEscrowCreated: {}", self.network.name, e);
-                } else {
-                    events_processed += 1;
+                if let FusionPlusChange::Created { swap, merkle_fill } = &mut change {
+                    *merkle_fill = self.merkle_fill(swap).await;
                 }
+                changes.push(change);
+                change_logs.push(log);
             }
         }
 
-        for log in escrow_logs {
-            if log.topics.is_empty() {
-                continue;
-            }
+        if changes.is_empty() {
+            return Ok(0);
+        }
 
-            let timestamp = self.get_block_timestamp(log.block_number_u64()).await?;
+        let applied = match &self.dry_run {
+            Some(dry_run) => {
+                changes.iter().for_each(|change| dry_run.record("fusion_plus_changes", change));
+                vec![None; changes.len()]
+            }
+            None => self
+                .db
+                .apply_fusion_plus_changes(&changes)
+                .await
+                .map_err(|e| format!("DB error: {}", e))?,
+        };
 
-            if log.topics[0].to_lowercase() == ESCROW_WITHDRAWAL_TOPIC {
-                if let Err(e) = self.process_escrow_withdrawal(log, timestamp).await {
-                    debug!("[{}] Failed to process EscrowWithdrawal: {}", self.network.name, e);
-                } else {
-                    events_processed += 1;
-                }
-            } else if log.topics[0].to_lowercase() == ESCROW_CANCELLED_TOPIC {
-                if let Err(e) = self.process_escrow_cancelled(log, timestamp).await {
-                    debug!("[{}] Failed to process EscrowCancelled: {}", self.network.name, e);
-                } else {
-                    events_processed += 1;
-                }
+        let mut events_processed = 0;
+        for ((change, log), applied) in changes.into_iter().zip(change_logs).zip(applied) {
+            if applied.is_some() {
+                events_processed += 1;
             }
+            // Stored with the batch, so a retry after a later phase fails skips it
+            self.processed_swaps.insert(log);
+            self.report_fusion_plus_change(change, log, applied).await;
         }
 
         if events_processed > 0 {
@@ -459,7 +2155,7 @@ impl ChainPoller {
     }
 
     /// Process Fusion (single-chain) logs
-    async fn process_fusion_logs(&mut self, logs: &[Log]) -> Result<usize, String> {
+    async fn process_fusion_logs(&mut self, logs: &[Log], ctx: &PollContext) -> Result<usize, String> {
         let mut events_processed = 0;
 
         for log in logs {
@@ -467,22 +2163,27 @@ impl ChainPoller {
                 continue;
             }
 
-            let timestamp = self.get_block_timestamp(log.block_number_u64()).await?;
             let topic0 = log.topics[0].to_lowercase();
 
             if topic0 == ORDER_FILLED_TOPIC {
-                if let Err(e) = self.process_order_filled(log, timestamp, "filled").await {
+                let timestamp = ctx.timestamp(log.block_number_u64())?;
+                if let Err(e) = self.process_order_filled(log, timestamp).await {
                     debug!("[{}] Failed to process OrderFilled: {}", self.network.name, e);
-                } else {
-                    events_processed += 1;
+                    continue;
                 }
+                events_processed += 1;
             } else if topic0 == ORDER_CANCELLED_TOPIC {
-                if let Err(e) = self.process_order_filled(log, timestamp, "cancelled").await {
-                    debug!("[{}] Failed to process OrderCancelled: {}", self.network.name, e);
-                } else {
-                    events_processed += 1;
+                match self.process_order_cancelled(log).await {
+                    Ok(true) => events_processed += 1,
+                    Ok(false) => {}
+                    Err(e) => {
+                        debug!("[{}] Failed to process OrderCancelled: {}", self.network.name, e);
+                        continue;
+                    }
                 }
             }
+            // A retry after a later phase fails skips this log
+            self.processed_swaps.insert(log);
         }
 
         if events_processed > 0 {
@@ -496,7 +2197,7 @@ impl ChainPoller {
     }
 
     /// Process Crypto2Fiat logs
-    async fn process_crypto2fiat_logs(&mut self, logs: &[Log]) -> Result<usize, String> {
+    async fn process_crypto2fiat_logs(&mut self, logs: &[Log], ctx: &PollContext) -> Result<usize, String> {
         let mut events_processed = 0;
 
         for log in logs {
@@ -504,12 +2205,13 @@ impl ChainPoller {
                 continue;
             }
 
-            let timestamp = self.get_block_timestamp(log.block_number_u64()).await?;
+            let timestamp = ctx.timestamp(log.block_number_u64())?;
 
             if let Err(e) = self.process_crypto2fiat_event(log, timestamp).await {
                 debug!("[{}] Failed to process Crypto2Fiat event: {}", self.network.name, e);
             } else {
                 events_processed += 1;
+                self.processed_swaps.insert(log);
             }
         }
 
@@ -523,13 +2225,303 @@ impl ChainPoller {
         Ok(events_processed)
     }
 
-    /// Process SrcEscrowCreated event
-    async fn process_src_escrow_created(&self, log: &Log, timestamp: u64) -> Result<(), String> {
+    /// Store watcher logs undecoded in `raw_events`
+    async fn process_watcher_logs(&self, logs: &[(String, Log)], ctx: &PollContext) -> Result<usize, String> {
+        let mut raw_events = Vec::with_capacity(logs.len());
+
+        for (label, log) in logs {
+            let block_number = log.block_number_u64();
+            let timestamp = ctx.timestamp(block_number)?;
+
+            raw_events.push(RawEvent {
+                chain_id: self.network.chain_id,
+                watcher: label.clone(),
+                address: log.address.to_lowercase(),
+                tx_hash: log.transaction_hash.clone(),
+                log_index: log.log_index_u32(),
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+                block_number,
+                block_timestamp: timestamp,
+            });
+        }
+
+        let queued = raw_events.len();
+        if queued > 0 {
+            debug!("[{}] Queued {} watcher events", self.network.name, queued);
+            self.writer.raw_events(raw_events).await?;
+        }
+
+        Ok(queued)
+    }
+
+    /// Store delegation logs, resolving each authority's delegate from its code
+    ///
+    /// The code is read at the log's block; a failed lookup (e.g. pruned state)
+    /// leaves the delegate unset rather than failing the batch.
+    async fn process_delegation_logs(&self, logs: &[Log], ctx: &PollContext) -> Result<usize, String> {
+        let mut delegates: HashMap<(String, u64), Option<String>> = HashMap::new();
+        let mut delegations = Vec::with_capacity(logs.len());
+
+        for log in logs {
+            let authority = log.address.to_lowercase();
+            if let Some(watchlist) = &self.watchlist {
+                if !watchlist.matches(&authority, &authority) {
+                    continue;
+                }
+            }
+
+            let block_number = log.block_number_u64();
+            let timestamp = ctx.timestamp(block_number)?;
+
+            let key = (authority.clone(), block_number);
+            let delegate = match delegates.get(&key) {
+                Some(delegate) => delegate.clone(),
+                None => {
+                    let delegate = match self.rpc.get_code(&authority, block_number).await {
+                        Ok(code) => delegate_from_code(&code),
+                        Err(e) => {
+                            debug!("[{}] Failed to read code of {}: {}", self.network.name, authority, e);
+                            None
+                        }
+                    };
+                    delegates.insert(key, delegate.clone());
+                    delegate
+                }
+            };
+
+            delegations.push(Delegation {
+                chain_id: self.network.chain_id,
+                authority,
+                delegate,
+                tx_hash: log.transaction_hash.clone(),
+                log_index: log.log_index_u32(),
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+                block_number,
+                block_timestamp: timestamp,
+                id: None,
+            });
+        }
+
+        let queued = delegations.len();
+        if queued > 0 {
+            debug!("[{}] Queued {} delegation events", self.network.name, queued);
+            self.writer.delegations(delegations).await?;
+        }
+
+        Ok(queued)
+    }
+
+    /// Fetch the receipts of `txs` (hash to block number) and queue their sender,
+    /// receiver and gas for `transactions`
+    ///
+    /// Receipts carry the same sender and receiver as eth_getTransactionByHash
+    /// plus the gas actually paid, so one request per transaction covers both.
+    /// Failures are logged and skipped; the batch's events are stored either way.
+    async fn store_transactions(&self, txs: HashMap<String, u64>, ctx: &PollContext) -> Result<(), String> {
+        if txs.is_empty() {
+            return Ok(());
+        }
+
+        let rpc = &self.rpc;
+        let fetched: Vec<_> = stream::iter(txs)
+            .map(|(tx_hash, block_number)| async move {
+                let receipt = rpc.get_transaction_receipt(&tx_hash).await;
+                (tx_hash, block_number, receipt)
+            })
+            .buffer_unordered(RECEIPT_FETCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut transactions = Vec::with_capacity(fetched.len());
+        for (tx_hash, block_number, receipt) in fetched {
+            let receipt = match receipt {
+                Ok(receipt) => receipt,
+                Err(e) => {
+                    warn!("[{}] Failed to fetch receipt of {}: {}", self.network.name, tx_hash, e);
+                    continue;
+                }
+            };
+            let Some(from_addr) = receipt.from else {
+                warn!("[{}] Receipt of {} has no sender", self.network.name, tx_hash);
+                continue;
+            };
+            let hex_u64 = |v: Option<String>| v.and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok());
+            let effective_gas_price = receipt
+                .effective_gas_price
+                .and_then(|v| U256::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+                .unwrap_or_default();
+
+            transactions.push(TransactionInfo {
+                chain_id: self.network.chain_id,
+                tx_hash,
+                block_number,
+                block_timestamp: ctx.timestamp(block_number)?,
+                from_addr: from_addr.to_lowercase(),
+                to_addr: receipt.to.map(|to| to.to_lowercase()),
+                gas_used: hex_u64(receipt.gas_used).unwrap_or(0),
+                effective_gas_price: effective_gas_price.to_string(),
+                id: None,
+            });
+        }
+
+        if !transactions.is_empty() {
+            debug!("[{}] Queued {} transactions", self.network.name, transactions.len());
+            self.writer.transactions(transactions).await?;
+        }
+        Ok(())
+    }
+
+    /// Fetch and cache metadata for tokens of `transfers` not yet in the `tokens` table
+    ///
+    /// Failures are logged and retried on the next batch containing the token;
+    /// transfers are stored either way.
+    async fn cache_token_metadata(&mut self, transfers: &[Transfer]) {
+        let mut unseen: Vec<String> = transfers
+            .iter()
+            .map(|t| t.token.to_lowercase())
+            .filter(|token| token != NATIVE_TOKEN && !self.known_tokens.contains(token))
+            .collect();
+        unseen.sort();
+        unseen.dedup();
+        if unseen.is_empty() {
+            return;
+        }
+
+        // Incomplete tokens stay out of known_tokens so they are looked up
+        // again, and refetched once their row is stale
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let refetch_before = now.saturating_sub(TOKEN_REFETCH_SECS);
+        let cached = match self.db.get_cached_tokens(self.network.chain_id, &unseen, refetch_before).await {
+            Ok(cached) => cached,
+            Err(e) => {
+                warn!("[{}] Failed to read token cache: {}", self.network.name, e);
+                return;
+            }
+        };
+        let mut fresh = HashSet::new();
+        for (token, status) in cached {
+            if status == "complete" {
+                self.known_tokens.insert(token.clone());
+            }
+            fresh.insert(token);
+        }
+
+        let missing: Vec<String> = unseen.into_iter().filter(|t| !fresh.contains(t)).collect();
+        let rpc = &self.rpc;
+        let fetched: Vec<_> = match self.network.multicall3.as_deref() {
+            Some(multicall3) => {
+                let batches: Vec<_> = stream::iter(missing.chunks(MULTICALL_BATCH_TOKENS).map(<[String]>::to_vec))
+                    .map(|tokens| async move {
+                        let infos = fetch_token_infos(rpc, multicall3, &tokens).await;
+                        (tokens, infos)
+                    })
+                    .buffer_unordered(TOKEN_FETCH_CONCURRENCY)
+                    .collect()
+                    .await;
+                let mut fetched = Vec::with_capacity(missing.len());
+                for (tokens, infos) in batches {
+                    match infos {
+                        Ok(infos) => fetched.extend(tokens.into_iter().zip(infos)),
+                        Err(e) => warn!(
+                            "[{}] Failed to fetch metadata for {} tokens: {}",
+                            self.network.name,
+                            tokens.len(),
+                            e
+                        ),
+                    }
+                }
+                fetched
+            }
+            None => {
+                stream::iter(missing)
+                    .map(|token| async move {
+                        let info = fetch_token_info(rpc, &token).await;
+                        (token, info)
+                    })
+                    .buffer_unordered(TOKEN_FETCH_CONCURRENCY)
+                    .collect()
+                    .await
+            }
+        };
+
+        for (token, info) in fetched {
+            let info = match info {
+                Ok(info) => info,
+                Err(e) => {
+                    warn!("[{}] Failed to fetch metadata for token {}: {}", self.network.name, token, e);
+                    continue;
+                }
+            };
+            if let Some(dry_run) = &self.dry_run {
+                dry_run.record(
+                    "tokens",
+                    &json!({ "chain_id": self.network.chain_id, "token": token, "info": info, "metadata_status": info.status() }),
+                );
+                self.known_tokens.insert(token);
+                continue;
+            }
+            match self.db.upsert_token(self.network.chain_id, &token, &info).await {
+                Ok(()) => {
+                    debug!(
+                        "[{}] Token {}: symbol={:?} decimals={:?} ({})",
+                        self.network.name,
+                        token,
+                        info.symbol,
+                        info.decimals,
+                        info.status()
+                    );
+                    if info.status() == "complete" {
+                        self.known_tokens.insert(token);
+                    }
+                }
+                Err(e) => warn!("[{}] Failed to cache token {}: {}", self.network.name, token, e),
+            }
+        }
+    }
+
+    /// Fetch the factory's escrow implementations and cache their proxy bytecode hashes
+    ///
+    /// Returns None (and retries on the next batch) if the calls fail; swaps
+    /// are then stored without escrow addresses.
+    async fn load_escrow_bytecode_hashes(&mut self) -> Option<EscrowBytecodeHashes> {
+        if self.escrow_bytecode_hashes.is_some() {
+            return self.escrow_bytecode_hashes;
+        }
+
+        let factory = &self.network.contracts.escrow_factory;
+        let src = self.rpc.call(factory, &selector(ESCROW_SRC_IMPLEMENTATION_SIG)).await;
+        let dst = self.rpc.call(factory, &selector(ESCROW_DST_IMPLEMENTATION_SIG)).await;
+
+        match (src, dst) {
+            (Ok(src), Ok(dst)) => {
+                let hashes = EscrowBytecodeHashes {
+                    src: proxy_bytecode_hash(&src)?,
+                    dst: proxy_bytecode_hash(&dst)?,
+                };
+                debug!("[{}] Escrow implementations: src={} dst={}", self.network.name, src, dst);
+                self.escrow_bytecode_hashes = Some(hashes);
+                Some(hashes)
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("[{}] Failed to read escrow implementations: {}", self.network.name, e);
+                None
+            }
+        }
+    }
+
+    /// Decode SrcEscrowCreated into a new swap
+    fn src_escrow_created_change(
+        &self,
+        log: &Log,
+        timestamp: u64,
+        bytecode_hashes: Option<EscrowBytecodeHashes>,
+    ) -> Result<FusionPlusChange, String> {
         let data = decode_src_escrow_created(&log.data)
             .ok_or_else(|| "Failed to decode SrcEscrowCreated data".to_string())?;
 
-        // Create new swap record
-        let swap = FusionPlusSwap::from_src_created(
+        let mut swap = FusionPlusSwap::from_src_created(
             &data,
             self.network.chain_id,
             &log.transaction_hash,
@@ -537,126 +2529,222 @@ impl ChainPoller {
             timestamp,
             log.log_index_u32(),
         );
+        swap.src_escrow_address = bytecode_hashes
+            .and_then(|h| compute_src_escrow_address(&data, &log.address, &h.src));
 
-        // Insert the swap into database
-        self.db
-            .insert_fusion_plus_swap(&swap)
-            .await
-            .map_err(|e| format!("DB error: {}", e))?;
-
-        // Note: swap_type is already set during transfer INSERT (no UPDATE needed)
-
-        info!(
-            "[{}] Fusion+ SrcEscrow created: order_hash={} dst_chain={}",
-            self.network.name, data.order_hash, data.dst_chain_id
-        );
-
-        Ok(())
+        Ok(FusionPlusChange::Created { swap: Box::new(swap), merkle_fill: None })
     }
 
-    /// Process DstEscrowCreated event
-    async fn process_dst_escrow_created(&self, log: &Log, timestamp: u64) -> Result<(), String> {
+    /// Decode DstEscrowCreated into the swap's destination leg
+    fn dst_escrow_created_change(
+        &self,
+        log: &Log,
+        timestamp: u64,
+        bytecode_hashes: Option<EscrowBytecodeHashes>,
+    ) -> Result<FusionPlusChange, String> {
         let data = decode_dst_escrow_created(&log.data)
             .ok_or_else(|| "Failed to decode DstEscrowCreated data".to_string())?;
+        let escrow_address = bytecode_hashes
+            .and_then(|h| compute_dst_escrow_address(&data, &log.address, &h.dst));
+
+        Ok(FusionPlusChange::DstCreated(FusionPlusDst {
+            order_hash: data.order_hash,
+            hashlock: data.hashlock,
+            dst_taker: data.dst_taker,
+            dst_timelocks: data.dst_timelocks,
+            chain_id: self.network.chain_id,
+            tx_hash: log.transaction_hash.clone(),
+            block_number: log.block_number_u64(),
+            block_timestamp: timestamp,
+            log_index: log.log_index_u32(),
+            escrow_address,
+        }))
+    }
 
-        // Update existing swap with destination data
-        let updated = self.db
-            .update_fusion_plus_dst(
-                &data.order_hash,
-                &data,
-                self.network.chain_id,
-                &log.transaction_hash,
-                log.block_number_u64(),
-                timestamp,
-                log.log_index_u32(),
-                Some(&log.address),
-            )
-            .await
-            .map_err(|e| format!("DB error: {}", e))?;
+    /// Decode EscrowWithdrawal, matching the swap by the hashlock of its secret
+    fn escrow_withdrawal_change(&self, log: &Log, timestamp: u64) -> Result<FusionPlusChange, String> {
+        let secret = decode_escrow_withdrawal(&log.data)
+            .ok_or_else(|| "Failed to decode EscrowWithdrawal data".to_string())?;
 
-        // Note: swap_type is already set during transfer INSERT (no UPDATE needed)
+        // Compute hashlock from secret: hashlock = keccak256(secret)
+        let hashlock = compute_hashlock_from_secret(&secret)
+            .ok_or_else(|| "Failed to compute hashlock from secret".to_string())?;
 
-        if updated {
-            info!(
-                "[{}] Fusion+ DstEscrow created: order_hash={}",
-                self.network.name, data.order_hash
-            );
-        } else {
-            debug!(
-                "[{}] Fusion+ DstEscrow created for unknown order: {}",
-                self.network.name, data.order_hash
-            );
+        Ok(FusionPlusChange::Withdrawn(FusionPlusWithdrawal {
+            hashlock,
+            escrow_address: log.address.to_lowercase(),
+            chain_id: self.network.chain_id,
+            secret,
+            tx_hash: log.transaction_hash.clone(),
+            block_number: log.block_number_u64(),
+            block_timestamp: timestamp,
+            log_index: log.log_index_u32(),
+            withdrawer: None,
+        }))
+    }
+
+    /// Merkle proof of a source escrow's fill, from the takerInteraction call
+    /// to the factory that validated it in the same transaction
+    ///
+    /// Only orders allowing multiple fills make that call. Nodes without
+    /// debug_traceTransaction turn the lookup off for the rest of the run.
+    async fn merkle_fill(&mut self, swap: &FusionPlusSwap) -> Option<MerkleFill> {
+        if !self.trace_merkle_fills {
+            return None;
+        }
+
+        match self.rpc.debug_trace_transaction(&swap.src_tx_hash).await {
+            Ok(frame) => {
+                let mut inputs = Vec::new();
+                traces::call_inputs(&frame, &self.network.contracts.escrow_factory, &selector(TAKER_INTERACTION_SIG), &mut inputs);
+                let fill = inputs.into_iter().filter_map(decode_taker_interaction).find(|fill| {
+                    fill.order_hash.eq_ignore_ascii_case(&swap.order_hash)
+                        && fill.secret_hash.eq_ignore_ascii_case(&swap.hashlock)
+                })?;
+                if !fill.proof_valid {
+                    warn!(
+                        "[{}] Fusion+ fill {} of {} has a proof that doesn't reach root {}",
+                        self.network.name, fill.index, swap.order_hash, fill.root
+                    );
+                }
+                Some(fill)
+            }
+            Err(e) if e.is_method_unsupported() => {
+                warn!(
+                    "[{}] debug_traceTransaction not supported ({}), storing Fusion+ fills without their Merkle proofs",
+                    self.network.name, e
+                );
+                self.trace_merkle_fills = false;
+                None
+            }
+            Err(e) => {
+                warn!("[{}] Failed to trace Fusion+ fill {}: {}", self.network.name, swap.src_tx_hash, e);
+                None
+            }
         }
-
-        Ok(())
     }
 
-    /// Process EscrowWithdrawal event
-    async fn process_escrow_withdrawal(&self, log: &Log, timestamp: u64) -> Result<(), String> {
-        let secret = decode_escrow_withdrawal(&log.data)
-            .ok_or_else(|| "Failed to decode EscrowWithdrawal data".to_string())?;
-
-        // Compute hashlock from secret: hashlock = keccak256(secret)
-        let hashlock = compute_hashlock_from_secret(&secret)
-            .ok_or_else(|| "Failed to compute hashlock from secret".to_string())?;
-
-        // Look up the swap by hashlock and update its status
-        if let Ok(Some(swap)) = self.db.get_fusion_plus_swap_by_hashlock(&hashlock).await {
-            // Determine if this is src or dst withdrawal based on chain_id
-            let is_src = swap.src_chain_id == self.network.chain_id;
+    /// Sender of a transaction, from its receipt
+    async fn transaction_sender(&self, tx_hash: &str) -> Option<String> {
+        match self.rpc.get_transaction_receipt(tx_hash).await {
+            Ok(receipt) => receipt.from.map(|from| from.to_lowercase()),
+            Err(e) => {
+                warn!("[{}] Failed to get receipt {} for Fusion+ withdrawal: {}", self.network.name, tx_hash, e);
+                None
+            }
+        }
+    }
 
-            // Update the swap status with secret and tx details
-            let updated = self.db
-                .update_fusion_plus_withdrawal_by_hashlock(
-                    &hashlock,
-                    self.network.chain_id,
-                    is_src,
-                    &secret,
-                    &log.transaction_hash,
-                    log.block_number_u64(),
-                    timestamp,
-                    log.log_index_u32(),
-                )
-                .await
-                .map_err(|e| format!("DB error: {}", e))?;
+    /// Log an applied Fusion+ change and publish the updated swap
+    ///
+    /// Note: swap_type is already set during transfer INSERT (no UPDATE needed)
+    async fn report_fusion_plus_change(&self, change: FusionPlusChange, log: &Log, applied: Option<FusionPlusApplied>) {
+        let side = |is_src: bool| if is_src { "source" } else { "destination" };
 
-            if updated {
-                let side = if is_src { "source" } else { "destination" };
+        match (change, applied) {
+            (FusionPlusChange::Created { swap, .. }, Some(_)) => {
+                info!(
+                    "[{}] Fusion+ SrcEscrow created: order_hash={} dst_chain={}",
+                    self.network.name, swap.order_hash, swap.dst_chain_id
+                );
+                self.publish_fusion_plus("src_escrow_created", log, *swap);
+            }
+            (FusionPlusChange::Created { swap, .. }, None) => {
+                debug!("[{}] Fusion+ swap already stored: {}", self.network.name, swap.order_hash);
+            }
+            (FusionPlusChange::DstCreated(dst), Some(_)) => {
+                info!(
+                    "[{}] Fusion+ DstEscrow created: order_hash={}",
+                    self.network.name, dst.order_hash
+                );
+                if let Ok(Some(swap)) = self.lookup_for_handlers(self.db.get_fusion_plus_swap(&dst.order_hash)).await {
+                    self.publish_fusion_plus("dst_escrow_created", log, swap);
+                }
+            }
+            (FusionPlusChange::DstCreated(dst), None) => {
+                debug!(
+                    "[{}] Fusion+ DstEscrow created for unknown order: {}",
+                    self.network.name, dst.order_hash
+                );
+            }
+            (FusionPlusChange::Withdrawn(withdrawal), applied) => {
+                if let Some(applied) = applied {
+                    info!(
+                        "[{}] Fusion+ {} withdrawal: order_hash={} hashlock={} tx={} withdrawer={}",
+                        self.network.name,
+                        side(applied.is_src),
+                        applied.order_hash,
+                        withdrawal.hashlock,
+                        withdrawal.tx_hash,
+                        withdrawal.withdrawer.as_deref().unwrap_or("unknown")
+                    );
+                    if let Ok(Some(swap)) = self.lookup_for_handlers(self.db.get_fusion_plus_swap(&applied.order_hash)).await {
+                        self.publish_fusion_plus("withdrawn", log, swap);
+                    }
+                }
+                debug!(
+                    "[{}] Fusion+ withdrawal from escrow {} with hashlock {}",
+                    self.network.name, log.address, withdrawal.hashlock
+                );
+            }
+            (FusionPlusChange::Cancelled(cancellation), Some(applied)) => {
                 info!(
-                    "[{}] Fusion+ {} withdrawal: order_hash={} secret={} tx={}",
-                    self.network.name, side, swap.order_hash, secret, log.transaction_hash
+                    "[{}] Fusion+ {} escrow cancelled: order_hash={} escrow={} tx={}",
+                    self.network.name, side(applied.is_src), applied.order_hash, log.address, cancellation.tx_hash
+                );
+                if let Ok(Some(swap)) = self.lookup_for_handlers(self.db.get_fusion_plus_swap(&applied.order_hash)).await {
+                    self.publish_fusion_plus("cancelled", log, swap);
+                }
+            }
+            (FusionPlusChange::Cancelled(_), None) => {
+                debug!(
+                    "[{}] Fusion+ escrow cancelled for unknown escrow: {}",
+                    self.network.name, log.address
                 );
             }
         }
-
-        // Note: swap_type is already set during transfer INSERT (no UPDATE needed)
-
-        debug!(
-            "[{}] Fusion+ withdrawal from escrow {} with hashlock {}",
-            self.network.name, log.address, hashlock
-        );
-
-        Ok(())
     }
 
-    /// Process EscrowCancelled event
-    async fn process_escrow_cancelled(&self, log: &Log, _timestamp: u64) -> Result<(), String> {
-        // Note: swap_type is already set during transfer INSERT (no UPDATE needed)
+    /// Run a swap lookup only when a handler wants swap updates
+    async fn lookup_for_handlers<F>(&self, lookup: F) -> Result<Option<FusionPlusSwap>, DbError>
+    where
+        F: std::future::Future<Output = Result<Option<FusionPlusSwap>, DbError>>,
+    {
+        if self.handlers.iter().any(|h| h.is_active()) {
+            lookup.await
+        } else {
+            Ok(None)
+        }
+    }
 
-        debug!(
-            "[{}] Fusion+ escrow cancelled: {}",
-            self.network.name, log.address
-        );
+    fn publish_fusion_plus(&self, stage: &'static str, log: &Log, swap: FusionPlusSwap) {
+        if self.handlers.is_empty() {
+            return;
+        }
+        let update = FusionPlusUpdate {
+            stage,
+            chain_id: self.network.chain_id,
+            tx_hash: log.transaction_hash.clone(),
+            block_number: log.block_number_u64(),
+            swap,
+        };
+        for handler in &self.handlers {
+            handler.on_fusion_plus_update(&update);
+        }
+    }
 
-        Ok(())
+    fn publish_fusion(&self, swap: &FusionSwap) {
+        for handler in &self.handlers {
+            handler.on_fusion_swap(swap);
+        }
     }
 
     // =========================================================================
     // Fusion (Single-Chain) Methods
     // =========================================================================
 
-    /// Process OrderFilled or OrderCancelled event
-    async fn process_order_filled(&self, log: &Log, timestamp: u64, status: &str) -> Result<(), String> {
+    /// Process OrderFilled event
+    async fn process_order_filled(&self, log: &Log, timestamp: u64) -> Result<(), String> {
         let data = decode_order_filled(&log.topics, &log.data)
             .ok_or_else(|| "Failed to decode OrderFilled data".to_string())?;
 
@@ -664,30 +2752,7 @@ impl ChainPoller {
         let remaining_hex = data.remaining.trim_start_matches("0x");
         let is_partial = !remaining_hex.chars().all(|c| c == '0');
 
-        // Get first and last transfers to populate maker/taker info
-        // First transfer = maker sends maker_token (maker = from_addr of first transfer)
-        // Last transfer = taker receives taker_token (taker = to_addr of last transfer)
-        let (maker, taker, maker_token, taker_token, maker_amount, taker_amount) =
-            match self.db.get_first_last_transfers(self.network.chain_id, &log.transaction_hash).await {
-                Ok(Some((first, last))) => {
-                    (
-                        first.from_addr.clone(),         // maker = sender of first transfer
-                        Some(last.to_addr.clone()),      // taker = recipient of last transfer
-                        Some(first.token.clone()),       // maker_token = token of first transfer
-                        Some(last.token.clone()),        // taker_token = token of last transfer
-                        Some(first.value.clone()),       // maker_amount = value of first transfer
-                        Some(last.value.clone()),        // taker_amount = value of last transfer
-                    )
-                }
-                Ok(None) => {
-                    // No transfers found for this tx (shouldn't happen normally)
-                    (String::new(), None, None, None, None, None)
-                }
-                Err(e) => {
-                    warn!("[{}] Failed to get transfers for fusion swap: {}", self.network.name, e);
-                    (String::new(), None, None, None, None, None)
-                }
-            };
+        let details = self.swap_details(&log.transaction_hash).await;
 
         let swap = FusionSwap {
             order_hash: data.order_hash.clone(),
@@ -696,33 +2761,287 @@ impl ChainPoller {
             block_number: log.block_number_u64(),
             block_timestamp: timestamp,
             log_index: log.log_index_u32(),
-            maker,
-            taker,
-            maker_token,
-            taker_token,
-            maker_amount,
-            taker_amount,
+            maker: details.as_ref().map(|d| d.maker.clone()).unwrap_or_default(),
+            taker: details.as_ref().map(|d| d.taker.clone()),
+            maker_token: details.as_ref().map(|d| d.maker_token.clone()),
+            taker_token: details.as_ref().map(|d| d.taker_token.clone()),
+            maker_amount: details.as_ref().map(|d| d.maker_amount.clone()),
+            taker_amount: details.map(|d| d.taker_amount),
             remaining: data.remaining.clone(),
             is_partial_fill: is_partial,
-            status: status.to_string(),
+            status: "filled".to_string(),
+            id: None,
         };
 
         // Insert swap record
-        self.db
-            .insert_fusion_swap(&swap)
-            .await
-            .map_err(|e| format!("DB error: {}", e))?;
+        match &self.dry_run {
+            Some(dry_run) => dry_run.record("fusion_swaps", &swap),
+            None => {
+                self.db
+                    .insert_fusion_swap(&swap)
+                    .await
+                    .map_err(|e| format!("DB error: {}", e))?;
+            }
+        }
 
         // Note: swap_type is already set during transfer INSERT (no UPDATE needed)
 
         info!(
-            "[{}] Fusion {} order: order_hash={} maker={} taker={:?} tx={}",
-            self.network.name, status, data.order_hash, swap.maker, swap.taker, log.transaction_hash
+            "[{}] Fusion filled order: order_hash={} maker={} taker={:?} tx={}",
+            self.network.name, data.order_hash, swap.maker, swap.taker, log.transaction_hash
         );
 
+        self.publish_fusion(&swap);
+
         Ok(())
     }
 
+    /// Maker/taker details of a Fusion fill from its transaction's transfers
+    async fn swap_details(&self, tx_hash: &str) -> Option<SwapDetails> {
+        // Get first and last transfers to populate maker/taker info
+        let details = match self.db.get_first_last_transfers(self.network.chain_id, tx_hash).await {
+            Ok(Some((mut first, mut last))) => {
+                if first.value.is_empty() || last.value.is_empty() {
+                    if let Err(e) = hydrate_transfer_values(&self.rpc, self.quirks.block_receipts, &mut [&mut first, &mut last]).await {
+                        warn!("[{}] Failed to re-fetch transfer values: {}", self.network.name, e);
+                    }
+                }
+                Some(SwapDetails::from_transfers(&first, &last))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("[{}] Failed to get transfers for fusion swap: {}", self.network.name, e);
+                None
+            }
+        };
+
+        // The tx's transfers may not be stored (yet): they can land in a later
+        // batch or be dropped by the watchlist. Read them from the receipt.
+        match details {
+            Some(details) => Some(details),
+            None => match self.rpc.get_transaction_receipt(tx_hash).await {
+                Ok(receipt) => SwapDetails::from_logs(&receipt.logs),
+                Err(e) => {
+                    warn!("[{}] Failed to get receipt {} for fusion swap: {}", self.network.name, tx_hash, e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// Retry enrichment of Fusion swaps stored without maker/token details
+    ///
+    /// Swaps older than the retry window, or that used up their attempts, are
+    /// marked failed and not retried again. Returns the number enriched.
+    async fn retry_swap_enrichment(&self, retry: &EnrichRetry) -> Result<usize, String> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let since = now.saturating_sub(retry.window_secs);
+
+        let expired = self.db
+            .expire_fusion_enrichment(self.network.chain_id, since)
+            .await
+            .map_err(|e| format!("DB error: {}", e))?;
+        if expired > 0 {
+            warn!("[{}] Gave up enriching {} Fusion swaps older than the retry window", self.network.name, expired);
+        }
+
+        let pending = self.db
+            .get_pending_fusion_enrichment(self.network.chain_id, since, ENRICH_BATCH_SIZE)
+            .await
+            .map_err(|e| format!("DB error: {}", e))?;
+
+        let mut enriched = 0;
+        for swap in pending {
+            let Some(id) = swap.id else { continue };
+
+            let Some(details) = self.swap_details(&swap.tx_hash).await else {
+                let gave_up = self.db
+                    .record_fusion_enrich_attempt(id, retry.max_attempts)
+                    .await
+                    .map_err(|e| format!("DB error: {}", e))?;
+                if gave_up {
+                    warn!(
+                        "[{}] Gave up enriching Fusion swap {} after {} attempts (tx={})",
+                        self.network.name, swap.order_hash, retry.max_attempts, swap.tx_hash
+                    );
+                }
+                continue;
+            };
+
+            let updated = self.db
+                .complete_fusion_enrichment(
+                    id,
+                    &details.maker,
+                    &details.taker,
+                    (&details.maker_token, &details.taker_token),
+                    (&details.maker_amount, &details.taker_amount),
+                )
+                .await
+                .map_err(|e| format!("DB error: {}", e))?;
+
+            if let Some(updated) = updated {
+                enriched += 1;
+                debug!(
+                    "[{}] Enriched Fusion swap {}: maker={} taker={:?}",
+                    self.network.name, updated.order_hash, updated.maker, updated.taker
+                );
+                self.publish_fusion(&updated);
+            }
+        }
+
+        Ok(enriched)
+    }
+
+    /// Process OrderCancelled event
+    ///
+    /// Moves the order's stored fill to `cancelled`. Returns false for orders
+    /// cancelled without a stored fill, which have no row to update.
+    async fn process_order_cancelled(&self, log: &Log) -> Result<bool, String> {
+        let order_hash = decode_order_cancelled(&log.topics, &log.data)
+            .ok_or_else(|| "Failed to decode OrderCancelled data".to_string())?;
+
+        if let Some(dry_run) = &self.dry_run {
+            let cancellation = json!({
+                "chain_id": self.network.chain_id,
+                "order_hash": order_hash,
+                "tx_hash": log.transaction_hash,
+                "block_number": log.block_number_u64(),
+            });
+            dry_run.record("fusion_swap_cancellations", &cancellation);
+            return Ok(false);
+        }
+
+        let swap = self
+            .db
+            .cancel_fusion_swap(self.network.chain_id, &order_hash, &log.transaction_hash, log.block_number_u64())
+            .await
+            .map_err(|e| format!("DB error: {}", e))?;
+
+        let Some(swap) = swap else {
+            debug!(
+                "[{}] Fusion order {} cancelled without a stored fill, tx={}",
+                self.network.name, order_hash, log.transaction_hash
+            );
+            return Ok(false);
+        };
+
+        info!(
+            "[{}] Fusion cancelled order: order_hash={} maker={} tx={}",
+            self.network.name, order_hash, swap.maker, log.transaction_hash
+        );
+
+        self.publish_fusion(&swap);
+
+        Ok(true)
+    }
+
+    /// Build the shared context for a batch covering `from_block..=to_block`
+    ///
+    /// Timestamps of the batch's blocks come from the cache where possible;
+    /// the rest are fetched concurrently and cached.
+    async fn poll_context(
+        &mut self,
+        head: u64,
+        (from_block, to_block): (u64, u64),
+        batch: &LogBatch,
+    ) -> Result<PollContext, String> {
+        let blocks: BTreeSet<u64> = batch
+            .logs()
+            .map(|log| log.block_number_u64())
+            .chain(batch.native_transfers.iter().map(|t| t.block_number_u64()))
+            .chain(batch.internal_transfers.iter().map(|t| t.block_number))
+            .collect();
+        let missing: Vec<u64> = blocks
+            .iter()
+            .copied()
+            .filter(|block| !self.block_timestamp_cache.contains_key(block))
+            .collect();
+
+        let rpc = &self.rpc;
+        let fetched: Vec<(u64, u64)> = stream::iter(missing)
+            .map(|block_number| async move {
+                rpc.get_block(block_number)
+                    .await
+                    .map(|block| (block_number, block.timestamp_u64()))
+                    .map_err(|e| format!("Failed to get block {}: {}", block_number, e))
+            })
+            .buffer_unordered(TIMESTAMP_FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        self.block_timestamp_cache.extend(fetched);
+
+        let timestamps = blocks
+            .into_iter()
+            .map(|block| (block, self.block_timestamp_cache[&block]))
+            .collect();
+
+        let tx_status = match self.config.tx_status {
+            Some(_) => self.fetch_tx_status(batch.txs_by_block()).await?,
+            None => HashMap::new(),
+        };
+
+        Ok(PollContext {
+            head,
+            from_block,
+            to_block,
+            timestamps,
+            tx_status,
+        })
+    }
+
+    /// Whether each transaction in `txs` succeeded, from its receipt
+    ///
+    /// Reads whole blocks' receipts where the chain serves eth_getBlockReceipts,
+    /// falling back to one request per transaction. Receipts from before
+    /// Byzantium have no status and count as succeeded.
+    async fn fetch_tx_status(&self, txs: BTreeMap<u64, HashSet<String>>) -> Result<HashMap<String, bool>, String> {
+        let succeeded = |receipt: &TransactionReceipt| !matches!(receipt.status.as_deref(), Some("0x0"));
+        let rpc = &self.rpc;
+        let name = &self.network.name;
+        let block_receipts = self.quirks.block_receipts;
+
+        let blocks: Vec<Vec<(String, bool)>> = stream::iter(txs)
+            .map(|(block_number, hashes)| async move {
+                if block_receipts {
+                    match rpc.get_block_receipts(block_number).await {
+                        Ok(receipts) => {
+                            let statuses: HashMap<String, bool> = receipts
+                                .iter()
+                                .map(|receipt| (receipt.transaction_hash.to_lowercase(), succeeded(receipt)))
+                                .collect();
+                            let found: Option<Vec<(String, bool)>> =
+                                hashes.iter().map(|hash| Some((hash.clone(), *statuses.get(hash)?))).collect();
+                            if let Some(found) = found {
+                                return Ok(found);
+                            }
+                        }
+                        Err(e) => debug!(
+                            "[{}] Failed to get receipts of block {}, fetching per transaction: {}",
+                            name, block_number, e
+                        ),
+                    }
+                }
+
+                let mut statuses = Vec::with_capacity(hashes.len());
+                for hash in hashes {
+                    let receipt = rpc
+                        .get_transaction_receipt(&hash)
+                        .await
+                        .map_err(|e| format!("Failed to get receipt {}: {}", hash, e))?;
+                    statuses.push((hash, succeeded(&receipt)));
+                }
+                Ok::<_, String>(statuses)
+            })
+            .buffer_unordered(RECEIPT_FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(blocks.into_iter().flatten().collect())
+    }
+
     /// Get block timestamp with caching
     async fn get_block_timestamp(&mut self, block_number: u64) -> Result<u64, String> {
         // Check cache first
@@ -774,10 +3093,15 @@ impl ChainPoller {
         event.log_index = log.log_index_u32();
 
         // Insert the event
-        self.db
-            .insert_crypto2fiat_event(&event)
-            .await
-            .map_err(|e| format!("DB error: {}", e))?;
+        match &self.dry_run {
+            Some(dry_run) => dry_run.record("crypto2fiat_events", &event),
+            None => {
+                self.db
+                    .insert_crypto2fiat_event(&event)
+                    .await
+                    .map_err(|e| format!("DB error: {}", e))?;
+            }
+        }
 
         // Note: swap_type is already set during transfer INSERT (no UPDATE needed)
 
@@ -789,3 +3113,555 @@ impl ChainPoller {
         Ok(())
     }
 }
+
+/// Parse a 32-byte ABI word (e.g. a Transfer's data) as a decimal string
+///
+/// Returns None for empty (compact mode) or malformed data.
+pub fn decode_uint256(data: &str) -> Option<String> {
+    let hex = data.strip_prefix("0x").unwrap_or(data);
+    if hex.is_empty() || hex.len() > 64 {
+        return None;
+    }
+    U256::from_str_radix(hex, 16).ok().map(|v| v.to_string())
+}
+
+/// An Approval's amount: its data as exactly one 32-byte word, lowercased
+///
+/// Anything else (empty, short or extra words, non-hex) is not an ERC-20
+/// Approval and would be stored as a bogus amount.
+fn approval_amount(data: &str) -> Option<String> {
+    let hex = data.strip_prefix("0x")?;
+    (hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| data.to_lowercase())
+}
+
+/// Sender and recipient of a Transfer log, or of a WETH Deposit (minted from
+/// the zero address) or Withdrawal (burned to it)
+///
+/// Returns None for logs without the indexed addresses, such as malformed
+/// Transfers.
+pub fn transfer_parties(log: &Log) -> Option<(String, String)> {
+    let address = |topic: &String| format!("0x{}", &topic[topic.len().saturating_sub(40)..]).to_lowercase();
+    let topic0 = log.topics.first()?.to_lowercase();
+
+    if topic0 == TRANSFER_TOPIC && log.topics.len() >= 3 {
+        Some((address(&log.topics[1]), address(&log.topics[2])))
+    } else if topic0 == WETH_DEPOSIT_TOPIC && log.topics.len() == 2 {
+        Some((ZERO_ADDRESS.to_string(), address(&log.topics[1])))
+    } else if topic0 == WETH_WITHDRAWAL_TOPIC && log.topics.len() == 2 {
+        Some((address(&log.topics[1]), ZERO_ADDRESS.to_string()))
+    } else {
+        None
+    }
+}
+
+/// Delegate address from an EIP-7702 delegation designator (`0xef0100 || address`)
+///
+/// Returns None for any other code, including an empty (undelegated) account.
+pub fn delegate_from_code(code: &str) -> Option<String> {
+    let code = code.strip_prefix("0x").unwrap_or(code).to_lowercase();
+    let address = code.strip_prefix("ef0100")?;
+    (address.len() == 40 && address.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("0x{}", address))
+}
+
+/// Maker and taker side of a Fusion fill
+///
+/// The first Transfer of the tx is the maker sending maker_token; the last is
+/// the taker receiving taker_token.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SwapDetails {
+    pub maker: String,
+    pub taker: String,
+    pub maker_token: String,
+    pub taker_token: String,
+    pub maker_amount: String,
+    pub taker_amount: String,
+}
+
+impl SwapDetails {
+    fn from_transfers(first: &Transfer, last: &Transfer) -> Self {
+        Self {
+            maker: first.from_addr.clone(),
+            taker: last.to_addr.clone(),
+            maker_token: first.token.clone(),
+            taker_token: last.token.clone(),
+            maker_amount: first.value.clone(),
+            taker_amount: last.value.clone(),
+        }
+    }
+
+    /// From a transaction receipt's logs; None if it has no ERC20 Transfer
+    pub fn from_logs(logs: &[Log]) -> Option<Self> {
+        let transfers: Vec<&Log> = logs
+            .iter()
+            .filter(|l| l.topics.len() == 3 && l.topics[0].eq_ignore_ascii_case(TRANSFER_TOPIC))
+            .collect();
+        let first = transfers.iter().min_by_key(|l| l.log_index_u32())?;
+        let last = transfers.iter().max_by_key(|l| l.log_index_u32())?;
+
+        Some(Self {
+            maker: format!("0x{}", &first.topics[1][26..]).to_lowercase(),
+            taker: format!("0x{}", &last.topics[2][26..]).to_lowercase(),
+            maker_token: first.address.to_lowercase(),
+            taker_token: last.address.to_lowercase(),
+            maker_amount: first.data.clone(),
+            taker_amount: last.data.clone(),
+        })
+    }
+}
+
+/// Re-fetch values for transfers stored in compact mode
+///
+/// Fetches each block's receipts once (or each transaction's, on chains
+/// without eth_getBlockReceipts) and copies the log data of the matching
+/// log_index into the transfer's value. Also used by the API to fill the
+/// transfers it returns.
+pub async fn hydrate_transfer_values(
+    rpc: &RpcClient,
+    block_receipts: bool,
+    transfers: &mut [&mut Transfer],
+) -> Result<(), String> {
+    let mut receipts: HashMap<String, Vec<Log>> = HashMap::new();
+    let mut fetched_blocks = HashSet::new();
+
+    for transfer in transfers.iter_mut() {
+        if !transfer.value.is_empty() {
+            continue;
+        }
+
+        let tx_hash = transfer.tx_hash.to_lowercase();
+        if !receipts.contains_key(&tx_hash) && block_receipts && fetched_blocks.insert(transfer.block_number) {
+            match rpc.get_block_receipts(transfer.block_number).await {
+                Ok(block_receipts) => receipts.extend(
+                    block_receipts.into_iter().map(|r| (r.transaction_hash.to_lowercase(), r.logs)),
+                ),
+                Err(e) => debug!(
+                    "[{}] Failed to get receipts of block {}, fetching per transaction: {}",
+                    rpc.chain_name(), transfer.block_number, e
+                ),
+            }
+        }
+        if !receipts.contains_key(&tx_hash) {
+            let receipt = rpc
+                .get_transaction_receipt(&tx_hash)
+                .await
+                .map_err(|e| format!("Failed to get receipt {}: {}", tx_hash, e))?;
+            receipts.insert(tx_hash.clone(), receipt.logs);
+        }
+
+        if let Some(log) = receipts[&tx_hash]
+            .iter()
+            .find(|l| l.log_index_u32() == transfer.log_index)
+        {
+            transfer.value = log.data.clone();
+            transfer.value_decimal = decode_uint256(&log.data);
+        }
+    }
+
+    Ok(())
+}
+
+/// Treat a failed swap-event query as empty, except for range errors which
+/// the adaptive fetch needs to see and an open circuit, which fails the poll
+fn empty_unless_range_error(e: RpcError) -> Result<Vec<Log>, RpcError> {
+    match e {
+        RpcError::RangeTooLarge(_) | RpcError::CircuitOpen(_) | RpcError::CircuitHalfOpen => Err(e),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Receive the next live event, or wait forever when no stream is attached
+async fn recv_live(live_logs: &mut Option<mpsc::Receiver<LiveEvent>>) -> Option<LiveEvent> {
+    match live_logs {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Collapse ascending block numbers into inclusive runs of consecutive blocks
+fn block_runs(blocks: impl IntoIterator<Item = u64>) -> Vec<(u64, u64)> {
+    let mut runs: Vec<(u64, u64)> = Vec::new();
+    for block in blocks {
+        match runs.last_mut() {
+            Some((_, last)) if block <= *last + 1 => *last = (*last).max(block),
+            _ => runs.push((block, block)),
+        }
+    }
+    runs
+}
+
+/// Whether two logs are the same log of the same transaction
+fn same_log(a: &Log, b: &Log) -> bool {
+    a.transaction_hash.eq_ignore_ascii_case(&b.transaction_hash) && a.log_index_u32() == b.log_index_u32()
+}
+
+/// Drop a held live log, returning whether it was held
+fn remove_pending(pending: &mut Vec<Log>, log: &Log) -> bool {
+    let before = pending.len();
+    pending.retain(|held| !same_log(held, log));
+    pending.len() < before
+}
+
+/// Take the held live logs at or below `settled`, keeping the rest held
+fn take_settled(pending: &mut Vec<Log>, settled: u64) -> Vec<Log> {
+    let (ready, held) = std::mem::take(pending)
+        .into_iter()
+        .partition(|log| log.block_number_u64() <= settled);
+    *pending = held;
+    ready
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_uint256() {
+        assert_eq!(
+            decode_uint256("0x00000000000000000000000000000000000000000000000000000000000003e8").as_deref(),
+            Some("1000")
+        );
+        assert_eq!(
+            decode_uint256("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").as_deref(),
+            Some("115792089237316195423570985008687907853269984665640564039457584007913129639935")
+        );
+        assert_eq!(decode_uint256(""), None);
+        assert_eq!(decode_uint256("0xzz"), None);
+        assert_eq!(decode_uint256(&format!("0x{}", "0".repeat(66))), None);
+    }
+
+    #[test]
+    fn test_approval_amount() {
+        let word = format!("0x{}", "0".repeat(62) + "Ff");
+        assert_eq!(approval_amount(&word), Some(word.to_lowercase()));
+        assert_eq!(approval_amount(UNLIMITED_APPROVAL).as_deref(), Some(UNLIMITED_APPROVAL));
+
+        assert_eq!(approval_amount(""), None);
+        assert_eq!(approval_amount("0x"), None);
+        assert_eq!(approval_amount(&word[2..]), None); // No 0x prefix
+        assert_eq!(approval_amount(&word[..65]), None); // Short word
+        assert_eq!(approval_amount(&format!("{}{}", word, "0".repeat(64))), None); // Two words
+        assert_eq!(approval_amount(&format!("0x{}", "g".repeat(64))), None);
+    }
+
+    #[test]
+    fn test_delegate_from_code() {
+        assert_eq!(
+            delegate_from_code("0xef010063C0C19A282A1B52B07DD5A65B58948A07DAE32B").as_deref(),
+            Some("0x63c0c19a282a1b52b07dd5a65b58948a07dae32b")
+        );
+        assert_eq!(delegate_from_code("0x"), None);
+        // Regular contract code
+        assert_eq!(delegate_from_code("0x6080604052"), None);
+        assert_eq!(delegate_from_code("0xef0100abcd"), None);
+    }
+
+    #[test]
+    fn test_transfer_parties() {
+        let log = |topics: &[&str]| Log {
+            address: "0x4200000000000000000000000000000000000006".to_string(),
+            topics: topics.iter().map(|t| t.to_string()).collect(),
+            data: format!("0x{:064x}", 1000),
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: "0x0".to_string(),
+        };
+        let alice = "0x000000000000000000000000A11CE00000000000000000000000000000000001";
+        let bob = "0x000000000000000000000000b0b0000000000000000000000000000000000002";
+
+        assert_eq!(
+            transfer_parties(&log(&[TRANSFER_TOPIC, alice, bob])),
+            Some((
+                "0xa11ce00000000000000000000000000000000001".to_string(),
+                "0xb0b0000000000000000000000000000000000002".to_string()
+            ))
+        );
+        assert_eq!(
+            transfer_parties(&log(&[WETH_DEPOSIT_TOPIC, alice])),
+            Some((ZERO_ADDRESS.to_string(), "0xa11ce00000000000000000000000000000000001".to_string()))
+        );
+        assert_eq!(
+            transfer_parties(&log(&[WETH_WITHDRAWAL_TOPIC, bob])),
+            Some(("0xb0b0000000000000000000000000000000000002".to_string(), ZERO_ADDRESS.to_string()))
+        );
+        assert_eq!(transfer_parties(&log(&[TRANSFER_TOPIC, alice])), None);
+        assert_eq!(transfer_parties(&log(&[APPROVAL_TOPIC, alice, bob])), None);
+
+        let topic = |signature: &str| format!("{:#x}", alloy_primitives::keccak256(signature));
+        assert_eq!(topic("Deposit(address,uint256)"), WETH_DEPOSIT_TOPIC);
+        assert_eq!(topic("Withdrawal(address,uint256)"), WETH_WITHDRAWAL_TOPIC);
+    }
+
+    #[test]
+    fn test_retain_new_leaves_marking_to_mark_seen() {
+        let log = |log_index: u32| Log {
+            address: "0x4200000000000000000000000000000000000006".to_string(),
+            topics: vec![TRANSFER_TOPIC.to_string()],
+            data: "0x".to_string(),
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: format!("0x{:x}", log_index),
+        };
+        let batch = || LogBatch { transfers: vec![log(1), log(2)], ..Default::default() };
+        let mut dedup = LogDeduplicator::new(10);
+
+        // A batch that fails after retain_new must be retried in full
+        let mut first = batch();
+        assert_eq!(first.retain_new(&dedup), 0);
+        let mut retry = batch();
+        assert_eq!(retry.retain_new(&dedup), 0);
+
+        retry.mark_seen(&mut dedup);
+        let mut again = batch();
+        assert_eq!(again.retain_new(&dedup), 2);
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn test_block_runs() {
+        assert_eq!(block_runs([]), []);
+        assert_eq!(block_runs([7]), [(7, 7)]);
+        // Several logs per block, gaps split the runs
+        assert_eq!(block_runs([5, 5, 6, 8, 8, 9, 10, 20]), [(5, 6), (8, 10), (20, 20)]);
+    }
+
+    #[test]
+    fn test_live_logs_held_until_settled() {
+        let log = |block: u64, tx_hash: &str| Log {
+            address: "0x4200000000000000000000000000000000000006".to_string(),
+            topics: vec![TRANSFER_TOPIC.to_string()],
+            data: "0x".to_string(),
+            block_number: format!("0x{:x}", block),
+            transaction_hash: tx_hash.to_string(),
+            log_index: "0x0".to_string(),
+        };
+        let mut pending = vec![log(10, "0xa"), log(11, "0xb"), log(12, "0xc")];
+
+        // A reorged-out log is dropped while still held, matched case-insensitively
+        assert!(remove_pending(&mut pending, &log(11, "0xB")));
+        assert!(!remove_pending(&mut pending, &log(11, "0xb")));
+
+        let ready = take_settled(&mut pending, 10);
+        assert_eq!(ready.iter().map(|l| l.transaction_hash.as_str()).collect::<Vec<_>>(), ["0xa"]);
+        assert_eq!(pending.len(), 1);
+        assert!(take_settled(&mut pending, 11).is_empty());
+        assert_eq!(take_settled(&mut pending, 12).len(), 1);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_retain_unprocessed_swaps() {
+        let log = |topic0: &str, log_index: u32| Log {
+            address: "0x111111125421ca6dc452d289314280a0f8842a65".to_string(),
+            topics: vec![topic0.to_string()],
+            data: "0x".to_string(),
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: format!("0x{:x}", log_index),
+        };
+        let mut processed = LogDeduplicator::new(10);
+        processed.insert(&log(ORDER_FILLED_TOPIC, 1));
+
+        let mut batch = LogBatch {
+            fusion: vec![log(ORDER_FILLED_TOPIC, 1), log(ORDER_FILLED_TOPIC, 2)],
+            transfers: vec![log(TRANSFER_TOPIC, 1)],
+            ..Default::default()
+        };
+        assert_eq!(batch.retain_unprocessed_swaps(&processed), 1);
+        assert_eq!(batch.fusion.len(), 1);
+        assert_eq!(batch.fusion[0].log_index_u32(), 2);
+        // Transfers are cheap to re-insert and left alone
+        assert_eq!(batch.transfers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_head_failover() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        // Both endpoints serve block 100; the first one's is a day old
+        let block = |age_secs: u64| {
+            move |_| async move {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": { "number": "0x64", "timestamp": format!("0x{:x}", now - age_secs) },
+                })
+            }
+        };
+        let stale = crate::rpc::mock_rpc(block(86_400)).await;
+        let fresh = crate::rpc::mock_rpc(block(5)).await;
+        let contents = format!(
+            "[[networks]]\nchain_id = 990012\nname = \"Test\"\nrpc_url = \"{}\"\nfallback_rpc_urls = [\"{}\"]",
+            stale, fresh
+        );
+        let network = crate::config::networks_from_toml(&contents, None, &|_| None).unwrap().remove(0);
+        let config = PollerConfig {
+            stale_head: Some(StaleHead { max_age_secs: 300, failover: true }),
+            ..Default::default()
+        };
+        let health = Arc::new(HealthRegistry::new(60));
+        let mut poller = ChainPoller::with_config(network, Arc::new(db), config).with_health(Arc::clone(&health));
+        let stale_head = || health.report().iter().find(|c| c.chain_id == 990_012).is_some_and(|c| c.stale_head);
+
+        // The first endpoint's head block is far behind the wall clock
+        poller.check_head(100).await;
+        assert!(stale_head());
+        assert_eq!(poller.rpc.url(), fresh);
+
+        // The fallback is judged from scratch and clears the stale flag
+        poller.block_timestamp_cache.clear();
+        poller.check_head(100).await;
+        assert!(!stale_head());
+        assert_eq!(poller.rpc.url(), fresh);
+    }
+
+    #[tokio::test]
+    async fn test_trace_errors() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        let db = Arc::new(db);
+        let address = "0x4611000000000000000000000000000000004611";
+        let watchlist = Arc::new(Watchlist::load(Arc::clone(&db)).await.unwrap());
+        watchlist.add(address, None).await.unwrap();
+
+        // A poller whose node answers eth_getLogs but fails trace_filter with `error`
+        let poller = |error: serde_json::Value| {
+            let (db, watchlist) = (Arc::clone(&db), Arc::clone(&watchlist));
+            async move {
+                let url = crate::rpc::mock_rpc(move |request: serde_json::Value| {
+                    let error = error.clone();
+                    async move {
+                        match request["method"].as_str() {
+                            Some("eth_getLogs") => json!({ "jsonrpc": "2.0", "id": request["id"], "result": [] }),
+                            _ => json!({ "jsonrpc": "2.0", "id": request["id"], "error": error }),
+                        }
+                    }
+                })
+                .await;
+                let contents = format!("[[networks]]\nchain_id = 990013\nname = \"Test\"\nrpc_url = \"{}\"", url);
+                let network = crate::config::networks_from_toml(&contents, None, &|_| None).unwrap().remove(0);
+                let config = PollerConfig { internal_transfers: Some(TraceMethod::TraceFilter), ..Default::default() };
+                ChainPoller::with_config(network, db, config).with_trace_watchlist(watchlist)
+            }
+        };
+
+        // An unsupported method: the batch is fetched without internal transfers and tracing stays off
+        let mut unsupported = poller(json!({
+            "code": -32601,
+            "message": "the method trace_filter does not exist/is not available",
+        }))
+        .await;
+        let (batch, end_block) = unsupported.fetch_batch_adaptive(100, 109).await.unwrap();
+        assert!(batch.internal_transfers.is_empty());
+        assert_eq!(end_block, 109);
+        assert!(unsupported.config.internal_transfers.is_none());
+
+        // Any other error fails the batch, to be retried with tracing still on
+        let mut failing = poller(json!({ "code": -32000, "message": "request timed out" })).await;
+        assert!(failing.fetch_batch_adaptive(100, 109).await.is_err());
+        assert_eq!(failing.config.internal_transfers, Some(TraceMethod::TraceFilter));
+
+        watchlist.remove(address).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_swap_logs_are_retried() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        let contents = "[[networks]]\nchain_id = 990009\nname = \"Test\"\nrpc_url = \"http://127.0.0.1:1\"";
+        let network = crate::config::networks_from_toml(contents, None, &|_| None).unwrap().remove(0);
+        let mut poller = ChainPoller::with_config(network, Arc::new(db), PollerConfig::default())
+            .with_dry_run(Arc::new(DryRun::new(Box::new(std::io::sink()))));
+
+        let word = |n: u64| format!("{:064x}", n);
+        let log = |data: String, log_index: u32| Log {
+            address: "0x3c1d5e7f9a1b3c5d7e9f1a3b5c7d9e1f3a5b7c9d".to_string(),
+            topics: vec![CRYPTO2FIAT_TOPIC.to_string(), format!("0x{}", word(1)), format!("0x{}", word(2)), format!("0x{}", word(3))],
+            data,
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: format!("0x{:x}", log_index),
+        };
+        let decoded = log(format!("0x{}{}{}", word(1000), word(0x40), word(0)), 1);
+        let undecodable = log("0x".to_string(), 2);
+        let ctx = PollContext {
+            head: 0x10,
+            from_block: 0x10,
+            to_block: 0x10,
+            timestamps: HashMap::from([(0x10, 1_000)]),
+            tx_status: HashMap::new(),
+        };
+
+        let logs = [decoded.clone(), undecodable.clone()];
+        assert_eq!(poller.process_crypto2fiat_logs(&logs, &ctx).await.unwrap(), 1);
+        assert!(poller.processed_swaps.contains(&decoded));
+        assert!(!poller.processed_swaps.contains(&undecodable));
+    }
+
+    #[test]
+    fn test_retain_txs() {
+        let log = |tx_hash: &str, block: u64| Log {
+            address: "0x4200000000000000000000000000000000000006".to_string(),
+            topics: vec![TRANSFER_TOPIC.to_string()],
+            data: "0x".to_string(),
+            block_number: format!("0x{:x}", block),
+            transaction_hash: tx_hash.to_string(),
+            log_index: "0x0".to_string(),
+        };
+        let mut batch = LogBatch {
+            transfers: vec![log("0xAA", 16), log("0xbb", 16)],
+            approvals: vec![log("0xcc", 17)],
+            watched: vec![("label".to_string(), log("0xbb", 16))],
+            ..Default::default()
+        };
+
+        let txs = batch.txs_by_block();
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[&16], HashSet::from(["0xaa".to_string(), "0xbb".to_string()]));
+
+        assert_eq!(batch.retain_txs(|tx_hash| tx_hash != "0xbb"), 2);
+        assert_eq!(batch.transfers.len(), 1);
+        assert_eq!(batch.approvals.len(), 1);
+        assert!(batch.watched.is_empty());
+    }
+
+    #[test]
+    fn test_swap_details_from_logs() {
+        let word = |addr: &str| format!("0x{:0>64}", addr.trim_start_matches("0x"));
+        let log = |address: &str, topics: Vec<String>, data: &str, log_index: &str| Log {
+            address: address.to_string(),
+            topics,
+            data: data.to_string(),
+            block_number: "0x10".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            log_index: log_index.to_string(),
+        };
+        let transfer = |token: &str, from: &str, to: &str, value: &str, log_index: &str| {
+            log(token, vec![TRANSFER_TOPIC.to_string(), word(from), word(to)], value, log_index)
+        };
+
+        let logs = vec![
+            transfer("0xBBBB", "0x2222", "0x1111", "0x02", "0x5"),
+            // ERC721 Transfer (tokenId indexed) is not a swap leg
+            log("0xcccc", vec![TRANSFER_TOPIC.to_string(), word("0x1"), word("0x2"), word("0x3")], "0x", "0x9"),
+            transfer("0xAAAA", "0x1111", "0x2222", "0x01", "0x1"),
+            log("0xdddd", vec![word("0xfeed")], "0x", "0x0"),
+        ];
+
+        assert_eq!(
+            SwapDetails::from_logs(&logs),
+            Some(SwapDetails {
+                maker: "0x0000000000000000000000000000000000001111".to_string(),
+                taker: "0x0000000000000000000000000000000000001111".to_string(),
+                maker_token: "0xaaaa".to_string(),
+                taker_token: "0xbbbb".to_string(),
+                maker_amount: "0x01".to_string(),
+                taker_amount: "0x02".to_string(),
+            })
+        );
+        assert_eq!(SwapDetails::from_logs(&logs[3..]), None);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::types::{Finality, AGGREGATION_ROUTER_V6, AGGREGATION_ROUTER_ZKSYNC, MULTICALL3, MULTICALL3_ZKSYNC};
+
+/// Chain-specific behaviour of the built-in chains
+///
+/// Pollers and config defaults read these flags instead of testing chain IDs,
+/// so every per-chain special case lives in `CHAIN_QUIRKS`. Chains not
+/// listed behave like a standard EVM chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainQuirks {
+    /// Default finality; `None` counts confirmations
+    pub finality: Option<Finality>,
+    /// Default Aggregation Router emitting Fusion events
+    pub aggregation_router: &'static str,
+    /// Nodes serve `eth_getBlockReceipts`, so a block's receipts take one call
+    /// instead of one per transaction
+    pub block_receipts: bool,
+    /// Default Multicall3 deployment
+    pub multicall3: &'static str,
+}
+
+const STANDARD: ChainQuirks = ChainQuirks {
+    finality: None,
+    aggregation_router: AGGREGATION_ROUTER_V6,
+    block_receipts: true,
+    multicall3: MULTICALL3,
+};
+
+/// Chains with fast finality track the `finalized` tag; Ethereum (and
+/// Sepolia) wait for `safe`. Rollups count confirmations since their tags
+/// follow L1 and lag minutes behind the head. zkSync Era has its own router
+/// and Multicall3 address, and its nodes don't serve `eth_getBlockReceipts`.
+const CHAIN_QUIRKS: &[(u32, ChainQuirks)] = &[
+    (1, ChainQuirks { finality: Some(Finality::Safe), ..STANDARD }),
+    (137, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+    (56, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+    (43114, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+    (146, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+    (
+        324,
+        ChainQuirks {
+            aggregation_router: AGGREGATION_ROUTER_ZKSYNC,
+            block_receipts: false,
+            multicall3: MULTICALL3_ZKSYNC,
+            ..STANDARD
+        },
+    ),
+    (11155111, ChainQuirks { finality: Some(Finality::Safe), ..STANDARD }),
+    (80002, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+    (97, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+    (43113, ChainQuirks { finality: Some(Finality::Finalized), ..STANDARD }),
+];
+
+impl ChainQuirks {
+    pub fn for_chain(chain_id: u32) -> Self {
+        CHAIN_QUIRKS
+            .iter()
+            .find(|(id, _)| *id == chain_id)
+            .map_or(STANDARD, |(_, quirks)| *quirks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_chain() {
+        assert_eq!(ChainQuirks::for_chain(31337), STANDARD);
+        assert_eq!(ChainQuirks::for_chain(1).finality, Some(Finality::Safe));
+
+        let zksync = ChainQuirks::for_chain(324);
+        assert_eq!(zksync.aggregation_router, AGGREGATION_ROUTER_ZKSYNC);
+        assert!(!zksync.block_receipts);
+        assert_eq!(zksync.multicall3, MULTICALL3_ZKSYNC);
+        assert_eq!(zksync.finality, None);
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+/// Token bucket limiting requests per second
+///
+/// The bucket holds up to one second worth of requests. Callers that find it
+/// empty reserve a token anyway and sleep until it is due, so requests queue
+/// in arrival order instead of failing.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// Available tokens; negative while requests are queued
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64) -> Self {
+        assert!(requests_per_second > 0.0, "rate limit must be positive");
+        Self {
+            requests_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: Self::capacity(requests_per_second),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+
+    /// Take a token at `now`, returning how long to wait before using it
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second)
+            .min(Self::capacity(self.requests_per_second));
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+
+    /// Burst size: one second of requests, at least one
+    fn capacity(requests_per_second: f64) -> f64 {
+        requests_per_second.max(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_queues_past_burst() {
+        let limiter = RateLimiter::new(4.0);
+        let start = Instant::now();
+
+        // A full bucket allows a one-second burst
+        for _ in 0..4 {
+            assert_eq!(limiter.reserve(start), Duration::ZERO);
+        }
+
+        // Then requests are spaced 250ms apart
+        assert_eq!(limiter.reserve(start), Duration::from_millis(250));
+        assert_eq!(limiter.reserve(start), Duration::from_millis(500));
+
+        // Refill pays back the queue before new tokens are available
+        let later = start + Duration::from_millis(250);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(later + Duration::from_secs(5)), Duration::ZERO);
+    }
+}
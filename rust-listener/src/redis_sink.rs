@@ -0,0 +1,63 @@
+use crate::config::RedisConfig;
+use crate::events::{EventBus, IndexedEvent};
+use redis::aio::ConnectionManager;
+use redis::streams::StreamMaxlen;
+use redis::AsyncCommands;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, warn};
+
+/// Stream key for a chain
+fn stream_key(prefix: &str, chain_id: u32) -> String {
+    format!("{}:{}", prefix, chain_id)
+}
+
+/// Tail the event bus and XADD each event to its chain's stream
+///
+/// Entries carry two fields: `type` (transfer, fusion_plus, fusion) and
+/// `data` (the event as JSON). Write failures are logged and the event is
+/// dropped; the connection manager reconnects on the next write.
+pub async fn run(config: RedisConfig, events: Arc<EventBus>) -> Result<(), String> {
+    let client = redis::Client::open(config.url.as_str())
+        .map_err(|e| format!("Invalid REDIS_URL: {}", e))?;
+    let mut conn = ConnectionManager::new(client)
+        .await
+        .map_err(|e| format!("Redis connection failed: {}", e))?;
+    let mut rx = events.subscribe();
+
+    info!(
+        "Publishing events to Redis streams {}:<chain_id> (maxlen ~{})",
+        config.stream_prefix, config.max_len
+    );
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Redis publisher lagged, {} events dropped", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+
+        if let Err(e) = publish(&mut conn, &config, &event).await {
+            error!("Redis XADD failed: {}", e);
+        }
+    }
+}
+
+async fn publish(conn: &mut ConnectionManager, config: &RedisConfig, event: &IndexedEvent) -> Result<(), String> {
+    let data = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    let key = stream_key(&config.stream_prefix, event.chain_id());
+
+    let _: String = conn
+        .xadd_maxlen(
+            &key,
+            StreamMaxlen::Approx(config.max_len),
+            "*",
+            &[("type", event.event_type()), ("data", data.as_str())],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
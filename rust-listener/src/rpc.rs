@@ -1,10 +1,23 @@
-use crate::types::{Block, Log, RpcResponse, TRANSFER_TOPIC};
+use crate::rate_limit::RateLimiter;
+use crate::types::{
+    AssetTransfer, Block, BlockTrace, BlockId, BlockRef, CallFrame, HttpOptions, Log, NetworkConfig, RpcResponse, Trace, TransactionReceipt, APPROVAL_TOPIC,
+    CRYPTO2FIAT_TOPIC, DST_ESCROW_CREATED_TOPIC, ESCROW_CANCELLED_TOPIC, ESCROW_WITHDRAWAL_TOPIC,
+    ORDER_CANCELLED_TOPIC, ORDER_FILLED_TOPIC, SRC_ESCROW_CREATED_TOPIC, TRANSFER_TOPIC,
+    WETH_DEPOSIT_TOPIC, WETH_WITHDRAWAL_TOPIC,
+};
+use futures_util::{SinkExt, StreamExt};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::time::Duration;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::time::sleep;
-use tracing::{debug, warn};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, error, info, instrument, warn};
 
 #[derive(Error, Debug)]
 pub enum RpcError {
@@ -12,20 +25,229 @@ pub enum RpcError {
     Http(#[from] reqwest::Error),
     #[error("RPC error: {0}")]
     Rpc(String),
+    /// Error object of a JSON-RPC response
+    #[error("RPC error {code}: {message}")]
+    JsonRpc { code: i64, message: String },
     #[error("Parse error: {0}")]
     Parse(String),
     #[error("Rate limited after max retries")]
     RateLimited,
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
+    #[error("Block range too large: {0}")]
+    RangeTooLarge(String),
+    #[error("Circuit open after repeated failures, retrying in {0:?}")]
+    CircuitOpen(Duration),
+    #[error("Circuit half-open, waiting for the trial request")]
+    CircuitHalfOpen,
+}
+
+impl RpcError {
+    /// Whether the node doesn't serve the method at all, as opposed to
+    /// failing this one call
+    pub fn is_method_unsupported(&self) -> bool {
+        match self {
+            RpcError::JsonRpc { code, message } => {
+                *code == -32601 || message.contains("does not exist") || message.contains("is not available")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Consecutive failures before an endpoint is marked unhealthy and rotated away from
+const FAILOVER_THRESHOLD: u32 = 3;
+
+/// How long an unhealthy endpoint rests before it is probed again
+const PROBE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Longest Retry-After wait honored; a longer one is capped to this
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Largest page alchemy_getAssetTransfers returns
+const ASSET_TRANSFERS_PAGE_SIZE: u32 = 1000;
+
+/// Traces requested per trace_filter page, below common provider result caps
+const TRACE_FILTER_PAGE_SIZE: usize = 1000;
+
+/// Request budget shared by every chain's client (RPC_GLOBAL_RATE_LIMIT)
+static GLOBAL_RATE_LIMIT: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Cap requests per second across all clients; only the first call takes effect
+pub fn set_global_rate_limit(requests_per_second: f64) {
+    let _ = GLOBAL_RATE_LIMIT.set(RateLimiter::new(requests_per_second));
+}
+
+/// Hosts whose responses are requested uncompressed (RPC_NO_COMPRESSION)
+static UNCOMPRESSED_HOSTS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Don't ask these hosts for gzip/deflate responses, for providers that
+/// mangle them; only the first call takes effect
+pub fn set_uncompressed_hosts(hosts: Vec<String>) {
+    let _ = UNCOMPRESSED_HOSTS.set(hosts.into_iter().map(|h| h.to_lowercase()).collect());
+}
+
+/// Whether responses from `url` are requested compressed
+fn compression_enabled(url: &str) -> bool {
+    let host = url_host(url);
+    !UNCOMPRESSED_HOSTS
+        .get()
+        .is_some_and(|hosts| hosts.iter().any(|h| host == *h || host.ends_with(&format!(".{}", h))))
+}
+
+/// HTTP client for one endpoint
+///
+/// With compression, gzip and deflate are advertised in Accept-Encoding and
+/// responses decoded transparently; large eth_getLogs results shrink several times.
+fn http_client(options: &HttpOptions, compression: bool) -> Client {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(options.timeout_secs))
+        .pool_max_idle_per_host(options.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(options.pool_idle_timeout_secs))
+        .tcp_keepalive(options.tcp_keepalive_secs.map(Duration::from_secs))
+        .gzip(compression)
+        .deflate(compression);
+    if let Some(secs) = options.connect_timeout_secs {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if options.http2_prior_knowledge {
+        builder = builder.http2_prior_knowledge();
+    }
+    builder.build().expect("Failed to create HTTP client")
+}
+
+/// Failure count and cooldown of every client's circuit breaker (RPC_BREAKER_*)
+static CIRCUIT_BREAKER: OnceLock<(u32, Duration)> = OnceLock::new();
+
+/// Open a client's circuit after `failures` consecutive failed requests, so
+/// requests fail fast for about `cooldown`; only the first call takes effect
+pub fn set_circuit_breaker(failures: u32, cooldown: Duration) {
+    let _ = CIRCUIT_BREAKER.set((failures.max(1), cooldown));
+}
+
+/// `duration` plus up to a quarter of it at random, so clients that failed
+/// together don't retry together
+fn jittered(duration: Duration) -> Duration {
+    duration.mul_f64(1.0 + rand::random::<f64>() * 0.25)
+}
+
+/// Circuit breaker of a client, over all its endpoints
+#[derive(Debug, Default)]
+struct Circuit {
+    /// Requests that failed after all retries and failover
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// When the single trial request allowed after the cooldown was let
+    /// through; set until it completes, and failing it reopens the circuit
+    trial_since: Option<Instant>,
+    trips: u64,
+}
+
+/// State of a client's circuit breaker, for health reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CircuitState {
+    /// `closed`, `open` or `half_open`
+    pub state: &'static str,
+    pub consecutive_failures: u32,
+    /// Times the circuit has opened
+    pub trips: u64,
+    /// Seconds until an open circuit lets a trial request through
+    pub retry_in_secs: Option<u64>,
+}
+
+/// Traffic of one RPC method (eth_getLogs per event category) on a client
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MethodUsage {
+    /// HTTP requests sent, retries included
+    pub requests: u64,
+    /// Calls that failed after all retries
+    pub errors: u64,
+    pub bytes_sent: u64,
+    /// Response bytes after decompression
+    pub bytes_received: u64,
+    /// Items returned: the length of array results such as logs, 1 for other results
+    pub results: u64,
+}
+
+/// Usage key of a request: the method, and for eth_getLogs the event
+/// category its topic0 filter selects
+fn usage_key(method: &str, params: &Value) -> String {
+    if method != "eth_getLogs" {
+        return method.to_string();
+    }
+    let category = |topic: &str| match topic.to_lowercase().as_str() {
+        TRANSFER_TOPIC | WETH_DEPOSIT_TOPIC | WETH_WITHDRAWAL_TOPIC => "transfers",
+        APPROVAL_TOPIC => "approvals",
+        SRC_ESCROW_CREATED_TOPIC | DST_ESCROW_CREATED_TOPIC | ESCROW_WITHDRAWAL_TOPIC | ESCROW_CANCELLED_TOPIC => {
+            "fusion_plus"
+        }
+        ORDER_FILLED_TOPIC | ORDER_CANCELLED_TOPIC => "fusion",
+        CRYPTO2FIAT_TOPIC => "crypto2fiat",
+        _ => "other",
+    };
+
+    let categories: BTreeSet<&str> = match &params[0]["topics"][0] {
+        Value::String(topic) => [category(topic)].into(),
+        Value::Array(topics) => topics.iter().filter_map(Value::as_str).map(category).collect(),
+        _ => BTreeSet::new(),
+    };
+    let label = match categories.len() {
+        0 => "all",
+        1 => categories.into_iter().next().unwrap_or("all"),
+        _ => "combined",
+    };
+    format!("eth_getLogs:{}", label)
+}
+
+/// One page of alchemy_getAssetTransfers results
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetTransfersPage {
+    transfers: Vec<AssetTransfer>,
+    page_key: Option<String>,
+}
+
+/// One RPC endpoint and its health
+struct Endpoint {
+    /// Own HTTP client, as compression can be turned off per endpoint
+    client: Client,
+    url: String,
+    health: Mutex<EndpointHealth>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// Set while the endpoint is unhealthy; reset on every failed probe
+    unhealthy_since: Option<Instant>,
+    /// Set when `verify_chain_id` couldn't reach the endpoint; its chain is
+    /// checked before it serves a request
+    chain_unverified: bool,
 }
 
 /// Generic JSON-RPC client for any Ethereum-compatible blockchain
 /// Works with any provider: Alchemy, Infura, QuickNode, public RPCs, etc.
+///
+/// With several endpoints, requests go to the active one; repeated 429/5xx
+/// responses or transport errors rotate to the next endpoint, and unhealthy
+/// endpoints earlier in the list are probed again after `PROBE_INTERVAL`.
+/// If requests keep failing on every endpoint, the circuit breaker (when
+/// configured with `set_circuit_breaker`) rejects requests for a cooldown.
 pub struct RpcClient {
-    client: Client,
-    url: String,
+    endpoints: Vec<Endpoint>,
+    active: AtomicUsize,
     chain_name: String,
     max_retries: u32,
     retry_base_delay_ms: u64,
+    /// Per-chain request budget; requests wait for a token before being sent
+    rate_limit: Option<RateLimiter>,
+    circuit: Mutex<Circuit>,
+    /// (failures, cooldown) of the circuit breaker, from `set_circuit_breaker`
+    circuit_breaker: Option<(u32, Duration)>,
+    /// Traffic per `usage_key`
+    usage: Mutex<BTreeMap<String, MethodUsage>>,
+    /// Chain the endpoints must serve, once `verify_chain_id` has run
+    chain_id: Option<u64>,
 }
 
 impl RpcClient {
@@ -38,6 +260,32 @@ impl RpcClient {
         Self::with_config(url, chain_name, 3, 100)
     }
 
+    /// Create a client over a network's primary and fallback endpoints
+    pub fn for_network(network: &NetworkConfig) -> Self {
+        let mut client = Self::with_endpoints(&network.rpc_urls(), &network.name, 3, 100);
+        if network.http != HttpOptions::default() {
+            client = client.with_http_options(&network.http);
+        }
+        match network.rate_limit {
+            Some(requests_per_second) => client.with_rate_limit(requests_per_second),
+            None => client,
+        }
+    }
+
+    /// Rebuild every endpoint's HTTP client with `options`
+    pub fn with_http_options(mut self, options: &HttpOptions) -> Self {
+        for endpoint in &mut self.endpoints {
+            endpoint.client = http_client(options, compression_enabled(&endpoint.url));
+        }
+        self
+    }
+
+    /// Limit this client to `requests_per_second`, queueing requests over budget
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.rate_limit = Some(RateLimiter::new(requests_per_second));
+        self
+    }
+
     /// Create a new RPC client with custom retry configuration
     ///
     /// # Arguments
@@ -51,22 +299,151 @@ impl RpcClient {
         max_retries: u32,
         retry_base_delay_ms: u64,
     ) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(180)) // 3 minutes for large getLogs queries
-            .pool_max_idle_per_host(2)         // Reduced from 5 to save memory
-            .pool_idle_timeout(Duration::from_secs(30)) // Release idle connections after 30s
-            .build()
-            .expect("Failed to create HTTP client");
+        Self::with_endpoints(&[url], chain_name, max_retries, retry_base_delay_ms)
+    }
+
+    /// Create a new RPC client over several endpoints, in order of preference
+    ///
+    /// `max_retries` applies per endpoint.
+    pub fn with_endpoints<S: AsRef<str>>(
+        urls: &[S],
+        chain_name: &str,
+        max_retries: u32,
+        retry_base_delay_ms: u64,
+    ) -> Self {
+        assert!(!urls.is_empty(), "RpcClient needs at least one endpoint");
 
         Self {
-            client,
-            url: url.to_string(),
+            endpoints: urls
+                .iter()
+                .map(|url| Endpoint {
+                    client: http_client(&HttpOptions::default(), compression_enabled(url.as_ref())),
+                    url: url.as_ref().to_string(),
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+                .collect(),
+            active: AtomicUsize::new(0),
             chain_name: chain_name.to_string(),
             max_retries,
             retry_base_delay_ms,
+            rate_limit: None,
+            circuit: Mutex::new(Circuit::default()),
+            circuit_breaker: CIRCUIT_BREAKER.get().copied(),
+            usage: Mutex::new(BTreeMap::new()),
+            chain_id: None,
+        }
+    }
+
+    /// Wait for both the per-chain and the global request budget
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limit {
+            limiter.acquire().await;
+        }
+        if let Some(limiter) = GLOBAL_RATE_LIMIT.get() {
+            limiter.acquire().await;
         }
     }
 
+    /// Pick the endpoint for the next attempt
+    ///
+    /// Normally the active endpoint; a preferred endpoint that has been
+    /// unhealthy for at least `PROBE_INTERVAL` is tried instead as a probe.
+    fn select_endpoint(&self) -> usize {
+        let active = self.active.load(Ordering::Relaxed);
+
+        for (idx, endpoint) in self.endpoints.iter().enumerate().take(active) {
+            let mut health = endpoint.health.lock().unwrap();
+            if let Some(since) = health.unhealthy_since {
+                if since.elapsed() >= PROBE_INTERVAL {
+                    // Push the next probe out even if this one hangs
+                    health.unhealthy_since = Some(Instant::now());
+                    debug!("[{}] Probing endpoint {}", self.chain_name, idx);
+                    return idx;
+                }
+            }
+        }
+
+        active
+    }
+
+    /// Record a successful response from an endpoint
+    fn mark_success(&self, idx: usize) {
+        let mut health = self.endpoints[idx].health.lock().unwrap();
+        health.consecutive_failures = 0;
+
+        if health.unhealthy_since.take().is_some() {
+            info!(
+                "[{}] RPC endpoint {} ({}) recovered",
+                self.chain_name,
+                idx,
+                provider_from_url(&self.endpoints[idx].url)
+            );
+        }
+        // Prefer endpoints earlier in the list once they work again
+        self.active.fetch_min(idx, Ordering::Relaxed);
+    }
+
+    /// Record a failed attempt, rotating away after repeated failures
+    fn mark_failure(&self, idx: usize) {
+        let mut health = self.endpoints[idx].health.lock().unwrap();
+        health.consecutive_failures += 1;
+
+        if health.unhealthy_since.is_some() {
+            health.unhealthy_since = Some(Instant::now()); // Failed probe
+            return;
+        }
+        if health.consecutive_failures < FAILOVER_THRESHOLD || self.endpoints.len() == 1 {
+            return;
+        }
+
+        health.unhealthy_since = Some(Instant::now());
+        drop(health);
+
+        self.rotate_from(idx, &format!("unhealthy after {} failures", FAILOVER_THRESHOLD));
+    }
+
+    /// Move off the active endpoint even though it answers, e.g. because it
+    /// serves a stale head
+    ///
+    /// The endpoint is probed again after `PROBE_INTERVAL` like one that
+    /// failed. Returns false if there is no other endpoint to use.
+    pub fn fail_over(&self, reason: &str) -> bool {
+        if self.endpoints.len() == 1 {
+            return false;
+        }
+        let idx = self.active.load(Ordering::Relaxed);
+        self.endpoints[idx].health.lock().unwrap().unhealthy_since = Some(Instant::now());
+        self.rotate_from(idx, reason)
+    }
+
+    /// Make the next usable endpoint after `idx` the active one
+    fn rotate_from(&self, idx: usize, reason: &str) -> bool {
+        // Next endpoint that isn't unhealthy, or simply the next one if all are
+        let count = self.endpoints.len();
+        let next = (1..count)
+            .map(|step| (idx + step) % count)
+            .find(|&i| self.endpoints[i].health.lock().unwrap().unhealthy_since.is_none())
+            .unwrap_or((idx + 1) % count);
+
+        if self
+            .active
+            .compare_exchange(idx, next, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            warn!(
+                "[{}] RPC endpoint {} ({}) {}, failing over to endpoint {} ({})",
+                self.chain_name,
+                idx,
+                provider_from_url(&self.endpoints[idx].url),
+                reason,
+                next,
+                provider_from_url(&self.endpoints[next].url)
+            );
+            return true;
+        }
+        false
+    }
+
     /// Check if an HTTP status code indicates a retryable error
     fn is_retryable_status(status: u16) -> bool {
         // 429 = Rate Limited
@@ -76,11 +453,122 @@ impl RpcClient {
         matches!(status, 429 | 502 | 503 | 504)
     }
 
-    /// Make a JSON-RPC request with automatic retry on rate limit and transient errors
+    /// Make a JSON-RPC request, failing fast while the circuit is open
+    #[instrument(name = "rpc", skip(self, params), fields(chain = %self.chain_name))]
     async fn request<T: serde::de::DeserializeOwned>(
         &self,
         method: &str,
         params: Value,
+    ) -> Result<T, RpcError> {
+        self.check_circuit()?;
+        let key = usage_key(method, &params);
+        let result = self.request_with_retries(method, params, &key).await;
+        self.record_circuit(&result);
+        if result.is_err() {
+            self.record_usage(&key, |usage| usage.errors += 1);
+        }
+        result
+    }
+
+    fn record_usage(&self, key: &str, update: impl FnOnce(&mut MethodUsage)) {
+        let mut usage = self.usage.lock().unwrap();
+        match usage.get_mut(key) {
+            Some(entry) => update(entry),
+            None => update(usage.entry(key.to_string()).or_default()),
+        }
+    }
+
+    /// Requests, bytes and results so far per method, with eth_getLogs split
+    /// by event category (`eth_getLogs:transfers`, `eth_getLogs:fusion_plus`, ...)
+    pub fn usage(&self) -> BTreeMap<String, MethodUsage> {
+        self.usage.lock().unwrap().clone()
+    }
+
+    /// Reject requests while the circuit is open; once the cooldown is over a
+    /// single trial request goes through, half-open, and the others are
+    /// rejected until it completes
+    fn check_circuit(&self) -> Result<(), RpcError> {
+        let Some((_, cooldown)) = self.circuit_breaker else {
+            return Ok(());
+        };
+        let mut circuit = self.circuit.lock().unwrap();
+        let now = Instant::now();
+        if let Some(until) = circuit.open_until {
+            if until > now {
+                return Err(RpcError::CircuitOpen(until - now));
+            }
+            circuit.open_until = None;
+            circuit.trial_since = Some(now);
+            return Ok(());
+        }
+        match circuit.trial_since {
+            // A trial that never completed, e.g. because it was cancelled, is replaced
+            Some(since) if now.duration_since(since) >= cooldown => {
+                circuit.trial_since = Some(now);
+                Ok(())
+            }
+            Some(_) => Err(RpcError::CircuitHalfOpen),
+            None => Ok(()),
+        }
+    }
+
+    /// Count a request's outcome towards the circuit breaker
+    ///
+    /// Only transport errors and exhausted rate limit/5xx retries count as
+    /// failures; an RPC error still means the endpoint is up.
+    fn record_circuit<T>(&self, result: &Result<T, RpcError>) {
+        let Some((failures, cooldown)) = self.circuit_breaker else {
+            return;
+        };
+        let mut circuit = self.circuit.lock().unwrap();
+        let trial = circuit.trial_since.take().is_some();
+
+        if !matches!(result, Err(RpcError::Http(_) | RpcError::RateLimited)) {
+            if trial {
+                info!("[{}] RPC circuit closed, requests succeed again", self.chain_name);
+            }
+            circuit.consecutive_failures = 0;
+            return;
+        }
+
+        circuit.consecutive_failures += 1;
+        if circuit.open_until.is_none() && (trial || circuit.consecutive_failures >= failures) {
+            let pause = jittered(cooldown);
+            circuit.open_until = Some(Instant::now() + pause);
+            circuit.trips += 1;
+            warn!(
+                "[{}] RPC circuit open after {} consecutive failed requests, pausing requests for {:?}",
+                self.chain_name, circuit.consecutive_failures, pause
+            );
+        }
+    }
+
+    /// Current state of the circuit breaker
+    pub fn circuit_state(&self) -> CircuitState {
+        let circuit = self.circuit.lock().unwrap();
+        let retry_in = circuit
+            .open_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|d| !d.is_zero());
+        let state = match (retry_in, circuit.trial_since.is_some() || circuit.open_until.is_some()) {
+            (Some(_), _) => "open",
+            (None, true) => "half_open",
+            (None, false) => "closed",
+        };
+        CircuitState {
+            state,
+            consecutive_failures: circuit.consecutive_failures,
+            trips: circuit.trips,
+            retry_in_secs: retry_in.map(|d| d.as_secs().max(1)),
+        }
+    }
+
+    /// Make a JSON-RPC request with automatic retry on rate limit and transient errors
+    async fn request_with_retries<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+        usage_key: &str,
     ) -> Result<T, RpcError> {
         let body = json!({
             "jsonrpc": "2.0",
@@ -88,36 +576,79 @@ impl RpcClient {
             "method": method,
             "params": params
         });
+        let payload = serde_json::to_vec(&body).expect("JSON-RPC request serializes");
 
         let mut retries = 0;
+        let max_attempts = self.max_retries * self.endpoints.len() as u32;
 
         loop {
-            let response = self
+            self.throttle().await;
+            let idx = self.select_endpoint();
+            if let Err(e) = self.check_unverified_chain(idx).await {
+                retries += 1;
+                if retries > max_attempts || self.endpoints.len() == 1 {
+                    return Err(e);
+                }
+                continue;
+            }
+            self.record_usage(usage_key, |usage| {
+                usage.requests += 1;
+                usage.bytes_sent += payload.len() as u64;
+            });
+
+            let endpoint = &self.endpoints[idx];
+            let response = match endpoint
                 .client
-                .post(&self.url)
-                .json(&body)
+                .post(&endpoint.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(payload.clone())
                 .send()
-                .await?;
+                .await
+            {
+                Ok(response) => response,
+                // Transport errors and timeouts are only retried when another endpoint can take over
+                Err(e) if self.endpoints.len() > 1 => {
+                    self.mark_failure(idx);
+                    retries += 1;
+                    if retries > max_attempts {
+                        return Err(e.into());
+                    }
+                    warn!(
+                        "[{}] Request error on {}, retry {}/{}: {}",
+                        self.chain_name, method, retries, max_attempts, e
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
 
             let status = response.status();
 
-            // Handle retryable errors with exponential backoff
+            // Handle retryable errors with exponential backoff, or the wait the provider asks for
             if Self::is_retryable_status(status.as_u16()) {
+                self.mark_failure(idx);
                 retries += 1;
-                if retries > self.max_retries {
+                if retries > max_attempts {
                     return Err(RpcError::RateLimited);
                 }
-                let delay = Duration::from_millis(
-                    self.retry_base_delay_ms * 2u64.pow(retries - 1)
-                );
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_retry_after);
+                let delay = match retry_after {
+                    Some(wait) => jittered(wait),
+                    None => self.backoff(retries),
+                };
                 warn!(
-                    "[{}] HTTP {} on {}, retry {}/{} in {:?}",
+                    "[{}] HTTP {} on {}, retry {}/{} in {:?}{}",
                     self.chain_name,
                     status.as_u16(),
                     method,
                     retries,
-                    self.max_retries,
-                    delay
+                    max_attempts,
+                    delay,
+                    if retry_after.is_some() { " (Retry-After)" } else { "" }
                 );
                 sleep(delay).await;
                 continue;
@@ -130,42 +661,69 @@ impl RpcClient {
                 )));
             }
 
-            let rpc_response: RpcResponse<T> = response.json().await?;
+            let bytes = response.bytes().await?;
+            let rpc_response: RpcResponse<Value> = serde_json::from_slice(&bytes)
+                .map_err(|e| RpcError::Parse(format!("Invalid response to {}: {}", method, e)))?;
+            let results = match &rpc_response.result {
+                Some(Value::Array(items)) => items.len() as u64,
+                Some(Value::Null) | None => 0,
+                Some(_) => 1,
+            };
+            self.record_usage(usage_key, |usage| {
+                usage.bytes_received += bytes.len() as u64;
+                usage.results += results;
+            });
 
             if let Some(error) = rpc_response.error {
+                // Checked first: Infura reports oversized queries with the rate limit code
+                if is_range_too_large(&error.message) {
+                    self.mark_success(idx);
+                    return Err(RpcError::RangeTooLarge(error.message));
+                }
+
                 // Some providers return rate limit as RPC error rather than HTTP 429
                 if error.code == -32005 || error.message.to_lowercase().contains("rate") {
+                    self.mark_failure(idx);
                     retries += 1;
-                    if retries > self.max_retries {
+                    if retries > max_attempts {
                         return Err(RpcError::RateLimited);
                     }
-                    let delay = Duration::from_millis(
-                        self.retry_base_delay_ms * 2u64.pow(retries - 1)
-                    );
+                    let delay = self.backoff(retries);
                     warn!(
                         "[{}] RPC rate limit on {}, retry {}/{} in {:?}",
                         self.chain_name,
                         method,
                         retries,
-                        self.max_retries,
+                        max_attempts,
                         delay
                     );
                     sleep(delay).await;
                     continue;
                 }
 
-                return Err(RpcError::Rpc(format!(
-                    "RPC error {}: {}",
-                    error.code, error.message
-                )));
+                self.mark_success(idx);
+                return Err(RpcError::JsonRpc { code: error.code, message: error.message });
             }
 
-            return rpc_response
+            self.mark_success(idx);
+            let result = rpc_response
                 .result
-                .ok_or_else(|| RpcError::Parse("Missing result in RPC response".to_string()));
+                .ok_or_else(|| RpcError::Parse("Missing result in RPC response".to_string()))?;
+            return serde_json::from_value(result)
+                .map_err(|e| RpcError::Parse(format!("Invalid result of {}: {}", method, e)));
         }
     }
 
+    /// Exponential backoff for the given retry number (1-based), capped at 10s
+    ///
+    /// The delay is drawn from the upper half of the step, so pollers
+    /// rate-limited together don't retry in lockstep.
+    fn backoff(&self, retries: u32) -> Duration {
+        let factor = 2u64.saturating_pow(retries.saturating_sub(1).min(16));
+        let step = Duration::from_millis(self.retry_base_delay_ms.saturating_mul(factor).min(10_000));
+        step / 2 + step.mul_f64(rand::random::<f64>() / 2.0)
+    }
+
     /// Get the current block number (eth_blockNumber)
     pub async fn get_block_number(&self) -> Result<u64, RpcError> {
         let result: String = self.request("eth_blockNumber", json!([])).await?;
@@ -173,6 +731,134 @@ impl RpcClient {
             .map_err(|e| RpcError::Parse(format!("Invalid block number: {}", e)))
     }
 
+    /// Get the chain ID reported by every configured endpoint (eth_chainId)
+    ///
+    /// Each endpoint is asked once, bypassing retries and failover, so a
+    /// fallback URL pointing at the wrong chain is caught before it is needed.
+    pub async fn get_chain_ids(&self) -> Vec<(&'static str, Result<u64, RpcError>)> {
+        let mut results = Vec::with_capacity(self.endpoints.len());
+        for endpoint in &self.endpoints {
+            self.throttle().await;
+            results.push((provider_from_url(&endpoint.url), Self::endpoint_chain_id(endpoint).await));
+        }
+        results
+    }
+
+    /// Ask one endpoint for its chain ID, without retries
+    async fn endpoint_chain_id(endpoint: &Endpoint) -> Result<u64, RpcError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_chainId",
+            "params": []
+        });
+        let response: RpcResponse<String> = endpoint
+            .client
+            .post(&endpoint.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.error {
+            return Err(RpcError::JsonRpc { code: error.code, message: error.message });
+        }
+        let chain_id = response
+            .result
+            .ok_or_else(|| RpcError::Parse("Missing result in RPC response".to_string()))?;
+        u64::from_str_radix(chain_id.trim_start_matches("0x"), 16)
+            .map_err(|e| RpcError::Parse(format!("Invalid chain ID: {}", e)))
+    }
+
+    /// Check that the endpoints serve `chain_id`, dropping those that serve
+    /// another chain from rotation
+    ///
+    /// A URL for another chain would store its logs under this chain's ID.
+    /// Endpoints that can't answer are kept, as they may just be down for now,
+    /// and are checked before the first request they serve. Fails only when
+    /// every endpoint serves another chain.
+    pub async fn verify_chain_id(&mut self, chain_id: u64) -> Result<(), String> {
+        self.chain_id = Some(chain_id);
+        let mut wrong = Vec::new();
+        for (idx, (provider, result)) in self.get_chain_ids().await.into_iter().enumerate() {
+            match result {
+                Ok(served) if served == chain_id => {
+                    debug!("[{}] {} serves chain {}", self.chain_name, provider, served);
+                }
+                Ok(served) => {
+                    error!(
+                        "[{}] RPC endpoint {} ({}) serves chain {} but chain {} is configured",
+                        self.chain_name, idx, provider, served, chain_id
+                    );
+                    wrong.push(idx);
+                }
+                Err(e) => {
+                    warn!("[{}] Could not verify chain ID of {}: {}", self.chain_name, provider, e);
+                    self.endpoints[idx].health.lock().unwrap().chain_unverified = true;
+                }
+            }
+        }
+
+        if wrong.len() == self.endpoints.len() {
+            return Err(format!("no RPC endpoint serves chain {}", chain_id));
+        }
+        if !wrong.is_empty() {
+            let mut idx = 0;
+            self.endpoints.retain(|_| {
+                idx += 1;
+                !wrong.contains(&(idx - 1))
+            });
+            self.active.store(0, Ordering::Relaxed);
+            warn!(
+                "[{}] Dropped {} RPC endpoint(s) serving another chain, using {}",
+                self.chain_name,
+                wrong.len(),
+                self.providers().join(" -> ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Check the chain of an endpoint `verify_chain_id` couldn't reach before
+    /// it serves a request
+    ///
+    /// One serving another chain is failed over like an unhealthy endpoint,
+    /// and checked again when it is probed.
+    async fn check_unverified_chain(&self, idx: usize) -> Result<(), RpcError> {
+        let Some(chain_id) = self.chain_id else {
+            return Ok(());
+        };
+        let endpoint = &self.endpoints[idx];
+        if !endpoint.health.lock().unwrap().chain_unverified {
+            return Ok(());
+        }
+
+        let served = match Self::endpoint_chain_id(endpoint).await {
+            Ok(served) => served,
+            Err(e) => {
+                self.mark_failure(idx);
+                return Err(e);
+            }
+        };
+        if served == chain_id {
+            endpoint.health.lock().unwrap().chain_unverified = false;
+            info!(
+                "[{}] RPC endpoint {} ({}) verified to serve chain {}",
+                self.chain_name, idx, provider_from_url(&endpoint.url), chain_id
+            );
+            return Ok(());
+        }
+
+        error!(
+            "[{}] RPC endpoint {} ({}) serves chain {} but chain {} is configured",
+            self.chain_name, idx, provider_from_url(&endpoint.url), served, chain_id
+        );
+        endpoint.health.lock().unwrap().unhealthy_since = Some(Instant::now());
+        self.rotate_from(idx, &format!("serves chain {}", served));
+        Err(RpcError::Rpc(format!("endpoint serves chain {} instead of {}", served, chain_id)))
+    }
+
     /// Get logs for Transfer events in a block range (eth_getLogs)
     ///
     /// Filters for ERC20 Transfer events only (topic[0] = Transfer signature)
@@ -195,6 +881,26 @@ impl RpcClient {
         self.request("eth_getLogs", params).await
     }
 
+    /// Get ERC20 Transfer and Approval logs in one query (eth_getLogs)
+    pub async fn get_token_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, RpcError> {
+        debug!(
+            "[{}] Getting transfer and approval logs from block {} to {}",
+            self.chain_name, from_block, to_block
+        );
+
+        let params = json!([{
+            "fromBlock": format!("0x{:x}", from_block),
+            "toBlock": format!("0x{:x}", to_block),
+            "topics": [[TRANSFER_TOPIC, APPROVAL_TOPIC]]
+        }]);
+
+        self.request("eth_getLogs", params).await
+    }
+
     /// Get logs with custom filter (eth_getLogs)
     ///
     /// For advanced use cases where you need custom topic filtering
@@ -309,17 +1015,196 @@ impl RpcClient {
         self.request("eth_getLogs", params).await
     }
 
+    /// Get logs between two blocks given by number or tag (eth_getLogs)
+    ///
+    /// Filters on topic0 (any of `topic0_options`, or every event when empty)
+    /// and optionally on the emitting address.
+    pub async fn get_logs_between(
+        &self,
+        from: BlockId,
+        to: BlockId,
+        address: Option<&str>,
+        topic0_options: Vec<String>,
+    ) -> Result<Vec<Log>, RpcError> {
+        debug!(
+            "[{}] Getting logs from {} with {} topic options for blocks {} to {}",
+            self.chain_name, address.unwrap_or("any address"), topic0_options.len(), from, to
+        );
+
+        let mut filter = json!({
+            "fromBlock": from.to_param(),
+            "toBlock": to.to_param(),
+        });
+        if let Some(address) = address {
+            filter["address"] = json!(address);
+        }
+        if !topic0_options.is_empty() {
+            filter["topics"] = json!([topic0_options]);
+        }
+
+        self.request("eth_getLogs", json!([filter])).await
+    }
+
     /// Get block by number (eth_getBlockByNumber)
     ///
     /// Returns block header without transactions (for getting timestamp)
     pub async fn get_block(&self, block_number: u64) -> Result<Block, RpcError> {
-        let params = json!([format!("0x{:x}", block_number), false]);
-        self.request("eth_getBlockByNumber", params).await
+        self.get_block_by_id(BlockId::Number(block_number)).await
     }
 
-    /// Get the RPC endpoint URL (for logging/debugging)
+    /// Get block by number or tag (eth_getBlockByNumber)
+    ///
+    /// Nodes that don't know the block or tag return an error or a null
+    /// block; both are reported as `RpcError::Rpc`/`RpcError::Parse`.
+    pub async fn get_block_by_id(&self, id: BlockId) -> Result<Block, RpcError> {
+        let block: Option<Block> = self.request("eth_getBlockByNumber", json!([id.to_param(), false])).await?;
+        block.ok_or_else(|| RpcError::Parse(format!("No block {}", id)))
+    }
+
+    /// Resolve a block number or tag to the block's number and hash
+    pub async fn get_block_ref(&self, id: BlockId) -> Result<BlockRef, RpcError> {
+        let block = self.get_block_by_id(id).await?;
+        match (block.number_u64(), block.hash) {
+            (Some(number), Some(hash)) => Ok(BlockRef { number, hash: hash.to_lowercase() }),
+            _ => Err(RpcError::Parse(format!("Block {} has no number or hash", id))),
+        }
+    }
+
+    /// Get an account's code at a block (eth_getCode)
+    pub async fn get_code(&self, address: &str, block_number: u64) -> Result<String, RpcError> {
+        let params = json!([address, format!("0x{:x}", block_number)]);
+        self.request("eth_getCode", params).await
+    }
+
+    /// Call a contract at the latest block (eth_call), returning the raw hex result
+    pub async fn call(&self, to: &str, data: &str) -> Result<String, RpcError> {
+        let params = json!([{ "to": to, "data": data }, "latest"]);
+        self.request("eth_call", params).await
+    }
+
+    /// Get transaction receipt by hash (eth_getTransactionReceipt)
+    ///
+    /// Fails with a parse error if the transaction is unknown to the node
+    pub async fn get_transaction_receipt(
+        &self,
+        tx_hash: &str,
+    ) -> Result<TransactionReceipt, RpcError> {
+        self.request("eth_getTransactionReceipt", json!([tx_hash])).await
+    }
+
+    /// Receipts of every transaction in a block (eth_getBlockReceipts)
+    ///
+    /// Not served by every node; see `ChainQuirks::block_receipts`
+    pub async fn get_block_receipts(&self, block_number: u64) -> Result<Vec<TransactionReceipt>, RpcError> {
+        self.request("eth_getBlockReceipts", json!([format!("0x{:x}", block_number)])).await
+    }
+
+    /// Native-currency transfers in a block range, via alchemy_getAssetTransfers
+    ///
+    /// Covers each transaction's own value (the `external` category), in
+    /// block order. Only Alchemy serves this method. Pages are followed until
+    /// the range is exhausted.
+    pub async fn get_native_transfers(&self, from_block: u64, to_block: u64) -> Result<Vec<AssetTransfer>, RpcError> {
+        let mut transfers = Vec::new();
+        let mut page_key: Option<String> = None;
+
+        loop {
+            let mut filter = json!({
+                "fromBlock": format!("0x{:x}", from_block),
+                "toBlock": format!("0x{:x}", to_block),
+                "category": ["external"],
+                "excludeZeroValue": true,
+                "maxCount": format!("0x{:x}", ASSET_TRANSFERS_PAGE_SIZE),
+            });
+            if let Some(key) = page_key.take() {
+                filter["pageKey"] = json!(key);
+            }
+
+            let page: AssetTransfersPage = self.request("alchemy_getAssetTransfers", json!([filter])).await?;
+            transfers.extend(page.transfers);
+            match page.page_key {
+                Some(key) => page_key = Some(key),
+                None => return Ok(transfers),
+            }
+        }
+    }
+
+    /// OpenEthereum-style traces of calls sent from or to `addresses` in a block range
+    ///
+    /// Served by Erigon, Nethermind, Reth and some providers. A filter's
+    /// address lists must both match, so senders and recipients are fetched
+    /// with one call each and merged. Each is paged with `after`/`count`
+    /// so a provider's result cap cannot truncate it.
+    pub async fn trace_filter(&self, from_block: u64, to_block: u64, addresses: &[String]) -> Result<Vec<Trace>, RpcError> {
+        let (sent, received) = tokio::try_join!(
+            self.trace_filter_pages(from_block, to_block, "fromAddress", addresses),
+            self.trace_filter_pages(from_block, to_block, "toAddress", addresses),
+        )?;
+
+        let mut seen = BTreeSet::new();
+        Ok(sent
+            .into_iter()
+            .chain(received)
+            .filter(|t| seen.insert((t.transaction_hash.clone(), t.trace_address.clone(), t.trace_type.clone())))
+            .collect())
+    }
+
+    /// Every page of one trace_filter, until a page comes back short
+    async fn trace_filter_pages(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        field: &str,
+        addresses: &[String],
+    ) -> Result<Vec<Trace>, RpcError> {
+        let mut traces = Vec::new();
+        loop {
+            let params = json!([{
+                "fromBlock": format!("0x{:x}", from_block),
+                "toBlock": format!("0x{:x}", to_block),
+                (field): addresses,
+                "after": traces.len(),
+                "count": TRACE_FILTER_PAGE_SIZE,
+            }]);
+            let page: Vec<Trace> = self.request("trace_filter", params).await?;
+            let last_page = page.len() < TRACE_FILTER_PAGE_SIZE;
+            traces.extend(page);
+            if last_page {
+                return Ok(traces);
+            }
+        }
+    }
+
+    /// Call trees of a block's transactions, via debug_traceBlockByNumber with the callTracer
+    pub async fn debug_trace_block(&self, block_number: u64) -> Result<Vec<BlockTrace>, RpcError> {
+        let params = json!([format!("0x{:x}", block_number), { "tracer": "callTracer" }]);
+        self.request("debug_traceBlockByNumber", params).await
+    }
+
+    /// Call tree of a transaction, via debug_traceTransaction with the callTracer
+    pub async fn debug_trace_transaction(&self, tx_hash: &str) -> Result<CallFrame, RpcError> {
+        let params = json!([tx_hash, { "tracer": "callTracer" }]);
+        self.request("debug_traceTransaction", params).await
+    }
+
+    /// Provider type of the active endpoint (for logging without leaking keys)
+    pub fn provider(&self) -> &'static str {
+        provider_from_url(self.url())
+    }
+
+    /// Provider types of all endpoints, in order of preference
+    pub fn providers(&self) -> Vec<&'static str> {
+        self.endpoints.iter().map(|e| provider_from_url(&e.url)).collect()
+    }
+
+    /// Provider of the active RPC endpoint (for logging/debugging)
+    pub fn active_provider(&self) -> &'static str {
+        provider_from_url(self.url())
+    }
+
+    /// Get the active RPC endpoint URL (for logging/debugging)
     pub fn url(&self) -> &str {
-        &self.url
+        &self.endpoints[self.active.load(Ordering::Relaxed)].url
     }
 
     /// Get the chain name (for logging/debugging)
@@ -328,6 +1213,255 @@ impl RpcClient {
     }
 }
 
+// =============================================================================
+// WebSocket subscriptions (eth_subscribe)
+// =============================================================================
+
+/// Request ids used for the two subscriptions on a connection
+const NEW_HEADS_REQUEST_ID: u64 = 1;
+const LOGS_REQUEST_ID: u64 = 2;
+
+/// A decoded message from an eth_subscribe connection
+#[derive(Debug)]
+enum WsMessage {
+    /// Subscription confirmed: (request id, subscription id)
+    Subscribed(u64, String),
+    /// newHeads notification carrying the block number
+    Head(u64),
+    /// logs notification
+    Log(Log),
+    /// logs notification for a log removed by a reorg
+    Removed(Log),
+    /// JSON-RPC error response
+    Error(String),
+    Other,
+}
+
+/// An event forwarded from an eth_subscribe connection
+#[derive(Debug, Clone)]
+pub enum LiveEvent {
+    /// A new head block number
+    Head(u64),
+    /// A log included in a block
+    Log(Log),
+    /// A previously sent log whose block was reorged out
+    Removed(Log),
+}
+
+impl RpcClient {
+    /// Stream logs whose topic0 is any of `topics` over a WebSocket subscription
+    ///
+    /// Subscribes to `newHeads` and `logs` and forwards heads, logs and
+    /// reorged-out logs to `tx`. Returns when the connection drops, nothing
+    /// arrives for `idle_timeout`, or the receiver is dropped (`Ok`); the
+    /// caller is expected to reconnect.
+    pub async fn subscribe_logs(
+        &self,
+        ws_url: &str,
+        topics: &[&str],
+        tx: mpsc::Sender<LiveEvent>,
+        idle_timeout: Duration,
+    ) -> Result<(), RpcError> {
+        let (mut ws, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .map_err(|e| RpcError::WebSocket(e.to_string()))?;
+
+        let requests = [
+            json!({
+                "jsonrpc": "2.0",
+                "id": NEW_HEADS_REQUEST_ID,
+                "method": "eth_subscribe",
+                "params": ["newHeads"]
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": LOGS_REQUEST_ID,
+                "method": "eth_subscribe",
+                "params": ["logs", { "topics": [topics] }]
+            }),
+        ];
+        for request in requests {
+            ws.send(Message::Text(request.to_string()))
+                .await
+                .map_err(|e| RpcError::WebSocket(e.to_string()))?;
+        }
+
+        loop {
+            let message = match timeout(idle_timeout, ws.next()).await {
+                Ok(Some(Ok(message))) => message,
+                Ok(Some(Err(e))) => return Err(RpcError::WebSocket(e.to_string())),
+                Ok(None) => return Err(RpcError::WebSocket("connection closed".to_string())),
+                Err(_) => {
+                    return Err(RpcError::WebSocket(format!(
+                        "no message for {}s",
+                        idle_timeout.as_secs()
+                    )))
+                }
+            };
+
+            let text = match message {
+                Message::Text(text) => text,
+                Message::Close(_) => return Err(RpcError::WebSocket("closed by server".to_string())),
+                _ => continue, // Pings are answered by tungstenite
+            };
+
+            let event = match parse_ws_message(&text) {
+                WsMessage::Subscribed(id, subscription) => {
+                    let kind = if id == NEW_HEADS_REQUEST_ID { "newHeads" } else { "logs" };
+                    info!(
+                        "[{}] Subscribed to {} ({})",
+                        self.chain_name, kind, subscription
+                    );
+                    continue;
+                }
+                WsMessage::Head(block_number) => {
+                    debug!("[{}] New head {}", self.chain_name, block_number);
+                    LiveEvent::Head(block_number)
+                }
+                WsMessage::Log(log) => LiveEvent::Log(log),
+                WsMessage::Removed(log) => {
+                    debug!(
+                        "[{}] Log {}:{} removed by a reorg",
+                        self.chain_name,
+                        log.transaction_hash,
+                        log.log_index_u32()
+                    );
+                    LiveEvent::Removed(log)
+                }
+                WsMessage::Error(e) => return Err(RpcError::Rpc(e)),
+                WsMessage::Other => continue,
+            };
+            if tx.send(event).await.is_err() {
+                return Ok(()); // Poller stopped
+            }
+        }
+    }
+}
+
+/// Decode a text frame from an eth_subscribe connection
+fn parse_ws_message(text: &str) -> WsMessage {
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        return WsMessage::Other;
+    };
+
+    if let Some(error) = value.get("error") {
+        return WsMessage::Error(error.to_string());
+    }
+
+    if let (Some(id), Some(subscription)) = (
+        value.get("id").and_then(Value::as_u64),
+        value.get("result").and_then(Value::as_str),
+    ) {
+        return WsMessage::Subscribed(id, subscription.to_string());
+    }
+
+    if value.get("method").and_then(Value::as_str) != Some("eth_subscription") {
+        return WsMessage::Other;
+    }
+    let Some(result) = value.pointer("/params/result") else {
+        return WsMessage::Other;
+    };
+
+    // Heads carry `number`, logs carry `logIndex`
+    if let Some(number) = result.get("number").and_then(Value::as_str) {
+        return u64::from_str_radix(number.trim_start_matches("0x"), 16)
+            .map(WsMessage::Head)
+            .unwrap_or(WsMessage::Other);
+    }
+    let removed = result.get("removed").and_then(Value::as_bool) == Some(true);
+    match serde_json::from_value::<Log>(result.clone()) {
+        Ok(log) if removed => WsMessage::Removed(log),
+        Ok(log) => WsMessage::Log(log),
+        Err(_) => WsMessage::Other,
+    }
+}
+
+/// Whether an RPC error message means an eth_getLogs range or result set was too large
+///
+/// Covers the wording used by Alchemy ("Log response size exceeded"), Infura
+/// ("query returned more than 10000 results"), QuickNode ("limited to a 10,000
+/// range"), Ankr/geth-style nodes ("block range is too wide", "exceed maximum
+/// block range") and similar.
+pub fn is_range_too_large(message: &str) -> bool {
+    const PATTERNS: [&str; 9] = [
+        "query returned more than",
+        "response size exceeded",
+        "response size should not",
+        "block range",
+        "range too large",
+        "range is too",
+        "is limited to a",
+        "too many results",
+        "exceed maximum",
+    ];
+    let message = message.to_lowercase();
+    PATTERNS.iter().any(|p| message.contains(p))
+}
+
+/// Parse a Retry-After header given in seconds, capped at `MAX_RETRY_AFTER`
+///
+/// The HTTP-date form is not used by RPC providers and is ignored.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let secs: u64 = value.trim().parse().ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// Lowercase host of a URL, without port or path
+fn url_host(url: &str) -> String {
+    url.split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', ':', '?'])
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// Classify an RPC endpoint by its host
+pub fn provider_from_url(url: &str) -> &'static str {
+    let host = url_host(url);
+
+    if host.ends_with("alchemy.com") {
+        "Alchemy"
+    } else if host.ends_with("infura.io") {
+        "Infura"
+    } else if host.contains("quiknode.pro") || host.contains("quicknode") {
+        "QuickNode"
+    } else if host.ends_with("ankr.com") {
+        "Ankr"
+    } else if host.ends_with("publicnode.com") {
+        "PublicNode"
+    } else if host == "localhost"
+        || host.starts_with("127.")
+        || host.starts_with("10.")
+        || host.starts_with("192.168.")
+    {
+        "self-hosted"
+    } else {
+        "custom"
+    }
+}
+
+/// Serve JSON-RPC for tests on a local port, answering each request body
+/// with `respond`; returns the URL
+#[cfg(test)]
+pub(crate) async fn mock_rpc<F, Fut>(respond: F) -> String
+where
+    F: Fn(serde_json::Value) -> Fut + Clone + Send + Sync + 'static,
+    Fut: std::future::Future<Output = serde_json::Value> + Send,
+{
+    let app = axum::Router::new().route(
+        "/",
+        axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| async move {
+            axum::Json(respond(request).await)
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,4 +1476,365 @@ mod tests {
         assert!(!RpcClient::is_retryable_status(400));
         assert!(!RpcClient::is_retryable_status(500));
     }
+
+    #[test]
+    fn test_retry_delays() {
+        assert_eq!(parse_retry_after("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("3600"), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+
+        let client = RpcClient::with_config("http://localhost:8545", "test", 3, 100);
+        for retries in 1..=10 {
+            let step = Duration::from_millis((100u64 << (retries - 1)).min(10_000));
+            let delay = client.backoff(retries);
+            assert!(delay >= step / 2 && delay <= step, "retry {}: {:?}", retries, delay);
+        }
+
+        let wait = jittered(Duration::from_secs(4));
+        assert!(wait >= Duration::from_secs(4) && wait <= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_usage_key() {
+        assert_eq!(usage_key("eth_blockNumber", &json!([])), "eth_blockNumber");
+
+        let filter = |topics: Value| json!([{ "fromBlock": "0x1", "toBlock": "0x2", "topics": topics }]);
+        assert_eq!(usage_key("eth_getLogs", &filter(json!([TRANSFER_TOPIC]))), "eth_getLogs:transfers");
+        assert_eq!(
+            usage_key("eth_getLogs", &filter(json!([[SRC_ESCROW_CREATED_TOPIC, ESCROW_CANCELLED_TOPIC]]))),
+            "eth_getLogs:fusion_plus"
+        );
+        assert_eq!(
+            usage_key("eth_getLogs", &filter(json!([[TRANSFER_TOPIC, APPROVAL_TOPIC]]))),
+            "eth_getLogs:combined"
+        );
+        assert_eq!(usage_key("eth_getLogs", &json!([{ "fromBlock": "0x1" }])), "eth_getLogs:all");
+    }
+
+    #[test]
+    fn test_block_id() {
+        assert_eq!(BlockId::parse("finalized"), Some(BlockId::Finalized));
+        assert_eq!(BlockId::parse(" Safe "), Some(BlockId::Safe));
+        assert_eq!(BlockId::parse("0x3e8"), Some(BlockId::Number(1000)));
+        assert_eq!(BlockId::parse("1000"), Some(BlockId::Number(1000)));
+        assert_eq!(BlockId::parse("earliest"), None);
+
+        assert_eq!(BlockId::Number(1000).to_param(), "0x3e8");
+        assert_eq!(BlockId::Pending.to_param(), "pending");
+        assert_eq!(BlockId::Number(1000).to_string(), "1000");
+        assert_eq!(BlockId::Latest.to_string(), "latest");
+    }
+
+    #[test]
+    fn test_asset_transfers_page() {
+        let page: AssetTransfersPage = serde_json::from_value(json!({
+            "transfers": [
+                {
+                    "blockNum": "0x1312d00",
+                    "uniqueId": "0xabc:external",
+                    "hash": "0xabc",
+                    "from": "0x1111111111111111111111111111111111111111",
+                    "to": "0x2222222222222222222222222222222222222222",
+                    "value": 1.5,
+                    "asset": "ETH",
+                    "category": "external",
+                    "rawContract": { "value": "0x14D1120D7B160000", "address": null, "decimal": "0x12" }
+                },
+                {
+                    "blockNum": "0x1312d01",
+                    "uniqueId": "0xdef:internal:0",
+                    "hash": "0xdef",
+                    "from": "0x2222222222222222222222222222222222222222",
+                    "to": null,
+                    "value": null,
+                    "asset": "ETH",
+                    "category": "internal",
+                    "rawContract": { "value": null, "address": null, "decimal": null }
+                }
+            ],
+            "pageKey": "f1a2b3"
+        }))
+        .unwrap();
+
+        assert_eq!(page.page_key.as_deref(), Some("f1a2b3"));
+        assert_eq!(page.transfers[0].block_number_u64(), 20_000_000);
+        assert_eq!(
+            page.transfers[0].value_word().as_deref(),
+            Some("0x00000000000000000000000000000000000000000000000014d1120d7b160000")
+        );
+        assert_eq!(page.transfers[1].to, None);
+        assert_eq!(page.transfers[1].value_word(), None);
+    }
+
+    #[test]
+    fn test_provider_from_url() {
+        assert_eq!(provider_from_url("https://eth-mainnet.g.alchemy.com/v2/key"), "Alchemy");
+        assert_eq!(provider_from_url("https://mainnet.infura.io/v3/key"), "Infura");
+        assert_eq!(provider_from_url("https://x.base-mainnet.quiknode.pro/key/"), "QuickNode");
+        assert_eq!(provider_from_url("http://127.0.0.1:8545"), "self-hosted");
+        assert_eq!(provider_from_url("https://rpc.example.org"), "custom");
+    }
+
+    #[test]
+    fn test_is_range_too_large() {
+        assert!(is_range_too_large("query returned more than 10000 results"));
+        assert!(is_range_too_large("Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"));
+        assert!(is_range_too_large("eth_getLogs is limited to a 10,000 range"));
+        assert!(is_range_too_large("block range is too wide"));
+        assert!(is_range_too_large("exceed maximum block range: 5000"));
+        assert!(!is_range_too_large("daily request count exceeded, request rate limited"));
+        assert!(!is_range_too_large("execution reverted"));
+    }
+
+    #[test]
+    fn test_failover_rotation_and_recovery() {
+        let rpc = RpcClient::with_endpoints(&["http://a", "http://b", "http://c"], "test", 3, 1);
+        assert_eq!(rpc.select_endpoint(), 0);
+
+        // Below the threshold the active endpoint is kept
+        rpc.mark_failure(0);
+        rpc.mark_failure(0);
+        assert_eq!(rpc.select_endpoint(), 0);
+        rpc.mark_success(0);
+        rpc.mark_failure(0);
+        rpc.mark_failure(0);
+        assert_eq!(rpc.select_endpoint(), 0);
+
+        rpc.mark_failure(0);
+        assert_eq!(rpc.url(), "http://b");
+
+        // b fails too; c is next, a is still resting
+        for _ in 0..FAILOVER_THRESHOLD {
+            rpc.mark_failure(1);
+        }
+        assert_eq!(rpc.url(), "http://c");
+
+        // A probe due on a becomes active again once it succeeds
+        rpc.endpoints[0].health.lock().unwrap().unhealthy_since = Some(Instant::now() - PROBE_INTERVAL);
+        assert_eq!(rpc.select_endpoint(), 0);
+        assert_eq!(rpc.select_endpoint(), 2); // Probe rescheduled
+        rpc.mark_success(0);
+        assert_eq!(rpc.url(), "http://a");
+    }
+
+    #[test]
+    fn test_parse_ws_message() {
+        let msg = parse_ws_message(r#"{"jsonrpc":"2.0","id":2,"result":"0xabc"}"#);
+        assert!(matches!(msg, WsMessage::Subscribed(2, ref sub) if sub == "0xabc"));
+
+        let msg = parse_ws_message(
+            r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"subscription":"0x1","result":{"number":"0x10","hash":"0x00"}}}"#,
+        );
+        assert!(matches!(msg, WsMessage::Head(16)));
+
+        let log = r#"{"address":"0x01","topics":["0xddf2"],"data":"0x","blockNumber":"0x10","transactionHash":"0xaa","logIndex":"0x3","removed":false}"#;
+        let msg = parse_ws_message(&format!(
+            r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"subscription":"0x2","result":{}}}}}"#,
+            log
+        ));
+        assert!(matches!(msg, WsMessage::Log(ref l) if l.log_index_u32() == 3 && l.transaction_hash == "0xaa"));
+
+        let removed = log.replace(r#""removed":false"#, r#""removed":true"#);
+        let msg = parse_ws_message(&format!(
+            r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"subscription":"0x2","result":{}}}}}"#,
+            removed
+        ));
+        assert!(matches!(msg, WsMessage::Removed(ref l) if l.log_index_u32() == 3 && l.transaction_hash == "0xaa"));
+
+        let msg = parse_ws_message(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"not supported"}}"#);
+        assert!(matches!(msg, WsMessage::Error(_)));
+    }
+
+    async fn chain_id_rpc(chain_id: u64) -> String {
+        mock_rpc(move |_| async move { json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", chain_id) }) }).await
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain_id_drops_wrong_endpoints() {
+        let (right, wrong) = (chain_id_rpc(8453).await, chain_id_rpc(1).await);
+        // Nothing listens on port 1; an endpoint that can't answer is kept
+        let down = "http://127.0.0.1:1".to_string();
+
+        let mut client = RpcClient::with_endpoints(&[wrong.clone(), right.clone(), down.clone()], "test", 0, 1);
+        client.active.store(2, Ordering::Relaxed);
+        client.verify_chain_id(8453).await.unwrap();
+        let urls: Vec<_> = client.endpoints.iter().map(|e| e.url.clone()).collect();
+        assert_eq!(urls, [right, down]);
+        assert_eq!(client.url(), urls[0]);
+        assert!(client.endpoints[1].health.lock().unwrap().chain_unverified);
+
+        let mut client = RpcClient::with_endpoints(&[wrong.clone(), wrong], "test", 0, 1);
+        assert!(client.verify_chain_id(8453).await.is_err());
+        assert_eq!(client.endpoints.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unverified_endpoint_checked_before_use() {
+        // Fails eth_chainId at startup, then reports `chain_id`; answers 0x10 otherwise
+        let flaky = |chain_id: u64| {
+            let calls = std::sync::Arc::new(AtomicUsize::new(0));
+            mock_rpc(move |request| {
+                let calls = std::sync::Arc::clone(&calls);
+                async move {
+                    if request["method"] != "eth_chainId" {
+                        return json!({ "jsonrpc": "2.0", "id": 1, "result": "0x10" });
+                    }
+                    if calls.fetch_add(1, Ordering::Relaxed) == 0 {
+                        return json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32603, "message": "warming up" } });
+                    }
+                    json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", chain_id) })
+                }
+            })
+        };
+        let right = mock_rpc(|request| async move {
+            let result = if request["method"] == "eth_chainId" { "0x2105" } else { "0x20" };
+            json!({ "jsonrpc": "2.0", "id": 1, "result": result })
+        })
+        .await;
+
+        // Kept at startup, then failed over once it turns out to serve another chain
+        let wrong = flaky(1).await;
+        let mut client = RpcClient::with_endpoints(&[wrong.clone(), right.clone()], "test", 2, 1);
+        client.verify_chain_id(8453).await.unwrap();
+        assert_eq!(client.endpoints.len(), 2);
+        assert_eq!(client.get_block_number().await.unwrap(), 0x20);
+        assert_eq!(client.url(), right);
+        assert!(client.endpoints[0].health.lock().unwrap().unhealthy_since.is_some());
+
+        // Used once it turns out to serve the configured chain
+        let late = flaky(8453).await;
+        let mut client = RpcClient::with_endpoints(&[late.clone(), right], "test", 2, 1);
+        client.verify_chain_id(8453).await.unwrap();
+        assert_eq!(client.get_block_number().await.unwrap(), 0x10);
+        assert_eq!(client.url(), late);
+        assert!(!client.endpoints[0].health.lock().unwrap().chain_unverified);
+
+        // A lone endpoint serving another chain fails every request
+        let mut client = RpcClient::with_endpoints(&[flaky(1).await], "test", 2, 1);
+        client.verify_chain_id(8453).await.unwrap();
+        assert!(client.get_block_number().await.is_err());
+        assert!(client.get_block_number().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain_id_uses_network_http_options() {
+        let slow = mock_rpc(|_| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1" })
+        })
+        .await;
+        let contents = format!("[[networks]]\nchain_id = 1\nrpc_url = \"{}\"\n[networks.http]\ntimeout_secs = 1", slow);
+        let network = crate::config::networks_from_toml(&contents, None, &|_| None).unwrap().remove(0);
+
+        let mut client = RpcClient::for_network(&network);
+        let started = Instant::now();
+        let results = client.get_chain_ids().await;
+        assert!(results[0].1.is_err());
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // A timed-out endpoint isn't dropped
+        client.verify_chain_id(1).await.unwrap();
+        assert_eq!(client.endpoints.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_transitions() {
+        let mut client = RpcClient::new("http://127.0.0.1:1", "test");
+        client.circuit_breaker = Some((2, Duration::from_millis(50)));
+        let failed: Result<(), RpcError> = Err(RpcError::RateLimited);
+
+        // Closed: an RPC error means the endpoint is up and resets the count
+        client.record_circuit(&failed);
+        client.record_circuit(&Err::<(), _>(RpcError::Rpc("execution reverted".to_string())));
+        client.record_circuit(&failed);
+        assert_eq!(client.circuit_state().state, "closed");
+        assert!(client.check_circuit().is_ok());
+
+        // Open after two consecutive failures
+        client.record_circuit(&failed);
+        assert_eq!(client.circuit_state().state, "open");
+        assert_eq!(client.circuit_state().trips, 1);
+        assert!(matches!(client.check_circuit(), Err(RpcError::CircuitOpen(_))));
+
+        // Half-open after the cooldown: one trial request, the others wait for it
+        sleep(Duration::from_millis(70)).await;
+        assert!(client.check_circuit().is_ok());
+        assert_eq!(client.circuit_state().state, "half_open");
+        assert!(matches!(client.check_circuit(), Err(RpcError::CircuitHalfOpen)));
+
+        // A failed trial reopens the circuit at once
+        client.record_circuit(&failed);
+        assert_eq!(client.circuit_state().state, "open");
+        assert_eq!(client.circuit_state().trips, 2);
+
+        // A successful trial closes it
+        sleep(Duration::from_millis(70)).await;
+        assert!(client.check_circuit().is_ok());
+        client.record_circuit(&Ok(()));
+        let state = client.circuit_state();
+        assert_eq!((state.state, state.consecutive_failures), ("closed", 0));
+        assert!(client.check_circuit().is_ok() && client.check_circuit().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_replaces_abandoned_trial() {
+        let mut client = RpcClient::new("http://127.0.0.1:1", "test");
+        client.circuit_breaker = Some((1, Duration::from_millis(50)));
+        client.record_circuit(&Err::<(), _>(RpcError::RateLimited));
+        sleep(Duration::from_millis(70)).await;
+
+        // The trial never reports back, e.g. because its poll was cancelled
+        assert!(client.check_circuit().is_ok());
+        assert!(matches!(client.check_circuit(), Err(RpcError::CircuitHalfOpen)));
+        sleep(Duration::from_millis(70)).await;
+        assert!(client.check_circuit().is_ok());
+    }
+
+    #[test]
+    fn test_is_method_unsupported() {
+        let error = |code, message: &str| RpcError::JsonRpc { code, message: message.to_string() };
+        assert!(error(-32601, "Method not found").is_method_unsupported());
+        assert!(error(-32000, "the method trace_filter does not exist/is not available").is_method_unsupported());
+        assert!(!error(-32000, "request timed out").is_method_unsupported());
+        assert!(!error(3, "execution reverted").is_method_unsupported());
+        assert!(!RpcError::Rpc("HTTP error 500 Internal Server Error from trace_filter".to_string()).is_method_unsupported());
+    }
+
+    #[tokio::test]
+    async fn test_trace_filter_pages() {
+        // 1500 matching traces, served at most `count` at a time from `after`
+        let url = mock_rpc(|request: serde_json::Value| async move {
+            let filter = &request["params"][0];
+            let after = filter["after"].as_u64().unwrap();
+            let count = filter["count"].as_u64().unwrap();
+            let traces: Vec<_> = (after..1500.min(after + count))
+                .map(|i| {
+                    json!({
+                        "action": { "from": "0x01", "to": "0x02", "value": "0x1" },
+                        "blockNumber": 1,
+                        "transactionHash": format!("0x{:064x}", i),
+                        "traceAddress": [0],
+                        "type": "call",
+                    })
+                })
+                .collect();
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": traces })
+        })
+        .await;
+        let client = RpcClient::new(&url, "test");
+
+        let traces = client.trace_filter(1, 1, &["0x01".to_string()]).await.unwrap();
+        assert_eq!(traces.len(), 1500);
+    }
+
+    #[test]
+    fn test_circuit_breaker_disabled() {
+        let mut client = RpcClient::new("http://127.0.0.1:1", "test");
+        client.circuit_breaker = None;
+        for _ in 0..10 {
+            client.record_circuit(&Err::<(), _>(RpcError::RateLimited));
+        }
+        assert!(client.check_circuit().is_ok());
+        assert_eq!(client.circuit_state().state, "closed");
+    }
 }
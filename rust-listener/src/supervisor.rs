@@ -0,0 +1,329 @@
+//! Per-chain poller supervision: restarts pollers that stop, applies admin
+//! API commands and reloads the networks config
+
+use crate::config::{ws_url_for, EnrichRetry, StaleHead, StorageMode, TxStatusCheck, WriteBatching};
+use crate::control::{ChainCommand, ChainControl};
+use crate::db::Database;
+use crate::events::EventBus;
+use crate::health::HealthRegistry;
+use crate::poller::{ChainPoller, PollerConfig};
+use crate::types::{NetworkConfig, TraceMethod};
+use crate::watchlist::Watchlist;
+use futures_util::FutureExt;
+use std::any::Any;
+use std::collections::{BTreeMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Instant};
+use tracing::{error, info, warn};
+
+/// Settings and shared handles every chain's poller is built with
+pub struct PollerFactory {
+    pub db: Arc<Database>,
+    /// Set in watchlist-only mode
+    pub watchlist: Option<Arc<Watchlist>>,
+    /// Set when internal transfers are traced
+    pub trace_watchlist: Option<Arc<Watchlist>>,
+    pub events: Arc<EventBus>,
+    pub health: Arc<HealthRegistry>,
+    pub storage_mode: StorageMode,
+    pub archive_raw_logs: bool,
+    pub fetch_token_metadata: bool,
+    pub fetch_transactions: bool,
+    pub internal_transfers: Option<TraceMethod>,
+    pub tx_status: Option<TxStatusCheck>,
+    pub delegation_topics: Vec<String>,
+    pub enrich_retry: Option<EnrichRetry>,
+    pub gap_scan_interval_secs: Option<u64>,
+    pub stale_head: Option<StaleHead>,
+    pub write_batching: WriteBatching,
+    pub ws_enabled: bool,
+}
+
+impl PollerFactory {
+    /// Poller for `network` with the shared settings and its own overrides
+    pub fn build(&self, network: &NetworkConfig) -> ChainPoller {
+        let config = PollerConfig {
+            storage_mode: self.storage_mode,
+            archive_raw_logs: self.archive_raw_logs,
+            fetch_token_metadata: self.fetch_token_metadata,
+            fetch_transactions: self.fetch_transactions,
+            internal_transfers: self.internal_transfers,
+            tx_status: self.tx_status,
+            delegation_topics: self.delegation_topics.clone(),
+            enrich_retry: self.enrich_retry,
+            gap_scan_interval_secs: self.gap_scan_interval_secs,
+            stale_head: self.stale_head,
+            write_batch_rows: self.write_batching.batch_rows,
+            write_flush_ms: self.write_batching.flush_ms,
+            ..Default::default()
+        }
+        .with_overrides(&network.poller);
+        let ws_url = self.ws_url(network);
+        let mut poller = ChainPoller::with_config(network.clone(), Arc::clone(&self.db), config);
+        if let Some(ws_url) = ws_url {
+            poller = poller.with_ws_subscription(ws_url);
+        }
+        if let Some(watchlist) = &self.watchlist {
+            poller = poller.with_watchlist(Arc::clone(watchlist));
+        }
+        if let Some(watchlist) = &self.trace_watchlist {
+            poller = poller.with_trace_watchlist(Arc::clone(watchlist));
+        }
+        poller.with_events(Arc::clone(&self.events)).with_health(Arc::clone(&self.health))
+    }
+
+    fn ws_url(&self, network: &NetworkConfig) -> Option<String> {
+        if self.ws_enabled { ws_url_for(network) } else { None }
+    }
+}
+
+/// A chain's supervisor task and the configuration its poller is built from
+pub struct RunningChain {
+    network: watch::Sender<NetworkConfig>,
+    handle: JoinHandle<()>,
+}
+
+impl RunningChain {
+    /// Stop the chain's supervisor and poller
+    pub fn abort(&self) {
+        self.handle.abort();
+    }
+}
+
+/// Start supervising a poller for `network`
+pub fn spawn_chain(factory: &Arc<PollerFactory>, control: &ChainControl, network: NetworkConfig) -> RunningChain {
+    let chain_name = network.name.clone();
+    if factory.ws_enabled && factory.ws_url(&network).is_none() {
+        warn!("[{}] No WebSocket URL (set WS_URL_{}), using HTTP polling", chain_name, network.chain_id);
+    }
+
+    let supervisor = Supervisor {
+        chain_id: network.chain_id,
+        chain_name: chain_name.clone(),
+        health: Arc::clone(&factory.health),
+        commands: control.register(network.chain_id),
+    };
+    let (network, network_rx) = watch::channel(network);
+    let factory = Arc::clone(factory);
+    let handle = tokio::spawn(supervisor.run(move || factory.build(&network_rx.borrow())));
+
+    info!("Spawned poller for {}", chain_name);
+    RunningChain { network, handle }
+}
+
+/// Apply a re-read networks config: start pollers for added chains, stop
+/// removed ones and restart those whose settings changed. Checkpoints are kept
+/// in the database, so restarted and re-added chains resume where they left off.
+///
+/// A config that failed to load leaves every running chain as it is.
+pub fn reload_networks(
+    chains: &mut BTreeMap<u32, RunningChain>,
+    factory: &Arc<PollerFactory>,
+    control: &ChainControl,
+    networks: Result<Vec<NetworkConfig>, String>,
+) {
+    let networks = match networks {
+        Ok(networks) => networks,
+        Err(e) => {
+            error!("Config reload failed, keeping the running chains: {}", e);
+            return;
+        }
+    };
+
+    let configured: HashSet<u32> = networks.iter().map(|n| n.chain_id).collect();
+    chains.retain(|&chain_id, chain| {
+        if configured.contains(&chain_id) {
+            return true;
+        }
+        info!("[{}] Removed from config, stopping poller", chain.network.borrow().name);
+        // Dropping the poller flushes its queued writes
+        chain.handle.abort();
+        control.unregister(chain_id);
+        factory.health.unregister(chain_id);
+        false
+    });
+
+    for network in networks {
+        match chains.get(&network.chain_id) {
+            None => {
+                info!("[{}] Added to config", network.name);
+                chains.insert(network.chain_id, spawn_chain(factory, control, network));
+            }
+            Some(chain) if *chain.network.borrow() != network => {
+                info!("[{}] Config changed", network.name);
+                chain.network.send_replace(network.clone());
+                control.send(network.chain_id, ChainCommand::Reload);
+            }
+            Some(_) => {}
+        }
+    }
+    info!("Config reloaded: {} chains", chains.len());
+}
+
+/// Delay before restarting a stopped poller, doubled per consecutive failure
+const POLLER_RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+const POLLER_RESTART_MAX_DELAY: Duration = Duration::from_secs(300);
+/// A poller that ran at least this long before stopping restarts after the base delay
+const POLLER_HEALTHY_RUN: Duration = Duration::from_secs(600);
+
+/// Keeps a chain's poller running, restarting it with exponential backoff
+/// whenever its run loop returns or panics, and applies admin API commands
+///
+/// `ChainPoller::run` only returns on failure (e.g. the checkpoint could not
+/// be initialized), so every exit is treated as one.
+struct Supervisor {
+    chain_id: u32,
+    chain_name: String,
+    health: Arc<HealthRegistry>,
+    commands: mpsc::UnboundedReceiver<ChainCommand>,
+}
+
+impl Supervisor {
+    /// Run pollers built by `build` until the task is aborted
+    async fn run<F>(mut self, mut build: F)
+    where
+        F: FnMut() -> ChainPoller,
+    {
+        let mut delay = POLLER_RESTART_BASE_DELAY;
+        loop {
+            let mut poller = build();
+            let started = Instant::now();
+            let command = {
+                let run = AssertUnwindSafe(poller.run()).catch_unwind();
+                tokio::pin!(run);
+                loop {
+                    tokio::select! {
+                        result = &mut run => {
+                            match result {
+                                Ok(()) => error!("[{}] Poller stopped", self.chain_name),
+                                Err(panic) => error!("[{}] Poller panicked: {}", self.chain_name, panic_message(panic.as_ref())),
+                            }
+                            break None;
+                        }
+                        Some(command) = self.commands.recv() => {
+                            if command != ChainCommand::Start {
+                                break Some(command);
+                            }
+                        }
+                    }
+                }
+            };
+            // Closes its writer and WebSocket tasks before the replacement starts
+            drop(poller);
+
+            let command = match command {
+                Some(command) => Some(command),
+                None => {
+                    if started.elapsed() >= POLLER_HEALTHY_RUN {
+                        delay = POLLER_RESTART_BASE_DELAY;
+                    }
+                    warn!("[{}] Restarting poller in {}s", self.chain_name, delay.as_secs());
+                    let command = tokio::select! {
+                        () = sleep(delay) => None,
+                        Some(command) = self.commands.recv() => Some(command),
+                    };
+                    delay = (delay * 2).min(POLLER_RESTART_MAX_DELAY);
+                    command
+                }
+            };
+
+            match command {
+                Some(ChainCommand::Stop) => {
+                    info!("[{}] Poller stopped through the admin API", self.chain_name);
+                    if !self.wait_for_start().await {
+                        return;
+                    }
+                    delay = POLLER_RESTART_BASE_DELAY;
+                }
+                Some(ChainCommand::Start | ChainCommand::Restart) => {
+                    info!("[{}] Restarting poller through the admin API", self.chain_name);
+                    delay = POLLER_RESTART_BASE_DELAY;
+                }
+                Some(ChainCommand::Reload) => {
+                    info!("[{}] Restarting poller with reloaded configuration", self.chain_name);
+                    delay = POLLER_RESTART_BASE_DELAY;
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Wait while stopped; false if the command channel closed
+    async fn wait_for_start(&mut self) -> bool {
+        self.health.set_stopped(self.chain_id, true);
+        loop {
+            match self.commands.recv().await {
+                // Reloaded configuration is picked up once started
+                Some(ChainCommand::Stop | ChainCommand::Reload) => {}
+                Some(ChainCommand::Start | ChainCommand::Restart) => break,
+                None => return false,
+            }
+        }
+        self.health.set_stopped(self.chain_id, false);
+        info!("[{}] Poller started through the admin API", self.chain_name);
+        true
+    }
+}
+
+/// Message carried by a panic payload, if it is a string
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(|s| s.as_str()))
+        .unwrap_or("non-string panic payload")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::networks_from_toml;
+
+    #[tokio::test]
+    async fn test_reload_networks_keeps_chains_on_error() {
+        let Some((_guard, db)) = crate::db::test_database().await else {
+            return;
+        };
+        let factory = Arc::new(PollerFactory {
+            db: Arc::new(db),
+            watchlist: None,
+            trace_watchlist: None,
+            events: Arc::new(EventBus::new()),
+            health: Arc::new(HealthRegistry::new(60)),
+            storage_mode: StorageMode::Full,
+            archive_raw_logs: false,
+            fetch_token_metadata: false,
+            fetch_transactions: false,
+            internal_transfers: None,
+            tx_status: None,
+            delegation_topics: Vec::new(),
+            enrich_retry: None,
+            gap_scan_interval_secs: None,
+            stale_head: None,
+            write_batching: WriteBatching { batch_rows: 1, flush_ms: 0 },
+            ws_enabled: false,
+        });
+        let control = ChainControl::new();
+        let contents = "[[networks]]\nchain_id = 990008\nname = \"Test\"\nrpc_url = \"http://127.0.0.1:1\"";
+        let network = networks_from_toml(contents, None, &|_| None).unwrap().remove(0);
+        let mut chains = BTreeMap::from([(
+            network.chain_id,
+            RunningChain { network: watch::channel(network).0, handle: tokio::spawn(std::future::pending()) },
+        )]);
+
+        // An unreadable file keeps the running chain
+        reload_networks(&mut chains, &factory, &control, Err("No such file or directory".to_string()));
+        assert!(!chains[&990_008].handle.is_finished());
+
+        // A config without the chain stops it
+        let handle = chains[&990_008].handle.abort_handle();
+        reload_networks(&mut chains, &factory, &control, Ok(Vec::new()));
+        assert!(chains.is_empty());
+        tokio::task::yield_now().await;
+        assert!(handle.is_finished());
+    }
+}
@@ -0,0 +1,69 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use tracing::Level;
+use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use tracing_subscriber::prelude::*;
+
+/// Flushes pending spans to the exporter when dropped
+pub struct Telemetry {
+    provider: Option<TracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OTLP spans: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the log output and, when `otlp_endpoint` is set, an OpenTelemetry
+/// exporter for the poll cycle, RPC and database spans
+///
+/// Spans only go to the exporter; log lines look the same with or without it.
+pub fn init(log_level: Level, otlp_endpoint: Option<&str>, service_name: &str) -> Telemetry {
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .with_thread_ids(false)
+        .with_file(false)
+        .with_line_number(false)
+        .with_filter(LevelFilter::from_level(log_level))
+        .with_filter(filter_fn(|metadata| !metadata.is_span()));
+
+    let provider = otlp_endpoint.and_then(|endpoint| match build_provider(endpoint, service_name) {
+        Ok(provider) => Some(provider),
+        Err(e) => {
+            eprintln!("Failed to start OTLP exporter for {}: {}", endpoint, e);
+            None
+        }
+    });
+
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("rust-listener"))
+            .with_filter(filter_fn(|metadata| metadata.is_span()))
+    });
+
+    tracing_subscriber::registry().with(fmt).with(otel).init();
+
+    Telemetry { provider }
+}
+
+fn build_provider(endpoint: &str, service_name: &str) -> Result<TracerProvider, String> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", service_name.to_string())]))
+        .build())
+}
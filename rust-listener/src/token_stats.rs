@@ -0,0 +1,36 @@
+use crate::db::Database;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+/// Transfers committed up to this long after their `created_at` still make the next rollup
+const ROLLUP_OVERLAP_SECS: u64 = 60;
+
+/// Roll up newly stored transfers into `token_stats` every `interval_secs`
+///
+/// Resumes from the last rollup stored in the database, so a restart only
+/// recounts the hours touched since then. Failed rollups are retried with the
+/// same window on the next tick.
+pub async fn run(db: Arc<Database>, interval_secs: u64) {
+    let mut since = match db.get_token_stats_updated_at().await {
+        Ok(updated_at) => updated_at.unwrap_or(0).saturating_sub(ROLLUP_OVERLAP_SECS),
+        Err(e) => {
+            warn!("Token stats: failed to read the last rollup, recounting all transfers: {}", e);
+            0
+        }
+    };
+    info!("Rolling up token stats every {}s", interval_secs);
+
+    loop {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        match db.rollup_token_stats(since).await {
+            Ok(rows) => {
+                debug!("Token stats: rolled up {} token-hours", rows);
+                since = started.saturating_sub(ROLLUP_OVERLAP_SECS);
+            }
+            Err(e) => warn!("Token stats rollup error: {}", e),
+        }
+        sleep(Duration::from_secs(interval_secs)).await;
+    }
+}
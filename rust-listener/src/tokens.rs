@@ -0,0 +1,276 @@
+use crate::fusion::selector;
+use crate::rpc::{RpcClient, RpcError};
+use crate::types::TokenInfo;
+use alloy_primitives::U256;
+use futures_util::future::join_all;
+
+const SYMBOL_SIG: &str = "symbol()";
+const NAME_SIG: &str = "name()";
+const DECIMALS_SIG: &str = "decimals()";
+const AGGREGATE3_SIG: &str = "aggregate3((address,bool,bytes)[])";
+
+/// Tokens read per Multicall3 `aggregate3` call (three calls each)
+pub const MULTICALL_BATCH_TOKENS: usize = 50;
+
+/// Longest symbol/name kept; longer strings are truncated
+const MAX_TEXT_CHARS: usize = 64;
+
+/// Read ERC-20 `symbol()`, `name()` and `decimals()` from `token` at the latest block
+///
+/// A call that reverts (or returns nothing, e.g. for an EOA) leaves its field
+/// empty; transport errors are returned so the token is retried later rather
+/// than cached without metadata.
+pub async fn fetch_token_info(rpc: &RpcClient, token: &str) -> Result<TokenInfo, RpcError> {
+    let (symbol_sel, name_sel, decimals_sel) = (selector(SYMBOL_SIG), selector(NAME_SIG), selector(DECIMALS_SIG));
+    let (symbol, name, decimals) = tokio::join!(
+        rpc.call(token, &symbol_sel),
+        rpc.call(token, &name_sel),
+        rpc.call(token, &decimals_sel),
+    );
+
+    Ok(TokenInfo {
+        symbol: reverted_as_none(symbol)?.as_deref().and_then(decode_string_result),
+        name: reverted_as_none(name)?.as_deref().and_then(decode_string_result),
+        decimals: reverted_as_none(decimals)?.as_deref().and_then(decode_decimals_result),
+    })
+}
+
+/// Read metadata for several tokens in one eth_call through Multicall3
+///
+/// Results are in the order of `tokens`. A token whose calls fail inside the
+/// batch (a non-standard token that reverts or runs out of the gas Multicall3
+/// forwards) is retried with `fetch_token_info`, as is every token when
+/// `aggregate3` itself reverts or returns something unexpected, e.g. because
+/// Multicall3 isn't deployed. A transport error fails the whole batch.
+pub async fn fetch_token_infos(
+    rpc: &RpcClient,
+    multicall3: &str,
+    tokens: &[String],
+) -> Result<Vec<Result<TokenInfo, RpcError>>, RpcError> {
+    let selectors = [selector(SYMBOL_SIG), selector(NAME_SIG), selector(DECIMALS_SIG)];
+    let calls: Vec<(&str, &str)> = tokens
+        .iter()
+        .flat_map(|token| selectors.iter().map(move |sel| (token.as_str(), sel.as_str())))
+        .collect();
+
+    let results = match rpc.call(multicall3, &encode_aggregate3(&calls)).await {
+        Ok(hex) => decode_aggregate3(&hex).filter(|results| results.len() == calls.len()),
+        Err(RpcError::Rpc(_) | RpcError::JsonRpc { .. }) => None,
+        Err(e) => return Err(e),
+    };
+
+    let batched: Vec<Option<TokenInfo>> = match &results {
+        Some(results) => results
+            .chunks(selectors.len())
+            .map(|chunk| match chunk {
+                [(true, symbol), (true, name), (true, decimals)] => Some(TokenInfo {
+                    symbol: decode_string_result(symbol),
+                    name: decode_string_result(name),
+                    decimals: decode_decimals_result(decimals),
+                }),
+                _ => None,
+            })
+            .collect(),
+        None => vec![None; tokens.len()],
+    };
+
+    Ok(join_all(tokens.iter().zip(batched).map(|(token, info)| async move {
+        match info {
+            Some(info) => Ok(info),
+            None => fetch_token_info(rpc, token).await,
+        }
+    }))
+    .await)
+}
+
+/// Calldata for `aggregate3` with `allowFailure` set on every call
+///
+/// Each call is `(target, calldata)` with hex strings.
+fn encode_aggregate3(calls: &[(&str, &str)]) -> String {
+    let word = |value: usize| format!("{:064x}", value);
+    let mut head = String::new();
+    let mut tail = String::new();
+    // Tuple offsets count from just after the array length
+    let mut offset = calls.len() * 32;
+    for (target, data) in calls {
+        let data = data.trim_start_matches("0x");
+        let padded = data.len().div_ceil(64) * 64;
+        head.push_str(&word(offset));
+        tail.push_str(&format!("{:0>64}", target.trim_start_matches("0x").to_lowercase()));
+        tail.push_str(&word(1));
+        tail.push_str(&word(0x60));
+        tail.push_str(&word(data.len() / 2));
+        tail.push_str(&format!("{:0<width$}", data, width = padded));
+        offset += 4 * 32 + padded / 2;
+    }
+    format!("{}{}{}{}{}", selector(AGGREGATE3_SIG), word(0x20), word(calls.len()), head, tail)
+}
+
+/// Decode `aggregate3`'s `(bool success, bytes returnData)[]` into hex return values
+fn decode_aggregate3(hex: &str) -> Option<Vec<(bool, String)>> {
+    let bytes = hex::decode(hex.trim_start_matches("0x")).ok()?;
+    let array = word_as_usize(&bytes, 0)?;
+    let len = word_as_usize(&bytes, array)?;
+    let base = array.checked_add(32)?;
+    (0..len)
+        .map(|i| {
+            let tuple = base.checked_add(word_as_usize(&bytes, base.checked_add(i.checked_mul(32)?)?)?)?;
+            let success = word_as_usize(&bytes, tuple)? != 0;
+            let data = tuple.checked_add(word_as_usize(&bytes, tuple.checked_add(32)?)?)?;
+            let data_len = word_as_usize(&bytes, data)?;
+            let start = data.checked_add(32)?;
+            let returned = bytes.get(start..start.checked_add(data_len)?)?;
+            Some((success, format!("0x{}", hex::encode(returned))))
+        })
+        .collect()
+}
+
+/// Treat an execution error (the node answered, the call failed) as a missing value
+fn reverted_as_none(result: Result<String, RpcError>) -> Result<Option<String>, RpcError> {
+    match result {
+        Ok(hex) => Ok(Some(hex)),
+        Err(RpcError::Rpc(_) | RpcError::JsonRpc { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Decode a `string` return value, or a `bytes32` one as used by older tokens (e.g. MKR)
+///
+/// Text returned without any ABI encoding, as a few tokens do, is accepted
+/// when the ABI decoding fails.
+pub fn decode_string_result(hex: &str) -> Option<String> {
+    let bytes = hex::decode(hex.trim_start_matches("0x")).ok()?;
+
+    let raw = if bytes.len() == 32 {
+        // bytes32: NUL-padded on the right
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(32);
+        &bytes[..end]
+    } else {
+        abi_string(&bytes).or_else(|| unencoded_text(&bytes))?
+    };
+
+    let text: String = String::from_utf8_lossy(raw)
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_TEXT_CHARS)
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// String data of an ABI-encoded `string`: offset word, length word, then the data
+fn abi_string(bytes: &[u8]) -> Option<&[u8]> {
+    let offset = word_as_usize(bytes, 0)?;
+    let len = word_as_usize(bytes, offset)?;
+    let end = offset.checked_add(32)?.checked_add(len)?;
+    bytes.get(offset + 32..end)
+}
+
+/// `bytes` without NUL padding, if what remains is plain UTF-8 text
+fn unencoded_text(bytes: &[u8]) -> Option<&[u8]> {
+    let start = bytes.iter().position(|&b| b != 0)?;
+    let end = bytes.iter().rposition(|&b| b != 0)? + 1;
+    let text = std::str::from_utf8(&bytes[start..end]).ok()?;
+    (!text.chars().any(char::is_control)).then_some(&bytes[start..end])
+}
+
+/// Decode a `uint8` return value
+///
+/// Only the first word of a longer return value is read, as tokens declaring
+/// `decimals()` with extra outputs return more.
+pub fn decode_decimals_result(hex: &str) -> Option<u8> {
+    let hex = hex.trim_start_matches("0x");
+    let word = hex.get(..hex.len().min(64))?;
+    if word.is_empty() {
+        return None;
+    }
+    U256::from_str_radix(word, 16).ok()?.try_into().ok()
+}
+
+/// Read the 32-byte word at `at` as an offset or length
+fn word_as_usize(bytes: &[u8], at: usize) -> Option<usize> {
+    let word = bytes.get(at..at.checked_add(32)?)?;
+    U256::from_be_slice(word).try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_string_result() {
+        // "USDC" as an ABI-encoded string
+        let usdc = concat!(
+            "0x0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "5553444300000000000000000000000000000000000000000000000000000000",
+        );
+        assert_eq!(decode_string_result(usdc).as_deref(), Some("USDC"));
+
+        // "MKR" as bytes32
+        let mkr = "0x4d4b520000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(decode_string_result(mkr).as_deref(), Some("MKR"));
+
+        assert_eq!(decode_string_result("0x"), None);
+        // Length past the end of the data
+        let truncated = concat!(
+            "0x0000000000000000000000000000000000000000000000000000000000000020",
+            "00000000000000000000000000000000000000000000000000000000000000ff",
+        );
+        assert_eq!(decode_string_result(truncated), None);
+
+        // "USDT" without ABI encoding, padded or not
+        assert_eq!(decode_string_result("0x55534454").as_deref(), Some("USDT"));
+        assert_eq!(decode_string_result("0x555344540000").as_deref(), Some("USDT"));
+    }
+
+    #[test]
+    fn test_aggregate3_round_trip() {
+        let token = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let calldata = encode_aggregate3(&[(token, "0x95d89b41"), (token, "0x313ce567")]);
+        let bytes = hex::decode(calldata.trim_start_matches("0x")).unwrap();
+        assert_eq!(&bytes[..4], &hex::decode("82ad56cb").unwrap()[..]);
+        // selector, array offset, length, two tuple offsets, two 160-byte tuples
+        assert_eq!(bytes.len(), 4 + 4 * 32 + 2 * 160);
+        assert_eq!(word_as_usize(&bytes[4..], 64), Some(64));
+        assert_eq!(word_as_usize(&bytes[4..], 96), Some(224));
+        assert_eq!(hex::encode(&bytes[4 + 140..4 + 160]), token[2..].to_lowercase());
+
+        // Return data: [(true, 6 as uint8), (false, "")]
+        let returned = concat!(
+            "0x0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "00000000000000000000000000000000000000000000000000000000000000c0",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000006",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        );
+        let results = decode_aggregate3(returned).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].0);
+        assert_eq!(decode_decimals_result(&results[0].1), Some(6));
+        assert_eq!(results[1], (false, "0x".to_string()));
+
+        assert_eq!(decode_aggregate3("0x"), None);
+    }
+
+    #[test]
+    fn test_decode_decimals_result() {
+        let six = "0x0000000000000000000000000000000000000000000000000000000000000006";
+        assert_eq!(decode_decimals_result(six), Some(6));
+        assert_eq!(decode_decimals_result("0x"), None);
+        let too_large = "0x0000000000000000000000000000000000000000000000000000000000000100";
+        assert_eq!(decode_decimals_result(too_large), None);
+        // Extra words after the value
+        let padded = concat!(
+            "0x0000000000000000000000000000000000000000000000000000000000000012",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        );
+        assert_eq!(decode_decimals_result(padded), Some(18));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::types::{hex_word, BlockTrace, CallFrame, Trace};
+use std::collections::HashSet;
+
+/// Value moved inside a transaction's call tree
+///
+/// Only nested calls are kept: the transaction's own value is not internal.
+/// Calls that reverted, or ran under a call that reverted, moved nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedTransfer {
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub trace_address: Vec<u32>,
+    /// call, create, create2 or selfdestruct
+    pub call_type: &'static str,
+    /// Lowercase addresses
+    pub from: String,
+    pub to: String,
+    /// Amount in wei as a 32-byte word
+    pub value: String,
+}
+
+impl TracedTransfer {
+    /// `trace_address` as stored, e.g. "0.2"
+    pub fn trace_path(&self) -> String {
+        self.trace_address.iter().map(u32::to_string).collect::<Vec<_>>().join(".")
+    }
+}
+
+/// Non-zero amount as a 32-byte word
+fn nonzero_word(value: Option<&str>) -> Option<String> {
+    hex_word(value?).filter(|word| !word.trim_start_matches("0x").trim_start_matches('0').is_empty())
+}
+
+/// Value transfers in trace_filter output
+///
+/// Traces of reverted calls carry an error; calls below them are dropped
+/// too, as far as their parents are among `traces`.
+pub fn from_trace_filter(traces: &[Trace]) -> Vec<TracedTransfer> {
+    let reverted: HashSet<(&str, &[u32])> = traces
+        .iter()
+        .filter(|t| t.error.is_some())
+        .filter_map(|t| Some((t.transaction_hash.as_deref()?, t.trace_address.as_slice())))
+        .collect();
+
+    traces
+        .iter()
+        .filter_map(|trace| {
+            let tx_hash = trace.transaction_hash.as_deref()?;
+            if trace.trace_address.is_empty()
+                || (0..=trace.trace_address.len()).any(|depth| reverted.contains(&(tx_hash, &trace.trace_address[..depth])))
+            {
+                return None;
+            }
+
+            let action = &trace.action;
+            let (call_type, from, to, value) = match trace.trace_type.as_str() {
+                "call" if action.call_type.as_deref() == Some("call") => {
+                    ("call", action.from.as_ref()?, action.to.as_ref()?, action.value.as_deref())
+                }
+                "create" => ("create", action.from.as_ref()?, trace.result.as_ref()?.address.as_ref()?, action.value.as_deref()),
+                "suicide" => (
+                    "selfdestruct",
+                    action.address.as_ref()?,
+                    action.refund_address.as_ref()?,
+                    action.balance.as_deref(),
+                ),
+                _ => return None,
+            };
+
+            Some(TracedTransfer {
+                tx_hash: tx_hash.to_lowercase(),
+                block_number: trace.block_number,
+                trace_address: trace.trace_address.clone(),
+                call_type,
+                from: from.to_lowercase(),
+                to: to.to_lowercase(),
+                value: nonzero_word(value)?,
+            })
+        })
+        .collect()
+}
+
+/// Value transfers in a block's debug_traceBlockByNumber callTracer output
+///
+/// Transactions without a `txHash` (older clients) are skipped.
+pub fn from_call_frames(block_number: u64, traces: &[BlockTrace]) -> Vec<TracedTransfer> {
+    let mut transfers = Vec::new();
+    for trace in traces {
+        let Some(tx_hash) = &trace.tx_hash else { continue };
+        if trace.result.error.is_some() {
+            continue;
+        }
+        for (index, call) in trace.result.calls.iter().enumerate() {
+            walk_frame(call, &mut vec![index as u32], tx_hash, block_number, &mut transfers);
+        }
+    }
+    transfers
+}
+
+fn walk_frame(frame: &CallFrame, path: &mut Vec<u32>, tx_hash: &str, block_number: u64, transfers: &mut Vec<TracedTransfer>) {
+    if frame.error.is_some() {
+        return;
+    }
+
+    let call_type = match frame.call_type.to_ascii_uppercase().as_str() {
+        "CALL" => Some("call"),
+        "CREATE" => Some("create"),
+        "CREATE2" => Some("create2"),
+        "SELFDESTRUCT" => Some("selfdestruct"),
+        _ => None,
+    };
+    if let (Some(call_type), Some(to), Some(value)) = (call_type, &frame.to, nonzero_word(frame.value.as_deref())) {
+        transfers.push(TracedTransfer {
+            tx_hash: tx_hash.to_lowercase(),
+            block_number,
+            trace_address: path.clone(),
+            call_type,
+            from: frame.from.to_lowercase(),
+            to: to.to_lowercase(),
+            value,
+        });
+    }
+
+    for (index, call) in frame.calls.iter().enumerate() {
+        path.push(index as u32);
+        walk_frame(call, path, tx_hash, block_number, transfers);
+        path.pop();
+    }
+}
+
+/// Input of the successful calls to `to` starting with `selector`, in call order
+///
+/// Calls under a reverted call are skipped along with it.
+pub fn call_inputs<'a>(frame: &'a CallFrame, to: &str, selector: &str, inputs: &mut Vec<&'a str>) {
+    if frame.error.is_some() {
+        return;
+    }
+    if let (Some(frame_to), Some(input)) = (&frame.to, &frame.input) {
+        let matches_selector = input.get(..selector.len()).is_some_and(|s| s.eq_ignore_ascii_case(selector));
+        if frame_to.eq_ignore_ascii_case(to) && matches_selector {
+            inputs.push(input);
+        }
+    }
+    for call in &frame.calls {
+        call_inputs(call, to, selector, inputs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const A: &str = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const B: &str = "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const C: &str = "0xcccccccccccccccccccccccccccccccccccccccc";
+
+    #[test]
+    fn test_from_trace_filter() {
+        let trace = |trace_address: Vec<u32>, action: serde_json::Value, trace_type: &str, error: Option<&str>| {
+            json!({
+                "action": action,
+                "blockNumber": 100,
+                "transactionHash": "0xTX",
+                "traceAddress": trace_address,
+                "type": trace_type,
+                "error": error,
+            })
+        };
+        let traces: Vec<Trace> = serde_json::from_value(json!([
+            // The transaction's own value is not internal
+            trace(vec![], json!({ "callType": "call", "from": A, "to": B, "value": "0x10" }), "call", None),
+            trace(vec![0], json!({ "callType": "call", "from": B, "to": C, "value": "0x5" }), "call", None),
+            trace(vec![1], json!({ "callType": "delegatecall", "from": B, "to": C, "value": "0x5" }), "call", None),
+            trace(vec![2], json!({ "callType": "call", "from": B, "to": C, "value": "0x0" }), "call", None),
+            // A reverted call and everything below it
+            trace(vec![3], json!({ "callType": "call", "from": B, "to": C, "value": "0x1" }), "call", Some("Reverted")),
+            trace(vec![3, 0], json!({ "callType": "call", "from": C, "to": A, "value": "0x1" }), "call", None),
+            trace(vec![4], json!({ "address": C, "refundAddress": A, "balance": "0x7" }), "suicide", None),
+            { "action": { "author": A, "value": "0x1", "rewardType": "block" }, "blockNumber": 100, "type": "reward" },
+        ]))
+        .unwrap();
+
+        let transfers = from_trace_filter(&traces);
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].tx_hash, "0xtx");
+        assert_eq!(transfers[0].trace_path(), "0");
+        assert_eq!((transfers[0].from.as_str(), transfers[0].to.as_str()), (B, C));
+        assert_eq!(transfers[0].value, format!("0x{:0>64}", "5"));
+        assert_eq!(transfers[1].call_type, "selfdestruct");
+        assert_eq!((transfers[1].from.as_str(), transfers[1].to.as_str()), (C, A));
+    }
+
+    #[test]
+    fn test_from_call_frames() {
+        let traces: Vec<BlockTrace> = serde_json::from_value(json!([
+            {
+                "txHash": "0xtx1",
+                "result": {
+                    "type": "CALL", "from": A, "to": B, "value": "0x10",
+                    "calls": [
+                        { "type": "STATICCALL", "from": B, "to": C },
+                        {
+                            "type": "CALL", "from": B, "to": C, "value": "0x3",
+                            "calls": [{ "type": "CREATE2", "from": C, "to": A, "value": "0x1" }]
+                        },
+                        {
+                            "type": "CALL", "from": B, "to": A, "value": "0x2", "error": "execution reverted",
+                            "calls": [{ "type": "CALL", "from": A, "to": C, "value": "0x2" }]
+                        }
+                    ]
+                }
+            },
+            {
+                "txHash": "0xtx2",
+                "result": {
+                    "type": "CALL", "from": A, "to": B, "value": "0x0", "error": "out of gas",
+                    "calls": [{ "type": "CALL", "from": B, "to": C, "value": "0x9" }]
+                }
+            },
+            { "result": { "type": "CALL", "from": A, "to": B, "calls": [{ "type": "CALL", "from": B, "to": C, "value": "0x9" }] } }
+        ]))
+        .unwrap();
+
+        let transfers = from_call_frames(100, &traces);
+        assert_eq!(transfers.len(), 2);
+        assert_eq!((transfers[0].trace_path().as_str(), transfers[0].call_type), ("1", "call"));
+        assert_eq!((transfers[1].trace_path().as_str(), transfers[1].call_type), ("1.0", "create2"));
+        assert_eq!(transfers[1].block_number, 100);
+    }
+
+    #[test]
+    fn test_call_inputs() {
+        let frame: CallFrame = serde_json::from_value(json!({
+            "type": "CALL", "from": A, "to": B, "input": "0x12345678aa",
+            "calls": [
+                { "type": "CALL", "from": B, "to": C, "input": "0x12345678bb" },
+                { "type": "CALL", "from": B, "to": C.to_uppercase().replace("0X", "0x"), "input": "0x12345678CC" },
+                { "type": "CALL", "from": B, "to": C, "input": "0x87654321dd" },
+                {
+                    "type": "CALL", "from": B, "to": A, "error": "execution reverted",
+                    "calls": [{ "type": "CALL", "from": A, "to": C, "input": "0x12345678ee" }]
+                },
+                { "type": "CALL", "from": B, "to": C }
+            ]
+        }))
+        .unwrap();
+
+        let mut inputs = Vec::new();
+        call_inputs(&frame, C, "0x12345678", &mut inputs);
+        assert_eq!(inputs, vec!["0x12345678bb", "0x12345678CC"]);
+    }
+}
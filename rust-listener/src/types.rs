@@ -3,11 +3,26 @@ use serde::{Deserialize, Serialize};
 /// ERC20 Transfer event topic (keccak256 of "Transfer(address,address,uint256)")
 pub const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
 
+/// ERC20 Approval event topic (keccak256 of "Approval(address,address,uint256)")
+pub const APPROVAL_TOPIC: &str = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+/// WETH9 Deposit(address indexed dst, uint256 wad) event signature
+pub const WETH_DEPOSIT_TOPIC: &str = "0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c";
+
+/// WETH9 Withdrawal(address indexed src, uint256 wad) event signature
+pub const WETH_WITHDRAWAL_TOPIC: &str = "0x7fcf532c15f0a6db0bd6d0e038bea71d30d808c7d98cb3bf7268a95bf5081b65";
+
+/// Counterparty of WETH deposits (mint) and withdrawals (burn) stored as transfers
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Approval amount treated as unlimited (type(uint256).max)
+pub const UNLIMITED_APPROVAL: &str = "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
 // ============================================================================
 // 1inch Fusion+ Constants
 // ============================================================================
 
-/// 1inch Fusion+ EscrowFactory contract address (same on all supported chains)
+/// 1inch Fusion+ EscrowFactory contract address (default on every chain; see `ContractAddresses`)
 pub const ESCROW_FACTORY: &str = "0xa7bcb4eac8964306f9e3764f67db6a7af6ddf99a";
 
 /// SrcEscrowCreated event topic - emitted on source chain when swap initiated
@@ -28,20 +43,26 @@ pub const ESCROW_CANCELLED_TOPIC: &str = "0x6e3be9294e58d10b9c8053cfd5e09871b67e
 // 1inch Fusion (Single-Chain) Constants - Aggregation Router V6
 // ============================================================================
 
-/// 1inch Aggregation Router V6 contract address (same on most chains)
+/// 1inch Aggregation Router V6 contract address (default on most chains; see `ContractAddresses`)
 /// This is the router that emits OrderFilled events for Fusion swaps
 pub const AGGREGATION_ROUTER_V6: &str = "0x111111125421ca6dc452d289314280a0f8842a65";
 
 /// 1inch Aggregation Router contract address for zkSync Era
 pub const AGGREGATION_ROUTER_ZKSYNC: &str = "0x6fd4383cb451173d5f9304f041c7bcbf27d561ff";
 
+/// Multicall3, deployed at the same address on most chains
+pub const MULTICALL3: &str = "0xca11bde05977b3631167028862be2a173976ca11";
+
+/// Multicall3 on zkSync Era
+pub const MULTICALL3_ZKSYNC: &str = "0xf9cda624fbc7e059355ce98a31693d299facd963";
+
 /// OrderFilled(bytes32 orderHash, uint256 remainingAmount) event topic
 /// keccak256("OrderFilled(bytes32,uint256)") - Aggregation Router V6 format
 pub const ORDER_FILLED_TOPIC: &str = "0xfec331350fce78ba658e082a71da20ac9f8d798a99b3c79681c8440cbfe77e07";
 
-/// OrderCancelled(bytes32 orderHash, uint256 remainingAmount) event topic
-/// keccak256("OrderCancelled(bytes32,uint256)")
-pub const ORDER_CANCELLED_TOPIC: &str = "0xc9f7df58a71d1f49f7d4e6d19a4b5d8f5c6c7b8a9d0e1f2a3b4c5d6e7f8a9b0c";
+/// OrderCancelled(bytes32 orderHash) event topic
+/// keccak256("OrderCancelled(bytes32)") - Aggregation Router V6 format
+pub const ORDER_CANCELLED_TOPIC: &str = "0x5152abf959f6564662358c2e52b702259b78bac5ee7842a0f01937e670efcc7d";
 
 // ============================================================================
 // Crypto2Fiat (KentuckyDelegate) Constants
@@ -52,11 +73,165 @@ pub const ORDER_CANCELLED_TOPIC: &str = "0xc9f7df58a71d1f49f7d4e6d19a4b5d8f5c6c7
 pub const CRYPTO2FIAT_TOPIC: &str = "0x86ac35f38cd2d17935b5bb6295c74cadb683bcfba935852c32096a81df8998ef";
 
 /// Network configuration for a blockchain
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct NetworkConfig {
     pub chain_id: u32,
-    pub name: &'static str,
+    pub name: String,
     pub rpc_url: String,
+    /// Endpoints tried in order when the primary `rpc_url` is unhealthy
+    pub fallback_rpc_urls: Vec<String>,
+    /// Per-chain poller settings; unset fields use the PollerConfig defaults
+    pub poller: PollerOverrides,
+    /// RPC requests per second for this chain; unlimited when unset
+    pub rate_limit: Option<f64>,
+    /// Connection settings for this chain's RPC endpoints
+    pub http: HttpOptions,
+    /// Use provider-specific APIs where the active endpoint supports them;
+    /// backfills on Alchemy also index native and internal transfers
+    pub enhanced_apis: bool,
+    /// User-defined contract watchers whose logs are stored in `raw_events`
+    pub watchers: Vec<WatcherConfig>,
+    /// Wrapped native token (WETH9-style) whose Deposit/Withdrawal events are
+    /// stored as mint/burn transfers
+    pub wrapped_native: Option<String>,
+    pub contracts: ContractAddresses,
+    /// Multicall3 batching token metadata calls; tokens are called one at a time when unset
+    pub multicall3: Option<String>,
+}
+
+/// 1inch contracts indexed on a chain (lowercase)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractAddresses {
+    /// Fusion+ EscrowFactory; SrcEscrowCreated/DstEscrowCreated from other addresses are ignored
+    pub escrow_factory: String,
+    /// Aggregation Router emitting Fusion OrderFilled/OrderCancelled
+    pub aggregation_router: String,
+}
+
+impl NetworkConfig {
+    /// Primary endpoint followed by the fallbacks
+    pub fn rpc_urls(&self) -> Vec<&str> {
+        std::iter::once(self.rpc_url.as_str())
+            .chain(self.fallback_rpc_urls.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// HTTP connection settings of a chain's RPC clients (`[networks.http]`)
+///
+/// The defaults suit rate-limited public providers. Self-hosted nodes can
+/// afford a larger pool, HTTP/2 and TCP keepalive.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpOptions {
+    /// Speak HTTP/2 without negotiating it first (h2c on plain http URLs);
+    /// requests fail against servers that only speak HTTP/1.1
+    pub http2_prior_knowledge: bool,
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: usize,
+    /// Idle connections are closed after this long
+    pub pool_idle_timeout_secs: u64,
+    /// Whole-request timeout, long enough for large getLogs responses
+    pub timeout_secs: u64,
+    /// Connect timeout; bounded only by `timeout_secs` when unset
+    pub connect_timeout_secs: Option<u64>,
+    /// TCP keepalive interval; off when unset
+    pub tcp_keepalive_secs: Option<u64>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            http2_prior_knowledge: false,
+            pool_max_idle_per_host: 2,
+            pool_idle_timeout_secs: 30,
+            timeout_secs: 180,
+            connect_timeout_secs: None,
+            tcp_keepalive_secs: None,
+        }
+    }
+}
+
+/// Which blocks the poller treats as settled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Finality {
+    /// `confirmation_blocks` below the latest block
+    #[default]
+    Confirmations,
+    /// Up to the node's `safe` block
+    Safe,
+    /// Up to the node's `finalized` block
+    Finalized,
+}
+
+impl Finality {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "confirmations" => Some(Self::Confirmations),
+            "safe" => Some(Self::Safe),
+            "finalized" => Some(Self::Finalized),
+            _ => None,
+        }
+    }
+
+    /// Block tag of the settled head, or None when counting confirmations
+    pub fn block_tag(self) -> Option<BlockId> {
+        match self {
+            Self::Confirmations => None,
+            Self::Safe => Some(BlockId::Safe),
+            Self::Finalized => Some(BlockId::Finalized),
+        }
+    }
+}
+
+/// Per-chain overrides of the poller defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PollerOverrides {
+    pub poll_interval_ms: Option<u64>,
+    pub confirmation_blocks: Option<u64>,
+    pub finality: Option<Finality>,
+    pub max_blocks_per_query: Option<u64>,
+    pub max_backfill_blocks: Option<u64>,
+    pub reorg_safety_blocks: Option<u64>,
+    /// Fetch every indexed topic with one getLogs call per range
+    pub combined_logs: Option<bool>,
+}
+
+impl PollerOverrides {
+    /// Combine two override sets; fields set in `other` win
+    pub fn merge(self, other: PollerOverrides) -> PollerOverrides {
+        PollerOverrides {
+            poll_interval_ms: other.poll_interval_ms.or(self.poll_interval_ms),
+            confirmation_blocks: other.confirmation_blocks.or(self.confirmation_blocks),
+            finality: other.finality.or(self.finality),
+            max_blocks_per_query: other.max_blocks_per_query.or(self.max_blocks_per_query),
+            max_backfill_blocks: other.max_backfill_blocks.or(self.max_backfill_blocks),
+            reorg_safety_blocks: other.reorg_safety_blocks.or(self.reorg_safety_blocks),
+            combined_logs: other.combined_logs.or(self.combined_logs),
+        }
+    }
+}
+
+/// A `[[watchers]]` entry: logs matching the address and/or topic0 list are
+/// stored verbatim under `label`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WatcherConfig {
+    pub label: String,
+    /// Contract to watch; any contract when unset
+    pub address: Option<String>,
+    /// Accepted topic0 values; every event of `address` when empty
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+impl WatcherConfig {
+    /// Whether a log falls under this watcher (address and topics are lowercase)
+    pub fn matches(&self, log: &Log) -> bool {
+        self.address.as_ref().is_none_or(|a| a.eq_ignore_ascii_case(&log.address))
+            && (self.topics.is_empty()
+                || log.topics.first().is_some_and(|t| self.topics.iter().any(|w| w.eq_ignore_ascii_case(t))))
+    }
 }
 
 /// Transfer event data to store in PostgreSQL
@@ -69,9 +244,114 @@ pub struct Transfer {
     pub from_addr: String,
     pub to_addr: String,
     pub value: String,
+    /// `value` as a decimal string; None in compact mode
+    pub value_decimal: Option<String>,
     pub block_number: u64,
     pub block_timestamp: u64,
     pub swap_type: Option<String>,
+    /// Receipt status of the transaction (1 succeeded, 0 failed); None unless TX_STATUS is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_status: Option<u8>,
+    /// Cached metadata of `token`, filled in by transfer queries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_info: Option<TokenInfo>,
+    /// Row id, set on rows read back from the database (pagination cursor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
+/// ERC-20 metadata cached in the `tokens` table; fields are None when the
+/// token does not implement the call
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+impl TokenInfo {
+    /// `metadata_status` of the row: "complete" with every field, "empty"
+    /// with none (an EOA or a contract that isn't a token), else "partial"
+    pub fn status(&self) -> &'static str {
+        match (self.symbol.is_some(), self.name.is_some(), self.decimals.is_some()) {
+            (true, true, true) => "complete",
+            (false, false, false) => "empty",
+            _ => "partial",
+        }
+    }
+}
+
+/// ERC20 Approval event data to store in PostgreSQL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub chain_id: u32,
+    pub tx_hash: String,
+    pub log_index: u32,
+    pub token: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: String,
+    /// Amount is type(uint256).max
+    pub is_unlimited: bool,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+}
+
+/// Undecoded log captured by a contract watcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawEvent {
+    pub chain_id: u32,
+    /// Label of the watcher that matched the log
+    pub watcher: String,
+    pub address: String,
+    pub tx_hash: String,
+    pub log_index: u32,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+}
+
+/// Log matched by a delegation topic (DELEGATION_TOPICS)
+///
+/// Under EIP-7702 a delegated EOA runs its delegate's code at its own
+/// address, so the emitting address is the authority.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delegation {
+    pub chain_id: u32,
+    /// Delegated account (the log's emitting address)
+    pub authority: String,
+    /// Contract the authority delegated to at that block, from its `0xef0100` code
+    /// designator; None if the account had no delegation or its code was unavailable
+    pub delegate: Option<String>,
+    pub tx_hash: String,
+    pub log_index: u32,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    /// Row id, set on rows read back from the database (pagination cursor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
+/// Sender, receiver and gas cost of a transaction that emitted indexed events
+/// (FETCH_TRANSACTIONS)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    pub chain_id: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub from_addr: String,
+    /// None for contract creations
+    pub to_addr: Option<String>,
+    pub gas_used: u64,
+    /// Effective gas price in wei, as a decimal string
+    pub effective_gas_price: String,
+    /// Row id, set on rows read back from the database (pagination cursor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
 }
 
 /// JSON-RPC response structures
@@ -88,7 +368,7 @@ pub struct RpcError {
 }
 
 /// Log entry from eth_getLogs
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Log {
     pub address: String,
@@ -99,6 +379,148 @@ pub struct Log {
     pub log_index: String,
 }
 
+/// Pseudo token address of native-currency transfers, which have no contract
+pub const NATIVE_TOKEN: &str = "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+
+/// `log_index` of a transaction's own value transfer, which has no log; far
+/// above any real log index
+pub const NATIVE_LOG_INDEX_BASE: u32 = 1 << 30;
+
+/// One transfer returned by alchemy_getAssetTransfers
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfer {
+    pub block_num: String,
+    pub hash: String,
+    pub from: String,
+    /// None for contract creations
+    pub to: Option<String>,
+    /// external, internal, erc20, erc721, erc1155 or specialnft
+    pub category: String,
+    pub raw_contract: AssetTransferContract,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetTransferContract {
+    /// Amount in the token's base unit as hex
+    pub value: Option<String>,
+}
+
+impl AssetTransfer {
+    /// Parse block number from hex string
+    pub fn block_number_u64(&self) -> u64 {
+        u64::from_str_radix(self.block_num.trim_start_matches("0x"), 16).unwrap_or(0)
+    }
+
+    /// The amount as a 32-byte word, the shape of a Transfer log's data
+    pub fn value_word(&self) -> Option<String> {
+        hex_word(self.raw_contract.value.as_deref()?)
+    }
+}
+
+/// A hex quantity (`0x1bc16d674ec80000`) left-padded to a 32-byte word
+pub fn hex_word(value: &str) -> Option<String> {
+    let hex = value.trim_start_matches("0x");
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("0x{:0>64}", hex.to_lowercase()))
+}
+
+/// How internal transfers are traced (INTERNAL_TRANSFERS)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceMethod {
+    /// OpenEthereum-style trace_filter (Erigon, Nethermind, Reth)
+    TraceFilter,
+    /// debug_traceBlockByNumber with the callTracer (Geth and most clients)
+    DebugTrace,
+}
+
+/// One trace from trace_filter
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trace {
+    pub action: TraceAction,
+    pub result: Option<TraceResult>,
+    pub block_number: u64,
+    /// None for block and uncle rewards
+    pub transaction_hash: Option<String>,
+    /// Position in the call tree; empty for the transaction's top-level call
+    #[serde(default)]
+    pub trace_address: Vec<u32>,
+    /// call, create, suicide or reward
+    #[serde(rename = "type")]
+    pub trace_type: String,
+    pub error: Option<String>,
+}
+
+/// Action of a trace; which fields are set depends on the trace type
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceAction {
+    /// call, delegatecall, staticcall or callcode
+    pub call_type: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub value: Option<String>,
+    /// Self-destructed contract, its beneficiary and the balance sent to it
+    pub address: Option<String>,
+    pub refund_address: Option<String>,
+    pub balance: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TraceResult {
+    /// Address of a created contract
+    pub address: Option<String>,
+}
+
+/// One transaction's call tree from debug_traceBlockByNumber
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTrace {
+    /// Missing on clients older than Geth 1.11
+    pub tx_hash: Option<String>,
+    pub result: CallFrame,
+}
+
+/// A call in callTracer output
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallFrame {
+    /// CALL, DELEGATECALL, STATICCALL, CREATE, CREATE2, SELFDESTRUCT, ...
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub value: Option<String>,
+    /// Call data
+    pub input: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+}
+
+/// Value moved by a contract call rather than a transaction or an event,
+/// found by tracing (INTERNAL_TRANSFERS)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternalTransfer {
+    pub chain_id: u32,
+    pub tx_hash: String,
+    /// Position in the call tree, e.g. "0.2" for the third call made by the first subcall
+    pub trace_address: String,
+    /// call, create, create2 or selfdestruct
+    pub call_type: String,
+    pub from_addr: String,
+    pub to_addr: String,
+    pub value: String,
+    pub value_decimal: Option<String>,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    /// Row id, set on rows read back from the database (pagination cursor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
 impl Log {
     /// Parse block number from hex string
     pub fn block_number_u64(&self) -> u64 {
@@ -111,13 +533,97 @@ impl Log {
     }
 }
 
+/// Transaction receipt from eth_getTransactionReceipt
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    pub transaction_hash: String,
+    pub status: Option<String>,
+    pub logs: Vec<Log>,
+    pub from: Option<String>,
+    /// None for contract creations
+    pub to: Option<String>,
+    pub gas_used: Option<String>,
+    pub effective_gas_price: Option<String>,
+}
+
+/// A block by number or by symbolic tag, as accepted by eth_getBlockByNumber
+/// and eth_getLogs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    Number(u64),
+    Latest,
+    Safe,
+    Finalized,
+    Pending,
+}
+
+impl BlockId {
+    /// Parse a tag name or a decimal / 0x-hex block number
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "latest" => Some(Self::Latest),
+            "safe" => Some(Self::Safe),
+            "finalized" => Some(Self::Finalized),
+            "pending" => Some(Self::Pending),
+            _ => match s.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok().map(Self::Number),
+                None => s.parse().ok().map(Self::Number),
+            },
+        }
+    }
+
+    /// JSON-RPC parameter: hex quantity or tag name
+    pub fn to_param(self) -> String {
+        match self {
+            Self::Number(n) => format!("0x{:x}", n),
+            Self::Latest => "latest".to_string(),
+            Self::Safe => "safe".to_string(),
+            Self::Finalized => "finalized".to_string(),
+            Self::Pending => "pending".to_string(),
+        }
+    }
+}
+
+impl From<u64> for BlockId {
+    fn from(n: u64) -> Self {
+        Self::Number(n)
+    }
+}
+
+impl std::fmt::Display for BlockId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            _ => f.write_str(&self.to_param()),
+        }
+    }
+}
+
+/// Number and hash of a block resolved from a `BlockId`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockRef {
+    pub number: u64,
+    pub hash: String,
+}
+
 /// Block data from eth_getBlockByNumber
 #[derive(Debug, Deserialize)]
 pub struct Block {
     pub timestamp: String,
+    #[serde(default)]
+    pub hash: Option<String>,
+    #[serde(default)]
+    pub number: Option<String>,
 }
 
 impl Block {
+    /// Parse block number from hex string
+    pub fn number_u64(&self) -> Option<u64> {
+        u64::from_str_radix(self.number.as_deref()?.trim_start_matches("0x"), 16).ok()
+    }
+
     /// Parse timestamp from hex string
     pub fn timestamp_u64(&self) -> u64 {
         u64::from_str_radix(self.timestamp.trim_start_matches("0x"), 16).unwrap_or(0)
@@ -129,7 +635,7 @@ impl Block {
 // ============================================================================
 
 /// Data decoded from SrcEscrowCreated event
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SrcEscrowCreatedData {
     pub order_hash: String,
     pub hashlock: String,
@@ -147,7 +653,7 @@ pub struct SrcEscrowCreatedData {
 }
 
 /// Data decoded from DstEscrowCreated event
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DstEscrowCreatedData {
     pub order_hash: String,
     pub hashlock: String,
@@ -165,6 +671,8 @@ pub struct FusionPlusSwap {
     pub order_hash: String,
     pub hashlock: String,
     pub secret: Option<String>,
+    /// Whether keccak256(secret) equals the hashlock; None until a secret is stored
+    pub secret_valid: Option<bool>,
 
     // Source chain data
     pub src_chain_id: u32,
@@ -195,6 +703,23 @@ pub struct FusionPlusSwap {
     pub dst_safety_deposit: String,
     pub dst_timelocks: Option<String>,
     pub dst_status: String,
+
+    // First secret reveal across both legs (earliest withdrawal block timestamp)
+    pub secret_revealed_chain_id: Option<u32>,
+    pub secret_revealed_at: Option<u64>,
+
+    // Per leg: sender of the withdrawal tx, and whether it came in the private
+    // (taker only) or public (any access token holder) withdrawal period
+    pub src_withdrawer: Option<String>,
+    pub src_withdrawal_phase: Option<String>,
+    pub dst_withdrawer: Option<String>,
+    pub dst_withdrawal_phase: Option<String>,
+
+    // EscrowCancelled tx and block timestamp, per leg
+    pub src_cancelled_tx_hash: Option<String>,
+    pub src_cancelled_at: Option<u64>,
+    pub dst_cancelled_tx_hash: Option<String>,
+    pub dst_cancelled_at: Option<u64>,
 }
 
 impl FusionPlusSwap {
@@ -211,6 +736,7 @@ impl FusionPlusSwap {
             order_hash: data.order_hash.clone(),
             hashlock: data.hashlock.clone(),
             secret: None,
+            secret_valid: None,
 
             src_chain_id: chain_id,
             src_tx_hash: tx_hash.to_string(),
@@ -239,16 +765,112 @@ impl FusionPlusSwap {
             dst_safety_deposit: data.dst_safety_deposit.clone(),
             dst_timelocks: None,
             dst_status: "pending".to_string(),
+
+            secret_revealed_chain_id: None,
+            secret_revealed_at: None,
+
+            src_withdrawer: None,
+            src_withdrawal_phase: None,
+            dst_withdrawer: None,
+            dst_withdrawal_phase: None,
+
+            src_cancelled_tx_hash: None,
+            src_cancelled_at: None,
+            dst_cancelled_tx_hash: None,
+            dst_cancelled_at: None,
         }
     }
 }
 
+/// Filters for listing Fusion+ swaps; empty/unset fields match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FusionPlusFilter {
+    /// Maker on either leg
+    pub maker: Option<String>,
+    /// Any of these source statuses
+    pub src_status: Vec<String>,
+    /// Any of these destination statuses
+    pub dst_status: Vec<String>,
+    pub src_chain_id: Option<u32>,
+    pub dst_chain_id: Option<u32>,
+    /// Source block timestamp window, inclusive lower bound
+    pub from_timestamp: Option<u64>,
+    /// Source block timestamp window, exclusive upper bound
+    pub to_timestamp: Option<u64>,
+}
+
+/// One escrow pair of a Fusion+ order, from `fusion_plus_fills`
+///
+/// Orders allowing multiple fills commit to a Merkle tree of secret hashes;
+/// each fill locks its escrows with its own leaf's hashlock and reveals its
+/// own secret. The swap record follows the first fill indexed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FusionPlusFill {
+    pub order_hash: String,
+    /// Merkle leaf index of the fill's secret; None for single-fill orders or
+    /// when the fill's transaction couldn't be traced
+    pub fill_index: Option<u64>,
+    /// Merkle root of the order's secret hashes
+    pub hashlock_root: Option<String>,
+    /// Whether the fill's proof leads from its leaf to `hashlock_root`
+    pub proof_valid: Option<bool>,
+    pub hashlock: String,
+    pub secret: Option<String>,
+    pub src_chain_id: u32,
+    pub src_tx_hash: String,
+    pub src_block_number: u64,
+    pub src_escrow_address: Option<String>,
+    pub src_amount: String,
+    pub src_status: String,
+    pub dst_chain_id: u32,
+    pub dst_tx_hash: Option<String>,
+    pub dst_block_number: Option<u64>,
+    pub dst_escrow_address: Option<String>,
+    pub dst_amount: String,
+    pub dst_status: String,
+}
+
+/// One lifecycle event of a Fusion+ swap, as recorded in `fusion_plus_events`
+///
+/// `event_type` is one of `src_created`, `dst_created`, `src_withdrawn`,
+/// `dst_withdrawn`, `src_cancelled` or `dst_cancelled`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FusionPlusEvent {
+    pub order_hash: String,
+    pub event_type: String,
+    pub chain_id: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub log_index: u32,
+    pub escrow_address: Option<String>,
+}
+
+/// Performance of a Fusion+ resolver (the source leg's taker) over the swaps
+/// it took in a window of source block timestamps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolverStats {
+    pub resolver: String,
+    pub swap_count: u64,
+    pub src_chains: Vec<u32>,
+    pub dst_chains: Vec<u32>,
+    /// Swaps with both legs withdrawn
+    pub completed_count: u64,
+    /// Swaps with either leg cancelled
+    pub cancelled_count: u64,
+    pub cancellation_rate: f64,
+    /// Mean seconds from src to dst escrow creation, over swaps with a dst escrow
+    pub avg_dst_delay_secs: Option<f64>,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
 // ============================================================================
 // 1inch Fusion (Single-Chain) Data Structures
 // ============================================================================
 
 /// Data decoded from OrderFilled/OrderCancelled events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OrderFilledData {
     pub maker: String,
     pub order_hash: String,
@@ -273,6 +895,48 @@ pub struct FusionSwap {
     pub remaining: String,
     pub is_partial_fill: bool,
     pub status: String,
+    /// Row id, set on rows read back from the database (pagination cursor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
+/// One OrderFilled of a Fusion order, amounts in decimal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFill {
+    /// 1-based position of the fill within its order
+    pub fill_index: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub log_index: u32,
+    pub taker: Option<String>,
+    pub maker_amount: Option<String>,
+    pub taker_amount: Option<String>,
+    pub remaining: Option<String>,
+}
+
+/// All fills of a Fusion order on one chain, aggregated
+///
+/// Amounts are decimal strings. `status` is "cancelled" once the order is
+/// cancelled, "filled" when the last fill left nothing remaining, and
+/// "partially_filled" otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionOrder {
+    pub chain_id: u32,
+    pub order_hash: String,
+    pub maker: String,
+    pub maker_token: Option<String>,
+    pub taker_token: Option<String>,
+    pub fill_count: u32,
+    pub filled_maker_amount: Option<String>,
+    pub filled_taker_amount: Option<String>,
+    pub remaining: Option<String>,
+    pub status: String,
+    pub first_fill_block: u64,
+    pub last_fill_block: u64,
+    pub first_fill_at: u64,
+    pub last_fill_at: u64,
+    pub fills: Vec<OrderFill>,
 }
 
 // ============================================================================
@@ -280,7 +944,7 @@ pub struct FusionSwap {
 // ============================================================================
 
 /// Crypto2Fiat event data from KentuckyDelegate
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Crypto2FiatEvent {
     pub order_id: String,      // bytes32 indexed - unique order ID
     pub token: String,         // address indexed - ERC20 token (or 0x0 for ETH)
@@ -292,4 +956,145 @@ pub struct Crypto2FiatEvent {
     pub block_number: u64,
     pub block_timestamp: u64,
     pub log_index: u32,
+    /// Row id, set on rows read back from the database (pagination cursor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
+// ============================================================================
+// Pagination
+// ============================================================================
+
+/// Keyset cursor for list queries
+///
+/// With `since_id`, rows inserted after that id are returned oldest first
+/// (for tailing new rows). Otherwise rows are returned newest first, starting
+/// below `before_timestamp`; pass the last row's `id` as `before_id` so rows
+/// sharing that block timestamp are not skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Cursor {
+    pub since_id: Option<i64>,
+    pub before_timestamp: Option<u64>,
+    pub before_id: Option<i64>,
+}
+
+// ============================================================================
+// Search
+// ============================================================================
+
+/// A stored hash or address matching a search prefix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub table: String,
+    /// Column the value was found in, e.g. `from_addr` or `src_tx_hash`
+    pub field: String,
+    pub chain_id: u32,
+    pub value: String,
+}
+
+// ============================================================================
+// Balance Delta Data Structures
+// ============================================================================
+
+/// Net token flow for an address over a time window
+///
+/// Sums are in raw token units; the `_formatted` fields scale them by the
+/// token's decimals and are None while its metadata is unknown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceDelta {
+    pub chain_id: u32,
+    pub address: String,
+    pub token: String,
+    pub inflow: String,       // Decimal sum of incoming transfer values
+    pub outflow: String,      // Decimal sum of outgoing transfer values
+    pub net: String,          // inflow - outflow, prefixed with '-' when negative
+    pub decimals: Option<u8>,
+    pub inflow_formatted: Option<String>,
+    pub outflow_formatted: Option<String>,
+    pub net_formatted: Option<String>,
+    pub transfer_count: u64,
+    /// Transfers stored without a value (compact mode), left out of the sums
+    pub unvalued_count: u64,
+}
+
+/// Transfer activity of an address over a time window, for wallet dashboards
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressActivity {
+    pub address: String,
+    pub transfer_count: u64,
+    /// Distinct (chain, token) pairs transferred
+    pub token_count: u64,
+    /// Timestamps of the first and last transfer in the window
+    pub first_seen: Option<u64>,
+    pub last_seen: Option<u64>,
+    /// Busiest tokens first
+    pub tokens: Vec<TokenActivity>,
+}
+
+/// An address's transfers of one token (raw token units)
+///
+/// Sums cover transfers stored with a value; compact mode stores none.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenActivity {
+    pub chain_id: u32,
+    pub token: String,
+    pub transfer_count: u64,
+    pub inflow: String,
+    pub outflow: String,
+    pub net: String,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+/// Transfer count and summed value of a token (raw token units), for one hour
+/// of `token_stats` or summed over a window
+///
+/// The volume covers transfers stored with a value; compact mode stores none.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenStats {
+    pub chain_id: u32,
+    pub token: String,
+    /// Start of the hour (unix seconds); None when summed over a window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hour: Option<u64>,
+    pub transfer_count: u64,
+    pub volume: String,
+}
+
+// ============================================================================
+// Watchlist Data Structures
+// ============================================================================
+
+/// Address whose transfers are kept when watchlist filtering is enabled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchedAddress {
+    pub address: String,
+    pub label: Option<String>,
+    pub created_at: u64,
+}
+
+// ============================================================================
+// Storage Data Structures
+// ============================================================================
+
+/// Disk usage of the database and its largest tables
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseSize {
+    /// pg_database_size of the whole database
+    pub total_bytes: u64,
+    /// `total_bytes` less the space held by dead rows, which PostgreSQL reuses
+    /// for new rows but only returns to the OS on VACUUM FULL
+    pub live_bytes: u64,
+    /// Largest first; partitions are counted under their root table
+    pub tables: Vec<TableSize>,
+}
+
+/// Disk usage of one table, including its indexes and TOAST data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSize {
+    pub table: String,
+    pub total_bytes: u64,
+    /// Row counts from the statistics collector (estimates)
+    pub live_rows: u64,
+    pub dead_rows: u64,
 }
@@ -0,0 +1,403 @@
+use crate::db::Database;
+use crate::fusion::{decode_order_filled, decode_src_escrow_created};
+use crate::poller::{transfer_parties, SwapDetails};
+use crate::rpc::{RpcClient, RpcError};
+use crate::types::{FusionPlusSwap, FusionSwap, Log, NetworkConfig, Transfer};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Result of verifying a sample of stored rows against the chain
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Rows compared against on-chain logs
+    pub checked: usize,
+    /// Rows whose stored fields differ from the on-chain log
+    pub drifted: usize,
+    /// Rows whose log is not in their transaction's receipt
+    pub missing: usize,
+    /// Rows not checked because their receipt could not be fetched
+    pub errors: usize,
+}
+
+impl VerifyReport {
+    fn merge(&mut self, other: VerifyReport) {
+        self.checked += other.checked;
+        self.drifted += other.drifted;
+        self.missing += other.missing;
+        self.errors += other.errors;
+    }
+}
+
+/// Re-fetch the logs of sampled stored rows and compare them field-by-field
+///
+/// Samples `sample_size` transfers, Fusion+ swaps (source leg) and Fusion swaps
+/// per chain and reports every field that no longer matches the RPC data.
+/// Rows whose receipt can't be fetched are counted as errors, not as missing.
+pub async fn run(db: &Database, networks: &[NetworkConfig], sample_size: u32) -> VerifyReport {
+    let mut report = VerifyReport::default();
+
+    for network in networks {
+        let verifier = ChainVerifier {
+            network,
+            rpc: RpcClient::for_network(network),
+            receipts: HashMap::new(),
+        };
+        let chain_report = verifier.verify(db, sample_size).await;
+
+        info!(
+            "[{}] Verified {} rows: {} drifted, {} missing, {} not fetched",
+            network.name, chain_report.checked, chain_report.drifted, chain_report.missing, chain_report.errors
+        );
+        report.merge(chain_report);
+    }
+
+    report
+}
+
+struct ChainVerifier<'a> {
+    network: &'a NetworkConfig,
+    rpc: RpcClient,
+    receipts: HashMap<String, Vec<Log>>,
+}
+
+impl ChainVerifier<'_> {
+    async fn verify(mut self, db: &Database, sample_size: u32) -> VerifyReport {
+        let mut report = VerifyReport::default();
+        let chain_id = self.network.chain_id;
+
+        match db.sample_transfers(chain_id, sample_size).await {
+            Ok(transfers) => {
+                for transfer in &transfers {
+                    let drift = self
+                        .receipt_logs(&transfer.tx_hash)
+                        .await
+                        .map(|logs| find_log(logs, transfer.log_index).map(|l| compare_transfer(transfer, l)));
+                    self.record(&mut report, "transfer", &transfer.tx_hash, drift);
+                }
+            }
+            Err(e) => warn!("[{}] Failed to sample transfers: {}", self.network.name, e),
+        }
+
+        match db.sample_fusion_plus_swaps(chain_id, sample_size).await {
+            Ok(swaps) => {
+                for swap in &swaps {
+                    let drift = self
+                        .receipt_logs(&swap.src_tx_hash)
+                        .await
+                        .map(|logs| find_log(logs, swap.src_log_index).map(|l| compare_fusion_plus_src(swap, l)));
+                    self.record(&mut report, "fusion_plus", &swap.src_tx_hash, drift);
+                }
+            }
+            Err(e) => warn!("[{}] Failed to sample Fusion+ swaps: {}", self.network.name, e),
+        }
+
+        match db.sample_fusion_swaps(chain_id, sample_size).await {
+            Ok(swaps) => {
+                for swap in &swaps {
+                    let drift = self
+                        .receipt_logs(&swap.tx_hash)
+                        .await
+                        .map(|logs| find_log(logs, swap.log_index).map(|l| compare_fusion_swap(swap, l, logs)));
+                    self.record(&mut report, "fusion", &swap.tx_hash, drift);
+                }
+            }
+            Err(e) => warn!("[{}] Failed to sample Fusion swaps: {}", self.network.name, e),
+        }
+
+        report
+    }
+
+    /// Fetch the logs of `tx_hash`'s receipt, caching receipts per transaction
+    async fn receipt_logs(&mut self, tx_hash: &str) -> Result<&[Log], RpcError> {
+        let tx_hash = tx_hash.to_lowercase();
+
+        if !self.receipts.contains_key(&tx_hash) {
+            let receipt = self.rpc.get_transaction_receipt(&tx_hash).await?;
+            self.receipts.insert(tx_hash.clone(), receipt.logs);
+        }
+
+        Ok(&self.receipts[&tx_hash])
+    }
+
+    fn record(&self, report: &mut VerifyReport, kind: &str, tx_hash: &str, drift: Result<Option<Vec<String>>, RpcError>) {
+        let drift = match drift {
+            Ok(drift) => drift,
+            Err(e) => {
+                // The row may be fine; a flaky endpoint says nothing about it
+                report.errors += 1;
+                warn!("[{}] {} {}: failed to get receipt: {}", self.network.name, kind, tx_hash, e);
+                return;
+            }
+        };
+        report.checked += 1;
+        match drift {
+            None => {
+                report.missing += 1;
+                warn!("[{}] {} {}: log not found on chain", self.network.name, kind, tx_hash);
+            }
+            Some(fields) if !fields.is_empty() => {
+                report.drifted += 1;
+                warn!(
+                    "[{}] {} {}: drift in {}",
+                    self.network.name, kind, tx_hash, fields.join(", ")
+                );
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// The log at `log_index` among a receipt's logs
+fn find_log(logs: &[Log], log_index: u32) -> Option<&Log> {
+    logs.iter().find(|l| l.log_index_u32() == log_index)
+}
+
+/// Compare a stored transfer with its on-chain log, returning drifted field names
+fn compare_transfer(stored: &Transfer, log: &Log) -> Vec<String> {
+    let mut drift = Vec::new();
+
+    // WETH deposits/withdrawals are stored as transfers from/to the zero address
+    let Some((from_addr, to_addr)) = transfer_parties(log) else {
+        drift.push("topics".to_string());
+        return drift;
+    };
+    if stored.token.to_lowercase() != log.address.to_lowercase() {
+        drift.push("token".to_string());
+    }
+    if stored.from_addr.to_lowercase() != from_addr {
+        drift.push("from_addr".to_string());
+    }
+    if stored.to_addr.to_lowercase() != to_addr {
+        drift.push("to_addr".to_string());
+    }
+    // Compact-mode rows carry no value
+    if !stored.value.is_empty() && stored.value.to_lowercase() != log.data.to_lowercase() {
+        drift.push("value".to_string());
+    }
+    if stored.block_number != log.block_number_u64() {
+        drift.push("block_number".to_string());
+    }
+
+    drift
+}
+
+/// Compare the source leg of a stored Fusion+ swap with its SrcEscrowCreated log
+fn compare_fusion_plus_src(stored: &FusionPlusSwap, log: &Log) -> Vec<String> {
+    let Some(data) = decode_src_escrow_created(&log.data) else {
+        return vec!["data".to_string()];
+    };
+
+    let mut drift = Vec::new();
+    let fields = [
+        ("order_hash", &stored.order_hash, &data.order_hash),
+        ("hashlock", &stored.hashlock, &data.hashlock),
+        ("src_maker", &stored.src_maker, &data.src_maker),
+        ("src_taker", &stored.src_taker, &data.src_taker),
+        ("src_token", &stored.src_token, &data.src_token),
+        ("src_amount", &stored.src_amount, &data.src_amount),
+        ("dst_token", &stored.dst_token, &data.dst_token),
+        ("dst_amount", &stored.dst_amount, &data.dst_amount),
+    ];
+    for (name, stored_value, chain_value) in fields {
+        if stored_value.to_lowercase() != chain_value.to_lowercase() {
+            drift.push(name.to_string());
+        }
+    }
+    if stored.dst_chain_id != data.dst_chain_id {
+        drift.push("dst_chain_id".to_string());
+    }
+    if stored.src_block_number != log.block_number_u64() {
+        drift.push("src_block_number".to_string());
+    }
+
+    drift
+}
+
+/// Compare a stored Fusion swap with its OrderFilled log, and its maker and
+/// taker details with the Transfers of the same receipt
+fn compare_fusion_swap(stored: &FusionSwap, log: &Log, receipt_logs: &[Log]) -> Vec<String> {
+    let Some(data) = decode_order_filled(&log.topics, &log.data) else {
+        return vec!["data".to_string()];
+    };
+
+    let mut drift = Vec::new();
+    if stored.order_hash.to_lowercase() != data.order_hash {
+        drift.push("order_hash".to_string());
+    }
+    if stored.remaining.to_lowercase() != data.remaining {
+        drift.push("remaining".to_string());
+    }
+    // Details are stored from the same Transfers; swaps still waiting for
+    // enrichment have none to compare
+    let details = SwapDetails::from_logs(receipt_logs);
+    let fields = [
+        ("maker", (!stored.maker.is_empty()).then_some(&stored.maker), details.as_ref().map(|d| &d.maker)),
+        ("taker", stored.taker.as_ref(), details.as_ref().map(|d| &d.taker)),
+        ("maker_token", stored.maker_token.as_ref(), details.as_ref().map(|d| &d.maker_token)),
+        ("taker_token", stored.taker_token.as_ref(), details.as_ref().map(|d| &d.taker_token)),
+        ("maker_amount", stored.maker_amount.as_ref(), details.as_ref().map(|d| &d.maker_amount)),
+        ("taker_amount", stored.taker_amount.as_ref(), details.as_ref().map(|d| &d.taker_amount)),
+    ];
+    for (name, stored_value, chain_value) in fields {
+        // Amounts are empty when a compact-mode value could not be re-fetched
+        let Some(stored_value) = stored_value.filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        if chain_value.is_none_or(|v| stored_value.to_lowercase() != v.to_lowercase()) {
+            drift.push(name.to_string());
+        }
+    }
+    if stored.block_number != log.block_number_u64() {
+        drift.push("block_number".to_string());
+    }
+
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transfer_log() -> Log {
+        Log {
+            address: "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913".to_string(),
+            topics: vec![
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+                "0x00000000000000000000000000000000000000000000000000000000000000aa".to_string(),
+                "0x00000000000000000000000000000000000000000000000000000000000000bb".to_string(),
+            ],
+            data: "0x00000000000000000000000000000000000000000000000000000000000003e8".to_string(),
+            block_number: "0x10".to_string(),
+            transaction_hash: "0x01".to_string(),
+            log_index: "0x2".to_string(),
+        }
+    }
+
+    fn stored_transfer() -> Transfer {
+        Transfer {
+            chain_id: 8453,
+            tx_hash: "0x01".to_string(),
+            log_index: 2,
+            token: "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913".to_string(),
+            from_addr: "0x00000000000000000000000000000000000000aa".to_string(),
+            to_addr: "0x00000000000000000000000000000000000000bb".to_string(),
+            value: "0x00000000000000000000000000000000000000000000000000000000000003e8".to_string(),
+            value_decimal: Some("1000".to_string()),
+            block_number: 16,
+            block_timestamp: 0,
+            swap_type: None,
+            tx_status: None,
+            token_info: None,
+            id: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_transfer_matches() {
+        assert!(compare_transfer(&stored_transfer(), &transfer_log()).is_empty());
+
+        // Compact-mode rows skip the value comparison
+        let mut compact = stored_transfer();
+        compact.value = String::new();
+        assert!(compare_transfer(&compact, &transfer_log()).is_empty());
+    }
+
+    #[test]
+    fn test_compare_transfer_drift() {
+        let mut stored = stored_transfer();
+        stored.to_addr = "0x00000000000000000000000000000000000000cc".to_string();
+        stored.block_number = 17;
+
+        assert_eq!(compare_transfer(&stored, &transfer_log()), vec!["to_addr", "block_number"]);
+    }
+
+    /// A Fusion fill: the maker's transfer, the OrderFilled, the taker's transfer
+    fn fill_receipt() -> Vec<Log> {
+        let order_filled = Log {
+            address: "0x111111125421ca6dc452d289314280a0f8842a65".to_string(),
+            topics: vec!["0xfec331350fce78ba658e082a71da20ac9f8d798a99b3c79681c8440cbfe77e07".to_string()],
+            data: "0x169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa90000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            log_index: "0x3".to_string(),
+            ..transfer_log()
+        };
+        let mut taker_transfer = transfer_log();
+        taker_transfer.address = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".to_string();
+        taker_transfer.topics[2] = "0x00000000000000000000000000000000000000000000000000000000000000dd".to_string();
+        taker_transfer.data = "0x00000000000000000000000000000000000000000000000000000000000001f4".to_string();
+        taker_transfer.log_index = "0x4".to_string();
+        vec![transfer_log(), order_filled, taker_transfer]
+    }
+
+    fn stored_fusion_swap() -> FusionSwap {
+        FusionSwap {
+            order_hash: "0x169c0db441eaf375fc6dd71f7f81d684ddbe8c751c68dd87dddf5032aaafafa9".to_string(),
+            chain_id: 8453,
+            tx_hash: "0x01".to_string(),
+            block_number: 16,
+            block_timestamp: 0,
+            log_index: 3,
+            maker: "0x00000000000000000000000000000000000000aa".to_string(),
+            taker: Some("0x00000000000000000000000000000000000000dd".to_string()),
+            maker_token: Some("0x833589fcd6edb6e08f4c7c32d4f71b54bda02913".to_string()),
+            taker_token: Some("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".to_string()),
+            maker_amount: Some("0x00000000000000000000000000000000000000000000000000000000000003e8".to_string()),
+            taker_amount: Some("0x00000000000000000000000000000000000000000000000000000000000001f4".to_string()),
+            remaining: "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            is_partial_fill: false,
+            status: "filled".to_string(),
+            id: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_fusion_swap() {
+        let receipt = fill_receipt();
+        assert!(compare_fusion_swap(&stored_fusion_swap(), &receipt[1], &receipt).is_empty());
+
+        let mut stored = stored_fusion_swap();
+        stored.maker_token = Some("0xdac17f958d2ee523a2206206994597c13d831ec7".to_string());
+        stored.taker_amount = Some(format!("0x{:064x}", 499));
+        assert_eq!(compare_fusion_swap(&stored, &receipt[1], &receipt), vec!["maker_token", "taker_amount"]);
+
+        // Details the receipt can't back up drift; ones never stored are skipped
+        assert_eq!(compare_fusion_swap(&stored_fusion_swap(), &receipt[1], &receipt[1..2]).len(), 6);
+        let unenriched = FusionSwap {
+            maker: String::new(),
+            taker: None,
+            maker_token: None,
+            taker_token: None,
+            maker_amount: None,
+            taker_amount: None,
+            ..stored_fusion_swap()
+        };
+        assert!(compare_fusion_swap(&unenriched, &receipt[1], &receipt[1..2]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_receipt_failures_are_errors() {
+        // Only 0x01 has a receipt; 0x02 was dropped and 0x03 fails to fetch
+        let url = crate::rpc::mock_rpc(|request| async move {
+            let result = match request["params"][0].as_str() {
+                Some("0x01") => json!({ "transactionHash": "0x01", "logs": fill_receipt() }),
+                Some("0x02") => json!({ "transactionHash": "0x02", "logs": [] }),
+                _ => return json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32603, "message": "internal error" } }),
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        })
+        .await;
+        let contents = format!("[[networks]]\nchain_id = 8453\nrpc_url = \"{}\"", url);
+        let network = crate::config::networks_from_toml(&contents, None, &|_| None).unwrap().remove(0);
+        let mut verifier = ChainVerifier { network: &network, rpc: RpcClient::for_network(&network), receipts: HashMap::new() };
+
+        let mut report = VerifyReport::default();
+        for tx_hash in ["0x01", "0x02", "0x03"] {
+            let drift = verifier
+                .receipt_logs(tx_hash)
+                .await
+                .map(|logs| find_log(logs, 2).map(|l| compare_transfer(&stored_transfer(), l)));
+            verifier.record(&mut report, "transfer", tx_hash, drift);
+        }
+        assert_eq!((report.checked, report.drifted, report.missing, report.errors), (2, 0, 1, 1));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::db::{Database, DbError};
+use crate::types::WatchedAddress;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Shared set of watched addresses, persisted in the `watchlist` table
+///
+/// Pollers check membership in memory; add/remove write through to the
+/// database so the list survives restarts and `refresh` picks up changes
+/// made by other processes.
+pub struct Watchlist {
+    db: Arc<Database>,
+    addresses: RwLock<HashSet<String>>,
+}
+
+impl Watchlist {
+    /// Load the watchlist from the database
+    pub async fn load(db: Arc<Database>) -> Result<Self, DbError> {
+        let watchlist = Self {
+            db,
+            addresses: RwLock::new(HashSet::new()),
+        };
+        watchlist.refresh().await?;
+        Ok(watchlist)
+    }
+
+    /// Reload the in-memory set from the database
+    pub async fn refresh(&self) -> Result<usize, DbError> {
+        let addresses: HashSet<String> = self
+            .db
+            .get_watched_addresses()
+            .await?
+            .into_iter()
+            .map(|w| w.address)
+            .collect();
+        let count = addresses.len();
+        *self.addresses.write().unwrap() = addresses;
+        Ok(count)
+    }
+
+    /// Watch an address, returning false if it was already watched
+    pub async fn add(&self, address: &str, label: Option<&str>) -> Result<bool, DbError> {
+        let added = self.db.add_watched_address(address, label).await?;
+        self.addresses.write().unwrap().insert(address.to_lowercase());
+        Ok(added)
+    }
+
+    /// Stop watching an address, returning false if it was not watched
+    pub async fn remove(&self, address: &str) -> Result<bool, DbError> {
+        let removed = self.db.remove_watched_address(address).await?;
+        self.addresses.write().unwrap().remove(&address.to_lowercase());
+        Ok(removed)
+    }
+
+    /// Watched addresses with their labels
+    pub async fn list(&self) -> Result<Vec<WatchedAddress>, DbError> {
+        self.db.get_watched_addresses().await
+    }
+
+    /// Whether a transfer between `from` and `to` (lowercase) touches a watched address
+    pub fn matches(&self, from: &str, to: &str) -> bool {
+        let addresses = self.addresses.read().unwrap();
+        addresses.contains(from) || addresses.contains(to)
+    }
+
+    /// Watched addresses (lowercase), sorted
+    pub fn addresses(&self) -> Vec<String> {
+        let mut addresses: Vec<String> = self.addresses.read().unwrap().iter().cloned().collect();
+        addresses.sort();
+        addresses
+    }
+
+    pub fn len(&self) -> usize {
+        self.addresses.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.read().unwrap().is_empty()
+    }
+}
@@ -0,0 +1,321 @@
+use crate::db::{ChainWrites, Database, DbError};
+use crate::dry_run::DryRun;
+use crate::events::EventHandler;
+use crate::types::{Approval, Delegation, InternalTransfer, Log, RawEvent, TransactionInfo, Transfer};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{sleep, sleep_until, Instant};
+use tracing::{debug, error};
+
+/// Writes queued between a chain poller and its writer task
+const WRITE_CHANNEL_CAPACITY: usize = 64;
+
+/// A write queued by the poller
+enum WriteOp {
+    Transfers(Vec<Transfer>),
+    Approvals(Vec<Approval>),
+    RawEvents(Vec<RawEvent>),
+    Delegations(Vec<Delegation>),
+    Transactions(Vec<TransactionInfo>),
+    InternalTransfers(Vec<InternalTransfer>),
+    RawLogs(Vec<(Log, u64)>),
+    ProcessedRange(u64, u64),
+    Checkpoint(u64),
+    BackfillProgress { from_block: u64, to_block: u64, next_block: u64 },
+    Flush(oneshot::Sender<Result<(), String>>),
+}
+
+/// Handle to a chain's writer task
+///
+/// The task buffers decoded rows and inserts them every `batch_rows` rows or
+/// `flush_ms` milliseconds, so slow database writes don't stall RPC polling.
+/// Checkpoints and backfill progress are committed in the same transaction
+/// as every row queued before them. When the database falls behind the
+/// channel fills up and the poller waits on send.
+#[derive(Clone)]
+pub struct ChainWriter {
+    tx: mpsc::Sender<WriteOp>,
+}
+
+impl ChainWriter {
+    /// Spawn the writer task for `chain_id`; it exits once every handle is dropped
+    pub fn spawn(
+        chain_id: u32,
+        chain_name: String,
+        db: Arc<Database>,
+        handlers: Vec<Arc<dyn EventHandler>>,
+        batch_rows: usize,
+        flush_ms: u64,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(WRITE_CHANNEL_CAPACITY);
+        let task = WriterTask {
+            chain_id,
+            chain_name,
+            db,
+            handlers,
+            batch_rows: batch_rows.max(1),
+            flush_interval: Duration::from_millis(flush_ms),
+            pending: Pending::default(),
+        };
+        tokio::spawn(task.run(rx));
+        Self { tx }
+    }
+
+    /// Handle whose rows are recorded to `dry_run` instead of written;
+    /// progress markers are dropped
+    pub fn dry_run(dry_run: Arc<DryRun>) -> Self {
+        let (tx, mut rx) = mpsc::channel(WRITE_CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            while let Some(op) = rx.recv().await {
+                match op {
+                    WriteOp::Transfers(rows) => rows.iter().for_each(|row| dry_run.record("transfers", row)),
+                    WriteOp::Approvals(rows) => rows.iter().for_each(|row| dry_run.record("approvals", row)),
+                    WriteOp::RawEvents(rows) => rows.iter().for_each(|row| dry_run.record("raw_events", row)),
+                    WriteOp::Delegations(rows) => rows.iter().for_each(|row| dry_run.record("delegations", row)),
+                    WriteOp::Transactions(rows) => rows.iter().for_each(|row| dry_run.record("transactions", row)),
+                    WriteOp::InternalTransfers(rows) => {
+                        rows.iter().for_each(|row| dry_run.record("internal_transfers", row))
+                    }
+                    WriteOp::RawLogs(rows) => rows.iter().for_each(|(log, _)| dry_run.record("raw_logs", log)),
+                    WriteOp::ProcessedRange(..) | WriteOp::Checkpoint(_) | WriteOp::BackfillProgress { .. } => {}
+                    WriteOp::Flush(done) => {
+                        let _ = done.send(Ok(()));
+                    }
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    pub async fn transfers(&self, transfers: Vec<Transfer>) -> Result<(), String> {
+        self.send(WriteOp::Transfers(transfers)).await
+    }
+
+    pub async fn approvals(&self, approvals: Vec<Approval>) -> Result<(), String> {
+        self.send(WriteOp::Approvals(approvals)).await
+    }
+
+    pub async fn raw_events(&self, raw_events: Vec<RawEvent>) -> Result<(), String> {
+        self.send(WriteOp::RawEvents(raw_events)).await
+    }
+
+    pub async fn delegations(&self, delegations: Vec<Delegation>) -> Result<(), String> {
+        self.send(WriteOp::Delegations(delegations)).await
+    }
+
+    pub async fn transactions(&self, transactions: Vec<TransactionInfo>) -> Result<(), String> {
+        self.send(WriteOp::Transactions(transactions)).await
+    }
+
+    pub async fn internal_transfers(&self, transfers: Vec<InternalTransfer>) -> Result<(), String> {
+        self.send(WriteOp::InternalTransfers(transfers)).await
+    }
+
+    /// Logs with their block timestamps, for `raw_logs`
+    pub async fn raw_logs(&self, logs: Vec<(Log, u64)>) -> Result<(), String> {
+        self.send(WriteOp::RawLogs(logs)).await
+    }
+
+    /// Mark `from_block..=to_block` as indexed once the rows queued before it are stored
+    pub async fn processed_range(&self, from_block: u64, to_block: u64) -> Result<(), String> {
+        self.send(WriteOp::ProcessedRange(from_block, to_block)).await
+    }
+
+    pub async fn checkpoint(&self, block_number: u64) -> Result<(), String> {
+        self.send(WriteOp::Checkpoint(block_number)).await
+    }
+
+    pub async fn backfill_progress(&self, from_block: u64, to_block: u64, next_block: u64) -> Result<(), String> {
+        self.send(WriteOp::BackfillProgress { from_block, to_block, next_block }).await
+    }
+
+    /// Write everything queued so far, returning once it is stored
+    pub async fn flush(&self) -> Result<(), String> {
+        let (done, result) = oneshot::channel();
+        self.send(WriteOp::Flush(done)).await?;
+        result.await.map_err(|_| "Writer task stopped".to_string())?
+    }
+
+    async fn send(&self, op: WriteOp) -> Result<(), String> {
+        self.tx.send(op).await.map_err(|_| "Writer task stopped".to_string())
+    }
+}
+
+/// Rows and progress markers not yet written
+#[derive(Default)]
+struct Pending {
+    transfers: Vec<Transfer>,
+    approvals: Vec<Approval>,
+    raw_events: Vec<RawEvent>,
+    delegations: Vec<Delegation>,
+    transactions: Vec<TransactionInfo>,
+    internal_transfers: Vec<InternalTransfer>,
+    raw_logs: Vec<(Log, u64)>,
+    processed_ranges: Vec<(u64, u64)>,
+    checkpoint: Option<u64>,
+    backfill_progress: Option<(u64, u64, u64)>,
+}
+
+impl Pending {
+    fn push(&mut self, op: WriteOp) {
+        match op {
+            WriteOp::Transfers(rows) => self.transfers.extend(rows),
+            WriteOp::Approvals(rows) => self.approvals.extend(rows),
+            WriteOp::RawEvents(rows) => self.raw_events.extend(rows),
+            WriteOp::Delegations(rows) => self.delegations.extend(rows),
+            WriteOp::Transactions(rows) => self.transactions.extend(rows),
+            WriteOp::InternalTransfers(rows) => self.internal_transfers.extend(rows),
+            WriteOp::RawLogs(rows) => self.raw_logs.extend(rows),
+            WriteOp::ProcessedRange(from_block, to_block) => self.processed_ranges.push((from_block, to_block)),
+            WriteOp::Checkpoint(block_number) => self.checkpoint = Some(block_number),
+            WriteOp::BackfillProgress { from_block, to_block, next_block } => {
+                self.backfill_progress = Some((from_block, to_block, next_block));
+            }
+            WriteOp::Flush(_) => unreachable!("flush requests are not buffered"),
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.transfers.len()
+            + self.approvals.len()
+            + self.raw_events.len()
+            + self.delegations.len()
+            + self.transactions.len()
+            + self.internal_transfers.len()
+            + self.raw_logs.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rows() == 0
+            && self.processed_ranges.is_empty()
+            && self.checkpoint.is_none()
+            && self.backfill_progress.is_none()
+    }
+}
+
+struct WriterTask {
+    chain_id: u32,
+    chain_name: String,
+    db: Arc<Database>,
+    /// Told about each transfer once it is stored
+    handlers: Vec<Arc<dyn EventHandler>>,
+    batch_rows: usize,
+    flush_interval: Duration,
+    pending: Pending,
+}
+
+impl WriterTask {
+    async fn run(mut self, mut rx: mpsc::Receiver<WriteOp>) {
+        // Set while rows are buffered: when they are written regardless of count
+        let mut deadline: Option<Instant> = None;
+
+        loop {
+            let op = match deadline {
+                Some(at) => tokio::select! {
+                    op = rx.recv() => op,
+                    () = sleep_until(at) => {
+                        self.write_until_done().await;
+                        deadline = None;
+                        continue;
+                    }
+                },
+                None => rx.recv().await,
+            };
+
+            let Some(op) = op else {
+                // Poller dropped: store what is left and stop
+                if let Err(e) = self.write().await {
+                    error!("[{}] Writer stopped with unwritten rows: {}", self.chain_name, e);
+                }
+                return;
+            };
+
+            if let WriteOp::Flush(done) = op {
+                let result = self.write().await.map_err(|e| format!("DB error: {}", e));
+                deadline = (!self.pending.is_empty()).then(|| Instant::now() + self.flush_interval);
+                let _ = done.send(result);
+                continue;
+            }
+
+            self.pending.push(op);
+            if self.pending.rows() >= self.batch_rows {
+                // Not receiving while this retries is what pushes back on the poller
+                self.write_until_done().await;
+                deadline = None;
+            } else if deadline.is_none() {
+                deadline = Some(Instant::now() + self.flush_interval);
+            }
+        }
+    }
+
+    /// Write the buffer, retrying every flush interval until it succeeds
+    async fn write_until_done(&mut self) {
+        while let Err(e) = self.write().await {
+            error!("[{}] Write failed, retrying: {}", self.chain_name, e);
+            sleep(self.flush_interval).await;
+        }
+    }
+
+    /// Write buffered rows and the progress markers queued after them in one transaction
+    ///
+    /// After a failure nothing was stored, and the whole buffer is retried.
+    async fn write(&mut self) -> Result<(), DbError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let rows = self.pending.rows();
+        let pending = &self.pending;
+
+        let writes = ChainWrites {
+            raw_logs: &pending.raw_logs,
+            transfers: &pending.transfers,
+            approvals: &pending.approvals,
+            raw_events: &pending.raw_events,
+            delegations: &pending.delegations,
+            transactions: &pending.transactions,
+            internal_transfers: &pending.internal_transfers,
+            processed_ranges: &pending.processed_ranges,
+            checkpoint: pending.checkpoint,
+            backfill_progress: pending.backfill_progress,
+        };
+        self.db.commit_writes(self.chain_id, &writes).await?;
+
+        for transfer in std::mem::take(&mut self.pending).transfers {
+            for handler in &self.handlers {
+                handler.on_transfer(&transfer);
+            }
+        }
+
+        if rows > 0 {
+            debug!("[{}] Wrote {} buffered rows", self.chain_name, rows);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_keeps_latest_progress() {
+        let mut pending = Pending::default();
+        assert!(pending.is_empty());
+
+        pending.push(WriteOp::Checkpoint(100));
+        assert_eq!(pending.rows(), 0);
+        assert!(!pending.is_empty());
+
+        pending.push(WriteOp::RawEvents(Vec::new()));
+        pending.push(WriteOp::Checkpoint(150));
+        pending.push(WriteOp::BackfillProgress { from_block: 1, to_block: 500, next_block: 101 });
+        pending.push(WriteOp::BackfillProgress { from_block: 1, to_block: 500, next_block: 201 });
+        assert_eq!(pending.checkpoint, Some(150));
+        assert_eq!(pending.backfill_progress, Some((1, 500, 201)));
+
+        pending.push(WriteOp::ProcessedRange(101, 200));
+        assert_eq!(pending.rows(), 0);
+        assert_eq!(pending.processed_ranges, vec![(101, 200)]);
+    }
+}